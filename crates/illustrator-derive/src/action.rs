@@ -0,0 +1,187 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Data, DeriveInput, Error, Expr, Fields, Ident, LitByteStr, LitStr, Path, Type};
+
+/// 構造体の `#[action_parameters(..)]`
+struct Container {
+    krate: Path,
+}
+
+impl Container {
+    fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let mut krate = None;
+
+        for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("action_parameters")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("crate") {
+                    krate = Some(meta.value()?.parse::<LitStr>()?.parse::<Path>()?);
+                } else {
+                    return Err(meta.error("unknown action_parameters attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(Self {
+            krate: krate.unwrap_or_else(|| parse_quote!(::illustrator_rs)),
+        })
+    }
+}
+
+/// フィールドの `#[action_param(..)]`
+struct Field {
+    ident: Ident,
+    ty: Type,
+    key: Option<LitStr>,
+    name: LitStr,
+    default: Option<Expr>,
+    hidden: bool,
+    skip: bool,
+}
+
+impl Field {
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let ident = field.ident.clone().expect("named field");
+        let mut key = None;
+        let mut name = None;
+        let mut default = None;
+        let mut hidden = false;
+        let mut skip = false;
+
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("action_param")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("key") {
+                    key = Some(meta.value()?.parse::<LitStr>()?);
+                } else if meta.path.is_ident("name") {
+                    name = Some(meta.value()?.parse::<LitStr>()?);
+                } else if meta.path.is_ident("default") {
+                    default = Some(meta.value()?.parse::<Expr>()?);
+                } else if meta.path.is_ident("hidden") {
+                    hidden = true;
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                } else {
+                    return Err(meta.error("unknown action_param attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        if let Some(key) = &key {
+            let value = key.value();
+            if value.len() != 4 || !value.is_ascii() {
+                return Err(Error::new_spanned(key, "action parameter key must be 4 ASCII characters"));
+            }
+        } else if !skip {
+            return Err(Error::new_spanned(&ident, "action parameter requires #[action_param(key = \"....\")]"));
+        }
+
+        let name = name.unwrap_or_else(|| {
+            let name = ident.to_string();
+            LitStr::new(name.strip_prefix("r#").unwrap_or(&name), ident.span())
+        });
+
+        Ok(Self {
+            ident,
+            ty: field.ty.clone(),
+            key,
+            name,
+            default,
+            hidden,
+            skip,
+        })
+    }
+
+    /// キーの 4 文字を big endian で並べた `ActionParamKeyID`
+    fn key_id(&self) -> TokenStream {
+        let key = self.key.as_ref().expect("key of a stored field");
+        let bytes = LitByteStr::new(key.value().as_bytes(), key.span());
+        quote!(::core::primitive::u32::from_be_bytes(*#bytes))
+    }
+}
+
+pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let container = Container::parse(&input)?;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().map(Field::parse).collect::<syn::Result<Vec<_>>>()?,
+            _ => return Err(Error::new_spanned(&input.ident, "#[derive(ActionParameters)] requires named fields")),
+        },
+        _ => return Err(Error::new_spanned(&input.ident, "#[derive(ActionParameters)] can only be used on structs")),
+    };
+
+    let mut keys = Vec::new();
+    for key in fields.iter().filter(|field| !field.skip).filter_map(|field| field.key.as_ref()) {
+        if keys.contains(&key.value()) {
+            return Err(Error::new_spanned(key, "duplicate action parameter key"));
+        }
+        keys.push(key.value());
+    }
+
+    let krate = &container.krate;
+    let action = quote!(#krate::action);
+
+    let schema = fields.iter().filter(|field| !field.skip).map(|field| {
+        let Field { ty, name, .. } = field;
+        let key = field.key_id();
+        let show = !field.hidden;
+        quote! {
+            #action::ActionParam {
+                key: #key,
+                name: #name,
+                kind: <#ty as #action::ActionField>::KIND,
+                show: #show,
+            }
+        }
+    });
+
+    let reads = fields.iter().map(|field| {
+        let Field { ident, ty, .. } = field;
+        let default = match &field.default {
+            Some(expr) => quote!(#expr),
+            None => quote!(::core::default::Default::default()),
+        };
+
+        if field.skip {
+            quote!(#ident: #default)
+        } else {
+            let key = field.key_id();
+            quote! {
+                #ident: match values.get::<#ty>(#key)? {
+                    ::core::option::Option::Some(value) => value,
+                    ::core::option::Option::None => #default,
+                }
+            }
+        }
+    });
+
+    let writes = fields.iter().filter(|field| !field.skip).map(|field| {
+        let ident = &field.ident;
+        let key = field.key_id();
+        quote!(values.set(#key, &self.#ident)?;)
+    });
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #action::ActionParameters for #name #ty_generics #where_clause {
+            fn schema() -> ::std::vec::Vec<#action::ActionParam> {
+                ::std::vec![#(#schema),*]
+            }
+
+            #[allow(unused_variables)]
+            fn read(values: &#action::ActionValues) -> #krate::AIResult<Self> {
+                ::core::result::Result::Ok(Self {
+                    #(#reads,)*
+                })
+            }
+
+            #[allow(unused_variables)]
+            fn write(&self, values: &mut #action::ActionValues) -> #krate::AIResult<()> {
+                #(#writes)*
+                ::core::result::Result::Ok(())
+            }
+        }
+    })
+}
@@ -0,0 +1,85 @@
+//! `illustrator-rs` の derive マクロ
+//!
+//! 通常は `illustrator_rs` から再エクスポートされたものを使います。
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod action;
+mod preferences;
+
+/// 構造体のフィールドを `AIPreferenceSuite` の設定値に対応付ける
+///
+/// `illustrator_rs::preferences::Preferences` を実装する。
+///
+/// ```ignore
+/// use illustrator_rs::preferences::Preferences;
+///
+/// #[derive(Preferences)]
+/// #[preferences(prefix = "MyPlugin", version = 2)]
+/// struct Settings {
+///     #[preference(default = 8)]
+///     grid_size: i32,
+///     #[preference(key = "snap", rename_from = "snapToGrid")]
+///     snap_to_grid: bool,
+///     label: String,
+///     #[preference(skip)]
+///     dirty: bool,
+/// }
+/// ```
+///
+/// 構造体の属性 `#[preferences(..)]`
+/// * `prefix = "..."` - 設定のプレフィックス（必須）
+/// * `version = N` - 保存形式の版（既定は 1）
+/// * `migrate = path` - 古い版から読み込むときに呼ぶ `fn(&mut PreferenceStore, i32) -> AIResult<()>`
+/// * `crate = "..."` - `illustrator_rs` を別名で参照している場合のパス
+///
+/// フィールドの属性 `#[preference(..)]`
+/// * `key = "..."` - 設定のキー（既定はフィールド名）
+/// * `default = expr` - 保存されていないときの値（既定は `Default::default()`）
+/// * `rename_from = "..."` - 古い版で使っていたキー（複数指定できる）
+/// * `skip` - 保存しない
+#[proc_macro_derive(Preferences, attributes(preferences, preference))]
+pub fn derive_preferences(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    preferences::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// 構造体のフィールドをアクションのパラメータに対応付ける
+///
+/// `illustrator_rs::action::ActionParameters` を実装する。フィールドの型は `ActionField` を実装していなければならない。
+///
+/// ```ignore
+/// use illustrator_rs::action::ActionParameters;
+///
+/// #[derive(ActionParameters, Default)]
+/// struct RoundCorners {
+///     #[action_param(key = "rads", name = "Radius")]
+///     radius: f64,
+///     #[action_param(key = "only", name = "Selected Only", default = true)]
+///     selected_only: bool,
+///     #[action_param(key = "vers", hidden)]
+///     version: i32,
+///     #[action_param(skip)]
+///     preview: bool,
+/// }
+/// ```
+///
+/// 構造体の属性 `#[action_parameters(..)]`
+/// * `crate = "..."` - `illustrator_rs` を別名で参照している場合のパス
+///
+/// フィールドの属性 `#[action_param(..)]`
+/// * `key = "...."` - パラメータのキー（ASCII 4 文字、必須）
+/// * `name = "..."` - アクションパネルに表示する名前（既定はフィールド名）
+/// * `default = expr` - 再生時に値がないときの値（既定は `Default::default()`）
+/// * `hidden` - アクションパネルに表示しない
+/// * `skip` - 記録しない
+#[proc_macro_derive(ActionParameters, attributes(action_parameters, action_param))]
+pub fn derive_action_parameters(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    action::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
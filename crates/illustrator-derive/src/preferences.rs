@@ -0,0 +1,198 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{parse_quote, Data, DeriveInput, Error, Expr, Fields, Ident, LitInt, LitStr, Path, Type};
+
+/// `Preferences::load` がバージョンの保存に使うキー
+const VERSION_KEY: &str = "_version";
+
+/// 構造体の `#[preferences(..)]`
+struct Container {
+    prefix: LitStr,
+    version: i32,
+    migrate: Option<Path>,
+    krate: Path,
+}
+
+impl Container {
+    fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let mut prefix = None;
+        let mut version = None;
+        let mut migrate = None;
+        let mut krate = None;
+
+        for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("preferences")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("prefix") {
+                    prefix = Some(meta.value()?.parse::<LitStr>()?);
+                } else if meta.path.is_ident("version") {
+                    let lit = meta.value()?.parse::<LitInt>()?;
+                    let value = lit.base10_parse::<i32>()?;
+                    if value < 1 {
+                        return Err(Error::new_spanned(lit, "version must be 1 or greater"));
+                    }
+                    version = Some(value);
+                } else if meta.path.is_ident("migrate") {
+                    migrate = Some(meta.value()?.parse::<Path>()?);
+                } else if meta.path.is_ident("crate") {
+                    krate = Some(meta.value()?.parse::<LitStr>()?.parse::<Path>()?);
+                } else {
+                    return Err(meta.error("unknown preferences attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        let prefix = prefix.ok_or_else(|| {
+            Error::new(Span::call_site(), "#[derive(Preferences)] requires #[preferences(prefix = \"...\")]")
+        })?;
+        if prefix.value().is_empty() {
+            return Err(Error::new_spanned(prefix, "prefix must not be empty"));
+        }
+
+        Ok(Self {
+            prefix,
+            version: version.unwrap_or(1),
+            migrate,
+            krate: krate.unwrap_or_else(|| parse_quote!(::illustrator_rs)),
+        })
+    }
+}
+
+/// フィールドの `#[preference(..)]`
+struct Field {
+    ident: Ident,
+    ty: Type,
+    key: LitStr,
+    default: Option<Expr>,
+    rename_from: Vec<LitStr>,
+    skip: bool,
+}
+
+impl Field {
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let ident = field.ident.clone().expect("named field");
+        let mut key = None;
+        let mut default = None;
+        let mut rename_from = Vec::new();
+        let mut skip = false;
+
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("preference")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("key") {
+                    key = Some(meta.value()?.parse::<LitStr>()?);
+                } else if meta.path.is_ident("default") {
+                    default = Some(meta.value()?.parse::<Expr>()?);
+                } else if meta.path.is_ident("rename_from") {
+                    rename_from.push(meta.value()?.parse::<LitStr>()?);
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                } else {
+                    return Err(meta.error("unknown preference attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        let key = key.unwrap_or_else(|| {
+            let name = ident.to_string();
+            LitStr::new(name.strip_prefix("r#").unwrap_or(&name), ident.span())
+        });
+        if key.value().is_empty() || key.value() == VERSION_KEY {
+            return Err(Error::new_spanned(&key, format!("`{}` cannot be used as a preference key", key.value())));
+        }
+
+        Ok(Self {
+            ident,
+            ty: field.ty.clone(),
+            key,
+            default,
+            rename_from,
+            skip,
+        })
+    }
+}
+
+pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let container = Container::parse(&input)?;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().map(Field::parse).collect::<syn::Result<Vec<_>>>()?,
+            _ => return Err(Error::new_spanned(&input.ident, "#[derive(Preferences)] requires named fields")),
+        },
+        _ => return Err(Error::new_spanned(&input.ident, "#[derive(Preferences)] can only be used on structs")),
+    };
+
+    let mut keys = Vec::new();
+    for field in fields.iter().filter(|field| !field.skip) {
+        if keys.contains(&field.key.value()) {
+            return Err(Error::new_spanned(&field.key, "duplicate preference key"));
+        }
+        keys.push(field.key.value());
+    }
+
+    let krate = &container.krate;
+    let preferences = quote!(#krate::preferences);
+    let prefix = &container.prefix;
+    let version = container.version;
+
+    let reads = fields.iter().map(|field| {
+        let Field { ident, ty, key, .. } = field;
+        let default = match &field.default {
+            Some(expr) => quote!(#expr),
+            None => quote!(::core::default::Default::default()),
+        };
+
+        if field.skip {
+            quote!(#ident: #default)
+        } else {
+            quote! {
+                #ident: match store.get::<#ty>(#key)? {
+                    ::core::option::Option::Some(value) => value,
+                    ::core::option::Option::None => #default,
+                }
+            }
+        }
+    });
+
+    let writes = fields.iter().filter(|field| !field.skip).map(|field| {
+        let Field { ident, key, .. } = field;
+        quote!(store.set(#key, &self.#ident)?;)
+    });
+
+    let renames = fields.iter().filter(|field| !field.skip).flat_map(|field| {
+        let Field { ty, key, .. } = field;
+        field
+            .rename_from
+            .iter()
+            .map(move |old| quote!(store.rename::<#ty>(#old, #key)?;))
+    });
+    let migrate = container.migrate.iter().map(|path| quote!(#path(store, from)?;));
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #preferences::Preferences for #name #ty_generics #where_clause {
+            const PREFIX: &'static str = #prefix;
+            const VERSION: i32 = #version;
+
+            fn read(store: &mut #preferences::PreferenceStore<'_>) -> #krate::AIResult<Self> {
+                ::core::result::Result::Ok(Self {
+                    #(#reads,)*
+                })
+            }
+
+            fn write(&self, store: &mut #preferences::PreferenceStore<'_>) -> #krate::AIResult<()> {
+                #(#writes)*
+                ::core::result::Result::Ok(())
+            }
+
+            #[allow(unused_variables)]
+            fn migrate(store: &mut #preferences::PreferenceStore<'_>, from: i32) -> #krate::AIResult<()> {
+                #(#renames)*
+                #(#migrate)*
+                ::core::result::Result::Ok(())
+            }
+        }
+    })
+}
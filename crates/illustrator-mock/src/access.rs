@@ -0,0 +1,28 @@
+use illustrator_sys::*;
+
+use crate::state::{with_state, HostEvent, NO_ERR};
+
+/// `SPAccessSuite` のスタンドイン（プラグインのロック数だけを追跡する）
+pub(crate) fn suite() -> SPAccessSuite {
+    let mut suite: SPAccessSuite = unsafe { std::mem::zeroed() };
+    suite.AcquirePlugin = Some(acquire_plugin);
+    suite.ReleasePlugin = Some(release_plugin);
+    suite
+}
+
+unsafe extern "C" fn acquire_plugin(plugin: SPPluginRef, access: *mut SPAccessRef) -> SPErr {
+    with_state(|state| {
+        state.access_count += 1;
+        state.events.push(HostEvent::AcquirePlugin);
+    });
+    *access = plugin as SPAccessRef;
+    NO_ERR
+}
+
+unsafe extern "C" fn release_plugin(_access: SPAccessRef) -> SPErr {
+    with_state(|state| {
+        state.access_count -= 1;
+        state.events.push(HostEvent::ReleasePlugin);
+    });
+    NO_ERR
+}
@@ -0,0 +1,595 @@
+use std::ffi::{c_char, CStr, CString};
+
+use illustrator_sys::*;
+
+use crate::art::BAD_PARAMETER;
+use crate::state::{lossy, with_state, HostEvent, NO_ERR};
+use crate::unicode;
+
+// AIActionManager.h の actionType*（#define のためバインディングに含まれない）
+const TYPE_INTEGER: ActionParamTypeID = u32::from_be_bytes(*b"long");
+const TYPE_REAL: ActionParamTypeID = u32::from_be_bytes(*b"doub");
+const TYPE_BOOLEAN: ActionParamTypeID = u32::from_be_bytes(*b"bool");
+const TYPE_STRING: ActionParamTypeID = u32::from_be_bytes(*b"TEXT");
+const TYPE_RAW_DATA: ActionParamTypeID = u32::from_be_bytes(*b"tdta");
+
+/// アクションのパラメータの値
+///
+/// C 文字列と Unicode 文字列はどちらも `String` として保存される。
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActionValue {
+    Integer(i32),
+    Real(f64),
+    Boolean(bool),
+    String(String),
+    Raw(Vec<u8>),
+}
+
+impl ActionValue {
+    fn type_id(&self) -> ActionParamTypeID {
+        match self {
+            ActionValue::Integer(_) => TYPE_INTEGER,
+            ActionValue::Real(_) => TYPE_REAL,
+            ActionValue::Boolean(_) => TYPE_BOOLEAN,
+            ActionValue::String(_) => TYPE_STRING,
+            ActionValue::Raw(_) => TYPE_RAW_DATA,
+        }
+    }
+}
+
+/// 値パラメータブロック（`AIActionParamValueRef` の中身）
+#[derive(Debug, Default)]
+pub(crate) struct ParamValue {
+    /// 追加順のキーと値
+    pub entries: Vec<(ActionParamKeyID, ActionValue)>,
+}
+
+impl ParamValue {
+    pub fn get(&self, key: ActionParamKeyID) -> Option<&ActionValue> {
+        self.entries.iter().find(|(k, _)| *k == key).map(|(_, value)| value)
+    }
+
+    pub fn set(&mut self, key: ActionParamKeyID, value: ActionValue) {
+        match self.entries.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, current)) => *current = value,
+            None => self.entries.push((key, value)),
+        }
+    }
+}
+
+/// 型パラメータブロックに登録されたパラメータ
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionParamInfo {
+    pub key: ActionParamKeyID,
+    pub name: String,
+    /// `'long'` などの `ActionParamTypeID`
+    pub type_id: ActionParamTypeID,
+    /// アクションパネルに表示するか
+    pub show: bool,
+}
+
+/// 型パラメータブロック（`AIActionParamTypeRef` の中身）
+#[derive(Debug, Default)]
+pub(crate) struct ParamType {
+    /// 追加順のパラメータ
+    pub entries: Vec<ActionParamInfo>,
+}
+
+impl ParamType {
+    fn get(&self, key: ActionParamKeyID) -> Option<&ActionParamInfo> {
+        self.entries.iter().find(|entry| entry.key == key)
+    }
+
+    fn set(&mut self, info: ActionParamInfo) {
+        match self.entries.iter_mut().find(|entry| entry.key == info.key) {
+            Some(current) => *current = info,
+            None => self.entries.push(info),
+        }
+    }
+}
+
+/// プラグインが登録したアクションイベント
+pub(crate) struct ActionEventEntry {
+    pub name: CString,
+    pub title: String,
+    pub version: i32,
+    pub flag: i32,
+    pub user_data: AIActionUserData,
+    /// 登録時の型パラメータブロックの写し（ブロック自体はプラグインが持つ）
+    pub parameters: Vec<ActionParamInfo>,
+}
+
+/// 記録モードで `RecordActionEvent` されたイベント
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedAction {
+    pub name: String,
+    /// `kDialogNone` などの `ActionDialogStatus`
+    pub dialog_status: ActionDialogStatus,
+    /// 記録時の値パラメータブロックの写し（追加順）
+    pub values: Vec<(ActionParamKeyID, ActionValue)>,
+}
+
+/// `AIActionManagerSuite` のスタンドイン
+///
+/// イベントの登録と記録、型・値パラメータブロックの作成と読み書きのみ。
+/// ブロックはホスト側で確保した `Box<ParamType>`・`Box<ParamValue>`。
+/// 再生は行わないので、記録したイベントは `MockHost::play_action` で送る。
+pub(crate) fn suite() -> AIActionManagerSuite {
+    let mut suite: AIActionManagerSuite = unsafe { std::mem::zeroed() };
+    suite.RegisterActionEvent = Some(register_action_event);
+    suite.RegisterActionEventUS = Some(register_action_event_us);
+    suite.IsActionEventRegistered = Some(is_action_event_registered);
+    suite.GetActionEventUserData = Some(get_action_event_user_data);
+    suite.InRecordMode = Some(in_record_mode);
+    suite.RecordActionEvent = Some(record_action_event);
+    suite.AINewActionParamType = Some(new_param_type);
+    suite.AIDeleteActionParamType = Some(delete_param_type);
+    suite.AIActionGetTypeKey = Some(get_type_key);
+    suite.AIActionHasTypeKey = Some(has_type_key);
+    suite.AIActionGetTypeCount = Some(get_type_count);
+    suite.AIActionSetTypeKey = Some(set_type_key);
+    suite.AIActionSetTypeKeyUS = Some(set_type_key_us);
+    suite.AIActionGetType = Some(get_type);
+    suite.AIActionTypeIsShow = Some(type_is_show);
+    suite.AINewActionParamValue = Some(new_param_value);
+    suite.AIDeleteActionParamValue = Some(delete_param_value);
+    suite.AIActionGetValueKey = Some(get_value_key);
+    suite.AIActionHasValueKey = Some(has_value_key);
+    suite.AIActionGetValueCount = Some(get_value_count);
+    suite.AIActionGetValueType = Some(get_value_type);
+    suite.AIActionGetInteger = Some(get_integer);
+    suite.AIActionSetInteger = Some(set_integer);
+    suite.AIActionGetReal = Some(get_real);
+    suite.AIActionSetReal = Some(set_real);
+    suite.AIActionGetBoolean = Some(get_boolean);
+    suite.AIActionSetBoolean = Some(set_boolean);
+    suite.AIActionSetString = Some(set_string);
+    suite.AIActionGetStringUS = Some(get_string_us);
+    suite.AIActionSetStringUS = Some(set_string_us);
+    suite.AIActionSetRawDataBytes = Some(set_raw_data_bytes);
+    suite.AIActionGetRawDataSize = Some(get_raw_data_size);
+    suite.AIActionGetRawData = Some(get_raw_data);
+    suite
+}
+
+pub(crate) fn new_param_value_ref() -> AIActionParamValueRef {
+    Box::into_raw(Box::<ParamValue>::default()) as AIActionParamValueRef
+}
+
+pub(crate) unsafe fn release_param_value(param: AIActionParamValueRef) {
+    if !param.is_null() {
+        drop(Box::from_raw(param as *mut ParamValue));
+    }
+}
+
+pub(crate) unsafe fn param_value<'a>(param: AIActionParamValueRef) -> Option<&'a mut ParamValue> {
+    (param as *mut ParamValue).as_mut()
+}
+
+pub(crate) fn new_param_type_ref() -> AIActionParamTypeRef {
+    Box::into_raw(Box::<ParamType>::default()) as AIActionParamTypeRef
+}
+
+unsafe fn param_type<'a>(param: AIActionParamTypeRef) -> Option<&'a mut ParamType> {
+    (param as *mut ParamType).as_mut()
+}
+
+/// 有効なブロックに対して `f` を呼ぶ（null は `kBadParameterErr`）
+unsafe fn with_param(param: AIActionParamValueRef, f: impl FnOnce(&mut ParamValue) -> ASErr) -> ASErr {
+    match param_value(param) {
+        Some(value) => f(value),
+        None => BAD_PARAMETER,
+    }
+}
+
+unsafe extern "C" fn new_param_value(param: *mut AIActionParamValueRef) -> ASErr {
+    if param.is_null() {
+        return BAD_PARAMETER;
+    }
+    *param = new_param_value_ref();
+    NO_ERR
+}
+
+unsafe extern "C" fn delete_param_value(param: AIActionParamValueRef) -> ASErr {
+    release_param_value(param);
+    NO_ERR
+}
+
+unsafe extern "C" fn get_value_key(param: AIActionParamValueRef, index: ai_uint32, key: *mut ActionParamKeyID) -> ASErr {
+    if key.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_param(param, |value| match value.entries.get(index as usize) {
+        Some((k, _)) => {
+            *key = *k;
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn has_value_key(param: AIActionParamValueRef, key: ActionParamKeyID, has_key: *mut AIBoolean) -> ASErr {
+    if has_key.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_param(param, |value| {
+        *has_key = value.get(key).is_some() as AIBoolean;
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn get_value_count(param: AIActionParamValueRef, count: *mut ai_uint32) -> ASErr {
+    if count.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_param(param, |value| {
+        *count = value.entries.len() as ai_uint32;
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn set_raw_data_bytes(
+    param: AIActionParamValueRef,
+    key: ActionParamKeyID,
+    size: ai_uint32,
+    data: *const c_char,
+) -> ASErr {
+    if data.is_null() && size != 0 {
+        return BAD_PARAMETER;
+    }
+    let bytes = match size {
+        0 => Vec::new(),
+        size => std::slice::from_raw_parts(data as *const u8, size as usize).to_vec(),
+    };
+    with_param(param, |value| {
+        value.set(key, ActionValue::Raw(bytes));
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn get_raw_data_size(param: AIActionParamValueRef, key: ActionParamKeyID, size: *mut ai_uint32) -> ASErr {
+    if size.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_param(param, |value| match value.get(key) {
+        Some(ActionValue::Raw(bytes)) => {
+            *size = bytes.len() as ai_uint32;
+            NO_ERR
+        }
+        _ => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn get_raw_data(param: AIActionParamValueRef, key: ActionParamKeyID, data: *mut c_char) -> ASErr {
+    with_param(param, |value| match value.get(key) {
+        Some(ActionValue::Raw(bytes)) if bytes.is_empty() => NO_ERR,
+        Some(ActionValue::Raw(bytes)) if !data.is_null() => {
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), data as *mut u8, bytes.len());
+            NO_ERR
+        }
+        _ => BAD_PARAMETER,
+    })
+}
+
+/// 有効な型ブロックに対して `f` を呼ぶ（null は `kBadParameterErr`）
+unsafe fn with_type(param: AIActionParamTypeRef, f: impl FnOnce(&mut ParamType) -> ASErr) -> ASErr {
+    match param_type(param) {
+        Some(value) => f(value),
+        None => BAD_PARAMETER,
+    }
+}
+
+/// 既に同じ名前のイベントがあれば `kBadParameterErr`
+unsafe fn register(
+    name: *const c_char,
+    title: String,
+    version: ai_int32,
+    flag: ai_int32,
+    user_data: AIActionUserData,
+    parameters: AIActionParamTypeRef,
+) -> ASErr {
+    if name.is_null() {
+        return BAD_PARAMETER;
+    }
+    let entry = ActionEventEntry {
+        name: CStr::from_ptr(name).to_owned(),
+        title,
+        version,
+        flag,
+        user_data,
+        parameters: param_type(parameters).map_or_else(Vec::new, |param| param.entries.clone()),
+    };
+
+    with_state(|state| {
+        if state.action_events.iter().any(|existing| existing.name == entry.name) {
+            return BAD_PARAMETER;
+        }
+        state.events.push(HostEvent::RegisterActionEvent(lossy(name)));
+        state.action_events.push(entry);
+        NO_ERR
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn register_action_event(
+    name: *const c_char,
+    localized_name: *const c_char,
+    version: ai_int32,
+    flag: ai_int32,
+    _callback: AIActionCallbackProc,
+    _plugin: SPPluginRef,
+    user_data: AIActionUserData,
+    parameters: AIActionParamTypeRef,
+) -> ASErr {
+    register(name, lossy(localized_name), version, flag, user_data, parameters)
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn register_action_event_us(
+    name: *const c_char,
+    localized_name: *const ai_UnicodeString,
+    version: ai_int32,
+    flag: ai_int32,
+    _callback: AIActionCallbackProc,
+    _plugin: SPPluginRef,
+    user_data: AIActionUserData,
+    parameters: AIActionParamTypeRef,
+) -> ASErr {
+    register(name, unicode::read(localized_name), version, flag, user_data, parameters)
+}
+
+unsafe extern "C" fn is_action_event_registered(name: *const c_char) -> AIBoolean {
+    if name.is_null() {
+        return 0;
+    }
+    let name = CStr::from_ptr(name);
+    with_state(|state| state.action_events.iter().any(|entry| entry.name.as_c_str() == name) as AIBoolean)
+}
+
+unsafe extern "C" fn get_action_event_user_data(name: *const c_char, user_data: *mut AIActionUserData) -> ASErr {
+    if name.is_null() || user_data.is_null() {
+        return BAD_PARAMETER;
+    }
+    let name = CStr::from_ptr(name);
+    with_state(|state| match state.action_events.iter().find(|entry| entry.name.as_c_str() == name) {
+        Some(entry) => {
+            *user_data = entry.user_data;
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn in_record_mode() -> AIBoolean {
+    with_state(|state| state.recording as AIBoolean)
+}
+
+/// 記録モードでなければ何もしない
+unsafe extern "C" fn record_action_event(
+    name: *const c_char,
+    dialog_status: ActionDialogStatus,
+    parameters: AIActionParamValueRef,
+) -> ASErr {
+    if name.is_null() {
+        return BAD_PARAMETER;
+    }
+    let action = RecordedAction {
+        name: lossy(name),
+        dialog_status,
+        values: param_value(parameters).map_or_else(Vec::new, |value| value.entries.clone()),
+    };
+
+    with_state(|state| {
+        if state.recording {
+            state.events.push(HostEvent::RecordActionEvent(action.name.clone()));
+            state.recorded_actions.push(action);
+        }
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn new_param_type(param: *mut AIActionParamTypeRef) -> ASErr {
+    if param.is_null() {
+        return BAD_PARAMETER;
+    }
+    *param = new_param_type_ref();
+    with_state(|state| state.events.push(HostEvent::NewActionParamType));
+    NO_ERR
+}
+
+unsafe extern "C" fn delete_param_type(param: AIActionParamTypeRef) -> ASErr {
+    if !param.is_null() {
+        drop(Box::from_raw(param as *mut ParamType));
+        with_state(|state| state.events.push(HostEvent::DeleteActionParamType));
+    }
+    NO_ERR
+}
+
+unsafe extern "C" fn get_type_key(param: AIActionParamTypeRef, index: ai_uint32, key: *mut ActionParamKeyID) -> ASErr {
+    if key.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_type(param, |value| match value.entries.get(index as usize) {
+        Some(entry) => {
+            *key = entry.key;
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn has_type_key(param: AIActionParamTypeRef, key: ActionParamKeyID, has_key: *mut AIBoolean) -> ASErr {
+    if has_key.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_type(param, |value| {
+        *has_key = value.get(key).is_some() as AIBoolean;
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn get_type_count(param: AIActionParamTypeRef, count: *mut ai_uint32) -> ASErr {
+    if count.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_type(param, |value| {
+        *count = value.entries.len() as ai_uint32;
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn set_type_key(
+    param: AIActionParamTypeRef,
+    key: ActionParamKeyID,
+    name: *const c_char,
+    type_id: ActionParamTypeID,
+    show: AIBoolean,
+) -> ASErr {
+    let name = lossy(name);
+    with_type(param, |value| {
+        value.set(ActionParamInfo { key, name, type_id, show: show != 0 });
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn set_type_key_us(
+    param: AIActionParamTypeRef,
+    key: ActionParamKeyID,
+    name: *const ai_UnicodeString,
+    type_id: ActionParamTypeID,
+    show: AIBoolean,
+) -> ASErr {
+    let name = unicode::read(name);
+    with_type(param, |value| {
+        value.set(ActionParamInfo { key, name, type_id, show: show != 0 });
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn get_type(param: AIActionParamTypeRef, key: ActionParamKeyID, type_id: *mut ActionParamTypeID) -> ASErr {
+    if type_id.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_type(param, |value| match value.get(key) {
+        Some(entry) => {
+            *type_id = entry.type_id;
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn type_is_show(param: AIActionParamTypeRef, key: ActionParamKeyID) -> AIBoolean {
+    param_type(param).and_then(|value| value.get(key)).is_some_and(|entry| entry.show) as AIBoolean
+}
+
+unsafe extern "C" fn get_value_type(
+    param: AIActionParamValueRef,
+    key: ActionParamKeyID,
+    type_id: *mut ActionParamTypeID,
+) -> ASErr {
+    if type_id.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_param(param, |value| match value.get(key) {
+        Some(current) => {
+            *type_id = current.type_id();
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+/// 値を種類ごとの出力引数へ書く（キーがない・種類が違うなら `kBadParameterErr`）
+unsafe fn get_value<T>(
+    param: AIActionParamValueRef,
+    key: ActionParamKeyID,
+    out: *mut T,
+    read: impl FnOnce(&ActionValue) -> Option<T>,
+) -> ASErr {
+    if out.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_param(param, |value| match value.get(key).and_then(read) {
+        Some(current) => {
+            *out = current;
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe fn set_value(param: AIActionParamValueRef, key: ActionParamKeyID, new_value: ActionValue) -> ASErr {
+    with_param(param, |value| {
+        value.set(key, new_value);
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn get_integer(param: AIActionParamValueRef, key: ActionParamKeyID, value: *mut ai_int32) -> ASErr {
+    get_value(param, key, value, |current| match current {
+        ActionValue::Integer(value) => Some(*value),
+        _ => None,
+    })
+}
+
+unsafe extern "C" fn set_integer(param: AIActionParamValueRef, key: ActionParamKeyID, value: ai_int32) -> ASErr {
+    set_value(param, key, ActionValue::Integer(value))
+}
+
+unsafe extern "C" fn get_real(param: AIActionParamValueRef, key: ActionParamKeyID, value: *mut AIReal) -> ASErr {
+    get_value(param, key, value, |current| match current {
+        ActionValue::Real(value) => Some(*value),
+        _ => None,
+    })
+}
+
+unsafe extern "C" fn set_real(param: AIActionParamValueRef, key: ActionParamKeyID, value: AIReal) -> ASErr {
+    set_value(param, key, ActionValue::Real(value))
+}
+
+unsafe extern "C" fn get_boolean(param: AIActionParamValueRef, key: ActionParamKeyID, value: *mut AIBoolean) -> ASErr {
+    get_value(param, key, value, |current| match current {
+        ActionValue::Boolean(value) => Some(*value as AIBoolean),
+        _ => None,
+    })
+}
+
+unsafe extern "C" fn set_boolean(param: AIActionParamValueRef, key: ActionParamKeyID, value: ASBoolean) -> ASErr {
+    set_value(param, key, ActionValue::Boolean(value != 0))
+}
+
+unsafe extern "C" fn set_string(param: AIActionParamValueRef, key: ActionParamKeyID, value: *const c_char) -> ASErr {
+    if value.is_null() {
+        return BAD_PARAMETER;
+    }
+    set_value(param, key, ActionValue::String(lossy(value)))
+}
+
+unsafe extern "C" fn get_string_us(
+    param: AIActionParamValueRef,
+    key: ActionParamKeyID,
+    value: *mut ai_UnicodeString,
+) -> ASErr {
+    if value.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_param(param, |current| match current.get(key) {
+        Some(ActionValue::String(text)) => {
+            unicode::write(value, text);
+            NO_ERR
+        }
+        _ => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn set_string_us(
+    param: AIActionParamValueRef,
+    key: ActionParamKeyID,
+    value: *const ai_UnicodeString,
+) -> ASErr {
+    if value.is_null() {
+        return BAD_PARAMETER;
+    }
+    set_value(param, key, ActionValue::String(unicode::read(value)))
+}
@@ -0,0 +1,620 @@
+use std::ffi::c_short;
+
+use illustrator_sys::*;
+
+use crate::dictionary;
+use crate::state::{with_state, HostEvent, HostState, NO_ERR};
+use crate::unicode;
+
+const ART_HANDLE_BASE: usize = 0x10_0000;
+const LAYER_HANDLE_BASE: usize = 0x20_0000;
+const HANDLE_STRIDE: usize = 16;
+
+pub(crate) const BAD_PARAMETER: AIErr = kBadParameterErr as AIErr;
+
+/// ドキュメント内のアートオブジェクト
+#[derive(Clone)]
+pub(crate) struct ArtNode {
+    pub art_type: i16,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    pub user_attr: i32,
+    pub bounds: AIRealRect,
+    pub name: String,
+    pub layer: usize,
+    pub layer_group: bool,
+    pub disposed: bool,
+    pub segments: Vec<AIPathSegment>,
+    pub closed: bool,
+    /// `SetPathStyle` で設定されたスタイル
+    pub style: Option<AIPathStyle>,
+    /// `GetDictionary` で作られる辞書
+    pub dictionary: Option<AIDictionaryRef>,
+}
+
+/// レイヤーとその最上位グループ
+///
+/// サブレイヤーのグループは親レイヤーのグループの子になる。
+#[derive(Clone)]
+pub(crate) struct LayerEntry {
+    pub title: String,
+    pub group: usize,
+    pub visible: bool,
+    pub editable: bool,
+    pub printed: bool,
+    pub preview: bool,
+    pub template: bool,
+    pub dim_placed_images: bool,
+    pub selected: bool,
+    pub color: AIRGBColor,
+    pub deleted: bool,
+}
+
+impl LayerEntry {
+    pub fn new(title: &str, group: usize) -> Self {
+        Self {
+            title: title.to_string(),
+            group,
+            visible: true,
+            editable: true,
+            printed: true,
+            preview: true,
+            template: false,
+            dim_placed_images: false,
+            selected: false,
+            // レイヤーパネルの最初の色（ライトブルー）
+            color: AIRGBColor { red: 0x4f4f, green: 0x8080, blue: 0xffff },
+            deleted: false,
+        }
+    }
+}
+
+/// ホストが保持するアートツリー
+///
+/// 子の並びは重ね順の上から下（`GetArtFirstChild` が最前面）。
+#[derive(Clone)]
+pub(crate) struct ArtTree {
+    pub nodes: Vec<ArtNode>,
+    pub layers: Vec<LayerEntry>,
+    /// 最上位のレイヤー（上から）
+    pub top_layers: Vec<usize>,
+    pub current_layer: usize,
+}
+
+impl ArtTree {
+    pub fn new() -> Self {
+        let mut tree = Self {
+            nodes: Vec::new(),
+            layers: Vec::new(),
+            top_layers: Vec::new(),
+            current_layer: 0,
+        };
+        tree.add_layer("Layer 1");
+        tree
+    }
+
+    /// 最前面にレイヤーを追加し、その番号を返す
+    pub fn add_layer(&mut self, title: &str) -> usize {
+        let layer = self.new_layer(title);
+        self.top_layers.insert(0, layer);
+        layer
+    }
+
+    /// どこにも配置されていないレイヤーを作る
+    pub fn new_layer(&mut self, title: &str) -> usize {
+        let layer = self.layers.len();
+        let group = self.push_node(AIArtType_kGroupArt as i16, layer);
+        self.nodes[group].layer_group = true;
+        self.layers.push(LayerEntry::new(title, group));
+        layer
+    }
+
+    pub fn push_node(&mut self, art_type: i16, layer: usize) -> usize {
+        self.nodes.push(ArtNode {
+            art_type,
+            parent: None,
+            children: Vec::new(),
+            user_attr: 0,
+            bounds: AIRealRect { left: 0.0, top: 0.0, right: 0.0, bottom: 0.0 },
+            name: String::new(),
+            layer,
+            layer_group: false,
+            disposed: false,
+            segments: Vec::new(),
+            closed: false,
+            style: None,
+            dictionary: None,
+        });
+        self.nodes.len() - 1
+    }
+
+    pub fn get(&self, art: AIArtHandle) -> Option<usize> {
+        let index = (art as usize).checked_sub(ART_HANDLE_BASE)? / HANDLE_STRIDE;
+        self.nodes
+            .get(index)
+            .filter(|node| !node.disposed)
+            .map(|_| index)
+    }
+
+    pub fn layer_index(&self, layer: AILayerHandle) -> Option<usize> {
+        let index = (layer as usize).checked_sub(LAYER_HANDLE_BASE)? / HANDLE_STRIDE;
+        self.layers
+            .get(index)
+            .filter(|layer| !layer.deleted)
+            .map(|_| index)
+    }
+
+    pub fn detach(&mut self, index: usize) {
+        if let Some(parent) = self.nodes[index].parent.take() {
+            self.nodes[parent].children.retain(|&child| child != index);
+        }
+    }
+
+    pub fn is_ancestor(&self, ancestor: usize, mut index: usize) -> bool {
+        while let Some(parent) = self.nodes[index].parent {
+            if parent == ancestor {
+                return true;
+            }
+            index = parent;
+        }
+        false
+    }
+
+    fn accepts_children(&self, index: usize) -> bool {
+        let node = &self.nodes[index];
+        node.layer_group
+            || node.art_type == AIArtType_kGroupArt as i16
+            || node.art_type == AIArtType_kCompoundPathArt as i16
+    }
+
+    /// `paint_order` と `prep` が示す位置へアートを移す
+    pub fn place(&mut self, index: usize, paint_order: i16, prep: Option<usize>) -> Result<(), AIErr> {
+        if let Some(prep) = prep {
+            if prep == index || self.is_ancestor(index, prep) {
+                return Err(BAD_PARAMETER);
+            }
+        }
+
+        let layer_group = self.layers[self.current_layer].group;
+        let order = paint_order as AIPaintOrder;
+
+        // 挿入先の親と、親の子の中での位置（移動元を外した後の位置）を決める
+        let (parent, position) = match (order, prep) {
+            (AIPaintOrder_kPlaceAbove, Some(prep)) | (AIPaintOrder_kPlaceDefault, Some(prep)) => {
+                let parent = self.nodes[prep].parent.ok_or(BAD_PARAMETER)?;
+                self.detach(index);
+                (parent, self.position(parent, prep))
+            }
+            (AIPaintOrder_kPlaceBelow, Some(prep)) => {
+                let parent = self.nodes[prep].parent.ok_or(BAD_PARAMETER)?;
+                self.detach(index);
+                (parent, self.position(parent, prep) + 1)
+            }
+            (AIPaintOrder_kPlaceInsideOnTop, Some(prep)) => {
+                if !self.accepts_children(prep) {
+                    return Err(kInvalidArtTypeForDestErr as AIErr);
+                }
+                self.detach(index);
+                (prep, 0)
+            }
+            (AIPaintOrder_kPlaceInsideOnBottom, Some(prep)) => {
+                if !self.accepts_children(prep) {
+                    return Err(kInvalidArtTypeForDestErr as AIErr);
+                }
+                self.detach(index);
+                (prep, self.nodes[prep].children.len())
+            }
+            (AIPaintOrder_kPlaceAboveAll, _) | (AIPaintOrder_kPlaceDefault, None) => {
+                self.detach(index);
+                (layer_group, 0)
+            }
+            (AIPaintOrder_kPlaceBelowAll, _) => {
+                self.detach(index);
+                (layer_group, self.nodes[layer_group].children.len())
+            }
+            _ => return Err(BAD_PARAMETER),
+        };
+
+        self.nodes[parent].children.insert(position, index);
+        self.nodes[index].parent = Some(parent);
+        if !self.nodes[index].layer_group {
+            self.set_layer(index, self.nodes[parent].layer);
+        }
+        Ok(())
+    }
+
+    pub fn position(&self, parent: usize, child: usize) -> usize {
+        self.nodes[parent]
+            .children
+            .iter()
+            .position(|&c| c == child)
+            .unwrap_or(0)
+    }
+
+    /// サブレイヤーのグループは自分のレイヤーを保つ
+    fn set_layer(&mut self, index: usize, layer: usize) {
+        self.nodes[index].layer = layer;
+        for child in self.nodes[index].children.clone() {
+            if !self.nodes[child].layer_group {
+                self.set_layer(child, layer);
+            }
+        }
+    }
+
+    /// 子孫も含めて複製し、複製の番号を返す（親は未設定）
+    pub fn duplicate(&mut self, index: usize) -> usize {
+        let mut node = self.nodes[index].clone();
+        node.parent = None;
+        node.children = Vec::new();
+        node.layer_group = false;
+        node.dictionary = node
+            .dictionary
+            .map(|dictionary| unsafe { dictionary::clone_dictionary(dictionary, self) });
+        self.nodes.push(node);
+        let copy = self.nodes.len() - 1;
+
+        for child in self.nodes[index].children.clone() {
+            let child_copy = self.duplicate(child);
+            self.nodes[child_copy].parent = Some(copy);
+            self.nodes[copy].children.push(child_copy);
+        }
+        copy
+    }
+
+    pub fn dispose(&mut self, index: usize) {
+        self.detach(index);
+        self.mark_disposed(index);
+    }
+
+    fn mark_disposed(&mut self, index: usize) {
+        self.nodes[index].disposed = true;
+        for child in self.nodes[index].children.clone() {
+            self.mark_disposed(child);
+        }
+    }
+
+    /// グループは子の外接矩形の和、パスはセグメントの制御点を含む矩形、それ以外は保持している矩形
+    pub fn bounds(&self, index: usize) -> AIRealRect {
+        let node = &self.nodes[index];
+        if !node.segments.is_empty() {
+            return control_bounds(&node.segments);
+        }
+        if node.children.is_empty() {
+            return node.bounds;
+        }
+
+        node.children
+            .iter()
+            .map(|&child| self.bounds(child))
+            .reduce(|a, b| AIRealRect {
+                left: a.left.min(b.left),
+                top: a.top.max(b.top),
+                right: a.right.max(b.right),
+                bottom: a.bottom.min(b.bottom),
+            })
+            .unwrap_or(node.bounds)
+    }
+}
+
+/// アンカーと方向線の端点をすべて含む矩形（曲線の正確な外接矩形より大きくなり得る）
+fn control_bounds(segments: &[AIPathSegment]) -> AIRealRect {
+    let mut points = segments.iter().flat_map(|s| [s.p, s.in_, s.out]);
+    let first = points.next().unwrap_or(AIRealPoint { h: 0.0, v: 0.0 });
+
+    points.fold(
+        AIRealRect { left: first.h, top: first.v, right: first.h, bottom: first.v },
+        |rect, p| AIRealRect {
+            left: rect.left.min(p.h),
+            top: rect.top.max(p.v),
+            right: rect.right.max(p.h),
+            bottom: rect.bottom.min(p.v),
+        },
+    )
+}
+
+pub(crate) fn art_handle(index: usize) -> AIArtHandle {
+    (ART_HANDLE_BASE + index * HANDLE_STRIDE) as AIArtHandle
+}
+
+pub(crate) fn layer_handle(index: usize) -> AILayerHandle {
+    (LAYER_HANDLE_BASE + index * HANDLE_STRIDE) as AILayerHandle
+}
+
+/// `AIArtSuite` のスタンドイン
+pub(crate) fn suite() -> AIArtSuite {
+    let mut suite: AIArtSuite = unsafe { std::mem::zeroed() };
+    suite.NewArt = Some(new_art);
+    suite.DisposeArt = Some(dispose_art);
+    suite.ReorderArt = Some(reorder_art);
+    suite.DuplicateArt = Some(duplicate_art);
+    suite.GetFirstArtOfLayer = Some(get_first_art_of_layer);
+    suite.GetLayerOfArt = Some(get_layer_of_art);
+    suite.GetArtType = Some(get_art_type);
+    suite.GetArtUserAttr = Some(get_art_user_attr);
+    suite.SetArtUserAttr = Some(set_art_user_attr);
+    suite.GetArtParent = Some(get_art_parent);
+    suite.GetArtFirstChild = Some(get_art_first_child);
+    suite.GetArtLastChild = Some(get_art_last_child);
+    suite.GetArtSibling = Some(get_art_sibling);
+    suite.GetArtPriorSibling = Some(get_art_prior_sibling);
+    suite.GetArtBounds = Some(get_art_bounds);
+    suite.ValidArt = Some(valid_art);
+    suite.SetArtName = Some(set_art_name);
+    suite.GetArtName = Some(get_art_name);
+    suite.IsArtLayerGroup = Some(is_art_layer_group);
+    suite.GetDictionary = Some(get_dictionary);
+    suite.HasDictionary = Some(has_dictionary);
+    suite.IsDictionaryEmpty = Some(is_dictionary_empty);
+    suite
+}
+
+pub(crate) fn with_art<R>(art: AIArtHandle, f: impl FnOnce(&mut HostState, usize) -> Result<R, AIErr>) -> Result<R, AIErr> {
+    with_state(|state| {
+        let index = state.art.get(art).ok_or(BAD_PARAMETER)?;
+        f(state, index)
+    })
+}
+
+pub(crate) fn prep_index(tree: &ArtTree, prep: AIArtHandle) -> Result<Option<usize>, AIErr> {
+    if prep.is_null() {
+        return Ok(None);
+    }
+    tree.get(prep).map(Some).ok_or(BAD_PARAMETER)
+}
+
+pub(crate) fn status(result: Result<(), AIErr>) -> AIErr {
+    result.err().unwrap_or(NO_ERR)
+}
+
+unsafe extern "C" fn new_art(art_type: ai_int16, paint_order: ai_int16, prep: AIArtHandle, new_art: *mut AIArtHandle) -> AIErr {
+    if art_type <= AIArtType_kUnknownArt as i16 || art_type > AIArtType_kLastArtType as i16 {
+        return kUnknownArtTypeErr as AIErr;
+    }
+
+    let result = with_state(|state| {
+        let prep = prep_index(&state.art, prep)?;
+        let layer = state.art.current_layer;
+        let index = state.art.push_node(art_type, layer);
+
+        if let Err(err) = state.art.place(index, paint_order, prep) {
+            state.art.nodes[index].disposed = true;
+            return Err(err);
+        }
+
+        state.events.push(HostEvent::NewArt { art_type });
+        Ok(index)
+    });
+
+    match result {
+        Ok(index) => {
+            if !new_art.is_null() {
+                *new_art = art_handle(index);
+            }
+            NO_ERR
+        }
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn dispose_art(art: AIArtHandle) -> AIErr {
+    status(with_art(art, |state, index| {
+        if state.art.nodes[index].layer_group {
+            return Err(kUntouchableLayerErr as AIErr);
+        }
+        let art_type = state.art.nodes[index].art_type;
+        state.art.dispose(index);
+        state.events.push(HostEvent::DisposeArt { art_type });
+        Ok(())
+    }))
+}
+
+/// レイヤーのグループは別のレイヤーのグループを基準にしたときだけ動かせる（レイヤーの移動）
+unsafe extern "C" fn reorder_art(art: AIArtHandle, paint_order: ai_int16, prep: AIArtHandle) -> AIErr {
+    status(with_art(art, |state, index| {
+        let prep = prep_index(&state.art, prep)?;
+
+        if state.art.nodes[index].layer_group {
+            let target = prep.filter(|&prep| state.art.nodes[prep].layer_group);
+            let Some(target) = target else {
+                return Err(kUntouchableLayerErr as AIErr);
+            };
+            let (layer, target) = (state.art.nodes[index].layer, state.art.nodes[target].layer);
+            return state.art.position_layer(layer, paint_order, Some(target));
+        }
+        state.art.place(index, paint_order, prep)
+    }))
+}
+
+unsafe extern "C" fn duplicate_art(art: AIArtHandle, paint_order: ai_int16, prep: AIArtHandle, new_art: *mut AIArtHandle) -> AIErr {
+    let result = with_art(art, |state, index| {
+        let prep = prep_index(&state.art, prep)?;
+        let copy = state.art.duplicate(index);
+
+        if let Err(err) = state.art.place(copy, paint_order, prep) {
+            state.art.dispose(copy);
+            return Err(err);
+        }
+        Ok(copy)
+    });
+
+    match result {
+        Ok(copy) => {
+            if !new_art.is_null() {
+                *new_art = art_handle(copy);
+            }
+            NO_ERR
+        }
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn get_first_art_of_layer(layer: AILayerHandle, art: *mut AIArtHandle) -> AIErr {
+    let result = with_state(|state| {
+        let layer = if layer.is_null() {
+            state.art.current_layer
+        } else {
+            state.art.layer_index(layer).ok_or(BAD_PARAMETER)?
+        };
+        Ok(state.art.layers[layer].group)
+    });
+
+    match result {
+        Ok(group) => {
+            *art = art_handle(group);
+            NO_ERR
+        }
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn get_layer_of_art(art: AIArtHandle, layer: *mut AILayerHandle) -> AIErr {
+    match with_art(art, |state, index| Ok(state.art.nodes[index].layer)) {
+        Ok(index) => {
+            *layer = layer_handle(index);
+            NO_ERR
+        }
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn get_art_type(art: AIArtHandle, art_type: *mut c_short) -> AIErr {
+    match with_art(art, |state, index| Ok(state.art.nodes[index].art_type)) {
+        Ok(value) => {
+            *art_type = value;
+            NO_ERR
+        }
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn get_art_user_attr(art: AIArtHandle, which: ai_int32, attr: *mut ai_int32) -> AIErr {
+    match with_art(art, |state, index| Ok(state.art.nodes[index].user_attr & which)) {
+        Ok(value) => {
+            *attr = value;
+            NO_ERR
+        }
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn set_art_user_attr(art: AIArtHandle, which: ai_int32, attr: ai_int32) -> AIErr {
+    status(with_art(art, |state, index| {
+        let node = &mut state.art.nodes[index];
+        node.user_attr = (node.user_attr & !which) | (attr & which);
+        Ok(())
+    }))
+}
+
+/// 見つからない場合は `null` を書き込む問い合わせ
+unsafe fn relative(art: AIArtHandle, out: *mut AIArtHandle, f: impl FnOnce(&ArtTree, usize) -> Option<usize>) -> AIErr {
+    match with_art(art, |state, index| Ok(f(&state.art, index))) {
+        Ok(found) => {
+            *out = found.map_or(std::ptr::null_mut(), art_handle);
+            NO_ERR
+        }
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn get_art_parent(art: AIArtHandle, parent: *mut AIArtHandle) -> AIErr {
+    relative(art, parent, |tree, index| tree.nodes[index].parent)
+}
+
+unsafe extern "C" fn get_art_first_child(art: AIArtHandle, child: *mut AIArtHandle) -> AIErr {
+    relative(art, child, |tree, index| tree.nodes[index].children.first().copied())
+}
+
+unsafe extern "C" fn get_art_last_child(art: AIArtHandle, child: *mut AIArtHandle) -> AIErr {
+    relative(art, child, |tree, index| tree.nodes[index].children.last().copied())
+}
+
+unsafe extern "C" fn get_art_sibling(art: AIArtHandle, sibling: *mut AIArtHandle) -> AIErr {
+    relative(art, sibling, |tree, index| {
+        let parent = tree.nodes[index].parent?;
+        let position = tree.position(parent, index);
+        tree.nodes[parent].children.get(position + 1).copied()
+    })
+}
+
+unsafe extern "C" fn get_art_prior_sibling(art: AIArtHandle, sibling: *mut AIArtHandle) -> AIErr {
+    relative(art, sibling, |tree, index| {
+        let parent = tree.nodes[index].parent?;
+        let position = tree.position(parent, index);
+        position.checked_sub(1).map(|p| tree.nodes[parent].children[p])
+    })
+}
+
+unsafe extern "C" fn get_art_bounds(art: AIArtHandle, bounds: *mut AIRealRect) -> AIErr {
+    match with_art(art, |state, index| Ok(state.art.bounds(index))) {
+        Ok(rect) => {
+            *bounds = rect;
+            NO_ERR
+        }
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn valid_art(art: AIArtHandle, _search_all_layer_lists: AIBoolean) -> AIBoolean {
+    with_state(|state| state.art.get(art).is_some()) as AIBoolean
+}
+
+unsafe extern "C" fn set_art_name(art: AIArtHandle, name: *const ai_UnicodeString) -> AIErr {
+    let name = unicode::read(name);
+    status(with_art(art, |state, index| {
+        state.art.nodes[index].name = name;
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn get_art_name(art: AIArtHandle, name: *mut ai_UnicodeString, is_default_name: *mut ASBoolean) -> AIErr {
+    match with_art(art, |state, index| Ok(state.art.nodes[index].name.clone())) {
+        Ok(value) => {
+            if !is_default_name.is_null() {
+                *is_default_name = value.is_empty() as ASBoolean;
+            }
+            unicode::write(name, &value);
+            NO_ERR
+        }
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn is_art_layer_group(art: AIArtHandle, is_layer_group: *mut ASBoolean) -> AIErr {
+    match with_art(art, |state, index| Ok(state.art.nodes[index].layer_group)) {
+        Ok(value) => {
+            *is_layer_group = value as ASBoolean;
+            NO_ERR
+        }
+        Err(err) => err,
+    }
+}
+
+/// 辞書がなければ作る（呼び出し元が参照を一つ所有する）
+unsafe extern "C" fn get_dictionary(art: AIArtHandle, dictionary: *mut AIDictionaryRef) -> AIErr {
+    let result = with_art(art, |state, index| {
+        let node = &mut state.art.nodes[index];
+        let owned = *node.dictionary.get_or_insert_with(dictionary::new_dictionary);
+        Ok(dictionary::retain_dictionary(owned))
+    });
+
+    match result {
+        Ok(value) => {
+            *dictionary = value;
+            NO_ERR
+        }
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn has_dictionary(art: AIArtHandle) -> AIBoolean {
+    with_art(art, |state, index| Ok(state.art.nodes[index].dictionary.is_some())).unwrap_or(false) as AIBoolean
+}
+
+unsafe extern "C" fn is_dictionary_empty(art: AIArtHandle) -> AIBoolean {
+    let len = with_art(art, |state, index| {
+        Ok(state.art.nodes[index]
+            .dictionary
+            .map_or(0, |dictionary| dictionary::dictionary_len(dictionary)))
+    });
+    (len.unwrap_or(0) == 0) as AIBoolean
+}
@@ -0,0 +1,531 @@
+use illustrator_sys::*;
+
+use crate::art::{status, BAD_PARAMETER};
+use crate::document::DocumentEntry;
+use crate::state::{with_state, NO_ERR};
+use crate::unicode;
+
+const NO_DOCUMENT: AIErr = kNoDocumentErr as AIErr;
+
+/// ドキュメントのアートボード
+#[derive(Clone)]
+pub(crate) struct ArtboardEntry {
+    pub name: String,
+    pub default_name: bool,
+    pub bounds: AIRealRect,
+    pub ruler_origin: AIRealPoint,
+    pub par: f64,
+    pub selected: bool,
+}
+
+impl ArtboardEntry {
+    /// `Init` が返す設定（名前は追加時に「Artboard N」になる）
+    pub fn new() -> Self {
+        Self {
+            name: String::new(),
+            default_name: true,
+            bounds: AIRealRect { left: 0.0, top: 792.0, right: 612.0, bottom: 0.0 },
+            ruler_origin: AIRealPoint { h: 0.0, v: 0.0 },
+            par: 1.0,
+            selected: false,
+        }
+    }
+
+    pub fn named(name: &str, bounds: AIRealRect) -> Self {
+        Self {
+            name: name.to_owned(),
+            default_name: false,
+            bounds,
+            ..Self::new()
+        }
+    }
+
+    fn overlaps(&self, other: &ArtboardEntry) -> bool {
+        let (a, b) = (&self.bounds, &other.bounds);
+        a.left < b.right && b.left < a.right && a.bottom < b.top && b.bottom < a.top
+    }
+}
+
+/// `ai::ArtboardProperties` が指す設定（`fImpl` は `Box<ArtboardEntry>`）
+unsafe fn properties<'a>(properties: *const ai_ArtboardProperties) -> Result<&'a mut ArtboardEntry, AIErr> {
+    if properties.is_null() {
+        return Err(BAD_PARAMETER);
+    }
+    ((*properties).fImpl as *mut ArtboardEntry).as_mut().ok_or(BAD_PARAMETER)
+}
+
+/// 設定を置き換える（未初期化なら確保する）
+unsafe fn store(out: *mut ai_ArtboardProperties, entry: ArtboardEntry) -> Result<(), AIErr> {
+    if out.is_null() {
+        return Err(BAD_PARAMETER);
+    }
+    match properties(out) {
+        Ok(current) => *current = entry,
+        Err(_) => (*out).fImpl = Box::into_raw(Box::new(entry)) as ai_ArtboardRef,
+    }
+    Ok(())
+}
+
+/// `list` が現在のドキュメントのリストのときだけ `f` を呼ぶ
+unsafe fn with_list<R>(list: *const ai_ArtboardList, f: impl FnOnce(&mut DocumentEntry) -> Result<R, AIErr>) -> Result<R, AIErr> {
+    if list.is_null() {
+        return Err(BAD_PARAMETER);
+    }
+    let token = (*list).fImpl as usize;
+
+    with_state(|state| {
+        let entry = state.document.as_mut().ok_or(NO_DOCUMENT)?;
+        if token != entry.handle() as usize {
+            return Err(BAD_PARAMETER);
+        }
+        f(entry)
+    })
+}
+
+fn checked_index(entry: &DocumentEntry, index: ai_ArtboardID) -> Result<usize, AIErr> {
+    usize::try_from(index)
+        .ok()
+        .filter(|&index| index < entry.artboards.len())
+        .ok_or(BAD_PARAMETER)
+}
+
+/// 追加するアートボード（自動の名前は追加後の番号で付け直す）
+fn added(entry: &DocumentEntry, mut artboard: ArtboardEntry) -> ArtboardEntry {
+    if artboard.default_name {
+        artboard.name = format!("Artboard {}", entry.artboards.len() + 1);
+    }
+    artboard.selected = false;
+    artboard
+}
+
+/// `AIArtboardSuite` のスタンドイン
+///
+/// アートボードリストの `fImpl` にはドキュメントのハンドルを入れ、閉じたドキュメントのリストを拒否する。
+pub(crate) fn suite() -> AIArtboardSuite {
+    let mut suite: AIArtboardSuite = unsafe { std::mem::zeroed() };
+    suite.Init = Some(init);
+    suite.CloneArtboard = Some(clone_artboard);
+    suite.Dispose = Some(dispose);
+    suite.GetPosition = Some(get_position);
+    suite.SetPosition = Some(set_position);
+    suite.GetPAR = Some(get_par);
+    suite.SetPAR = Some(set_par);
+    suite.GetName = Some(get_name);
+    suite.SetName = Some(set_name);
+    suite.GetRulerOrigin = Some(get_ruler_origin);
+    suite.SetRulerOrigin = Some(set_ruler_origin);
+    suite.IsDefaultName = Some(is_default_name);
+    suite.SetIsDefaultName = Some(set_is_default_name);
+    suite.IsSelected = Some(is_selected);
+    suite.GetArtboardList = Some(get_artboard_list);
+    suite.ReleaseArtboardList = Some(release_artboard_list);
+    suite.AddNew = Some(add_new);
+    suite.Insert = Some(insert);
+    suite.Delete = Some(delete);
+    suite.GetCount = Some(get_count);
+    suite.GetActive = Some(get_active);
+    suite.SetActive = Some(set_active);
+    suite.Update = Some(update);
+    suite.GetArtboardProperties = Some(get_artboard_properties);
+    suite.SelectArtboard = Some(select_artboard);
+    suite.SelectAllArtboards = Some(select_all_artboards);
+    suite.DeselectArtboard = Some(deselect_artboard);
+    suite.DeselectAllArtboards = Some(deselect_all_artboards);
+    suite.AreAnyArtboardsOverlapping = Some(are_any_artboards_overlapping);
+    suite
+}
+
+unsafe extern "C" fn init(artboard: *mut ai_ArtboardProperties) -> AIErr {
+    if artboard.is_null() {
+        return BAD_PARAMETER;
+    }
+    (*artboard).fImpl = Box::into_raw(Box::new(ArtboardEntry::new())) as ai_ArtboardRef;
+    NO_ERR
+}
+
+unsafe extern "C" fn clone_artboard(artboard: *mut ai_ArtboardProperties, source: *const ai_ArtboardProperties) -> AIErr {
+    status(properties(source).and_then(|source| store(artboard, source.clone())))
+}
+
+unsafe extern "C" fn dispose(artboard: *mut ai_ArtboardProperties) -> AIErr {
+    if !artboard.is_null() && !(*artboard).fImpl.is_null() {
+        drop(Box::from_raw((*artboard).fImpl as *mut ArtboardEntry));
+        (*artboard).fImpl = std::ptr::null_mut();
+    }
+    NO_ERR
+}
+
+unsafe extern "C" fn get_position(artboard: *const ai_ArtboardProperties, bounds: *mut AIRealRect) -> AIErr {
+    write(bounds, properties(artboard).map(|entry| entry.bounds))
+}
+
+unsafe extern "C" fn set_position(artboard: *mut ai_ArtboardProperties, bounds: *const AIRealRect) -> AIErr {
+    if bounds.is_null() || (*bounds).right <= (*bounds).left || (*bounds).top <= (*bounds).bottom {
+        return BAD_PARAMETER;
+    }
+    status(properties(artboard).map(|entry| entry.bounds = *bounds))
+}
+
+unsafe extern "C" fn get_par(artboard: *const ai_ArtboardProperties, par: *mut AIReal) -> AIErr {
+    write(par, properties(artboard).map(|entry| entry.par))
+}
+
+unsafe extern "C" fn set_par(artboard: *mut ai_ArtboardProperties, par: AIReal) -> AIErr {
+    if par <= 0.0 {
+        return BAD_PARAMETER;
+    }
+    status(properties(artboard).map(|entry| entry.par = par))
+}
+
+unsafe extern "C" fn get_name(artboard: *const ai_ArtboardProperties, name: *mut ai_UnicodeString) -> AIErr {
+    status(properties(artboard).map(|entry| unicode::write(name, &entry.name)))
+}
+
+unsafe extern "C" fn set_name(artboard: *mut ai_ArtboardProperties, name: *const ai_UnicodeString) -> AIErr {
+    status(properties(artboard).map(|entry| {
+        entry.name = unicode::read(name);
+        entry.default_name = false;
+    }))
+}
+
+unsafe extern "C" fn get_ruler_origin(artboard: *const ai_ArtboardProperties, origin: *mut AIRealPoint) -> AIErr {
+    write(origin, properties(artboard).map(|entry| entry.ruler_origin))
+}
+
+unsafe extern "C" fn set_ruler_origin(artboard: *mut ai_ArtboardProperties, origin: *const AIRealPoint) -> AIErr {
+    if origin.is_null() {
+        return BAD_PARAMETER;
+    }
+    status(properties(artboard).map(|entry| entry.ruler_origin = *origin))
+}
+
+unsafe extern "C" fn is_default_name(artboard: *const ai_ArtboardProperties, default: *mut AIBoolean) -> AIErr {
+    write(default, properties(artboard).map(|entry| entry.default_name as AIBoolean))
+}
+
+unsafe extern "C" fn set_is_default_name(artboard: *mut ai_ArtboardProperties, default: *const AIBoolean) -> AIErr {
+    if default.is_null() {
+        return BAD_PARAMETER;
+    }
+    status(properties(artboard).map(|entry| entry.default_name = *default != 0))
+}
+
+unsafe extern "C" fn is_selected(artboard: *const ai_ArtboardProperties, selected: *mut AIBoolean) -> AIErr {
+    write(selected, properties(artboard).map(|entry| entry.selected as AIBoolean))
+}
+
+unsafe extern "C" fn get_artboard_list(list: *mut ai_ArtboardList) -> AIErr {
+    if list.is_null() {
+        return BAD_PARAMETER;
+    }
+    let result = with_state(|state| state.document.as_ref().map(DocumentEntry::handle).ok_or(NO_DOCUMENT));
+    status(result.map(|handle| (*list).fImpl = handle as ai_ArtboardListRef))
+}
+
+unsafe extern "C" fn release_artboard_list(list: *mut ai_ArtboardList) -> AIErr {
+    if !list.is_null() {
+        (*list).fImpl = std::ptr::null_mut();
+    }
+    NO_ERR
+}
+
+unsafe extern "C" fn add_new(list: *mut ai_ArtboardList, artboard: *mut ai_ArtboardProperties, index: *mut ai_ArtboardID) -> AIErr {
+    let result = properties(artboard).and_then(|artboard| {
+        let artboard = artboard.clone();
+        with_list(list, |entry| {
+            let artboard = added(entry, artboard);
+            entry.artboards.push(artboard);
+            Ok((entry.artboards.len() - 1) as ai_ArtboardID)
+        })
+    });
+    write(index, result)
+}
+
+unsafe extern "C" fn insert(list: *mut ai_ArtboardList, artboard: *mut ai_ArtboardProperties, index: *mut ai_ArtboardID) -> AIErr {
+    if index.is_null() {
+        return BAD_PARAMETER;
+    }
+    let result = properties(artboard).and_then(|artboard| {
+        let artboard = artboard.clone();
+        with_list(list, |entry| {
+            let position = usize::try_from(*index)
+                .ok()
+                .filter(|&position| position <= entry.artboards.len())
+                .ok_or(BAD_PARAMETER)?;
+            let artboard = added(entry, artboard);
+            entry.artboards.insert(position, artboard);
+            if entry.active_artboard >= position && entry.artboards.len() > 1 {
+                entry.active_artboard += 1;
+            }
+            Ok(position as ai_ArtboardID)
+        })
+    });
+    write(index, result)
+}
+
+unsafe extern "C" fn delete(list: *mut ai_ArtboardList, index: ai_ArtboardID) -> AIErr {
+    status(with_list(list, |entry| {
+        let index = checked_index(entry, index)?;
+        // 最後のアートボードは削除できない
+        if entry.artboards.len() == 1 {
+            return Err(kCantHappenErr as AIErr);
+        }
+        entry.artboards.remove(index);
+        if entry.active_artboard > index || entry.active_artboard == entry.artboards.len() {
+            entry.active_artboard -= 1;
+        }
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn get_count(list: *const ai_ArtboardList, count: *mut ai_ArtboardID) -> AIErr {
+    write(count, with_list(list, |entry| Ok(entry.artboards.len() as ai_ArtboardID)))
+}
+
+unsafe extern "C" fn get_active(list: *const ai_ArtboardList, index: *mut ai_ArtboardID) -> AIErr {
+    write(index, with_list(list, |entry| Ok(entry.active_artboard as ai_ArtboardID)))
+}
+
+unsafe extern "C" fn set_active(list: *mut ai_ArtboardList, index: ai_ArtboardID) -> AIErr {
+    status(with_list(list, |entry| {
+        entry.active_artboard = checked_index(entry, index)?;
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn update(list: *mut ai_ArtboardList, index: ai_ArtboardID, artboard: *const ai_ArtboardProperties) -> AIErr {
+    status(properties(artboard).and_then(|artboard| {
+        let artboard = artboard.clone();
+        with_list(list, |entry| {
+            let index = checked_index(entry, index)?;
+            let target = &mut entry.artboards[index];
+            *target = ArtboardEntry {
+                selected: target.selected,
+                ..artboard
+            };
+            Ok(())
+        })
+    }))
+}
+
+unsafe extern "C" fn get_artboard_properties(
+    list: *mut ai_ArtboardList,
+    index: ai_ArtboardID,
+    artboard: *mut ai_ArtboardProperties,
+) -> AIErr {
+    status(with_list(list, |entry| {
+        let index = checked_index(entry, index)?;
+        Ok(entry.artboards[index].clone())
+    })
+    .and_then(|copy| store(artboard, copy)))
+}
+
+unsafe extern "C" fn select_artboard(list: *mut ai_ArtboardList, index: ai_ArtboardID, exclusively: AIBoolean) -> AIErr {
+    status(with_list(list, |entry| {
+        let index = checked_index(entry, index)?;
+        if exclusively != 0 {
+            entry.artboards.iter_mut().for_each(|artboard| artboard.selected = false);
+        }
+        entry.artboards[index].selected = true;
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn select_all_artboards(list: *mut ai_ArtboardList) -> AIErr {
+    status(with_list(list, |entry| {
+        entry.artboards.iter_mut().for_each(|artboard| artboard.selected = true);
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn deselect_artboard(list: *mut ai_ArtboardList, index: ai_ArtboardID) -> AIErr {
+    status(with_list(list, |entry| {
+        let index = checked_index(entry, index)?;
+        entry.artboards[index].selected = false;
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn deselect_all_artboards(list: *mut ai_ArtboardList) -> AIErr {
+    status(with_list(list, |entry| {
+        entry.artboards.iter_mut().for_each(|artboard| artboard.selected = false);
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn are_any_artboards_overlapping(list: *mut ai_ArtboardList, overlapping: *mut AIBoolean) -> AIErr {
+    let result = with_list(list, |entry| {
+        let artboards = &entry.artboards;
+        let any = (0..artboards.len()).any(|i| artboards[i + 1..].iter().any(|other| artboards[i].overlaps(other)));
+        Ok(any as AIBoolean)
+    });
+    write(overlapping, result)
+}
+
+/// `AIArtboardRangeHandle` が指す範囲
+struct RangeEntry {
+    indices: Vec<ASInt32>,
+    all: bool,
+}
+
+/// `AIArtboardRangeIterator` が指す位置
+struct RangeCursor {
+    indices: Vec<ASInt32>,
+    position: usize,
+}
+
+/// `"1-3,5"` を 0 始まりの番号に展開する（`"5-3"` のような逆順も許す）
+pub(crate) fn parse_range(text: &str) -> Option<Vec<ASInt32>> {
+    if text.trim().is_empty() {
+        return Some(Vec::new());
+    }
+
+    let number = |part: &str| part.trim().parse::<ASInt32>().ok().filter(|&n| n >= 1).map(|n| n - 1);
+    let mut indices = Vec::new();
+
+    for part in text.split(',') {
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let (first, last) = (number(first)?, number(last)?);
+                if first <= last {
+                    indices.extend(first..=last);
+                } else {
+                    indices.extend((last..=first).rev());
+                }
+            }
+            None => indices.push(number(part)?),
+        }
+    }
+    Some(indices)
+}
+
+unsafe fn range<'a>(handle: AIArtboardRangeHandle) -> Result<&'a mut RangeEntry, AIErr> {
+    (handle as *mut RangeEntry).as_mut().ok_or(BAD_PARAMETER)
+}
+
+unsafe fn cursor<'a>(iterator: AIArtboardRangeIterator) -> Result<&'a mut RangeCursor, AIErr> {
+    (iterator as *mut RangeCursor).as_mut().ok_or(BAD_PARAMETER)
+}
+
+/// `AIArtboardRangeSuite` のスタンドイン
+///
+/// 終端を越えた `Next`/`Previous` は `kBadParameterErr` を返す。
+pub(crate) fn range_suite() -> AIArtboardRangeSuite {
+    let mut suite: AIArtboardRangeSuite = unsafe { std::mem::zeroed() };
+    suite.Create = Some(create_range);
+    suite.GetCount = Some(get_range_count);
+    suite.Begin = Some(begin);
+    suite.Next = Some(next);
+    suite.Previous = Some(previous);
+    suite.Back = Some(back);
+    suite.DisposeIterator = Some(dispose_iterator);
+    suite.Dispose = Some(dispose_range);
+    suite.ValidateString = Some(validate_string);
+    suite.IsAllSelected = Some(is_all_selected);
+    suite.RemoveDuplicate = Some(remove_duplicate);
+    suite
+}
+
+unsafe extern "C" fn create_range(
+    all_artboards: ASBoolean,
+    range_string: *const ai_UnicodeString,
+    handle: *mut AIArtboardRangeHandle,
+) -> AIErr {
+    let result = if all_artboards != 0 {
+        with_state(|state| {
+            let count = state.document.as_ref().ok_or(NO_DOCUMENT)?.artboards.len();
+            Ok(RangeEntry {
+                indices: (0..count as ASInt32).collect(),
+                all: true,
+            })
+        })
+    } else {
+        parse_range(&unicode::read(range_string))
+            .map(|indices| RangeEntry { indices, all: false })
+            .ok_or(BAD_PARAMETER)
+    };
+    write(handle, result.map(|entry| Box::into_raw(Box::new(entry)) as AIArtboardRangeHandle))
+}
+
+unsafe extern "C" fn get_range_count(handle: AIArtboardRangeHandle, count: *mut ASInt32) -> AIErr {
+    write(count, range(handle).map(|entry| entry.indices.len() as ASInt32))
+}
+
+unsafe extern "C" fn begin(handle: AIArtboardRangeHandle, iterator: *mut AIArtboardRangeIterator) -> AIErr {
+    let result = range(handle).map(|entry| {
+        let cursor = RangeCursor {
+            indices: entry.indices.clone(),
+            position: 0,
+        };
+        Box::into_raw(Box::new(cursor)) as AIArtboardRangeIterator
+    });
+    write(iterator, result)
+}
+
+unsafe extern "C" fn next(iterator: AIArtboardRangeIterator, index: *mut ASInt32) -> AIErr {
+    let result = cursor(iterator).and_then(|cursor| {
+        let value = *cursor.indices.get(cursor.position).ok_or(BAD_PARAMETER)?;
+        cursor.position += 1;
+        Ok(value)
+    });
+    write(index, result)
+}
+
+unsafe extern "C" fn previous(iterator: AIArtboardRangeIterator, index: *mut ASInt32) -> AIErr {
+    let result = cursor(iterator).and_then(|cursor| {
+        let position = cursor.position.checked_sub(1).ok_or(BAD_PARAMETER)?;
+        cursor.position = position;
+        Ok(cursor.indices[position])
+    });
+    write(index, result)
+}
+
+unsafe extern "C" fn back(iterator: AIArtboardRangeIterator) -> AIErr {
+    status(cursor(iterator).map(|cursor| cursor.position = cursor.indices.len()))
+}
+
+unsafe extern "C" fn dispose_iterator(iterator: AIArtboardRangeIterator) -> AIErr {
+    if !iterator.is_null() {
+        drop(Box::from_raw(iterator as *mut RangeCursor));
+    }
+    NO_ERR
+}
+
+unsafe extern "C" fn dispose_range(handle: AIArtboardRangeHandle) -> AIErr {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle as *mut RangeEntry));
+    }
+    NO_ERR
+}
+
+unsafe extern "C" fn validate_string(range_string: *const ai_UnicodeString) -> AIErr {
+    match parse_range(&unicode::read(range_string)) {
+        Some(_) => NO_ERR,
+        None => BAD_PARAMETER,
+    }
+}
+
+unsafe extern "C" fn is_all_selected(handle: AIArtboardRangeHandle, all: *mut ASBoolean) -> AIErr {
+    write(all, range(handle).map(|entry| entry.all as ASBoolean))
+}
+
+unsafe extern "C" fn remove_duplicate(handle: AIArtboardRangeHandle) -> AIErr {
+    status(range(handle).map(|entry| {
+        let mut seen = std::collections::HashSet::new();
+        entry.indices.retain(|index| seen.insert(*index));
+    }))
+}
+
+/// 成功したら `out` に書き込む
+unsafe fn write<T>(out: *mut T, result: Result<T, AIErr>) -> AIErr {
+    match result {
+        Ok(value) => {
+            if out.is_null() {
+                return BAD_PARAMETER;
+            }
+            *out = value;
+            NO_ERR
+        }
+        Err(err) => err,
+    }
+}
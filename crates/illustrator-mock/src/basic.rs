@@ -0,0 +1,129 @@
+use std::alloc::{alloc, dealloc, realloc, Layout};
+use std::ffi::{c_char, c_void, CStr};
+use std::ptr::null;
+
+use illustrator_sys::*;
+
+use crate::state::{lossy, with_state, HostEvent, NO_ERR};
+
+/// ブロックの先頭に確保サイズを保持するためのヘッダ長
+const BLOCK_HEADER: usize = 16;
+
+pub(crate) fn suite() -> SPBasicSuite {
+    SPBasicSuite {
+        AcquireSuite: Some(acquire_suite),
+        ReleaseSuite: Some(release_suite),
+        IsEqual: Some(is_equal),
+        AllocateBlock: Some(allocate_block),
+        FreeBlock: Some(free_block),
+        ReallocateBlock: Some(reallocate_block),
+        Undefined: Some(undefined),
+    }
+}
+
+/// `SPBlocksSuite` のスタンドイン（`SPBasicSuite` と同じアロケータを使う）
+pub(crate) fn blocks_suite() -> SPBlocksSuite {
+    SPBlocksSuite {
+        AllocateBlock: Some(allocate_debug_block),
+        FreeBlock: Some(free_block),
+        ReallocateBlock: Some(reallocate_debug_block),
+    }
+}
+
+unsafe extern "C" fn acquire_suite(name: *const c_char, version: ai_int32, suite: *mut *const c_void) -> SPErr {
+    let key = (CStr::from_ptr(name).to_owned(), version);
+
+    with_state(|state| {
+        state.events.push(HostEvent::AcquireSuite { name: lossy(name), version });
+
+        match state.suites.get_mut(&key) {
+            Some(entry) => {
+                entry.refcount += 1;
+                *suite = entry.table;
+                NO_ERR
+            }
+            None => {
+                *suite = null();
+                kSPSuiteNotFoundError as SPErr
+            }
+        }
+    })
+}
+
+unsafe extern "C" fn release_suite(name: *const c_char, version: ai_int32) -> SPErr {
+    let key = (CStr::from_ptr(name).to_owned(), version);
+
+    with_state(|state| {
+        state.events.push(HostEvent::ReleaseSuite { name: lossy(name), version });
+
+        match state.suites.get_mut(&key) {
+            Some(entry) if entry.refcount > 0 => {
+                entry.refcount -= 1;
+                NO_ERR
+            }
+            _ => kSPSuiteNotFoundError as SPErr,
+        }
+    })
+}
+
+unsafe extern "C" fn is_equal(token1: *const c_char, token2: *const c_char) -> SPBoolean {
+    (CStr::from_ptr(token1) == CStr::from_ptr(token2)) as SPBoolean
+}
+
+fn layout(size: usize) -> Layout {
+    Layout::from_size_align(size + BLOCK_HEADER, BLOCK_HEADER).unwrap()
+}
+
+unsafe extern "C" fn allocate_block(size: usize, block: *mut *mut c_void) -> SPErr {
+    let base = alloc(layout(size));
+    if base.is_null() {
+        return kSPOutOfMemoryError as SPErr;
+    }
+
+    *(base as *mut usize) = size;
+    *block = base.add(BLOCK_HEADER) as *mut c_void;
+    NO_ERR
+}
+
+unsafe extern "C" fn free_block(block: *mut c_void) -> SPErr {
+    if block.is_null() {
+        return NO_ERR;
+    }
+
+    let base = (block as *mut u8).sub(BLOCK_HEADER);
+    dealloc(base, layout(*(base as *mut usize)));
+    NO_ERR
+}
+
+unsafe extern "C" fn reallocate_block(block: *mut c_void, new_size: usize, new_block: *mut *mut c_void) -> SPErr {
+    if block.is_null() {
+        return allocate_block(new_size, new_block);
+    }
+
+    let base = (block as *mut u8).sub(BLOCK_HEADER);
+    let base = realloc(base, layout(*(base as *mut usize)), new_size + BLOCK_HEADER);
+    if base.is_null() {
+        return kSPOutOfMemoryError as SPErr;
+    }
+
+    *(base as *mut usize) = new_size;
+    *new_block = base.add(BLOCK_HEADER) as *mut c_void;
+    NO_ERR
+}
+
+unsafe extern "C" fn allocate_debug_block(size: usize, _debug: *const c_char, block: *mut *mut c_void) -> SPErr {
+    allocate_block(size, block)
+}
+
+unsafe extern "C" fn reallocate_debug_block(
+    block: *mut c_void,
+    new_size: usize,
+    _debug: *const c_char,
+    new_block: *mut *mut c_void,
+) -> SPErr {
+    reallocate_block(block, new_size, new_block)
+}
+
+unsafe extern "C" fn undefined() -> SPErr {
+    kSPUnimplementedError as SPErr
+}
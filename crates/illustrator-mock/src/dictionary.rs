@@ -0,0 +1,918 @@
+use std::collections::BTreeMap;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::ptr::{null, null_mut};
+
+use illustrator_sys::*;
+
+use crate::art::{art_handle, prep_index, status, with_art, ArtTree, BAD_PARAMETER};
+use crate::state::{lossy, with_state, NO_ERR};
+use crate::unicode;
+
+const NO_SUCH_KEY: AIErr = kNoSuchKey as AIErr;
+
+// `AIEntry.h` の無名 enum はバインディングに含まれないため、値をここで定義している
+const INTEGER_TYPE: AIEntryType = 1;
+const BOOLEAN_TYPE: AIEntryType = 2;
+const REAL_TYPE: AIEntryType = 3;
+const STRING_TYPE: AIEntryType = 4;
+const DICT_TYPE: AIEntryType = 5;
+const ARRAY_TYPE: AIEntryType = 6;
+const BINARY_TYPE: AIEntryType = 7;
+const GRAPHIC_OBJECT_TYPE: AIEntryType = 25;
+const UNICODE_STRING_TYPE: AIEntryType = 26;
+
+/// 参照カウントを持つホスト側のオブジェクト
+///
+/// `AIDictionaryRef`・`AIArrayRef`・`AIEntryRef`・`AIDictionaryIterator` はこれを指す。
+struct Counted<T> {
+    refcount: i32,
+    value: T,
+}
+
+type Map = BTreeMap<String, Value>;
+
+/// 辞書のキーの一覧を作った時点で固定したイテレータ
+struct Cursor {
+    dictionary: AIDictionaryRef,
+    keys: Vec<String>,
+    position: usize,
+}
+
+impl Drop for Cursor {
+    fn drop(&mut self) {
+        unsafe { release::<Map, _>(self.dictionary) };
+    }
+}
+
+fn create<T, P>(value: T) -> *mut P {
+    Box::into_raw(Box::new(Counted { refcount: 1, value })) as *mut P
+}
+
+unsafe fn object<'a, T, P>(raw: *const P) -> Option<&'a mut Counted<T>> {
+    (raw as *mut Counted<T>).as_mut()
+}
+
+unsafe fn add_ref<T, P>(raw: *const P) -> ai_int32 {
+    match object::<T, P>(raw) {
+        Some(object) => {
+            object.refcount += 1;
+            object.refcount
+        }
+        None => 0,
+    }
+}
+
+unsafe fn release<T, P>(raw: *const P) -> ai_int32 {
+    let Some(object) = object::<T, P>(raw) else {
+        return 0;
+    };
+    object.refcount -= 1;
+
+    let refcount = object.refcount;
+    if refcount <= 0 {
+        drop(Box::from_raw(raw as *mut Counted<T>));
+    }
+    refcount
+}
+
+/// 辞書・配列・エントリが保持する値
+///
+/// 辞書と配列は参照を保持し、`Clone` で参照を足して `Drop` で解放する。
+enum Value {
+    Integer(i32),
+    Boolean(bool),
+    Real(f64),
+    String(CString),
+    Unicode(String),
+    Binary(Vec<u8>),
+    Dictionary(AIDictionaryRef),
+    Array(AIArrayRef),
+    /// アートツリーから取り外されたアート
+    Art(usize),
+}
+
+impl Value {
+    fn entry_type(&self) -> AIEntryType {
+        match self {
+            Value::Integer(_) => INTEGER_TYPE,
+            Value::Boolean(_) => BOOLEAN_TYPE,
+            Value::Real(_) => REAL_TYPE,
+            Value::String(_) => STRING_TYPE,
+            Value::Unicode(_) => UNICODE_STRING_TYPE,
+            Value::Binary(_) => BINARY_TYPE,
+            Value::Dictionary(_) => DICT_TYPE,
+            Value::Array(_) => ARRAY_TYPE,
+            Value::Art(_) => GRAPHIC_OBJECT_TYPE,
+        }
+    }
+
+    /// 入れ子の辞書・配列・アートも複製する
+    unsafe fn deep_clone(&self, tree: &mut ArtTree) -> Value {
+        match self {
+            Value::Dictionary(dictionary) => Value::Dictionary(clone_dictionary(*dictionary, tree)),
+            Value::Array(array) => {
+                let values = object::<Vec<Value>, _>(*array)
+                    .map(|array| array.value.iter().map(|value| value.deep_clone(tree)).collect())
+                    .unwrap_or_default();
+                Value::Array(create::<Vec<Value>, _>(values))
+            }
+            Value::Art(index) => Value::Art(tree.duplicate(*index)),
+            other => other.clone(),
+        }
+    }
+}
+
+impl Clone for Value {
+    fn clone(&self) -> Self {
+        match self {
+            Value::Integer(value) => Value::Integer(*value),
+            Value::Boolean(value) => Value::Boolean(*value),
+            Value::Real(value) => Value::Real(*value),
+            Value::String(value) => Value::String(value.clone()),
+            Value::Unicode(value) => Value::Unicode(value.clone()),
+            Value::Binary(value) => Value::Binary(value.clone()),
+            Value::Dictionary(dictionary) => {
+                unsafe { add_ref::<Map, _>(*dictionary) };
+                Value::Dictionary(*dictionary)
+            }
+            Value::Array(array) => {
+                unsafe { add_ref::<Vec<Value>, _>(*array) };
+                Value::Array(*array)
+            }
+            Value::Art(index) => Value::Art(*index),
+        }
+    }
+}
+
+impl Drop for Value {
+    fn drop(&mut self) {
+        match self {
+            Value::Dictionary(dictionary) => unsafe {
+                release::<Map, _>(*dictionary);
+            },
+            Value::Array(array) => unsafe {
+                release::<Vec<Value>, _>(*array);
+            },
+            _ => {}
+        }
+    }
+}
+
+/// 空の辞書（参照数 1）
+pub(crate) fn new_dictionary() -> AIDictionaryRef {
+    create::<Map, _>(Map::new())
+}
+
+/// 参照を足して同じ辞書を返す
+pub(crate) unsafe fn retain_dictionary(dictionary: AIDictionaryRef) -> AIDictionaryRef {
+    add_ref::<Map, _>(dictionary);
+    dictionary
+}
+
+pub(crate) unsafe fn release_dictionary(dictionary: AIDictionaryRef) {
+    release::<Map, _>(dictionary);
+}
+
+/// 中身を複製した辞書（格納されたアートも複製する）
+pub(crate) unsafe fn clone_dictionary(dictionary: ConstAIDictionaryRef, tree: &mut ArtTree) -> AIDictionaryRef {
+    let map = object::<Map, _>(dictionary)
+        .map(|dictionary| {
+            dictionary
+                .value
+                .iter()
+                .map(|(key, value)| (key.clone(), value.deep_clone(tree)))
+                .collect()
+        })
+        .unwrap_or_default();
+    create::<Map, _>(map)
+}
+
+/// 辞書のキー（昇順）
+pub(crate) unsafe fn dictionary_keys(dictionary: ConstAIDictionaryRef) -> Vec<String> {
+    object::<Map, _>(dictionary)
+        .map(|dictionary| dictionary.value.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+pub(crate) unsafe fn dictionary_len(dictionary: ConstAIDictionaryRef) -> usize {
+    object::<Map, _>(dictionary).map_or(0, |dictionary| dictionary.value.len())
+}
+
+/// `AIDictionarySuite` のスタンドイン
+///
+/// `AIDictKey` はホストが保持するキー文字列へのポインタ。
+pub(crate) fn suite() -> AIDictionarySuite {
+    let mut suite: AIDictionarySuite = unsafe { std::mem::zeroed() };
+    suite.CreateDictionary = Some(create_dictionary);
+    suite.AddRef = Some(dictionary_add_ref);
+    suite.Release = Some(dictionary_release);
+    suite.Clone = Some(clone);
+    suite.Size = Some(size);
+    suite.Begin = Some(begin);
+    suite.Key = Some(key);
+    suite.GetKeyString = Some(get_key_string);
+    suite.IsKnown = Some(is_known);
+    suite.DeleteEntry = Some(delete_entry);
+    suite.GetEntryType = Some(get_entry_type);
+    suite.GetArtEntry = Some(get_art_entry);
+    suite.MoveArtToEntry = Some(move_art_to_entry);
+    suite.MoveEntryToArt = Some(move_entry_to_art);
+    suite.CopyArtToEntry = Some(copy_art_to_entry);
+    suite.CopyEntryToArt = Some(copy_entry_to_art);
+    suite.Get = Some(get);
+    suite.Set = Some(set);
+    suite.GetBooleanEntry = Some(get_boolean_entry);
+    suite.SetBooleanEntry = Some(set_boolean_entry);
+    suite.GetIntegerEntry = Some(get_integer_entry);
+    suite.SetIntegerEntry = Some(set_integer_entry);
+    suite.GetRealEntry = Some(get_real_entry);
+    suite.SetRealEntry = Some(set_real_entry);
+    suite.GetStringEntry = Some(get_string_entry);
+    suite.SetStringEntry = Some(set_string_entry);
+    suite.GetBinaryEntry = Some(get_binary_entry);
+    suite.SetBinaryEntry = Some(set_binary_entry);
+    suite.GetDictEntry = Some(get_dict_entry);
+    suite.SetDictEntry = Some(set_dict_entry);
+    suite.GetArrayEntry = Some(get_array_entry);
+    suite.SetArrayEntry = Some(set_array_entry);
+    suite.GetUnicodeStringEntry = Some(get_unicode_string_entry);
+    suite.SetUnicodeStringEntry = Some(set_unicode_string_entry);
+    suite
+}
+
+/// 成功したら `out` に書き込む
+unsafe fn write<T>(out: *mut T, result: Result<T, AIErr>) -> AIErr {
+    match result {
+        Ok(value) => {
+            if out.is_null() {
+                return BAD_PARAMETER;
+            }
+            *out = value;
+            NO_ERR
+        }
+        Err(err) => err,
+    }
+}
+
+unsafe fn with_key<R>(
+    dictionary: ConstAIDictionaryRef,
+    key: AIDictKey,
+    f: impl FnOnce(&mut Map, String) -> Result<R, AIErr>,
+) -> Result<R, AIErr> {
+    let dictionary = object::<Map, _>(dictionary).ok_or(BAD_PARAMETER)?;
+    if key.is_null() {
+        return Err(BAD_PARAMETER);
+    }
+    f(&mut dictionary.value, lossy(key as *const c_char))
+}
+
+/// 値を読む（キーがなければ `kNoSuchKey`、種類が違えば `kBadParameterErr`）
+unsafe fn lookup<R>(dictionary: ConstAIDictionaryRef, key: AIDictKey, f: impl FnOnce(&Value) -> Option<R>) -> Result<R, AIErr> {
+    with_key(dictionary, key, |map, key| {
+        let value = map.get(&key).ok_or(NO_SUCH_KEY)?;
+        f(value).ok_or(BAD_PARAMETER)
+    })
+}
+
+unsafe fn insert(dictionary: AIDictionaryRef, key: AIDictKey, value: Value) -> AIErr {
+    status(with_key(dictionary, key, |map, key| {
+        map.insert(key, value);
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn create_dictionary(dictionary: *mut AIDictionaryRef) -> AIErr {
+    write(dictionary, Ok(new_dictionary()))
+}
+
+unsafe extern "C" fn dictionary_add_ref(dictionary: AIDictionaryRef) -> ai_int32 {
+    add_ref::<Map, _>(dictionary)
+}
+
+unsafe extern "C" fn dictionary_release(dictionary: AIDictionaryRef) -> ai_int32 {
+    release::<Map, _>(dictionary)
+}
+
+unsafe extern "C" fn clone(src: ConstAIDictionaryRef, dst: *mut AIDictionaryRef) -> AIErr {
+    if object::<Map, _>(src).is_none() {
+        return BAD_PARAMETER;
+    }
+    write(dst, Ok(with_state(|state| clone_dictionary(src, &mut state.art))))
+}
+
+unsafe extern "C" fn size(dictionary: ConstAIDictionaryRef) -> ai_uint32 {
+    dictionary_len(dictionary) as ai_uint32
+}
+
+unsafe extern "C" fn begin(dictionary: ConstAIDictionaryRef, iterator: *mut AIDictionaryIterator) -> AIErr {
+    if object::<Map, _>(dictionary).is_none() {
+        return BAD_PARAMETER;
+    }
+
+    let cursor = Cursor {
+        dictionary: retain_dictionary(dictionary as AIDictionaryRef),
+        keys: dictionary_keys(dictionary),
+        position: 0,
+    };
+    write(iterator, Ok(create::<Cursor, _>(cursor)))
+}
+
+/// キーはホストが破棄するまで同じポインタを返す
+unsafe extern "C" fn key(key_string: *const c_char) -> AIDictKey {
+    if key_string.is_null() {
+        return null();
+    }
+    intern(CStr::from_ptr(key_string))
+}
+
+fn intern(name: &CStr) -> AIDictKey {
+    with_state(|state| {
+        let keys = &mut state.dictionary_keys;
+        let index = match keys.iter().position(|key| key.as_c_str() == name) {
+            Some(index) => index,
+            None => {
+                keys.push(name.to_owned());
+                keys.len() - 1
+            }
+        };
+        keys[index].as_ptr() as AIDictKey
+    })
+}
+
+unsafe extern "C" fn get_key_string(key: AIDictKey) -> *const c_char {
+    key as *const c_char
+}
+
+unsafe extern "C" fn is_known(dictionary: ConstAIDictionaryRef, key: AIDictKey) -> AIBoolean {
+    with_key(dictionary, key, |map, key| Ok(map.contains_key(&key))).unwrap_or(false) as AIBoolean
+}
+
+unsafe extern "C" fn delete_entry(dictionary: AIDictionaryRef, key: AIDictKey) -> AIErr {
+    let removed = with_key(dictionary, key, |map, key| map.remove(&key).ok_or(NO_SUCH_KEY));
+    status(removed.map(drop))
+}
+
+unsafe extern "C" fn get_entry_type(dictionary: ConstAIDictionaryRef, key: AIDictKey, entry_type: *mut AIEntryType) -> AIErr {
+    write(entry_type, lookup(dictionary, key, |value| Some(value.entry_type())))
+}
+
+fn art_index(value: &Value) -> Option<usize> {
+    match value {
+        Value::Art(index) => Some(*index),
+        _ => None,
+    }
+}
+
+unsafe extern "C" fn get_art_entry(dictionary: ConstAIDictionaryRef, key: AIDictKey, art: *mut AIArtHandle) -> AIErr {
+    write(art, lookup(dictionary, key, art_index).map(art_handle))
+}
+
+/// アートをツリーから外す（レイヤーのグループは外せない）
+fn detach_art(art: AIArtHandle) -> Result<usize, AIErr> {
+    with_art(art, |state, index| {
+        if state.art.nodes[index].layer_group {
+            return Err(kUntouchableLayerErr as AIErr);
+        }
+        state.art.detach(index);
+        Ok(index)
+    })
+}
+
+unsafe extern "C" fn move_art_to_entry(dictionary: AIDictionaryRef, key: AIDictKey, art: AIArtHandle) -> AIErr {
+    if object::<Map, _>(dictionary).is_none() {
+        return BAD_PARAMETER;
+    }
+    match detach_art(art) {
+        Ok(index) => insert(dictionary, key, Value::Art(index)),
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn copy_art_to_entry(dictionary: AIDictionaryRef, key: AIDictKey, art: AIArtHandle) -> AIErr {
+    if object::<Map, _>(dictionary).is_none() {
+        return BAD_PARAMETER;
+    }
+    match with_art(art, |state, index| Ok(state.art.duplicate(index))) {
+        Ok(copy) => insert(dictionary, key, Value::Art(copy)),
+        Err(err) => err,
+    }
+}
+
+/// 格納されたアート（または複製）を `paint_order` と `prep` が示す位置へ置く
+unsafe fn place_entry(
+    dictionary: ConstAIDictionaryRef,
+    key: AIDictKey,
+    paint_order: ai_int16,
+    prep: AIArtHandle,
+    duplicate: bool,
+) -> Result<usize, AIErr> {
+    let index = lookup(dictionary, key, art_index)?;
+
+    with_state(|state| {
+        let prep = prep_index(&state.art, prep)?;
+        let index = if duplicate { state.art.duplicate(index) } else { index };
+
+        if let Err(err) = state.art.place(index, paint_order, prep) {
+            if duplicate {
+                state.art.dispose(index);
+            }
+            return Err(err);
+        }
+        Ok(index)
+    })
+}
+
+unsafe extern "C" fn move_entry_to_art(
+    dictionary: AIDictionaryRef,
+    key: AIDictKey,
+    paint_order: ai_int16,
+    prep: AIArtHandle,
+    art: *mut AIArtHandle,
+) -> AIErr {
+    let result = place_entry(dictionary, key, paint_order, prep, false).and_then(|index| {
+        with_key(dictionary, key, |map, key| {
+            map.remove(&key);
+            Ok(index)
+        })
+    });
+    write(art, result.map(art_handle))
+}
+
+unsafe extern "C" fn copy_entry_to_art(
+    dictionary: ConstAIDictionaryRef,
+    key: AIDictKey,
+    paint_order: ai_int16,
+    prep: AIArtHandle,
+    art: *mut AIArtHandle,
+) -> AIErr {
+    write(art, place_entry(dictionary, key, paint_order, prep, true).map(art_handle))
+}
+
+fn new_entry(value: Value) -> AIEntryRef {
+    create::<Value, _>(value)
+}
+
+unsafe extern "C" fn get(dictionary: ConstAIDictionaryRef, key: AIDictKey) -> AIEntryRef {
+    with_key(dictionary, key, |map, key| Ok(map.get(&key).cloned()))
+        .ok()
+        .flatten()
+        .map_or(null_mut(), new_entry)
+}
+
+unsafe extern "C" fn set(dictionary: AIDictionaryRef, key: AIDictKey, entry: AIEntryRef) -> AIErr {
+    match object::<Value, _>(entry) {
+        Some(entry) => insert(dictionary, key, entry.value.clone()),
+        None => BAD_PARAMETER,
+    }
+}
+
+unsafe extern "C" fn get_boolean_entry(dictionary: ConstAIDictionaryRef, key: AIDictKey, value: *mut AIBoolean) -> AIErr {
+    let result = lookup(dictionary, key, |value| match value {
+        Value::Boolean(value) => Some(*value as AIBoolean),
+        _ => None,
+    });
+    write(value, result)
+}
+
+unsafe extern "C" fn set_boolean_entry(dictionary: AIDictionaryRef, key: AIDictKey, value: AIBoolean) -> AIErr {
+    insert(dictionary, key, Value::Boolean(value != 0))
+}
+
+unsafe extern "C" fn get_integer_entry(dictionary: ConstAIDictionaryRef, key: AIDictKey, value: *mut ai_int32) -> AIErr {
+    let result = lookup(dictionary, key, |value| match value {
+        Value::Integer(value) => Some(*value),
+        _ => None,
+    });
+    write(value, result)
+}
+
+unsafe extern "C" fn set_integer_entry(dictionary: AIDictionaryRef, key: AIDictKey, value: ai_int32) -> AIErr {
+    insert(dictionary, key, Value::Integer(value))
+}
+
+unsafe extern "C" fn get_real_entry(dictionary: ConstAIDictionaryRef, key: AIDictKey, value: *mut AIReal) -> AIErr {
+    let result = lookup(dictionary, key, |value| match value {
+        Value::Real(value) => Some(*value),
+        _ => None,
+    });
+    write(value, result)
+}
+
+unsafe extern "C" fn set_real_entry(dictionary: AIDictionaryRef, key: AIDictKey, value: AIReal) -> AIErr {
+    insert(dictionary, key, Value::Real(value))
+}
+
+/// 返すポインタはエントリが置き換えられるまで有効
+unsafe extern "C" fn get_string_entry(dictionary: ConstAIDictionaryRef, key: AIDictKey, value: *mut *const c_char) -> AIErr {
+    let result = lookup(dictionary, key, |value| match value {
+        Value::String(value) => Some(value.as_ptr()),
+        _ => None,
+    });
+    write(value, result)
+}
+
+unsafe extern "C" fn set_string_entry(dictionary: AIDictionaryRef, key: AIDictKey, value: *const c_char) -> AIErr {
+    if value.is_null() {
+        return BAD_PARAMETER;
+    }
+    insert(dictionary, key, Value::String(CStr::from_ptr(value).to_owned()))
+}
+
+fn binary(value: &Value) -> Option<Vec<u8>> {
+    match value {
+        Value::Binary(bytes) => Some(bytes.clone()),
+        _ => None,
+    }
+}
+
+/// `value` が `null` なら大きさだけを書き込み、そうでなければ `size` バイトまでを写す
+unsafe fn copy_binary(bytes: &[u8], value: *mut c_void, size: *mut usize) -> AIErr {
+    if size.is_null() {
+        return BAD_PARAMETER;
+    }
+    if !value.is_null() {
+        let count = bytes.len().min(*size);
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), value as *mut u8, count);
+    }
+    *size = bytes.len();
+    NO_ERR
+}
+
+unsafe extern "C" fn get_binary_entry(dictionary: ConstAIDictionaryRef, key: AIDictKey, value: *mut c_void, size: *mut usize) -> AIErr {
+    match lookup(dictionary, key, binary) {
+        Ok(bytes) => copy_binary(&bytes, value, size),
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn set_binary_entry(dictionary: AIDictionaryRef, key: AIDictKey, value: *mut c_void, size: usize) -> AIErr {
+    if value.is_null() && size > 0 {
+        return BAD_PARAMETER;
+    }
+    let bytes = if size == 0 { Vec::new() } else { std::slice::from_raw_parts(value as *const u8, size).to_vec() };
+    insert(dictionary, key, Value::Binary(bytes))
+}
+
+unsafe extern "C" fn get_dict_entry(dictionary: ConstAIDictionaryRef, key: AIDictKey, value: *mut AIDictionaryRef) -> AIErr {
+    let result = lookup(dictionary, key, |value| match value {
+        Value::Dictionary(dictionary) => Some(retain_dictionary(*dictionary)),
+        _ => None,
+    });
+    write(value, result)
+}
+
+unsafe extern "C" fn set_dict_entry(dictionary: AIDictionaryRef, key: AIDictKey, value: AIDictionaryRef) -> AIErr {
+    if object::<Map, _>(value).is_none() || value == dictionary {
+        return BAD_PARAMETER;
+    }
+    insert(dictionary, key, Value::Dictionary(retain_dictionary(value)))
+}
+
+unsafe extern "C" fn get_array_entry(dictionary: ConstAIDictionaryRef, key: AIDictKey, value: *mut AIArrayRef) -> AIErr {
+    let result = lookup(dictionary, key, |value| match value {
+        Value::Array(array) => {
+            add_ref::<Vec<Value>, _>(*array);
+            Some(*array)
+        }
+        _ => None,
+    });
+    write(value, result)
+}
+
+unsafe extern "C" fn set_array_entry(dictionary: AIDictionaryRef, key: AIDictKey, value: AIArrayRef) -> AIErr {
+    if object::<Vec<Value>, _>(value).is_none() {
+        return BAD_PARAMETER;
+    }
+    add_ref::<Vec<Value>, _>(value);
+    insert(dictionary, key, Value::Array(value))
+}
+
+unsafe extern "C" fn get_unicode_string_entry(dictionary: ConstAIDictionaryRef, key: AIDictKey, value: *mut ai_UnicodeString) -> AIErr {
+    let result = lookup(dictionary, key, |value| match value {
+        Value::Unicode(text) => Some(text.clone()),
+        _ => None,
+    });
+    status(result.map(|text| unicode::write(value, &text)))
+}
+
+unsafe extern "C" fn set_unicode_string_entry(dictionary: AIDictionaryRef, key: AIDictKey, value: *const ai_UnicodeString) -> AIErr {
+    if value.is_null() {
+        return BAD_PARAMETER;
+    }
+    insert(dictionary, key, Value::Unicode(unicode::read(value)))
+}
+
+/// `AIEntrySuite` のスタンドイン
+///
+/// SDK と同じく `To*` は呼び出しのたびにエントリの参照を一つ解放する。
+pub(crate) fn entry_suite() -> AIEntrySuite {
+    let mut suite: AIEntrySuite = unsafe { std::mem::zeroed() };
+    suite.AddRef = Some(entry_add_ref);
+    suite.Release = Some(entry_release);
+    suite.GetType = Some(get_type);
+    suite.ToBoolean = Some(to_boolean);
+    suite.FromBoolean = Some(from_boolean);
+    suite.ToInteger = Some(to_integer);
+    suite.FromInteger = Some(from_integer);
+    suite.ToReal = Some(to_real);
+    suite.FromReal = Some(from_real);
+    suite.ToString = Some(to_string);
+    suite.FromString = Some(from_string);
+    suite.ToDict = Some(to_dict);
+    suite.FromDict = Some(from_dict);
+    suite.ToArt = Some(to_art);
+    suite.ToArray = Some(to_array);
+    suite.FromArray = Some(from_array);
+    suite.ToBinary = Some(to_binary);
+    suite.FromBinary = Some(from_binary);
+    suite.ToUnicodeString = Some(to_unicode_string);
+    suite.FromUnicodeString = Some(from_unicode_string);
+    suite
+}
+
+unsafe extern "C" fn entry_add_ref(entry: AIEntryRef) -> ai_int32 {
+    add_ref::<Value, _>(entry)
+}
+
+unsafe extern "C" fn entry_release(entry: AIEntryRef) -> ai_int32 {
+    release::<Value, _>(entry)
+}
+
+unsafe extern "C" fn get_type(entry: AIEntryRef) -> AIEntryType {
+    object::<Value, _>(entry).map_or(0, |entry| entry.value.entry_type())
+}
+
+/// 値を読んでから参照を一つ解放する（種類が違えば `kBadParameterErr`）
+unsafe fn consume<R>(entry: AIEntryRef, f: impl FnOnce(&Value) -> Option<R>) -> Result<R, AIErr> {
+    let result = object::<Value, _>(entry)
+        .ok_or(BAD_PARAMETER)
+        .and_then(|entry| f(&entry.value).ok_or(BAD_PARAMETER));
+    release::<Value, _>(entry);
+    result
+}
+
+unsafe extern "C" fn to_boolean(entry: AIEntryRef, value: *mut ASBoolean) -> AIErr {
+    let result = consume(entry, |value| match value {
+        Value::Boolean(value) => Some(*value as ASBoolean),
+        _ => None,
+    });
+    write(value, result)
+}
+
+unsafe extern "C" fn from_boolean(value: ASBoolean) -> AIEntryRef {
+    new_entry(Value::Boolean(value != 0))
+}
+
+unsafe extern "C" fn to_integer(entry: AIEntryRef, value: *mut ai_int32) -> AIErr {
+    let result = consume(entry, |value| match value {
+        Value::Integer(value) => Some(*value),
+        _ => None,
+    });
+    write(value, result)
+}
+
+unsafe extern "C" fn from_integer(value: ai_int32) -> AIEntryRef {
+    new_entry(Value::Integer(value))
+}
+
+unsafe extern "C" fn to_real(entry: AIEntryRef, value: *mut AIReal) -> AIErr {
+    let result = consume(entry, |value| match value {
+        Value::Real(value) => Some(*value),
+        _ => None,
+    });
+    write(value, result)
+}
+
+unsafe extern "C" fn from_real(value: AIReal) -> AIEntryRef {
+    new_entry(Value::Real(value))
+}
+
+/// 返すポインタはエントリが解放されるまで有効
+unsafe extern "C" fn to_string(entry: AIEntryRef, value: *mut *const c_char) -> AIErr {
+    let result = consume(entry, |value| match value {
+        Value::String(value) => Some(value.as_ptr()),
+        _ => None,
+    });
+    write(value, result)
+}
+
+unsafe extern "C" fn from_string(value: *const c_char) -> AIEntryRef {
+    if value.is_null() {
+        return null_mut();
+    }
+    new_entry(Value::String(CStr::from_ptr(value).to_owned()))
+}
+
+unsafe extern "C" fn to_dict(entry: AIEntryRef, value: *mut AIDictionaryRef) -> AIErr {
+    let result = consume(entry, |value| match value {
+        Value::Dictionary(dictionary) => Some(retain_dictionary(*dictionary)),
+        _ => None,
+    });
+    write(value, result)
+}
+
+unsafe extern "C" fn from_dict(value: AIDictionaryRef) -> AIEntryRef {
+    if object::<Map, _>(value).is_none() {
+        return null_mut();
+    }
+    new_entry(Value::Dictionary(retain_dictionary(value)))
+}
+
+unsafe extern "C" fn to_art(entry: AIEntryRef, art: *mut AIArtHandle) -> AIErr {
+    write(art, consume(entry, art_index).map(art_handle))
+}
+
+unsafe extern "C" fn to_array(entry: AIEntryRef, value: *mut AIArrayRef) -> AIErr {
+    let result = consume(entry, |value| match value {
+        Value::Array(array) => {
+            add_ref::<Vec<Value>, _>(*array);
+            Some(*array)
+        }
+        _ => None,
+    });
+    write(value, result)
+}
+
+unsafe extern "C" fn from_array(value: AIArrayRef) -> AIEntryRef {
+    if object::<Vec<Value>, _>(value).is_none() {
+        return null_mut();
+    }
+    add_ref::<Vec<Value>, _>(value);
+    new_entry(Value::Array(value))
+}
+
+unsafe extern "C" fn to_binary(entry: AIEntryRef, value: *mut c_void, size: *mut usize) -> AIErr {
+    match consume(entry, binary) {
+        Ok(bytes) => copy_binary(&bytes, value, size),
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn from_binary(value: *mut c_void, size: usize) -> AIEntryRef {
+    if value.is_null() && size > 0 {
+        return null_mut();
+    }
+    let bytes = if size == 0 { Vec::new() } else { std::slice::from_raw_parts(value as *const u8, size).to_vec() };
+    new_entry(Value::Binary(bytes))
+}
+
+unsafe extern "C" fn to_unicode_string(entry: AIEntryRef, value: *mut ai_UnicodeString) -> AIErr {
+    let result = consume(entry, |value| match value {
+        Value::Unicode(text) => Some(text.clone()),
+        _ => None,
+    });
+    status(result.map(|text| unicode::write(value, &text)))
+}
+
+unsafe extern "C" fn from_unicode_string(value: *const ai_UnicodeString) -> AIEntryRef {
+    if value.is_null() {
+        return null_mut();
+    }
+    new_entry(Value::Unicode(unicode::read(value)))
+}
+
+/// `AIDictionaryIteratorSuite` のスタンドイン
+///
+/// キーは昇順に並び、`Begin` 以降に追加されたキーは列挙されない。
+pub(crate) fn iterator_suite() -> AIDictionaryIteratorSuite {
+    let mut suite: AIDictionaryIteratorSuite = unsafe { std::mem::zeroed() };
+    suite.AddRef = Some(iterator_add_ref);
+    suite.Release = Some(iterator_release);
+    suite.IsValid = Some(is_valid);
+    suite.AtEnd = Some(at_end);
+    suite.Next = Some(next);
+    suite.Prev = Some(prev);
+    suite.GetKey = Some(get_key);
+    suite.GetEntry = Some(get_entry);
+    suite
+}
+
+unsafe extern "C" fn iterator_add_ref(iterator: AIDictionaryIterator) -> ai_int32 {
+    add_ref::<Cursor, _>(iterator)
+}
+
+unsafe extern "C" fn iterator_release(iterator: AIDictionaryIterator) -> ai_int32 {
+    release::<Cursor, _>(iterator)
+}
+
+unsafe fn cursor<'a>(iterator: AIDictionaryIterator) -> Option<&'a mut Cursor> {
+    object::<Cursor, _>(iterator).map(|cursor| &mut cursor.value)
+}
+
+unsafe extern "C" fn is_valid(iterator: AIDictionaryIterator) -> AIBoolean {
+    cursor(iterator).is_some() as AIBoolean
+}
+
+unsafe extern "C" fn at_end(iterator: AIDictionaryIterator) -> AIBoolean {
+    cursor(iterator).map_or(true, |cursor| cursor.position >= cursor.keys.len()) as AIBoolean
+}
+
+unsafe extern "C" fn next(iterator: AIDictionaryIterator) {
+    if let Some(cursor) = cursor(iterator) {
+        cursor.position = (cursor.position + 1).min(cursor.keys.len());
+    }
+}
+
+unsafe extern "C" fn prev(iterator: AIDictionaryIterator) {
+    if let Some(cursor) = cursor(iterator) {
+        cursor.position = cursor.position.saturating_sub(1);
+    }
+}
+
+unsafe extern "C" fn get_key(iterator: AIDictionaryIterator) -> AIDictKey {
+    let name = cursor(iterator).and_then(|cursor| cursor.keys.get(cursor.position).cloned());
+    match name.and_then(|name| CString::new(name).ok()) {
+        Some(name) => intern(&name),
+        None => null(),
+    }
+}
+
+/// キーが削除されていれば `null`
+unsafe extern "C" fn get_entry(iterator: AIDictionaryIterator) -> AIEntryRef {
+    let Some(cursor) = cursor(iterator) else {
+        return null_mut();
+    };
+    let value = cursor
+        .keys
+        .get(cursor.position)
+        .and_then(|key| object::<Map, _>(cursor.dictionary)?.value.get(key).cloned());
+    value.map_or(null_mut(), new_entry)
+}
+
+/// `AIArraySuite` のスタンドイン
+pub(crate) fn array_suite() -> AIArraySuite {
+    let mut suite: AIArraySuite = unsafe { std::mem::zeroed() };
+    suite.CreateArray = Some(create_array);
+    suite.AddRef = Some(array_add_ref);
+    suite.Release = Some(array_release);
+    suite.Size = Some(array_size);
+    suite.DeleteEntry = Some(array_delete_entry);
+    suite.GetEntryType = Some(array_get_entry_type);
+    suite.Get = Some(array_get);
+    suite.Set = Some(array_set);
+    suite.AppendEntry = Some(append_entry);
+    suite
+}
+
+unsafe fn with_array<R>(array: AIArrayRef, f: impl FnOnce(&mut Vec<Value>) -> Result<R, AIErr>) -> Result<R, AIErr> {
+    f(&mut object::<Vec<Value>, _>(array).ok_or(BAD_PARAMETER)?.value)
+}
+
+fn slot(values: &mut [Value], index: ai_int32) -> Result<&mut Value, AIErr> {
+    usize::try_from(index)
+        .ok()
+        .and_then(|index| values.get_mut(index))
+        .ok_or(BAD_PARAMETER)
+}
+
+unsafe extern "C" fn create_array(array: *mut AIArrayRef) -> AIErr {
+    write(array, Ok(create::<Vec<Value>, _>(Vec::new())))
+}
+
+unsafe extern "C" fn array_add_ref(array: AIArrayRef) -> ai_int32 {
+    add_ref::<Vec<Value>, _>(array)
+}
+
+unsafe extern "C" fn array_release(array: AIArrayRef) -> ai_int32 {
+    release::<Vec<Value>, _>(array)
+}
+
+unsafe extern "C" fn array_size(array: AIArrayRef) -> ai_int32 {
+    with_array(array, |values| Ok(values.len() as ai_int32)).unwrap_or(0)
+}
+
+unsafe extern "C" fn array_delete_entry(array: AIArrayRef, i: ai_int32) -> AIErr {
+    status(with_array(array, |values| {
+        slot(values, i)?;
+        values.remove(i as usize);
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn array_get_entry_type(array: AIArrayRef, i: ai_int32, entry_type: *mut AIEntryType) -> AIErr {
+    write(entry_type, with_array(array, |values| Ok(slot(values, i)?.entry_type())))
+}
+
+unsafe extern "C" fn array_get(array: AIArrayRef, i: ai_int32) -> AIEntryRef {
+    with_array(array, |values| Ok(slot(values, i)?.clone())).map_or(null_mut(), new_entry)
+}
+
+unsafe extern "C" fn array_set(array: AIArrayRef, i: ai_int32, entry: AIEntryRef) -> AIErr {
+    let Some(entry) = object::<Value, _>(entry) else {
+        return BAD_PARAMETER;
+    };
+    status(with_array(array, |values| {
+        *slot(values, i)? = entry.value.clone();
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn append_entry(array: AIArrayRef, entry: AIEntryRef) -> AIErr {
+    let Some(entry) = object::<Value, _>(entry) else {
+        return BAD_PARAMETER;
+    };
+    status(with_array(array, |values| {
+        values.push(entry.value.clone());
+        Ok(())
+    }))
+}
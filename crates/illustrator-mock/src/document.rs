@@ -0,0 +1,443 @@
+use illustrator_sys::*;
+
+use crate::art::{status, BAD_PARAMETER};
+use crate::artboard::ArtboardEntry;
+use crate::dictionary;
+use crate::state::{with_state, HostEvent, HostState, NO_ERR};
+use crate::unicode;
+
+const DOCUMENT_HANDLE_BASE: usize = 0x30_0000;
+const VIEW_HANDLE_BASE: usize = 0x40_0000;
+const HANDLE_STRIDE: usize = 16;
+
+const NO_DOCUMENT: AIErr = kNoDocumentErr as AIErr;
+
+/// ドキュメントウィンドウ
+///
+/// ビュー座標はウィンドウ左上が原点で y 軸が下向き。
+#[derive(Clone, Copy)]
+pub(crate) struct ViewEntry {
+    pub center: AIRealPoint,
+    pub zoom: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl ViewEntry {
+    pub fn new() -> Self {
+        Self {
+            center: AIRealPoint { h: 306.0, v: 396.0 },
+            zoom: 1.0,
+            width: 800.0,
+            height: 600.0,
+        }
+    }
+
+    pub fn bounds(&self) -> AIRealRect {
+        let (half_width, half_height) = (self.width / 2.0 / self.zoom, self.height / 2.0 / self.zoom);
+        AIRealRect {
+            left: self.center.h - half_width,
+            top: self.center.v + half_height,
+            right: self.center.h + half_width,
+            bottom: self.center.v - half_height,
+        }
+    }
+
+    pub fn artwork_to_view(&self, point: AIRealPoint) -> AIRealPoint {
+        AIRealPoint {
+            h: (point.h - self.center.h) * self.zoom + self.width / 2.0,
+            v: (self.center.v - point.v) * self.zoom + self.height / 2.0,
+        }
+    }
+
+    pub fn view_to_artwork(&self, point: AIRealPoint) -> AIRealPoint {
+        AIRealPoint {
+            h: (point.h - self.width / 2.0) / self.zoom + self.center.h,
+            v: self.center.v - (point.v - self.height / 2.0) / self.zoom,
+        }
+    }
+}
+
+/// アクティブなドキュメント
+pub(crate) struct DocumentEntry {
+    /// 開くたびに変わる番号（ハンドルの元になる）
+    pub serial: usize,
+    pub file_path: Option<String>,
+    pub file_name: String,
+    pub modified: bool,
+    pub color_model: i16,
+    pub ruler_units: i16,
+    pub ruler_origin: AIRealPoint,
+    pub setup: AIDocumentSetup,
+    pub views: Vec<ViewEntry>,
+    pub artboards: Vec<ArtboardEntry>,
+    pub active_artboard: usize,
+    pub dictionary: AIDictionaryRef,
+    pub non_recorded_dictionary: AIDictionaryRef,
+}
+
+impl DocumentEntry {
+    /// レターサイズ・RGB・ポイント単位の新規ドキュメント
+    pub fn new(serial: usize, file_path: Option<&str>) -> Self {
+        let file_name = match file_path {
+            Some(path) => file_name(path).to_owned(),
+            None => format!("Untitled-{}", serial + 1),
+        };
+
+        Self {
+            serial,
+            file_path: file_path.map(str::to_owned),
+            file_name,
+            modified: false,
+            color_model: AIDocumentColorModelValue_kDocRGBColor as i16,
+            ruler_units: AIDocumentRulerUnitValue_kPointsUnits as i16,
+            ruler_origin: AIRealPoint { h: 0.0, v: 792.0 },
+            setup: AIDocumentSetup {
+                width: 612.0,
+                height: 792.0,
+                showPlacedImages: 1,
+                outputResolution: 800.0,
+                splitLongPaths: 0,
+                useDefaultScreen: 1,
+                compatibleGradients: 0,
+                printTiles: 0,
+                tileFullPages: 0,
+            },
+            views: vec![ViewEntry::new()],
+            artboards: vec![ArtboardEntry::named(
+                "Artboard 1",
+                AIRealRect { left: 0.0, top: 792.0, right: 612.0, bottom: 0.0 },
+            )],
+            active_artboard: 0,
+            dictionary: dictionary::new_dictionary(),
+            non_recorded_dictionary: dictionary::new_dictionary(),
+        }
+    }
+
+    /// 保存先を変える（ファイル名も合わせて変わる）
+    pub fn set_file_path(&mut self, path: &str) {
+        self.file_path = Some(path.to_owned());
+        self.file_name = file_name(path).to_owned();
+    }
+
+    pub fn handle(&self) -> AIDocumentHandle {
+        (DOCUMENT_HANDLE_BASE + self.serial * HANDLE_STRIDE) as AIDocumentHandle
+    }
+}
+
+impl Drop for DocumentEntry {
+    fn drop(&mut self) {
+        unsafe {
+            dictionary::release_dictionary(self.dictionary);
+            dictionary::release_dictionary(self.non_recorded_dictionary);
+        }
+    }
+}
+
+/// パスの最後の要素
+pub(crate) fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+pub(crate) fn view_handle(index: usize) -> AIDocumentViewHandle {
+    (VIEW_HANDLE_BASE + index * HANDLE_STRIDE) as AIDocumentViewHandle
+}
+
+/// `null` はアクティブなビュー（0 番目）
+fn view_index(document: &DocumentEntry, view: AIDocumentViewHandle) -> Result<usize, AIErr> {
+    if view.is_null() {
+        return Ok(0);
+    }
+
+    let offset = (view as usize).checked_sub(VIEW_HANDLE_BASE).ok_or(BAD_PARAMETER)?;
+    let index = offset / HANDLE_STRIDE;
+    if offset % HANDLE_STRIDE != 0 || index >= document.views.len() {
+        return Err(BAD_PARAMETER);
+    }
+    Ok(index)
+}
+
+fn with_document<R>(f: impl FnOnce(&mut DocumentEntry) -> Result<R, AIErr>) -> Result<R, AIErr> {
+    with_state(|state| f(state.document.as_mut().ok_or(NO_DOCUMENT)?))
+}
+
+/// `document` がアクティブなドキュメントのときだけ `f` を呼ぶ
+fn with_handle<R>(document: AIDocumentHandle, f: impl FnOnce(&mut DocumentEntry) -> Result<R, AIErr>) -> Result<R, AIErr> {
+    with_document(|entry| {
+        if entry.handle() != document {
+            return Err(BAD_PARAMETER);
+        }
+        f(entry)
+    })
+}
+
+fn with_view<R>(view: AIDocumentViewHandle, f: impl FnOnce(&mut ViewEntry) -> Result<R, AIErr>) -> Result<R, AIErr> {
+    with_document(|entry| {
+        let index = view_index(entry, view)?;
+        f(&mut entry.views[index])
+    })
+}
+
+/// 成功したら `out` に書き込む
+unsafe fn write<T>(out: *mut T, result: Result<T, AIErr>) -> AIErr {
+    match result {
+        Ok(value) => {
+            if out.is_null() {
+                return BAD_PARAMETER;
+            }
+            *out = value;
+            NO_ERR
+        }
+        Err(err) => err,
+    }
+}
+
+/// `AIDocumentSuite` のスタンドイン
+///
+/// ホストは一度に一つのドキュメントだけを開いている。
+pub(crate) fn suite() -> AIDocumentSuite {
+    let mut suite: AIDocumentSuite = unsafe { std::mem::zeroed() };
+    suite.GetDocument = Some(get_document);
+    suite.DocumentExists = Some(document_exists);
+    suite.GetDocumentFileSpecification = Some(get_document_file_specification);
+    suite.GetDocumentFileSpecificationFromHandle = Some(get_document_file_specification_from_handle);
+    suite.GetDocumentFileName = Some(get_document_file_name);
+    suite.GetDocumentFileNameFromHandle = Some(get_document_file_name_from_handle);
+    suite.GetDocumentModified = Some(get_document_modified);
+    suite.SetDocumentModified = Some(set_document_modified);
+    suite.GetDocumentColorModel = Some(get_document_color_model);
+    suite.SetDocumentColorModel = Some(set_document_color_model);
+    suite.GetDocumentRulerUnits = Some(get_document_ruler_units);
+    suite.SetDocumentRulerUnits = Some(set_document_ruler_units);
+    suite.GetDocumentRulerOrigin = Some(get_document_ruler_origin);
+    suite.SetDocumentRulerOrigin = Some(set_document_ruler_origin);
+    suite.GetDocumentSetup = Some(get_document_setup);
+    suite.SetDocumentSetup = Some(set_document_setup);
+    suite.RedrawDocument = Some(redraw_document);
+    suite.GetDictionary = Some(get_dictionary);
+    suite.GetNonRecordedDictionary = Some(get_non_recorded_dictionary);
+    suite
+}
+
+unsafe extern "C" fn get_document(document: *mut AIDocumentHandle) -> AIErr {
+    write(document, with_document(|entry| Ok(entry.handle())))
+}
+
+unsafe extern "C" fn document_exists(document: AIDocumentHandle, exists: *mut AIBoolean) -> AIErr {
+    let open = with_state(|state| state.document.as_ref().is_some_and(|entry| entry.handle() == document));
+    write(exists, Ok(open as AIBoolean))
+}
+
+unsafe fn file_specification(file: *mut ai_FilePath, entry: &DocumentEntry) -> Result<(), AIErr> {
+    crate::file_path::set(file, entry.file_path.as_deref().unwrap_or(""));
+    Ok(())
+}
+
+unsafe extern "C" fn get_document_file_specification(file: *mut ai_FilePath) -> AIErr {
+    status(with_document(|entry| file_specification(file, entry)))
+}
+
+unsafe extern "C" fn get_document_file_specification_from_handle(document: AIDocumentHandle, file: *mut ai_FilePath) -> AIErr {
+    status(with_handle(document, |entry| file_specification(file, entry)))
+}
+
+unsafe extern "C" fn get_document_file_name(name: *mut ai_UnicodeString) -> AIErr {
+    status(with_document(|entry| {
+        unicode::write(name, &entry.file_name);
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn get_document_file_name_from_handle(document: AIDocumentHandle, name: *mut ai_UnicodeString) -> AIErr {
+    status(with_handle(document, |entry| {
+        unicode::write(name, &entry.file_name);
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn get_document_modified(modified: *mut AIBoolean) -> AIErr {
+    write(modified, with_document(|entry| Ok(entry.modified as AIBoolean)))
+}
+
+unsafe extern "C" fn set_document_modified(modified: AIBoolean) -> AIErr {
+    status(with_document(|entry| {
+        entry.modified = modified != 0;
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn get_document_color_model(color_model: *mut ai_int16) -> AIErr {
+    write(color_model, with_document(|entry| Ok(entry.color_model)))
+}
+
+unsafe extern "C" fn set_document_color_model(color_model: ai_int16) -> AIErr {
+    let known = [
+        AIDocumentColorModelValue_kDocGrayColor,
+        AIDocumentColorModelValue_kDocRGBColor,
+        AIDocumentColorModelValue_kDocCMYKColor,
+    ];
+    if !known.contains(&(color_model as AIDocumentColorModelValue)) {
+        return BAD_PARAMETER;
+    }
+
+    status(with_document(|entry| {
+        entry.color_model = color_model;
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn get_document_ruler_units(units: *mut ai_int16) -> AIErr {
+    write(units, with_document(|entry| Ok(entry.ruler_units)))
+}
+
+unsafe extern "C" fn set_document_ruler_units(units: ai_int16) -> AIErr {
+    let valid = AIDocumentRulerUnitValue_kInchesUnits as i16..=AIDocumentRulerUnitValue_kLastUnit as i16;
+    if !valid.contains(&units) {
+        return BAD_PARAMETER;
+    }
+
+    status(with_document(|entry| {
+        entry.ruler_units = units;
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn get_document_ruler_origin(origin: *mut AIRealPoint) -> AIErr {
+    write(origin, with_document(|entry| Ok(entry.ruler_origin)))
+}
+
+unsafe extern "C" fn set_document_ruler_origin(origin: *mut AIRealPoint) -> AIErr {
+    if origin.is_null() {
+        return BAD_PARAMETER;
+    }
+    status(with_document(|entry| {
+        entry.ruler_origin = *origin;
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn get_document_setup(setup: *mut AIDocumentSetup) -> AIErr {
+    write(setup, with_document(|entry| Ok(entry.setup)))
+}
+
+unsafe extern "C" fn set_document_setup(setup: *mut AIDocumentSetup) -> AIErr {
+    if setup.is_null() || (*setup).width <= 0.0 || (*setup).height <= 0.0 {
+        return BAD_PARAMETER;
+    }
+    status(with_document(|entry| {
+        entry.setup = *setup;
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn redraw_document() -> AIErr {
+    status(with_state(|state: &mut HostState| {
+        state.document.as_ref().ok_or(NO_DOCUMENT)?;
+        state.events.push(HostEvent::RedrawDocument);
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn get_dictionary(dictionary: *mut AIDictionaryRef) -> AIErr {
+    write(dictionary, with_document(|entry| Ok(dictionary::retain_dictionary(entry.dictionary))))
+}
+
+unsafe extern "C" fn get_non_recorded_dictionary(dictionary: *mut AIDictionaryRef) -> AIErr {
+    write(
+        dictionary,
+        with_document(|entry| Ok(dictionary::retain_dictionary(entry.non_recorded_dictionary))),
+    )
+}
+
+/// `AIDocumentViewSuite` のスタンドイン
+pub(crate) fn view_suite() -> AIDocumentViewSuite {
+    let mut suite: AIDocumentViewSuite = unsafe { std::mem::zeroed() };
+    suite.CountDocumentViews = Some(count_document_views);
+    suite.GetNthDocumentView = Some(get_nth_document_view);
+    suite.GetDocumentViewDocument = Some(get_document_view_document);
+    suite.GetDocumentViewBounds = Some(get_document_view_bounds);
+    suite.GetDocumentViewCenter = Some(get_document_view_center);
+    suite.SetDocumentViewCenter = Some(set_document_view_center);
+    suite.GetDocumentViewZoom = Some(get_document_view_zoom);
+    suite.SetDocumentViewZoom = Some(set_document_view_zoom);
+    suite.FixedArtworkPointToViewPoint = Some(fixed_artwork_point_to_view_point);
+    suite.FixedViewPointToArtworkPoint = Some(fixed_view_point_to_artwork_point);
+    suite
+}
+
+unsafe extern "C" fn count_document_views(count: *mut ai_int32) -> AIErr {
+    let views = with_state(|state| state.document.as_ref().map_or(0, |entry| entry.views.len()));
+    write(count, Ok(views as ai_int32))
+}
+
+unsafe extern "C" fn get_nth_document_view(n: ai_int32, view: *mut AIDocumentViewHandle) -> AIErr {
+    let result = with_document(|entry| {
+        if n < 0 || n as usize >= entry.views.len() {
+            return Err(BAD_PARAMETER);
+        }
+        Ok(view_handle(n as usize))
+    });
+    write(view, result)
+}
+
+unsafe extern "C" fn get_document_view_document(view: AIDocumentViewHandle, document: *mut AIDocumentHandle) -> AIErr {
+    let result = with_document(|entry| {
+        view_index(entry, view)?;
+        Ok(entry.handle())
+    });
+    write(document, result)
+}
+
+unsafe extern "C" fn get_document_view_bounds(view: AIDocumentViewHandle, bounds: *mut AIRealRect) -> AIErr {
+    write(bounds, with_view(view, |entry| Ok(entry.bounds())))
+}
+
+unsafe extern "C" fn get_document_view_center(view: AIDocumentViewHandle, center: *mut AIRealPoint) -> AIErr {
+    write(center, with_view(view, |entry| Ok(entry.center)))
+}
+
+unsafe extern "C" fn set_document_view_center(view: AIDocumentViewHandle, center: *const AIRealPoint) -> AIErr {
+    if center.is_null() {
+        return BAD_PARAMETER;
+    }
+    status(with_view(view, |entry| {
+        entry.center = *center;
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn get_document_view_zoom(view: AIDocumentViewHandle, zoom: *mut AIReal) -> AIErr {
+    write(zoom, with_view(view, |entry| Ok(entry.zoom)))
+}
+
+unsafe extern "C" fn set_document_view_zoom(view: AIDocumentViewHandle, zoom: AIReal) -> AIErr {
+    // Illustrator の表示倍率の範囲（3.13% 〜 64000%）
+    let zoom = zoom.clamp(0.0313, 640.0);
+    status(with_view(view, |entry| {
+        entry.zoom = zoom;
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn fixed_artwork_point_to_view_point(
+    view: AIDocumentViewHandle,
+    artwork_point: *const AIRealPoint,
+    view_point: *mut AIRealPoint,
+) -> AIErr {
+    if artwork_point.is_null() {
+        return BAD_PARAMETER;
+    }
+    write(view_point, with_view(view, |entry| Ok(entry.artwork_to_view(*artwork_point))))
+}
+
+unsafe extern "C" fn fixed_view_point_to_artwork_point(
+    view: AIDocumentViewHandle,
+    view_point: *const AIRealPoint,
+    artwork_point: *mut AIRealPoint,
+) -> AIErr {
+    if view_point.is_null() {
+        return BAD_PARAMETER;
+    }
+    write(artwork_point, with_view(view, |entry| Ok(entry.view_to_artwork(*view_point))))
+}
+
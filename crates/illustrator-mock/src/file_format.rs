@@ -0,0 +1,146 @@
+use std::ffi::{c_char, CStr, CString};
+
+use illustrator_sys::*;
+
+use crate::art::BAD_PARAMETER;
+use crate::state::{lossy, with_state, HostEvent, HostState, NO_ERR};
+use crate::unicode;
+
+/// プラグインが登録したファイルフォーマット
+pub(crate) struct FileFormatEntry {
+    pub name: CString,
+    pub title: String,
+    pub extension: String,
+    pub options: i32,
+    pub priority: i32,
+}
+
+/// `AIFileFormatSuite` のスタンドイン
+///
+/// 開く・保存のダイアログは再現しないので、メッセージは `MockHost::go_file_format` などで送る。
+pub(crate) fn suite() -> AIFileFormatSuite {
+    let mut suite: AIFileFormatSuite = unsafe { std::mem::zeroed() };
+    suite.AddFileFormat = Some(add_file_format);
+    suite.GetFileFormatName = Some(get_file_format_name);
+    suite.GetFileFormatOptions = Some(get_file_format_options);
+    suite.SetFileFormatOptions = Some(set_file_format_options);
+    suite.CountFileFormats = Some(count_file_formats);
+    suite.GetNthFileFormat = Some(get_nth_file_format);
+    suite.GetFileFormatExtension = Some(get_file_format_extension);
+    suite.GetFileFormatTitle = Some(get_file_format_title);
+    suite.SetFileFormatPriority = Some(set_file_format_priority);
+    suite
+}
+
+/// ハンドルは登録順の通し番号から作る
+pub(crate) fn file_format_handle(index: usize) -> AIFileFormatHandle {
+    (0x9000 + index * 8) as AIFileFormatHandle
+}
+
+pub(crate) fn file_format_index(state: &HostState, format: AIFileFormatHandle) -> Option<usize> {
+    let index = (format as usize).checked_sub(0x9000)?;
+    (index % 8 == 0).then_some(index / 8).filter(|&index| index < state.file_formats.len())
+}
+
+/// 有効なフォーマットに対して `f` を呼ぶ（無効なハンドルは `kBadParameterErr`）
+fn with_file_format(format: AIFileFormatHandle, f: impl FnOnce(&mut FileFormatEntry)) -> AIErr {
+    with_state(|state| match file_format_index(state, format) {
+        Some(index) => {
+            f(&mut state.file_formats[index]);
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn add_file_format(
+    _plugin: SPPluginRef,
+    name: *const c_char,
+    data: *mut PlatformAddFileFormatData,
+    options: ai_int32,
+    format: *mut AIFileFormatHandle,
+    _extended_options: ai_int32,
+) -> AIErr {
+    if name.is_null() || data.is_null() {
+        return BAD_PARAMETER;
+    }
+    let entry = FileFormatEntry {
+        name: CStr::from_ptr(name).to_owned(),
+        title: unicode::read(&(*data).title),
+        extension: unicode::read(&(*data).extension),
+        options,
+        priority: 0,
+    };
+
+    with_state(|state| {
+        if state.file_formats.iter().any(|existing| existing.name == entry.name) {
+            return BAD_PARAMETER;
+        }
+
+        let handle = file_format_handle(state.file_formats.len());
+        state.events.push(HostEvent::AddFileFormat(lossy(name)));
+        state.file_formats.push(entry);
+
+        if !format.is_null() {
+            *format = handle;
+        }
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn get_file_format_name(format: AIFileFormatHandle, name: *mut *const c_char) -> AIErr {
+    if name.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_file_format(format, |entry| *name = entry.name.as_ptr())
+}
+
+unsafe extern "C" fn get_file_format_options(format: AIFileFormatHandle, options: *mut ai_int32) -> AIErr {
+    if options.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_file_format(format, |entry| *options = entry.options)
+}
+
+unsafe extern "C" fn set_file_format_options(format: AIFileFormatHandle, options: ai_int32) -> AIErr {
+    with_file_format(format, |entry| entry.options = options)
+}
+
+unsafe extern "C" fn count_file_formats(count: *mut ai_int32) -> AIErr {
+    if count.is_null() {
+        return BAD_PARAMETER;
+    }
+    *count = with_state(|state| state.file_formats.len() as ai_int32);
+    NO_ERR
+}
+
+unsafe extern "C" fn get_nth_file_format(n: ai_int32, format: *mut AIFileFormatHandle) -> AIErr {
+    if format.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_state(|state| match usize::try_from(n).ok().filter(|&n| n < state.file_formats.len()) {
+        Some(index) => {
+            *format = file_format_handle(index);
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn get_file_format_extension(format: AIFileFormatHandle, extension: *mut ai_UnicodeString) -> AIErr {
+    if extension.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_file_format(format, |entry| unicode::write(extension, &entry.extension))
+}
+
+unsafe extern "C" fn get_file_format_title(format: AIFileFormatHandle, title: *mut ai_UnicodeString) -> AIErr {
+    if title.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_file_format(format, |entry| unicode::write(title, &entry.title))
+}
+
+unsafe extern "C" fn set_file_format_priority(format: AIFileFormatHandle, priority: ai_int32) -> AIErr {
+    with_file_format(format, |entry| entry.priority = priority)
+}
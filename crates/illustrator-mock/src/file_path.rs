@@ -0,0 +1,106 @@
+use illustrator_sys::*;
+
+use crate::art::BAD_PARAMETER;
+use crate::document;
+use crate::state::NO_ERR;
+use crate::unicode;
+
+/// `AIFilePathSuite` のスタンドイン
+///
+/// `impl_` にはホスト側で確保したパス文字列（`Box<String>`）を格納する。
+pub(crate) fn suite() -> AIFilePathSuite {
+    let mut suite: AIFilePathSuite = unsafe { std::mem::zeroed() };
+    suite.NewFilePath = Some(new_file_path);
+    suite.DeleteFilePath = Some(delete_file_path);
+    suite.Copy = Some(copy);
+    suite.IsEmpty = Some(is_empty);
+    suite.MakeEmpty = Some(make_empty);
+    suite.Set = Some(set_path);
+    suite.GetFileName = Some(get_file_name);
+    suite.GetFullPath = Some(get_full_path);
+    suite
+}
+
+unsafe fn buffer<'a>(path: *const ai_FilePath) -> Option<&'a mut String> {
+    ((*path).impl_ as *mut String).as_mut()
+}
+
+/// ホストが保持するパスを置き換える（`GetDocumentFileSpecification` などの出力引数用）
+pub(crate) unsafe fn set(path: *mut ai_FilePath, text: &str) {
+    if path.is_null() {
+        return;
+    }
+    match buffer(path) {
+        Some(current) => *current = text.to_owned(),
+        None => (*path).impl_ = Box::into_raw(Box::new(text.to_owned())) as *mut FilePathImpl,
+    }
+}
+
+/// ホストが保持するパスを読み出す
+pub(crate) unsafe fn read(path: *const ai_FilePath) -> String {
+    if path.is_null() {
+        return String::new();
+    }
+    buffer(path).map(|p| p.clone()).unwrap_or_default()
+}
+
+/// ホストが確保したパスを解放する（メッセージに埋め込んだパス用）
+pub(crate) unsafe fn release(path: *mut ai_FilePath) {
+    delete_file_path(path);
+}
+
+unsafe extern "C" fn new_file_path(path: *mut ai_FilePath) -> AIErr {
+    if path.is_null() {
+        return BAD_PARAMETER;
+    }
+    (*path).impl_ = Box::into_raw(Box::new(String::new())) as *mut FilePathImpl;
+    NO_ERR
+}
+
+unsafe extern "C" fn delete_file_path(path: *mut ai_FilePath) -> AIErr {
+    if !path.is_null() && !(*path).impl_.is_null() {
+        drop(Box::from_raw((*path).impl_ as *mut String));
+        (*path).impl_ = std::ptr::null_mut();
+    }
+    NO_ERR
+}
+
+unsafe extern "C" fn copy(src: *const ai_FilePath, dest: *mut ai_FilePath) -> AIErr {
+    if dest.is_null() {
+        return BAD_PARAMETER;
+    }
+    set(dest, &read(src));
+    NO_ERR
+}
+
+unsafe extern "C" fn is_empty(path: *const ai_FilePath) -> AIBool8 {
+    read(path).is_empty() as AIBool8
+}
+
+unsafe extern "C" fn make_empty(path: *mut ai_FilePath) {
+    set(path, "");
+}
+
+unsafe extern "C" fn set_path(
+    path_string: *const ai_UnicodeString,
+    _expand_name: AIBool8,
+    _dont_strip_trailing_space: AIBool8,
+    path: *mut ai_FilePath,
+) -> AIErr {
+    if path.is_null() {
+        return BAD_PARAMETER;
+    }
+    set(path, &unicode::read(path_string));
+    NO_ERR
+}
+
+unsafe extern "C" fn get_file_name(path: *const ai_FilePath, _display_name: AIBool8, file_name: *mut ai_UnicodeString) -> AIErr {
+    let full = read(path);
+    unicode::write(file_name, document::file_name(&full));
+    NO_ERR
+}
+
+unsafe extern "C" fn get_full_path(path: *const ai_FilePath, _display_name: AIBool8, full_path: *mut ai_UnicodeString) -> AIErr {
+    unicode::write(full_path, &read(path));
+    NO_ERR
+}
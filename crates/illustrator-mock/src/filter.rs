@@ -0,0 +1,146 @@
+use std::ffi::{c_char, c_uchar, CStr, CString};
+
+use illustrator_sys::*;
+
+use crate::art::BAD_PARAMETER;
+use crate::state::{lossy, with_state, HostEvent, HostState, NO_ERR};
+
+/// プラグインが登録したフィルター
+pub(crate) struct FilterEntry {
+    pub name: CString,
+    pub category: String,
+    pub title: String,
+    pub options: i32,
+    /// ホストが保持する最後のパラメータ（「フィルターを再実行」で渡される）
+    pub parameters: PlatformFilterParameters,
+}
+
+/// `AIFilterSuite` のスタンドイン
+///
+/// フィルターメニューは再現しないので、メッセージは `MockHost::get_filter_parameters` などで送る。
+pub(crate) fn suite() -> AIFilterSuite {
+    let mut suite: AIFilterSuite = unsafe { std::mem::zeroed() };
+    suite.AddFilter = Some(add_filter);
+    suite.GetFilterName = Some(get_filter_name);
+    suite.GetFilterOptions = Some(get_filter_options);
+    suite.SetFilterOptions = Some(set_filter_options);
+    suite.GetFilterParameters = Some(get_filter_parameters);
+    suite.SetFilterParameters = Some(set_filter_parameters);
+    suite.CountFilters = Some(count_filters);
+    suite.GetNthFilter = Some(get_nth_filter);
+    suite
+}
+
+/// ハンドルは登録順の通し番号から作る
+pub(crate) fn filter_handle(index: usize) -> AIFilterHandle {
+    (0xA000 + index * 8) as AIFilterHandle
+}
+
+pub(crate) fn filter_index(state: &HostState, filter: AIFilterHandle) -> Option<usize> {
+    let index = (filter as usize).checked_sub(0xA000)?;
+    (index % 8 == 0).then_some(index / 8).filter(|&index| index < state.filters.len())
+}
+
+/// 有効なフィルターに対して `f` を呼ぶ（無効なハンドルは `kBadParameterErr`）
+fn with_filter(filter: AIFilterHandle, f: impl FnOnce(&mut FilterEntry)) -> AIErr {
+    with_state(|state| match filter_index(state, filter) {
+        Some(index) => {
+            f(&mut state.filters[index]);
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+/// Pascal 文字列（先頭 1 バイトが長さ）を読む
+unsafe fn read_pascal(text: *const c_uchar) -> String {
+    if text.is_null() {
+        return String::new();
+    }
+    let bytes = std::slice::from_raw_parts(text.add(1), *text as usize);
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+unsafe extern "C" fn add_filter(
+    _plugin: SPPluginRef,
+    name: *mut c_char,
+    data: *mut PlatformAddFilterData,
+    options: ai_int32,
+    filter: *mut AIFilterHandle,
+) -> AIErr {
+    if name.is_null() || data.is_null() {
+        return BAD_PARAMETER;
+    }
+    let entry = FilterEntry {
+        name: CStr::from_ptr(name).to_owned(),
+        category: read_pascal((*data).category),
+        title: read_pascal((*data).title),
+        options,
+        parameters: std::ptr::null_mut(),
+    };
+
+    with_state(|state| {
+        if state.filters.iter().any(|existing| existing.name == entry.name) {
+            return BAD_PARAMETER;
+        }
+
+        let handle = filter_handle(state.filters.len());
+        state.events.push(HostEvent::AddFilter(lossy(name)));
+        state.filters.push(entry);
+
+        if !filter.is_null() {
+            *filter = handle;
+        }
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn get_filter_name(filter: AIFilterHandle, name: *mut *mut c_char) -> AIErr {
+    if name.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_filter(filter, |entry| *name = entry.name.as_ptr() as *mut c_char)
+}
+
+unsafe extern "C" fn get_filter_options(filter: AIFilterHandle, options: *mut ai_int32) -> AIErr {
+    if options.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_filter(filter, |entry| *options = entry.options)
+}
+
+unsafe extern "C" fn set_filter_options(filter: AIFilterHandle, options: ai_int32) -> AIErr {
+    with_filter(filter, |entry| entry.options = options)
+}
+
+unsafe extern "C" fn get_filter_parameters(filter: AIFilterHandle, parameters: *mut PlatformFilterParameters) -> AIErr {
+    if parameters.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_filter(filter, |entry| *parameters = entry.parameters)
+}
+
+unsafe extern "C" fn set_filter_parameters(filter: AIFilterHandle, parameters: PlatformFilterParameters) -> AIErr {
+    with_filter(filter, |entry| entry.parameters = parameters)
+}
+
+unsafe extern "C" fn count_filters(count: *mut ai_int32) -> AIErr {
+    if count.is_null() {
+        return BAD_PARAMETER;
+    }
+    *count = with_state(|state| state.filters.len() as ai_int32);
+    NO_ERR
+}
+
+unsafe extern "C" fn get_nth_filter(n: ai_int32, filter: *mut AIFilterHandle) -> AIErr {
+    if filter.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_state(|state| match usize::try_from(n).ok().filter(|&n| n < state.filters.len()) {
+        Some(index) => {
+            *filter = filter_handle(index);
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
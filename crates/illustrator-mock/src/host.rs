@@ -0,0 +1,1351 @@
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::ptr::null_mut;
+use std::sync::{Mutex, MutexGuard};
+
+use illustrator_rs::{AIPlugin, Plugin};
+use illustrator_sys::*;
+
+use crate::state::{self, cstr, with_state, HostEvent, HostState};
+use crate::document::{self, DocumentEntry};
+use crate::action::{ActionParamInfo, ActionValue, RecordedAction};
+use crate::artboard::ArtboardEntry;
+use crate::file_format;
+use crate::filter;
+use crate::live_effect;
+use crate::menu;
+use crate::plugin_group;
+use crate::timer;
+use crate::tool;
+use crate::undo::{self, UndoContext};
+use crate::preferences::{self, PreferenceValue};
+use crate::{
+    access, action, art, artboard, basic, dictionary, file_path, layer, notifier, path, path_style, plugins, unicode,
+    user,
+};
+
+/// `define_plugin!` が生成する `PluginMain` のシグネチャ
+pub type PluginEntry = unsafe extern "C" fn(*mut c_char, *mut c_char, *mut c_void) -> ASErr;
+
+/// 先頭に `SPMessageData` を持つメッセージ構造体
+pub trait HostMessage {
+    fn data(&mut self) -> &mut SPMessageData;
+}
+
+macro_rules! impl_host_message {
+    ($($message:ty),* $(,)?) => {
+        $(
+            impl HostMessage for $message {
+                fn data(&mut self) -> &mut SPMessageData { &mut self.d }
+            }
+        )*
+    };
+}
+
+impl_host_message!(
+    SPInterfaceMessage,
+    SPAccessMessage,
+    SPPropertiesMessage,
+    SPPurgeCachesMessage,
+    AINotifierMessage,
+    AIMenuMessage,
+    AIToolMessage,
+    AITimerMessage,
+    AIFilterMessage,
+    AIFileFormatMessage,
+    DoActionMessage,
+    AIPluginGroupMessage,
+    AILiveEffectEditParamMessage,
+    AILiveEffectGoMessage,
+    AILiveEffectInterpParamMessage,
+    AILiveEffectInputTypeMessage,
+    AILiveEffectScaleParamMessage,
+    AILiveEffectConvertColorMessage,
+    AILiveEffectAdjustColorsMessage,
+    AILiveEffectHandleMergeMessage,
+    AIClipboardMessage,
+    AIWorkspaceMessage,
+);
+
+impl HostMessage for AIUpdateFileFormatMessage {
+    fn data(&mut self) -> &mut SPMessageData { &mut self.ffm.d }
+}
+
+/// `MockHost::run_in_message` がプラグインに送るメッセージ
+#[repr(C)]
+struct JobMessage {
+    d: SPMessageData,
+    /// `&mut dyn FnMut(&mut T)` を指す
+    job: *mut c_void,
+}
+
+impl HostMessage for JobMessage {
+    fn data(&mut self) -> &mut SPMessageData { &mut self.d }
+}
+
+/// `run_in_message` がプラグインのルーターに登録する caller/selector
+const JOB_CALLER: &CStr = c"Mock Host Caller";
+const JOB_SELECTOR: &CStr = c"Mock Host Run Job";
+
+/// `MockHost::layer_info` が返すレイヤーの状態
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerInfo {
+    pub title: String,
+    pub visible: bool,
+    pub editable: bool,
+    pub printed: bool,
+    pub preview: bool,
+    pub template: bool,
+    pub color: (u16, u16, u16),
+    pub parent: Option<AILayerHandle>,
+}
+
+/// `MockHost::artboards` が返すアートボードの状態
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArtboardInfo {
+    pub name: String,
+    /// (left, top, right, bottom)
+    pub bounds: (f64, f64, f64, f64),
+    pub selected: bool,
+}
+
+/// `MockHost::menu_item_info` が返すメニュー項目の状態
+#[derive(Debug, Clone, PartialEq)]
+pub struct MenuItemInfo {
+    pub key: String,
+    pub group: String,
+    pub text: String,
+    pub options: i32,
+    pub enabled: bool,
+    pub checked: bool,
+}
+
+/// `MockHost::tool_info` が返すツールの状態
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolInfo {
+    pub name: String,
+    pub title: String,
+    pub tooltip: String,
+    pub options: i32,
+    /// `sameGroupAs`・`sameToolsetAs` に渡されたツール番号
+    pub same_group_as: i16,
+    pub same_toolset_as: i16,
+    /// `SetToolIcons` で設定されたアイコンのリソース名（通常・ダーク）
+    pub icons: Option<(String, String)>,
+}
+
+/// `MockHost::live_effect_info` が返すライブエフェクトの状態
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiveEffectInfo {
+    pub name: String,
+    pub title: String,
+    /// （メジャー, マイナー）バージョン
+    pub version: (i32, i32),
+    /// `prefersAsInput` に渡された `AIStyleFilterPreferredInputArtType`
+    pub input_preference: i32,
+    pub style_filter_flags: i32,
+    /// `AddLiveEffectMenuItem` で追加された（カテゴリ, 項目名）
+    pub menu: Option<(String, String)>,
+}
+
+/// `MockHost::plugin_group_info` が返すプラグイングループの状態
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginGroupInfo {
+    pub name: String,
+    pub description: String,
+    /// （メジャー, マイナー）バージョン
+    pub version: (i32, i32),
+    /// `AddAIPluginGroup` に渡された `AIPluginGroupOptions`
+    pub options: i32,
+}
+
+/// `MockHost::file_format_info` が返すファイルフォーマットの状態
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileFormatInfo {
+    pub name: String,
+    pub title: String,
+    /// カンマ区切りの拡張子
+    pub extension: String,
+    /// `AddFileFormat` に渡された `AIFileFormatOptions`
+    pub options: i32,
+    pub priority: i32,
+}
+
+/// `MockHost::filter_info` が返すフィルターの状態
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterInfo {
+    pub name: String,
+    pub category: String,
+    pub title: String,
+    pub options: i32,
+    /// ホストが保持している最後のパラメータがあるか
+    pub has_parameters: bool,
+}
+
+/// `MockHost::action_event` が返すアクションイベントの状態
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionEventInfo {
+    pub name: String,
+    /// アクションパネルに表示される名前
+    pub title: String,
+    pub version: i32,
+    pub flag: i32,
+    /// 登録時の型パラメータブロック（追加順）
+    pub parameters: Vec<ActionParamInfo>,
+}
+
+/// `MockHost::undo_info` が返す、最後に送ったメッセージの取り消し単位
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndoInfo {
+    /// `SetUndoTextUS` で設定された取り消しの名前
+    pub undo_text: Option<String>,
+    pub redo_text: Option<String>,
+    /// `SetSilent` で履歴に残らない操作にされたか
+    pub silent: bool,
+    /// `SetKind` で設定された `AIUndoContextKind`
+    pub kind: i32,
+    /// `UndoChanges` で変更が戻された回数
+    pub reverts: usize,
+}
+
+/// プラグインのグローバル変数は static なので、ホストは同時に一つだけ動かす
+static HOST_LOCK: Mutex<()> = Mutex::new(());
+
+/// Illustrator の代わりにプラグインへメッセージを送るインプロセスホスト
+///
+/// 生成時に現在のスレッドへスイートテーブルを登録し、破棄時に取り除く。
+pub struct MockHost {
+    entry: PluginEntry,
+    plugin_ref: Box<u64>,
+    globals: *mut c_void,
+    basic: Box<SPBasicSuite>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl MockHost {
+    pub fn new(entry: PluginEntry) -> Self {
+        let lock = HOST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut state = HostState::new();
+        state.register_suite(cstr(kSPBasicSuite), kSPBasicSuiteVersion as i32, basic::suite());
+        state.register_suite(cstr(kSPPluginsSuite), kSPPluginsSuiteVersion as i32, plugins::suite());
+        state.register_suite(cstr(kSPAccessSuite), kSPAccessSuiteVersion as i32, access::suite());
+        state.register_suite(cstr(kAINotifierSuite), kAINotifierSuiteVersion as i32, notifier::suite());
+        state.register_suite(cstr(kAIUserSuite), kAIUserSuiteVersion as i32, user::suite());
+        state.register_suite(cstr(kAIUnicodeStringSuite), kAIUnicodeStringSuiteVersion as i32, unicode::suite());
+        state.register_suite(cstr(kAIArtSuite), kAIArtSuiteVersion as i32, art::suite());
+        state.register_suite(cstr(kAIPathSuite), kAIPathSuiteVersion as i32, path::suite());
+        state.register_suite(cstr(kAIPathStyleSuite), kAIPathStyleSuiteVersion as i32, path_style::suite());
+        state.register_suite(cstr(kAILayerSuite), kAILayerSuiteVersion as i32, layer::suite());
+        state.register_suite(cstr(kAILayerListSuite), kAILayerListSuiteVersion as i32, layer::list_suite());
+        state.register_suite(cstr(kAIDocumentSuite), kAIDocumentSuiteVersion as i32, document::suite());
+        state.register_suite(cstr(kAIDocumentViewSuite), kAIDocumentViewSuiteVersion as i32, document::view_suite());
+        state.register_suite(cstr(kAIArtboardSuite), kAIArtboardSuiteVersion as i32, artboard::suite());
+        state.register_suite(cstr(kAIArtboardRangeSuite), kAIArtboardRangeSuiteVersion as i32, artboard::range_suite());
+        state.register_suite(cstr(kAIDictionarySuite), kAIDictionarySuiteVersion as i32, dictionary::suite());
+        state.register_suite(cstr(kAIEntrySuite), kAIEntrySuiteVersion as i32, dictionary::entry_suite());
+        state.register_suite(
+            cstr(kAIDictionaryIteratorSuite),
+            kAIDictionaryIteratorSuiteVersion as i32,
+            dictionary::iterator_suite(),
+        );
+        state.register_suite(cstr(kAIArraySuite), kAIArraySuiteVersion as i32, dictionary::array_suite());
+        state.register_suite(cstr(kAIPreferenceSuite), kAIPreferenceSuiteVersion as i32, preferences::suite());
+        state.register_suite(cstr(kAIMenuSuite), kAIMenuSuiteVersion as i32, menu::suite());
+        state.register_suite(cstr(kAIToolSuite), kAIToolSuiteVersion as i32, tool::suite());
+        state.register_suite(cstr(kAITimerSuite), kAITimerSuiteVersion as i32, timer::suite());
+        state.register_suite(cstr(kAILiveEffectSuite), kAILiveEffectSuiteVersion as i32, live_effect::suite());
+        state.register_suite(cstr(kAIPluginGroupSuite), kAIPluginGroupSuiteVersion as i32, plugin_group::suite());
+        state.register_suite(cstr(kAIFileFormatSuite), kAIFileFormatSuiteVersion as i32, file_format::suite());
+        state.register_suite(cstr(kAIActionManagerSuite), kAIActionManagerSuiteVersion as i32, action::suite());
+        state.register_suite(cstr(kAIFilePathSuite), kAIFilePathSuiteVersion as i32, file_path::suite());
+        state.register_suite(cstr(kAIUndoSuite), kAIUndoSuiteVersion as i32, undo::suite());
+        state.register_suite(cstr(kAIFilterSuite), kAIFilterSuiteVersion as i32, filter::suite());
+        state.register_suite(cstr(kSPBlocksSuite), kSPBlocksSuiteVersion as i32, basic::blocks_suite());
+        state::install(state);
+
+        Self {
+            entry,
+            plugin_ref: Box::new(0),
+            globals: null_mut(),
+            basic: Box::new(basic::suite()),
+            _lock: lock,
+        }
+    }
+
+    /// 追加のスイートテーブルを登録する
+    pub fn register_suite<S: 'static>(&mut self, name: &CStr, version: i32, table: S) {
+        with_state(|state| state.register_suite(name, version, table));
+    }
+
+    /// ホストがプラグインに渡す `SPMessageData`
+    pub fn message_data(&mut self) -> SPMessageData {
+        SPMessageData {
+            SPCheck: kSPValidSPMessageData as ai_int32,
+            self_: self.plugin_ref(),
+            globals: self.globals,
+            basic: &mut *self.basic,
+        }
+    }
+
+    pub fn plugin_ref(&mut self) -> SPPluginRef {
+        &mut *self.plugin_ref as *mut u64 as SPPluginRef
+    }
+
+    /// プラグインが `SPMessageData::globals` に保存した値
+    pub fn globals(&self) -> *mut c_void {
+        self.globals
+    }
+
+    /// `define_plugin!` で起動したプラグインを借りて `f` を呼ぶ
+    ///
+    /// `T` は `define_plugin!` に渡した型でなければならない。プラグインが起動していなければパニックする。
+    pub fn with_plugin<T: AIPlugin, R>(&mut self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.plugin::<T>().handler)
+    }
+
+    fn plugin<T: AIPlugin>(&mut self) -> &mut Plugin<T> {
+        let plugin = self.globals as *mut Plugin<T>;
+        assert!(!plugin.is_null(), "plugin is not running");
+        unsafe { &mut *plugin }
+    }
+
+    /// プラグインへのメッセージの中で `job` を実行し、その結果を返す
+    ///
+    /// メッセージを受け取ったときと同じく、スイートの取得や取り消し単位がこのメッセージに属する。
+    /// 初回はプラグインの `MessageRouter` に専用の caller/selector を登録する。
+    pub fn run_in_message<T: AIPlugin, R>(&mut self, job: impl FnOnce(&mut T) -> R) -> R {
+        let router = &mut self.plugin::<T>().fRouter;
+        if unsafe { router.resolve(JOB_CALLER.as_ptr(), JOB_SELECTOR.as_ptr()) }.is_none() {
+            router.register(JOB_CALLER, JOB_SELECTOR, |plugin: &mut T, message| {
+                // `run_in_message` が同じ `T` で作ったメッセージしか届かない
+                let message = unsafe { &mut *(message as *mut JobMessage) };
+                let job = unsafe { &mut *(message.job as *mut &mut dyn FnMut(&mut T)) };
+                job(plugin);
+                state::NO_ERR
+            });
+        }
+
+        let mut job = Some(job);
+        let mut result = None;
+        let error = {
+            let mut run = |plugin: &mut T| result = job.take().map(|job| job(plugin));
+            let mut run: &mut dyn FnMut(&mut T) = &mut run;
+            let mut message = JobMessage {
+                d: self.message_data(),
+                job: &mut run as *mut &mut dyn FnMut(&mut T) as *mut c_void,
+            };
+            self.send(JOB_CALLER, JOB_SELECTOR, &mut message)
+        };
+        assert_eq!(error, state::NO_ERR, "the job message failed");
+        result.expect("job was run")
+    }
+
+    /// 任意の caller/selector でメッセージを送る
+    pub fn send<M: HostMessage>(&mut self, caller: &CStr, selector: &CStr, message: &mut M) -> ASErr {
+        *message.data() = self.message_data();
+        // メッセージごとに新しい取り消し単位を始める
+        with_state(|state| {
+            state.undo = UndoContext::new();
+            state.undo.snapshot = Some(state.art.clone());
+        });
+
+        let error = unsafe {
+            (self.entry)(
+                caller.as_ptr() as *mut c_char,
+                selector.as_ptr() as *mut c_char,
+                message as *mut M as *mut c_void,
+            )
+        };
+
+        self.globals = message.data().globals;
+        error
+    }
+
+    pub fn startup(&mut self) -> ASErr {
+        let mut message: SPInterfaceMessage = unsafe { std::mem::zeroed() };
+        self.send(cstr(kSPInterfaceCaller), cstr(kSPInterfaceStartupSelector), &mut message)
+    }
+
+    pub fn shutdown(&mut self) -> ASErr {
+        let mut message: SPInterfaceMessage = unsafe { std::mem::zeroed() };
+        self.send(cstr(kSPInterfaceCaller), cstr(kSPInterfaceShutdownSelector), &mut message)
+    }
+
+    pub fn unload(&mut self) -> ASErr {
+        let mut message: SPAccessMessage = unsafe { std::mem::zeroed() };
+        self.send(cstr(kSPAccessCaller), cstr(kSPAccessUnloadSelector), &mut message)
+    }
+
+    pub fn reload(&mut self) -> ASErr {
+        let mut message: SPAccessMessage = unsafe { std::mem::zeroed() };
+        self.send(cstr(kSPAccessCaller), cstr(kSPAccessReloadSelector), &mut message)
+    }
+
+    /// `notifier_type` を購読している有効な通知すべてに `kSelectorAINotify` を送る
+    ///
+    /// 最初に失敗したエラーを返す。
+    pub fn notify(&mut self, notifier_type: &CStr, notify_data: *mut c_void) -> ASErr {
+        let handles: Vec<AINotifierHandle> = with_state(|state| {
+            state
+                .notifiers
+                .iter()
+                .filter(|n| n.active && n.notifier_type.as_c_str() == notifier_type)
+                .map(|n| n.handle)
+                .collect()
+        });
+
+        let mut error = state::NO_ERR;
+        for handle in handles {
+            let mut message: AINotifierMessage = unsafe { std::mem::zeroed() };
+            message.notifier = handle;
+            message.type_ = notifier_type.as_ptr();
+            message.notifyData = notify_data;
+
+            let result = self.send(cstr(kCallerAINotify), cstr(kSelectorAINotify), &mut message);
+            if error == state::NO_ERR {
+                error = result;
+            }
+        }
+        error
+    }
+
+    pub fn app_started(&mut self) -> ASErr {
+        self.notify(cstr(kAIApplicationStartedNotifier), null_mut())
+    }
+
+    pub fn app_shutdown(&mut self) -> ASErr {
+        self.notify(cstr(kAIApplicationShutdownNotifier), null_mut())
+    }
+
+    pub fn go_menu_item(&mut self, menu_item: AIMenuItemHandle) -> ASErr {
+        let mut message: AIMenuMessage = unsafe { std::mem::zeroed() };
+        message.menuItem = menu_item;
+        self.send(cstr(kCallerAIMenu), cstr(kSelectorAIGoMenuItem), &mut message)
+    }
+
+    pub fn update_menu_item(&mut self, menu_item: AIMenuItemHandle) -> ASErr {
+        let mut message: AIMenuMessage = unsafe { std::mem::zeroed() };
+        message.menuItem = menu_item;
+        self.send(cstr(kCallerAIMenu), cstr(kSelectorAIUpdateMenuItem), &mut message)
+    }
+
+    /// タイマーに `kSelectorAIGoTimer` を送る（無効なタイマーには送らず `kNoErr`）
+    pub fn fire_timer(&mut self, timer: AITimerHandle) -> ASErr {
+        let active = with_state(|state| timer::timer_index(state, timer).is_some_and(|index| state.timers[index].active));
+        if !active {
+            return state::NO_ERR;
+        }
+
+        let mut message: AITimerMessage = unsafe { std::mem::zeroed() };
+        message.timer = timer;
+        self.send(cstr(kCallerAITimer), cstr(kSelectorAIGoTimer), &mut message)
+    }
+
+    /// 空のパラメータ辞書を作る（参照数 1、`release_live_effect_parameters` で解放する）
+    pub fn new_live_effect_parameters(&self) -> AILiveEffectParameters {
+        dictionary::new_dictionary()
+    }
+
+    pub fn release_live_effect_parameters(&self, parameters: AILiveEffectParameters) {
+        unsafe { dictionary::release_dictionary(parameters) }
+    }
+
+    /// パラメータ辞書のキー（昇順）
+    pub fn live_effect_parameter_keys(&self, parameters: AILiveEffectParameters) -> Vec<String> {
+        unsafe { dictionary::dictionary_keys(parameters) }
+    }
+
+    /// `kSelectorAIEditLiveEffectParameters` を送る（コンテキストはエフェクトのハンドル）
+    pub fn edit_live_effect(
+        &mut self,
+        effect: AILiveEffectHandle,
+        parameters: AILiveEffectParameters,
+        is_new_instance: bool,
+    ) -> ASErr {
+        let mut message: AILiveEffectEditParamMessage = unsafe { std::mem::zeroed() };
+        message.effect = effect;
+        message.parameters = parameters;
+        message.context = effect as AILiveEffectParamContext;
+        message.allowPreview = 1;
+        message.isNewInstance = is_new_instance as AIBoolean;
+        self.send(cstr(kCallerAILiveEffect), cstr(kSelectorAIEditLiveEffectParameters), &mut message)
+    }
+
+    /// `kSelectorAIGoLiveEffect` を送り、プラグインが返したアートを返す
+    pub fn go_live_effect(
+        &mut self,
+        effect: AILiveEffectHandle,
+        parameters: AILiveEffectParameters,
+        art: AIArtHandle,
+    ) -> (ASErr, AIArtHandle) {
+        let mut message: AILiveEffectGoMessage = unsafe { std::mem::zeroed() };
+        message.effect = effect;
+        message.parameters = parameters;
+        message.art = art;
+        message.srcArt = art;
+        let error = self.send(cstr(kCallerAILiveEffect), cstr(kSelectorAIGoLiveEffect), &mut message);
+        (error, message.art)
+    }
+
+    /// `kSelectorAILiveEffectInterpolate` を送る（結果は `output` に書かれる）
+    pub fn interpolate_live_effect(
+        &mut self,
+        effect: AILiveEffectHandle,
+        start: AILiveEffectParameters,
+        end: AILiveEffectParameters,
+        output: AILiveEffectParameters,
+        percent: f64,
+    ) -> ASErr {
+        let mut message: AILiveEffectInterpParamMessage = unsafe { std::mem::zeroed() };
+        message.effect = effect;
+        message.startParams = start;
+        message.endParams = end;
+        message.outParams = output;
+        message.percent = percent;
+        self.send(cstr(kCallerAILiveEffect), cstr(kSelectorAILiveEffectInterpolate), &mut message)
+    }
+
+    /// `kSelectorAILiveEffectInputType` を送り、プラグインが返した `typeMask` を返す
+    pub fn live_effect_input_type(
+        &mut self,
+        effect: AILiveEffectHandle,
+        parameters: AILiveEffectParameters,
+        art: AIArtHandle,
+    ) -> (ASErr, i32) {
+        let mut message: AILiveEffectInputTypeMessage = unsafe { std::mem::zeroed() };
+        message.effect = effect;
+        message.parameters = parameters;
+        message.inputArt = art;
+        let error = self.send(cstr(kCallerAILiveEffect), cstr(kSelectorAILiveEffectInputType), &mut message);
+        (error, message.typeMask)
+    }
+
+    /// `kSelectorAILiveEffectScaleParameters` を送り、パラメータが変わったかを返す
+    pub fn scale_live_effect(
+        &mut self,
+        effect: AILiveEffectHandle,
+        parameters: AILiveEffectParameters,
+        scale: f64,
+    ) -> (ASErr, bool) {
+        let mut message: AILiveEffectScaleParamMessage = unsafe { std::mem::zeroed() };
+        message.effect = effect;
+        message.parameters = parameters;
+        message.scaleFactor = scale;
+        let error = self.send(cstr(kCallerAILiveEffect), cstr(kSelectorAILiveEffectScaleParameters), &mut message);
+        (error, message.scaledParams != 0)
+    }
+
+    /// `kSelectorAILiveEffectConverColorSpace` を送る
+    pub fn convert_live_effect_color_space(
+        &mut self,
+        effect: AILiveEffectHandle,
+        parameters: AILiveEffectParameters,
+        color_space: AIColorTag,
+    ) -> ASErr {
+        let mut message: AILiveEffectConvertColorMessage = unsafe { std::mem::zeroed() };
+        message.effect = effect;
+        message.parameters = parameters;
+        message.newColorSpace = color_space;
+        self.send(cstr(kCallerAILiveEffect), cstr(kSelectorAILiveEffectConverColorSpace), &mut message)
+    }
+
+    /// プラグインアートに `kSelectorAINotifyEdits` を送る
+    ///
+    /// `time` と `code` は AIPluginGroup.h の `kBeforeOperationTime`・`kTransformOperationCode` などの文字列。
+    pub fn notify_plugin_group_edit(&mut self, art: AIArtHandle, time: &str, code: &str, pre_edit_art: AIArtHandle) -> ASErr {
+        let Some(entry) = with_state(|state| plugin_group::plugin_art_index(state, art).map(|index| state.plugin_art[index].group))
+        else {
+            return art::BAD_PARAMETER;
+        };
+        let (Ok(time), Ok(code)) = (CString::new(time), CString::new(code)) else {
+            return art::BAD_PARAMETER;
+        };
+
+        let mut message: AIPluginGroupMessage = unsafe { std::mem::zeroed() };
+        message.entry = plugin_group::plugin_group_handle(entry);
+        message.art = art;
+        message.time = time.as_ptr();
+        message.code = code.as_ptr();
+        message.preEditArt = pre_edit_art;
+        self.send(cstr(kCallerAIPluginGroup), cstr(kSelectorAINotifyEdits), &mut message)
+    }
+
+    /// プラグインアートに `kSelectorAIUpdateArt` を送り、成功したら汚れていない状態にする
+    pub fn update_plugin_art(&mut self, art: AIArtHandle) -> ASErr {
+        let Some(entry) = with_state(|state| plugin_group::plugin_art_index(state, art).map(|index| state.plugin_art[index].group))
+        else {
+            return art::BAD_PARAMETER;
+        };
+
+        let mut message: AIPluginGroupMessage = unsafe { std::mem::zeroed() };
+        message.entry = plugin_group::plugin_group_handle(entry);
+        message.art = art;
+        let error = self.send(cstr(kCallerAIPluginGroup), cstr(kSelectorAIUpdateArt), &mut message);
+        if error == state::NO_ERR {
+            with_state(|state| {
+                if let Some(index) = plugin_group::plugin_art_index(state, art) {
+                    state.plugin_art[index].dirty = false;
+                }
+            });
+        }
+        error
+    }
+
+    /// 空の値パラメータブロックを作る（`release_action_parameters` で解放する）
+    pub fn new_action_parameters(&self) -> AIActionParamValueRef {
+        action::new_param_value_ref()
+    }
+
+    pub fn release_action_parameters(&self, parameters: AIActionParamValueRef) {
+        unsafe { action::release_param_value(parameters) }
+    }
+
+    /// 値パラメータブロックのキー（追加順）
+    pub fn action_parameter_keys(&self, parameters: AIActionParamValueRef) -> Vec<ActionParamKeyID> {
+        let Some(value) = (unsafe { action::param_value(parameters) }) else {
+            return Vec::new();
+        };
+        value.entries.iter().map(|(key, _)| *key).collect()
+    }
+
+    /// 値パラメータブロックの値
+    pub fn action_parameter(&self, parameters: AIActionParamValueRef, key: ActionParamKeyID) -> Option<ActionValue> {
+        unsafe { action::param_value(parameters) }?.get(key).cloned()
+    }
+
+    pub fn set_action_parameter(&self, parameters: AIActionParamValueRef, key: ActionParamKeyID, value: ActionValue) {
+        if let Some(param) = unsafe { action::param_value(parameters) } {
+            param.set(key, value);
+        }
+    }
+
+    /// アクションパネルの記録を開始・停止する（`InRecordMode` の結果になる）
+    pub fn set_recording(&mut self, recording: bool) {
+        with_state(|state| state.recording = recording);
+    }
+
+    /// 記録モードで記録されたイベント（記録順）
+    pub fn recorded_actions(&self) -> Vec<RecordedAction> {
+        with_state(|state| state.recorded_actions.clone())
+    }
+
+    /// `name` で登録されたアクションイベント
+    pub fn action_event(&self, name: &str) -> Option<ActionEventInfo> {
+        with_state(|state| {
+            let entry = state.action_events.iter().find(|entry| entry.name.to_bytes() == name.as_bytes())?;
+            Some(ActionEventInfo {
+                name: name.to_owned(),
+                title: entry.title.clone(),
+                version: entry.version,
+                flag: entry.flag,
+                parameters: entry.parameters.clone(),
+            })
+        })
+    }
+
+    /// 登録されたイベントを `values` で再生する（`kActionCaller` の `kDoActionSelector`）
+    ///
+    /// `show_dialog` はアクションパネルのダイアログの切り替え。登録されていないイベントは `kBadParameterErr`。
+    pub fn play_action(&mut self, name: &str, values: &[(ActionParamKeyID, ActionValue)], show_dialog: bool) -> ASErr {
+        let user_data = with_state(|state| {
+            let entry = state.action_events.iter().find(|entry| entry.name.to_bytes() == name.as_bytes())?;
+            Some(entry.user_data)
+        });
+        let Some(user_data) = user_data else {
+            return art::BAD_PARAMETER;
+        };
+
+        let parameters = self.new_action_parameters();
+        for (key, value) in values {
+            self.set_action_parameter(parameters, *key, value.clone());
+        }
+
+        let mut message: DoActionMessage = unsafe { std::mem::zeroed() };
+        message.userData = user_data;
+        message.showDialog = show_dialog as AIBoolean;
+        message.param = parameters;
+        let error = self.send(cstr(kActionCaller), cstr(kDoActionSelector), &mut message);
+
+        self.release_action_parameters(parameters);
+        error
+    }
+
+    /// ファイルフォーマットにメッセージを送る（`option` は `kFileFormatRead` などの要求する操作）
+    pub fn send_file_format(
+        &mut self,
+        selector: &CStr,
+        format: AIFileFormatHandle,
+        option: i32,
+        file_path: &str,
+        parameters: AIActionParamValueRef,
+    ) -> ASErr {
+        let mut message: AIFileFormatMessage = unsafe { std::mem::zeroed() };
+        message.fileFormat = format;
+        message.option = option;
+        message.actionParm = parameters as *mut c_void;
+        unsafe { file_path::set(&mut message.filePath, file_path) };
+
+        let error = self.send(cstr(kCallerAIFileFormat), selector, &mut message);
+        unsafe { file_path::release(&mut message.filePath) };
+        error
+    }
+
+    /// `kSelectorAICheckFileFormat` を送る（読めないファイルなら `kUnknownFormatErr`）
+    pub fn check_file_format(&mut self, format: AIFileFormatHandle, file_path: &str) -> ASErr {
+        let option = AIFileFormatOptions_kFileFormatRead;
+        self.send_file_format(cstr(kSelectorAICheckFileFormat), format, option, file_path, null_mut())
+    }
+
+    /// `kSelectorAIGetFileFormatParameters` を送る（設定は `parameters` に書かれる）
+    pub fn get_file_format_parameters(
+        &mut self,
+        format: AIFileFormatHandle,
+        option: i32,
+        file_path: &str,
+        parameters: AIActionParamValueRef,
+    ) -> ASErr {
+        self.send_file_format(cstr(kSelectorAIGetFileFormatParameters), format, option, file_path, parameters)
+    }
+
+    /// `kSelectorAIGoFileFormat` を送る
+    pub fn go_file_format(
+        &mut self,
+        format: AIFileFormatHandle,
+        option: i32,
+        file_path: &str,
+        parameters: AIActionParamValueRef,
+    ) -> ASErr {
+        self.send_file_format(cstr(kSelectorAIGoFileFormat), format, option, file_path, parameters)
+    }
+
+    /// ファイルフォーマット caller の `kDoActionSelector` を送る（`userData` はフォーマットのハンドル）
+    pub fn set_file_format_parameters(&mut self, format: AIFileFormatHandle, parameters: AIActionParamValueRef) -> ASErr {
+        let mut message: DoActionMessage = unsafe { std::mem::zeroed() };
+        message.userData = format as AIActionUserData;
+        message.param = parameters;
+        self.send(cstr(kCallerAIFileFormat), cstr(kDoActionSelector), &mut message)
+    }
+
+    /// フィルターにメッセージを送る
+    ///
+    /// ホストが保持しているパラメータを渡し、プラグインが書き換えたパラメータを保持し直す。
+    pub fn send_filter(&mut self, selector: &CStr, filter: AIFilterHandle) -> ASErr {
+        let mut message: AIFilterMessage = unsafe { std::mem::zeroed() };
+        message.filter = filter;
+        message.parameters = with_state(|state| {
+            filter::filter_index(state, filter).map_or(null_mut(), |index| state.filters[index].parameters)
+        });
+
+        let error = self.send(cstr(kCallerAIFilter), selector, &mut message);
+        with_state(|state| {
+            if let Some(index) = filter::filter_index(state, filter) {
+                state.filters[index].parameters = message.parameters;
+            }
+        });
+        error
+    }
+
+    /// `kSelectorAIGetFilterParameters` を送る（メニューからフィルターを選んだときのダイアログ）
+    pub fn get_filter_parameters(&mut self, filter: AIFilterHandle) -> ASErr {
+        self.send_filter(cstr(kSelectorAIGetFilterParameters), filter)
+    }
+
+    /// `kSelectorAIGoFilter` を送る（単独で送ると「フィルターを再実行」と同じ）
+    pub fn go_filter(&mut self, filter: AIFilterHandle) -> ASErr {
+        self.send_filter(cstr(kSelectorAIGoFilter), filter)
+    }
+
+    /// パラメータの編集に続けてフィルターを実行する（編集が失敗・キャンセルされたら実行しない）
+    pub fn run_filter(&mut self, filter: AIFilterHandle) -> ASErr {
+        let error = self.get_filter_parameters(filter);
+        if error != 0 {
+            return error;
+        }
+        self.go_filter(filter)
+    }
+
+    /// ツールにメッセージを送る（`modifiers` は `AIEventModifersValue` の組み合わせ）
+    pub fn send_tool(&mut self, selector: &CStr, tool: AIToolHandle, cursor: AIRealPoint, modifiers: u16) -> ASErr {
+        let mut event: AIEvent = unsafe { std::mem::zeroed() };
+        event.modifiers = modifiers;
+
+        let mut message: AIToolMessage = unsafe { std::mem::zeroed() };
+        message.tool = tool;
+        message.cursor = cursor;
+        message.pressure = 127;
+        message.event = &mut event;
+        self.send(cstr(kCallerAITool), selector, &mut message)
+    }
+
+    /// ツールを選択状態にして `kSelectorAISelectTool` を送る
+    pub fn select_tool(&mut self, tool: AIToolHandle) -> ASErr {
+        with_state(|state| state.selected_tool = tool::tool_index(state, tool));
+        self.send_tool(cstr(kSelectorAISelectTool), tool, AIRealPoint { h: 0.0, v: 0.0 }, 0)
+    }
+
+    /// `from` で押して `to` まで一直線にドラッグし、離す
+    pub fn drag_tool(&mut self, tool: AIToolHandle, from: AIRealPoint, to: AIRealPoint, steps: usize) -> ASErr {
+        let error = self.send_tool(cstr(kSelectorAIToolMouseDown), tool, from, 0);
+        if error != 0 {
+            return error;
+        }
+        for step in 1..=steps {
+            let t = step as f64 / steps as f64;
+            let point = AIRealPoint {
+                h: from.h + (to.h - from.h) * t,
+                v: from.v + (to.v - from.v) * t,
+            };
+            let error = self.send_tool(cstr(kSelectorAIToolMouseDrag), tool, point, 0);
+            if error != 0 {
+                return error;
+            }
+        }
+        self.send_tool(cstr(kSelectorAIToolMouseUp), tool, to, 0)
+    }
+
+    /// 記録されたイベントの複製
+    pub fn events(&self) -> Vec<HostEvent> {
+        with_state(|state| state.events.clone())
+    }
+
+    /// 記録されたイベントを取り出して消去する
+    pub fn take_events(&mut self) -> Vec<HostEvent> {
+        with_state(|state| std::mem::take(&mut state.events))
+    }
+
+    /// スイートの現在の参照数（未登録なら `None`）
+    pub fn suite_refcount(&self, name: &CStr, version: i32) -> Option<i32> {
+        with_state(|state| state.suites.get(&(name.to_owned(), version)).map(|e| e.refcount))
+    }
+
+    /// `SPAccessSuite::AcquirePlugin` によるロック数
+    pub fn access_count(&self) -> i32 {
+        with_state(|state| state.access_count)
+    }
+
+    /// 登録済みの通知（種類と有効状態）
+    pub fn notifiers(&self) -> Vec<(String, bool)> {
+        with_state(|state| {
+            state
+                .notifiers
+                .iter()
+                .map(|n| (n.notifier_type.to_string_lossy().into_owned(), n.active))
+                .collect()
+        })
+    }
+
+    /// レイヤーの最上位グループ（`GetFirstArtOfLayer` が返すアート）
+    pub fn layer_art(&self, layer: usize) -> Option<AIArtHandle> {
+        with_state(|state| state.art.layers.get(layer).map(|l| art::art_handle(l.group)))
+    }
+
+    /// 最前面にレイヤーを追加し、その番号を返す
+    pub fn add_layer(&mut self, title: &str) -> usize {
+        with_state(|state| state.art.add_layer(title))
+    }
+
+    /// `parent` の最前面にサブレイヤーを追加し、その番号を返す
+    pub fn add_sublayer(&mut self, parent: usize, title: &str) -> Option<usize> {
+        with_state(|state| {
+            state.art.layer_index(art::layer_handle(parent))?;
+            let layer = state.art.new_layer(title);
+            let order = AIPaintOrder_kPlaceInsideOnTop as i16;
+            state.art.position_layer(layer, order, Some(parent)).ok()?;
+            Some(layer)
+        })
+    }
+
+    /// レイヤー番号に対応するハンドル
+    pub fn layer_handle(&self, layer: usize) -> AILayerHandle {
+        art::layer_handle(layer)
+    }
+
+    /// レイヤーのタイトルをレイヤーパネルの表示順（親が先）に並べたもの
+    ///
+    /// サブレイヤーは階層の深さだけ先頭に `"  "` が付く。
+    pub fn layer_outline(&self) -> Vec<String> {
+        with_state(|state| {
+            let tree = &state.art;
+            tree.preorder_layers()
+                .into_iter()
+                .map(|layer| {
+                    let mut depth = 0;
+                    let mut parent = tree.layer_parent(layer);
+                    while let Some(index) = parent {
+                        depth += 1;
+                        parent = tree.layer_parent(index);
+                    }
+                    format!("{}{}", "  ".repeat(depth), tree.layers[layer].title)
+                })
+                .collect()
+        })
+    }
+
+    /// レイヤーの状態（削除済みなら `None`）
+    pub fn layer_info(&self, layer: AILayerHandle) -> Option<LayerInfo> {
+        with_state(|state| {
+            let index = state.art.layer_index(layer)?;
+            let entry = &state.art.layers[index];
+            Some(LayerInfo {
+                title: entry.title.clone(),
+                visible: entry.visible,
+                editable: entry.editable,
+                printed: entry.printed,
+                preview: entry.preview,
+                template: entry.template,
+                color: (entry.color.red, entry.color.green, entry.color.blue),
+                parent: state.art.layer_parent(index).map(art::layer_handle),
+            })
+        })
+    }
+
+    /// アートを直接作る（プラグインを通さずにテスト用のツリーを組み立てる）
+    pub fn add_art(&mut self, art_type: i16, parent: AIArtHandle, bounds: AIRealRect) -> Option<AIArtHandle> {
+        with_state(|state| {
+            let parent = state.art.get(parent)?;
+            let index = state.art.push_node(art_type, state.art.nodes[parent].layer);
+            state.art.nodes[index].bounds = bounds;
+            state.art.place(index, AIPaintOrder_kPlaceInsideOnBottom as i16, Some(parent)).ok()?;
+            Some(art::art_handle(index))
+        })
+    }
+
+    /// アートの子（重ね順の上から）
+    pub fn art_children(&self, art: AIArtHandle) -> Vec<AIArtHandle> {
+        with_state(|state| {
+            state
+                .art
+                .get(art)
+                .map(|index| state.art.nodes[index].children.iter().map(|&c| art::art_handle(c)).collect())
+                .unwrap_or_default()
+        })
+    }
+
+    /// アートの種類（破棄済みなら `None`）
+    pub fn art_type(&self, art: AIArtHandle) -> Option<i16> {
+        with_state(|state| state.art.get(art).map(|index| state.art.nodes[index].art_type))
+    }
+
+    /// パスアートのセグメントと閉じているか
+    pub fn path_segments(&self, art: AIArtHandle) -> Option<(Vec<AIPathSegment>, bool)> {
+        with_state(|state| {
+            state.art.get(art).map(|index| {
+                let node = &state.art.nodes[index];
+                (node.segments.clone(), node.closed)
+            })
+        })
+    }
+
+    /// パスアートのセグメントと閉じているかを直接設定する（パス以外は `false`）
+    pub fn set_path_segments(&mut self, art: AIArtHandle, segments: &[AIPathSegment], closed: bool) -> bool {
+        with_state(|state| {
+            let Some(index) = state.art.get(art) else {
+                return false;
+            };
+            let node = &mut state.art.nodes[index];
+            if node.art_type != AIArtType_kPathArt as i16 {
+                return false;
+            }
+            node.segments = segments.to_vec();
+            node.closed = closed;
+            true
+        })
+    }
+
+    /// アートのユーザー属性
+    pub fn art_user_attr(&self, art: AIArtHandle) -> Option<i32> {
+        with_state(|state| state.art.get(art).map(|index| state.art.nodes[index].user_attr))
+    }
+
+    /// アートのユーザー属性を直接設定する（破棄済みなら `false`）
+    pub fn set_art_user_attr(&mut self, art: AIArtHandle, user_attr: i32) -> bool {
+        with_state(|state| {
+            state
+                .art
+                .get(art)
+                .map(|index| state.art.nodes[index].user_attr = user_attr)
+                .is_some()
+        })
+    }
+
+    /// パスアートのスタイル（未設定なら塗りも線もないスタイル）
+    pub fn path_style(&self, art: AIArtHandle) -> Option<AIPathStyle> {
+        with_state(|state| {
+            state
+                .art
+                .get(art)
+                .map(|index| state.art.nodes[index].style.unwrap_or_else(path_style::empty_style))
+        })
+    }
+
+    /// パスアートのスタイルを直接設定する（破棄済みなら `false`）
+    pub fn set_path_style(&mut self, art: AIArtHandle, style: AIPathStyle) -> bool {
+        with_state(|state| {
+            state
+                .art
+                .get(art)
+                .map(|index| state.art.nodes[index].style = Some(style))
+                .is_some()
+        })
+    }
+
+    /// アートの辞書のキー（昇順、辞書がなければ空）
+    pub fn art_dictionary_keys(&self, art: AIArtHandle) -> Vec<String> {
+        with_state(|state| {
+            let dictionary = state.art.get(art).and_then(|index| state.art.nodes[index].dictionary);
+            dictionary.map_or_else(Vec::new, |dictionary| unsafe { dictionary::dictionary_keys(dictionary) })
+        })
+    }
+
+    /// アクティブなドキュメントの辞書のキー（昇順）
+    pub fn document_dictionary_keys(&self) -> Vec<String> {
+        with_state(|state| {
+            state
+                .document
+                .as_ref()
+                .map_or_else(Vec::new, |entry| unsafe { dictionary::dictionary_keys(entry.dictionary) })
+        })
+    }
+
+    /// 環境設定に保存された値
+    pub fn preference(&self, prefix: &str, suffix: &str) -> Option<PreferenceValue> {
+        with_state(|state| state.preferences.get(&(prefix.to_owned(), suffix.to_owned())).cloned())
+    }
+
+    /// 環境設定に値を保存する（`None` なら削除）
+    pub fn set_preference(&mut self, prefix: &str, suffix: &str, value: Option<PreferenceValue>) {
+        let key = (prefix.to_owned(), suffix.to_owned());
+        with_state(|state| match value {
+            Some(value) => state.preferences.insert(key, value),
+            None => state.preferences.remove(&key),
+        });
+    }
+
+    /// `key` で追加されたメニュー項目（取り除かれていれば `None`）
+    pub fn menu_item(&self, key: &str) -> Option<AIMenuItemHandle> {
+        with_state(|state| {
+            state
+                .menu_items
+                .iter()
+                .position(|entry| entry.key == key && !entry.removed)
+                .map(menu::item_handle)
+        })
+    }
+
+    /// メニュー項目の状態
+    pub fn menu_item_info(&self, item: AIMenuItemHandle) -> Option<MenuItemInfo> {
+        with_state(|state| {
+            let index = (0..state.menu_items.len()).find(|&index| menu::item_handle(index) == item)?;
+            let entry = &state.menu_items[index];
+            (!entry.removed).then(|| MenuItemInfo {
+                key: entry.key.clone(),
+                group: entry.group.clone(),
+                text: entry.text.clone(),
+                options: entry.options,
+                enabled: entry.enabled,
+                checked: entry.checked,
+            })
+        })
+    }
+
+    /// 追加されたメニューグループの名前と、サブメニューなら親の項目
+    pub fn menu_groups(&self) -> Vec<(String, Option<AIMenuItemHandle>)> {
+        with_state(|state| {
+            state
+                .menu_groups
+                .iter()
+                .map(|group| (group.name.to_string_lossy().into_owned(), group.parent.map(menu::item_handle)))
+                .collect()
+        })
+    }
+
+    /// `name` で追加されたツール
+    pub fn tool(&self, name: &str) -> Option<AIToolHandle> {
+        with_state(|state| {
+            state
+                .tools
+                .iter()
+                .position(|entry| entry.name.to_bytes() == name.as_bytes())
+                .map(tool::tool_handle)
+        })
+    }
+
+    /// ツールの状態
+    pub fn tool_info(&self, tool: AIToolHandle) -> Option<ToolInfo> {
+        with_state(|state| {
+            let entry = &state.tools[tool::tool_index(state, tool)?];
+            Some(ToolInfo {
+                name: entry.name.to_string_lossy().into_owned(),
+                title: entry.title.clone(),
+                tooltip: entry.tooltip.clone(),
+                options: entry.options,
+                same_group_as: entry.same_group_as,
+                same_toolset_as: entry.same_toolset_as,
+                icons: entry.icon_names.as_ref().map(|(normal, dark)| {
+                    (normal.to_string_lossy().into_owned(), dark.to_string_lossy().into_owned())
+                }),
+            })
+        })
+    }
+
+    /// `name` で追加されたタイマー
+    pub fn timer(&self, name: &str) -> Option<AITimerHandle> {
+        with_state(|state| {
+            state
+                .timers
+                .iter()
+                .position(|entry| entry.name.to_bytes() == name.as_bytes())
+                .map(timer::timer_handle)
+        })
+    }
+
+    /// タイマーの周期（ティック数）と有効かどうか
+    pub fn timer_state(&self, timer: AITimerHandle) -> Option<(i32, bool)> {
+        with_state(|state| {
+            let entry = &state.timers[timer::timer_index(state, timer)?];
+            Some((entry.period, entry.active))
+        })
+    }
+
+    /// `name` で登録されたライブエフェクト
+    pub fn live_effect(&self, name: &str) -> Option<AILiveEffectHandle> {
+        with_state(|state| {
+            state
+                .live_effects
+                .iter()
+                .position(|entry| entry.name.to_bytes() == name.as_bytes())
+                .map(live_effect::live_effect_handle)
+        })
+    }
+
+    /// ライブエフェクトの状態
+    pub fn live_effect_info(&self, effect: AILiveEffectHandle) -> Option<LiveEffectInfo> {
+        with_state(|state| {
+            let entry = &state.live_effects[live_effect::live_effect_index(state, effect)?];
+            Some(LiveEffectInfo {
+                name: entry.name.to_string_lossy().into_owned(),
+                title: entry.title.to_string_lossy().into_owned(),
+                version: entry.version,
+                input_preference: entry.input_preference,
+                style_filter_flags: entry.style_filter_flags,
+                menu: entry.menu.clone(),
+            })
+        })
+    }
+
+    /// `name` で登録されたプラグイングループ
+    pub fn plugin_group(&self, name: &str) -> Option<AIPluginGroupHandle> {
+        with_state(|state| {
+            state
+                .plugin_groups
+                .iter()
+                .position(|entry| entry.name.to_bytes() == name.as_bytes())
+                .map(plugin_group::plugin_group_handle)
+        })
+    }
+
+    /// プラグイングループの状態
+    pub fn plugin_group_info(&self, group: AIPluginGroupHandle) -> Option<PluginGroupInfo> {
+        with_state(|state| {
+            let entry = &state.plugin_groups[plugin_group::plugin_group_index(state, group)?];
+            Some(PluginGroupInfo {
+                name: entry.name.to_string_lossy().into_owned(),
+                description: entry.description.to_string_lossy().into_owned(),
+                version: entry.version,
+                options: entry.options,
+            })
+        })
+    }
+
+    /// プラグインアートの（編集グループ, 結果グループ）
+    pub fn plugin_art_groups(&self, art: AIArtHandle) -> Option<(AIArtHandle, AIArtHandle)> {
+        with_state(|state| {
+            let entry = &state.plugin_art[plugin_group::plugin_art_index(state, art)?];
+            Some((art::art_handle(entry.edit), art::art_handle(entry.result)))
+        })
+    }
+
+    /// プラグインアートのデータ領域
+    pub fn plugin_art_data(&self, art: AIArtHandle) -> Option<Vec<u8>> {
+        with_state(|state| Some(state.plugin_art[plugin_group::plugin_art_index(state, art)?].data.clone()))
+    }
+
+    /// 結果グループを作り直す必要があるか（`UseAIPluginGroup` の直後と `MarkPluginArtDirty` の後は `true`）
+    pub fn plugin_art_dirty(&self, art: AIArtHandle) -> Option<bool> {
+        with_state(|state| Some(state.plugin_art[plugin_group::plugin_art_index(state, art)?].dirty))
+    }
+
+    /// `name` で登録されたファイルフォーマット
+    pub fn file_format(&self, name: &str) -> Option<AIFileFormatHandle> {
+        with_state(|state| {
+            state
+                .file_formats
+                .iter()
+                .position(|entry| entry.name.to_bytes() == name.as_bytes())
+                .map(file_format::file_format_handle)
+        })
+    }
+
+    /// ファイルフォーマットの状態
+    pub fn file_format_info(&self, format: AIFileFormatHandle) -> Option<FileFormatInfo> {
+        with_state(|state| {
+            let entry = &state.file_formats[file_format::file_format_index(state, format)?];
+            Some(FileFormatInfo {
+                name: entry.name.to_string_lossy().into_owned(),
+                title: entry.title.clone(),
+                extension: entry.extension.clone(),
+                options: entry.options,
+                priority: entry.priority,
+            })
+        })
+    }
+
+    /// 名前で登録されたフィルター
+    pub fn filter(&self, name: &str) -> Option<AIFilterHandle> {
+        with_state(|state| {
+            state
+                .filters
+                .iter()
+                .position(|entry| entry.name.to_bytes() == name.as_bytes())
+                .map(filter::filter_handle)
+        })
+    }
+
+    /// フィルターの状態
+    pub fn filter_info(&self, filter: AIFilterHandle) -> Option<FilterInfo> {
+        with_state(|state| {
+            let entry = &state.filters[filter::filter_index(state, filter)?];
+            Some(FilterInfo {
+                name: entry.name.to_string_lossy().into_owned(),
+                category: entry.category.clone(),
+                title: entry.title.clone(),
+                options: entry.options,
+                has_parameters: !entry.parameters.is_null(),
+            })
+        })
+    }
+
+    /// 最後に送ったメッセージの取り消し単位
+    pub fn undo_info(&self) -> UndoInfo {
+        with_state(|state| UndoInfo {
+            undo_text: state.undo.undo_text.clone(),
+            redo_text: state.undo.redo_text.clone(),
+            silent: state.undo.silent,
+            kind: state.undo.kind,
+            reverts: state.undo.reverts,
+        })
+    }
+
+    /// 新しいドキュメントを開いてアクティブにする（`file_path` が `None` なら未保存の新規ドキュメント）
+    ///
+    /// 以前のドキュメントは閉じられ、ハンドルは無効になる。
+    pub fn open_document(&mut self, file_path: Option<&str>) -> AIDocumentHandle {
+        with_state(|state| {
+            let entry = DocumentEntry::new(state.documents_opened, file_path);
+            let handle = entry.handle();
+            state.documents_opened += 1;
+            state.document = Some(entry);
+            handle
+        })
+    }
+
+    /// アクティブなドキュメントを閉じる
+    pub fn close_document(&mut self) {
+        with_state(|state| state.document = None);
+    }
+
+    /// アクティブなドキュメントのハンドル
+    pub fn document(&self) -> Option<AIDocumentHandle> {
+        with_state(|state| state.document.as_ref().map(DocumentEntry::handle))
+    }
+
+    /// アクティブなドキュメントに未保存の変更があるか
+    pub fn document_modified(&self) -> Option<bool> {
+        with_state(|state| state.document.as_ref().map(|entry| entry.modified))
+    }
+
+    /// 保存したことにする（パスを設定して変更フラグを下ろす）
+    pub fn save_document(&mut self, file_path: &str) {
+        with_state(|state| {
+            if let Some(entry) = state.document.as_mut() {
+                entry.set_file_path(file_path);
+                entry.modified = false;
+            }
+        });
+    }
+
+    /// アクティブなドキュメントの定規の単位（`AIDocumentRulerUnitValue`）
+    pub fn ruler_units(&self) -> Option<i16> {
+        with_state(|state| state.document.as_ref().map(|entry| entry.ruler_units))
+    }
+
+    /// ビューを追加し、そのハンドルを返す
+    pub fn add_view(&mut self) -> Option<AIDocumentViewHandle> {
+        with_state(|state| {
+            let entry = state.document.as_mut()?;
+            entry.views.push(document::ViewEntry::new());
+            Some(document::view_handle(entry.views.len() - 1))
+        })
+    }
+
+    /// `index` 番目のビューの中心・表示倍率・ウィンドウの大きさ（ピクセル）を設定する
+    pub fn set_view(&mut self, index: usize, center: AIRealPoint, zoom: f64, width: f64, height: f64) {
+        with_state(|state| {
+            if let Some(view) = state.document.as_mut().and_then(|entry| entry.views.get_mut(index)) {
+                *view = document::ViewEntry { center, zoom, width, height };
+            }
+        });
+    }
+
+    /// `index` 番目のビューの中心と表示倍率
+    pub fn view(&self, index: usize) -> Option<(AIRealPoint, f64)> {
+        with_state(|state| {
+            let view = state.document.as_ref()?.views.get(index)?;
+            Some((view.center, view.zoom))
+        })
+    }
+
+    /// 末尾にアートボードを追加し、その番号を返す
+    pub fn add_artboard(&mut self, name: &str, bounds: AIRealRect) -> Option<usize> {
+        with_state(|state| {
+            let entry = state.document.as_mut()?;
+            entry.artboards.push(ArtboardEntry::named(name, bounds));
+            Some(entry.artboards.len() - 1)
+        })
+    }
+
+    /// アクティブなドキュメントのアートボード（番号順）
+    pub fn artboards(&self) -> Vec<ArtboardInfo> {
+        with_state(|state| {
+            state
+                .document
+                .as_ref()
+                .map(|entry| {
+                    entry
+                        .artboards
+                        .iter()
+                        .map(|artboard| {
+                            let AIRealRect { left, top, right, bottom } = artboard.bounds;
+                            ArtboardInfo {
+                                name: artboard.name.clone(),
+                                bounds: (left, top, right, bottom),
+                                selected: artboard.selected,
+                            }
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    }
+
+    /// アクティブなアートボードの番号
+    pub fn active_artboard(&self) -> Option<usize> {
+        with_state(|state| state.document.as_ref().map(|entry| entry.active_artboard))
+    }
+}
+
+impl Drop for MockHost {
+    fn drop(&mut self) {
+        state::uninstall();
+    }
+}
@@ -0,0 +1,496 @@
+use std::ptr::null_mut;
+
+use illustrator_sys::*;
+
+use crate::art::{layer_handle, status, ArtTree, BAD_PARAMETER};
+use crate::state::{with_state, HostEvent, NO_ERR};
+use crate::unicode;
+
+/// ホストが持つ唯一のレイヤーリスト（ドキュメントのレイヤーリスト）
+const DOCUMENT_LAYER_LIST: usize = 0x50_0000;
+
+/// `position_layer` の移動先
+enum LayerSlot {
+    /// 基準レイヤーと同じ階層（0 なら直前、1 なら直後）
+    Beside(usize, usize),
+    /// 基準レイヤーのサブレイヤー（`true` なら最前面）
+    Inside(usize, bool),
+    /// 最上位の最前面
+    Top,
+    /// 最上位の最背面
+    Bottom,
+}
+
+impl ArtTree {
+    /// 親レイヤー（最上位なら `None`）
+    pub fn layer_parent(&self, layer: usize) -> Option<usize> {
+        let group = self.layers[layer].group;
+        self.nodes[group].parent.map(|parent| self.nodes[parent].layer)
+    }
+
+    /// サブレイヤー（上から）
+    pub fn sublayers(&self, layer: usize) -> Vec<usize> {
+        let group = self.layers[layer].group;
+        self.nodes[group]
+            .children
+            .iter()
+            .filter(|&&child| self.nodes[child].layer_group)
+            .map(|&child| self.nodes[child].layer)
+            .collect()
+    }
+
+    /// 同じ階層のレイヤー（自分を含む、上から）
+    pub fn sibling_layers(&self, layer: usize) -> Vec<usize> {
+        match self.layer_parent(layer) {
+            Some(parent) => self.sublayers(parent),
+            None => self.top_layers.clone(),
+        }
+    }
+
+    /// レイヤーとその子孫のレイヤー（親が先）
+    pub fn layer_subtree(&self, layer: usize) -> Vec<usize> {
+        let mut layers = vec![layer];
+        for sublayer in self.sublayers(layer) {
+            layers.extend(self.layer_subtree(sublayer));
+        }
+        layers
+    }
+
+    /// レイヤーパネルの表示順（親が先）
+    pub fn preorder_layers(&self) -> Vec<usize> {
+        self.top_layers.iter().flat_map(|&layer| self.layer_subtree(layer)).collect()
+    }
+
+    /// `relative` を基準にレイヤーを移す（`paint_order` は `AIArtSuite::NewArt` と同じ）
+    pub fn position_layer(&mut self, layer: usize, paint_order: i16, relative: Option<usize>) -> Result<(), AIErr> {
+        let group = self.layers[layer].group;
+        if let Some(relative) = relative {
+            let target = self.layers[relative].group;
+            if relative == layer || self.is_ancestor(group, target) {
+                return Err(BAD_PARAMETER);
+            }
+        }
+
+        let order = paint_order as AIPaintOrder;
+        let slot = match (order, relative) {
+            (AIPaintOrder_kPlaceAbove | AIPaintOrder_kPlaceDefault, Some(relative)) => LayerSlot::Beside(relative, 0),
+            (AIPaintOrder_kPlaceBelow, Some(relative)) => LayerSlot::Beside(relative, 1),
+            (AIPaintOrder_kPlaceInsideOnTop, Some(relative)) => LayerSlot::Inside(relative, true),
+            (AIPaintOrder_kPlaceInsideOnBottom, Some(relative)) => LayerSlot::Inside(relative, false),
+            (AIPaintOrder_kPlaceAboveAll, _) | (AIPaintOrder_kPlaceAbove | AIPaintOrder_kPlaceDefault, None) => LayerSlot::Top,
+            (AIPaintOrder_kPlaceBelowAll, _) | (AIPaintOrder_kPlaceBelow, None) => LayerSlot::Bottom,
+            _ => return Err(BAD_PARAMETER),
+        };
+
+        self.top_layers.retain(|&top| top != layer);
+        self.detach(group);
+
+        match slot {
+            LayerSlot::Beside(relative, offset) => {
+                let target = self.layers[relative].group;
+                match self.nodes[target].parent {
+                    Some(parent) => {
+                        let position = self.position(parent, target) + offset;
+                        self.nodes[parent].children.insert(position, group);
+                        self.nodes[group].parent = Some(parent);
+                    }
+                    None => {
+                        let position = self.top_layers.iter().position(|&top| top == relative).unwrap_or(0);
+                        self.top_layers.insert(position + offset, layer);
+                    }
+                }
+            }
+            LayerSlot::Inside(relative, on_top) => {
+                let parent = self.layers[relative].group;
+                let position = if on_top { 0 } else { self.nodes[parent].children.len() };
+                self.nodes[parent].children.insert(position, group);
+                self.nodes[group].parent = Some(parent);
+            }
+            LayerSlot::Top => self.top_layers.insert(0, layer),
+            LayerSlot::Bottom => self.top_layers.push(layer),
+        }
+        Ok(())
+    }
+
+    /// レイヤーとサブレイヤー、その中のアートを削除する
+    pub fn delete_layer(&mut self, layer: usize) -> Result<(), AIErr> {
+        if self.top_layers == [layer] {
+            // 最後のレイヤーは削除できない
+            return Err(kCantHappenErr as AIErr);
+        }
+
+        for index in self.layer_subtree(layer) {
+            self.layers[index].deleted = true;
+        }
+        self.top_layers.retain(|&top| top != layer);
+        self.dispose(self.layers[layer].group);
+
+        if self.layers[self.current_layer].deleted {
+            self.current_layer = self.top_layers[0];
+        }
+        Ok(())
+    }
+}
+
+fn with_layer<R>(layer: AILayerHandle, f: impl FnOnce(&mut ArtTree, usize) -> Result<R, AIErr>) -> Result<R, AIErr> {
+    with_state(|state| {
+        let index = state.art.layer_index(layer).ok_or(BAD_PARAMETER)?;
+        f(&mut state.art, index)
+    })
+}
+
+/// 成功したら `out` に書き込む
+unsafe fn write<T>(out: *mut T, result: Result<T, AIErr>) -> AIErr {
+    match result {
+        Ok(value) => {
+            if out.is_null() {
+                return BAD_PARAMETER;
+            }
+            *out = value;
+            NO_ERR
+        }
+        Err(err) => err,
+    }
+}
+
+fn handle(layer: Option<usize>) -> AILayerHandle {
+    layer.map_or(null_mut(), layer_handle)
+}
+
+/// `AILayerSuite` のスタンドイン
+///
+/// レイヤーは `AIArtSuite` のアートツリーのレイヤーグループと対応する。
+pub(crate) fn suite() -> AILayerSuite {
+    let mut suite: AILayerSuite = unsafe { std::mem::zeroed() };
+    suite.CountLayers = Some(count_layers);
+    suite.GetNthLayer = Some(get_nth_layer);
+    suite.GetCurrentLayer = Some(get_current_layer);
+    suite.SetCurrentLayer = Some(set_current_layer);
+    suite.GetFirstLayer = Some(get_first_layer);
+    suite.GetNextLayer = Some(get_next_layer);
+    suite.GetPrevLayer = Some(get_prev_layer);
+    suite.GetNextPreorderLayer = Some(get_next_preorder_layer);
+    suite.GetLayerFirstChild = Some(get_layer_first_child);
+    suite.GetLayerParent = Some(get_layer_parent);
+    suite.InsertLayer = Some(insert_layer);
+    suite.DeleteLayer = Some(delete_layer);
+    suite.GetLayerTitle = Some(get_layer_title);
+    suite.SetLayerTitle = Some(set_layer_title);
+    suite.GetLayerByTitle = Some(get_layer_by_title);
+    suite.GetLayerColor = Some(get_layer_color);
+    suite.SetLayerColor = Some(set_layer_color);
+    suite.GetLayerVisible = Some(get_layer_visible);
+    suite.SetLayerVisible = Some(set_layer_visible);
+    suite.GetLayerEditable = Some(get_layer_editable);
+    suite.SetLayerEditable = Some(set_layer_editable);
+    suite.GetLayerPrinted = Some(get_layer_printed);
+    suite.SetLayerPrinted = Some(set_layer_printed);
+    suite.GetLayerPreview = Some(get_layer_preview);
+    suite.SetLayerPreview = Some(set_layer_preview);
+    suite.GetLayerIsTemplate = Some(get_layer_is_template);
+    suite.SetLayerIsTemplate = Some(set_layer_is_template);
+    suite.GetLayerDimPlacedImages = Some(get_layer_dim_placed_images);
+    suite.SetLayerDimPlacedImages = Some(set_layer_dim_placed_images);
+    suite.GetLayerSelected = Some(get_layer_selected);
+    suite.SetLayerSelected = Some(set_layer_selected);
+    suite.LayerHasArt = Some(layer_has_art);
+    suite.LayerHasSelectedArt = Some(layer_has_selected_art);
+    suite.SelectArtOnLayer = Some(select_art_on_layer);
+    suite.DeselectArtOnLayer = Some(deselect_art_on_layer);
+    suite
+}
+
+unsafe extern "C" fn count_layers(count: *mut ai_int32) -> AIErr {
+    write(count, Ok(with_state(|state| state.art.top_layers.len() as ai_int32)))
+}
+
+unsafe extern "C" fn get_nth_layer(n: ai_int32, layer: *mut AILayerHandle) -> AIErr {
+    let result = with_state(|state| {
+        let n = usize::try_from(n).map_err(|_| BAD_PARAMETER)?;
+        state.art.top_layers.get(n).map(|&index| layer_handle(index)).ok_or(BAD_PARAMETER)
+    });
+    write(layer, result)
+}
+
+unsafe extern "C" fn get_current_layer(layer: *mut AILayerHandle) -> AIErr {
+    write(layer, Ok(with_state(|state| layer_handle(state.art.current_layer))))
+}
+
+unsafe extern "C" fn set_current_layer(layer: AILayerHandle) -> AIErr {
+    status(with_layer(layer, |tree, index| {
+        tree.current_layer = index;
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn get_first_layer(first: *mut AILayerHandle) -> AIErr {
+    write(first, Ok(with_state(|state| handle(state.art.top_layers.first().copied()))))
+}
+
+/// 同じ階層で `offset` だけ離れたレイヤー（無ければ `null`）
+fn sibling(tree: &ArtTree, index: usize, offset: isize) -> AILayerHandle {
+    let siblings = tree.sibling_layers(index);
+    let position = siblings.iter().position(|&layer| layer == index);
+    let target = position.and_then(|position| position.checked_add_signed(offset));
+    handle(target.and_then(|target| siblings.get(target).copied()))
+}
+
+unsafe extern "C" fn get_next_layer(prev: AILayerHandle, next: *mut AILayerHandle) -> AIErr {
+    write(next, with_layer(prev, |tree, index| Ok(sibling(tree, index, 1))))
+}
+
+unsafe extern "C" fn get_prev_layer(next: AILayerHandle, prev: *mut AILayerHandle) -> AIErr {
+    write(prev, with_layer(next, |tree, index| Ok(sibling(tree, index, -1))))
+}
+
+unsafe extern "C" fn get_next_preorder_layer(prev: AILayerHandle, next: *mut AILayerHandle) -> AIErr {
+    let result = with_layer(prev, |tree, index| {
+        let order = tree.preorder_layers();
+        let position = order.iter().position(|&layer| layer == index);
+        Ok(handle(position.and_then(|position| order.get(position + 1).copied())))
+    });
+    write(next, result)
+}
+
+unsafe extern "C" fn get_layer_first_child(layer: AILayerHandle, child: *mut AILayerHandle) -> AIErr {
+    write(child, with_layer(layer, |tree, index| Ok(handle(tree.sublayers(index).first().copied()))))
+}
+
+unsafe extern "C" fn get_layer_parent(layer: AILayerHandle, parent: *mut AILayerHandle) -> AIErr {
+    write(parent, with_layer(layer, |tree, index| Ok(handle(tree.layer_parent(index)))))
+}
+
+unsafe extern "C" fn insert_layer(layer: AILayerHandle, paint_order: ai_int16, new_layer: *mut AILayerHandle) -> AIErr {
+    let result = with_state(|state| {
+        let relative = if layer.is_null() {
+            None
+        } else {
+            Some(state.art.layer_index(layer).ok_or(BAD_PARAMETER)?)
+        };
+
+        let title = format!("Layer {}", state.art.layers.len() + 1);
+        let index = state.art.new_layer(&title);
+        if let Err(err) = state.art.position_layer(index, paint_order, relative) {
+            state.art.delete_layer(index)?;
+            return Err(err);
+        }
+
+        state.events.push(HostEvent::InsertLayer(title));
+        Ok(layer_handle(index))
+    });
+    write(new_layer, result)
+}
+
+unsafe extern "C" fn delete_layer(layer: AILayerHandle) -> AIErr {
+    status(with_state(|state| {
+        let index = state.art.layer_index(layer).ok_or(BAD_PARAMETER)?;
+        state.art.delete_layer(index)?;
+        state.events.push(HostEvent::DeleteLayer(state.art.layers[index].title.clone()));
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn get_layer_title(layer: AILayerHandle, title: *mut ai_UnicodeString) -> AIErr {
+    status(with_layer(layer, |tree, index| {
+        unicode::write(title, &tree.layers[index].title);
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn set_layer_title(layer: AILayerHandle, new_title: *const ai_UnicodeString) -> AIErr {
+    status(with_layer(layer, |tree, index| {
+        tree.layers[index].title = unicode::read(new_title);
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn get_layer_by_title(layer: *mut AILayerHandle, title: *const ai_UnicodeString) -> AIErr {
+    let title = unicode::read(title);
+    let result = with_state(|state| {
+        let tree = &state.art;
+        let found = tree.preorder_layers().into_iter().find(|&index| tree.layers[index].title == title);
+        Ok(handle(found))
+    });
+    write(layer, result)
+}
+
+unsafe extern "C" fn get_layer_color(layer: AILayerHandle, color: *mut AIRGBColor) -> AIErr {
+    write(color, with_layer(layer, |tree, index| Ok(tree.layers[index].color)))
+}
+
+unsafe extern "C" fn set_layer_color(layer: AILayerHandle, color: AIRGBColor) -> AIErr {
+    status(with_layer(layer, |tree, index| {
+        tree.layers[index].color = color;
+        Ok(())
+    }))
+}
+
+/// `LayerEntry` の真偽値フィールドの getter/setter を定義する
+macro_rules! layer_flag {
+    ($get:ident, $set:ident, $field:ident) => {
+        unsafe extern "C" fn $get(layer: AILayerHandle, value: *mut AIBoolean) -> AIErr {
+            write(value, with_layer(layer, |tree, index| Ok(tree.layers[index].$field as AIBoolean)))
+        }
+
+        unsafe extern "C" fn $set(layer: AILayerHandle, value: AIBoolean) -> AIErr {
+            status(with_layer(layer, |tree, index| {
+                tree.layers[index].$field = value != 0;
+                Ok(())
+            }))
+        }
+    };
+}
+
+layer_flag!(get_layer_visible, set_layer_visible, visible);
+layer_flag!(get_layer_editable, set_layer_editable, editable);
+layer_flag!(get_layer_printed, set_layer_printed, printed);
+layer_flag!(get_layer_preview, set_layer_preview, preview);
+layer_flag!(get_layer_is_template, set_layer_is_template, template);
+layer_flag!(get_layer_dim_placed_images, set_layer_dim_placed_images, dim_placed_images);
+layer_flag!(get_layer_selected, set_layer_selected, selected);
+
+/// レイヤーに直接属するアート（サブレイヤーの中身は含まない）
+fn layer_art(tree: &ArtTree, layer: usize) -> Vec<usize> {
+    fn visit(tree: &ArtTree, index: usize, out: &mut Vec<usize>) {
+        for &child in &tree.nodes[index].children {
+            if !tree.nodes[child].layer_group {
+                out.push(child);
+                visit(tree, child, out);
+            }
+        }
+    }
+
+    let mut art = Vec::new();
+    visit(tree, tree.layers[layer].group, &mut art);
+    art
+}
+
+const SELECTED: ai_int32 = AIArtUserAttr_kArtSelected as ai_int32;
+
+unsafe extern "C" fn layer_has_art(layer: AILayerHandle, has_art: *mut AIBoolean) -> AIErr {
+    write(has_art, with_layer(layer, |tree, index| Ok(!layer_art(tree, index).is_empty() as AIBoolean)))
+}
+
+unsafe extern "C" fn layer_has_selected_art(layer: AILayerHandle, has_selected: *mut AIBoolean) -> AIErr {
+    let result = with_layer(layer, |tree, index| {
+        let selected = layer_art(tree, index)
+            .into_iter()
+            .any(|art| tree.nodes[art].user_attr & SELECTED != 0);
+        Ok(selected as AIBoolean)
+    });
+    write(has_selected, result)
+}
+
+unsafe extern "C" fn select_art_on_layer(layer: AILayerHandle) -> AIErr {
+    status(with_layer(layer, |tree, index| {
+        for art in layer_art(tree, index) {
+            tree.nodes[art].user_attr |= SELECTED;
+        }
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn deselect_art_on_layer(layer: AILayerHandle) -> AIErr {
+    status(with_layer(layer, |tree, index| {
+        for art in layer_art(tree, index) {
+            tree.nodes[art].user_attr &= !SELECTED;
+        }
+        Ok(())
+    }))
+}
+
+/// `AILayerListSuite` のスタンドイン
+///
+/// スタックにはドキュメントのレイヤーリストだけがあり、`Push`/`Pop` は実装しない。
+pub(crate) fn list_suite() -> AILayerListSuite {
+    let mut suite: AILayerListSuite = unsafe { std::mem::zeroed() };
+    suite.Count = Some(list_count);
+    suite.GetFirst = Some(list_get_first);
+    suite.GetLast = Some(list_get_first);
+    suite.GetNext = Some(list_get_next);
+    suite.GetLayerOfArt = Some(list_get_layer_of_art);
+    suite.GetTag = Some(list_get_tag);
+    suite.CountLayers = Some(list_count_layers);
+    suite.GetFirstLayer = Some(list_get_first_layer);
+    suite.GetLastLayer = Some(list_get_last_layer);
+    suite.GetNextLayer = Some(list_get_next_layer);
+    suite.GetPrevLayer = Some(list_get_prev_layer);
+    suite
+}
+
+fn document_list() -> AILayerList {
+    DOCUMENT_LAYER_LIST as AILayerList
+}
+
+fn check_list(list: AILayerList) -> Result<(), AIErr> {
+    if list != document_list() {
+        return Err(BAD_PARAMETER);
+    }
+    Ok(())
+}
+
+unsafe extern "C" fn list_count(count: *mut ai_int32) -> AIErr {
+    write(count, Ok(1))
+}
+
+unsafe extern "C" fn list_get_first(list: *mut AILayerList) -> AIErr {
+    write(list, Ok(document_list()))
+}
+
+unsafe extern "C" fn list_get_next(list: AILayerList, next: *mut AILayerList) -> AIErr {
+    write(next, check_list(list).map(|_| null_mut()))
+}
+
+unsafe extern "C" fn list_get_layer_of_art(art: AIArtHandle, list: *mut AILayerList, layer: *mut AILayerHandle) -> AIErr {
+    let result = with_state(|state| {
+        let index = state.art.get(art).ok_or(BAD_PARAMETER)?;
+        Ok(layer_handle(state.art.nodes[index].layer))
+    });
+
+    match result {
+        Ok(found) => {
+            if !list.is_null() {
+                *list = document_list();
+            }
+            write(layer, Ok(found))
+        }
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn list_get_tag(list: AILayerList) -> *const std::ffi::c_char {
+    match check_list(list) {
+        Ok(()) => kAIDocumentLayerList.as_ptr() as *const std::ffi::c_char,
+        Err(_) => std::ptr::null(),
+    }
+}
+
+unsafe extern "C" fn list_count_layers(list: AILayerList, count: *mut ai_int32) -> AIErr {
+    let result = check_list(list).map(|_| with_state(|state| state.art.top_layers.len() as ai_int32));
+    write(count, result)
+}
+
+unsafe extern "C" fn list_get_first_layer(list: AILayerList, layer: *mut AILayerHandle) -> AIErr {
+    let result = check_list(list).map(|_| with_state(|state| handle(state.art.top_layers.first().copied())));
+    write(layer, result)
+}
+
+unsafe extern "C" fn list_get_last_layer(list: AILayerList, layer: *mut AILayerHandle) -> AIErr {
+    let result = check_list(list).map(|_| with_state(|state| handle(state.art.top_layers.last().copied())));
+    write(layer, result)
+}
+
+unsafe extern "C" fn list_get_next_layer(list: AILayerList, layer: AILayerHandle, next: *mut AILayerHandle) -> AIErr {
+    if let Err(err) = check_list(list) {
+        return err;
+    }
+    get_next_layer(layer, next)
+}
+
+unsafe extern "C" fn list_get_prev_layer(list: AILayerList, layer: AILayerHandle, prev: *mut AILayerHandle) -> AIErr {
+    if let Err(err) = check_list(list) {
+        return err;
+    }
+    get_prev_layer(layer, prev)
+}
+
@@ -0,0 +1,60 @@
+//! Illustrator を起動せずにプラグインを動かすためのホストシミュレータ
+//!
+//! `SPBasicSuite` の `AcquireSuite`/`ReleaseSuite` と、`SPPluginsSuite`・`AINotifierSuite`・
+//! `SPAccessSuite`・`AIUserSuite`・`AIArtSuite`（アートツリー）・`AIPathSuite`・`AIPathStyleSuite`・
+//! `AILayerSuite`・`AILayerListSuite`・`AIDocumentSuite`・`AIDocumentViewSuite`・`AIArtboardSuite`・
+//! `AIArtboardRangeSuite`・`AIDictionarySuite`・`AIEntrySuite`・`AIDictionaryIteratorSuite`・`AIArraySuite`・
+//! `AIPreferenceSuite`・`AIMenuSuite`・`AIToolSuite`・`AITimerSuite`・`AILiveEffectSuite`・`AIPluginGroupSuite`・
+//! `AIFileFormatSuite`・`AIFilterSuite`・`AIActionManagerSuite`（イベントの登録・記録とパラメータブロック）・
+//! `AIFilePathSuite`・`AIUndoSuite`（アートツリーのみ戻す）・`SPBlocksSuite` のスタンドインを提供します。
+//! `define_plugin!` が生成した `PluginMain` に startup → notify → menu → shutdown を送り、
+//! プラグインが行ったスイート呼び出しを [`HostEvent`] として検証できます。
+//! 起動中のプラグインは `MockHost::with_plugin` で借り、スイートを使う処理は `MockHost::run_in_message` で
+//! プラグインへのメッセージの中で実行します。
+//!
+//! ```no_run
+//! use illustrator_mock::{HostEvent, MockHost};
+//!
+//! # extern "C" fn PluginMain(_: *mut std::ffi::c_char, _: *mut std::ffi::c_char, _: *mut std::ffi::c_void) -> i32 { 0 }
+//! let mut host = MockHost::new(PluginMain);
+//! assert_eq!(host.startup(), 0);
+//! assert_eq!(host.app_started(), 0);
+//! assert_eq!(host.shutdown(), 0);
+//! assert!(host.events().contains(&HostEvent::SetPluginName("My Plugin".into())));
+//! ```
+
+mod state;
+mod basic;
+mod plugins;
+mod notifier;
+mod access;
+mod user;
+mod art;
+mod path;
+mod path_style;
+mod layer;
+mod document;
+mod artboard;
+mod dictionary;
+mod preferences;
+mod menu;
+mod tool;
+mod timer;
+mod live_effect;
+mod plugin_group;
+mod file_format;
+mod filter;
+mod action;
+mod file_path;
+mod undo;
+mod host;
+
+pub mod unicode;
+
+pub use action::{ActionParamInfo, ActionValue, RecordedAction};
+pub use host::{
+    ActionEventInfo, ArtboardInfo, FileFormatInfo, FilterInfo, HostMessage, LayerInfo, LiveEffectInfo, MenuItemInfo,
+    MockHost, PluginEntry, PluginGroupInfo, ToolInfo, UndoInfo,
+};
+pub use preferences::PreferenceValue;
+pub use state::{cstr, HostEvent};
@@ -0,0 +1,207 @@
+use std::ffi::{c_char, CStr, CString};
+
+use illustrator_sys::*;
+
+use crate::art::BAD_PARAMETER;
+use crate::dictionary;
+use crate::state::{lossy, with_state, HostEvent, HostState, NO_ERR};
+
+/// プラグインが登録したライブエフェクト
+pub(crate) struct LiveEffectEntry {
+    pub name: CString,
+    pub title: CString,
+    pub version: (i32, i32),
+    pub input_preference: i32,
+    pub style_filter_flags: i32,
+    /// 効果メニューの（カテゴリ, 項目名）
+    pub menu: Option<(String, String)>,
+}
+
+/// `AILiveEffectSuite` のスタンドイン
+///
+/// 編集中のパラメータのコンテキストはエフェクトのハンドルと同じ値（`MockHost::edit_live_effect`）。
+pub(crate) fn suite() -> AILiveEffectSuite {
+    let mut suite: AILiveEffectSuite = unsafe { std::mem::zeroed() };
+    suite.AddLiveEffect = Some(add_live_effect);
+    suite.AddLiveEffectMenuItem = Some(add_live_effect_menu_item);
+    suite.UpdateParameters = Some(update_parameters);
+    suite.CountLiveEffects = Some(count_live_effects);
+    suite.GetNthLiveEffect = Some(get_nth_live_effect);
+    suite.GetLiveEffectName = Some(get_live_effect_name);
+    suite.GetLiveEffectTitle = Some(get_live_effect_title);
+    suite.GetLiveEffectVersion = Some(get_live_effect_version);
+    suite.GetInputPreference = Some(get_input_preference);
+    suite.GetStyleFilterFlags = Some(get_style_filter_flags);
+    suite.CreateLiveEffectParameters = Some(create_live_effect_parameters);
+    suite.GetLiveEffectHandleByName = Some(get_live_effect_handle_by_name);
+    suite
+}
+
+/// ハンドルは登録順の通し番号から作る
+pub(crate) fn live_effect_handle(index: usize) -> AILiveEffectHandle {
+    (0x7000 + index * 8) as AILiveEffectHandle
+}
+
+pub(crate) fn live_effect_index(state: &HostState, effect: AILiveEffectHandle) -> Option<usize> {
+    let index = (effect as usize).checked_sub(0x7000)?;
+    (index % 8 == 0).then_some(index / 8).filter(|&index| index < state.live_effects.len())
+}
+
+/// 有効なエフェクトに対して `f` を呼ぶ（無効なハンドルは `kBadParameterErr`）
+fn with_live_effect(effect: AILiveEffectHandle, f: impl FnOnce(&mut LiveEffectEntry)) -> AIErr {
+    with_state(|state| match live_effect_index(state, effect) {
+        Some(index) => {
+            f(&mut state.live_effects[index]);
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn add_live_effect(data: *mut AILiveEffectData, effect: *mut AILiveEffectHandle) -> AIErr {
+    if data.is_null() || (*data).name.is_null() || (*data).title.is_null() {
+        return BAD_PARAMETER;
+    }
+    let data = &*data;
+    let entry = LiveEffectEntry {
+        name: CStr::from_ptr(data.name).to_owned(),
+        title: CStr::from_ptr(data.title).to_owned(),
+        version: (data.majorVersion, data.minorVersion),
+        input_preference: data.prefersAsInput,
+        style_filter_flags: data.styleFilterFlags,
+        menu: None,
+    };
+
+    with_state(|state| {
+        if state.live_effects.iter().any(|existing| existing.name == entry.name) {
+            return BAD_PARAMETER;
+        }
+
+        let handle = live_effect_handle(state.live_effects.len());
+        state.events.push(HostEvent::AddLiveEffect(lossy(data.name)));
+        state.live_effects.push(entry);
+
+        if !effect.is_null() {
+            *effect = handle;
+        }
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn add_live_effect_menu_item(
+    effect: AILiveEffectHandle,
+    _menu_name: *const c_char,
+    data: *mut AddLiveEffectMenuData,
+    menu_item: *mut AIMenuItemHandle,
+    menu_group: *mut AIMenuGroup,
+) -> AIErr {
+    if data.is_null() {
+        return BAD_PARAMETER;
+    }
+    let menu = (lossy((*data).category), lossy((*data).title));
+    let err = with_live_effect(effect, |entry| entry.menu = Some(menu));
+
+    // 効果メニューは再現しないので、項目とグループは返さない
+    if err == NO_ERR {
+        if !menu_item.is_null() {
+            *menu_item = std::ptr::null_mut();
+        }
+        if !menu_group.is_null() {
+            *menu_group = std::ptr::null_mut();
+        }
+    }
+    err
+}
+
+unsafe extern "C" fn update_parameters(context: AILiveEffectParamContext) -> AIErr {
+    with_state(|state| match live_effect_index(state, context as AILiveEffectHandle) {
+        Some(index) => {
+            let name = state.live_effects[index].name.to_string_lossy().into_owned();
+            state.events.push(HostEvent::UpdateLiveEffectParameters(name));
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn count_live_effects(count: *mut ai_int32) -> AIErr {
+    if count.is_null() {
+        return BAD_PARAMETER;
+    }
+    *count = with_state(|state| state.live_effects.len() as ai_int32);
+    NO_ERR
+}
+
+unsafe extern "C" fn get_nth_live_effect(n: ai_int32, effect: *mut AILiveEffectHandle) -> AIErr {
+    if effect.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_state(|state| match usize::try_from(n).ok().filter(|&n| n < state.live_effects.len()) {
+        Some(index) => {
+            *effect = live_effect_handle(index);
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn get_live_effect_name(effect: AILiveEffectHandle, name: *mut *const c_char) -> AIErr {
+    if name.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_live_effect(effect, |entry| *name = entry.name.as_ptr())
+}
+
+unsafe extern "C" fn get_live_effect_title(effect: AILiveEffectHandle, title: *mut *const c_char) -> AIErr {
+    if title.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_live_effect(effect, |entry| *title = entry.title.as_ptr())
+}
+
+unsafe extern "C" fn get_live_effect_version(
+    effect: AILiveEffectHandle,
+    major: *mut ai_int32,
+    minor: *mut ai_int32,
+) -> AIErr {
+    if major.is_null() || minor.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_live_effect(effect, |entry| (*major, *minor) = entry.version)
+}
+
+unsafe extern "C" fn get_input_preference(effect: AILiveEffectHandle, preference: *mut ai_int32) -> AIErr {
+    if preference.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_live_effect(effect, |entry| *preference = entry.input_preference)
+}
+
+unsafe extern "C" fn get_style_filter_flags(effect: AILiveEffectHandle, flags: *mut ai_int32) -> AIErr {
+    if flags.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_live_effect(effect, |entry| *flags = entry.style_filter_flags)
+}
+
+unsafe extern "C" fn create_live_effect_parameters(parameters: *mut AILiveEffectParameters) -> AIErr {
+    if parameters.is_null() {
+        return BAD_PARAMETER;
+    }
+    *parameters = dictionary::new_dictionary();
+    NO_ERR
+}
+
+unsafe extern "C" fn get_live_effect_handle_by_name(name: *const c_char, effect: *mut AILiveEffectHandle) -> AIErr {
+    if name.is_null() || effect.is_null() {
+        return BAD_PARAMETER;
+    }
+    let name = CStr::from_ptr(name);
+    with_state(|state| match state.live_effects.iter().position(|entry| entry.name.as_c_str() == name) {
+        Some(index) => {
+            *effect = live_effect_handle(index);
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
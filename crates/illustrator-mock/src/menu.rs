@@ -0,0 +1,237 @@
+use std::ffi::{c_char, CStr, CString};
+
+use illustrator_sys::*;
+
+use crate::art::BAD_PARAMETER;
+use crate::state::{lossy, with_state, HostEvent, HostState, NO_ERR};
+use crate::unicode;
+
+/// プラグインが追加したメニュー項目
+pub(crate) struct MenuItemEntry {
+    pub key: String,
+    pub group: String,
+    pub text: String,
+    pub options: i32,
+    pub enabled: bool,
+    pub checked: bool,
+    /// `RemoveMenuItem` 済み（ハンドルを使い回さないため残しておく）
+    pub removed: bool,
+}
+
+/// 追加されたメニューグループ
+pub(crate) struct MenuGroupEntry {
+    pub name: CString,
+    /// サブメニューなら親の項目
+    pub parent: Option<usize>,
+}
+
+/// `AIMenuSuite` のスタンドイン
+///
+/// グループの配置は記録するだけで、組み込みのグループ（`kOpenMenuGroup` など）は存在するものとして扱う。
+pub(crate) fn suite() -> AIMenuSuite {
+    let mut suite: AIMenuSuite = unsafe { std::mem::zeroed() };
+    suite.AddMenuItem = Some(add_menu_item);
+    suite.GetMenuItemOptions = Some(get_menu_item_options);
+    suite.SetMenuItemOptions = Some(set_menu_item_options);
+    suite.EnableItem = Some(enable_item);
+    suite.DisableItem = Some(disable_item);
+    suite.IsItemEnabled = Some(is_item_enabled);
+    suite.CheckItem = Some(check_item);
+    suite.IsItemChecked = Some(is_item_checked);
+    suite.GetItemText = Some(get_item_text);
+    suite.SetItemText = Some(set_item_text);
+    suite.RemoveMenuItem = Some(remove_menu_item);
+    suite.AddMenuGroup = Some(add_menu_group);
+    suite.AddMenuGroupAsSubMenu = Some(add_menu_group_as_sub_menu);
+    suite.GetMenuGroupName = Some(get_menu_group_name);
+    suite
+}
+
+/// ハンドルは追加順の通し番号から作る
+pub(crate) fn item_handle(index: usize) -> AIMenuItemHandle {
+    (0x3000 + index * 8) as AIMenuItemHandle
+}
+
+fn group_handle(index: usize) -> AIMenuGroup {
+    (0x4000 + index * 8) as AIMenuGroup
+}
+
+fn item_index(state: &HostState, item: AIMenuItemHandle) -> Option<usize> {
+    let index = (item as usize).checked_sub(0x3000)?;
+    (index % 8 == 0)
+        .then_some(index / 8)
+        .filter(|&index| state.menu_items.get(index).is_some_and(|entry| !entry.removed))
+}
+
+fn group_index(state: &HostState, group: AIMenuGroup) -> Option<usize> {
+    let index = (group as usize).checked_sub(0x4000)?;
+    (index % 8 == 0).then_some(index / 8).filter(|&index| index < state.menu_groups.len())
+}
+
+/// 有効な項目に対して `f` を呼ぶ（無効なハンドルは `kBadParameterErr`）
+fn with_item(item: AIMenuItemHandle, f: impl FnOnce(&mut MenuItemEntry)) -> AIErr {
+    with_state(|state| match item_index(state, item) {
+        Some(index) => {
+            f(&mut state.menu_items[index]);
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn add_menu_item(
+    _plugin: SPPluginRef,
+    key: *const c_char,
+    data: *mut AIPlatformAddMenuItemDataUS,
+    options: AIMenuItemOption,
+    menu_item: *mut AIMenuItemHandle,
+) -> AIErr {
+    if key.is_null() || data.is_null() || (*data).groupName.is_null() {
+        return BAD_PARAMETER;
+    }
+    let entry = MenuItemEntry {
+        key: lossy(key),
+        group: lossy((*data).groupName),
+        text: unicode::read(&(*data).itemText),
+        options,
+        enabled: true,
+        checked: false,
+        removed: false,
+    };
+
+    with_state(|state| {
+        let handle = item_handle(state.menu_items.len());
+        state.events.push(HostEvent::AddMenuItem(entry.key.clone()));
+        state.menu_items.push(entry);
+
+        if !menu_item.is_null() {
+            *menu_item = handle;
+        }
+    });
+    NO_ERR
+}
+
+unsafe extern "C" fn get_menu_item_options(item: AIMenuItemHandle, options: *mut AIMenuItemOption) -> AIErr {
+    if options.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_item(item, |entry| *options = entry.options)
+}
+
+unsafe extern "C" fn set_menu_item_options(item: AIMenuItemHandle, options: AIMenuItemOption) -> AIErr {
+    with_item(item, |entry| entry.options = options)
+}
+
+unsafe extern "C" fn enable_item(item: AIMenuItemHandle) -> AIErr {
+    with_item(item, |entry| entry.enabled = true)
+}
+
+unsafe extern "C" fn disable_item(item: AIMenuItemHandle) -> AIErr {
+    with_item(item, |entry| entry.enabled = false)
+}
+
+unsafe extern "C" fn is_item_enabled(item: AIMenuItemHandle, enabled: *mut ASBoolean) -> AIErr {
+    if enabled.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_item(item, |entry| *enabled = entry.enabled as ASBoolean)
+}
+
+unsafe extern "C" fn check_item(item: AIMenuItemHandle, checked: AIBoolean) -> AIErr {
+    with_item(item, |entry| entry.checked = checked != 0)
+}
+
+unsafe extern "C" fn is_item_checked(item: AIMenuItemHandle, checked: *mut AIBoolean) -> AIErr {
+    if checked.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_item(item, |entry| *checked = entry.checked as AIBoolean)
+}
+
+unsafe extern "C" fn get_item_text(item: AIMenuItemHandle, text: *mut ai_UnicodeString) -> AIErr {
+    if text.is_null() {
+        return BAD_PARAMETER;
+    }
+    let mut stored = None;
+    let err = with_item(item, |entry| stored = Some(entry.text.clone()));
+    if let Some(stored) = stored {
+        unicode::write(text, &stored);
+    }
+    err
+}
+
+unsafe extern "C" fn set_item_text(item: AIMenuItemHandle, text: *const ai_UnicodeString) -> AIErr {
+    if text.is_null() {
+        return BAD_PARAMETER;
+    }
+    let text = unicode::read(text);
+    with_item(item, |entry| entry.text = text)
+}
+
+unsafe extern "C" fn remove_menu_item(item: AIMenuItemHandle) -> AIErr {
+    let mut key = None;
+    let err = with_item(item, |entry| {
+        entry.removed = true;
+        key = Some(entry.key.clone());
+    });
+    if let Some(key) = key {
+        with_state(|state| state.events.push(HostEvent::RemoveMenuItem(key)));
+    }
+    err
+}
+
+/// 同名のグループがあればそれを、なければ新しく追加して返す
+fn add_group(state: &mut HostState, name: &CStr, parent: Option<usize>) -> AIMenuGroup {
+    if let Some(index) = state.menu_groups.iter().position(|group| group.name.as_c_str() == name) {
+        return group_handle(index);
+    }
+    state.menu_groups.push(MenuGroupEntry {
+        name: name.to_owned(),
+        parent,
+    });
+    group_handle(state.menu_groups.len() - 1)
+}
+
+unsafe extern "C" fn add_menu_group(
+    name: *const c_char,
+    _options: AIMenuGroupOption,
+    near_group: *const c_char,
+    group: *mut AIMenuGroup,
+) -> AIErr {
+    if name.is_null() || near_group.is_null() || group.is_null() {
+        return BAD_PARAMETER;
+    }
+    *group = with_state(|state| add_group(state, CStr::from_ptr(name), None));
+    NO_ERR
+}
+
+unsafe extern "C" fn add_menu_group_as_sub_menu(
+    name: *const c_char,
+    _options: AIMenuGroupOption,
+    item: AIMenuItemHandle,
+    group: *mut AIMenuGroup,
+) -> AIErr {
+    if name.is_null() || group.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_state(|state| match item_index(state, item) {
+        Some(parent) => {
+            *group = add_group(state, CStr::from_ptr(name), Some(parent));
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn get_menu_group_name(group: AIMenuGroup, name: *mut *const c_char) -> AIErr {
+    if name.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_state(|state| match group_index(state, group) {
+        Some(index) => {
+            *name = state.menu_groups[index].name.as_ptr();
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
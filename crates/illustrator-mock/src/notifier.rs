@@ -0,0 +1,125 @@
+use std::ffi::{c_char, c_void, CStr, CString};
+
+use illustrator_sys::*;
+
+use crate::state::{lossy, with_state, HostEvent, NO_ERR};
+
+/// プラグインが登録した通知
+pub(crate) struct NotifierEntry {
+    pub handle: AINotifierHandle,
+    pub name: CString,
+    pub notifier_type: CString,
+    pub active: bool,
+}
+
+/// `AINotifierSuite` のスタンドイン
+pub(crate) fn suite() -> AINotifierSuite {
+    let mut suite: AINotifierSuite = unsafe { std::mem::zeroed() };
+    suite.AddNotifier = Some(add_notifier);
+    suite.GetNotifierName = Some(get_notifier_name);
+    suite.GetNotifierType = Some(get_notifier_type);
+    suite.GetNotifierActive = Some(get_notifier_active);
+    suite.SetNotifierActive = Some(set_notifier_active);
+    suite.CountNotifiers = Some(count_notifiers);
+    suite.GetNthNotifier = Some(get_nth_notifier);
+    suite.Notify = Some(notify);
+    suite
+}
+
+/// ハンドルは登録順の通し番号から作る（ホスト外では参照されない）
+fn handle_for(index: usize) -> AINotifierHandle {
+    (0x1000 + index * 8) as AINotifierHandle
+}
+
+unsafe extern "C" fn add_notifier(
+    _plugin: SPPluginRef,
+    name: *const c_char,
+    notifier_type: *const c_char,
+    notifier: *mut AINotifierHandle,
+) -> AIErr {
+    with_state(|state| {
+        let handle = handle_for(state.notifiers.len());
+
+        state.events.push(HostEvent::AddNotifier {
+            name: lossy(name),
+            notifier_type: lossy(notifier_type),
+        });
+        state.notifiers.push(NotifierEntry {
+            handle,
+            name: CStr::from_ptr(name).to_owned(),
+            notifier_type: CStr::from_ptr(notifier_type).to_owned(),
+            active: true,
+        });
+
+        if !notifier.is_null() {
+            *notifier = handle;
+        }
+    });
+    NO_ERR
+}
+
+fn with_notifier<R>(handle: AINotifierHandle, f: impl FnOnce(&mut NotifierEntry) -> R) -> Result<R, AIErr> {
+    with_state(|state| {
+        state
+            .notifiers
+            .iter_mut()
+            .find(|n| n.handle == handle)
+            .map(f)
+            .ok_or(kBadParameterErr as AIErr)
+    })
+}
+
+unsafe extern "C" fn get_notifier_name(notifier: AINotifierHandle, name: *mut *const c_char) -> AIErr {
+    match with_notifier(notifier, |n| n.name.as_ptr()) {
+        Ok(ptr) => { *name = ptr; NO_ERR }
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn get_notifier_type(notifier: AINotifierHandle, notifier_type: *mut *const c_char) -> AIErr {
+    match with_notifier(notifier, |n| n.notifier_type.as_ptr()) {
+        Ok(ptr) => { *notifier_type = ptr; NO_ERR }
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn get_notifier_active(notifier: AINotifierHandle, active: *mut AIBoolean) -> AIErr {
+    match with_notifier(notifier, |n| n.active) {
+        Ok(value) => { *active = value as AIBoolean; NO_ERR }
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn set_notifier_active(notifier: AINotifierHandle, active: AIBoolean) -> AIErr {
+    let result = with_notifier(notifier, |n| {
+        n.active = active != 0;
+        n.notifier_type.to_string_lossy().into_owned()
+    });
+
+    match result {
+        Ok(notifier_type) => {
+            with_state(|state| {
+                state.events.push(HostEvent::SetNotifierActive { notifier_type, active: active != 0 })
+            });
+            NO_ERR
+        }
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn count_notifiers(count: *mut ai_int32) -> AIErr {
+    *count = with_state(|state| state.notifiers.len() as ai_int32);
+    NO_ERR
+}
+
+unsafe extern "C" fn get_nth_notifier(n: ai_int32, notifier: *mut AINotifierHandle) -> AIErr {
+    match with_state(|state| state.notifiers.get(n as usize).map(|e| e.handle)) {
+        Some(handle) => { *notifier = handle; NO_ERR }
+        None => kBadParameterErr as AIErr,
+    }
+}
+
+unsafe extern "C" fn notify(notifier_type: *const c_char, _notify_data: *mut c_void) -> AIErr {
+    with_state(|state| state.events.push(HostEvent::BroadcastNotify(lossy(notifier_type))));
+    NO_ERR
+}
@@ -0,0 +1,148 @@
+use illustrator_sys::*;
+
+use crate::art::{status, with_art, BAD_PARAMETER};
+use crate::state::{HostState, NO_ERR};
+
+/// `AIPathSuite` のスタンドイン
+///
+/// セグメントは `AIArtSuite` のアートツリーのノードに保持する。
+pub(crate) fn suite() -> AIPathSuite {
+    let mut suite: AIPathSuite = unsafe { std::mem::zeroed() };
+    suite.GetPathSegmentCount = Some(get_path_segment_count);
+    suite.SetPathSegmentCount = Some(set_path_segment_count);
+    suite.GetPathSegments = Some(get_path_segments);
+    suite.SetPathSegments = Some(set_path_segments);
+    suite.InsertPathSegments = Some(insert_path_segments);
+    suite.DeletePathSegments = Some(delete_path_segments);
+    suite.GetPathClosed = Some(get_path_closed);
+    suite.SetPathClosed = Some(set_path_closed);
+    suite
+}
+
+fn empty_segment() -> AIPathSegment {
+    let origin = AIRealPoint { h: 0.0, v: 0.0 };
+    AIPathSegment { p: origin, in_: origin, out: origin, corner: 1 }
+}
+
+/// パスアートのセグメントにアクセスする（パス以外は `kBadParameterErr`）
+fn with_path<R>(
+    path: AIArtHandle,
+    f: impl FnOnce(&mut Vec<AIPathSegment>, &mut bool) -> Result<R, AIErr>,
+) -> Result<R, AIErr> {
+    with_art(path, |state: &mut HostState, index| {
+        let node = &mut state.art.nodes[index];
+        if node.art_type != AIArtType_kPathArt as i16 {
+            return Err(BAD_PARAMETER);
+        }
+        f(&mut node.segments, &mut node.closed)
+    })
+}
+
+/// `start..start + count` が既存セグメントの範囲内か
+fn range(segments: &[AIPathSegment], start: ai_int16, count: ai_int16) -> Result<std::ops::Range<usize>, AIErr> {
+    if start < 0 || count < 0 {
+        return Err(BAD_PARAMETER);
+    }
+    let range = start as usize..start as usize + count as usize;
+    if range.end > segments.len() {
+        return Err(BAD_PARAMETER);
+    }
+    Ok(range)
+}
+
+unsafe extern "C" fn get_path_segment_count(path: AIArtHandle, count: *mut ai_int16) -> AIErr {
+    match with_path(path, |segments, _| Ok(segments.len() as ai_int16)) {
+        Ok(value) => {
+            *count = value;
+            NO_ERR
+        }
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn set_path_segment_count(path: AIArtHandle, count: ai_int16) -> AIErr {
+    status(with_path(path, |segments, _| {
+        if count < 0 {
+            return Err(BAD_PARAMETER);
+        }
+        segments.resize(count as usize, empty_segment());
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn get_path_segments(
+    path: AIArtHandle,
+    seg_number: ai_int16,
+    count: ai_int16,
+    out: *mut AIPathSegment,
+) -> AIErr {
+    status(with_path(path, |segments, _| {
+        let range = range(segments, seg_number, count)?;
+        std::ptr::copy_nonoverlapping(segments[range].as_ptr(), out, count as usize);
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn set_path_segments(
+    path: AIArtHandle,
+    seg_number: ai_int16,
+    count: ai_int16,
+    input: *const AIPathSegment,
+) -> AIErr {
+    status(with_path(path, |segments, _| {
+        if seg_number < 0 || count < 0 || seg_number as usize > segments.len() {
+            return Err(BAD_PARAMETER);
+        }
+        // 末尾を越える分は追加される
+        let end = seg_number as usize + count as usize;
+        if end > segments.len() {
+            segments.resize(end, empty_segment());
+        }
+        let source = std::slice::from_raw_parts(input, count as usize);
+        segments[seg_number as usize..end].copy_from_slice(source);
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn insert_path_segments(
+    path: AIArtHandle,
+    seg_number: ai_int16,
+    count: ai_int16,
+    input: *const AIPathSegment,
+) -> AIErr {
+    status(with_path(path, |segments, _| {
+        range(segments, seg_number, 1)?;
+        if count < 0 {
+            return Err(BAD_PARAMETER);
+        }
+        let source = std::slice::from_raw_parts(input, count as usize);
+        let at = seg_number as usize + 1;
+        segments.splice(at..at, source.iter().copied());
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn delete_path_segments(path: AIArtHandle, seg_number: ai_int16, count: ai_int16) -> AIErr {
+    status(with_path(path, |segments, _| {
+        let range = range(segments, seg_number, count)?;
+        segments.drain(range);
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn get_path_closed(path: AIArtHandle, closed: *mut AIBoolean) -> AIErr {
+    match with_path(path, |_, is_closed| Ok(*is_closed)) {
+        Ok(value) => {
+            *closed = value as AIBoolean;
+            NO_ERR
+        }
+        Err(err) => err,
+    }
+}
+
+unsafe extern "C" fn set_path_closed(path: AIArtHandle, closed: AIBoolean) -> AIErr {
+    status(with_path(path, |_, is_closed| {
+        *is_closed = closed != 0;
+        Ok(())
+    }))
+}
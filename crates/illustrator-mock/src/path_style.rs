@@ -0,0 +1,58 @@
+use illustrator_sys::*;
+
+use crate::art::{status, with_art, BAD_PARAMETER};
+
+/// `AIPathStyleSuite` のスタンドイン
+///
+/// スタイルはアートツリーのノードに保持する。未設定のパスは塗りも線もないスタイルを返す。
+pub(crate) fn suite() -> AIPathStyleSuite {
+    let mut suite: AIPathStyleSuite = unsafe { std::mem::zeroed() };
+    suite.GetPathStyle = Some(get_path_style);
+    suite.SetPathStyle = Some(set_path_style);
+    suite
+}
+
+/// 塗りも線もないスタイル
+pub(crate) fn empty_style() -> AIPathStyle {
+    unsafe { std::mem::zeroed() }
+}
+
+/// スタイルを持てるアートか（パスと複合パスのみ）
+fn is_styled(art_type: i16) -> bool {
+    art_type == AIArtType_kPathArt as i16 || art_type == AIArtType_kCompoundPathArt as i16
+}
+
+unsafe extern "C" fn get_path_style(
+    path: AIArtHandle,
+    style: *mut AIPathStyle,
+    has_advanced_fill: *mut AIBoolean,
+) -> AIErr {
+    if style.is_null() {
+        return BAD_PARAMETER;
+    }
+    status(with_art(path, |state, index| {
+        let node = &state.art.nodes[index];
+        if !is_styled(node.art_type) {
+            return Err(BAD_PARAMETER);
+        }
+        *style = node.style.unwrap_or_else(empty_style);
+        if !has_advanced_fill.is_null() {
+            *has_advanced_fill = 0;
+        }
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn set_path_style(path: AIArtHandle, style: *const AIPathStyle) -> AIErr {
+    if style.is_null() {
+        return BAD_PARAMETER;
+    }
+    status(with_art(path, |state, index| {
+        let node = &mut state.art.nodes[index];
+        if !is_styled(node.art_type) {
+            return Err(BAD_PARAMETER);
+        }
+        node.style = Some(*style);
+        Ok(())
+    }))
+}
@@ -0,0 +1,290 @@
+use std::ffi::{c_char, c_void, CStr, CString};
+
+use illustrator_sys::*;
+
+use crate::art::{art_handle, status, with_art, BAD_PARAMETER};
+use crate::state::{lossy, with_state, HostEvent, HostState, NO_ERR};
+
+/// プラグインが登録したプラグイングループ
+pub(crate) struct PluginGroupEntry {
+    pub name: CString,
+    pub description: CString,
+    pub version: (i32, i32),
+    pub options: i32,
+}
+
+/// `UseAIPluginGroup` で結び付けられたプラグインアート
+pub(crate) struct PluginArtEntry {
+    /// プラグインアートのアートツリー上の番号
+    pub art: usize,
+    /// `plugin_groups` の添字
+    pub group: usize,
+    /// 編集グループ・結果グループ（どちらも親を持たない）
+    pub edit: usize,
+    pub result: usize,
+    pub data: Vec<u8>,
+    pub dirty: bool,
+}
+
+/// `AIPluginGroupSuite` のスタンドイン
+///
+/// 編集の通知と更新は自動では送られないので、`MockHost::notify_plugin_group_edit` と
+/// `MockHost::update_plugin_art` で送る。
+pub(crate) fn suite() -> AIPluginGroupSuite {
+    let mut suite: AIPluginGroupSuite = unsafe { std::mem::zeroed() };
+    suite.AddAIPluginGroup = Some(add_plugin_group);
+    suite.UseAIPluginGroup = Some(use_plugin_group);
+    suite.GetAIPluginGroupName = Some(get_plugin_group_name);
+    suite.GetAIPluginGroupVersion = Some(get_plugin_group_version);
+    suite.GetAIPluginGroupDescription = Some(get_plugin_group_description);
+    suite.GetAIPluginGroupOptions = Some(get_plugin_group_options);
+    suite.CountAIPluginGroups = Some(count_plugin_groups);
+    suite.GetNthAIPluginGroup = Some(get_nth_plugin_group);
+    suite.GetPluginArtPluginGroup = Some(get_plugin_art_plugin_group);
+    suite.GetPluginArtEditArt = Some(get_plugin_art_edit_art);
+    suite.GetPluginArtResultArt = Some(get_plugin_art_result_art);
+    suite.GetPluginArtDataCount = Some(get_plugin_art_data_count);
+    suite.SetPluginArtDataCount = Some(set_plugin_art_data_count);
+    suite.GetPluginArtDataRange = Some(get_plugin_art_data_range);
+    suite.SetPluginArtDataRange = Some(set_plugin_art_data_range);
+    suite.MarkPluginArtDirty = Some(mark_plugin_art_dirty);
+    suite.MarkPluginArtClean = Some(mark_plugin_art_clean);
+    suite
+}
+
+/// ハンドルは登録順の通し番号から作る
+pub(crate) fn plugin_group_handle(index: usize) -> AIPluginGroupHandle {
+    (0x8000 + index * 8) as AIPluginGroupHandle
+}
+
+pub(crate) fn plugin_group_index(state: &HostState, group: AIPluginGroupHandle) -> Option<usize> {
+    let index = (group as usize).checked_sub(0x8000)?;
+    (index % 8 == 0).then_some(index / 8).filter(|&index| index < state.plugin_groups.len())
+}
+
+/// プラグインアートの `plugin_art` の添字
+pub(crate) fn plugin_art_index(state: &HostState, art: AIArtHandle) -> Option<usize> {
+    let art = state.art.get(art)?;
+    state.plugin_art.iter().position(|entry| entry.art == art)
+}
+
+/// 有効なプラグイングループに対して `f` を呼ぶ（無効なハンドルは `kBadParameterErr`）
+fn with_plugin_group(group: AIPluginGroupHandle, f: impl FnOnce(&mut PluginGroupEntry)) -> AIErr {
+    with_state(|state| match plugin_group_index(state, group) {
+        Some(index) => {
+            f(&mut state.plugin_groups[index]);
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+/// グループに結び付いたプラグインアートに対して `f` を呼ぶ
+fn with_plugin_art(art: AIArtHandle, f: impl FnOnce(&mut PluginArtEntry) -> AIErr) -> AIErr {
+    with_state(|state| match plugin_art_index(state, art) {
+        Some(index) => f(&mut state.plugin_art[index]),
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn add_plugin_group(
+    _plugin: SPPluginRef,
+    name: *const c_char,
+    data: *mut AIAddPluginGroupData,
+    options: ai_int32,
+    group: *mut AIPluginGroupHandle,
+) -> AIErr {
+    if name.is_null() || data.is_null() {
+        return BAD_PARAMETER;
+    }
+    let data = &*data;
+    let description = if data.desc.is_null() { CString::default() } else { CStr::from_ptr(data.desc).to_owned() };
+    let entry = PluginGroupEntry {
+        name: CStr::from_ptr(name).to_owned(),
+        description,
+        version: (data.major, data.minor),
+        options,
+    };
+
+    with_state(|state| {
+        if state.plugin_groups.iter().any(|existing| existing.name == entry.name) {
+            return BAD_PARAMETER;
+        }
+
+        let handle = plugin_group_handle(state.plugin_groups.len());
+        state.events.push(HostEvent::AddPluginGroup(lossy(name)));
+        state.plugin_groups.push(entry);
+
+        if !group.is_null() {
+            *group = handle;
+        }
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn use_plugin_group(art: AIArtHandle, group: AIPluginGroupHandle) -> AIErr {
+    status(with_art(art, |state, index| {
+        let group = plugin_group_index(state, group).ok_or(BAD_PARAMETER)?;
+        if state.art.nodes[index].art_type != AIArtType_kPluginArt as i16 {
+            return Err(BAD_PARAMETER);
+        }
+
+        match state.plugin_art.iter_mut().find(|entry| entry.art == index) {
+            Some(entry) => entry.group = group,
+            None => {
+                let layer = state.art.nodes[index].layer;
+                let edit = state.art.push_node(AIArtType_kGroupArt as i16, layer);
+                let result = state.art.push_node(AIArtType_kGroupArt as i16, layer);
+                state.plugin_art.push(PluginArtEntry {
+                    art: index,
+                    group,
+                    edit,
+                    result,
+                    data: Vec::new(),
+                    dirty: true,
+                });
+            }
+        }
+        Ok(())
+    }))
+}
+
+unsafe extern "C" fn get_plugin_group_name(group: AIPluginGroupHandle, name: *mut *mut c_char) -> AIErr {
+    if name.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_plugin_group(group, |entry| *name = entry.name.as_ptr() as *mut c_char)
+}
+
+unsafe extern "C" fn get_plugin_group_version(
+    group: AIPluginGroupHandle,
+    major: *mut ai_int32,
+    minor: *mut ai_int32,
+) -> AIErr {
+    if major.is_null() || minor.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_plugin_group(group, |entry| (*major, *minor) = entry.version)
+}
+
+unsafe extern "C" fn get_plugin_group_description(group: AIPluginGroupHandle, description: *mut *mut c_char) -> AIErr {
+    if description.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_plugin_group(group, |entry| *description = entry.description.as_ptr() as *mut c_char)
+}
+
+unsafe extern "C" fn get_plugin_group_options(group: AIPluginGroupHandle, options: *mut ai_int32) -> AIErr {
+    if options.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_plugin_group(group, |entry| *options = entry.options)
+}
+
+unsafe extern "C" fn count_plugin_groups(count: *mut ai_int32) -> AIErr {
+    if count.is_null() {
+        return BAD_PARAMETER;
+    }
+    *count = with_state(|state| state.plugin_groups.len() as ai_int32);
+    NO_ERR
+}
+
+unsafe extern "C" fn get_nth_plugin_group(n: ai_int32, group: *mut AIPluginGroupHandle) -> AIErr {
+    if group.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_state(|state| match usize::try_from(n).ok().filter(|&n| n < state.plugin_groups.len()) {
+        Some(index) => {
+            *group = plugin_group_handle(index);
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn get_plugin_art_plugin_group(art: AIArtHandle, group: *mut AIPluginGroupHandle) -> AIErr {
+    if group.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_plugin_art(art, |entry| {
+        *group = plugin_group_handle(entry.group);
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn get_plugin_art_edit_art(art: AIArtHandle, edit: *mut AIArtHandle) -> AIErr {
+    if edit.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_plugin_art(art, |entry| {
+        *edit = art_handle(entry.edit);
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn get_plugin_art_result_art(art: AIArtHandle, result: *mut AIArtHandle) -> AIErr {
+    if result.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_plugin_art(art, |entry| {
+        *result = art_handle(entry.result);
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn get_plugin_art_data_count(art: AIArtHandle, count: *mut usize) -> AIErr {
+    if count.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_plugin_art(art, |entry| {
+        *count = entry.data.len();
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn set_plugin_art_data_count(art: AIArtHandle, count: usize) -> AIErr {
+    with_plugin_art(art, |entry| {
+        entry.data.resize(count, 0);
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn get_plugin_art_data_range(art: AIArtHandle, data: *mut c_void, index: usize, count: usize) -> AIErr {
+    if data.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_plugin_art(art, |entry| match entry.data.get(index..index.saturating_add(count)) {
+        Some(range) => {
+            std::ptr::copy_nonoverlapping(range.as_ptr(), data as *mut u8, count);
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn set_plugin_art_data_range(art: AIArtHandle, data: *mut c_void, index: usize, count: usize) -> AIErr {
+    if data.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_plugin_art(art, |entry| match entry.data.get_mut(index..index.saturating_add(count)) {
+        Some(range) => {
+            range.copy_from_slice(std::slice::from_raw_parts(data as *const u8, count));
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn mark_plugin_art_dirty(art: AIArtHandle) -> AIErr {
+    with_plugin_art(art, |entry| {
+        entry.dirty = true;
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn mark_plugin_art_clean(art: AIArtHandle) -> AIErr {
+    with_plugin_art(art, |entry| {
+        entry.dirty = false;
+        NO_ERR
+    })
+}
@@ -0,0 +1,29 @@
+use std::ffi::{c_char, CStr};
+use std::ptr::null;
+
+use illustrator_sys::*;
+
+use crate::state::{lossy, with_state, HostEvent, NO_ERR};
+
+/// `SPPluginsSuite` のスタンドイン（プラグイン名の取得・設定のみ）
+pub(crate) fn suite() -> SPPluginsSuite {
+    let mut suite: SPPluginsSuite = unsafe { std::mem::zeroed() };
+    suite.SetPluginName = Some(set_plugin_name);
+    suite.GetPluginName = Some(get_plugin_name);
+    suite
+}
+
+unsafe extern "C" fn set_plugin_name(_plugin: SPPluginRef, name: *const c_char) -> SPErr {
+    with_state(|state| {
+        state.events.push(HostEvent::SetPluginName(lossy(name)));
+        state.plugin_name = Some(CStr::from_ptr(name).to_owned());
+    });
+    NO_ERR
+}
+
+unsafe extern "C" fn get_plugin_name(_plugin: SPPluginRef, name: *mut *const c_char) -> SPErr {
+    with_state(|state| {
+        *name = state.plugin_name.as_ref().map_or(null(), |n| n.as_ptr());
+    });
+    NO_ERR
+}
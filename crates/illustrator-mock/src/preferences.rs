@@ -0,0 +1,154 @@
+use std::ffi::{c_char, CStr};
+
+use illustrator_sys::*;
+
+use crate::art::BAD_PARAMETER;
+use crate::state::{lossy, with_state, NO_ERR};
+use crate::unicode;
+
+/// ホストの環境設定に保存された値
+///
+/// C 文字列と Unicode 文字列はどちらも `String` として保存される。
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreferenceValue {
+    Boolean(bool),
+    Integer(i32),
+    Real(f64),
+    String(String),
+}
+
+/// `AIPreferenceSuite` のスタンドイン
+///
+/// 値はプレフィックスとサフィックスの組で `HostState` に保存される。
+/// 保存されていない、または種類の違う値を `Get*` すると出力引数を変更せずに `kNoErr` を返す。
+pub(crate) fn suite() -> AIPreferenceSuite {
+    let mut suite: AIPreferenceSuite = unsafe { std::mem::zeroed() };
+    suite.GetBooleanPreference = Some(get_boolean);
+    suite.PutBooleanPreference = Some(put_boolean);
+    suite.GetIntegerPreference = Some(get_integer);
+    suite.PutIntegerPreference = Some(put_integer);
+    suite.GetRealPreference = Some(get_real);
+    suite.PutRealPreference = Some(put_real);
+    suite.PutStringPreference = Some(put_string);
+    suite.GetUnicodeStringPreference = Some(get_unicode_string);
+    suite.PutUnicodeStringPreference = Some(put_unicode_string);
+    suite.RemovePreference = Some(remove);
+    suite.PreferenceExists = Some(exists);
+    suite
+}
+
+unsafe fn key(prefix: *const c_char, suffix: *const c_char) -> Option<(String, String)> {
+    if prefix.is_null() || suffix.is_null() {
+        return None;
+    }
+    Some((lossy(prefix), lossy(suffix)))
+}
+
+unsafe fn get<T>(
+    prefix: *const c_char,
+    suffix: *const c_char,
+    out: *mut T,
+    read: impl FnOnce(&PreferenceValue) -> Option<T>,
+) -> AIErr {
+    let Some(key) = key(prefix, suffix).filter(|_| !out.is_null()) else {
+        return BAD_PARAMETER;
+    };
+    if let Some(value) = with_state(|state| state.preferences.get(&key).and_then(read)) {
+        *out = value;
+    }
+    NO_ERR
+}
+
+unsafe fn put(prefix: *const c_char, suffix: *const c_char, value: PreferenceValue) -> AIErr {
+    let Some(key) = key(prefix, suffix) else {
+        return BAD_PARAMETER;
+    };
+    with_state(|state| state.preferences.insert(key, value));
+    NO_ERR
+}
+
+unsafe extern "C" fn get_boolean(prefix: *const c_char, suffix: *const c_char, value: *mut AIBoolean) -> AIErr {
+    get(prefix, suffix, value, |stored| match stored {
+        PreferenceValue::Boolean(value) => Some(*value as AIBoolean),
+        _ => None,
+    })
+}
+
+unsafe extern "C" fn put_boolean(prefix: *const c_char, suffix: *const c_char, value: AIBoolean) -> AIErr {
+    put(prefix, suffix, PreferenceValue::Boolean(value != 0))
+}
+
+unsafe extern "C" fn get_integer(prefix: *const c_char, suffix: *const c_char, value: *mut ai_int32) -> AIErr {
+    get(prefix, suffix, value, |stored| match stored {
+        PreferenceValue::Integer(value) => Some(*value),
+        _ => None,
+    })
+}
+
+unsafe extern "C" fn put_integer(prefix: *const c_char, suffix: *const c_char, value: ai_int32) -> AIErr {
+    put(prefix, suffix, PreferenceValue::Integer(value))
+}
+
+unsafe extern "C" fn get_real(prefix: *const c_char, suffix: *const c_char, value: *mut f64) -> AIErr {
+    get(prefix, suffix, value, |stored| match stored {
+        PreferenceValue::Real(value) => Some(*value),
+        _ => None,
+    })
+}
+
+unsafe extern "C" fn put_real(prefix: *const c_char, suffix: *const c_char, value: f64) -> AIErr {
+    put(prefix, suffix, PreferenceValue::Real(value))
+}
+
+unsafe extern "C" fn put_string(prefix: *const c_char, suffix: *const c_char, value: *const c_char) -> AIErr {
+    if value.is_null() {
+        return BAD_PARAMETER;
+    }
+    let text = CStr::from_ptr(value).to_string_lossy().into_owned();
+    put(prefix, suffix, PreferenceValue::String(text))
+}
+
+unsafe extern "C" fn get_unicode_string(
+    prefix: *const c_char,
+    suffix: *const c_char,
+    value: *mut ai_UnicodeString,
+) -> AIErr {
+    let Some(key) = key(prefix, suffix).filter(|_| !value.is_null()) else {
+        return BAD_PARAMETER;
+    };
+    let stored = with_state(|state| match state.preferences.get(&key) {
+        Some(PreferenceValue::String(text)) => Some(text.clone()),
+        _ => None,
+    });
+    if let Some(text) = stored {
+        unicode::write(value, &text);
+    }
+    NO_ERR
+}
+
+unsafe extern "C" fn put_unicode_string(
+    prefix: *const c_char,
+    suffix: *const c_char,
+    value: *const ai_UnicodeString,
+) -> AIErr {
+    if value.is_null() {
+        return BAD_PARAMETER;
+    }
+    put(prefix, suffix, PreferenceValue::String(unicode::read(value)))
+}
+
+unsafe extern "C" fn remove(prefix: *const c_char, suffix: *const c_char) -> AIErr {
+    let Some(key) = key(prefix, suffix) else {
+        return BAD_PARAMETER;
+    };
+    with_state(|state| state.preferences.remove(&key));
+    NO_ERR
+}
+
+unsafe extern "C" fn exists(prefix: *const c_char, suffix: *const c_char, does_exist: *mut AIBoolean) -> AIErr {
+    let Some(key) = key(prefix, suffix).filter(|_| !does_exist.is_null()) else {
+        return BAD_PARAMETER;
+    };
+    *does_exist = with_state(|state| state.preferences.contains_key(&key)) as AIBoolean;
+    NO_ERR
+}
@@ -0,0 +1,175 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{c_void, CStr, CString};
+
+use illustrator_sys::*;
+
+use crate::action::{ActionEventEntry, RecordedAction};
+use crate::art::ArtTree;
+use crate::document::DocumentEntry;
+use crate::file_format::FileFormatEntry;
+use crate::filter::FilterEntry;
+use crate::live_effect::LiveEffectEntry;
+use crate::menu::{MenuGroupEntry, MenuItemEntry};
+use crate::notifier::NotifierEntry;
+use crate::plugin_group::{PluginArtEntry, PluginGroupEntry};
+use crate::preferences::PreferenceValue;
+use crate::timer::TimerEntry;
+use crate::tool::ToolEntry;
+use crate::undo::UndoContext;
+
+pub(crate) const NO_ERR: ASErr = kNoErr as ASErr;
+
+/// ホストが記録したプラグインからの呼び出し
+#[derive(Debug, Clone, PartialEq)]
+pub enum HostEvent {
+    AcquireSuite { name: String, version: i32 },
+    ReleaseSuite { name: String, version: i32 },
+    SetPluginName(String),
+    AddNotifier { name: String, notifier_type: String },
+    SetNotifierActive { notifier_type: String, active: bool },
+    BroadcastNotify(String),
+    AcquirePlugin,
+    ReleasePlugin,
+    ErrorAlert(String),
+    MessageAlert(String),
+    WarningAlert(String),
+    NewArt { art_type: i16 },
+    DisposeArt { art_type: i16 },
+    RedrawDocument,
+    InsertLayer(String),
+    DeleteLayer(String),
+    AddMenuItem(String),
+    RemoveMenuItem(String),
+    AddTool(String),
+    AddTimer(String),
+    AddLiveEffect(String),
+    UpdateLiveEffectParameters(String),
+    AddPluginGroup(String),
+    AddFileFormat(String),
+    AddFilter(String),
+    NewActionParamType,
+    DeleteActionParamType,
+    RegisterActionEvent(String),
+    RecordActionEvent(String),
+    SetUndoText { undo: String, redo: String },
+    UndoChanges,
+    SetUndoSilent(bool),
+    SetUndoKind(i32),
+}
+
+/// ホストに登録されたスイートの関数テーブル
+pub(crate) struct SuiteEntry {
+    pub table: *const c_void,
+    pub refcount: i32,
+    _owner: Box<dyn Any>,
+}
+
+/// スレッドごとのホスト状態
+pub(crate) struct HostState {
+    pub events: Vec<HostEvent>,
+    pub suites: HashMap<(CString, i32), SuiteEntry>,
+    pub notifiers: Vec<NotifierEntry>,
+    pub plugin_name: Option<CString>,
+    pub access_count: i32,
+    pub art: ArtTree,
+    /// アクティブなドキュメント（`None` なら何も開いていない）
+    pub document: Option<DocumentEntry>,
+    pub documents_opened: usize,
+    /// `AIDictionarySuite::Key` が返したキー（ポインタはホストが破棄されるまで有効）
+    pub dictionary_keys: Vec<CString>,
+    /// `AIPreferenceSuite` の値（プレフィックス・サフィックスの組がキー）
+    pub preferences: HashMap<(String, String), PreferenceValue>,
+    pub menu_items: Vec<MenuItemEntry>,
+    pub menu_groups: Vec<MenuGroupEntry>,
+    pub tools: Vec<ToolEntry>,
+    /// 選択中のツール（`tools` の添字）
+    pub selected_tool: Option<usize>,
+    pub timers: Vec<TimerEntry>,
+    pub live_effects: Vec<LiveEffectEntry>,
+    pub plugin_groups: Vec<PluginGroupEntry>,
+    pub plugin_art: Vec<PluginArtEntry>,
+    pub file_formats: Vec<FileFormatEntry>,
+    pub filters: Vec<FilterEntry>,
+    pub action_events: Vec<ActionEventEntry>,
+    /// アクションパネルが記録中か
+    pub recording: bool,
+    pub recorded_actions: Vec<RecordedAction>,
+    /// 最後に送ったメッセージの取り消し単位
+    pub undo: UndoContext,
+}
+
+impl HostState {
+    pub fn new() -> Self {
+        Self {
+            events: Vec::new(),
+            suites: HashMap::new(),
+            notifiers: Vec::new(),
+            plugin_name: None,
+            access_count: 0,
+            art: ArtTree::new(),
+            document: Some(DocumentEntry::new(0, None)),
+            documents_opened: 1,
+            dictionary_keys: Vec::new(),
+            preferences: HashMap::new(),
+            menu_items: Vec::new(),
+            menu_groups: Vec::new(),
+            tools: Vec::new(),
+            selected_tool: None,
+            timers: Vec::new(),
+            live_effects: Vec::new(),
+            plugin_groups: Vec::new(),
+            plugin_art: Vec::new(),
+            file_formats: Vec::new(),
+            filters: Vec::new(),
+            action_events: Vec::new(),
+            recording: false,
+            recorded_actions: Vec::new(),
+            undo: UndoContext::new(),
+        }
+    }
+
+    /// 関数テーブルを登録する（同名・同バージョンは置き換え）
+    pub fn register_suite<S: 'static>(&mut self, name: &CStr, version: i32, table: S) {
+        let owner = Box::new(table);
+        let table = &*owner as *const S as *const c_void;
+
+        self.suites.insert(
+            (name.to_owned(), version),
+            SuiteEntry { table, refcount: 0, _owner: owner },
+        );
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<Option<HostState>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn install(state: HostState) {
+    STATE.with(|s| *s.borrow_mut() = Some(state));
+}
+
+pub(crate) fn uninstall() {
+    STATE.with(|s| *s.borrow_mut() = None);
+}
+
+/// 現在のスレッドで有効なホスト状態にアクセスする
+pub(crate) fn with_state<R>(f: impl FnOnce(&mut HostState) -> R) -> R {
+    STATE.with(|s| {
+        let mut state = s.borrow_mut();
+        f(state.as_mut().expect("MockHost is not active on this thread"))
+    })
+}
+
+/// バインディングの `&[u8; N]` 定数（NUL終端）を `CStr` として扱う
+pub fn cstr(bytes: &'static [u8]) -> &'static CStr {
+    CStr::from_bytes_with_nul(bytes).expect("constant must be NUL terminated")
+}
+
+pub(crate) unsafe fn lossy(ptr: *const std::ffi::c_char) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+    CStr::from_ptr(ptr).to_string_lossy().into_owned()
+}
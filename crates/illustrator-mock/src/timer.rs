@@ -0,0 +1,130 @@
+use std::ffi::{c_char, CStr, CString};
+
+use illustrator_sys::*;
+
+use crate::art::BAD_PARAMETER;
+use crate::state::{lossy, with_state, HostEvent, HostState, NO_ERR};
+
+/// プラグインが追加したタイマー
+pub(crate) struct TimerEntry {
+    pub name: CString,
+    pub period: i32,
+    pub active: bool,
+}
+
+/// `AITimerSuite` のスタンドイン
+///
+/// 時間は進まないので、`kSelectorAIGoTimer` は `MockHost::fire_timer` で送る。
+pub(crate) fn suite() -> AITimerSuite {
+    let mut suite: AITimerSuite = unsafe { std::mem::zeroed() };
+    suite.AddTimer = Some(add_timer);
+    suite.GetTimerName = Some(get_timer_name);
+    suite.GetTimerActive = Some(get_timer_active);
+    suite.SetTimerActive = Some(set_timer_active);
+    suite.GetTimerPeriod = Some(get_timer_period);
+    suite.SetTimerPeriod = Some(set_timer_period);
+    suite.CountTimers = Some(count_timers);
+    suite.GetNthTimer = Some(get_nth_timer);
+    suite
+}
+
+/// ハンドルは追加順の通し番号から作る
+pub(crate) fn timer_handle(index: usize) -> AITimerHandle {
+    (0x6000 + index * 8) as AITimerHandle
+}
+
+pub(crate) fn timer_index(state: &HostState, timer: AITimerHandle) -> Option<usize> {
+    let index = (timer as usize).checked_sub(0x6000)?;
+    (index % 8 == 0).then_some(index / 8).filter(|&index| index < state.timers.len())
+}
+
+/// 有効なタイマーに対して `f` を呼ぶ（無効なハンドルは `kBadParameterErr`）
+fn with_timer(timer: AITimerHandle, f: impl FnOnce(&mut TimerEntry)) -> AIErr {
+    with_state(|state| match timer_index(state, timer) {
+        Some(index) => {
+            f(&mut state.timers[index]);
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn add_timer(
+    _plugin: SPPluginRef,
+    name: *const c_char,
+    period: ai_int32,
+    timer: *mut AITimerHandle,
+) -> AIErr {
+    if name.is_null() || period < 0 {
+        return BAD_PARAMETER;
+    }
+    let entry = TimerEntry {
+        name: CStr::from_ptr(name).to_owned(),
+        period,
+        active: true,
+    };
+
+    with_state(|state| {
+        let handle = timer_handle(state.timers.len());
+        state.events.push(HostEvent::AddTimer(lossy(name)));
+        state.timers.push(entry);
+
+        if !timer.is_null() {
+            *timer = handle;
+        }
+    });
+    NO_ERR
+}
+
+unsafe extern "C" fn get_timer_name(timer: AITimerHandle, name: *mut *mut c_char) -> AIErr {
+    if name.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_timer(timer, |entry| *name = entry.name.as_ptr() as *mut c_char)
+}
+
+unsafe extern "C" fn get_timer_active(timer: AITimerHandle, active: *mut AIBoolean) -> AIErr {
+    if active.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_timer(timer, |entry| *active = entry.active as AIBoolean)
+}
+
+unsafe extern "C" fn set_timer_active(timer: AITimerHandle, active: AIBoolean) -> AIErr {
+    with_timer(timer, |entry| entry.active = active != 0)
+}
+
+unsafe extern "C" fn get_timer_period(timer: AITimerHandle, period: *mut ai_int32) -> AIErr {
+    if period.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_timer(timer, |entry| *period = entry.period)
+}
+
+unsafe extern "C" fn set_timer_period(timer: AITimerHandle, period: ai_int32) -> AIErr {
+    if period < 0 {
+        return BAD_PARAMETER;
+    }
+    with_timer(timer, |entry| entry.period = period)
+}
+
+unsafe extern "C" fn count_timers(count: *mut ai_int32) -> AIErr {
+    if count.is_null() {
+        return BAD_PARAMETER;
+    }
+    *count = with_state(|state| state.timers.len() as ai_int32);
+    NO_ERR
+}
+
+unsafe extern "C" fn get_nth_timer(n: ai_int32, timer: *mut AITimerHandle) -> AIErr {
+    if timer.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_state(|state| match usize::try_from(n).ok().filter(|&n| n < state.timers.len()) {
+        Some(index) => {
+            *timer = timer_handle(index);
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
@@ -0,0 +1,217 @@
+use std::ffi::{c_char, CStr, CString};
+
+use illustrator_sys::*;
+
+use crate::art::BAD_PARAMETER;
+use crate::state::{with_state, HostEvent, HostState, NO_ERR};
+use crate::unicode;
+
+/// AITypes.h の `kNoTool`
+const NO_TOOL: AIToolType = -2;
+
+/// プラグインが追加したツール
+pub(crate) struct ToolEntry {
+    pub name: CString,
+    pub title: String,
+    pub tooltip: String,
+    pub options: i32,
+    /// 同じグループ・ツールセットのツール番号（新しく作った場合は `kNoTool`）
+    pub same_group_as: AIToolType,
+    pub same_toolset_as: AIToolType,
+    pub icon_names: Option<(CString, CString)>,
+}
+
+/// `AIToolSuite` のスタンドイン
+///
+/// ツール番号は追加順の通し番号。
+pub(crate) fn suite() -> AIToolSuite {
+    let mut suite: AIToolSuite = unsafe { std::mem::zeroed() };
+    suite.AddTool = Some(add_tool);
+    suite.GetToolName = Some(get_tool_name);
+    suite.GetToolOptions = Some(get_tool_options);
+    suite.SetToolOptions = Some(set_tool_options);
+    suite.GetSelectedTool = Some(get_selected_tool);
+    suite.SetSelectedTool = Some(set_selected_tool);
+    suite.GetToolNumberFromName = Some(get_tool_number_from_name);
+    suite.GetToolNumberFromHandle = Some(get_tool_number_from_handle);
+    suite.GetToolTitle = Some(get_tool_title);
+    suite.SetToolTitle = Some(set_tool_title);
+    suite.GetTooltip = Some(get_tooltip);
+    suite.SetTooltip = Some(set_tooltip);
+    suite.SetToolIcons = Some(set_tool_icons);
+    suite
+}
+
+pub(crate) fn tool_handle(index: usize) -> AIToolHandle {
+    (0x5000 + index * 8) as AIToolHandle
+}
+
+pub(crate) fn tool_index(state: &HostState, tool: AIToolHandle) -> Option<usize> {
+    let index = (tool as usize).checked_sub(0x5000)?;
+    (index % 8 == 0).then_some(index / 8).filter(|&index| index < state.tools.len())
+}
+
+/// 有効なツールに対して `f` を呼ぶ（無効なハンドルは `kBadParameterErr`）
+fn with_tool(tool: AIToolHandle, f: impl FnOnce(&mut ToolEntry)) -> AIErr {
+    with_state(|state| match tool_index(state, tool) {
+        Some(index) => {
+            f(&mut state.tools[index]);
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+/// 登録済みのツール番号なら `Some`（`kNoTool` はそのまま）
+fn check_number(state: &HostState, number: AIToolType) -> Option<AIToolType> {
+    (number == NO_TOOL || (0..state.tools.len() as AIToolType).contains(&number)).then_some(number)
+}
+
+unsafe extern "C" fn add_tool(
+    _plugin: SPPluginRef,
+    name: *const c_char,
+    data: *const AIAddToolData,
+    options: ai_int32,
+    tool: *mut AIToolHandle,
+) -> AIErr {
+    if name.is_null() || data.is_null() {
+        return BAD_PARAMETER;
+    }
+    let data = &*data;
+    let name = CStr::from_ptr(name).to_owned();
+    let title = unicode::read(&data.title);
+    let tooltip = unicode::read(&data.tooltip);
+
+    with_state(|state| {
+        if state.tools.iter().any(|entry| entry.name == name) {
+            return BAD_PARAMETER;
+        }
+        let (Some(same_group_as), Some(same_toolset_as)) =
+            (check_number(state, data.sameGroupAs), check_number(state, data.sameToolsetAs))
+        else {
+            return BAD_PARAMETER;
+        };
+
+        let handle = tool_handle(state.tools.len());
+        state.events.push(HostEvent::AddTool(name.to_string_lossy().into_owned()));
+        state.tools.push(ToolEntry {
+            name,
+            title,
+            tooltip,
+            options,
+            same_group_as,
+            same_toolset_as,
+            icon_names: None,
+        });
+
+        if !tool.is_null() {
+            *tool = handle;
+        }
+        NO_ERR
+    })
+}
+
+unsafe extern "C" fn get_tool_name(tool: AIToolHandle, name: *mut *mut c_char) -> AIErr {
+    if name.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_tool(tool, |entry| *name = entry.name.as_ptr() as *mut c_char)
+}
+
+unsafe extern "C" fn get_tool_options(tool: AIToolHandle, options: *mut ai_int32) -> AIErr {
+    if options.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_tool(tool, |entry| *options = entry.options)
+}
+
+unsafe extern "C" fn set_tool_options(tool: AIToolHandle, options: ai_int32) -> AIErr {
+    with_tool(tool, |entry| entry.options = options)
+}
+
+unsafe extern "C" fn get_selected_tool(tool: *mut AIToolHandle) -> AIErr {
+    if tool.is_null() {
+        return BAD_PARAMETER;
+    }
+    *tool = with_state(|state| state.selected_tool.map_or(std::ptr::null_mut(), tool_handle));
+    NO_ERR
+}
+
+unsafe extern "C" fn set_selected_tool(tool: AIToolHandle) -> AIErr {
+    with_state(|state| match tool_index(state, tool) {
+        Some(index) => {
+            state.selected_tool = Some(index);
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn get_tool_number_from_name(name: *const c_char, number: *mut AIToolType) -> AIErr {
+    if name.is_null() || number.is_null() {
+        return BAD_PARAMETER;
+    }
+    let name = CStr::from_ptr(name);
+    with_state(|state| match state.tools.iter().position(|entry| entry.name.as_c_str() == name) {
+        Some(index) => {
+            *number = index as AIToolType;
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn get_tool_number_from_handle(tool: AIToolHandle, number: *mut AIToolType) -> AIErr {
+    if number.is_null() {
+        return BAD_PARAMETER;
+    }
+    with_state(|state| match tool_index(state, tool) {
+        Some(index) => {
+            *number = index as AIToolType;
+            NO_ERR
+        }
+        None => BAD_PARAMETER,
+    })
+}
+
+unsafe extern "C" fn get_tool_title(tool: AIToolHandle, title: *mut ai_UnicodeString) -> AIErr {
+    if title.is_null() {
+        return BAD_PARAMETER;
+    }
+    let mut stored = None;
+    let err = with_tool(tool, |entry| stored = Some(entry.title.clone()));
+    if let Some(stored) = stored {
+        unicode::write(title, &stored);
+    }
+    err
+}
+
+unsafe extern "C" fn set_tool_title(tool: AIToolHandle, title: ai_UnicodeString) -> AIErr {
+    let title = unicode::read(&title);
+    with_tool(tool, |entry| entry.title = title)
+}
+
+unsafe extern "C" fn get_tooltip(tool: AIToolHandle, tooltip: *mut ai_UnicodeString) -> AIErr {
+    if tooltip.is_null() {
+        return BAD_PARAMETER;
+    }
+    let mut stored = None;
+    let err = with_tool(tool, |entry| stored = Some(entry.tooltip.clone()));
+    if let Some(stored) = stored {
+        unicode::write(tooltip, &stored);
+    }
+    err
+}
+
+unsafe extern "C" fn set_tooltip(tool: AIToolHandle, tooltip: ai_UnicodeString) -> AIErr {
+    let tooltip = unicode::read(&tooltip);
+    with_tool(tool, |entry| entry.tooltip = tooltip)
+}
+
+unsafe extern "C" fn set_tool_icons(tool: AIToolHandle, normal: *const c_char, dark: *const c_char) -> AIErr {
+    if normal.is_null() || dark.is_null() {
+        return BAD_PARAMETER;
+    }
+    let names = (CStr::from_ptr(normal).to_owned(), CStr::from_ptr(dark).to_owned());
+    with_tool(tool, |entry| entry.icon_names = Some(names))
+}
@@ -0,0 +1,108 @@
+use illustrator_sys::*;
+
+use crate::art::{ArtTree, BAD_PARAMETER};
+use crate::state::{with_state, HostEvent, NO_ERR};
+use crate::unicode;
+
+/// メッセージ 1 回分の取り消し単位
+pub(crate) struct UndoContext {
+    pub undo_text: Option<String>,
+    pub redo_text: Option<String>,
+    pub silent: bool,
+    /// `SetKind` で設定された `AIUndoContextKind`
+    pub kind: i32,
+    /// `UndoChanges` が呼ばれた回数
+    pub reverts: usize,
+    /// メッセージ開始時点のアートツリー（`UndoChanges` で戻す先）
+    pub snapshot: Option<ArtTree>,
+}
+
+impl UndoContext {
+    pub fn new() -> Self {
+        Self {
+            undo_text: None,
+            redo_text: None,
+            silent: false,
+            kind: AIUndoContextKind_kAIStandardUndoContext as i32,
+            reverts: 0,
+            snapshot: None,
+        }
+    }
+}
+
+/// `AIUndoSuite` のスタンドイン
+///
+/// 取り消し単位は `MockHost::send` ごとに作り直し、そのときのアートツリーを保存する。
+/// `UndoChanges` はアートツリーだけを戻す（辞書の中身やドキュメントの設定は戻らない）。
+pub(crate) fn suite() -> AIUndoSuite {
+    let mut suite: AIUndoSuite = unsafe { std::mem::zeroed() };
+    suite.SetUndoTextUS = Some(set_undo_text_us);
+    suite.SetUndoRedoCmdTextUS = Some(set_undo_redo_cmd_text_us);
+    suite.UndoChanges = Some(undo_changes);
+    suite.SetSilent = Some(set_silent);
+    suite.IsSilent = Some(is_silent);
+    suite.SetKind = Some(set_kind);
+    suite
+}
+
+unsafe extern "C" fn set_undo_text_us(undo_text: *const ai_UnicodeString, redo_text: *const ai_UnicodeString) -> AIErr {
+    let undo_text = unicode::read(undo_text);
+    let redo_text = unicode::read(redo_text);
+
+    with_state(|state| {
+        state.events.push(HostEvent::SetUndoText {
+            undo: undo_text.clone(),
+            redo: redo_text.clone(),
+        });
+        state.undo.undo_text = Some(undo_text);
+        state.undo.redo_text = Some(redo_text);
+    });
+    NO_ERR
+}
+
+/// コマンド名は保存しない
+unsafe extern "C" fn set_undo_redo_cmd_text_us(
+    undo_text: *const ai_UnicodeString,
+    redo_text: *const ai_UnicodeString,
+    _cmd_text: *const ai_UnicodeString,
+) -> AIErr {
+    set_undo_text_us(undo_text, redo_text)
+}
+
+unsafe extern "C" fn undo_changes() -> AIErr {
+    with_state(|state| {
+        state.events.push(HostEvent::UndoChanges);
+        state.undo.reverts += 1;
+        if let Some(snapshot) = &state.undo.snapshot {
+            state.art = snapshot.clone();
+        }
+    });
+    NO_ERR
+}
+
+unsafe extern "C" fn set_silent(silent: AIBoolean) -> AIErr {
+    with_state(|state| {
+        state.events.push(HostEvent::SetUndoSilent(silent != 0));
+        state.undo.silent = silent != 0;
+    });
+    NO_ERR
+}
+
+unsafe extern "C" fn is_silent(silent: *mut AIBoolean) -> AIErr {
+    if silent.is_null() {
+        return BAD_PARAMETER;
+    }
+    *silent = with_state(|state| {
+        let kind = AIUndoContextKind_kAISilentUndoContext as i32;
+        (state.undo.silent || state.undo.kind == kind) as AIBoolean
+    });
+    NO_ERR
+}
+
+unsafe extern "C" fn set_kind(kind: ai_int32) -> AIErr {
+    with_state(|state| {
+        state.events.push(HostEvent::SetUndoKind(kind));
+        state.undo.kind = kind;
+    });
+    NO_ERR
+}
@@ -0,0 +1,130 @@
+use std::ffi::{c_char, CStr};
+use std::ptr::null_mut;
+
+use illustrator_sys::*;
+
+use crate::state::NO_ERR;
+
+static EMPTY: [u16; 1] = [0];
+
+/// `AIUnicodeStringSuite` のスタンドイン
+///
+/// `fImpl` にはホスト側で確保した UTF-16 バッファ（`Box<Vec<u16>>`）を格納する。
+pub(crate) fn suite() -> AIUnicodeStringSuite {
+    let mut suite: AIUnicodeStringSuite = unsafe { std::mem::zeroed() };
+    suite.Initialize = Some(initialize);
+    suite.InitializeUTF16 = Some(initialize_utf16);
+    suite.Destroy = Some(destroy);
+    suite.Assign = Some(assign);
+    suite.Copy = Some(assign);
+    suite.Append = Some(append);
+    suite.Clear = Some(clear);
+    suite.Length = Some(length);
+    suite.Empty = Some(empty);
+    suite.UTF_16 = Some(utf_16);
+    suite
+}
+
+unsafe fn buffer<'a>(s: *const ai_UnicodeString) -> Option<&'a mut Vec<u16>> {
+    ((*s).fImpl as *mut Vec<u16>).as_mut()
+}
+
+unsafe fn store(s: *mut ai_UnicodeString, units: Vec<u16>) {
+    destroy(s);
+    (*s).fImpl = Box::into_raw(Box::new(units)) as *mut CAIUnicodeStringImpl;
+}
+
+/// ホストが保持する文字列を Rust の `String` として読み出す
+pub unsafe fn read(s: *const ai_UnicodeString) -> String {
+    if s.is_null() {
+        return String::new();
+    }
+    buffer(s).map_or_else(String::new, |units| String::from_utf16_lossy(units))
+}
+
+/// ホストが保持する文字列を置き換える（`GetArtName` などの出力引数用）
+pub unsafe fn write(s: *mut ai_UnicodeString, text: &str) {
+    if !s.is_null() {
+        store(s, text.encode_utf16().collect());
+    }
+}
+
+unsafe extern "C" fn initialize(
+    s: *mut ai_UnicodeString,
+    string: *const c_char,
+    len: ai_UnicodeString_offset_type,
+    _encoding: AICharacterEncoding,
+) -> AIErr {
+    let text = if len < 0 {
+        CStr::from_ptr(string).to_string_lossy().into_owned()
+    } else {
+        String::from_utf8_lossy(std::slice::from_raw_parts(string as *const u8, len as usize)).into_owned()
+    };
+    (*s).fImpl = null_mut();
+    store(s, text.encode_utf16().collect());
+    NO_ERR
+}
+
+unsafe extern "C" fn initialize_utf16(
+    s: *mut ai_UnicodeString,
+    units: *const ASUnicode,
+    count: ai_UnicodeString_offset_type,
+) -> AIErr {
+    let count = if count < 0 {
+        (0..).take_while(|&i| *units.add(i) != 0).count()
+    } else {
+        count as usize
+    };
+    (*s).fImpl = null_mut();
+    store(s, std::slice::from_raw_parts(units, count).to_vec());
+    NO_ERR
+}
+
+unsafe extern "C" fn destroy(s: *mut ai_UnicodeString) -> AIErr {
+    if !(*s).fImpl.is_null() {
+        drop(Box::from_raw((*s).fImpl as *mut Vec<u16>));
+        (*s).fImpl = null_mut();
+    }
+    NO_ERR
+}
+
+unsafe extern "C" fn assign(s: *mut ai_UnicodeString, other: *const ai_UnicodeString) -> AIErr {
+    let units = buffer(other).map(|u| u.clone()).unwrap_or_default();
+    store(s, units);
+    NO_ERR
+}
+
+unsafe extern "C" fn append(s: *mut ai_UnicodeString, other: *const ai_UnicodeString) -> AIErr {
+    let mut units = buffer(s).map(|u| u.clone()).unwrap_or_default();
+    units.extend(buffer(other).map(|u| u.clone()).unwrap_or_default());
+    store(s, units);
+    NO_ERR
+}
+
+unsafe extern "C" fn clear(s: *mut ai_UnicodeString) {
+    store(s, Vec::new());
+}
+
+unsafe extern "C" fn length(s: *const ai_UnicodeString) -> ai_UnicodeString_size_type {
+    buffer(s).map_or(0, |u| u.len())
+}
+
+unsafe extern "C" fn empty(s: *const ai_UnicodeString) -> AIBool8 {
+    (length(s) == 0) as AIBool8
+}
+
+unsafe extern "C" fn utf_16(
+    s: *const ai_UnicodeString,
+    out: *mut *const ai_UnicodeString_UTF16Char,
+) -> ai_UnicodeString_size_type {
+    match buffer(s) {
+        Some(units) => {
+            *out = units.as_ptr();
+            units.len()
+        }
+        None => {
+            *out = EMPTY.as_ptr();
+            0
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use std::ffi::c_char;
+
+use illustrator_sys::*;
+
+use crate::state::{with_state, HostEvent};
+use crate::unicode;
+
+/// `AIUserSuite` のスタンドイン（アラート表示を記録する）
+pub(crate) fn suite() -> AIUserSuite {
+    let mut suite: AIUserSuite = unsafe { std::mem::zeroed() };
+    suite.ErrorAlert = Some(error_alert);
+    suite.MessageAlert = Some(message_alert);
+    suite.WarningAlert = Some(warning_alert);
+    suite
+}
+
+unsafe extern "C" fn error_alert(msg: *const ai_UnicodeString) {
+    let text = unicode::read(msg);
+    with_state(|state| state.events.push(HostEvent::ErrorAlert(text)));
+}
+
+unsafe extern "C" fn message_alert(msg: *const ai_UnicodeString) {
+    let text = unicode::read(msg);
+    with_state(|state| state.events.push(HostEvent::MessageAlert(text)));
+}
+
+unsafe extern "C" fn warning_alert(msg: *const ai_UnicodeString, _dont_show_key: *const c_char) {
+    let text = unicode::read(msg);
+    with_state(|state| state.events.push(HostEvent::WarningAlert(text)));
+}
@@ -0,0 +1,139 @@
+//! アクションイベントの記録と、アクションパネルからの再生
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use illustrator_mock::{ActionValue, HostEvent, MockHost, RecordedAction};
+use illustrator_rs::action::param_key;
+use illustrator_rs::ai_sys::*;
+use illustrator_rs::{AIError, AIResult, Action, ActionEvent, ActionParameters, Actions, SafePlugin};
+
+const FACTOR: ActionParamKeyID = param_key(b"fctr");
+
+#[derive(ActionParameters, Debug, Default, PartialEq)]
+struct Scale {
+    #[action_param(key = "fctr", name = "Factor")]
+    factor: i32,
+}
+
+/// ダイアログで 1 増やし、`go` で受け取った値を記録する
+struct Scaler {
+    runs: Rc<RefCell<Vec<i32>>>,
+}
+
+impl Action<ActionPlugin> for Scaler {
+    type Parameters = Scale;
+
+    const HAS_DIALOG: bool = true;
+
+    fn edit_parameters(&mut self, _: &mut ActionPlugin, parameters: &mut Scale) -> AIResult<()> {
+        parameters.factor += 1;
+        Ok(())
+    }
+
+    fn go(&mut self, _: &mut ActionPlugin, parameters: &Scale) -> AIResult<()> {
+        self.runs.borrow_mut().push(parameters.factor);
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct ActionPlugin {
+    actions: Actions<Self>,
+    scale: Option<ActionEvent<Self, Scale>>,
+    runs: Rc<RefCell<Vec<i32>>>,
+}
+
+impl SafePlugin for ActionPlugin {
+    fn startup(&mut self) -> AIResult<()> {
+        let runs = self.runs.clone();
+        self.scale = Some(self.actions.add(c"action:scale", "Scale", Scaler { runs })?);
+        Ok(())
+    }
+
+    fn actions(&mut self) -> Option<&mut Actions<Self>> {
+        Some(&mut self.actions)
+    }
+}
+
+illustrator_rs::define_plugin!(ActionPlugin, "Action Plugin");
+
+fn started_host() -> MockHost {
+    let mut host = MockHost::new(PluginMain);
+    assert_eq!(host.startup(), kNoErr);
+    host
+}
+
+/// メニューから実行したときのように `ActionEvent::run` を呼ぶ
+fn run_scale(host: &mut MockHost) -> AIResult<()> {
+    host.run_in_message(|plugin: &mut ActionPlugin| {
+        let scale = plugin.scale.clone().expect("event was added");
+        scale.run(plugin)
+    })
+}
+
+fn runs(host: &mut MockHost) -> Vec<i32> {
+    host.with_plugin(|plugin: &mut ActionPlugin| plugin.runs.borrow().clone())
+}
+
+#[test]
+fn run_records_in_record_mode() {
+    let mut host = started_host();
+
+    assert_eq!(run_scale(&mut host), Ok(()));
+    assert_eq!(host.recorded_actions(), vec![]);
+
+    host.set_recording(true);
+    assert_eq!(run_scale(&mut host), Ok(()));
+    assert_eq!(runs(&mut host), vec![1, 2]);
+
+    let recorded = RecordedAction {
+        name: "action:scale".into(),
+        dialog_status: _ActionDialogStatus_kDialogOff,
+        values: vec![(FACTOR, ActionValue::Integer(2))],
+    };
+    assert_eq!(host.recorded_actions(), vec![recorded]);
+}
+
+#[test]
+fn replay_uses_the_recorded_parameters() {
+    let mut host = started_host();
+
+    assert_eq!(host.play_action("action:scale", &[(FACTOR, ActionValue::Integer(5))], false), kNoErr);
+    assert_eq!(runs(&mut host), vec![5]);
+
+    // ダイアログがオフなら編集せず、記録された値で実行する
+    assert_eq!(host.play_action("action:scale", &[(FACTOR, ActionValue::Integer(3))], false), kNoErr);
+    assert_eq!(runs(&mut host), vec![5, 3]);
+    assert_eq!(host.recorded_actions(), vec![]);
+}
+
+#[test]
+fn replay_with_dialog_edits_the_parameters() {
+    let mut host = started_host();
+
+    assert_eq!(host.play_action("action:scale", &[(FACTOR, ActionValue::Integer(5))], true), kNoErr);
+    assert_eq!(runs(&mut host), vec![6]);
+
+    // 編集した値は次の実行に引き継がれる
+    assert_eq!(run_scale(&mut host), Ok(()));
+    assert_eq!(runs(&mut host), vec![6, 7]);
+}
+
+#[test]
+fn failed_registration_deletes_the_parameter_type() {
+    let mut host = started_host();
+    host.take_events();
+
+    let result = host.run_in_message(|plugin: &mut ActionPlugin| {
+        let runs = plugin.runs.clone();
+        plugin.actions.add(c"action:scale", "Scale Again", Scaler { runs }).map(|_| ())
+    });
+    assert_eq!(result, Err(AIError::BadParameter));
+    assert_eq!(host.with_plugin(|plugin: &mut ActionPlugin| plugin.actions.names().count()), 1);
+
+    let events = host.take_events();
+    let new = events.iter().filter(|event| **event == HostEvent::NewActionParamType).count();
+    let deleted = events.iter().filter(|event| **event == HostEvent::DeleteActionParamType).count();
+    assert_eq!((new, deleted), (1, 1));
+}
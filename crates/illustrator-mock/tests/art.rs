@@ -0,0 +1,146 @@
+//! `Art` のツリーの走査と重ね順の変更
+
+use std::ptr::null_mut;
+
+use illustrator_mock::MockHost;
+use illustrator_rs::ai_sys::*;
+use illustrator_rs::{AIError, AIResult, Art, ArtType, PaintOrder, SafePlugin};
+
+#[derive(Default)]
+struct ArtPlugin;
+
+impl SafePlugin for ArtPlugin {}
+
+illustrator_rs::define_plugin!(ArtPlugin, "Art Plugin");
+
+/// スイートを使えるようにプラグインへのメッセージの中で `job` を実行する
+fn run<R>(host: &mut MockHost, job: impl FnOnce() -> AIResult<R>) -> AIResult<R> {
+    host.run_in_message(|_: &mut ArtPlugin| job())
+}
+
+fn art(handle: AIArtHandle) -> Art {
+    unsafe { Art::from_raw(handle) }.expect("handle is not null")
+}
+
+fn handles(arts: impl IntoIterator<Item = AIResult<Art>>) -> AIResult<Vec<AIArtHandle>> {
+    arts.into_iter().map(|art| art.map(Art::as_raw)).collect()
+}
+
+/// 最初のレイヤーに置いたテスト用のツリー
+struct Tree {
+    layer: AIArtHandle,
+    group: AIArtHandle,
+    first: AIArtHandle,
+    second: AIArtHandle,
+    last: AIArtHandle,
+}
+
+/// ```text
+/// Layer 1
+/// ├ group
+/// │ ├ first
+/// │ └ second
+/// └ last
+/// ```
+fn tree_host() -> (MockHost, Tree) {
+    let mut host = MockHost::new(PluginMain);
+    assert_eq!(host.startup(), kNoErr);
+
+    let bounds = AIRealRect { left: 0.0, top: 10.0, right: 10.0, bottom: 0.0 };
+    let path = AIArtType_kPathArt as i16;
+    let layer = host.layer_art(0).unwrap();
+    let group = host.add_art(AIArtType_kGroupArt as i16, layer, bounds).unwrap();
+    let first = host.add_art(path, group, bounds).unwrap();
+    let second = host.add_art(path, group, bounds).unwrap();
+    let last = host.add_art(path, layer, bounds).unwrap();
+
+    let tree = Tree { layer, group, first, second, last };
+    (host, tree)
+}
+
+#[test]
+fn children_are_listed_from_the_top() {
+    let (mut host, tree) = tree_host();
+    let Tree { layer, group, first, second, last } = tree;
+
+    let children = run(&mut host, || {
+        let layer = unsafe { Art::first_of_layer(null_mut()) }?;
+        handles(layer.children())
+    });
+    assert_eq!(children, Ok(vec![group, last]));
+
+    let siblings = run(&mut host, move || handles(art(first).siblings()));
+    assert_eq!(siblings, Ok(vec![second]));
+
+    let descendants = run(&mut host, || {
+        let layer = unsafe { Art::first_of_layer(null_mut()) }?;
+        handles(layer.descendants())
+    });
+    assert_eq!(descendants, Ok(vec![group, first, second, last]));
+
+    let relatives = run(&mut host, move || {
+        let group = art(group);
+        Ok((
+            group.parent()?.map(Art::as_raw),
+            group.first_child()?.map(Art::as_raw),
+            group.last_child()?.map(Art::as_raw),
+            art(second).prior_sibling()?.map(Art::as_raw),
+            art(last).next_sibling()?.map(Art::as_raw),
+        ))
+    });
+    assert_eq!(relatives, Ok((Some(layer), Some(first), Some(second), Some(first), None)));
+
+    let types = run(&mut host, move || Ok((art(layer).is_layer_group()?, art(group).art_type()?)));
+    assert_eq!(types, Ok((true, ArtType::Group)));
+}
+
+#[test]
+fn empty_art_has_no_children() {
+    let (mut host, tree) = tree_host();
+    let last = tree.last;
+
+    let result = run(&mut host, move || Ok((handles(art(last).children())?, handles(art(last).descendants())?)));
+    assert_eq!(result, Ok((vec![], vec![])));
+}
+
+#[test]
+fn reorder_moves_art() {
+    let (mut host, tree) = tree_host();
+    let Tree { layer, group, first, second, last } = tree;
+
+    assert_eq!(run(&mut host, move || art(last).reorder(PaintOrder::Above, Some(art(first)))), Ok(()));
+    assert_eq!(host.art_children(group), [last, first, second]);
+    assert_eq!(host.art_children(layer), [group]);
+
+    assert_eq!(run(&mut host, move || art(first).reorder(PaintOrder::Below, Some(art(second)))), Ok(()));
+    assert_eq!(host.art_children(group), [last, second, first]);
+
+    assert_eq!(run(&mut host, move || art(second).reorder(PaintOrder::BelowAll, None)), Ok(()));
+    assert_eq!(host.art_children(layer), [group, second]);
+
+    assert_eq!(run(&mut host, move || art(second).reorder(PaintOrder::InsideOnTop, Some(art(group)))), Ok(()));
+    assert_eq!(host.art_children(group), [second, last, first]);
+
+    assert_eq!(run(&mut host, move || art(last).reorder(PaintOrder::AboveAll, None)), Ok(()));
+    assert_eq!(host.art_children(layer), [last, group]);
+
+    let parent = run(&mut host, move || Ok(art(last).parent()?.map(Art::as_raw)));
+    assert_eq!(parent, Ok(Some(layer)));
+}
+
+#[test]
+fn reorder_rejects_invalid_destinations() {
+    let (mut host, tree) = tree_host();
+    let Tree { layer, group, first, second, last } = tree;
+
+    // パスの中には置けない
+    let result = run(&mut host, move || art(last).reorder(PaintOrder::InsideOnTop, Some(art(first))));
+    assert_eq!(result, Err(AIError::InvalidArtTypeForDest));
+
+    // 自分の子孫の中には置けない
+    let result = run(&mut host, move || art(group).reorder(PaintOrder::Above, Some(art(second))));
+    assert_eq!(result, Err(AIError::BadParameter));
+
+    assert_eq!(host.art_children(layer), [group, last]);
+    assert_eq!(host.art_children(group), [first, second]);
+}
@@ -0,0 +1,109 @@
+//! `Artboards` の挿入・削除・複製と `ArtboardRange` の解釈
+
+use illustrator_mock::MockHost;
+use illustrator_rs::ai_sys::*;
+use illustrator_rs::{AIError, AIResult, ArtboardProperties, ArtboardRange, Artboards, Rect, SafePlugin};
+
+#[derive(Default)]
+struct ArtboardPlugin;
+
+impl SafePlugin for ArtboardPlugin {}
+
+illustrator_rs::define_plugin!(ArtboardPlugin, "Artboard Plugin");
+
+/// スイートを使えるようにプラグインへのメッセージの中で `job` を実行する
+fn run<R>(host: &mut MockHost, job: impl FnOnce() -> AIResult<R>) -> AIResult<R> {
+    host.run_in_message(|_: &mut ArtboardPlugin| job())
+}
+
+fn started_host() -> MockHost {
+    let mut host = MockHost::new(PluginMain);
+    assert_eq!(host.startup(), kNoErr);
+    host
+}
+
+fn names(host: &MockHost) -> Vec<String> {
+    host.artboards().into_iter().map(|artboard| artboard.name).collect()
+}
+
+#[test]
+fn insert_delete_and_duplicate() {
+    let mut host = started_host();
+    assert_eq!(names(&host), ["Artboard 1"]);
+    assert_eq!(host.active_artboard(), Some(0));
+
+    let inserted = run(&mut host, || {
+        let mut properties = ArtboardProperties::new()?;
+        properties.set_name("Cover")?;
+        properties.set_bounds(Rect::new(-200.0, 100.0, -100.0, 0.0))?;
+        Artboards::current()?.insert(0, &mut properties)
+    });
+    assert_eq!(inserted, Ok(0));
+    assert_eq!(names(&host), ["Cover", "Artboard 1"]);
+    assert_eq!(host.artboards()[0].bounds, (-200.0, 100.0, -100.0, 0.0));
+    // 前に挿入されたのでアクティブなアートボードの番号もずれる
+    assert_eq!(host.active_artboard(), Some(1));
+
+    let copy = run(&mut host, || Artboards::current()?.duplicate(0));
+    assert_eq!(copy, Ok(1));
+    assert_eq!(names(&host), ["Cover", "Cover", "Artboard 1"]);
+    assert_eq!(host.artboards()[1].bounds, host.artboards()[0].bounds);
+    assert_eq!(host.active_artboard(), Some(2));
+
+    let count = run(&mut host, || {
+        let mut artboards = Artboards::current()?;
+        artboards.delete(0)?;
+        artboards.len()
+    });
+    assert_eq!(count, Ok(2));
+    assert_eq!(names(&host), ["Cover", "Artboard 1"]);
+    assert_eq!(host.active_artboard(), Some(1));
+}
+
+#[test]
+fn out_of_range_index_is_rejected() {
+    let mut host = started_host();
+
+    assert_eq!(run(&mut host, || Artboards::current()?.delete(1)), Err(AIError::BadParameter));
+    assert_eq!(run(&mut host, || Artboards::current()?.duplicate(3)), Err(AIError::BadParameter));
+    assert_eq!(names(&host), ["Artboard 1"]);
+}
+
+#[test]
+fn cloned_properties_are_independent() {
+    let mut host = started_host();
+
+    let names = run(&mut host, || {
+        let mut properties = ArtboardProperties::new()?;
+        properties.set_name("Original")?;
+        let mut copy = properties.try_clone()?;
+        copy.set_name("Copy")?;
+        Ok((properties.name()?, copy.name()?))
+    });
+    assert_eq!(names, Ok(("Original".to_string(), "Copy".to_string())));
+}
+
+#[test]
+fn artboards_need_a_document() {
+    let mut host = started_host();
+    host.close_document();
+
+    assert!(run(&mut host, || Artboards::current().map(drop)).is_err());
+}
+
+#[test]
+fn range_lists_zero_based_indices() {
+    let mut host = started_host();
+
+    let indices = run(&mut host, || ArtboardRange::parse("1-3,5")?.to_vec());
+    assert_eq!(indices, Ok(vec![0, 1, 2, 4]));
+
+    let all = run(&mut host, || {
+        let range = ArtboardRange::all()?;
+        Ok((range.is_all()?, range.to_vec()?))
+    });
+    assert_eq!(all, Ok((true, vec![0])));
+
+    assert_eq!(run(&mut host, || ArtboardRange::validate("1-3,5")), Ok(()));
+    assert!(run(&mut host, || ArtboardRange::parse("a-b").map(drop)).is_err());
+}
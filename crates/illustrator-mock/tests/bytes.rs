@@ -0,0 +1,60 @@
+//! `to_bytes` で書き出したバイト列の読み込みと、壊れたデータの拒否
+
+use illustrator_mock::MockHost;
+use illustrator_rs::ai_sys::*;
+use illustrator_rs::bytes::{from_bytes, to_bytes};
+use illustrator_rs::dictionary::Error;
+use illustrator_rs::{AIResult, SafePlugin};
+
+#[derive(Default)]
+struct BytesPlugin;
+
+impl SafePlugin for BytesPlugin {}
+
+illustrator_rs::define_plugin!(BytesPlugin, "Bytes Plugin");
+
+/// スイートを使えるようにプラグインへのメッセージの中で `job` を実行する
+fn run<R>(job: impl FnOnce() -> AIResult<R>) -> AIResult<R> {
+    let mut host = MockHost::new(PluginMain);
+    assert_eq!(host.startup(), kNoErr);
+    host.run_in_message(|_: &mut BytesPlugin| job())
+}
+
+/// 1 要素の配列を `depth` 段重ねた中に整数を置いたデータ
+fn nested(depth: usize) -> Vec<u8> {
+    let mut bytes = vec![1];
+    for _ in 0..depth {
+        bytes.push(6);
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+    }
+    bytes.push(1);
+    bytes.extend_from_slice(&7i32.to_le_bytes());
+    bytes
+}
+
+#[test]
+fn values_round_trip() {
+    let value = (7, vec!["a".to_owned(), "β".to_owned()], Some(0.5));
+    let result = run(|| Ok(to_bytes(&value).and_then(|bytes| from_bytes::<(i32, Vec<String>, Option<f64>)>(&bytes))));
+    assert_eq!(result, Ok(Ok(value)));
+}
+
+#[test]
+fn nesting_is_limited() {
+    let result = run(|| Ok(from_bytes::<Vec<Vec<i32>>>(&nested(2))));
+    assert_eq!(result, Ok(Ok(vec![vec![7]])));
+
+    let result = run(|| Ok(from_bytes::<()>(&nested(10_000))));
+    assert!(matches!(result, Ok(Err(Error::Message(_)))));
+}
+
+#[test]
+fn broken_data_is_rejected() {
+    let mut trailing = nested(0);
+    trailing.push(0);
+
+    for bytes in [vec![], vec![2, 1, 0, 0, 0, 0], nested(1)[..7].to_vec(), trailing] {
+        let result = run(|| Ok(from_bytes::<i32>(&bytes)));
+        assert!(matches!(result, Ok(Err(Error::Message(_)))), "{:?}", bytes);
+    }
+}
@@ -0,0 +1,134 @@
+//! 辞書へのシリアライズと、格納された値のデシリアライズ
+
+use illustrator_mock::MockHost;
+use illustrator_rs::ai_sys::*;
+use illustrator_rs::dictionary::Error;
+use illustrator_rs::{AIResult, Dictionary, EntryType, SafePlugin};
+use serde::{Deserialize, Serialize};
+
+#[derive(Default)]
+struct DictionaryPlugin;
+
+impl SafePlugin for DictionaryPlugin {}
+
+illustrator_rs::define_plugin!(DictionaryPlugin, "Dictionary Plugin");
+
+/// スイートを使えるようにプラグインへのメッセージの中で `job` を実行する
+fn run<R>(job: impl FnOnce() -> AIResult<R>) -> AIResult<R> {
+    let mut host = MockHost::new(PluginMain);
+    assert_eq!(host.startup(), kNoErr);
+    host.run_in_message(|_: &mut DictionaryPlugin| job())
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Corner {
+    radius: f64,
+    round: bool,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Square,
+    Star(i32),
+    Polygon { sides: i32, inset: f64 },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Settings {
+    name: String,
+    corner: Corner,
+    sizes: Vec<i32>,
+    label: Option<String>,
+    note: Option<String>,
+    shapes: Vec<Shape>,
+    #[serde(with = "serde_bytes")]
+    thumbnail: Vec<u8>,
+}
+
+fn settings() -> Settings {
+    Settings {
+        name: "角丸 & Star".into(),
+        corner: Corner { radius: 4.5, round: true },
+        sizes: vec![8, 16, 32],
+        label: Some("Label".into()),
+        note: None,
+        shapes: vec![Shape::Square, Shape::Star(5), Shape::Polygon { sides: 6, inset: 0.25 }],
+        thumbnail: vec![0, 1, 254, 255],
+    }
+}
+
+#[test]
+fn fields_round_trip() {
+    let result = run(|| {
+        let mut dictionary = Dictionary::new()?;
+        dictionary.store_fields(&settings()).expect("settings are stored");
+
+        let types = (
+            dictionary.entry_type("name")?,
+            dictionary.entry_type("corner")?,
+            dictionary.entry_type("sizes")?,
+            dictionary.entry_type("note")?,
+            dictionary.entry_type("thumbnail")?,
+        );
+        let loaded: Settings = dictionary.load_fields().expect("settings are loaded");
+        Ok((types, loaded))
+    });
+
+    let types = (
+        Some(EntryType::UnicodeString),
+        Some(EntryType::Dictionary),
+        Some(EntryType::Array),
+        None,
+        Some(EntryType::Binary),
+    );
+    assert_eq!(result, Ok((types, settings())));
+}
+
+#[test]
+fn store_fields_keeps_other_keys() {
+    let result = run(|| {
+        let mut dictionary = Dictionary::new()?;
+        dictionary.set_int("extra", 7)?;
+        dictionary.store_fields(&Corner { radius: 1.0, round: false }).expect("corner is stored");
+        Ok((dictionary.get_int("extra")?, dictionary.len()?))
+    });
+    assert_eq!(result, Ok((Some(7), 3)));
+}
+
+#[test]
+fn integers_are_read_as_reals() {
+    let result = run(|| {
+        let mut dictionary = Dictionary::new()?;
+        dictionary.set_int("radius", 3)?;
+        dictionary.set_bool("round", true)?;
+        Ok(dictionary.load_fields::<Corner>())
+    });
+    assert_eq!(result, Ok(Ok(Corner { radius: 3.0, round: true })));
+}
+
+#[test]
+fn c_strings_and_unicode_strings_are_both_strings() {
+    let result = run(|| {
+        let mut dictionary = Dictionary::new()?;
+        dictionary.set_c_string("plain", c"Star")?;
+        dictionary.set_unicode("wide", "星形")?;
+
+        // ユニットバリアントは C 文字列からも読める
+        dictionary.set_c_string("shape", c"Square")?;
+        Ok((dictionary.load::<String>("plain"), dictionary.load::<String>("wide"), dictionary.load::<Shape>("shape")))
+    });
+
+    let strings = (Ok(Some("Star".to_owned())), Ok(Some("星形".to_owned())), Ok(Some(Shape::Square)));
+    assert_eq!(result, Ok(strings));
+}
+
+#[test]
+fn mismatched_types_are_errors() {
+    let result = run(|| {
+        let mut dictionary = Dictionary::new()?;
+        dictionary.set_unicode("radius", "wide")?;
+        dictionary.set_bool("round", true)?;
+        Ok(dictionary.load_fields::<Corner>())
+    });
+    assert!(matches!(result, Ok(Err(Error::Message(_)))));
+}
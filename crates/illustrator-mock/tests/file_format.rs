@@ -0,0 +1,114 @@
+//! ファイルフォーマットの設定がアクションのパラメータで受け渡され、読めない設定では直前の値を使うこと
+
+use std::cell::RefCell;
+use std::io::Write;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use illustrator_mock::{ActionValue, MockHost};
+use illustrator_rs::ai_sys::*;
+use illustrator_rs::{AIResult, FileContext, FileFormat, FileFormats, FormatCapabilities, SafePlugin};
+
+/// `edit_options` で 1 増やし、`write` で受け取った値を記録する
+struct Counter {
+    writes: Rc<RefCell<Vec<u32>>>,
+}
+
+impl FileFormat<FormatPlugin> for Counter {
+    type Options = u32;
+
+    fn edit_options(&mut self, _: &mut FormatPlugin, _: &FileContext, options: &mut u32) -> AIResult<()> {
+        *options += 1;
+        Ok(())
+    }
+
+    fn write(&mut self, _: &mut FormatPlugin, _: &FileContext, writer: &mut dyn Write, options: &u32) -> AIResult<()> {
+        self.writes.borrow_mut().push(*options);
+        writeln!(writer, "{}", options)?;
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct FormatPlugin {
+    formats: FileFormats<Self>,
+    writes: Rc<RefCell<Vec<u32>>>,
+}
+
+impl SafePlugin for FormatPlugin {
+    fn startup(&mut self) -> AIResult<()> {
+        let writes = self.writes.clone();
+        self.formats
+            .format(c"format:counter", "Counter")
+            .extension("count")
+            .capabilities(FormatCapabilities::WRITE)
+            .add(Counter { writes })?;
+        Ok(())
+    }
+
+    fn file_formats(&mut self) -> Option<&mut FileFormats<Self>> {
+        Some(&mut self.formats)
+    }
+}
+
+illustrator_rs::define_plugin!(FormatPlugin, "Format Plugin");
+
+/// 設定を格納するキー（`'RsOp'`）
+const OPTIONS_KEY: ActionParamKeyID = u32::from_be_bytes(*b"RsOp");
+const WRITE: i32 = AIFileFormatOptions_kFileFormatWrite as i32;
+
+fn started() -> (MockHost, AIFileFormatHandle) {
+    let mut host = MockHost::new(PluginMain);
+    assert_eq!(host.startup(), kNoErr);
+    let format = host.file_format("format:counter").expect("format was added");
+    (host, format)
+}
+
+fn output(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("illustrator-mock-{}-{}.count", name, std::process::id()))
+}
+
+fn writes(host: &mut MockHost) -> Vec<u32> {
+    host.with_plugin(|plugin: &mut FormatPlugin| plugin.writes.borrow().clone())
+}
+
+#[test]
+fn options_are_replayed_from_the_action_parameters() {
+    let (mut host, format) = started();
+    let path = output("replay");
+    let file = path.to_str().unwrap();
+    let parameters = host.new_action_parameters();
+
+    assert_eq!(host.get_file_format_parameters(format, WRITE, file, parameters), kNoErr);
+    assert_eq!(host.go_file_format(format, WRITE, file, parameters), kNoErr);
+    assert!(matches!(host.action_parameter(parameters, OPTIONS_KEY), Some(ActionValue::Raw(_))));
+
+    // 記録したパラメータを再生すると、編集せずに同じ設定で書き出す
+    assert_eq!(host.set_file_format_parameters(format, parameters), kNoErr);
+    assert_eq!(host.go_file_format(format, WRITE, file, parameters), kNoErr);
+    assert_eq!(writes(&mut host), vec![1, 1]);
+
+    host.release_action_parameters(parameters);
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn unreadable_options_keep_the_last_options() {
+    let (mut host, format) = started();
+    let path = output("unreadable");
+    let file = path.to_str().unwrap();
+    let parameters = host.new_action_parameters();
+
+    assert_eq!(host.get_file_format_parameters(format, WRITE, file, parameters), kNoErr);
+    assert_eq!(host.get_file_format_parameters(format, WRITE, file, parameters), kNoErr);
+
+    // 別の `Options` で記録されたような、読めない設定
+    host.set_action_parameter(parameters, OPTIONS_KEY, ActionValue::Raw(vec![0xff, 0x00]));
+    assert_eq!(host.set_file_format_parameters(format, parameters), kNoErr);
+    assert_eq!(host.go_file_format(format, WRITE, file, parameters), kNoErr);
+    assert_eq!(writes(&mut host), vec![2]);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "2\n");
+
+    host.release_action_parameters(parameters);
+    let _ = std::fs::remove_file(path);
+}
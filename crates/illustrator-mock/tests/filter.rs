@@ -0,0 +1,132 @@
+//! フィルターのパラメータが実行の間で保持され、読めないブロックは既定値に戻ること
+
+use std::cell::RefCell;
+use std::ffi::c_void;
+use std::ptr::null_mut;
+use std::rc::Rc;
+
+use illustrator_mock::MockHost;
+use illustrator_rs::ai_sys::*;
+use illustrator_rs::{AIResult, Filter, FilterItem, Filters, SafePlugin, SuiteFn, SuiteGuard};
+
+/// `edit_parameters` で 1 増やし、`go` で受け取った値を記録する
+struct Counter {
+    runs: Rc<RefCell<Vec<u32>>>,
+}
+
+impl Filter<FilterPlugin> for Counter {
+    type Parameters = u32;
+
+    fn edit_parameters(&mut self, _: &mut FilterPlugin, _: FilterItem, parameters: &mut u32) -> AIResult<()> {
+        *parameters += 1;
+        Ok(())
+    }
+
+    fn go(&mut self, _: &mut FilterPlugin, _: FilterItem, parameters: &u32) -> AIResult<()> {
+        self.runs.borrow_mut().push(*parameters);
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct FilterPlugin {
+    filters: Filters<Self>,
+    runs: Rc<RefCell<Vec<u32>>>,
+}
+
+impl SafePlugin for FilterPlugin {
+    fn startup(&mut self) -> AIResult<()> {
+        let runs = self.runs.clone();
+        self.filters.add(c"filter:counter", "Test", "Counter", Counter { runs })?;
+        Ok(())
+    }
+
+    fn filters(&mut self) -> Option<&mut Filters<Self>> {
+        Some(&mut self.filters)
+    }
+}
+
+illustrator_rs::define_plugin!(FilterPlugin, "Filter Plugin");
+
+/// スイートを使えるようにプラグインへのメッセージの中で `job` を実行する
+fn run<R>(host: &mut MockHost, job: impl FnOnce() -> AIResult<R>) -> AIResult<R> {
+    host.run_in_message(|_: &mut FilterPlugin| job())
+}
+
+fn started() -> (MockHost, AIFilterHandle) {
+    let mut host = MockHost::new(PluginMain);
+    assert_eq!(host.startup(), kNoErr);
+    let filter = host.with_plugin(|plugin: &mut FilterPlugin| plugin.filters.filters().next().map(FilterItem::as_raw));
+    (host, filter.expect("filter was added"))
+}
+
+fn runs(host: &mut MockHost) -> Vec<u32> {
+    host.with_plugin(|plugin: &mut FilterPlugin| plugin.runs.borrow().clone())
+}
+
+fn filter_parameters(host: &mut MockHost, filter: AIFilterHandle) -> PlatformFilterParameters {
+    let block = run(host, || {
+        let suite = SuiteGuard::<AIFilterSuite>::acquire()?;
+        let mut block: PlatformFilterParameters = null_mut();
+        unsafe { suite.GetFilterParameters.call((filter, &mut block as *mut _))? };
+        Ok(block)
+    });
+    block.expect("parameters are readable")
+}
+
+fn set_filter_parameters(host: &mut MockHost, filter: AIFilterHandle, block: PlatformFilterParameters) {
+    run(host, || {
+        let suite = SuiteGuard::<AIFilterSuite>::acquire()?;
+        unsafe { suite.SetFilterParameters.call((filter, block)) }
+    })
+    .expect("parameters are replaced");
+}
+
+#[test]
+fn parameters_are_kept_between_runs() {
+    let (mut host, filter) = started();
+
+    assert_eq!(host.run_filter(filter), kNoErr);
+    assert_eq!(host.go_filter(filter), kNoErr);
+    assert_eq!(host.run_filter(filter), kNoErr);
+    assert_eq!(runs(&mut host), vec![1, 1, 2]);
+}
+
+#[test]
+fn foreign_block_falls_back_to_default() {
+    let (mut host, filter) = started();
+    assert_eq!(host.run_filter(filter), kNoErr);
+    assert_eq!(host.run_filter(filter), kNoErr);
+
+    // データ長が大きすぎるヘッダーを持つ、プラグインが確保していないブロック
+    let mut foreign = [0xffu8; 8];
+    set_filter_parameters(&mut host, filter, foreign.as_mut_ptr() as *mut c_void);
+    assert_eq!(host.go_filter(filter), kNoErr);
+    assert_eq!(runs(&mut host), vec![1, 2, 0]);
+}
+
+#[test]
+fn corrupted_data_falls_back_to_default() {
+    let (mut host, filter) = started();
+    assert_eq!(host.run_filter(filter), kNoErr);
+    assert_eq!(host.run_filter(filter), kNoErr);
+
+    // ヘッダーの後ろにある形式の版を壊す
+    let block = filter_parameters(&mut host, filter);
+    unsafe { *(block as *mut u8).add(std::mem::size_of::<u32>()) = 0xff };
+    assert_eq!(host.go_filter(filter), kNoErr);
+    assert_eq!(runs(&mut host), vec![1, 2, 0]);
+}
+
+#[test]
+fn foreign_block_is_not_reallocated() {
+    let (mut host, filter) = started();
+    assert_eq!(host.run_filter(filter), kNoErr);
+
+    let mut foreign = [0xffu8; 8];
+    let foreign = foreign.as_mut_ptr() as *mut c_void;
+    set_filter_parameters(&mut host, filter, foreign);
+    assert_eq!(host.run_filter(filter), kNoErr);
+    assert_ne!(filter_parameters(&mut host, filter), foreign);
+    assert_eq!(runs(&mut host), vec![1, 1]);
+}
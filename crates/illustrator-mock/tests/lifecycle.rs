@@ -0,0 +1,160 @@
+//! `define_plugin!` のプラグインに startup → notify → menu → shutdown を送る
+
+use std::cell::Cell;
+use std::ffi::CString;
+use std::ptr::null_mut;
+
+use illustrator_mock::{HostEvent, MockHost};
+use illustrator_rs::ai_suites::AISuite;
+use illustrator_rs::{AIError, AIResult, Menus, NotifierType, Notifiers, SafePlugin, Suite};
+use illustrator_rs::ai_sys::AIMenuSuite;
+
+thread_local! {
+    /// `true` なら `startup` が失敗する
+    static FAIL_STARTUP: Cell<bool> = const { Cell::new(false) };
+}
+
+#[derive(Default)]
+struct LifecyclePlugin {
+    notifiers: Notifiers<Self>,
+    menus: Menus<Self>,
+    post_started: bool,
+    documents_opened: usize,
+    selected: usize,
+}
+
+impl SafePlugin for LifecyclePlugin {
+    fn startup(&mut self) -> AIResult<()> {
+        if FAIL_STARTUP.get() {
+            return Err(AIError::CantHappen);
+        }
+        self.notifiers.subscribe(NotifierType::DocumentOpened, |plugin: &mut Self, _| {
+            plugin.documents_opened += 1;
+            Ok(())
+        })?;
+        self.menus
+            .item(c"lifecycle:hello", c"Lifecycle Group", "Hello")
+            .on_select(|plugin: &mut Self| {
+                plugin.selected += 1;
+                Ok(())
+            })
+            .add()?;
+        Ok(())
+    }
+
+    fn post_startup(&mut self) -> AIResult<()> {
+        self.post_started = true;
+        Ok(())
+    }
+
+    fn notifiers(&mut self) -> Option<&mut Notifiers<Self>> {
+        Some(&mut self.notifiers)
+    }
+
+    fn menus(&mut self) -> Option<&mut Menus<Self>> {
+        Some(&mut self.menus)
+    }
+}
+
+illustrator_rs::define_plugin!(LifecyclePlugin, name = "Lifecycle Plugin", suites = [AISuite::MenuSuite]);
+
+/// ホストが `globals` に保持しているプラグインを借りる
+fn plugin<R>(host: &mut MockHost, f: impl FnOnce(&mut LifecyclePlugin) -> R) -> R {
+    host.with_plugin(f)
+}
+
+fn menu_suite_refcount(host: &MockHost) -> Option<i32> {
+    host.suite_refcount(AIMenuSuite::name(), AIMenuSuite::version())
+}
+
+#[test]
+fn startup_registers_the_plugin() {
+    let mut host = MockHost::new(PluginMain);
+
+    assert_eq!(host.startup(), 0);
+    assert!(!host.globals().is_null());
+
+    let events = host.events();
+    assert!(events.contains(&HostEvent::SetPluginName("Lifecycle Plugin".into())));
+    assert!(events.contains(&HostEvent::AcquireSuite {
+        name: AISuite::MenuSuite.name().to_string_lossy().into_owned(),
+        version: AISuite::MenuSuite.version(),
+    }));
+    assert!(events.contains(&HostEvent::AddMenuItem("lifecycle:hello".into())));
+    assert!(events.contains(&HostEvent::AcquirePlugin));
+    assert_eq!(menu_suite_refcount(&host), Some(1));
+
+    let notifiers: Vec<String> = host.notifiers().into_iter().map(|(name, _)| name).collect();
+    for notifier in [
+        NotifierType::ApplicationStarted,
+        NotifierType::ApplicationShutdown,
+        NotifierType::DocumentOpened,
+    ] {
+        assert!(notifiers.contains(&notifier.name().to_string_lossy().into_owned()));
+    }
+}
+
+#[test]
+fn notifications_reach_their_handlers() {
+    let mut host = MockHost::new(PluginMain);
+    assert_eq!(host.startup(), 0);
+    assert!(!plugin(&mut host, |plugin| plugin.post_started));
+
+    assert_eq!(host.app_started(), 0);
+    assert!(plugin(&mut host, |plugin| plugin.post_started));
+
+    assert_eq!(host.notify(NotifierType::DocumentOpened.name(), null_mut()), 0);
+    assert_eq!(host.notify(NotifierType::DocumentOpened.name(), null_mut()), 0);
+    assert_eq!(plugin(&mut host, |plugin| plugin.documents_opened), 2);
+}
+
+#[test]
+fn menu_items_dispatch_to_their_handlers() {
+    let mut host = MockHost::new(PluginMain);
+    assert_eq!(host.startup(), 0);
+
+    let item = host.menu_item("lifecycle:hello").expect("menu item was added");
+    assert_eq!(host.go_menu_item(item), 0);
+    assert_eq!(host.go_menu_item(item), 0);
+    assert_eq!(plugin(&mut host, |plugin| plugin.selected), 2);
+}
+
+#[test]
+fn shutdown_releases_the_plugin_and_its_suites() {
+    let mut host = MockHost::new(PluginMain);
+    assert_eq!(host.startup(), 0);
+    assert_eq!(host.app_started(), 0);
+    assert_eq!(host.app_shutdown(), 0);
+    host.take_events();
+
+    assert_eq!(host.shutdown(), 0);
+    assert!(host.globals().is_null());
+    assert_eq!(menu_suite_refcount(&host), Some(0));
+    assert!(host.events().contains(&HostEvent::ReleaseSuite {
+        name: AISuite::MenuSuite.name().to_string_lossy().into_owned(),
+        version: AISuite::MenuSuite.version(),
+    }));
+
+    // 終了後のメッセージは受け取らない
+    assert_eq!(host.app_started(), 0);
+}
+
+#[test]
+fn failed_startup_releases_its_suites() {
+    FAIL_STARTUP.set(true);
+    let mut host = MockHost::new(PluginMain);
+    let error = host.startup();
+    FAIL_STARTUP.set(false);
+
+    assert_eq!(error, AIError::CantHappen.code());
+    assert!(host.globals().is_null());
+    assert_eq!(menu_suite_refcount(&host), Some(0));
+
+    // 起動中に取得したスイートはすべて返されている
+    for event in host.events() {
+        if let HostEvent::AcquireSuite { name, version } = event {
+            let name = CString::new(name).expect("suite name has no NUL");
+            assert_eq!(host.suite_refcount(&name, version), Some(0), "{:?} was not released", name);
+        }
+    }
+}
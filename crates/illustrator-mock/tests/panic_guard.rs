@@ -0,0 +1,114 @@
+//! ハンドラ内のパニックは捕捉され、リロードまでプラグインを停止させる
+
+use std::ptr::null_mut;
+use std::sync::Mutex;
+
+use illustrator_mock::{HostEvent, MockHost};
+use illustrator_rs::ai_sys::*;
+use illustrator_rs::panic_guard::{self, PanicReport};
+use illustrator_rs::{kRustPanicErr, AIPlugin, NotifierType};
+
+#[derive(Default)]
+struct PanickingPlugin {
+    notified: usize,
+}
+
+impl AIPlugin for PanickingPlugin {
+    fn Notify(&mut self, _message: *mut AINotifierMessage) -> ASErr {
+        self.notified += 1;
+        if self.notified == 1 {
+            panic!("boom");
+        }
+        kNoErr
+    }
+}
+
+illustrator_rs::define_plugin!(PanickingPlugin, "Panicking Plugin");
+
+static REPORTS: Mutex<Vec<PanicReport>> = Mutex::new(Vec::new());
+
+fn record(report: &PanicReport) {
+    REPORTS.lock().unwrap().push(report.clone());
+}
+
+fn alerts(host: &MockHost) -> Vec<String> {
+    host.events()
+        .into_iter()
+        .filter_map(|event| match event {
+            HostEvent::ErrorAlert(text) => Some(text),
+            _ => None,
+        })
+        .collect()
+}
+
+fn notify(host: &mut MockHost) -> ASErr {
+    host.notify(NotifierType::ApplicationStarted.name(), null_mut())
+}
+
+/// 起動してから最初の通知でパニックさせる
+fn panicked_host() -> MockHost {
+    let mut host = MockHost::new(PluginMain);
+    panic_guard::clear_poison();
+    panic_guard::set_panic_logger(record);
+    panic_guard::set_alert_on_panic(true);
+    REPORTS.lock().unwrap().clear();
+
+    assert_eq!(host.startup(), kNoErr);
+    host.take_events();
+    assert_eq!(notify(&mut host), kRustPanicErr);
+    host
+}
+
+#[test]
+fn panic_returns_panic_error() {
+    let mut host = panicked_host();
+    assert!(panic_guard::is_poisoned());
+
+    let reports = REPORTS.lock().unwrap().clone();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].payload, "boom");
+    assert_eq!(reports[0].caller.as_bytes(), &kCallerAINotify[..kCallerAINotify.len() - 1]);
+
+    assert_eq!(host.reload(), kNoErr);
+    assert_eq!(host.shutdown(), kNoErr);
+}
+
+#[test]
+fn panic_is_reported_with_error_alert() {
+    let mut host = panicked_host();
+
+    let alerts = alerts(&host);
+    assert_eq!(alerts.len(), 1);
+    assert!(alerts[0].contains("boom"));
+
+    assert_eq!(host.reload(), kNoErr);
+    assert_eq!(host.shutdown(), kNoErr);
+}
+
+#[test]
+fn poisoned_plugin_refuses_messages() {
+    let mut host = panicked_host();
+    host.take_events();
+
+    // 停止中のメッセージはハンドラへ届かない
+    assert_eq!(notify(&mut host), kRustPanicErr);
+    assert_eq!(host.shutdown(), kRustPanicErr);
+    assert!(host.events().is_empty());
+    assert!(!host.globals().is_null());
+
+    assert_eq!(host.reload(), kNoErr);
+    assert_eq!(host.shutdown(), kNoErr);
+}
+
+#[test]
+fn reload_clears_poison() {
+    let mut host = panicked_host();
+
+    assert_eq!(host.reload(), kNoErr);
+    assert!(!panic_guard::is_poisoned());
+
+    // 二度目の通知はパニックしない
+    assert_eq!(notify(&mut host), kNoErr);
+    assert_eq!(host.shutdown(), kNoErr);
+    assert!(host.globals().is_null());
+}
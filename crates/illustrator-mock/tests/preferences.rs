@@ -0,0 +1,166 @@
+//! `#[derive(Preferences)]` の設定が起動時に読み込まれ、終了時に保存されること
+
+use illustrator_mock::{MockHost, PreferenceValue};
+use illustrator_rs::ai_sys::*;
+use illustrator_rs::preferences::PluginPreferences;
+use illustrator_rs::{AIResult, PreferenceStore, Preferences, SafePlugin};
+
+const PREFIX: &str = "Preferences Test";
+
+#[derive(Clone, Debug, Default, PartialEq, Preferences)]
+#[preferences(prefix = "Preferences Test", version = 2, migrate = migrate_settings)]
+struct Settings {
+    #[preference(default = 8)]
+    grid_size: i32,
+    #[preference(key = "snap", rename_from = "snapToGrid")]
+    snap_to_grid: bool,
+    label: String,
+    ratio: f64,
+    #[preference(skip)]
+    dirty: bool,
+}
+
+/// 版 1 はグリッドの大きさを半分の値で `gridHalf` に保存していた
+fn migrate_settings(store: &mut PreferenceStore<'_>, from: i32) -> AIResult<()> {
+    if from < 2 {
+        if let Some(half) = store.get::<i32>("gridHalf")? {
+            store.set("grid_size", &(half * 2))?;
+            store.remove("gridHalf")?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct PreferencesPlugin {
+    settings: Settings,
+}
+
+impl SafePlugin for PreferencesPlugin {
+    fn preferences(&mut self) -> Option<&mut dyn PluginPreferences> {
+        Some(&mut self.settings)
+    }
+}
+
+illustrator_rs::define_plugin!(PreferencesPlugin, "Preferences Plugin");
+
+fn settings(host: &mut MockHost) -> Settings {
+    host.with_plugin(|plugin: &mut PreferencesPlugin| plugin.settings.clone())
+}
+
+fn set_settings(host: &mut MockHost, settings: Settings) {
+    host.with_plugin(|plugin: &mut PreferencesPlugin| plugin.settings = settings);
+}
+
+fn started_host() -> MockHost {
+    let mut host = MockHost::new(PluginMain);
+    assert_eq!(host.startup(), kNoErr);
+    host
+}
+
+fn preference(host: &MockHost, key: &str) -> Option<PreferenceValue> {
+    host.preference(PREFIX, key)
+}
+
+fn set_preference(host: &mut MockHost, key: &str, value: PreferenceValue) {
+    host.set_preference(PREFIX, key, Some(value));
+}
+
+#[test]
+fn unsaved_settings_use_defaults() {
+    let mut host = started_host();
+    assert_eq!(host.app_started(), kNoErr);
+
+    let expected = Settings { grid_size: 8, ..Settings::default() };
+    assert_eq!(settings(&mut host), expected);
+    assert_eq!(preference(&host, "_version"), Some(PreferenceValue::Integer(2)));
+}
+
+#[test]
+fn settings_round_trip() {
+    let mut host = started_host();
+    assert_eq!(host.app_started(), kNoErr);
+
+    let changed = Settings {
+        grid_size: 12,
+        snap_to_grid: true,
+        label: "Grid & Guides".into(),
+        ratio: 0.25,
+        dirty: true,
+    };
+    set_settings(&mut host, changed);
+    assert_eq!(host.app_shutdown(), kNoErr);
+
+    assert_eq!(preference(&host, "grid_size"), Some(PreferenceValue::Integer(12)));
+    assert_eq!(preference(&host, "snap"), Some(PreferenceValue::Boolean(true)));
+    assert_eq!(preference(&host, "label"), Some(PreferenceValue::String("Grid & Guides".into())));
+    assert_eq!(preference(&host, "ratio"), Some(PreferenceValue::Real(0.25)));
+    assert_eq!(preference(&host, "dirty"), None);
+
+    set_settings(&mut host, Settings::default());
+    assert_eq!(host.app_started(), kNoErr);
+    let expected = Settings {
+        grid_size: 12,
+        snap_to_grid: true,
+        label: "Grid & Guides".into(),
+        ratio: 0.25,
+        dirty: false,
+    };
+    assert_eq!(settings(&mut host), expected);
+}
+
+#[test]
+fn old_version_is_migrated() {
+    let mut host = started_host();
+    set_preference(&mut host, "_version", PreferenceValue::Integer(1));
+    set_preference(&mut host, "snapToGrid", PreferenceValue::Boolean(true));
+    set_preference(&mut host, "gridHalf", PreferenceValue::Integer(5));
+
+    assert_eq!(host.app_started(), kNoErr);
+    let settings = settings(&mut host);
+    assert!(settings.snap_to_grid);
+    assert_eq!(settings.grid_size, 10);
+
+    // 古いキーは新しいキーへ移される
+    assert_eq!(preference(&host, "snapToGrid"), None);
+    assert_eq!(preference(&host, "snap"), Some(PreferenceValue::Boolean(true)));
+    assert_eq!(preference(&host, "gridHalf"), None);
+    assert_eq!(preference(&host, "grid_size"), Some(PreferenceValue::Integer(10)));
+    assert_eq!(preference(&host, "_version"), Some(PreferenceValue::Integer(2)));
+}
+
+#[test]
+fn unversioned_settings_are_migrated() {
+    let mut host = started_host();
+    set_preference(&mut host, "snapToGrid", PreferenceValue::Boolean(true));
+
+    assert_eq!(host.app_started(), kNoErr);
+    assert!(settings(&mut host).snap_to_grid);
+    assert_eq!(preference(&host, "snapToGrid"), None);
+}
+
+#[test]
+fn rename_keeps_the_new_key() {
+    let mut host = started_host();
+    set_preference(&mut host, "_version", PreferenceValue::Integer(1));
+    set_preference(&mut host, "snapToGrid", PreferenceValue::Boolean(true));
+    set_preference(&mut host, "snap", PreferenceValue::Boolean(false));
+
+    assert_eq!(host.app_started(), kNoErr);
+    assert!(!settings(&mut host).snap_to_grid);
+    assert_eq!(preference(&host, "snapToGrid"), None);
+    assert_eq!(preference(&host, "snap"), Some(PreferenceValue::Boolean(false)));
+}
+
+#[test]
+fn current_version_is_not_migrated() {
+    let mut host = started_host();
+    set_preference(&mut host, "_version", PreferenceValue::Integer(2));
+    set_preference(&mut host, "snapToGrid", PreferenceValue::Boolean(true));
+    set_preference(&mut host, "gridHalf", PreferenceValue::Integer(5));
+
+    assert_eq!(host.app_started(), kNoErr);
+    assert_eq!(settings(&mut host), Settings { grid_size: 8, ..Settings::default() });
+    assert_eq!(preference(&host, "snapToGrid"), Some(PreferenceValue::Boolean(true)));
+    assert_eq!(preference(&host, "gridHalf"), Some(PreferenceValue::Integer(5)));
+}
@@ -0,0 +1,52 @@
+//! 必須スイートが取得できないプラグインは起動に失敗する
+
+use illustrator_mock::{HostEvent, MockHost};
+use illustrator_rs::ai_suites::AISuite;
+use illustrator_rs::{AIError, AIPlugin};
+
+#[derive(Default)]
+struct RasterPlugin;
+
+impl AIPlugin for RasterPlugin {}
+
+// モックホストは AIRasterSuite を提供しない
+illustrator_rs::define_plugin!(
+    RasterPlugin,
+    name = "Raster Plugin",
+    suites = [AISuite::ArtSuite, AISuite::RasterSuite],
+    optional_suites = [AISuite::LiveEffectSuite],
+);
+
+#[test]
+fn missing_required_suite_fails_startup() {
+    let mut host = MockHost::new(PluginMain);
+
+    assert_eq!(host.startup(), AIError::SPSuiteNotFound.code());
+    assert!(host.globals().is_null());
+
+    let raster = AISuite::RasterSuite.name().to_string_lossy().into_owned();
+    let alerts: Vec<String> = host
+        .events()
+        .into_iter()
+        .filter_map(|event| match event {
+            HostEvent::ErrorAlert(text) => Some(text),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(alerts.len(), 1);
+    assert!(alerts[0].contains("Raster Plugin"));
+    assert!(alerts[0].contains(&raster));
+
+    // 取得できたスイートはすべて解放されている
+    for suite in [AISuite::ArtSuite, AISuite::LiveEffectSuite] {
+        assert_eq!(host.suite_refcount(suite.name(), suite.version()), Some(0));
+    }
+}
+
+#[test]
+fn messages_before_startup_are_ignored() {
+    let mut host = MockHost::new(PluginMain);
+
+    assert_eq!(host.app_started(), 0);
+    assert!(host.events().is_empty());
+}
@@ -0,0 +1,163 @@
+//! モックのドキュメントを `SvgFormat` で書き出し、期待する SVG と比べる
+
+use illustrator_mock::MockHost;
+use illustrator_rs::ai_sys::*;
+use illustrator_rs::{AIResult, Art, Artboards, FileFormats, FormatCapabilities, Layer, SafePlugin, SvgFormat};
+
+#[derive(Default)]
+struct SvgPlugin {
+    formats: FileFormats<Self>,
+}
+
+impl SafePlugin for SvgPlugin {
+    fn startup(&mut self) -> AIResult<()> {
+        self.formats
+            .format(c"format:svg", "SVG")
+            .extension("svg")
+            .capabilities(FormatCapabilities::WRITE)
+            .add(SvgFormat)?;
+        Ok(())
+    }
+
+    fn file_formats(&mut self) -> Option<&mut FileFormats<Self>> {
+        Some(&mut self.formats)
+    }
+}
+
+illustrator_rs::define_plugin!(SvgPlugin, "SVG Plugin");
+
+const PATH: i16 = AIArtType_kPathArt as i16;
+const GROUP: i16 = AIArtType_kGroupArt as i16;
+
+fn point(h: f64, v: f64) -> AIRealPoint {
+    AIRealPoint { h, v }
+}
+
+/// 直線で結んだ頂点
+fn polygon(points: &[(f64, f64)]) -> Vec<AIPathSegment> {
+    points
+        .iter()
+        .map(|&(h, v)| AIPathSegment { p: point(h, v), in_: point(h, v), out: point(h, v), corner: 1 })
+        .collect()
+}
+
+fn rect(left: f64, top: f64, right: f64, bottom: f64) -> Vec<AIPathSegment> {
+    polygon(&[(left, top), (right, top), (right, bottom), (left, bottom)])
+}
+
+fn gray(gray: f64) -> AIColor {
+    let mut color: AIColor = unsafe { std::mem::zeroed() };
+    color.kind = AIColorTag_kGrayColor;
+    color.c.g = AIGrayColorStyle { gray };
+    color
+}
+
+fn cmyk(cyan: f64, magenta: f64, yellow: f64, black: f64) -> AIColor {
+    let mut color: AIColor = unsafe { std::mem::zeroed() };
+    color.kind = AIColorTag_kFourColor;
+    color.c.f = AIFourColorStyle { cyan, magenta, yellow, black };
+    color
+}
+
+fn rgb(red: f64, green: f64, blue: f64) -> AIColor {
+    let mut color: AIColor = unsafe { std::mem::zeroed() };
+    color.kind = AIColorTag_kThreeColor;
+    color.c.rgb = AIThreeColorStyle { red, green, blue };
+    color
+}
+
+fn filled(color: AIColor) -> AIPathStyle {
+    let mut style: AIPathStyle = unsafe { std::mem::zeroed() };
+    style.fillPaint = 1;
+    style.fill.color = color;
+    style
+}
+
+fn stroked(color: AIColor, width: f64) -> AIPathStyle {
+    let mut style: AIPathStyle = unsafe { std::mem::zeroed() };
+    style.strokePaint = 1;
+    style.stroke.color = color;
+    style.stroke.width = width;
+    style.stroke.cap = AILineCap_kAIRoundCap;
+    style.stroke.join = AILineJoin_kAIBevelJoin;
+    style.stroke.miterLimit = 4.0;
+    style
+}
+
+/// `parent` の最背面にパスを置く
+fn add_path(host: &mut MockHost, parent: AIArtHandle, segments: &[AIPathSegment], style: AIPathStyle) -> AIArtHandle {
+    let bounds = AIRealRect { left: 0.0, top: 0.0, right: 0.0, bottom: 0.0 };
+    let path = host.add_art(PATH, parent, bounds).unwrap();
+    assert!(host.set_path_segments(path, segments, true));
+    assert!(host.set_path_style(path, style));
+    path
+}
+
+/// ```text
+/// Hidden（非表示のレイヤー）
+/// └ ghost
+/// Front
+/// └ gray
+/// Layer 1
+/// ├ hidden（非表示のアート）
+/// ├ Clipped
+/// │ ├ mask（クリップパス）
+/// │ └ stroke
+/// └ Background
+/// ```
+///
+/// アクティブなアートボードは (100, 300) から (300, 100) の `Crop`。
+fn document_host() -> MockHost {
+    let mut host = MockHost::new(PluginMain);
+    assert_eq!(host.startup(), kNoErr);
+
+    let back = host.layer_art(0).unwrap();
+    let hidden = add_path(&mut host, back, &rect(100.0, 300.0, 300.0, 100.0), filled(rgb(0.0, 0.0, 1.0)));
+    assert!(host.set_art_user_attr(hidden, AIArtUserAttr_kArtHidden as i32));
+
+    let bounds = AIRealRect { left: 0.0, top: 0.0, right: 0.0, bottom: 0.0 };
+    let clipped = host.add_art(GROUP, back, bounds).unwrap();
+    let mask = add_path(&mut host, clipped, &rect(150.0, 250.0, 250.0, 150.0), filled(gray(0.0)));
+    assert!(host.set_art_user_attr(mask, AIArtUserAttr_kArtIsClipMask as i32));
+    let triangle = polygon(&[(120.0, 280.0), (280.0, 280.0), (200.0, 120.0)]);
+    add_path(&mut host, clipped, &triangle, stroked(cmyk(0.0, 1.0, 1.0, 0.5), 2.5));
+
+    let background = add_path(&mut host, back, &rect(100.0, 300.0, 300.0, 100.0), filled(rgb(1.0, 0.0, 0.0)));
+
+    let front = host.add_layer("Front");
+    let front = host.layer_art(front).unwrap();
+    add_path(&mut host, front, &rect(110.5, 290.25, 130.0, 270.0), filled(gray(0.25)));
+
+    let hidden_layer = host.add_layer("Hidden");
+    let ghost = host.layer_art(hidden_layer).unwrap();
+    add_path(&mut host, ghost, &rect(0.0, 792.0, 612.0, 0.0), filled(gray(1.0)));
+
+    host.add_artboard("Crop", AIRealRect { left: 100.0, top: 300.0, right: 300.0, bottom: 100.0 })
+        .unwrap();
+    let hidden_layer = host.layer_handle(hidden_layer);
+    let result = host.run_in_message(|_: &mut SvgPlugin| {
+        unsafe { Art::from_raw(clipped) }.unwrap().set_name("Clipped")?;
+        unsafe { Art::from_raw(background) }.unwrap().set_name("Background")?;
+        unsafe { Layer::from_raw(hidden_layer) }.unwrap().set_visible(false)?;
+        Artboards::current()?.set_active(1)
+    });
+    assert_eq!(result, Ok(()));
+    host
+}
+
+#[test]
+fn document_is_written_as_svg() {
+    let mut host = document_host();
+    let format = host.file_format("format:svg").expect("format was added");
+    let path = std::env::temp_dir().join(format!("illustrator-mock-document-{}.svg", std::process::id()));
+    let file = path.to_str().unwrap();
+    let parameters = host.new_action_parameters();
+
+    let write = AIFileFormatOptions_kFileFormatWrite as i32;
+    assert_eq!(host.go_file_format(format, write, file, parameters), kNoErr);
+    let written = std::fs::read_to_string(&path).unwrap();
+
+    host.release_action_parameters(parameters);
+    let _ = std::fs::remove_file(path);
+    assert_eq!(written, include_str!("golden/document.svg"));
+}
@@ -0,0 +1,175 @@
+//! `UndoTransaction` が設定する取り消し単位と、確定しなかったときの巻き戻し
+
+use illustrator_mock::{HostEvent, MockHost};
+use illustrator_rs::ai_sys::*;
+use illustrator_rs::{AIError, AIResult, Art, ArtType, Menus, PaintOrder, SafePlugin, UndoKind, UndoTransaction};
+
+#[derive(Default)]
+struct UndoPlugin {
+    menus: Menus<Self>,
+    /// 二つ目のトランザクションを始めたときの結果
+    second: Option<AIResult<()>>,
+}
+
+fn add_path() -> AIResult<Art> {
+    Art::new(ArtType::Path, PaintOrder::AboveAll, None)
+}
+
+impl SafePlugin for UndoPlugin {
+    fn startup(&mut self) -> AIResult<()> {
+        self.menus
+            .item(c"undo:commit", c"Undo Group", "Commit")
+            .on_select(|_: &mut Self| {
+                let transaction = UndoTransaction::begin("Undo Add Path", "Redo Add Path")?;
+                add_path()?;
+                transaction.commit();
+                Ok(())
+            })
+            .add()?;
+        self.menus
+            .item(c"undo:fail", c"Undo Group", "Fail")
+            .on_select(|_: &mut Self| {
+                let result = UndoTransaction::run("Undo Fail", "Redo Fail", || -> AIResult<()> {
+                    add_path()?;
+                    Err(AIError::Canceled)
+                });
+                assert_eq!(result, Err(AIError::Canceled));
+                Ok(())
+            })
+            .add()?;
+        self.menus
+            .item(c"undo:twice", c"Undo Group", "Twice")
+            .on_select(|plugin: &mut Self| {
+                let first = UndoTransaction::begin("Undo First", "Redo First")?;
+                add_path()?;
+                first.commit();
+
+                plugin.second = Some(UndoTransaction::begin("Undo Second", "Redo Second").map(drop));
+                Ok(())
+            })
+            .add()?;
+        self.menus
+            .item(c"undo:silent", c"Undo Group", "Silent")
+            .on_select(|_: &mut Self| {
+                let transaction = UndoTransaction::silent()?;
+                add_path()?;
+                drop(transaction);
+                Ok(())
+            })
+            .add()?;
+        self.menus
+            .item(c"undo:append", c"Undo Group", "Append")
+            .on_select(|_: &mut Self| {
+                let transaction = UndoTransaction::with_kind(UndoKind::Append)?;
+                add_path()?;
+                transaction.revert()
+            })
+            .add()?;
+        Ok(())
+    }
+
+    fn menus(&mut self) -> Option<&mut Menus<Self>> {
+        Some(&mut self.menus)
+    }
+}
+
+illustrator_rs::define_plugin!(UndoPlugin, "Undo Plugin");
+
+
+/// 最初のレイヤーに置かれたアートの数
+fn art_count(host: &MockHost) -> usize {
+    host.art_children(host.layer_art(0).unwrap()).len()
+}
+
+/// `key` のメニュー項目を選び、その間に記録されたイベントを返す
+fn select(host: &mut MockHost, key: &str) -> Vec<HostEvent> {
+    let item = host.menu_item(key).expect("menu item was added");
+    host.take_events();
+    assert_eq!(host.go_menu_item(item), kNoErr);
+    host.take_events()
+}
+
+/// 取り消し単位に関するイベントだけを残す
+fn undo_events(events: Vec<HostEvent>) -> Vec<HostEvent> {
+    events
+        .into_iter()
+        .filter(|event| {
+            matches!(
+                event,
+                HostEvent::SetUndoSilent(_) | HostEvent::SetUndoKind(_) | HostEvent::UndoChanges
+            )
+        })
+        .collect()
+}
+
+fn started_host() -> MockHost {
+    let mut host = MockHost::new(PluginMain);
+    assert_eq!(host.startup(), kNoErr);
+    host
+}
+
+#[test]
+fn committed_transaction_sets_undo_text() {
+    let mut host = started_host();
+
+    let events = select(&mut host, "undo:commit");
+    assert!(events.contains(&HostEvent::SetUndoText {
+        undo: "Undo Add Path".into(),
+        redo: "Redo Add Path".into(),
+    }));
+    assert!(!events.contains(&HostEvent::UndoChanges));
+    assert_eq!(art_count(&host), 1);
+
+    let undo = host.undo_info();
+    assert_eq!(undo.undo_text.as_deref(), Some("Undo Add Path"));
+    assert_eq!(undo.reverts, 0);
+}
+
+#[test]
+fn failed_transaction_reverts_the_message() {
+    let mut host = started_host();
+
+    let events = select(&mut host, "undo:fail");
+    assert!(events.contains(&HostEvent::UndoChanges));
+    assert_eq!(host.undo_info().reverts, 1);
+    assert_eq!(art_count(&host), 0);
+}
+
+#[test]
+fn second_transaction_in_a_message_is_refused() {
+    let mut host = started_host();
+
+    let events = select(&mut host, "undo:twice");
+    let second = host.with_plugin(|plugin: &mut UndoPlugin| plugin.second.take());
+    assert_eq!(second, Some(Err(AIError::BadParameter)));
+    // 確定した一つ目の変更は戻らない
+    assert!(!events.contains(&HostEvent::UndoChanges));
+    assert_eq!(art_count(&host), 1);
+
+    // 次のメッセージでは再び始められる
+    select(&mut host, "undo:commit");
+    assert_eq!(art_count(&host), 2);
+}
+
+#[test]
+fn revert_restores_the_undo_kind() {
+    let mut host = started_host();
+
+    let events = select(&mut host, "undo:silent");
+    assert_eq!(
+        undo_events(events),
+        [HostEvent::SetUndoSilent(true), HostEvent::UndoChanges, HostEvent::SetUndoSilent(false)]
+    );
+    assert!(!host.undo_info().silent);
+    assert_eq!(art_count(&host), 0);
+
+    let standard = AIUndoContextKind_kAIStandardUndoContext as i32;
+    let append = AIUndoContextKind_kAIAppendUndoContext as i32;
+    let events = select(&mut host, "undo:append");
+    assert_eq!(
+        undo_events(events),
+        [HostEvent::SetUndoKind(append), HostEvent::UndoChanges, HostEvent::SetUndoKind(standard)]
+    );
+    assert_eq!(host.undo_info().kind, standard);
+    assert_eq!(art_count(&host), 0);
+}
@@ -0,0 +1,192 @@
+//! バインディングの定数から `AIError`・`NotifierType` のバリアント一覧を生成する
+//!
+//! `illustrator-sys` が `cargo:bindings` で渡すバインディングのパスを `DEP_ILLUSTRATOR_BINDINGS` から受け取る。
+
+use std::collections::HashSet;
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+
+/// 名前と説明を固定するエラー（定数, バリアント, 説明）
+///
+/// バインディングに無い定数もここに挙げたものは常に生成される。
+const KNOWN_ERRORS: &[(&str, &str, &str)] = &[
+    ("kUnhandledMsgErr", "Unhandled", "ハンドラがメッセージを処理しなかった"),
+    ("kBadParameterErr", "BadParameter", "不正な引数"),
+    ("kCanceledErr", "Canceled", "ユーザーによるキャンセル"),
+    ("kOutOfMemoryErr", "OutOfMemory", "メモリ不足"),
+    ("kNotImplementedErr", "NotImplemented", "未実装の機能"),
+    ("kCantHappenErr", "CantHappen", "起こり得ない状態"),
+    ("kNoDocumentErr", "NoDocument", "開いているドキュメントがない"),
+    ("kSelectorClashErr", "SelectorClash", "セレクタの衝突"),
+    ("kNameNotFoundErr", "NameNotFound", "名前が見つからない"),
+    ("kNameInUseErr", "NameInUse", "名前が既に使われている"),
+    ("kInvalidNameErr", "InvalidName", "不正な名前"),
+    ("kNameTooLongErr", "NameTooLong", "名前が長すぎる"),
+    ("kUnknownFormatErr", "UnknownFormat", "未知のファイルフォーマット"),
+    ("kToolCantTrackCursorErr", "ToolCantTrackCursor", "ツールがカーソルを追跡できない"),
+    ("kNoSuchKey", "NoSuchKey", "辞書にキーが存在しない"),
+    ("kUnknownArtTypeErr", "UnknownArtType", "未知のアート種別"),
+    ("kUnknownPaintOrderTypeErr", "UnknownPaintOrderType", "未知の重ね順"),
+    ("kUntouchableArtObjectErr", "UntouchableArtObject", "編集できないアート"),
+    ("kTooDeepNestingErr", "TooDeepNesting", "グループの入れ子が深すぎる"),
+    ("kUntouchableLayerErr", "UntouchableLayer", "編集できないレイヤー"),
+    ("kInvalidArtTypeForDestErr", "InvalidArtTypeForDest", "移動先に置けないアート種別"),
+    // Sweet Pea
+    ("kSPUnimplementedError", "SPUnimplemented", "未実装のスイート関数"),
+    ("kSPUserCanceledError", "SPUserCanceled", "ユーザーによるキャンセル"),
+    ("kSPOutOfMemoryError", "SPOutOfMemory", "メモリ不足"),
+    ("kSPBadParameterError", "SPBadParameter", "不正な引数"),
+    ("kSPSuiteNotFoundError", "SPSuiteNotFound", "スイートが見つからない"),
+    ("kSPSuiteAlreadyExistsError", "SPSuiteAlreadyExists", "スイートが既に登録されている"),
+    ("kSPSuiteAlreadyReleasedError", "SPSuiteAlreadyReleased", "スイートが既に解放されている"),
+    ("kSPCantAcquirePluginError", "SPCantAcquirePlugin", "プラグインを取得できない"),
+    ("kSPCantReleasePluginError", "SPCantReleasePlugin", "プラグインを解放できない"),
+    ("kSPPluginNotFound", "SPPluginNotFound", "プラグインが見つからない"),
+    ("kSPCorruptPiPLError", "SPCorruptPiPL", "PiPL が壊れている"),
+    // illustrator-rs
+    ("kRustPanicErr", "Panic", "ハンドラ内でパニックが発生した"),
+];
+
+/// クレート内で使っている通知（バインディングが読めないときの一覧）
+const KNOWN_NOTIFIERS: &[&str] = &[
+    "kAIApplicationStartedNotifier",
+    "kAIApplicationShutdownNotifier",
+    "kAIArtSelectionChangedNotifier",
+    "kAIArtPropertiesChangedNotifier",
+    "kAIDocumentChangedNotifier",
+    "kAIDocumentOpenedNotifier",
+    "kAIDocumentClosedNotifier",
+    "kAIDocumentNewNotifier",
+    "kAIDocumentSavedNotifier",
+    "kAIPreferenceChangedNotifier",
+];
+
+/// エラーではない `k*Err` 定数
+const NOT_ERRORS: &[&str] = &["kNoErr", "kSPNoError"];
+
+/// `AIError` に手書きされているバリアント
+const RESERVED_VARIANTS: &[&str] = &["Io", "Other"];
+
+/// エラーコードとして扱う定数の型
+const ERROR_TYPES: &[&str] = &["i32", "u32", "ASErr", "AIErr", "SPErr", "ai_int32", "AIErrorCode"];
+
+/// `AIErrorCode` 列挙子の接頭辞（`AIErrorCode_kAIFileReadError`）
+const ERROR_CODE_PREFIX: &str = "AIErrorCode_";
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=DEP_ILLUSTRATOR_BINDINGS");
+
+    let bindings = match env::var("DEP_ILLUSTRATOR_BINDINGS") {
+        Ok(path) => {
+            println!("cargo:rerun-if-changed={}", path);
+            fs::read_to_string(&path).unwrap_or_else(|e| panic!("failed to read bindings {}: {}", path, e))
+        }
+        Err(_) => String::new(),
+    };
+    let constants = constants(&bindings);
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    fs::write(out_dir.join("ai_errors.rs"), ai_errors(&constants)).expect("failed to write ai_errors.rs");
+    fs::write(out_dir.join("notifier_types.rs"), notifier_types(&constants)).expect("failed to write notifier_types.rs");
+}
+
+/// バインディングの `pub const 名前: 型 = …;` から（名前, 型）を集める
+fn constants(bindings: &str) -> Vec<(&str, &str)> {
+    bindings
+        .lines()
+        .filter_map(|line| {
+            let rest = line.trim_start().strip_prefix("pub const ")?;
+            let (name, rest) = rest.split_once(':')?;
+            let (ty, _) = rest.split_once('=')?;
+            Some((name.trim(), ty.trim()))
+        })
+        .collect()
+}
+
+/// `ai_errors!` の呼び出しを生成する
+fn ai_errors(constants: &[(&str, &str)]) -> String {
+    let mut variants: HashSet<String> = RESERVED_VARIANTS.iter().map(|v| v.to_string()).collect();
+    let mut seen: HashSet<&str> = NOT_ERRORS.iter().copied().collect();
+    let mut code = String::from("ai_errors! {\n");
+
+    for &(constant, variant, doc) in KNOWN_ERRORS {
+        seen.insert(constant);
+        variants.insert(variant.to_string());
+        writeln!(code, "    #[doc = {:?}]\n    {} = {},", doc, variant, constant).unwrap();
+    }
+
+    let mut generated: Vec<(&str, String)> = constants
+        .iter()
+        .filter(|(_, ty)| ERROR_TYPES.contains(ty))
+        .filter_map(|&(constant, _)| Some((constant, error_variant(constant)?)))
+        .collect();
+    generated.sort();
+
+    for (constant, variant) in generated {
+        if !seen.insert(constant) || !variants.insert(variant.clone()) {
+            continue;
+        }
+        writeln!(code, "    #[doc = \"`{}`\"]\n    {} = {},", constant, variant, constant).unwrap();
+    }
+
+    code.push_str("}\n");
+    code
+}
+
+/// `notifier_types!` の呼び出しを生成する
+///
+/// バインディングに `kAI*Notifier` が無ければ `KNOWN_NOTIFIERS` だけを並べる。
+fn notifier_types(constants: &[(&str, &str)]) -> String {
+    let mut names: Vec<&str> = constants
+        .iter()
+        .filter(|(name, ty)| ty.starts_with("&[u8") && notifier_variant(name).is_some())
+        .map(|&(name, _)| name)
+        .collect();
+    if names.is_empty() {
+        names = KNOWN_NOTIFIERS.to_vec();
+    }
+
+    let mut seen = HashSet::new();
+    let mut code = String::from("notifier_types! {\n");
+    for name in names {
+        let Some(variant) = notifier_variant(name) else { continue };
+        if seen.insert(variant.clone()) {
+            writeln!(code, "    {} => {},", variant, name).unwrap();
+        }
+    }
+    code.push_str("}\n");
+    code
+}
+
+/// `kAIDocumentOpenedNotifier` → `DocumentOpened`
+fn notifier_variant(constant: &str) -> Option<String> {
+    let name = constant.strip_prefix("kAI")?.strip_suffix("Notifier")?;
+    identifier(name)
+}
+
+/// エラー定数ならバリアント名を返す
+///
+/// `k*Err`・`k*Error` と `AIErrorCode` の列挙子が対象で、接頭辞の `k`/`kAI` と接尾辞を取り除く。
+fn error_variant(constant: &str) -> Option<String> {
+    if let Some(code) = constant.strip_prefix(ERROR_CODE_PREFIX) {
+        if code.ends_with("RangeStart") || code.ends_with("RangeEnd") {
+            return None;
+        }
+        let name = code.strip_prefix("kAI")?;
+        let name = name.strip_suffix("Error").or_else(|| name.strip_suffix("Err")).unwrap_or(name);
+        return identifier(name);
+    }
+
+    let name = constant.strip_prefix('k')?;
+    let name = name.strip_suffix("Error").or_else(|| name.strip_suffix("Err"))?;
+    identifier(name)
+}
+
+/// 大文字で始まる英数字の名前だけをバリアントにする
+fn identifier(name: &str) -> Option<String> {
+    let valid = name.starts_with(|c: char| c.is_ascii_uppercase()) && name.chars().all(|c| c.is_ascii_alphanumeric());
+    valid.then(|| name.to_string())
+}
@@ -0,0 +1,529 @@
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+use std::ptr::null_mut;
+use std::rc::Rc;
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::error::{AIError, AIResult, SuiteFn};
+use crate::externs::plugin_ref;
+use crate::unicode::UnicodeString;
+use crate::util::{self, Registry};
+
+pub use illustrator_derive::ActionParameters;
+
+/// 4 文字のパラメータキー（`'crnr'` など）
+pub const fn param_key(key: &[u8; 4]) -> ActionParamKeyID {
+    u32::from_be_bytes(*key)
+}
+
+/// パラメータの種類（`ActionParamTypeID`）
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ActionParamKind {
+    /// `actionTypeInteger`（`'long'`）
+    Integer,
+    /// `actionTypeReal`（`'doub'`）
+    Real,
+    /// `actionTypeBoolean`（`'bool'`）
+    Boolean,
+    /// `actionTypeString`（`'TEXT'`）
+    String,
+}
+
+impl ActionParamKind {
+    pub fn type_id(self) -> ActionParamTypeID {
+        match self {
+            ActionParamKind::Integer => param_key(b"long"),
+            ActionParamKind::Real => param_key(b"doub"),
+            ActionParamKind::Boolean => param_key(b"bool"),
+            ActionParamKind::String => param_key(b"TEXT"),
+        }
+    }
+
+    /// 対応しない種類（単位付きの実数・列挙・生データなど）は `None`
+    pub fn from_type_id(id: ActionParamTypeID) -> Option<Self> {
+        [Self::Integer, Self::Real, Self::Boolean, Self::String]
+            .into_iter()
+            .find(|kind| kind.type_id() == id)
+    }
+}
+
+/// パラメータの値
+#[derive(Clone, Debug, PartialEq)]
+pub enum ActionValue {
+    Integer(i32),
+    Real(f64),
+    Boolean(bool),
+    String(String),
+}
+
+impl ActionValue {
+    pub fn kind(&self) -> ActionParamKind {
+        match self {
+            ActionValue::Integer(_) => ActionParamKind::Integer,
+            ActionValue::Real(_) => ActionParamKind::Real,
+            ActionValue::Boolean(_) => ActionParamKind::Boolean,
+            ActionValue::String(_) => ActionParamKind::String,
+        }
+    }
+}
+
+/// アクションのパラメータとして記録できる型
+pub trait ActionField: Sized {
+    const KIND: ActionParamKind;
+
+    fn to_action(&self) -> ActionValue;
+
+    /// 種類が違う、または範囲に収まらなければ `None`
+    fn from_action(value: ActionValue) -> Option<Self>;
+}
+
+impl ActionField for bool {
+    const KIND: ActionParamKind = ActionParamKind::Boolean;
+
+    fn to_action(&self) -> ActionValue {
+        ActionValue::Boolean(*self)
+    }
+
+    fn from_action(value: ActionValue) -> Option<Self> {
+        match value {
+            ActionValue::Boolean(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// `i32` に収まる整数型
+macro_rules! integer_fields {
+    ($($ty:ty),*) => {
+        $(
+            impl ActionField for $ty {
+                const KIND: ActionParamKind = ActionParamKind::Integer;
+
+                fn to_action(&self) -> ActionValue {
+                    ActionValue::Integer(i32::from(*self))
+                }
+
+                fn from_action(value: ActionValue) -> Option<Self> {
+                    match value {
+                        ActionValue::Integer(value) => <$ty>::try_from(value).ok(),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+integer_fields!(i8, i16, i32, u8, u16);
+
+impl ActionField for f64 {
+    const KIND: ActionParamKind = ActionParamKind::Real;
+
+    fn to_action(&self) -> ActionValue {
+        ActionValue::Real(*self)
+    }
+
+    fn from_action(value: ActionValue) -> Option<Self> {
+        match value {
+            ActionValue::Real(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl ActionField for f32 {
+    const KIND: ActionParamKind = ActionParamKind::Real;
+
+    fn to_action(&self) -> ActionValue {
+        ActionValue::Real(f64::from(*self))
+    }
+
+    fn from_action(value: ActionValue) -> Option<Self> {
+        f64::from_action(value).map(|value| value as f32)
+    }
+}
+
+impl ActionField for String {
+    const KIND: ActionParamKind = ActionParamKind::String;
+
+    fn to_action(&self) -> ActionValue {
+        ActionValue::String(self.clone())
+    }
+
+    fn from_action(value: ActionValue) -> Option<Self> {
+        match value {
+            ActionValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// 型パラメータブロック（TPB）に登録するパラメータ
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ActionParam {
+    pub key: ActionParamKeyID,
+    /// アクションパネルに表示する名前
+    pub name: &'static str,
+    pub kind: ActionParamKind,
+    /// アクションパネルに表示するか
+    pub show: bool,
+}
+
+/// 値パラメータブロック（VPB）
+///
+/// `new` で作ったブロックは破棄時に削除される。`from_raw` で借りたブロックは削除しない。
+pub struct ActionValues {
+    raw: AIActionParamValueRef,
+    owned: bool,
+}
+
+impl ActionValues {
+    pub fn new() -> AIResult<Self> {
+        let suite = SuiteGuard::<AIActionManagerSuite>::acquire()?;
+        let mut raw: AIActionParamValueRef = null_mut();
+
+        unsafe { suite.AINewActionParamValue.call((&mut raw as *mut _,))? };
+        if raw.is_null() {
+            return Err(AIError::CantHappen);
+        }
+        Ok(Self { raw, owned: true })
+    }
+
+    /// # Safety
+    /// `raw` はこの値より長く生存する有効なブロックでなければならない。
+    pub unsafe fn from_raw(raw: AIActionParamValueRef) -> Option<Self> {
+        (!raw.is_null()).then_some(Self { raw, owned: false })
+    }
+
+    pub fn as_raw(&self) -> AIActionParamValueRef {
+        self.raw
+    }
+
+    pub fn contains(&self, key: ActionParamKeyID) -> AIResult<bool> {
+        let suite = SuiteGuard::<AIActionManagerSuite>::acquire()?;
+        let mut has_key: AIBoolean = 0;
+
+        unsafe { suite.AIActionHasValueKey.call((self.raw, key, &mut has_key as *mut _))? };
+        Ok(has_key != 0)
+    }
+
+    /// 格納されたキー（格納順）
+    pub fn keys(&self) -> AIResult<Vec<ActionParamKeyID>> {
+        let suite = SuiteGuard::<AIActionManagerSuite>::acquire()?;
+        let mut count: ai_uint32 = 0;
+
+        unsafe {
+            suite.AIActionGetValueCount.call((self.raw, &mut count as *mut _))?;
+            (0..count)
+                .map(|index| {
+                    let mut key: ActionParamKeyID = 0;
+                    suite.AIActionGetValueKey.call((self.raw, index, &mut key as *mut _))?;
+                    Ok(key)
+                })
+                .collect()
+        }
+    }
+
+    /// 格納された値（なければ、または対応しない種類なら `None`）
+    pub fn value(&self, key: ActionParamKeyID) -> AIResult<Option<ActionValue>> {
+        if !self.contains(key)? {
+            return Ok(None);
+        }
+        let suite = SuiteGuard::<AIActionManagerSuite>::acquire()?;
+        let mut type_id: ActionParamTypeID = 0;
+
+        unsafe {
+            suite.AIActionGetValueType.call((self.raw, key, &mut type_id as *mut _))?;
+            let value = match ActionParamKind::from_type_id(type_id) {
+                Some(ActionParamKind::Integer) => {
+                    let mut value: ai_int32 = 0;
+                    suite.AIActionGetInteger.call((self.raw, key, &mut value as *mut _))?;
+                    ActionValue::Integer(value)
+                }
+                Some(ActionParamKind::Real) => {
+                    let mut value: AIReal = 0.0;
+                    suite.AIActionGetReal.call((self.raw, key, &mut value as *mut _))?;
+                    ActionValue::Real(value)
+                }
+                Some(ActionParamKind::Boolean) => {
+                    let mut value: AIBoolean = 0;
+                    suite.AIActionGetBoolean.call((self.raw, key, &mut value as *mut _))?;
+                    ActionValue::Boolean(value != 0)
+                }
+                Some(ActionParamKind::String) => {
+                    let mut value = UnicodeString::empty()?;
+                    suite.AIActionGetStringUS.call((self.raw, key, value.as_mut_ptr()))?;
+                    ActionValue::String(value.to_string_lossy())
+                }
+                None => return Ok(None),
+            };
+            Ok(Some(value))
+        }
+    }
+
+    pub fn set_value(&mut self, key: ActionParamKeyID, value: &ActionValue) -> AIResult<()> {
+        let suite = SuiteGuard::<AIActionManagerSuite>::acquire()?;
+
+        unsafe {
+            match value {
+                ActionValue::Integer(value) => suite.AIActionSetInteger.call((self.raw, key, *value)),
+                ActionValue::Real(value) => suite.AIActionSetReal.call((self.raw, key, *value)),
+                ActionValue::Boolean(value) => suite.AIActionSetBoolean.call((self.raw, key, *value as ASBoolean)),
+                ActionValue::String(value) => {
+                    let value = UnicodeString::new(value)?;
+                    suite.AIActionSetStringUS.call((self.raw, key, value.as_ptr()))
+                }
+            }
+        }
+    }
+
+    /// 格納された値（なければ、または型に合わなければ `None`）
+    pub fn get<T: ActionField>(&self, key: ActionParamKeyID) -> AIResult<Option<T>> {
+        Ok(self.value(key)?.and_then(T::from_action))
+    }
+
+    pub fn set<T: ActionField>(&mut self, key: ActionParamKeyID, value: &T) -> AIResult<()> {
+        self.set_value(key, &value.to_action())
+    }
+}
+
+impl Drop for ActionValues {
+    fn drop(&mut self) {
+        if !self.owned {
+            return;
+        }
+        if let Ok(suite) = SuiteGuard::<AIActionManagerSuite>::acquire() {
+            let _ = unsafe { suite.AIDeleteActionParamValue.call((self.raw,)) };
+        }
+    }
+}
+
+/// アクションとして記録・再生されるパラメータ（`#[derive(ActionParameters)]` で実装する）
+pub trait ActionParameters: Sized {
+    /// 型パラメータブロックに登録するパラメータ（アクションパネルに表示される順）
+    fn schema() -> Vec<ActionParam>;
+
+    /// 各フィールドを読む（値がなければ既定値）
+    fn read(values: &ActionValues) -> AIResult<Self>;
+
+    fn write(&self, values: &mut ActionValues) -> AIResult<()>;
+}
+
+/// アクションの振る舞い
+///
+/// メニューやツールから実行したときは `ActionEvent::run` を、
+/// アクションパネルから再生したときは `kDoActionSelector` から `play` の流れで呼ばれる。
+#[allow(unused_variables)]
+pub trait Action<P> {
+    type Parameters: ActionParameters + Default;
+
+    /// パラメータを編集するダイアログを持つか
+    ///
+    /// `true` なら記録したイベントにダイアログの切り替えが表示され、オンのときは再生時にも
+    /// `edit_parameters` が呼ばれる。
+    const HAS_DIALOG: bool = false;
+
+    /// 実行前にパラメータを編集する（ダイアログを閉じたら `Err(AIError::Canceled)` を返す）
+    fn edit_parameters(&mut self, plugin: &mut P, parameters: &mut Self::Parameters) -> AIResult<()> {
+        Ok(())
+    }
+
+    fn go(&mut self, plugin: &mut P, parameters: &Self::Parameters) -> AIResult<()>;
+}
+
+/// 記録モードならイベントを記録する（記録したかを返す）
+fn record<T: ActionParameters>(name: &CStr, parameters: &T, dialog: bool) -> AIResult<bool> {
+    let suite = SuiteGuard::<AIActionManagerSuite>::acquire()?;
+    let in_record_mode = suite.InRecordMode.ok_or(AIError::NotImplemented)?;
+    if unsafe { in_record_mode() } == 0 {
+        return Ok(false);
+    }
+
+    let mut values = ActionValues::new()?;
+    parameters.write(&mut values)?;
+    let status = if dialog { _ActionDialogStatus_kDialogOff } else { _ActionDialogStatus_kDialogNone };
+
+    unsafe { suite.RecordActionEvent.call((name.as_ptr(), status, values.as_raw()))? };
+    Ok(true)
+}
+
+/// `Action` と最後に使ったパラメータ
+struct Registered<A, T> {
+    action: A,
+    parameters: T,
+}
+
+/// `Parameters` の型を隠してメッセージを `Action` へ渡す
+pub(crate) trait Handler<P> {
+    fn play(&mut self, plugin: &mut P, message: &mut DoActionMessage) -> AIResult<()>;
+    fn run(&mut self, plugin: &mut P, name: &CStr) -> AIResult<()>;
+}
+
+impl<P, A: Action<P>> Handler<P> for Registered<A, A::Parameters> {
+    fn play(&mut self, plugin: &mut P, message: &mut DoActionMessage) -> AIResult<()> {
+        // パラメータのない再生では前回の値を使う
+        if let Some(values) = unsafe { ActionValues::from_raw(message.param) } {
+            self.parameters = A::Parameters::read(&values)?;
+        }
+        if A::HAS_DIALOG && message.showDialog != 0 {
+            self.action.edit_parameters(plugin, &mut self.parameters)?;
+        }
+        self.action.go(plugin, &self.parameters)
+    }
+
+    fn run(&mut self, plugin: &mut P, name: &CStr) -> AIResult<()> {
+        if A::HAS_DIALOG {
+            self.action.edit_parameters(plugin, &mut self.parameters)?;
+        }
+        self.action.go(plugin, &self.parameters)?;
+        record(name, &self.parameters, A::HAS_DIALOG)?;
+        Ok(())
+    }
+}
+
+type Behavior<P> = Rc<RefCell<dyn Handler<P>>>;
+
+/// 登録したアクションイベント
+///
+/// メニューやツールのハンドラに渡して、アクションを実行・記録するのに使う。
+pub struct ActionEvent<P, T> {
+    name: Rc<CString>,
+    behavior: Behavior<P>,
+    parameters: PhantomData<fn() -> T>,
+}
+
+impl<P, T> Clone for ActionEvent<P, T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            behavior: self.behavior.clone(),
+            parameters: PhantomData,
+        }
+    }
+}
+
+impl<P, T: ActionParameters> ActionEvent<P, T> {
+    /// `RegisterActionEvent` に渡した一意な名前
+    pub fn name(&self) -> &CStr {
+        &self.name
+    }
+
+    /// ユーザーの操作としてアクションを実行し、記録モードならアクションパネルに記録する
+    ///
+    /// ダイアログを持つアクションは、実行前に `edit_parameters` が呼ばれる。
+    pub fn run(&self, plugin: &mut P) -> AIResult<()> {
+        self.behavior.borrow_mut().run(plugin, &self.name)
+    }
+
+    /// ツールのドラッグなど `run` を通さずに行った操作を、`parameters` で記録する
+    ///
+    /// 記録モードでなければ何もしない。記録したかを返す。
+    pub fn record(&self, parameters: &T) -> AIResult<bool> {
+        record(&self.name, parameters, false)
+    }
+}
+
+/// プラグインが登録したアクションイベントと、その振る舞い
+///
+/// 起動時（`SafePlugin::startup`）にイベントを登録し、`SafePlugin::actions` から返すと、
+/// `kDoActionSelector` のメッセージが `Action` へ送られる。
+pub struct Actions<P> {
+    /// 再生時のメッセージの `userData` で引き、項目ごとの設定はイベント名
+    entries: Registry<AIActionUserData, dyn Handler<P>, Rc<CString>>,
+}
+
+impl<P> Default for Actions<P> {
+    fn default() -> Self {
+        Self { entries: Registry::default() }
+    }
+}
+
+impl<P> Actions<P> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// アクションイベントを登録し、`action` と結び付ける
+    ///
+    /// `name` はイベントを識別する一意な名前、`title` はアクションパネルに表示される名前。
+    /// パラメータは `A::Parameters::schema` の順に型パラメータブロックへ登録される。
+    pub fn add<A>(&mut self, name: &CStr, title: &str, action: A) -> AIResult<ActionEvent<P, A::Parameters>>
+    where
+        A: Action<P> + 'static,
+        A::Parameters: 'static,
+    {
+        let suite = SuiteGuard::<AIActionManagerSuite>::acquire()?;
+        let title = UnicodeString::new(title)?;
+        // 再生時のメッセージはイベント名を持たないので、登録順の通し番号で識別する
+        let user_data = (self.entries.len() + 1) as AIActionUserData;
+        let mut param_type: AIActionParamTypeRef = null_mut();
+
+        unsafe {
+            // 型パラメータブロックは登録したイベントが持ち続ける。登録できなければ削除する
+            suite.AINewActionParamType.call((&mut param_type as *mut _,))?;
+            let registered = (|| {
+                for param in A::Parameters::schema() {
+                    let name = UnicodeString::new(param.name)?;
+                    suite.AIActionSetTypeKeyUS.call((
+                        param_type,
+                        param.key,
+                        name.as_ptr(),
+                        param.kind.type_id(),
+                        param.show as AIBoolean,
+                    ))?;
+                }
+
+                suite.RegisterActionEventUS.call((
+                    name.as_ptr(),
+                    title.as_ptr(),
+                    1,
+                    0,
+                    None,
+                    plugin_ref(),
+                    user_data,
+                    param_type,
+                ))
+            })();
+            if registered.is_err() {
+                let _ = suite.AIDeleteActionParamType.call((param_type,));
+            }
+            registered?;
+        }
+
+        let name = Rc::new(name.to_owned());
+        let behavior: Behavior<P> = Rc::new(RefCell::new(Registered {
+            action,
+            parameters: A::Parameters::default(),
+        }));
+        self.entries.push(user_data, behavior.clone(), name.clone());
+
+        Ok(ActionEvent {
+            name,
+            behavior,
+            parameters: PhantomData,
+        })
+    }
+
+    /// 登録したイベントの名前（登録順）
+    pub fn names(&self) -> impl Iterator<Item = &CStr> + '_ {
+        self.entries.extras().map(|name| name.as_c_str())
+    }
+
+    pub fn contains(&self, name: &CStr) -> bool {
+        self.names().any(|registered| registered == name)
+    }
+}
+
+/// `kDoActionSelector` の再生を `Action` へ送る（イベントは `userData` の通し番号で探す）
+pub(crate) fn dispatch<P>(
+    plugin: &mut P,
+    actions: fn(&mut P) -> Option<&mut Actions<P>>,
+    message: *mut DoActionMessage,
+) -> Option<AIResult<()>> {
+    let message = unsafe { message.as_mut() }?;
+    util::dispatch(plugin, |plugin| Some(&actions(plugin)?.entries), message.userData, |behavior, plugin, _| {
+        behavior.play(plugin, message)
+    })
+}
@@ -1,8 +1,11 @@
-use std::ffi::c_void;
-use illustrator_sys::ai_sys::*;
+use std::ffi::{c_char, c_void};
+use crate::ai_sys::*;
+
+use crate::router::{self, MessageRouter, Route};
 
 pub trait AIPlugin {
     // プラグイン基本イベント
+    fn StartupPlugin(&mut self) -> ASErr { kNoErr }
     fn PostStartupPlugin(&mut self) -> ASErr { kNoErr }
     fn PreShutdownPlugin(&mut self) -> ASErr { kNoErr }
 
@@ -71,155 +74,75 @@ pub trait AIPlugin {
     fn WorkspaceRestore(&mut self, _message: *mut AIWorkspaceMessage) -> ASErr { kUnhandledMsgErr }
     fn WorkspaceDefault(&mut self, _message: *mut AIWorkspaceMessage) -> ASErr { kUnhandledMsgErr }
 
+    // ルーター拡張 - 追加の caller/selector を登録する
+    fn RegisterMessageHandlers(&mut self, _router: &mut MessageRouter<Self>) where Self: Sized {}
+
     // メッセージディスパッチのヘルパーメソッド - 各イベントハンドラを呼び出す
     fn dispatch_message(&mut self, caller: *const c_char, selector: *const c_char, message: *mut c_void) -> ASErr {
-        unsafe {
-            if strcmp(caller, kCallerAINotify.as_ptr()) == 0 {
-                if strcmp(selector, kSelectorAINotify.as_ptr()) == 0 {
-                    return self.Notify(message as *mut AINotifierMessage);
-                }
-            }
-            else if strcmp(caller, kActionCaller.as_ptr()) == 0 {
-                if strcmp(selector, kDoActionSelector.as_ptr()) == 0 {
-                    return self.GoAction(message as *mut DoActionMessage);
-                }
-            }
-            else if strcmp(caller, kCallerAIMenu.as_ptr()) == 0 {
-                if strcmp(selector, kSelectorAIGoMenuItem.as_ptr()) == 0 {
-                    return self.GoMenuItem(message as *mut AIMenuMessage);
-                }
-                else if strcmp(selector, kSelectorAIUpdateMenuItem.as_ptr()) == 0 {
-                    return self.UpdateMenuItem(message as *mut AIMenuMessage);
-                }
-            }
-            else if strcmp(caller, kCallerAIFilter.as_ptr()) == 0 {
-                if strcmp(selector, kSelectorAIGetFilterParameters.as_ptr()) == 0 {
-                    return self.GetFilterParameters(message as *mut AIFilterMessage);
-                }
-                else if strcmp(selector, kSelectorAIGoFilter.as_ptr()) == 0 {
-                    return self.GoFilter(message as *mut AIFilterMessage);
-                }
-            }
-            else if strcmp(caller, kCallerAIPluginGroup.as_ptr()) == 0 {
-                if strcmp(selector, kSelectorAINotifyEdits.as_ptr()) == 0 {
-                    return self.PluginGroupNotify(message as *mut AIPluginGroupMessage);
-                }
-                else if strcmp(selector, kSelectorAIUpdateArt.as_ptr()) == 0 {
-                    return self.PluginGroupUpdate(message as *mut AIPluginGroupMessage);
-                }
-            }
-            else if strcmp(caller, kCallerAIFileFormat.as_ptr()) == 0 {
-                if strcmp(selector, kSelectorAIGetFileFormatParameters.as_ptr()) == 0 {
-                    return self.GetFileFormatParameters(message as *mut AIFileFormatMessage);
-                }
-                else if strcmp(selector, kSelectorAIGoFileFormat.as_ptr()) == 0 {
-                    return self.GoFileFormat(message as *mut AIFileFormatMessage);
-                }
-                else if strcmp(selector, kSelectorAICheckFileFormat.as_ptr()) == 0 {
-                    return self.CheckFileFormat(message as *mut AIFileFormatMessage);
-                }
-                else if strcmp(selector, kSelectorAIUpdateFileFormat.as_ptr()) == 0 {
-                    return self.FileFormatUpdate(message as *mut AIUpdateFileFormatMessage);
-                }
-                else if strcmp(selector, kDoActionSelector.as_ptr()) == 0 {
-                    return self.SetFileFormatParameters(message as *mut DoActionMessage);
-                }
-            }
-            else if strcmp(caller, kCallerAITool.as_ptr()) == 0 {
-                if strcmp(selector, kSelectorAIEditToolOptions.as_ptr()) == 0 {
-                    return self.EditTool(message as *mut AIToolMessage);
-                }
-                else if strcmp(selector, kSelectorAITrackToolCursor.as_ptr()) == 0 {
-                    return self.TrackToolCursor(message as *mut AIToolMessage);
-                }
-                else if strcmp(selector, kSelectorAIToolMouseDown.as_ptr()) == 0 {
-                    return self.ToolMouseDown(message as *mut AIToolMessage);
-                }
-                else if strcmp(selector, kSelectorAIToolMouseDrag.as_ptr()) == 0 {
-                    return self.ToolMouseDrag(message as *mut AIToolMessage);
-                }
-                else if strcmp(selector, kSelectorAIToolMouseUp.as_ptr()) == 0 {
-                    return self.ToolMouseUp(message as *mut AIToolMessage);
-                }
-                else if strcmp(selector, kSelectorAISelectTool.as_ptr()) == 0 {
-                    return self.SelectTool(message as *mut AIToolMessage);
-                }
-                else if strcmp(selector, kSelectorAIDeselectTool.as_ptr()) == 0 {
-                    return self.DeselectTool(message as *mut AIToolMessage);
-                }
-                else if strcmp(selector, kSelectorAIReselectTool.as_ptr()) == 0 {
-                    return self.ReselectTool(message as *mut AIToolMessage);
-                }
-                else if strcmp(selector, kSelectorAIToolDecreaseDiameter.as_ptr()) == 0 {
-                    return self.DecreaseDiameter(message as *mut AIToolMessage);
-                }
-                else if strcmp(selector, kSelectorAIToolIncreaseDiameter.as_ptr()) == 0 {
-                    return self.IncreaseDiameter(message as *mut AIToolMessage);
-                }
-            }
-            else if strcmp(caller, kCallerAILiveEffect.as_ptr()) == 0 {
-                if strcmp(selector, kSelectorAIEditLiveEffectParameters.as_ptr()) == 0 {
-                    return self.EditLiveEffectParameters(message as *mut AILiveEffectEditParamMessage);
-                }
-                else if strcmp(selector, kSelectorAIGoLiveEffect.as_ptr()) == 0 {
-                    return self.GoLiveEffect(message as *mut AILiveEffectGoMessage);
-                }
-                else if strcmp(selector, kSelectorAILiveEffectInterpolate.as_ptr()) == 0 {
-                    return self.LiveEffectInterpolate(message as *mut AILiveEffectInterpParamMessage);
-                }
-                else if strcmp(selector, kSelectorAILiveEffectInputType.as_ptr()) == 0 {
-                    return self.LiveEffectGetInputType(message as *mut AILiveEffectInputTypeMessage);
-                }
-                else if strcmp(selector, kSelectorAILiveEffectScaleParameters.as_ptr()) == 0 {
-                    return self.LiveEffectScaleParameters(message as *mut AILiveEffectScaleParamMessage);
-                }
-                else if strcmp(selector, kSelectorAILiveEffectConverColorSpace.as_ptr()) == 0 {
-                    return self.LiveEffectConvertColorSpace(message as *mut AILiveEffectConvertColorMessage);
-                }
-                else if strcmp(selector, kSelectorAILiveEffectAdjustColors.as_ptr()) == 0 {
-                    return self.LiveEffectAdjustColors(message as *mut AILiveEffectAdjustColorsMessage);
-                }
-                else if strcmp(selector, kSelectorAILiveEffectHandleMerge.as_ptr()) == 0 {
-                    return self.LiveEffectHandleMerge(message as *mut AILiveEffectHandleMergeMessage);
-                }
-            }
-            else if strcmp(caller, kCallerAITimer.as_ptr()) == 0 {
-                if strcmp(selector, kSelectorAIGoTimer.as_ptr()) == 0 {
-                    return self.GoTimer(message as *mut AITimerMessage);
-                }
-            }
-            else if strcmp(caller, kCallerAIClipboard.as_ptr()) == 0 {
-                if strcmp(selector, kSelectorAIGoClipboard.as_ptr()) == 0 {
-                    return self.GoClipboard(message as *mut AIClipboardMessage);
-                }
-                else if strcmp(selector, kSelectorAICanCopyClipboard.as_ptr()) == 0 {
-                    return self.CanCopyClipboard(message as *mut AIClipboardMessage);
-                }
-                else if strcmp(selector, kSelectorAICloneClipboard.as_ptr()) == 0 {
-                    return self.CloneClipboard(message as *mut AIClipboardMessage);
-                }
-                else if strcmp(selector, kSelectorAIDisposeClipboard.as_ptr()) == 0 {
-                    return self.DisposeClipboard(message as *mut AIClipboardMessage);
-                }
-            }
-            else if strcmp(caller, kAIWorkspaceCaller.as_ptr()) == 0 {
-                if strcmp(selector, kAIWSWriteSelector.as_ptr()) == 0 {
-                    return self.WorkspaceWrite(message as *mut AIWorkspaceMessage);
-                }
-                else if strcmp(selector, kAIWSRestoreSelector.as_ptr()) == 0 {
-                    return self.WorkspaceRestore(message as *mut AIWorkspaceMessage);
-                }
-                else if strcmp(selector, kAIWSDefaultSelector.as_ptr()) == 0 {
-                    return self.WorkspaceDefault(message as *mut AIWorkspaceMessage);
-                }
-            }
+        match unsafe { router::resolve(caller, selector) } {
+            Some(route) => self.dispatch_route(route, message),
+            None => kUnhandledMsgErr,
         }
-
-        kUnhandledMsgErr
     }
-}
 
-// 標準スイートをインポート
-extern "C" {
-    fn strcmp(s1: *const c_char, s2: *const c_char) -> i32;
+    // 解決済みの Route に対応するイベントハンドラを呼び出す
+    fn dispatch_route(&mut self, route: Route, message: *mut c_void) -> ASErr {
+        match route {
+            Route::AcquireProperty => self.AcquireProperty(message as *mut SPPropertiesMessage),
+            Route::ReleaseProperty => self.ReleaseProperty(message as *mut SPPropertiesMessage),
+
+            Route::Notify => self.Notify(message as *mut AINotifierMessage),
+
+            Route::GoAction => self.GoAction(message as *mut DoActionMessage),
+
+            Route::GoMenuItem => self.GoMenuItem(message as *mut AIMenuMessage),
+            Route::UpdateMenuItem => self.UpdateMenuItem(message as *mut AIMenuMessage),
+
+            Route::GetFilterParameters => self.GetFilterParameters(message as *mut AIFilterMessage),
+            Route::GoFilter => self.GoFilter(message as *mut AIFilterMessage),
+
+            Route::PluginGroupNotify => self.PluginGroupNotify(message as *mut AIPluginGroupMessage),
+            Route::PluginGroupUpdate => self.PluginGroupUpdate(message as *mut AIPluginGroupMessage),
+
+            Route::GetFileFormatParameters => self.GetFileFormatParameters(message as *mut AIFileFormatMessage),
+            Route::GoFileFormat => self.GoFileFormat(message as *mut AIFileFormatMessage),
+            Route::CheckFileFormat => self.CheckFileFormat(message as *mut AIFileFormatMessage),
+            Route::FileFormatUpdate => self.FileFormatUpdate(message as *mut AIUpdateFileFormatMessage),
+            Route::SetFileFormatParameters => self.SetFileFormatParameters(message as *mut DoActionMessage),
+
+            Route::EditTool => self.EditTool(message as *mut AIToolMessage),
+            Route::TrackToolCursor => self.TrackToolCursor(message as *mut AIToolMessage),
+            Route::ToolMouseDown => self.ToolMouseDown(message as *mut AIToolMessage),
+            Route::ToolMouseDrag => self.ToolMouseDrag(message as *mut AIToolMessage),
+            Route::ToolMouseUp => self.ToolMouseUp(message as *mut AIToolMessage),
+            Route::SelectTool => self.SelectTool(message as *mut AIToolMessage),
+            Route::DeselectTool => self.DeselectTool(message as *mut AIToolMessage),
+            Route::ReselectTool => self.ReselectTool(message as *mut AIToolMessage),
+            Route::DecreaseDiameter => self.DecreaseDiameter(message as *mut AIToolMessage),
+            Route::IncreaseDiameter => self.IncreaseDiameter(message as *mut AIToolMessage),
+
+            Route::EditLiveEffectParameters => self.EditLiveEffectParameters(message as *mut AILiveEffectEditParamMessage),
+            Route::GoLiveEffect => self.GoLiveEffect(message as *mut AILiveEffectGoMessage),
+            Route::LiveEffectInterpolate => self.LiveEffectInterpolate(message as *mut AILiveEffectInterpParamMessage),
+            Route::LiveEffectGetInputType => self.LiveEffectGetInputType(message as *mut AILiveEffectInputTypeMessage),
+            Route::LiveEffectScaleParameters => self.LiveEffectScaleParameters(message as *mut AILiveEffectScaleParamMessage),
+            Route::LiveEffectConvertColorSpace => self.LiveEffectConvertColorSpace(message as *mut AILiveEffectConvertColorMessage),
+            Route::LiveEffectAdjustColors => self.LiveEffectAdjustColors(message as *mut AILiveEffectAdjustColorsMessage),
+            Route::LiveEffectHandleMerge => self.LiveEffectHandleMerge(message as *mut AILiveEffectHandleMergeMessage),
+
+            Route::GoTimer => self.GoTimer(message as *mut AITimerMessage),
+
+            Route::GoClipboard => self.GoClipboard(message as *mut AIClipboardMessage),
+            Route::CanCopyClipboard => self.CanCopyClipboard(message as *mut AIClipboardMessage),
+            Route::CloneClipboard => self.CloneClipboard(message as *mut AIClipboardMessage),
+            Route::DisposeClipboard => self.DisposeClipboard(message as *mut AIClipboardMessage),
+
+            Route::WorkspaceWrite => self.WorkspaceWrite(message as *mut AIWorkspaceMessage),
+            Route::WorkspaceRestore => self.WorkspaceRestore(message as *mut AIWorkspaceMessage),
+            Route::WorkspaceDefault => self.WorkspaceDefault(message as *mut AIWorkspaceMessage),
+
+            // Sweet Pea のメッセージと登録ハンドラは Plugin 側で処理する
+            _ => kUnhandledMsgErr,
+        }
+    }
 }
@@ -0,0 +1,336 @@
+use std::ffi::{c_char, c_void, CStr};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::ptr::{null, null_mut};
+
+use crate::ai_sys::*;
+use crate::error::{AIError, AIResult, SuiteFn};
+
+/// `AISuite` のバリアントと SDK のスイート構造体・名前・バージョン定数の対応を定義する
+macro_rules! ai_suites {
+    ($($variant:ident: $suite:ident = ($name:ident, $version:ident),)*) => {
+        /// `define_plugin!` で宣言できるスイート
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum AISuite {
+            $($variant,)*
+        }
+
+        impl AISuite {
+            /// スイート名（`kAIArtSuite` など）
+            pub fn name(self) -> &'static CStr {
+                match self {
+                    $(AISuite::$variant => unsafe { CStr::from_bytes_with_nul_unchecked($name) },)*
+                }
+            }
+
+            /// スイートのバージョン（`kAIArtSuiteVersion` など）
+            pub fn version(self) -> i32 {
+                match self {
+                    $(AISuite::$variant => $version as i32,)*
+                }
+            }
+        }
+
+        $(
+            unsafe impl Suite for $suite {
+                const SUITE: AISuite = AISuite::$variant;
+            }
+        )*
+    };
+}
+
+ai_suites! {
+    // Sweet Pea
+    SPPluginsSuite: SPPluginsSuite = (kSPPluginsSuite, kSPPluginsSuiteVersion),
+    SPAccessSuite: SPAccessSuite = (kSPAccessSuite, kSPAccessSuiteVersion),
+    SPBlocksSuite: SPBlocksSuite = (kSPBlocksSuite, kSPBlocksSuiteVersion),
+    SPPropertiesSuite: SPPropertiesSuite = (kSPPropertiesSuite, kSPPropertiesSuiteVersion),
+    SPFilesSuite: SPFilesSuite = (kSPFilesSuite, kSPFilesSuiteVersion),
+
+    // Illustrator
+    UserSuite: AIUserSuite = (kAIUserSuite, kAIUserSuiteVersion),
+    NotifierSuite: AINotifierSuite = (kAINotifierSuite, kAINotifierSuiteVersion),
+    UnicodeStringSuite: AIUnicodeStringSuite = (kAIUnicodeStringSuite, kAIUnicodeStringSuiteVersion),
+    ArtSuite: AIArtSuite = (kAIArtSuite, kAIArtSuiteVersion),
+    ArtSetSuite: AIArtSetSuite = (kAIArtSetSuite, kAIArtSetSuiteVersion),
+    MatchingArtSuite: AIMatchingArtSuite = (kAIMatchingArtSuite, kAIMatchingArtSuiteVersion),
+    PathSuite: AIPathSuite = (kAIPathSuite, kAIPathSuiteVersion),
+    PathStyleSuite: AIPathStyleSuite = (kAIPathStyleSuite, kAIPathStyleSuiteVersion),
+    RealMathSuite: AIRealMathSuite = (kAIRealMathSuite, kAIRealMathSuiteVersion),
+    RealBezierSuite: AIRealBezierSuite = (kAIRealBezierSuite, kAIRealBezierSuiteVersion),
+    TransformArtSuite: AITransformArtSuite = (kAITransformArtSuite, kAITransformArtSuiteVersion),
+    LayerSuite: AILayerSuite = (kAILayerSuite, kAILayerSuiteVersion),
+    LayerListSuite: AILayerListSuite = (kAILayerListSuite, kAILayerListSuiteVersion),
+    DocumentSuite: AIDocumentSuite = (kAIDocumentSuite, kAIDocumentSuiteVersion),
+    DocumentViewSuite: AIDocumentViewSuite = (kAIDocumentViewSuite, kAIDocumentViewSuiteVersion),
+    ArtboardSuite: AIArtboardSuite = (kAIArtboardSuite, kAIArtboardSuiteVersion),
+    ArtboardRangeSuite: AIArtboardRangeSuite = (kAIArtboardRangeSuite, kAIArtboardRangeSuiteVersion),
+    DictionarySuite: AIDictionarySuite = (kAIDictionarySuite, kAIDictionarySuiteVersion),
+    DictionaryIteratorSuite: AIDictionaryIteratorSuite = (kAIDictionaryIteratorSuite, kAIDictionaryIteratorSuiteVersion),
+    EntrySuite: AIEntrySuite = (kAIEntrySuite, kAIEntrySuiteVersion),
+    ArraySuite: AIArraySuite = (kAIArraySuite, kAIArraySuiteVersion),
+    PreferenceSuite: AIPreferenceSuite = (kAIPreferenceSuite, kAIPreferenceSuiteVersion),
+    MenuSuite: AIMenuSuite = (kAIMenuSuite, kAIMenuSuiteVersion),
+    ToolSuite: AIToolSuite = (kAIToolSuite, kAIToolSuiteVersion),
+    TimerSuite: AITimerSuite = (kAITimerSuite, kAITimerSuiteVersion),
+    LiveEffectSuite: AILiveEffectSuite = (kAILiveEffectSuite, kAILiveEffectSuiteVersion),
+    PluginGroupSuite: AIPluginGroupSuite = (kAIPluginGroupSuite, kAIPluginGroupSuiteVersion),
+    FileFormatSuite: AIFileFormatSuite = (kAIFileFormatSuite, kAIFileFormatSuiteVersion),
+    FilterSuite: AIFilterSuite = (kAIFilterSuite, kAIFilterSuiteVersion),
+    ActionManagerSuite: AIActionManagerSuite = (kAIActionManagerSuite, kAIActionManagerSuiteVersion),
+    UndoSuite: AIUndoSuite = (kAIUndoSuite, kAIUndoSuiteVersion),
+    ClipboardSuite: AIClipboardSuite = (kAIClipboardSuite, kAIClipboardSuiteVersion),
+    RasterSuite: AIRasterSuite = (kAIRasterSuite, kAIRasterSuiteVersion),
+    PlacedSuite: AIPlacedSuite = (kAIPlacedSuite, kAIPlacedSuiteVersion),
+    MaskSuite: AIMaskSuite = (kAIMaskSuite, kAIMaskSuiteVersion),
+    ColorConversionSuite: AIColorConversionSuite = (kAIColorConversionSuite, kAIColorConversionSuiteVersion),
+    PaintStyleSuite: AIPaintStyleSuite = (kAIPaintStyleSuite, kAIPaintStyleSuiteVersion),
+    ArtStyleSuite: AIArtStyleSuite = (kAIArtStyleSuite, kAIArtStyleSuiteVersion),
+    FilePathSuite: AIFilePathSuite = (kAIFilePathSuite, kAIFilePathSuiteVersion),
+    FoldersSuite: AIFoldersSuite = (kAIFoldersSuite, kAIFoldersSuiteVersion),
+    DataFilterSuite: AIDataFilterSuite = (kAIDataFilterSuite, kAIDataFilterSuiteVersion),
+    AppContextSuite: AIAppContextSuite = (kAIAppContextSuite, kAIAppContextSuiteVersion),
+    WorkspaceSuite: AIWorkspaceSuite = (kAIWorkspaceSuite, kAIWorkspaceSuiteVersion),
+}
+
+// プラグイン全体で共有するスイートのポインタ（SDK の C++ 実装と同じシンボル名）
+#[no_mangle]
+pub static mut sSPBasic: *mut SPBasicSuite = null_mut();
+#[no_mangle]
+pub static mut sAIUser: *mut AIUserSuite = null_mut();
+#[no_mangle]
+pub static mut sSPPlugins: *mut SPPluginsSuite = null_mut();
+#[no_mangle]
+pub static mut sAINotifier: *mut AINotifierSuite = null_mut();
+#[no_mangle]
+pub static mut sSPAccess: *mut SPAccessSuite = null_mut();
+
+/// SDK のスイート構造体とスイート名・バージョンの対応
+///
+/// `ai_suites!` で列挙されたスイート構造体にだけ実装されるため、
+/// 登録されていない構造体を `SuiteGuard` で取得しようとするとコンパイルエラーになる。
+///
+/// # Safety
+/// `SUITE` の名前とバージョンで取得した関数テーブルが `Self` のレイアウトでなければならない。
+pub unsafe trait Suite: Sized + 'static {
+    const SUITE: AISuite;
+
+    fn name() -> &'static CStr {
+        Self::SUITE.name()
+    }
+
+    fn version() -> i32 {
+        Self::SUITE.version()
+    }
+}
+
+/// 取得したスイートの関数テーブル
+///
+/// `Deref` でスイート構造体として使え、破棄時にスイートを解放する。
+///
+/// ```ignore
+/// let art = SuiteGuard::<AIArtSuite>::acquire()?;
+/// art.GetArtType.call((handle, &mut art_type))?;
+/// ```
+pub struct SuiteGuard<S: Suite> {
+    table: *const S,
+    basic: *const SPBasicSuite,
+    _marker: PhantomData<S>,
+}
+
+impl<S: Suite> SuiteGuard<S> {
+    /// `sSPBasic` からスイートを取得する
+    pub fn acquire() -> AIResult<Self> {
+        let basic = unsafe { sSPBasic.as_ref() }.ok_or(AIError::SPSuiteNotFound)?;
+        Self::acquire_from(basic)
+    }
+
+    /// 指定した `SPBasicSuite` からスイートを取得する
+    pub fn acquire_from(basic: &SPBasicSuite) -> AIResult<Self> {
+        let mut table: *const c_void = null();
+
+        unsafe {
+            basic
+                .AcquireSuite
+                .call((S::name().as_ptr() as *const c_char, S::version(), &mut table as *mut _))?;
+        }
+
+        if table.is_null() {
+            return Err(AIError::SPSuiteNotFound);
+        }
+
+        Ok(Self {
+            table: table as *const S,
+            basic,
+            _marker: PhantomData,
+        })
+    }
+
+    /// 関数テーブルへの生ポインタ
+    pub fn as_ptr(&self) -> *const S {
+        self.table
+    }
+
+    /// 同じスイートの参照をもう一つ取得する（取得できなければ解放もしない）
+    pub fn try_clone(&self) -> AIResult<Self> {
+        Self::acquire_from(unsafe { &*self.basic })
+    }
+}
+
+impl<S: Suite> Deref for SuiteGuard<S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        unsafe { &*self.table }
+    }
+}
+
+impl<S: Suite> Drop for SuiteGuard<S> {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = (*self.basic).ReleaseSuite.call((S::name().as_ptr() as *const c_char, S::version()));
+        }
+    }
+}
+
+/// `Plugin` 自身が使うため、宣言にかかわらず常に取得するスイート
+const CORE_SUITES: &[AISuite] = &[
+    AISuite::SPPluginsSuite,
+    AISuite::SPAccessSuite,
+    AISuite::NotifierSuite,
+    AISuite::UserSuite,
+];
+
+/// 必須スイートを取得できなかったときのエラー
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SuiteError {
+    pub suite: AISuite,
+    pub error: AIError,
+}
+
+impl fmt::Display for SuiteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "required suite {} (version {}) could not be acquired: {}",
+            self.suite.name().to_string_lossy(),
+            self.suite.version(),
+            self.error
+        )
+    }
+}
+
+impl std::error::Error for SuiteError {}
+
+impl From<SuiteError> for ASErr {
+    fn from(error: SuiteError) -> Self {
+        error.error.code()
+    }
+}
+
+/// プラグインが取得したスイートの一覧
+///
+/// 必須スイートは起動時にすべて取得し、一つでも欠けていれば起動に失敗する。
+/// 任意スイートは取得できなかったものをメッセージごとに再取得する。
+/// 取得したスイートは破棄時に取得と逆の順序で解放される。
+pub struct Suites {
+    acquired: Vec<(AISuite, *const c_void)>,
+    pending: Vec<AISuite>,
+}
+
+impl Suites {
+    pub fn new(required: &[AISuite], optional: &[AISuite]) -> Result<Box<Self>, SuiteError> {
+        // 途中で失敗した場合は Drop で取得済みのスイートが解放される
+        let mut suites = Box::new(Self {
+            acquired: Vec::new(),
+            pending: Vec::new(),
+        });
+
+        for &suite in CORE_SUITES.iter().chain(required) {
+            if suites.is_acquired(suite) {
+                continue;
+            }
+
+            let table = unsafe { acquire(suite) }.map_err(|error| SuiteError { suite, error })?;
+            suites.acquired.push((suite, table));
+            unsafe { set_global(suite, table) };
+        }
+
+        suites.pending = optional
+            .iter()
+            .copied()
+            .filter(|&suite| !suites.is_acquired(suite))
+            .collect();
+        suites.acquire_Optional_Suites();
+
+        Ok(suites)
+    }
+
+    /// 取得済みのスイートの関数テーブル
+    pub fn get(&self, suite: AISuite) -> Option<*const c_void> {
+        self.acquired
+            .iter()
+            .find(|&&(acquired, _)| acquired == suite)
+            .map(|&(_, table)| table)
+    }
+
+    pub fn is_acquired(&self, suite: AISuite) -> bool {
+        self.get(suite).is_some()
+    }
+
+    /// まだ取得できていない任意スイートの取得を再試行する
+    pub fn acquire_Optional_Suites(&mut self) {
+        let mut index = 0;
+
+        while index < self.pending.len() {
+            let suite = self.pending[index];
+
+            match unsafe { acquire(suite) } {
+                Ok(table) => {
+                    self.pending.remove(index);
+                    self.acquired.push((suite, table));
+                }
+                Err(_) => index += 1,
+            }
+        }
+    }
+}
+
+impl Drop for Suites {
+    fn drop(&mut self) {
+        while let Some((suite, _)) = self.acquired.pop() {
+            unsafe {
+                set_global(suite, null());
+
+                if let Some(basic) = sSPBasic.as_ref() {
+                    let _ = basic.ReleaseSuite.call((suite.name().as_ptr(), suite.version()));
+                }
+            }
+        }
+    }
+}
+
+unsafe fn acquire(suite: AISuite) -> Result<*const c_void, AIError> {
+    let basic = sSPBasic.as_ref().ok_or(AIError::SPSuiteNotFound)?;
+    let mut table: *const c_void = null();
+
+    basic
+        .AcquireSuite
+        .call((suite.name().as_ptr() as *const c_char, suite.version(), &mut table as *mut _))?;
+
+    if table.is_null() {
+        return Err(AIError::SPSuiteNotFound);
+    }
+    Ok(table)
+}
+
+unsafe fn set_global(suite: AISuite, table: *const c_void) {
+    match suite {
+        AISuite::SPPluginsSuite => sSPPlugins = table as *mut _,
+        AISuite::SPAccessSuite => sSPAccess = table as *mut _,
+        AISuite::NotifierSuite => sAINotifier = table as *mut _,
+        AISuite::UserSuite => sAIUser = table as *mut _,
+        _ => {}
+    }
+}
@@ -0,0 +1,452 @@
+use std::ffi::c_short;
+use std::ptr::null_mut;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::dictionary::{self, Dictionary};
+use crate::error::{AIError, AIResult, SuiteFn};
+use crate::geometry::Rect;
+use crate::layer::Layer;
+use crate::unicode::UnicodeString;
+use crate::util::flags;
+
+/// アートの種類（`AIArtType`）
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ArtType {
+    Group,
+    Path,
+    CompoundPath,
+    Placed,
+    MysteryPath,
+    Raster,
+    Plugin,
+    Mesh,
+    TextFrame,
+    Symbol,
+    Foreign,
+    LegacyText,
+    Chart,
+    RadialRepeat,
+    GridRepeat,
+    Symmetry,
+    ConcentricRepeat,
+    /// 未知の種類
+    Unknown(i16),
+}
+
+impl ArtType {
+    pub fn from_raw(raw: i16) -> Self {
+        match raw as AIArtType {
+            AIArtType_kGroupArt => ArtType::Group,
+            AIArtType_kPathArt => ArtType::Path,
+            AIArtType_kCompoundPathArt => ArtType::CompoundPath,
+            AIArtType_kPlacedArt => ArtType::Placed,
+            AIArtType_kMysteryPathArt => ArtType::MysteryPath,
+            AIArtType_kRasterArt => ArtType::Raster,
+            AIArtType_kPluginArt => ArtType::Plugin,
+            AIArtType_kMeshArt => ArtType::Mesh,
+            AIArtType_kTextFrameArt => ArtType::TextFrame,
+            AIArtType_kSymbolArt => ArtType::Symbol,
+            AIArtType_kForeignArt => ArtType::Foreign,
+            AIArtType_kLegacyTextArt => ArtType::LegacyText,
+            AIArtType_kChartArt => ArtType::Chart,
+            AIArtType_kRadialRepeatArt => ArtType::RadialRepeat,
+            AIArtType_kGridRepeatArt => ArtType::GridRepeat,
+            AIArtType_kSymmetryArt => ArtType::Symmetry,
+            AIArtType_kConcentricRepeatArt => ArtType::ConcentricRepeat,
+            _ => ArtType::Unknown(raw),
+        }
+    }
+
+    pub fn to_raw(self) -> i16 {
+        let raw = match self {
+            ArtType::Group => AIArtType_kGroupArt,
+            ArtType::Path => AIArtType_kPathArt,
+            ArtType::CompoundPath => AIArtType_kCompoundPathArt,
+            ArtType::Placed => AIArtType_kPlacedArt,
+            ArtType::MysteryPath => AIArtType_kMysteryPathArt,
+            ArtType::Raster => AIArtType_kRasterArt,
+            ArtType::Plugin => AIArtType_kPluginArt,
+            ArtType::Mesh => AIArtType_kMeshArt,
+            ArtType::TextFrame => AIArtType_kTextFrameArt,
+            ArtType::Symbol => AIArtType_kSymbolArt,
+            ArtType::Foreign => AIArtType_kForeignArt,
+            ArtType::LegacyText => AIArtType_kLegacyTextArt,
+            ArtType::Chart => AIArtType_kChartArt,
+            ArtType::RadialRepeat => AIArtType_kRadialRepeatArt,
+            ArtType::GridRepeat => AIArtType_kGridRepeatArt,
+            ArtType::Symmetry => AIArtType_kSymmetryArt,
+            ArtType::ConcentricRepeat => AIArtType_kConcentricRepeatArt,
+            ArtType::Unknown(raw) => return raw,
+        };
+        raw as i16
+    }
+}
+
+/// 作成・移動先の指定（`AIPaintOrder`）
+///
+/// `Above`/`Below`/`Inside*` は基準となるアートが必要。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PaintOrder {
+    /// 基準アートの直前、基準が無ければ現在のレイヤーの最前面
+    Default,
+    Above,
+    Below,
+    InsideOnTop,
+    InsideOnBottom,
+    /// 現在のレイヤーの最前面
+    AboveAll,
+    /// 現在のレイヤーの最背面
+    BelowAll,
+}
+
+impl PaintOrder {
+    pub fn to_raw(self) -> i16 {
+        let raw = match self {
+            PaintOrder::Default => AIPaintOrder_kPlaceDefault,
+            PaintOrder::Above => AIPaintOrder_kPlaceAbove,
+            PaintOrder::Below => AIPaintOrder_kPlaceBelow,
+            PaintOrder::InsideOnTop => AIPaintOrder_kPlaceInsideOnTop,
+            PaintOrder::InsideOnBottom => AIPaintOrder_kPlaceInsideOnBottom,
+            PaintOrder::AboveAll => AIPaintOrder_kPlaceAboveAll,
+            PaintOrder::BelowAll => AIPaintOrder_kPlaceBelowAll,
+        };
+        raw as i16
+    }
+}
+
+flags! {
+    /// アートのユーザー属性（`AIArtUserAttr` の組み合わせ）
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct ArtAttributes(pub u32) {
+        const SELECTED = AIArtUserAttr_kArtSelected;
+        const LOCKED = AIArtUserAttr_kArtLocked;
+        const HIDDEN = AIArtUserAttr_kArtHidden;
+        const FULLY_SELECTED = AIArtUserAttr_kArtFullySelected;
+        const EXPANDED = AIArtUserAttr_kArtExpanded;
+        const TARGETED = AIArtUserAttr_kArtTargeted;
+        const IS_CLIP_MASK = AIArtUserAttr_kArtIsClipMask;
+        const IS_TEXT_WRAP = AIArtUserAttr_kArtIsTextWrap;
+    }
+}
+
+/// アートオブジェクトへの参照（`AIArtHandle`）
+///
+/// ハンドルはドキュメントが所有しており、`Art` を破棄してもアートは削除されない。
+/// 削除には `dispose` を使う。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Art(AIArtHandle);
+
+impl Art {
+    /// # Safety
+    /// `handle` は現在のドキュメントの有効なアートでなければならない。
+    pub unsafe fn from_raw(handle: AIArtHandle) -> Option<Self> {
+        (!handle.is_null()).then_some(Self(handle))
+    }
+
+    pub fn as_raw(self) -> AIArtHandle {
+        self.0
+    }
+
+    /// 新しいアートを作る（`prep` は `paint_order` の基準）
+    pub fn new(art_type: ArtType, paint_order: PaintOrder, prep: Option<Art>) -> AIResult<Art> {
+        let suite = SuiteGuard::<AIArtSuite>::acquire()?;
+        let mut art: AIArtHandle = null_mut();
+
+        unsafe {
+            suite
+                .NewArt
+                .call((art_type.to_raw(), paint_order.to_raw(), raw(prep), &mut art as *mut _))?;
+            Art::from_raw(art).ok_or(AIError::CantHappen)
+        }
+    }
+
+    /// レイヤーの最上位グループ（`layer` が `null` なら現在のレイヤー）
+    ///
+    /// # Safety
+    /// `layer` は `null` か、現在のドキュメントの有効なレイヤーでなければならない。
+    pub unsafe fn first_of_layer(layer: AILayerHandle) -> AIResult<Art> {
+        let suite = SuiteGuard::<AIArtSuite>::acquire()?;
+        let mut art: AIArtHandle = null_mut();
+
+        suite.GetFirstArtOfLayer.call((layer, &mut art as *mut _))?;
+        Art::from_raw(art).ok_or(AIError::CantHappen)
+    }
+
+    /// アートと子孫を削除する
+    pub fn dispose(self) -> AIResult<()> {
+        let suite = SuiteGuard::<AIArtSuite>::acquire()?;
+        unsafe { suite.DisposeArt.call((self.0,)) }
+    }
+
+    /// 重ね順を変更する
+    pub fn reorder(self, paint_order: PaintOrder, prep: Option<Art>) -> AIResult<()> {
+        let suite = SuiteGuard::<AIArtSuite>::acquire()?;
+        unsafe { suite.ReorderArt.call((self.0, paint_order.to_raw(), raw(prep))) }
+    }
+
+    /// 子孫も含めて複製する
+    pub fn duplicate(self, paint_order: PaintOrder, prep: Option<Art>) -> AIResult<Art> {
+        let suite = SuiteGuard::<AIArtSuite>::acquire()?;
+        let mut art: AIArtHandle = null_mut();
+
+        unsafe {
+            suite
+                .DuplicateArt
+                .call((self.0, paint_order.to_raw(), raw(prep), &mut art as *mut _))?;
+            Art::from_raw(art).ok_or(AIError::CantHappen)
+        }
+    }
+
+    pub fn art_type(self) -> AIResult<ArtType> {
+        let suite = SuiteGuard::<AIArtSuite>::acquire()?;
+        let mut art_type: c_short = 0;
+
+        unsafe { suite.GetArtType.call((self.0, &mut art_type as *mut _))? };
+        Ok(ArtType::from_raw(art_type))
+    }
+
+    /// アートが属するレイヤー
+    pub fn layer(self) -> AIResult<Layer> {
+        let suite = SuiteGuard::<AIArtSuite>::acquire()?;
+        let mut layer: AILayerHandle = null_mut();
+
+        unsafe {
+            suite.GetLayerOfArt.call((self.0, &mut layer as *mut _))?;
+            Layer::from_raw(layer).ok_or(AIError::CantHappen)
+        }
+    }
+
+    /// ドキュメントにまだ存在するか
+    pub fn is_valid(self) -> bool {
+        let Ok(suite) = SuiteGuard::<AIArtSuite>::acquire() else {
+            return false;
+        };
+        match suite.ValidArt {
+            Some(valid_art) => unsafe { valid_art(self.0, true as AIBoolean) != 0 },
+            None => false,
+        }
+    }
+
+    /// レイヤーの最上位グループか
+    pub fn is_layer_group(self) -> AIResult<bool> {
+        let suite = SuiteGuard::<AIArtSuite>::acquire()?;
+        let mut is_layer_group: ASBoolean = 0;
+
+        unsafe { suite.IsArtLayerGroup.call((self.0, &mut is_layer_group as *mut _))? };
+        Ok(is_layer_group != 0)
+    }
+
+    pub fn parent(self) -> AIResult<Option<Art>> {
+        self.relative(|suite| suite.GetArtParent)
+    }
+
+    pub fn first_child(self) -> AIResult<Option<Art>> {
+        self.relative(|suite| suite.GetArtFirstChild)
+    }
+
+    pub fn last_child(self) -> AIResult<Option<Art>> {
+        self.relative(|suite| suite.GetArtLastChild)
+    }
+
+    /// 一つ下のアート
+    pub fn next_sibling(self) -> AIResult<Option<Art>> {
+        self.relative(|suite| suite.GetArtSibling)
+    }
+
+    /// 一つ上のアート
+    pub fn prior_sibling(self) -> AIResult<Option<Art>> {
+        self.relative(|suite| suite.GetArtPriorSibling)
+    }
+
+    fn relative(
+        self,
+        function: impl FnOnce(&AIArtSuite) -> Option<unsafe extern "C" fn(AIArtHandle, *mut AIArtHandle) -> AIErr>,
+    ) -> AIResult<Option<Art>> {
+        let suite = SuiteGuard::<AIArtSuite>::acquire()?;
+        let mut art: AIArtHandle = null_mut();
+
+        unsafe {
+            function(&suite).call((self.0, &mut art as *mut _))?;
+            Ok(Art::from_raw(art))
+        }
+    }
+
+    /// 子を重ね順の上から列挙する
+    pub fn children(self) -> Siblings {
+        Siblings { next: self.first_child().transpose() }
+    }
+
+    /// 自分より下にある兄弟を列挙する（自分は含まない）
+    pub fn siblings(self) -> Siblings {
+        Siblings { next: self.next_sibling().transpose() }
+    }
+
+    /// 子孫を深さ優先（親が先）で列挙する（自分は含まない）
+    pub fn descendants(self) -> Descendants {
+        Descendants { stack: vec![self.children()] }
+    }
+
+    /// 外接矩形
+    pub fn bounds(self) -> AIResult<Rect> {
+        let suite = SuiteGuard::<AIArtSuite>::acquire()?;
+        let mut bounds = AIRealRect { left: 0.0, top: 0.0, right: 0.0, bottom: 0.0 };
+
+        unsafe { suite.GetArtBounds.call((self.0, &mut bounds as *mut _))? };
+        Ok(bounds.into())
+    }
+
+    /// `mask` で指定した属性のうち、設定されているもの
+    pub fn attributes(self, mask: ArtAttributes) -> AIResult<ArtAttributes> {
+        let suite = SuiteGuard::<AIArtSuite>::acquire()?;
+        let mut attr: ai_int32 = 0;
+
+        unsafe { suite.GetArtUserAttr.call((self.0, mask.bits() as ai_int32, &mut attr as *mut _))? };
+        Ok(ArtAttributes(attr as u32))
+    }
+
+    /// `mask` で指定した属性を `value` の値にする
+    pub fn set_attributes(self, mask: ArtAttributes, value: ArtAttributes) -> AIResult<()> {
+        let suite = SuiteGuard::<AIArtSuite>::acquire()?;
+        unsafe { suite.SetArtUserAttr.call((self.0, mask.bits() as ai_int32, value.bits() as ai_int32)) }
+    }
+
+    pub fn has_attribute(self, attribute: ArtAttributes) -> AIResult<bool> {
+        Ok(self.attributes(attribute)?.contains(attribute))
+    }
+
+    pub fn set_attribute(self, attribute: ArtAttributes, on: bool) -> AIResult<()> {
+        let value = if on { attribute } else { ArtAttributes::empty() };
+        self.set_attributes(attribute, value)
+    }
+
+    pub fn is_selected(self) -> AIResult<bool> {
+        self.has_attribute(ArtAttributes::SELECTED)
+    }
+
+    pub fn set_selected(self, selected: bool) -> AIResult<()> {
+        self.set_attribute(ArtAttributes::SELECTED, selected)
+    }
+
+    pub fn is_locked(self) -> AIResult<bool> {
+        self.has_attribute(ArtAttributes::LOCKED)
+    }
+
+    pub fn set_locked(self, locked: bool) -> AIResult<()> {
+        self.set_attribute(ArtAttributes::LOCKED, locked)
+    }
+
+    pub fn is_hidden(self) -> AIResult<bool> {
+        self.has_attribute(ArtAttributes::HIDDEN)
+    }
+
+    pub fn set_hidden(self, hidden: bool) -> AIResult<()> {
+        self.set_attribute(ArtAttributes::HIDDEN, hidden)
+    }
+
+    /// レイヤーパネルに表示される名前（未設定なら空文字列）
+    pub fn name(self) -> AIResult<String> {
+        let suite = SuiteGuard::<AIArtSuite>::acquire()?;
+        let mut name = UnicodeString::empty()?;
+        let mut is_default: ASBoolean = 0;
+
+        unsafe { suite.GetArtName.call((self.0, name.as_mut_ptr(), &mut is_default as *mut _))? };
+        if is_default != 0 {
+            return Ok(String::new());
+        }
+        Ok(name.to_string_lossy())
+    }
+
+    pub fn set_name(self, name: &str) -> AIResult<()> {
+        let suite = SuiteGuard::<AIArtSuite>::acquire()?;
+        let name = UnicodeString::new(name)?;
+        unsafe { suite.SetArtName.call((self.0, name.as_ptr())) }
+    }
+
+    /// アートの辞書（なければ作られる）
+    pub fn dictionary(self) -> AIResult<Dictionary> {
+        let suite = SuiteGuard::<AIArtSuite>::acquire()?;
+        let mut dictionary: AIDictionaryRef = null_mut();
+
+        unsafe {
+            suite.GetDictionary.call((self.0, &mut dictionary as *mut _))?;
+            Dictionary::from_raw(dictionary)?.ok_or(AIError::CantHappen)
+        }
+    }
+
+    /// 辞書を持っているか（`dictionary` と違い辞書を作らない）
+    pub fn has_dictionary(self) -> AIResult<bool> {
+        let suite = SuiteGuard::<AIArtSuite>::acquire()?;
+        let has_dictionary = suite.HasDictionary.ok_or(AIError::NotImplemented)?;
+        Ok(unsafe { has_dictionary(self.0) } != 0)
+    }
+
+    /// 値をシリアライズしてアートの辞書に保存する
+    pub fn store<T: Serialize + ?Sized>(self, key: &str, value: &T) -> Result<(), dictionary::Error> {
+        self.dictionary()?.store(key, value)
+    }
+
+    /// アートの辞書から値を読み出す（辞書やキーがなければ `None`）
+    pub fn load<T: DeserializeOwned>(self, key: &str) -> Result<Option<T>, dictionary::Error> {
+        if !self.has_dictionary()? {
+            return Ok(None);
+        }
+        self.dictionary()?.load(key)
+    }
+}
+
+fn raw(art: Option<Art>) -> AIArtHandle {
+    art.map_or(null_mut(), Art::as_raw)
+}
+
+/// `GetArtSibling` をたどるイテレータ
+///
+/// エラーが起きた場合はそれを一度返して終了する。
+pub struct Siblings {
+    next: Option<AIResult<Art>>,
+}
+
+impl Iterator for Siblings {
+    type Item = AIResult<Art>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+
+        if let Ok(art) = current {
+            self.next = art.next_sibling().transpose();
+        }
+        Some(current)
+    }
+}
+
+/// 子孫を深さ優先でたどるイテレータ
+pub struct Descendants {
+    stack: Vec<Siblings>,
+}
+
+impl Iterator for Descendants {
+    type Item = AIResult<Art>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let siblings = self.stack.last_mut()?;
+
+            match siblings.next() {
+                Some(Ok(art)) => {
+                    self.stack.push(art.children());
+                    return Some(Ok(art));
+                }
+                Some(Err(error)) => {
+                    self.stack.clear();
+                    return Some(Err(error));
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
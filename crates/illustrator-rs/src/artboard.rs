@@ -0,0 +1,452 @@
+use std::ptr::null_mut;
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::error::{AIError, AIResult, SuiteFn};
+use crate::geometry::{Point, Rect};
+use crate::unicode::UnicodeString;
+
+/// アートボードの設定（`ai::ArtboardProperties`）
+///
+/// `Init` で確保し、破棄時に `Dispose` で解放する。変更は `Artboards::update` で反映する。
+pub struct ArtboardProperties {
+    raw: ai_ArtboardProperties,
+    suite: SuiteGuard<AIArtboardSuite>,
+}
+
+impl ArtboardProperties {
+    /// 既定の設定を作る
+    pub fn new() -> AIResult<Self> {
+        let suite = SuiteGuard::<AIArtboardSuite>::acquire()?;
+        let mut raw: ai_ArtboardProperties = unsafe { std::mem::zeroed() };
+
+        // `Init` が失敗したら `Dispose` しないよう、成功してから包む
+        unsafe { suite.Init.call((&mut raw as *mut _,))? };
+        Ok(Self { raw, suite })
+    }
+
+    /// 同じ設定の複製を作る
+    pub fn try_clone(&self) -> AIResult<Self> {
+        let suite = self.suite.try_clone()?;
+        let mut raw: ai_ArtboardProperties = unsafe { std::mem::zeroed() };
+
+        // `CloneArtboard` は初期化していない設定に書き込む（`Init` 済みだと元の実体が漏れる）
+        unsafe { suite.CloneArtboard.call((&mut raw as *mut _, &self.raw as *const _))? };
+        Ok(Self { raw, suite })
+    }
+
+    pub fn as_ptr(&self) -> *const ai_ArtboardProperties {
+        &self.raw
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut ai_ArtboardProperties {
+        &mut self.raw
+    }
+
+    pub fn name(&self) -> AIResult<String> {
+        let mut name = UnicodeString::empty()?;
+
+        unsafe { self.suite.GetName.call((&self.raw as *const _, name.as_mut_ptr()))? };
+        Ok(name.to_string_lossy())
+    }
+
+    pub fn set_name(&mut self, name: &str) -> AIResult<()> {
+        let name = UnicodeString::new(name)?;
+        unsafe { self.suite.SetName.call((&mut self.raw as *mut _, name.as_ptr())) }
+    }
+
+    /// 名前が「アートボード 1」のような自動の名前か
+    pub fn is_default_name(&self) -> AIResult<bool> {
+        let mut default: AIBoolean = 0;
+
+        unsafe { self.suite.IsDefaultName.call((&self.raw as *const _, &mut default as *mut _))? };
+        Ok(default != 0)
+    }
+
+    /// ドキュメント座標での位置と大きさ
+    pub fn bounds(&self) -> AIResult<Rect> {
+        let mut bounds: AIRealRect = Rect::default().into();
+
+        unsafe { self.suite.GetPosition.call((&self.raw as *const _, &mut bounds as *mut _))? };
+        Ok(bounds.into())
+    }
+
+    pub fn set_bounds(&mut self, bounds: Rect) -> AIResult<()> {
+        let bounds: AIRealRect = bounds.into();
+        unsafe { self.suite.SetPosition.call((&mut self.raw as *mut _, &bounds as *const _)) }
+    }
+
+    /// アートボードの定規の原点（アートボードの左上からの距離）
+    pub fn ruler_origin(&self) -> AIResult<Point> {
+        let mut origin: AIRealPoint = Point::default().into();
+
+        unsafe { self.suite.GetRulerOrigin.call((&self.raw as *const _, &mut origin as *mut _))? };
+        Ok(origin.into())
+    }
+
+    pub fn set_ruler_origin(&mut self, origin: Point) -> AIResult<()> {
+        let origin: AIRealPoint = origin.into();
+        unsafe { self.suite.SetRulerOrigin.call((&mut self.raw as *mut _, &origin as *const _)) }
+    }
+
+    /// ピクセルの縦横比
+    pub fn pixel_aspect_ratio(&self) -> AIResult<f64> {
+        let mut ratio: AIReal = 1.0;
+
+        unsafe { self.suite.GetPAR.call((&self.raw as *const _, &mut ratio as *mut _))? };
+        Ok(ratio)
+    }
+
+    pub fn set_pixel_aspect_ratio(&mut self, ratio: f64) -> AIResult<()> {
+        unsafe { self.suite.SetPAR.call((&mut self.raw as *mut _, ratio)) }
+    }
+
+    /// アートボードパネルで選択されているか（`Artboards::properties` で取得したときの状態）
+    pub fn is_selected(&self) -> AIResult<bool> {
+        let mut selected: AIBoolean = 0;
+
+        unsafe { self.suite.IsSelected.call((&self.raw as *const _, &mut selected as *mut _))? };
+        Ok(selected != 0)
+    }
+}
+
+impl Drop for ArtboardProperties {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = self.suite.Dispose.call((&mut self.raw as *mut _,));
+        }
+    }
+}
+
+/// 現在のドキュメントのアートボード（`ai::ArtboardList`）
+///
+/// アートボードは 0 始まりの番号で指定する。破棄時に `ReleaseArtboardList` で解放する。
+pub struct Artboards {
+    raw: ai_ArtboardList,
+    suite: SuiteGuard<AIArtboardSuite>,
+}
+
+impl Artboards {
+    /// 現在のドキュメントのアートボードリストを取得する
+    pub fn current() -> AIResult<Self> {
+        let suite = SuiteGuard::<AIArtboardSuite>::acquire()?;
+        let mut raw = ai_ArtboardList { fImpl: null_mut() };
+
+        // 取得に失敗したリストを `ReleaseArtboardList` しないよう、成功してから包む
+        unsafe { suite.GetArtboardList.call((&mut raw as *mut _,))? };
+        Ok(Self { raw, suite })
+    }
+
+    pub fn as_ptr(&self) -> *const ai_ArtboardList {
+        &self.raw
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut ai_ArtboardList {
+        &mut self.raw
+    }
+
+    pub fn len(&self) -> AIResult<usize> {
+        let mut count: ai_ArtboardID = 0;
+
+        unsafe { self.suite.GetCount.call((&self.raw as *const _, &mut count as *mut _))? };
+        Ok(count.max(0) as usize)
+    }
+
+    pub fn is_empty(&self) -> AIResult<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// `index` 番目のアートボードの設定の複製
+    pub fn properties(&mut self, index: usize) -> AIResult<ArtboardProperties> {
+        let id = artboard_id(index)?;
+        let mut properties = ArtboardProperties::new()?;
+
+        unsafe {
+            self.suite
+                .GetArtboardProperties
+                .call((&mut self.raw as *mut _, id, properties.as_mut_ptr()))?;
+        }
+        Ok(properties)
+    }
+
+    /// `index` 番目のアートボードに設定を反映する
+    pub fn update(&mut self, index: usize, properties: &ArtboardProperties) -> AIResult<()> {
+        let id = artboard_id(index)?;
+        unsafe { self.suite.Update.call((&mut self.raw as *mut _, id, properties.as_ptr())) }
+    }
+
+    /// `index` 番目のアートボードの設定を取得し、`f` で変更して反映する
+    pub fn modify(&mut self, index: usize, f: impl FnOnce(&mut ArtboardProperties) -> AIResult<()>) -> AIResult<()> {
+        let mut properties = self.properties(index)?;
+        f(&mut properties)?;
+        self.update(index, &properties)
+    }
+
+    pub fn name(&mut self, index: usize) -> AIResult<String> {
+        self.properties(index)?.name()
+    }
+
+    pub fn set_name(&mut self, index: usize, name: &str) -> AIResult<()> {
+        self.modify(index, |properties| properties.set_name(name))
+    }
+
+    pub fn bounds(&mut self, index: usize) -> AIResult<Rect> {
+        self.properties(index)?.bounds()
+    }
+
+    pub fn set_bounds(&mut self, index: usize, bounds: Rect) -> AIResult<()> {
+        self.modify(index, |properties| properties.set_bounds(bounds))
+    }
+
+    /// 名前が一致する最初のアートボードの番号
+    pub fn find(&mut self, name: &str) -> AIResult<Option<usize>> {
+        for index in 0..self.len()? {
+            if self.name(index)? == name {
+                return Ok(Some(index));
+            }
+        }
+        Ok(None)
+    }
+
+    /// アクティブなアートボードの番号
+    pub fn active(&self) -> AIResult<usize> {
+        let mut index: ai_ArtboardID = 0;
+
+        unsafe { self.suite.GetActive.call((&self.raw as *const _, &mut index as *mut _))? };
+        usize::try_from(index).map_err(|_| AIError::CantHappen)
+    }
+
+    pub fn set_active(&mut self, index: usize) -> AIResult<()> {
+        let id = artboard_id(index)?;
+        unsafe { self.suite.SetActive.call((&mut self.raw as *mut _, id)) }
+    }
+
+    /// 末尾にアートボードを追加し、その番号を返す
+    pub fn add(&mut self, properties: &mut ArtboardProperties) -> AIResult<usize> {
+        let mut index: ai_ArtboardID = 0;
+
+        unsafe {
+            self.suite
+                .AddNew
+                .call((&mut self.raw as *mut _, properties.as_mut_ptr(), &mut index as *mut _))?;
+        }
+        usize::try_from(index).map_err(|_| AIError::CantHappen)
+    }
+
+    /// `index` の位置にアートボードを挿入し、挿入された番号を返す
+    pub fn insert(&mut self, index: usize, properties: &mut ArtboardProperties) -> AIResult<usize> {
+        let mut index = artboard_id(index)?;
+
+        unsafe {
+            self.suite
+                .Insert
+                .call((&mut self.raw as *mut _, properties.as_mut_ptr(), &mut index as *mut _))?;
+        }
+        usize::try_from(index).map_err(|_| AIError::CantHappen)
+    }
+
+    /// アートボードを削除する（中のアートは残る）
+    pub fn delete(&mut self, index: usize) -> AIResult<()> {
+        let id = artboard_id(index)?;
+        unsafe { self.suite.Delete.call((&mut self.raw as *mut _, id)) }
+    }
+
+    /// 同じ設定のアートボードを直後に挿入し、その番号を返す
+    pub fn duplicate(&mut self, index: usize) -> AIResult<usize> {
+        let mut copy = self.properties(index)?.try_clone()?;
+        self.insert(index + 1, &mut copy)
+    }
+
+    /// アートボードを選択する（`exclusively` なら他の選択を解除する）
+    pub fn select(&mut self, index: usize, exclusively: bool) -> AIResult<()> {
+        let id = artboard_id(index)?;
+        unsafe {
+            self.suite
+                .SelectArtboard
+                .call((&mut self.raw as *mut _, id, exclusively as AIBoolean))
+        }
+    }
+
+    pub fn deselect(&mut self, index: usize) -> AIResult<()> {
+        let id = artboard_id(index)?;
+        unsafe { self.suite.DeselectArtboard.call((&mut self.raw as *mut _, id)) }
+    }
+
+    pub fn select_all(&mut self) -> AIResult<()> {
+        unsafe { self.suite.SelectAllArtboards.call((&mut self.raw as *mut _,)) }
+    }
+
+    pub fn deselect_all(&mut self) -> AIResult<()> {
+        unsafe { self.suite.DeselectAllArtboards.call((&mut self.raw as *mut _,)) }
+    }
+
+    /// 重なっているアートボードがあるか
+    pub fn any_overlapping(&mut self) -> AIResult<bool> {
+        let mut overlapping: AIBoolean = 0;
+
+        unsafe {
+            self.suite
+                .AreAnyArtboardsOverlapping
+                .call((&mut self.raw as *mut _, &mut overlapping as *mut _))?;
+        }
+        Ok(overlapping != 0)
+    }
+
+    /// すべてのアートボードの設定を番号順に取得する
+    pub fn all(&mut self) -> AIResult<Vec<ArtboardProperties>> {
+        (0..self.len()?).map(|index| self.properties(index)).collect()
+    }
+}
+
+impl Drop for Artboards {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = self.suite.ReleaseArtboardList.call((&mut self.raw as *mut _,));
+        }
+    }
+}
+
+fn artboard_id(index: usize) -> AIResult<ai_ArtboardID> {
+    ai_ArtboardID::try_from(index).map_err(|_| AIError::BadParameter)
+}
+
+/// `"1-3,5"` のようなアートボードの範囲指定（`AIArtboardRangeHandle`）
+///
+/// 文字列中の番号は 1 始まりで、列挙される番号は 0 始まり。破棄時に `Dispose` で解放する。
+pub struct ArtboardRange {
+    handle: AIArtboardRangeHandle,
+    suite: SuiteGuard<AIArtboardRangeSuite>,
+}
+
+impl ArtboardRange {
+    /// 範囲指定の文字列を解釈する
+    pub fn parse(range: &str) -> AIResult<Self> {
+        Self::create(false, range)
+    }
+
+    /// 現在のドキュメントのすべてのアートボード
+    pub fn all() -> AIResult<Self> {
+        Self::create(true, "")
+    }
+
+    fn create(all_artboards: bool, range: &str) -> AIResult<Self> {
+        let suite = SuiteGuard::<AIArtboardRangeSuite>::acquire()?;
+        let range = UnicodeString::new(range)?;
+        let mut handle: AIArtboardRangeHandle = null_mut();
+
+        unsafe {
+            suite
+                .Create
+                .call((all_artboards as ASBoolean, range.as_ptr(), &mut handle as *mut _))?;
+        }
+        if handle.is_null() {
+            return Err(AIError::CantHappen);
+        }
+        Ok(Self { handle, suite })
+    }
+
+    /// 範囲指定の文字列として正しいか
+    pub fn validate(range: &str) -> AIResult<()> {
+        let suite = SuiteGuard::<AIArtboardRangeSuite>::acquire()?;
+        let range = UnicodeString::new(range)?;
+        unsafe { suite.ValidateString.call((range.as_ptr(),)) }
+    }
+
+    pub fn as_raw(&self) -> AIArtboardRangeHandle {
+        self.handle
+    }
+
+    /// 範囲に含まれる番号の数（重複を含む）
+    pub fn len(&self) -> AIResult<usize> {
+        let mut count: ASInt32 = 0;
+
+        unsafe { self.suite.GetCount.call((self.handle, &mut count as *mut _))? };
+        Ok(count.max(0) as usize)
+    }
+
+    pub fn is_empty(&self) -> AIResult<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// すべてのアートボードを表す範囲か
+    pub fn is_all(&self) -> AIResult<bool> {
+        let mut all: ASBoolean = 0;
+
+        unsafe { self.suite.IsAllSelected.call((self.handle, &mut all as *mut _))? };
+        Ok(all != 0)
+    }
+
+    /// 重複する番号を取り除く（最初に現れたものを残す）
+    pub fn remove_duplicates(&mut self) -> AIResult<()> {
+        unsafe { self.suite.RemoveDuplicate.call((self.handle,)) }
+    }
+
+    /// 範囲に含まれる番号（0 始まり）を指定された順に列挙する
+    pub fn iter(&self) -> AIResult<ArtboardRangeIter<'_>> {
+        let remaining = self.len()?;
+        let mut iterator: AIArtboardRangeIterator = null_mut();
+
+        unsafe { self.suite.Begin.call((self.handle, &mut iterator as *mut _))? };
+        Ok(ArtboardRangeIter {
+            range: self,
+            iterator,
+            remaining,
+        })
+    }
+
+    /// 範囲に含まれる番号（0 始まり）
+    pub fn to_vec(&self) -> AIResult<Vec<usize>> {
+        self.iter()?.collect()
+    }
+}
+
+impl Drop for ArtboardRange {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = self.suite.Dispose.call((self.handle,));
+        }
+    }
+}
+
+/// `ArtboardRange` の番号を順にたどるイテレータ
+///
+/// 終端は `GetCount` の数で判定する（`Next` が返す終端のエラーは使わない）。
+/// エラーが起きた場合はそれを一度返して終了する。
+pub struct ArtboardRangeIter<'a> {
+    range: &'a ArtboardRange,
+    iterator: AIArtboardRangeIterator,
+    remaining: usize,
+}
+
+impl Iterator for ArtboardRangeIter<'_> {
+    type Item = AIResult<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let mut index: ASInt32 = 0;
+        let result = unsafe { self.range.suite.Next.call((self.iterator, &mut index as *mut _)) };
+        match result {
+            Ok(()) => Some(usize::try_from(index).map_err(|_| AIError::BadParameter)),
+            Err(error) => {
+                self.remaining = 0;
+                Some(Err(error))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
+
+impl Drop for ArtboardRangeIter<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = self.range.suite.DisposeIterator.call((self.iterator,));
+        }
+    }
+}
@@ -0,0 +1,404 @@
+use crate::geometry::{Point, Rect};
+
+/// パスのアンカーポイント（`AIPathSegment`）
+///
+/// `in_point` は前のセグメントから入る方向線、`out_point` は次のセグメントへ出る方向線の端点。
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PathSegment {
+    pub anchor: Point,
+    pub in_point: Point,
+    pub out_point: Point,
+    /// 方向線が独立して動くコーナーポイントか
+    pub corner: bool,
+}
+
+impl PathSegment {
+    /// 方向線を持たないコーナーポイント
+    pub fn corner(anchor: Point) -> Self {
+        Self {
+            anchor,
+            in_point: anchor,
+            out_point: anchor,
+            corner: true,
+        }
+    }
+
+    /// 方向線が一直線に並ぶスムーズポイント
+    pub fn smooth(anchor: Point, in_point: Point, out_point: Point) -> Self {
+        Self {
+            anchor,
+            in_point,
+            out_point,
+            corner: false,
+        }
+    }
+}
+
+/// 3次ベジェ曲線
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CubicBezier {
+    pub p0: Point,
+    pub p1: Point,
+    pub p2: Point,
+    pub p3: Point,
+}
+
+impl CubicBezier {
+    pub fn new(p0: Point, p1: Point, p2: Point, p3: Point) -> Self {
+        Self { p0, p1, p2, p3 }
+    }
+
+    /// 二つのセグメントを結ぶ曲線
+    pub fn between(from: &PathSegment, to: &PathSegment) -> Self {
+        Self::new(from.anchor, from.out_point, to.in_point, to.anchor)
+    }
+
+    /// `t` (0..=1) における点
+    pub fn point_at(&self, t: f64) -> Point {
+        let mt = 1.0 - t;
+        let a = mt * mt * mt;
+        let b = 3.0 * mt * mt * t;
+        let c = 3.0 * mt * t * t;
+        let d = t * t * t;
+
+        Point::new(
+            a * self.p0.x + b * self.p1.x + c * self.p2.x + d * self.p3.x,
+            a * self.p0.y + b * self.p1.y + c * self.p2.y + d * self.p3.y,
+        )
+    }
+
+    /// `t` における接線ベクトル
+    pub fn derivative_at(&self, t: f64) -> Point {
+        let mt = 1.0 - t;
+        let a = 3.0 * mt * mt;
+        let b = 6.0 * mt * t;
+        let c = 3.0 * t * t;
+
+        Point::new(
+            a * (self.p1.x - self.p0.x) + b * (self.p2.x - self.p1.x) + c * (self.p3.x - self.p2.x),
+            a * (self.p1.y - self.p0.y) + b * (self.p2.y - self.p1.y) + c * (self.p3.y - self.p2.y),
+        )
+    }
+
+    /// de Casteljau 法で `t` の位置で二分する
+    pub fn split(&self, t: f64) -> (CubicBezier, CubicBezier) {
+        let lerp = |a: Point, b: Point| Point::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t);
+
+        let p01 = lerp(self.p0, self.p1);
+        let p12 = lerp(self.p1, self.p2);
+        let p23 = lerp(self.p2, self.p3);
+        let p012 = lerp(p01, p12);
+        let p123 = lerp(p12, p23);
+        let mid = lerp(p012, p123);
+
+        (
+            CubicBezier::new(self.p0, p01, p012, mid),
+            CubicBezier::new(mid, p123, p23, self.p3),
+        )
+    }
+
+    /// 両端と極値から求めた正確な外接矩形
+    pub fn bounds(&self) -> Rect {
+        let mut rect = Rect::new(self.p0.x, self.p0.y, self.p0.x, self.p0.y);
+        let mut include = |p: Point| {
+            rect.left = rect.left.min(p.x);
+            rect.right = rect.right.max(p.x);
+            rect.bottom = rect.bottom.min(p.y);
+            rect.top = rect.top.max(p.y);
+        };
+
+        include(self.p3);
+        let extrema = extrema(self.p0.x, self.p1.x, self.p2.x, self.p3.x)
+            .into_iter()
+            .chain(extrema(self.p0.y, self.p1.y, self.p2.y, self.p3.y));
+        for t in extrema.flatten() {
+            include(self.point_at(t));
+        }
+        rect
+    }
+
+    /// 折れ線近似による長さ
+    ///
+    /// `flatness` は制御点の折れ線の長さに対する許容誤差の比（例: `1e-3`）。
+    /// 分割は `MAX_LENGTH_DEPTH` 段までで打ち切るため、0 を渡しても必ず終わる。
+    pub fn length(&self, flatness: f64) -> f64 {
+        self.subdivided_length(flatness, MAX_LENGTH_DEPTH)
+    }
+
+    fn subdivided_length(&self, flatness: f64, depth: u32) -> f64 {
+        let chord = distance(self.p0, self.p3);
+        let polygon = distance(self.p0, self.p1) + distance(self.p1, self.p2) + distance(self.p2, self.p3);
+
+        if depth == 0 || polygon - chord <= flatness * polygon {
+            return (polygon + chord) / 2.0;
+        }
+
+        let (left, right) = self.split(0.5);
+        left.subdivided_length(flatness, depth - 1) + right.subdivided_length(flatness, depth - 1)
+    }
+
+    /// 直線（方向線を持たない）か
+    pub fn is_line(&self) -> bool {
+        self.p1 == self.p0 && self.p2 == self.p3
+    }
+}
+
+/// `CubicBezier::length` の分割の最大段数（曲線あたり最大 4096 区間）
+const MAX_LENGTH_DEPTH: u32 = 12;
+
+/// 1次元の3次ベジェの導関数が 0 になる `t` (0..1)
+fn extrema(p0: f64, p1: f64, p2: f64, p3: f64) -> [Option<f64>; 2] {
+    // B'(t) / 3 = a t^2 + b t + c
+    let a = -p0 + 3.0 * p1 - 3.0 * p2 + p3;
+    let b = 2.0 * (p0 - 2.0 * p1 + p2);
+    let c = p1 - p0;
+    let inside = |t: f64| (t > 0.0 && t < 1.0).then_some(t);
+
+    if a.abs() < 1e-12 {
+        if b.abs() < 1e-12 {
+            return [None, None];
+        }
+        return [inside(-c / b), None];
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return [None, None];
+    }
+
+    let root = discriminant.sqrt();
+    [inside((-b + root) / (2.0 * a)), inside((-b - root) / (2.0 * a))]
+}
+
+fn distance(a: Point, b: Point) -> f64 {
+    (b.x - a.x).hypot(b.y - a.y)
+}
+
+/// ホストを介さずに扱えるパスの形状
+///
+/// `Path::to_bezier`/`Path::set_bezier` で Illustrator のパスと相互に変換できる。
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BezierPath {
+    pub segments: Vec<PathSegment>,
+    pub closed: bool,
+}
+
+impl BezierPath {
+    pub fn new(segments: Vec<PathSegment>, closed: bool) -> Self {
+        Self { segments, closed }
+    }
+
+    /// 頂点を直線で結ぶ折れ線
+    pub fn from_points(points: &[Point], closed: bool) -> Self {
+        Self::new(points.iter().copied().map(PathSegment::corner).collect(), closed)
+    }
+
+    /// 矩形（左上から時計回り）
+    pub fn rectangle(rect: Rect) -> Self {
+        Self::from_points(
+            &[
+                Point::new(rect.left, rect.top),
+                Point::new(rect.right, rect.top),
+                Point::new(rect.right, rect.bottom),
+                Point::new(rect.left, rect.bottom),
+            ],
+            true,
+        )
+    }
+
+    /// 4つの曲線で近似した楕円
+    pub fn ellipse(rect: Rect) -> Self {
+        // 円弧を3次ベジェで近似するときの方向線の長さの比
+        const KAPPA: f64 = 0.552_284_749_830_793_4;
+
+        let center = rect.center();
+        let rx = rect.width() / 2.0;
+        let ry = rect.height() / 2.0;
+        let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+
+        let top = Point::new(center.x, center.y + ry);
+        let right = Point::new(center.x + rx, center.y);
+        let bottom = Point::new(center.x, center.y - ry);
+        let left = Point::new(center.x - rx, center.y);
+
+        Self::new(
+            vec![
+                PathSegment::smooth(top, Point::new(top.x - kx, top.y), Point::new(top.x + kx, top.y)),
+                PathSegment::smooth(right, Point::new(right.x, right.y + ky), Point::new(right.x, right.y - ky)),
+                PathSegment::smooth(bottom, Point::new(bottom.x + kx, bottom.y), Point::new(bottom.x - kx, bottom.y)),
+                PathSegment::smooth(left, Point::new(left.x, left.y - ky), Point::new(left.x, left.y + ky)),
+            ],
+            true,
+        )
+    }
+
+    /// セグメント間の曲線（閉じたパスは最後から最初への曲線を含む）
+    pub fn curves(&self) -> Vec<CubicBezier> {
+        let mut curves: Vec<CubicBezier> = self
+            .segments
+            .windows(2)
+            .map(|pair| CubicBezier::between(&pair[0], &pair[1]))
+            .collect();
+
+        if self.closed && self.segments.len() > 1 {
+            curves.push(CubicBezier::between(&self.segments[self.segments.len() - 1], &self.segments[0]));
+        }
+        curves
+    }
+
+    /// 外接矩形（セグメントが無ければ `None`）
+    pub fn bounds(&self) -> Option<Rect> {
+        let first = self.segments.first()?;
+        let point = Rect::new(first.anchor.x, first.anchor.y, first.anchor.x, first.anchor.y);
+
+        Some(
+            self.curves()
+                .iter()
+                .map(CubicBezier::bounds)
+                .fold(point, |acc, rect| acc.union(&rect)),
+        )
+    }
+
+    /// 全長（`flatness` は `CubicBezier::length` と同じ許容誤差の比）
+    pub fn length(&self, flatness: f64) -> f64 {
+        self.curves().iter().map(|curve| curve.length(flatness)).sum()
+    }
+
+    /// 向きを反転する
+    pub fn reverse(&mut self) {
+        self.segments.reverse();
+        for segment in &mut self.segments {
+            std::mem::swap(&mut segment.in_point, &mut segment.out_point);
+        }
+    }
+
+    /// 全ての点を `f` で写す
+    pub fn map_points(&mut self, mut f: impl FnMut(Point) -> Point) {
+        for segment in &mut self.segments {
+            segment.anchor = f(segment.anchor);
+            segment.in_point = f(segment.in_point);
+            segment.out_point = f(segment.out_point);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_sys::AIPathSegment;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!((actual - expected).abs() <= tolerance, "{actual} != {expected}");
+    }
+
+    fn s_curve() -> CubicBezier {
+        CubicBezier::new(
+            Point::new(0.0, 0.0),
+            Point::new(10.0, 40.0),
+            Point::new(60.0, -40.0),
+            Point::new(70.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn length_of_straight_curve() {
+        let line = CubicBezier::new(
+            Point::new(0.0, 0.0),
+            Point::new(0.0, 0.0),
+            Point::new(30.0, 40.0),
+            Point::new(30.0, 40.0),
+        );
+        assert_close(line.length(1e-3), 50.0, 1e-9);
+    }
+
+    #[test]
+    fn length_of_circle() {
+        let circle = BezierPath::ellipse(Rect::new(-10.0, 10.0, 10.0, -10.0));
+        // 3次ベジェによる円の近似誤差は半径の 0.03% 程度
+        assert_close(circle.length(1e-4), 2.0 * std::f64::consts::PI * 10.0, 0.05);
+    }
+
+    #[test]
+    fn length_terminates_without_tolerance() {
+        let curve = s_curve();
+        let exact = curve.length(0.0);
+        assert!(exact.is_finite());
+        assert_close(curve.length(1e-3), exact, exact * 1e-3);
+
+        let point = CubicBezier::new(Point::new(5.0, 5.0), Point::new(5.0, 5.0), Point::new(5.0, 5.0), Point::new(5.0, 5.0));
+        assert_eq!(point.length(0.0), 0.0);
+        assert!(curve.length(f64::NAN).is_finite());
+    }
+
+    #[test]
+    fn split_preserves_the_curve() {
+        let curve = s_curve();
+        let (left, right) = curve.split(0.25);
+
+        assert_eq!(left.p0, curve.p0);
+        assert_eq!(right.p3, curve.p3);
+        assert_eq!(left.p3, right.p0);
+        assert_eq!(left.p3, curve.point_at(0.25));
+
+        for i in 0..=10 {
+            let t = i as f64 / 10.0;
+            let expected = curve.point_at(0.25 * t);
+            let actual = left.point_at(t);
+            assert_close(actual.x, expected.x, 1e-9);
+            assert_close(actual.y, expected.y, 1e-9);
+
+            let expected = curve.point_at(0.25 + 0.75 * t);
+            let actual = right.point_at(t);
+            assert_close(actual.x, expected.x, 1e-9);
+            assert_close(actual.y, expected.y, 1e-9);
+        }
+    }
+
+    #[test]
+    fn bounds_include_extrema() {
+        let bounds = s_curve().bounds();
+
+        // 極値は t = 1/2 ∓ √3/6、y = ±20√3/3
+        let peak = 20.0 * 3f64.sqrt() / 3.0;
+        assert_close(bounds.left, 0.0, 1e-9);
+        assert_close(bounds.right, 70.0, 1e-9);
+        assert_close(bounds.top, peak, 1e-9);
+        assert_close(bounds.bottom, -peak, 1e-9);
+    }
+
+    #[test]
+    fn path_bounds_and_length() {
+        let rect = BezierPath::rectangle(Rect::new(0.0, 20.0, 30.0, 0.0));
+        assert_eq!(rect.bounds(), Some(Rect::new(0.0, 20.0, 30.0, 0.0)));
+        assert_close(rect.length(0.0), 100.0, 1e-9);
+
+        let mut open = rect.clone();
+        open.closed = false;
+        assert_close(open.length(0.0), 80.0, 1e-9);
+
+        assert_eq!(BezierPath::default().bounds(), None);
+    }
+
+    #[test]
+    fn segments_round_trip_through_ai_path_segment() {
+        let mut path = BezierPath::ellipse(Rect::new(-8.0, 4.0, 8.0, -4.0));
+        path.segments.push(PathSegment::corner(Point::new(1.5, -2.5)));
+
+        let raw: Vec<AIPathSegment> = path.segments.iter().copied().map(AIPathSegment::from).collect();
+        let back: Vec<PathSegment> = raw.into_iter().map(PathSegment::from).collect();
+        assert_eq!(BezierPath::new(back, path.closed), path);
+    }
+
+    #[test]
+    fn reverse_swaps_direction_handles() {
+        let mut path = BezierPath::ellipse(Rect::new(-8.0, 4.0, 8.0, -4.0));
+        let original = path.clone();
+
+        path.reverse();
+        assert_eq!(path.segments[0].anchor, original.segments[3].anchor);
+        assert_eq!(path.segments[0].in_point, original.segments[3].out_point);
+
+        path.reverse();
+        assert_eq!(path, original);
+    }
+}
@@ -0,0 +1,160 @@
+use std::ffi::CString;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::dictionary::{from_entry, to_entry, Array, Dictionary, Entry, EntryType, Error};
+
+/// 形式のバージョン（先頭の 1 バイト）
+const FORMAT_VERSION: u8 = 1;
+
+/// 読み込む配列と辞書の入れ子の上限（壊れたデータで再帰が深くなりすぎないように）
+const MAX_DEPTH: usize = 64;
+
+/// 値をバイト列にシリアライズする
+///
+/// `to_entry` と同じ規則で `Entry` にしてから、その木を書き出す。
+/// プラグイングループのデータ領域のような、辞書を置けない場所に格納するときに使う。
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    let mut bytes = vec![FORMAT_VERSION];
+    write_entry(&mut bytes, &to_entry(value)?)?;
+    Ok(bytes)
+}
+
+/// `to_bytes` で書き出したバイト列から値をデシリアライズする
+pub fn from_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, Error> {
+    let mut reader = Reader(bytes);
+    match reader.u8()? {
+        FORMAT_VERSION => {}
+        version => return Err(Error::Message(format!("unsupported data version {}", version))),
+    }
+
+    let entry = reader.entry(0)?;
+    if !reader.0.is_empty() {
+        return Err(Error::Message("trailing bytes after the stored value".into()));
+    }
+    from_entry(&entry)
+}
+
+fn write_len(bytes: &mut Vec<u8>, len: usize) -> Result<(), Error> {
+    let len = u32::try_from(len).map_err(|_| Error::Message("value is too large to be stored".into()))?;
+    bytes.extend_from_slice(&len.to_le_bytes());
+    Ok(())
+}
+
+fn write_bytes(bytes: &mut Vec<u8>, value: &[u8]) -> Result<(), Error> {
+    write_len(bytes, value.len())?;
+    bytes.extend_from_slice(value);
+    Ok(())
+}
+
+/// 種類（`AIEntryType` の値）の 1 バイトに続けて値を書く
+fn write_entry(bytes: &mut Vec<u8>, entry: &Entry) -> Result<(), Error> {
+    let entry_type = entry.entry_type()?;
+    let tag = u8::try_from(entry_type.to_raw()).map_err(|_| unsupported(entry_type))?;
+    bytes.push(tag);
+
+    match entry_type {
+        EntryType::Integer => bytes.extend_from_slice(&entry.to_int()?.to_le_bytes()),
+        EntryType::Boolean => bytes.push(entry.to_bool()? as u8),
+        EntryType::Real => bytes.extend_from_slice(&entry.to_real()?.to_le_bytes()),
+        EntryType::String => write_bytes(bytes, entry.to_c_string()?.as_bytes())?,
+        EntryType::UnicodeString => write_bytes(bytes, entry.to_unicode()?.as_bytes())?,
+        EntryType::Binary => write_bytes(bytes, &entry.to_binary()?)?,
+        EntryType::Array => {
+            let array = entry.to_array()?;
+            write_len(bytes, array.len()?)?;
+            for item in array.iter() {
+                write_entry(bytes, &item?)?;
+            }
+        }
+        EntryType::Dictionary => {
+            let dictionary = entry.to_dictionary()?;
+            write_len(bytes, dictionary.len()?)?;
+            for item in dictionary.iter()? {
+                let (key, item) = item?;
+                write_bytes(bytes, key.as_bytes())?;
+                write_entry(bytes, &item)?;
+            }
+        }
+        _ => return Err(unsupported(entry_type)),
+    }
+    Ok(())
+}
+
+fn unsupported(entry_type: EntryType) -> Error {
+    Error::Message(format!("{:?} entries cannot be stored as bytes", entry_type))
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], Error> {
+        if self.0.len() < len {
+            return Err(Error::Message("stored data is truncated".into()));
+        }
+        let (head, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn len(&mut self) -> Result<usize, Error> {
+        Ok(u32::from_le_bytes(self.array()?) as usize)
+    }
+
+    fn bytes(&mut self) -> Result<Vec<u8>, Error> {
+        let len = self.len()?;
+        Ok(self.take(len)?.to_vec())
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        String::from_utf8(self.bytes()?).map_err(|_| Error::Message("stored string is not UTF-8".into()))
+    }
+
+    /// `depth` は外側にある配列と辞書の数
+    fn entry(&mut self, depth: usize) -> Result<Entry, Error> {
+        let entry_type = EntryType::from_raw(self.u8()?.into());
+
+        let entry = match entry_type {
+            EntryType::Integer => Entry::from_int(i32::from_le_bytes(self.array()?))?,
+            EntryType::Boolean => Entry::from_bool(self.u8()? != 0)?,
+            EntryType::Real => Entry::from_real(f64::from_le_bytes(self.array()?))?,
+            EntryType::String => {
+                let value = CString::new(self.bytes()?).map_err(|_| Error::Message("stored string contains NUL".into()))?;
+                Entry::from_c_string(&value)?
+            }
+            EntryType::UnicodeString => Entry::from_unicode(&self.string()?)?,
+            EntryType::Binary => Entry::from_binary(&self.bytes()?)?,
+            EntryType::Array | EntryType::Dictionary if depth >= MAX_DEPTH => {
+                return Err(Error::Message("stored data is nested too deeply".into()));
+            }
+            EntryType::Array => {
+                let mut array = Array::new()?;
+                for _ in 0..self.len()? {
+                    array.push(&self.entry(depth + 1)?)?;
+                }
+                Entry::from_array(&array)?
+            }
+            EntryType::Dictionary => {
+                let mut dictionary = Dictionary::new()?;
+                for _ in 0..self.len()? {
+                    let key = self.string()?;
+                    dictionary.set(&key, &self.entry(depth + 1)?)?;
+                }
+                Entry::from_dictionary(&dictionary)?
+            }
+            _ => return Err(unsupported(entry_type)),
+        };
+        Ok(entry)
+    }
+}
@@ -0,0 +1,871 @@
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::fmt;
+use std::ptr::{null, null_mut};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::art::{Art, PaintOrder};
+use crate::error::{AIError, AIResult, SuiteFn};
+use crate::unicode::UnicodeString;
+use crate::util::read_c_string;
+
+mod de;
+mod ser;
+
+pub use de::from_entry;
+pub use ser::to_entry;
+
+/// エントリの値の種類（`AIEntryType`）
+///
+/// `AIEntry.h` の無名 enum はバインディングに含まれないため、値をここで定義している。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EntryType {
+    Integer,
+    Boolean,
+    Real,
+    /// プラットフォームの文字コードの C 文字列
+    String,
+    Dictionary,
+    Array,
+    Binary,
+    Point,
+    Matrix,
+    /// アートオブジェクト（`kGraphicObjectType`）
+    Art,
+    UnicodeString,
+    /// その他の種類
+    Other(AIEntryType),
+}
+
+impl EntryType {
+    pub fn from_raw(raw: AIEntryType) -> Self {
+        match raw {
+            1 => EntryType::Integer,
+            2 => EntryType::Boolean,
+            3 => EntryType::Real,
+            4 => EntryType::String,
+            5 => EntryType::Dictionary,
+            6 => EntryType::Array,
+            7 => EntryType::Binary,
+            8 => EntryType::Point,
+            9 => EntryType::Matrix,
+            25 => EntryType::Art,
+            26 => EntryType::UnicodeString,
+            _ => EntryType::Other(raw),
+        }
+    }
+
+    pub fn to_raw(self) -> AIEntryType {
+        match self {
+            EntryType::Integer => 1,
+            EntryType::Boolean => 2,
+            EntryType::Real => 3,
+            EntryType::String => 4,
+            EntryType::Dictionary => 5,
+            EntryType::Array => 6,
+            EntryType::Binary => 7,
+            EntryType::Point => 8,
+            EntryType::Matrix => 9,
+            EntryType::Art => 25,
+            EntryType::UnicodeString => 26,
+            EntryType::Other(raw) => raw,
+        }
+    }
+}
+
+/// 辞書と配列の値（`AIEntryRef`）
+///
+/// 参照カウントを持ち、`try_clone` で `AddRef`、破棄時に `Release` する。
+/// SDK の `To*` は呼び出しのたびに参照を一つ解放するため、呼び出す前に参照を足している。
+pub struct Entry {
+    raw: AIEntryRef,
+    suite: SuiteGuard<AIEntrySuite>,
+}
+
+impl Entry {
+    /// 参照を一つ引き取る
+    ///
+    /// # Safety
+    /// `raw` は `null` か、呼び出し元が所有する参照でなければならない。
+    pub unsafe fn from_raw(raw: AIEntryRef) -> AIResult<Option<Self>> {
+        if raw.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            raw,
+            suite: SuiteGuard::acquire()?,
+        }))
+    }
+
+    pub fn as_raw(&self) -> AIEntryRef {
+        self.raw
+    }
+
+    /// 同じ値への参照を一つ増やす
+    pub fn try_clone(&self) -> AIResult<Self> {
+        let suite = self.suite.try_clone()?;
+        let add_ref = suite.AddRef.ok_or(AIError::NotImplemented)?;
+        unsafe { add_ref(self.raw) };
+        Ok(Self { raw: self.raw, suite })
+    }
+
+    fn create(make: impl FnOnce(&AIEntrySuite) -> Option<AIEntryRef>) -> AIResult<Self> {
+        let suite = SuiteGuard::<AIEntrySuite>::acquire()?;
+        let raw = make(&suite).ok_or(AIError::NotImplemented)?;
+
+        if raw.is_null() {
+            return Err(AIError::OutOfMemory);
+        }
+        Ok(Self { raw, suite })
+    }
+
+    pub fn from_bool(value: bool) -> AIResult<Self> {
+        Self::create(|suite| suite.FromBoolean.map(|f| unsafe { f(value as ASBoolean) }))
+    }
+
+    pub fn from_int(value: i32) -> AIResult<Self> {
+        Self::create(|suite| suite.FromInteger.map(|f| unsafe { f(value) }))
+    }
+
+    pub fn from_real(value: f64) -> AIResult<Self> {
+        Self::create(|suite| suite.FromReal.map(|f| unsafe { f(value) }))
+    }
+
+    /// プラットフォームの文字コードの C 文字列
+    pub fn from_c_string(value: &CStr) -> AIResult<Self> {
+        Self::create(|suite| suite.FromString.map(|f| unsafe { f(value.as_ptr()) }))
+    }
+
+    pub fn from_unicode(value: &str) -> AIResult<Self> {
+        let value = UnicodeString::new(value)?;
+        Self::create(|suite| suite.FromUnicodeString.map(|f| unsafe { f(value.as_ptr()) }))
+    }
+
+    pub fn from_binary(value: &[u8]) -> AIResult<Self> {
+        // SDK は値を複製するだけなので `*mut` でも書き換えられない
+        let pointer = value.as_ptr() as *mut c_void;
+        Self::create(|suite| suite.FromBinary.map(|f| unsafe { f(pointer, value.len()) }))
+    }
+
+    pub fn from_dictionary(value: &Dictionary) -> AIResult<Self> {
+        Self::create(|suite| suite.FromDict.map(|f| unsafe { f(value.raw) }))
+    }
+
+    pub fn from_array(value: &Array) -> AIResult<Self> {
+        Self::create(|suite| suite.FromArray.map(|f| unsafe { f(value.raw) }))
+    }
+
+    pub fn entry_type(&self) -> AIResult<EntryType> {
+        let get_type = self.suite.GetType.ok_or(AIError::NotImplemented)?;
+        Ok(EntryType::from_raw(unsafe { get_type(self.raw) }))
+    }
+
+    /// `To*` に渡すための参照を足す
+    fn retained(&self) -> AIResult<AIEntryRef> {
+        let add_ref = self.suite.AddRef.ok_or(AIError::NotImplemented)?;
+        unsafe { add_ref(self.raw) };
+        Ok(self.raw)
+    }
+
+    pub fn to_bool(&self) -> AIResult<bool> {
+        let mut value: ASBoolean = 0;
+
+        unsafe { self.suite.ToBoolean.call((self.retained()?, &mut value as *mut _))? };
+        Ok(value != 0)
+    }
+
+    pub fn to_int(&self) -> AIResult<i32> {
+        let mut value: ai_int32 = 0;
+
+        unsafe { self.suite.ToInteger.call((self.retained()?, &mut value as *mut _))? };
+        Ok(value)
+    }
+
+    pub fn to_real(&self) -> AIResult<f64> {
+        let mut value: AIReal = 0.0;
+
+        unsafe { self.suite.ToReal.call((self.retained()?, &mut value as *mut _))? };
+        Ok(value)
+    }
+
+    pub fn to_c_string(&self) -> AIResult<CString> {
+        let mut value: *const c_char = null();
+
+        unsafe {
+            self.suite.ToString.call((self.retained()?, &mut value as *mut _))?;
+            if value.is_null() {
+                return Err(AIError::CantHappen);
+            }
+            Ok(CStr::from_ptr(value).to_owned())
+        }
+    }
+
+    pub fn to_unicode(&self) -> AIResult<String> {
+        let mut value = UnicodeString::empty()?;
+
+        unsafe { self.suite.ToUnicodeString.call((self.retained()?, value.as_mut_ptr()))? };
+        Ok(value.to_string_lossy())
+    }
+
+    pub fn to_binary(&self) -> AIResult<Vec<u8>> {
+        let mut size: usize = 0;
+
+        unsafe {
+            self.suite
+                .ToBinary
+                .call((self.retained()?, null_mut::<c_void>(), &mut size as *mut _))?;
+        }
+        let mut value = vec![0u8; size];
+        unsafe {
+            self.suite
+                .ToBinary
+                .call((self.retained()?, value.as_mut_ptr() as *mut c_void, &mut size as *mut _))?;
+        }
+        value.truncate(size);
+        Ok(value)
+    }
+
+    pub fn to_dictionary(&self) -> AIResult<Dictionary> {
+        let mut value: AIDictionaryRef = null_mut();
+
+        unsafe {
+            self.suite.ToDict.call((self.retained()?, &mut value as *mut _))?;
+            Dictionary::from_raw(value)?.ok_or(AIError::CantHappen)
+        }
+    }
+
+    pub fn to_array(&self) -> AIResult<Array> {
+        let mut value: AIArrayRef = null_mut();
+
+        unsafe {
+            self.suite.ToArray.call((self.retained()?, &mut value as *mut _))?;
+            Array::from_raw(value)?.ok_or(AIError::CantHappen)
+        }
+    }
+
+    /// 辞書に格納されたアート（ドキュメントのアートツリーには含まれない）
+    pub fn to_art(&self) -> AIResult<Art> {
+        let mut art: AIArtHandle = null_mut();
+
+        unsafe {
+            self.suite.ToArt.call((self.retained()?, &mut art as *mut _))?;
+            Art::from_raw(art).ok_or(AIError::CantHappen)
+        }
+    }
+}
+
+
+impl Drop for Entry {
+    fn drop(&mut self) {
+        if let Some(release) = self.suite.Release {
+            unsafe { release(self.raw) };
+        }
+    }
+}
+
+impl fmt::Debug for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Entry")
+            .field("raw", &self.raw)
+            .field("type", &self.entry_type().ok())
+            .finish()
+    }
+}
+
+/// `kNoSuchKey` を `None` にする
+fn optional<T>(result: AIResult<T>) -> AIResult<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(AIError::NoSuchKey) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// アートやドキュメントにデータを保存する辞書（`AIDictionaryRef`）
+///
+/// 参照カウントを持ち、`try_clone` で `AddRef`、破棄時に `Release` する。
+/// キーは ASCII の文字列で、`-` で始まるキーは一時的なものとしてファイルに保存されない。
+pub struct Dictionary {
+    raw: AIDictionaryRef,
+    suite: SuiteGuard<AIDictionarySuite>,
+}
+
+impl Dictionary {
+    /// 空の辞書を作る
+    pub fn new() -> AIResult<Self> {
+        let suite = SuiteGuard::<AIDictionarySuite>::acquire()?;
+        let mut raw: AIDictionaryRef = null_mut();
+
+        unsafe { suite.CreateDictionary.call((&mut raw as *mut _,))? };
+        if raw.is_null() {
+            return Err(AIError::CantHappen);
+        }
+        Ok(Self { raw, suite })
+    }
+
+    /// 参照を一つ引き取る
+    ///
+    /// # Safety
+    /// `raw` は `null` か、呼び出し元が所有する参照でなければならない。
+    pub unsafe fn from_raw(raw: AIDictionaryRef) -> AIResult<Option<Self>> {
+        if raw.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            raw,
+            suite: SuiteGuard::acquire()?,
+        }))
+    }
+
+    /// ホストが所有する辞書を借りる（参照を一つ増やす）
+    ///
+    /// # Safety
+    /// `raw` は `null` か、有効な辞書でなければならない。
+    pub unsafe fn from_borrowed(raw: AIDictionaryRef) -> AIResult<Option<Self>> {
+        if raw.is_null() {
+            return Ok(None);
+        }
+
+        // 参照を増やせないうちに包むと、破棄時に借りた参照を解放してしまう
+        let suite = SuiteGuard::<AIDictionarySuite>::acquire()?;
+        let add_ref = suite.AddRef.ok_or(AIError::NotImplemented)?;
+        add_ref(raw);
+        Ok(Some(Self { raw, suite }))
+    }
+
+    pub fn as_raw(&self) -> AIDictionaryRef {
+        self.raw
+    }
+
+    /// 同じ値への参照を一つ増やす
+    pub fn try_clone(&self) -> AIResult<Self> {
+        let suite = self.suite.try_clone()?;
+        let add_ref = suite.AddRef.ok_or(AIError::NotImplemented)?;
+        unsafe { add_ref(self.raw) };
+        Ok(Self { raw: self.raw, suite })
+    }
+
+    /// 中身を複製した別の辞書を作る（`try_clone` は同じ辞書への参照を増やすだけ）
+    pub fn deep_clone(&self) -> AIResult<Dictionary> {
+        let mut raw: AIDictionaryRef = null_mut();
+
+        unsafe {
+            self.suite.Clone.call((self.raw as ConstAIDictionaryRef, &mut raw as *mut _))?;
+            Dictionary::from_raw(raw)?.ok_or(AIError::CantHappen)
+        }
+    }
+
+    fn key(&self, key: &str) -> AIResult<AIDictKey> {
+        let make_key = self.suite.Key.ok_or(AIError::NotImplemented)?;
+        let key = CString::new(key).map_err(|_| AIError::BadParameter)?;
+
+        let key = unsafe { make_key(key.as_ptr()) };
+        if key.is_null() {
+            return Err(AIError::BadParameter);
+        }
+        Ok(key)
+    }
+
+    pub fn len(&self) -> AIResult<usize> {
+        let size = self.suite.Size.ok_or(AIError::NotImplemented)?;
+        Ok(unsafe { size(self.raw) } as usize)
+    }
+
+    pub fn is_empty(&self) -> AIResult<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    pub fn contains(&self, key: &str) -> AIResult<bool> {
+        let is_known = self.suite.IsKnown.ok_or(AIError::NotImplemented)?;
+        let key = self.key(key)?;
+        Ok(unsafe { is_known(self.raw, key) } != 0)
+    }
+
+    /// エントリを削除する（なければ何もしない）
+    pub fn remove(&mut self, key: &str) -> AIResult<()> {
+        let key = self.key(key)?;
+        optional(unsafe { self.suite.DeleteEntry.call((self.raw, key)) }).map(|_| ())
+    }
+
+    pub fn entry_type(&self, key: &str) -> AIResult<Option<EntryType>> {
+        let key = self.key(key)?;
+        let mut entry_type: AIEntryType = 0;
+
+        optional(unsafe { self.suite.GetEntryType.call((self.raw, key, &mut entry_type as *mut _)) })
+            .map(|found| found.map(|()| EntryType::from_raw(entry_type)))
+    }
+
+    pub fn get(&self, key: &str) -> AIResult<Option<Entry>> {
+        let get = self.suite.Get.ok_or(AIError::NotImplemented)?;
+        let key = self.key(key)?;
+        unsafe { Entry::from_raw(get(self.raw, key)) }
+    }
+
+    pub fn set(&mut self, key: &str, entry: &Entry) -> AIResult<()> {
+        let key = self.key(key)?;
+        unsafe { self.suite.Set.call((self.raw, key, entry.as_raw())) }
+    }
+
+    pub fn get_bool(&self, key: &str) -> AIResult<Option<bool>> {
+        let key = self.key(key)?;
+        let mut value: AIBoolean = 0;
+
+        optional(unsafe { self.suite.GetBooleanEntry.call((self.raw, key, &mut value as *mut _)) })
+            .map(|found| found.map(|()| value != 0))
+    }
+
+    pub fn set_bool(&mut self, key: &str, value: bool) -> AIResult<()> {
+        let key = self.key(key)?;
+        unsafe { self.suite.SetBooleanEntry.call((self.raw, key, value as AIBoolean)) }
+    }
+
+    pub fn get_int(&self, key: &str) -> AIResult<Option<i32>> {
+        let key = self.key(key)?;
+        let mut value: ai_int32 = 0;
+
+        optional(unsafe { self.suite.GetIntegerEntry.call((self.raw, key, &mut value as *mut _)) })
+            .map(|found| found.map(|()| value))
+    }
+
+    pub fn set_int(&mut self, key: &str, value: i32) -> AIResult<()> {
+        let key = self.key(key)?;
+        unsafe { self.suite.SetIntegerEntry.call((self.raw, key, value)) }
+    }
+
+    pub fn get_real(&self, key: &str) -> AIResult<Option<f64>> {
+        let key = self.key(key)?;
+        let mut value: AIReal = 0.0;
+
+        optional(unsafe { self.suite.GetRealEntry.call((self.raw, key, &mut value as *mut _)) })
+            .map(|found| found.map(|()| value))
+    }
+
+    pub fn set_real(&mut self, key: &str, value: f64) -> AIResult<()> {
+        let key = self.key(key)?;
+        unsafe { self.suite.SetRealEntry.call((self.raw, key, value)) }
+    }
+
+    /// プラットフォームの文字コードの C 文字列
+    pub fn get_c_string(&self, key: &str) -> AIResult<Option<CString>> {
+        let key = self.key(key)?;
+        let mut value: *const c_char = null();
+
+        let found = optional(unsafe { self.suite.GetStringEntry.call((self.raw, key, &mut value as *mut _)) })?;
+        if found.is_none() || value.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(unsafe { CStr::from_ptr(value) }.to_owned()))
+    }
+
+    pub fn set_c_string(&mut self, key: &str, value: &CStr) -> AIResult<()> {
+        let key = self.key(key)?;
+        unsafe { self.suite.SetStringEntry.call((self.raw, key, value.as_ptr())) }
+    }
+
+    pub fn get_unicode(&self, key: &str) -> AIResult<Option<String>> {
+        let key = self.key(key)?;
+        let mut value = UnicodeString::empty()?;
+
+        optional(unsafe { self.suite.GetUnicodeStringEntry.call((self.raw, key, value.as_mut_ptr())) })
+            .map(|found| found.map(|()| value.to_string_lossy()))
+    }
+
+    pub fn set_unicode(&mut self, key: &str, value: &str) -> AIResult<()> {
+        let key = self.key(key)?;
+        let value = UnicodeString::new(value)?;
+        unsafe { self.suite.SetUnicodeStringEntry.call((self.raw, key, value.as_ptr())) }
+    }
+
+    pub fn get_binary(&self, key: &str) -> AIResult<Option<Vec<u8>>> {
+        self.get(key)?.map(|entry| entry.to_binary()).transpose()
+    }
+
+    pub fn set_binary(&mut self, key: &str, value: &[u8]) -> AIResult<()> {
+        let key = self.key(key)?;
+        unsafe {
+            self.suite
+                .SetBinaryEntry
+                .call((self.raw, key, value.as_ptr() as *mut c_void, value.len()))
+        }
+    }
+
+    pub fn get_dictionary(&self, key: &str) -> AIResult<Option<Dictionary>> {
+        let key = self.key(key)?;
+        let mut value: AIDictionaryRef = null_mut();
+
+        match optional(unsafe { self.suite.GetDictEntry.call((self.raw, key, &mut value as *mut _)) })? {
+            Some(()) => unsafe { Dictionary::from_raw(value) },
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_dictionary(&mut self, key: &str, value: &Dictionary) -> AIResult<()> {
+        let key = self.key(key)?;
+        unsafe { self.suite.SetDictEntry.call((self.raw, key, value.raw)) }
+    }
+
+    pub fn get_array(&self, key: &str) -> AIResult<Option<Array>> {
+        let key = self.key(key)?;
+        let mut value: AIArrayRef = null_mut();
+
+        match optional(unsafe { self.suite.GetArrayEntry.call((self.raw, key, &mut value as *mut _)) })? {
+            Some(()) => unsafe { Array::from_raw(value) },
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_array(&mut self, key: &str, value: &Array) -> AIResult<()> {
+        let key = self.key(key)?;
+        unsafe { self.suite.SetArrayEntry.call((self.raw, key, value.raw)) }
+    }
+
+    /// 格納されたアート（ドキュメントのアートツリーには含まれない）
+    pub fn get_art(&self, key: &str) -> AIResult<Option<Art>> {
+        let key = self.key(key)?;
+        let mut art: AIArtHandle = null_mut();
+
+        let found = optional(unsafe { self.suite.GetArtEntry.call((self.raw, key, &mut art as *mut _)) })?;
+        Ok(found.and_then(|()| unsafe { Art::from_raw(art) }))
+    }
+
+    /// アートをアートツリーから取り外して格納する
+    pub fn move_art_in(&mut self, key: &str, art: Art) -> AIResult<()> {
+        let key = self.key(key)?;
+        unsafe { self.suite.MoveArtToEntry.call((self.raw, key, art.as_raw())) }
+    }
+
+    /// アートの複製を格納する
+    pub fn copy_art_in(&mut self, key: &str, art: Art) -> AIResult<()> {
+        let key = self.key(key)?;
+        unsafe { self.suite.CopyArtToEntry.call((self.raw, key, art.as_raw())) }
+    }
+
+    /// 格納されたアートをアートツリーに戻す（エントリは削除される）
+    pub fn move_art_out(&mut self, key: &str, paint_order: PaintOrder, prep: Option<Art>) -> AIResult<Art> {
+        let key = self.key(key)?;
+        let mut art: AIArtHandle = null_mut();
+
+        unsafe {
+            self.suite.MoveEntryToArt.call((
+                self.raw,
+                key,
+                paint_order.to_raw(),
+                prep.map_or(null_mut(), Art::as_raw),
+                &mut art as *mut _,
+            ))?;
+            Art::from_raw(art).ok_or(AIError::CantHappen)
+        }
+    }
+
+    /// 格納されたアートの複製をアートツリーに置く
+    pub fn copy_art_out(&self, key: &str, paint_order: PaintOrder, prep: Option<Art>) -> AIResult<Art> {
+        let key = self.key(key)?;
+        let mut art: AIArtHandle = null_mut();
+
+        unsafe {
+            self.suite.CopyEntryToArt.call((
+                self.raw as ConstAIDictionaryRef,
+                key,
+                paint_order.to_raw(),
+                prep.map_or(null_mut(), Art::as_raw),
+                &mut art as *mut _,
+            ))?;
+            Art::from_raw(art).ok_or(AIError::CantHappen)
+        }
+    }
+
+    /// キーとエントリを列挙する（順序はホストが決める）
+    pub fn iter(&self) -> AIResult<DictionaryIter> {
+        let suite = SuiteGuard::<AIDictionaryIteratorSuite>::acquire()?;
+        let mut iterator: AIDictionaryIterator = null_mut();
+
+        unsafe { self.suite.Begin.call((self.raw as ConstAIDictionaryRef, &mut iterator as *mut _))? };
+        if iterator.is_null() {
+            return Err(AIError::CantHappen);
+        }
+        Ok(DictionaryIter {
+            iterator,
+            dictionary: self.suite.try_clone()?,
+            suite,
+            done: false,
+        })
+    }
+
+    /// 値をシリアライズして `key` に格納する
+    ///
+    /// 構造体とマップは辞書、シーケンスとタプルは配列、文字列は Unicode 文字列になる。
+    /// `None` のフィールドは格納されない。
+    pub fn store<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), Error> {
+        match ser::to_optional_entry(value)? {
+            Some(entry) => self.set(key, &entry)?,
+            None => self.remove(key)?,
+        }
+        Ok(())
+    }
+
+    /// `key` に格納された値をデシリアライズする（なければ `None`）
+    pub fn load<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, Error> {
+        match self.get(key)? {
+            Some(entry) => from_entry(&entry).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// 構造体やマップをシリアライズし、そのキーをこの辞書に直接格納する
+    ///
+    /// `value` にないキーはそのまま残る。
+    pub fn store_fields<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let entry = to_entry(value)?;
+        if entry.entry_type()? != EntryType::Dictionary {
+            return Err(Error::Message("only structs and maps can be stored as fields".into()));
+        }
+        for item in entry.to_dictionary()?.iter()? {
+            let (key, entry) = item?;
+            self.set(&key, &entry)?;
+        }
+        Ok(())
+    }
+
+    /// この辞書全体を構造体やマップとしてデシリアライズする
+    pub fn load_fields<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        from_entry(&Entry::from_dictionary(self)?)
+    }
+}
+
+
+impl Drop for Dictionary {
+    fn drop(&mut self) {
+        if let Some(release) = self.suite.Release {
+            unsafe { release(self.raw) };
+        }
+    }
+}
+
+impl fmt::Debug for Dictionary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dictionary").field("raw", &self.raw).finish()
+    }
+}
+
+/// 辞書のキーとエントリをたどるイテレータ
+///
+/// エラーが起きた場合はそれを一度返して終了する。
+pub struct DictionaryIter {
+    iterator: AIDictionaryIterator,
+    dictionary: SuiteGuard<AIDictionarySuite>,
+    suite: SuiteGuard<AIDictionaryIteratorSuite>,
+    done: bool,
+}
+
+impl DictionaryIter {
+    fn current(&self) -> AIResult<(String, Entry)> {
+        let get_key = self.suite.GetKey.ok_or(AIError::NotImplemented)?;
+        let get_entry = self.suite.GetEntry.ok_or(AIError::NotImplemented)?;
+        let key_string = self.dictionary.GetKeyString.ok_or(AIError::NotImplemented)?;
+
+        unsafe {
+            let name = read_c_string(key_string(get_key(self.iterator)))?;
+            let entry = Entry::from_raw(get_entry(self.iterator))?.ok_or(AIError::CantHappen)?;
+            Ok((name, entry))
+        }
+    }
+}
+
+impl Iterator for DictionaryIter {
+    type Item = AIResult<(String, Entry)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let (Some(at_end), Some(next)) = (self.suite.AtEnd, self.suite.Next) else {
+            self.done = true;
+            return Some(Err(AIError::NotImplemented));
+        };
+        if unsafe { at_end(self.iterator) } != 0 {
+            self.done = true;
+            return None;
+        }
+
+        let current = self.current();
+        if current.is_err() {
+            self.done = true;
+        }
+        unsafe { next(self.iterator) };
+        Some(current)
+    }
+}
+
+impl Drop for DictionaryIter {
+    fn drop(&mut self) {
+        if let Some(release) = self.suite.Release {
+            unsafe { release(self.iterator) };
+        }
+    }
+}
+
+/// 辞書に格納する配列（`AIArrayRef`）
+///
+/// 参照カウントを持ち、`try_clone` で `AddRef`、破棄時に `Release` する。
+pub struct Array {
+    raw: AIArrayRef,
+    suite: SuiteGuard<AIArraySuite>,
+}
+
+impl Array {
+    /// 空の配列を作る
+    pub fn new() -> AIResult<Self> {
+        let suite = SuiteGuard::<AIArraySuite>::acquire()?;
+        let mut raw: AIArrayRef = null_mut();
+
+        unsafe { suite.CreateArray.call((&mut raw as *mut _,))? };
+        if raw.is_null() {
+            return Err(AIError::CantHappen);
+        }
+        Ok(Self { raw, suite })
+    }
+
+    /// 参照を一つ引き取る
+    ///
+    /// # Safety
+    /// `raw` は `null` か、呼び出し元が所有する参照でなければならない。
+    pub unsafe fn from_raw(raw: AIArrayRef) -> AIResult<Option<Self>> {
+        if raw.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            raw,
+            suite: SuiteGuard::acquire()?,
+        }))
+    }
+
+    pub fn as_raw(&self) -> AIArrayRef {
+        self.raw
+    }
+
+    /// 同じ値への参照を一つ増やす
+    pub fn try_clone(&self) -> AIResult<Self> {
+        let suite = self.suite.try_clone()?;
+        let add_ref = suite.AddRef.ok_or(AIError::NotImplemented)?;
+        unsafe { add_ref(self.raw) };
+        Ok(Self { raw: self.raw, suite })
+    }
+
+    pub fn len(&self) -> AIResult<usize> {
+        let size = self.suite.Size.ok_or(AIError::NotImplemented)?;
+        Ok(unsafe { size(self.raw) }.max(0) as usize)
+    }
+
+    pub fn is_empty(&self) -> AIResult<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    pub fn entry_type(&self, index: usize) -> AIResult<EntryType> {
+        let index = array_index(index)?;
+        let mut entry_type: AIEntryType = 0;
+
+        unsafe { self.suite.GetEntryType.call((self.raw, index, &mut entry_type as *mut _))? };
+        Ok(EntryType::from_raw(entry_type))
+    }
+
+    pub fn get(&self, index: usize) -> AIResult<Entry> {
+        let get = self.suite.Get.ok_or(AIError::NotImplemented)?;
+        let index = array_index(index)?;
+        unsafe { Entry::from_raw(get(self.raw, index))?.ok_or(AIError::BadParameter) }
+    }
+
+    pub fn set(&mut self, index: usize, entry: &Entry) -> AIResult<()> {
+        let index = array_index(index)?;
+        unsafe { self.suite.Set.call((self.raw, index, entry.as_raw())) }
+    }
+
+    /// 末尾に追加する
+    pub fn push(&mut self, entry: &Entry) -> AIResult<()> {
+        unsafe { self.suite.AppendEntry.call((self.raw, entry.as_raw())) }
+    }
+
+    pub fn remove(&mut self, index: usize) -> AIResult<()> {
+        let index = array_index(index)?;
+        unsafe { self.suite.DeleteEntry.call((self.raw, index)) }
+    }
+
+    /// エントリを先頭から列挙する
+    pub fn iter(&self) -> impl Iterator<Item = AIResult<Entry>> + '_ {
+        let len = self.len();
+        let (len, error) = match len {
+            Ok(len) => (len, None),
+            Err(error) => (0, Some(Err(error))),
+        };
+        error.into_iter().chain((0..len).map(|index| self.get(index)))
+    }
+}
+
+
+impl Drop for Array {
+    fn drop(&mut self) {
+        if let Some(release) = self.suite.Release {
+            unsafe { release(self.raw) };
+        }
+    }
+}
+
+impl fmt::Debug for Array {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Array").field("raw", &self.raw).finish()
+    }
+}
+
+fn array_index(index: usize) -> AIResult<ai_int32> {
+    ai_int32::try_from(index).map_err(|_| AIError::BadParameter)
+}
+
+/// シリアライズ・デシリアライズのエラー
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// スイート関数が返したエラー
+    Host(AIError),
+    /// 値を辞書で表せない、または格納された値が型に合わない
+    Message(String),
+}
+
+impl From<AIError> for Error {
+    fn from(error: AIError) -> Self {
+        Error::Host(error)
+    }
+}
+
+/// ホストへ返すときは、値が型に合わないエラーを `kBadParameterErr` として扱う
+impl From<Error> for AIError {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Host(error) => error,
+            Error::Message(_) => AIError::BadParameter,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Host(error) => fmt::Display::fmt(error, f),
+            Error::Message(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl serde::ser::Error for Error {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Error::Message(message.to_string())
+    }
+}
+
+impl serde::de::Error for Error {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Error::Message(message.to_string())
+    }
+}
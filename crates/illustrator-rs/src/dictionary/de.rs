@@ -0,0 +1,185 @@
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use super::{Entry, EntryType, Error};
+
+/// `Entry` から値をデシリアライズする
+///
+/// 整数は実数としても読め、C 文字列と Unicode 文字列はどちらも文字列として読める。
+pub fn from_entry<T: DeserializeOwned>(entry: &Entry) -> Result<T, Error> {
+    T::deserialize(EntryDeserializer(entry.try_clone()?))
+}
+
+struct EntryDeserializer(Entry);
+
+impl<'de> de::Deserializer<'de> for EntryDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let entry = self.0;
+
+        match entry.entry_type()? {
+            EntryType::Boolean => visitor.visit_bool(entry.to_bool()?),
+            EntryType::Integer => visitor.visit_i32(entry.to_int()?),
+            EntryType::Real => visitor.visit_f64(entry.to_real()?),
+            EntryType::String => visitor.visit_string(entry.to_c_string()?.to_string_lossy().into_owned()),
+            EntryType::UnicodeString => visitor.visit_string(entry.to_unicode()?),
+            EntryType::Binary => visitor.visit_byte_buf(entry.to_binary()?),
+            EntryType::Array => {
+                let array = entry.to_array()?;
+                let entries = array.iter().collect::<Result<Vec<_>, _>>()?;
+                visitor.visit_seq(SeqAccess(entries.into_iter()))
+            }
+            EntryType::Dictionary => {
+                let dictionary = entry.to_dictionary()?;
+                let entries = dictionary.iter()?.collect::<Result<Vec<_>, _>>()?;
+                visitor.visit_map(MapAccess {
+                    entries: entries.into_iter(),
+                    value: None,
+                })
+            }
+            other => Err(Error::Message(format!("entry type {:?} cannot be deserialized", other))),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        // 格納されていない値は `None`（辞書のキーが無い場合は serde が `None` にする）
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let entry = self.0;
+
+        match entry.entry_type()? {
+            EntryType::String => visitor.visit_enum(EnumAccess {
+                variant: entry.to_c_string()?.to_string_lossy().into_owned(),
+                value: None,
+            }),
+            EntryType::UnicodeString => visitor.visit_enum(EnumAccess {
+                variant: entry.to_unicode()?,
+                value: None,
+            }),
+            EntryType::Dictionary => {
+                let mut entries = entry.to_dictionary()?.iter()?;
+                let (variant, value) = match (entries.next(), entries.next()) {
+                    (Some(first), None) => first?,
+                    _ => return Err(Error::Message("an enum must be stored as a dictionary with one key".into())),
+                };
+                visitor.visit_enum(EnumAccess {
+                    variant,
+                    value: Some(value),
+                })
+            }
+            other => Err(Error::Message(format!("entry type {:?} cannot be deserialized as an enum", other))),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct SeqAccess(std::vec::IntoIter<Entry>);
+
+impl<'de> de::SeqAccess<'de> for SeqAccess {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        self.0
+            .next()
+            .map(|entry| seed.deserialize(EntryDeserializer(entry)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+struct MapAccess {
+    entries: std::vec::IntoIter<(String, Entry)>,
+    value: Option<Entry>,
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.value = Some(value);
+        seed.deserialize(key.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| Error::Message("next_value called before next_key".into()))?;
+        seed.deserialize(EntryDeserializer(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// バリアント名と、ユニットバリアント以外なら値
+struct EnumAccess {
+    variant: String,
+    value: Option<Entry>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess {
+    type Error = Error;
+    type Variant = VariantAccess;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, VariantAccess), Error> {
+        let variant = seed.deserialize(IntoDeserializer::<Error>::into_deserializer(self.variant))?;
+        Ok((variant, VariantAccess(self.value)))
+    }
+}
+
+struct VariantAccess(Option<Entry>);
+
+impl VariantAccess {
+    fn value(self) -> Result<EntryDeserializer, Error> {
+        self.0
+            .map(EntryDeserializer)
+            .ok_or_else(|| Error::Message("expected a variant with a value".into()))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for VariantAccess {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.0 {
+            None => Ok(()),
+            Some(_) => Err(Error::Message("expected a unit variant".into())),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self.value()?)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_seq(self.value()?, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_map(self.value()?, visitor)
+    }
+}
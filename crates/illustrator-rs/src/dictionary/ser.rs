@@ -0,0 +1,483 @@
+use serde::ser::{self, Impossible, Serialize};
+
+use super::{Array, Dictionary, Entry, Error};
+
+/// 値を `Entry` にシリアライズする
+///
+/// `None` とユニットは辞書で表せないため、単独ではエラーになる（構造体のフィールドなら省かれる）。
+pub fn to_entry<T: Serialize + ?Sized>(value: &T) -> Result<Entry, Error> {
+    to_optional_entry(value)?.ok_or_else(|| Error::Message("a unit or None value cannot be stored".into()))
+}
+
+/// `None` とユニットを `None` として返す
+pub(super) fn to_optional_entry<T: Serialize + ?Sized>(value: &T) -> Result<Option<Entry>, Error> {
+    value.serialize(EntrySerializer)
+}
+
+/// 列挙型のバリアントを `{ バリアント名: 値 }` の辞書で包む
+fn wrap_variant(variant: Option<&'static str>, entry: Entry) -> Result<Entry, Error> {
+    let Some(variant) = variant else {
+        return Ok(entry);
+    };
+    let mut dictionary = Dictionary::new()?;
+    dictionary.set(variant, &entry)?;
+    Ok(Entry::from_dictionary(&dictionary)?)
+}
+
+fn integer<T: TryInto<i32> + std::fmt::Display + Copy>(value: T) -> Result<Option<Entry>, Error> {
+    let integer = value
+        .try_into()
+        .map_err(|_| Error::Message(format!("integer {} does not fit in 32 bits", value)))?;
+    Ok(Some(Entry::from_int(integer)?))
+}
+
+struct EntrySerializer;
+
+impl ser::Serializer for EntrySerializer {
+    type Ok = Option<Entry>;
+    type Error = Error;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeArray;
+    type SerializeMap = SerializeDictionary;
+    type SerializeStruct = SerializeDictionary;
+    type SerializeStructVariant = SerializeDictionary;
+
+    fn serialize_bool(self, value: bool) -> Result<Self::Ok, Error> {
+        Ok(Some(Entry::from_bool(value)?))
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<Self::Ok, Error> {
+        integer(value)
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<Self::Ok, Error> {
+        integer(value)
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<Self::Ok, Error> {
+        integer(value)
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<Self::Ok, Error> {
+        integer(value)
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<Self::Ok, Error> {
+        integer(value)
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<Self::Ok, Error> {
+        integer(value)
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<Self::Ok, Error> {
+        integer(value)
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<Self::Ok, Error> {
+        integer(value)
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<Self::Ok, Error> {
+        Ok(Some(Entry::from_real(value as f64)?))
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<Self::Ok, Error> {
+        Ok(Some(Entry::from_real(value)?))
+    }
+
+    fn serialize_char(self, value: char) -> Result<Self::Ok, Error> {
+        Ok(Some(Entry::from_unicode(value.encode_utf8(&mut [0; 4]))?))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Self::Ok, Error> {
+        Ok(Some(Entry::from_unicode(value)?))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Error> {
+        Ok(Some(Entry::from_binary(value)?))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<Self::Ok, Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Error> {
+        wrap_variant(Some(variant), to_entry(value)?).map(Some)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        SerializeArray::new(None)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        SerializeArray::new(None)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, Error> {
+        SerializeArray::new(None)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        SerializeArray::new(Some(variant))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        SerializeDictionary::new(None)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, Error> {
+        SerializeDictionary::new(None)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        SerializeDictionary::new(Some(variant))
+    }
+}
+
+/// シーケンスとタプルを `Array` に詰める
+struct SerializeArray {
+    array: Array,
+    variant: Option<&'static str>,
+}
+
+impl SerializeArray {
+    fn new(variant: Option<&'static str>) -> Result<Self, Error> {
+        Ok(Self {
+            array: Array::new()?,
+            variant,
+        })
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        // 配列には欠けた要素を表せない
+        let entry = to_optional_entry(value)?
+            .ok_or_else(|| Error::Message("a unit or None element cannot be stored in an array".into()))?;
+        Ok(self.array.push(&entry)?)
+    }
+
+    fn finish(self) -> Result<Option<Entry>, Error> {
+        let entry = Entry::from_array(&self.array)?;
+        wrap_variant(self.variant, entry).map(Some)
+    }
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Option<Entry>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Option<Entry>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Option<Entry>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeArray {
+    type Ok = Option<Entry>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        self.finish()
+    }
+}
+
+/// 構造体とマップを `Dictionary` に詰める（値が `None` のキーは省く）
+struct SerializeDictionary {
+    dictionary: Dictionary,
+    key: Option<String>,
+    variant: Option<&'static str>,
+}
+
+impl SerializeDictionary {
+    fn new(variant: Option<&'static str>) -> Result<Self, Error> {
+        Ok(Self {
+            dictionary: Dictionary::new()?,
+            key: None,
+            variant,
+        })
+    }
+
+    fn insert<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), Error> {
+        if let Some(entry) = to_optional_entry(value)? {
+            self.dictionary.set(key, &entry)?;
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Option<Entry>, Error> {
+        let entry = Entry::from_dictionary(&self.dictionary)?;
+        wrap_variant(self.variant, entry).map(Some)
+    }
+}
+
+impl ser::SerializeMap for SerializeDictionary {
+    type Ok = Option<Entry>;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error::Message("serialize_value called before serialize_key".into()))?;
+        self.insert(&key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for SerializeDictionary {
+    type Ok = Option<Entry>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeDictionary {
+    type Ok = Option<Entry>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        self.finish()
+    }
+}
+
+/// マップのキーを辞書のキーにする（文字列・文字・整数・ユニットバリアントのみ）
+struct KeySerializer;
+
+fn key_error() -> Error {
+    Error::Message("dictionary keys must be strings or integers".into())
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<String, Error>;
+    type SerializeTuple = Impossible<String, Error>;
+    type SerializeTupleStruct = Impossible<String, Error>;
+    type SerializeTupleVariant = Impossible<String, Error>;
+    type SerializeMap = Impossible<String, Error>;
+    type SerializeStruct = Impossible<String, Error>;
+    type SerializeStructVariant = Impossible<String, Error>;
+
+    fn serialize_bool(self, _value: bool) -> Result<String, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<String, Error> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<String, Error> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<String, Error> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<String, Error> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<String, Error> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<String, Error> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<String, Error> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<String, Error> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_f32(self, _value: f32) -> Result<String, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_f64(self, _value: f64) -> Result<String, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_char(self, value: char) -> Result<String, Error> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_str(self, value: &str) -> Result<String, Error> {
+        Ok(value.to_owned())
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result<String, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_none(self) -> Result<String, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<String, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_unit(self) -> Result<String, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<String, Error> {
+        Ok(variant.to_owned())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<String, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(key_error())
+    }
+}
@@ -0,0 +1,707 @@
+use std::path::PathBuf;
+use std::ptr::null_mut;
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::dictionary::Dictionary;
+use crate::error::{AIError, AIResult, SuiteFn};
+use crate::file_path::FilePath;
+use crate::geometry::{Point, Rect};
+use crate::unicode::UnicodeString;
+use crate::util::flags;
+
+/// 定規の単位（`AIDocumentRulerUnitValue`）
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RulerUnits {
+    Inches,
+    Centimeters,
+    Points,
+    Picas,
+    Millimeters,
+    Pixels,
+    /// 級（0.25mm）
+    Q,
+    /// フィート・インチ（数値はインチとして扱う）
+    FeetInches,
+    Meters,
+    Yards,
+    Feet,
+    /// 未知の単位
+    Unknown(i16),
+}
+
+impl RulerUnits {
+    pub fn from_raw(raw: i16) -> Self {
+        match raw as AIDocumentRulerUnitValue {
+            AIDocumentRulerUnitValue_kInchesUnits => RulerUnits::Inches,
+            AIDocumentRulerUnitValue_kCentimetersUnits => RulerUnits::Centimeters,
+            AIDocumentRulerUnitValue_kPointsUnits => RulerUnits::Points,
+            AIDocumentRulerUnitValue_kPicasUnits => RulerUnits::Picas,
+            AIDocumentRulerUnitValue_kMillimetersUnits => RulerUnits::Millimeters,
+            AIDocumentRulerUnitValue_kPixelsUnits => RulerUnits::Pixels,
+            AIDocumentRulerUnitValue_kQUnits => RulerUnits::Q,
+            AIDocumentRulerUnitValue_kFeetInchesUnits => RulerUnits::FeetInches,
+            AIDocumentRulerUnitValue_kMetersUnits => RulerUnits::Meters,
+            AIDocumentRulerUnitValue_kYardsUnits => RulerUnits::Yards,
+            AIDocumentRulerUnitValue_kFeetsUnits => RulerUnits::Feet,
+            _ => RulerUnits::Unknown(raw),
+        }
+    }
+
+    pub fn to_raw(self) -> i16 {
+        let raw = match self {
+            RulerUnits::Inches => AIDocumentRulerUnitValue_kInchesUnits,
+            RulerUnits::Centimeters => AIDocumentRulerUnitValue_kCentimetersUnits,
+            RulerUnits::Points => AIDocumentRulerUnitValue_kPointsUnits,
+            RulerUnits::Picas => AIDocumentRulerUnitValue_kPicasUnits,
+            RulerUnits::Millimeters => AIDocumentRulerUnitValue_kMillimetersUnits,
+            RulerUnits::Pixels => AIDocumentRulerUnitValue_kPixelsUnits,
+            RulerUnits::Q => AIDocumentRulerUnitValue_kQUnits,
+            RulerUnits::FeetInches => AIDocumentRulerUnitValue_kFeetInchesUnits,
+            RulerUnits::Meters => AIDocumentRulerUnitValue_kMetersUnits,
+            RulerUnits::Yards => AIDocumentRulerUnitValue_kYardsUnits,
+            RulerUnits::Feet => AIDocumentRulerUnitValue_kFeetsUnits,
+            RulerUnits::Unknown(raw) => return raw,
+        };
+        raw as i16
+    }
+
+    /// 1単位あたりのポイント数（未知の単位はポイントとみなす）
+    pub fn points_per_unit(self) -> f64 {
+        const INCH: f64 = 72.0;
+        const MILLIMETER: f64 = INCH / 25.4;
+
+        match self {
+            RulerUnits::Inches | RulerUnits::FeetInches => INCH,
+            RulerUnits::Centimeters => MILLIMETER * 10.0,
+            RulerUnits::Points | RulerUnits::Pixels | RulerUnits::Unknown(_) => 1.0,
+            RulerUnits::Picas => 12.0,
+            RulerUnits::Millimeters => MILLIMETER,
+            RulerUnits::Q => MILLIMETER * 0.25,
+            RulerUnits::Meters => MILLIMETER * 1000.0,
+            RulerUnits::Yards => INCH * 36.0,
+            RulerUnits::Feet => INCH * 12.0,
+        }
+    }
+
+    /// この単位の値をポイントに変換する
+    pub fn to_points(self, value: f64) -> f64 {
+        value * self.points_per_unit()
+    }
+
+    /// ポイントをこの単位の値に変換する
+    pub fn from_points(self, points: f64) -> f64 {
+        points / self.points_per_unit()
+    }
+}
+
+/// ドキュメントのカラーモード（`AIDocumentColorModelValue`）
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColorModel {
+    Gray,
+    Rgb,
+    Cmyk,
+    /// 未知のカラーモード
+    Unknown(i16),
+}
+
+impl ColorModel {
+    pub fn from_raw(raw: i16) -> Self {
+        match raw as AIDocumentColorModelValue {
+            AIDocumentColorModelValue_kDocGrayColor => ColorModel::Gray,
+            AIDocumentColorModelValue_kDocRGBColor => ColorModel::Rgb,
+            AIDocumentColorModelValue_kDocCMYKColor => ColorModel::Cmyk,
+            _ => ColorModel::Unknown(raw),
+        }
+    }
+
+    pub fn to_raw(self) -> i16 {
+        let raw = match self {
+            ColorModel::Gray => AIDocumentColorModelValue_kDocGrayColor,
+            ColorModel::Rgb => AIDocumentColorModelValue_kDocRGBColor,
+            ColorModel::Cmyk => AIDocumentColorModelValue_kDocCMYKColor,
+            ColorModel::Unknown(raw) => return raw,
+        };
+        raw as i16
+    }
+}
+
+/// ドキュメント設定（`AIDocumentSetup`）
+///
+/// 寸法はポイント単位。
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DocumentSetup {
+    pub width: f64,
+    pub height: f64,
+    pub show_placed_images: bool,
+    pub output_resolution: f64,
+    pub split_long_paths: bool,
+    pub use_default_screen: bool,
+    pub compatible_gradients: bool,
+    pub print_tiles: bool,
+    pub tile_full_pages: bool,
+}
+
+impl From<AIDocumentSetup> for DocumentSetup {
+    fn from(setup: AIDocumentSetup) -> Self {
+        Self {
+            width: setup.width,
+            height: setup.height,
+            show_placed_images: setup.showPlacedImages != 0,
+            output_resolution: setup.outputResolution,
+            split_long_paths: setup.splitLongPaths != 0,
+            use_default_screen: setup.useDefaultScreen != 0,
+            compatible_gradients: setup.compatibleGradients != 0,
+            print_tiles: setup.printTiles != 0,
+            tile_full_pages: setup.tileFullPages != 0,
+        }
+    }
+}
+
+impl From<DocumentSetup> for AIDocumentSetup {
+    fn from(setup: DocumentSetup) -> Self {
+        AIDocumentSetup {
+            width: setup.width,
+            height: setup.height,
+            showPlacedImages: setup.show_placed_images as AIBoolean,
+            outputResolution: setup.output_resolution,
+            splitLongPaths: setup.split_long_paths as AIBoolean,
+            useDefaultScreen: setup.use_default_screen as AIBoolean,
+            compatibleGradients: setup.compatible_gradients as AIBoolean,
+            printTiles: setup.print_tiles as AIBoolean,
+            tileFullPages: setup.tile_full_pages as AIBoolean,
+        }
+    }
+}
+
+/// ドキュメント座標と定規の座標の変換
+///
+/// 定規の座標は原点からの距離を定規の単位で表し、画面表示と同じく y 軸が下向き。
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RulerCoordinates {
+    pub origin: Point,
+    pub units: RulerUnits,
+}
+
+impl RulerCoordinates {
+    /// ドキュメント座標（ポイント）を定規の座標にする
+    pub fn to_ruler(&self, point: Point) -> Point {
+        Point::new(
+            self.units.from_points(point.x - self.origin.x),
+            self.units.from_points(self.origin.y - point.y),
+        )
+    }
+
+    /// 定規の座標をドキュメント座標（ポイント）にする
+    pub fn from_ruler(&self, point: Point) -> Point {
+        Point::new(
+            self.origin.x + self.units.to_points(point.x),
+            self.origin.y - self.units.to_points(point.y),
+        )
+    }
+}
+
+/// 開いているドキュメント（`AIDocumentHandle`）
+///
+/// `AIDocumentSuite` の多くの関数はアクティブなドキュメントにしか作用しないため、
+/// ハンドルを取らない操作はアクティブでなければ `AIError::BadParameter` を返す。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Document(AIDocumentHandle);
+
+impl Document {
+    /// # Safety
+    /// `handle` は開いているドキュメントでなければならない。
+    pub unsafe fn from_raw(handle: AIDocumentHandle) -> Option<Self> {
+        (!handle.is_null()).then_some(Self(handle))
+    }
+
+    pub fn as_raw(self) -> AIDocumentHandle {
+        self.0
+    }
+
+    /// アクティブなドキュメント（開いていなければ `None`）
+    pub fn current() -> AIResult<Option<Document>> {
+        let suite = SuiteGuard::<AIDocumentSuite>::acquire()?;
+        let mut document: AIDocumentHandle = null_mut();
+
+        match unsafe { suite.GetDocument.call((&mut document as *mut _,)) } {
+            Ok(()) => Ok(unsafe { Document::from_raw(document) }),
+            Err(AIError::NoDocument) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn is_current(self) -> AIResult<bool> {
+        Ok(Document::current()? == Some(self))
+    }
+
+    /// まだ開かれているか
+    pub fn exists(self) -> AIResult<bool> {
+        let suite = SuiteGuard::<AIDocumentSuite>::acquire()?;
+        let mut exists: AIBoolean = 0;
+
+        unsafe { suite.DocumentExists.call((self.0, &mut exists as *mut _))? };
+        Ok(exists != 0)
+    }
+
+    /// 保存先のファイル（一度も保存されていなければ `None`）
+    pub fn file_path(self) -> AIResult<Option<PathBuf>> {
+        let suite = SuiteGuard::<AIDocumentSuite>::acquire()?;
+        let mut file = FilePath::empty()?;
+
+        unsafe { suite.GetDocumentFileSpecificationFromHandle.call((self.0, file.as_mut_ptr()))? };
+        if file.is_empty() {
+            return Ok(None);
+        }
+        file.to_path_buf().map(Some)
+    }
+
+    /// ウィンドウのタイトルに表示されるファイル名
+    pub fn file_name(self) -> AIResult<String> {
+        let suite = SuiteGuard::<AIDocumentSuite>::acquire()?;
+        let mut name = UnicodeString::empty()?;
+
+        unsafe { suite.GetDocumentFileNameFromHandle.call((self.0, name.as_mut_ptr()))? };
+        Ok(name.to_string_lossy())
+    }
+
+    /// 未保存の変更があるか
+    pub fn is_modified(self) -> AIResult<bool> {
+        let suite = self.current_suite()?;
+        let mut modified: AIBoolean = 0;
+
+        unsafe { suite.GetDocumentModified.call((&mut modified as *mut _,))? };
+        Ok(modified != 0)
+    }
+
+    pub fn set_modified(self, modified: bool) -> AIResult<()> {
+        let suite = self.current_suite()?;
+        unsafe { suite.SetDocumentModified.call((modified as AIBoolean,)) }
+    }
+
+    pub fn color_model(self) -> AIResult<ColorModel> {
+        let suite = self.current_suite()?;
+        let mut color_model: ai_int16 = 0;
+
+        unsafe { suite.GetDocumentColorModel.call((&mut color_model as *mut _,))? };
+        Ok(ColorModel::from_raw(color_model))
+    }
+
+    pub fn set_color_model(self, color_model: ColorModel) -> AIResult<()> {
+        let suite = self.current_suite()?;
+        unsafe { suite.SetDocumentColorModel.call((color_model.to_raw(),)) }
+    }
+
+    pub fn ruler_units(self) -> AIResult<RulerUnits> {
+        let suite = self.current_suite()?;
+        let mut units: ai_int16 = 0;
+
+        unsafe { suite.GetDocumentRulerUnits.call((&mut units as *mut _,))? };
+        Ok(RulerUnits::from_raw(units))
+    }
+
+    pub fn set_ruler_units(self, units: RulerUnits) -> AIResult<()> {
+        let suite = self.current_suite()?;
+        unsafe { suite.SetDocumentRulerUnits.call((units.to_raw(),)) }
+    }
+
+    /// 定規の原点（ドキュメント座標）
+    pub fn ruler_origin(self) -> AIResult<Point> {
+        let suite = self.current_suite()?;
+        let mut origin = AIRealPoint { h: 0.0, v: 0.0 };
+
+        unsafe { suite.GetDocumentRulerOrigin.call((&mut origin as *mut _,))? };
+        Ok(origin.into())
+    }
+
+    pub fn set_ruler_origin(self, origin: Point) -> AIResult<()> {
+        let suite = self.current_suite()?;
+        let mut origin: AIRealPoint = origin.into();
+        unsafe { suite.SetDocumentRulerOrigin.call((&mut origin as *mut _,)) }
+    }
+
+    /// 現在の定規の原点と単位による座標変換
+    pub fn ruler_coordinates(self) -> AIResult<RulerCoordinates> {
+        Ok(RulerCoordinates {
+            origin: self.ruler_origin()?,
+            units: self.ruler_units()?,
+        })
+    }
+
+    pub fn setup(self) -> AIResult<DocumentSetup> {
+        let suite = self.current_suite()?;
+        let mut setup: AIDocumentSetup = DocumentSetup::default().into();
+
+        unsafe { suite.GetDocumentSetup.call((&mut setup as *mut _,))? };
+        Ok(setup.into())
+    }
+
+    pub fn set_setup(self, setup: DocumentSetup) -> AIResult<()> {
+        let suite = self.current_suite()?;
+        let mut setup: AIDocumentSetup = setup.into();
+        unsafe { suite.SetDocumentSetup.call((&mut setup as *mut _,)) }
+    }
+
+    /// 変更をすぐに画面へ反映する
+    pub fn redraw(self) -> AIResult<()> {
+        let suite = self.current_suite()?;
+        unsafe { suite.RedrawDocument.call(()) }
+    }
+
+    /// ドキュメントの辞書（保存され、取り消しの対象になる）
+    pub fn dictionary(self) -> AIResult<Dictionary> {
+        self.dictionary_with(|suite| suite.GetDictionary)
+    }
+
+    /// 取り消しの対象にならない辞書（ファイルには保存される）
+    pub fn non_recorded_dictionary(self) -> AIResult<Dictionary> {
+        self.dictionary_with(|suite| suite.GetNonRecordedDictionary)
+    }
+
+    fn dictionary_with(
+        self,
+        function: impl FnOnce(&AIDocumentSuite) -> Option<unsafe extern "C" fn(*mut AIDictionaryRef) -> AIErr>,
+    ) -> AIResult<Dictionary> {
+        let suite = self.current_suite()?;
+        let mut dictionary: AIDictionaryRef = null_mut();
+
+        unsafe {
+            function(&suite).call((&mut dictionary as *mut _,))?;
+            Dictionary::from_raw(dictionary)?.ok_or(AIError::CantHappen)
+        }
+    }
+
+    /// 変更検出用に現在の状態を記録する
+    pub fn snapshot(self) -> AIResult<DocumentSnapshot> {
+        Ok(DocumentSnapshot {
+            document: self,
+            file_path: self.file_path()?,
+            modified: self.is_modified()?,
+            color_model: self.color_model()?,
+            ruler_units: self.ruler_units()?,
+            ruler_origin: self.ruler_origin()?,
+            setup: self.setup()?,
+        })
+    }
+
+    /// アクティブなドキュメントのときだけスイートを返す
+    fn current_suite(self) -> AIResult<SuiteGuard<AIDocumentSuite>> {
+        if !self.is_current()? {
+            return Err(AIError::BadParameter);
+        }
+        SuiteGuard::acquire()
+    }
+}
+
+/// ドキュメントウィンドウ（`AIDocumentViewHandle`）
+///
+/// `DocumentView::current()` は `null` ハンドルで、SDK はそれをアクティブなビューとして扱う。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DocumentView(AIDocumentViewHandle);
+
+impl DocumentView {
+    /// アクティブなドキュメントのアクティブなビュー
+    pub fn current() -> Self {
+        Self(null_mut())
+    }
+
+    /// # Safety
+    /// `handle` は `null` か、開いているビューでなければならない。
+    pub unsafe fn from_raw(handle: AIDocumentViewHandle) -> Self {
+        Self(handle)
+    }
+
+    pub fn as_raw(self) -> AIDocumentViewHandle {
+        self.0
+    }
+
+    /// アクティブなドキュメントのビューの数
+    pub fn count() -> AIResult<usize> {
+        let suite = SuiteGuard::<AIDocumentViewSuite>::acquire()?;
+        let mut count: ai_int32 = 0;
+
+        unsafe { suite.CountDocumentViews.call((&mut count as *mut _,))? };
+        Ok(count.max(0) as usize)
+    }
+
+    /// アクティブなドキュメントの `index` 番目のビュー
+    pub fn nth(index: usize) -> AIResult<DocumentView> {
+        let suite = SuiteGuard::<AIDocumentViewSuite>::acquire()?;
+        let index = ai_int32::try_from(index).map_err(|_| AIError::BadParameter)?;
+        let mut view: AIDocumentViewHandle = null_mut();
+
+        unsafe { suite.GetNthDocumentView.call((index, &mut view as *mut _))? };
+        Ok(DocumentView(view))
+    }
+
+    /// アクティブなドキュメントのすべてのビュー
+    pub fn all() -> AIResult<Vec<DocumentView>> {
+        (0..DocumentView::count()?).map(DocumentView::nth).collect()
+    }
+
+    /// ビューが表示しているドキュメント
+    pub fn document(self) -> AIResult<Document> {
+        let suite = SuiteGuard::<AIDocumentViewSuite>::acquire()?;
+        let mut document: AIDocumentHandle = null_mut();
+
+        unsafe {
+            suite.GetDocumentViewDocument.call((self.0, &mut document as *mut _))?;
+            Document::from_raw(document).ok_or(AIError::NoDocument)
+        }
+    }
+
+    /// 表示されている範囲（ドキュメント座標）
+    pub fn bounds(self) -> AIResult<Rect> {
+        let suite = SuiteGuard::<AIDocumentViewSuite>::acquire()?;
+        let mut bounds = AIRealRect { left: 0.0, top: 0.0, right: 0.0, bottom: 0.0 };
+
+        unsafe { suite.GetDocumentViewBounds.call((self.0, &mut bounds as *mut _))? };
+        Ok(bounds.into())
+    }
+
+    /// 表示の中心（ドキュメント座標）
+    pub fn center(self) -> AIResult<Point> {
+        let suite = SuiteGuard::<AIDocumentViewSuite>::acquire()?;
+        let mut center = AIRealPoint { h: 0.0, v: 0.0 };
+
+        unsafe { suite.GetDocumentViewCenter.call((self.0, &mut center as *mut _))? };
+        Ok(center.into())
+    }
+
+    pub fn set_center(self, center: Point) -> AIResult<()> {
+        let suite = SuiteGuard::<AIDocumentViewSuite>::acquire()?;
+        let center: AIRealPoint = center.into();
+        unsafe { suite.SetDocumentViewCenter.call((self.0, &center as *const _)) }
+    }
+
+    /// 表示倍率（1.0 が 100%）
+    pub fn zoom(self) -> AIResult<f64> {
+        let suite = SuiteGuard::<AIDocumentViewSuite>::acquire()?;
+        let mut zoom: AIReal = 0.0;
+
+        unsafe { suite.GetDocumentViewZoom.call((self.0, &mut zoom as *mut _))? };
+        Ok(zoom)
+    }
+
+    pub fn set_zoom(self, zoom: f64) -> AIResult<()> {
+        let suite = SuiteGuard::<AIDocumentViewSuite>::acquire()?;
+        unsafe { suite.SetDocumentViewZoom.call((self.0, zoom)) }
+    }
+
+    /// ドキュメント座標をビュー（ウィンドウ内のピクセル）座標にする
+    pub fn artwork_to_view(self, point: Point) -> AIResult<Point> {
+        let suite = SuiteGuard::<AIDocumentViewSuite>::acquire()?;
+        let artwork: AIRealPoint = point.into();
+        let mut view = AIRealPoint { h: 0.0, v: 0.0 };
+
+        unsafe {
+            suite
+                .FixedArtworkPointToViewPoint
+                .call((self.0, &artwork as *const _, &mut view as *mut _))?;
+        }
+        Ok(view.into())
+    }
+
+    /// ビュー座標をドキュメント座標にする
+    pub fn view_to_artwork(self, point: Point) -> AIResult<Point> {
+        let suite = SuiteGuard::<AIDocumentViewSuite>::acquire()?;
+        let view: AIRealPoint = point.into();
+        let mut artwork = AIRealPoint { h: 0.0, v: 0.0 };
+
+        unsafe {
+            suite
+                .FixedViewPointToArtworkPoint
+                .call((self.0, &view as *const _, &mut artwork as *mut _))?;
+        }
+        Ok(artwork.into())
+    }
+
+    /// ドキュメント座標の矩形をビュー座標にする（ビュー座標は y 軸が下向き）
+    pub fn artwork_rect_to_view(self, rect: Rect) -> AIResult<Rect> {
+        let a = self.artwork_to_view(Point::new(rect.left, rect.top))?;
+        let b = self.artwork_to_view(Point::new(rect.right, rect.bottom))?;
+        Ok(Rect::new(a.x.min(b.x), a.y.min(b.y), a.x.max(b.x), a.y.max(b.y)))
+    }
+
+    /// ドキュメント上の長さ（`units`）が画面上で何ピクセルになるか
+    pub fn length_to_view(self, length: f64, units: RulerUnits) -> AIResult<f64> {
+        Ok(units.to_points(length) * self.zoom()?)
+    }
+
+    /// 画面上のピクセル数をドキュメント上の長さ（`units`）にする
+    pub fn length_from_view(self, pixels: f64, units: RulerUnits) -> AIResult<f64> {
+        Ok(units.from_points(pixels / self.zoom()?))
+    }
+
+    /// 変更検出用に現在の状態を記録する
+    pub fn snapshot(self) -> AIResult<ViewSnapshot> {
+        Ok(ViewSnapshot {
+            zoom: self.zoom()?,
+            center: self.center()?,
+            bounds: self.bounds()?,
+        })
+    }
+}
+
+flags! {
+    /// ドキュメントやビューで変化した項目
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct DocumentChanges(pub u32) {
+        /// アクティブなドキュメントが切り替わった（開く・閉じるを含む）
+        const DOCUMENT = 1 << 0;
+        const FILE_PATH = 1 << 1;
+        const MODIFIED = 1 << 2;
+        const COLOR_MODEL = 1 << 3;
+        const RULER_UNITS = 1 << 4;
+        const RULER_ORIGIN = 1 << 5;
+        const SETUP = 1 << 6;
+        const ZOOM = 1 << 7;
+        const CENTER = 1 << 8;
+        const VIEW_BOUNDS = 1 << 9;
+    }
+}
+
+/// ある時点のドキュメントの状態
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocumentSnapshot {
+    pub document: Document,
+    pub file_path: Option<PathBuf>,
+    pub modified: bool,
+    pub color_model: ColorModel,
+    pub ruler_units: RulerUnits,
+    pub ruler_origin: Point,
+    pub setup: DocumentSetup,
+}
+
+impl DocumentSnapshot {
+    /// `previous` から変化した項目
+    pub fn changes_since(&self, previous: &DocumentSnapshot) -> DocumentChanges {
+        if self.document != previous.document {
+            return DocumentChanges::DOCUMENT
+                | DocumentChanges::FILE_PATH
+                | DocumentChanges::MODIFIED
+                | DocumentChanges::COLOR_MODEL
+                | DocumentChanges::RULER_UNITS
+                | DocumentChanges::RULER_ORIGIN
+                | DocumentChanges::SETUP;
+        }
+
+        let mut changes = DocumentChanges::empty();
+        let mut mark = |changed: bool, flag: DocumentChanges| {
+            if changed {
+                changes |= flag;
+            }
+        };
+
+        mark(self.file_path != previous.file_path, DocumentChanges::FILE_PATH);
+        mark(self.modified != previous.modified, DocumentChanges::MODIFIED);
+        mark(self.color_model != previous.color_model, DocumentChanges::COLOR_MODEL);
+        mark(self.ruler_units != previous.ruler_units, DocumentChanges::RULER_UNITS);
+        mark(self.ruler_origin != previous.ruler_origin, DocumentChanges::RULER_ORIGIN);
+        mark(self.setup != previous.setup, DocumentChanges::SETUP);
+        changes
+    }
+}
+
+/// ある時点のビューの状態
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewSnapshot {
+    pub zoom: f64,
+    pub center: Point,
+    pub bounds: Rect,
+}
+
+impl ViewSnapshot {
+    /// `previous` から変化した項目
+    pub fn changes_since(&self, previous: &ViewSnapshot) -> DocumentChanges {
+        let mut changes = DocumentChanges::empty();
+
+        if self.zoom != previous.zoom {
+            changes |= DocumentChanges::ZOOM;
+        }
+        if self.center != previous.center {
+            changes |= DocumentChanges::CENTER;
+        }
+        if self.bounds != previous.bounds {
+            changes |= DocumentChanges::VIEW_BOUNDS;
+        }
+        changes
+    }
+}
+
+/// 通知を受けるたびにアクティブなドキュメントとビューの変化を調べる
+///
+/// 通知の種類ごとに何が変わったかを推測する代わりに、
+/// [`DocumentWatcher::NOTIFIERS`] のいずれかを受けたら `poll` を呼び、結果の差分で処理を分ける。
+///
+/// ```ignore
+/// if message.is(kAIDocumentViewChangedNotifier) {
+///     let changes = self.watcher.poll()?;
+///     if changes.intersects(DocumentChanges::ZOOM | DocumentChanges::CENTER) {
+///         self.redraw_overlay()?;
+///     }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct DocumentWatcher {
+    document: Option<DocumentSnapshot>,
+    view: Option<ViewSnapshot>,
+}
+
+impl DocumentWatcher {
+    /// `poll` を呼ぶきっかけになる通知
+    pub const NOTIFIERS: &'static [&'static [u8]] = &[
+        kAIDocumentChangedNotifier,
+        kAIDocumentOpenedNotifier,
+        kAIDocumentClosedNotifier,
+        kAIDocumentNewNotifier,
+        kAIDocumentSavedNotifier,
+        kAIDocumentClrMdlChangedNotifier,
+        kAIDocumentRulerUnitChangedNotifier,
+        kAIDocumentRulerOriginChangedNotifier,
+        kAIDocumentViewChangedNotifier,
+        kAIDocumentViewActiveViewChangedNotifier,
+    ];
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 最後に記録したドキュメントの状態
+    pub fn document(&self) -> Option<&DocumentSnapshot> {
+        self.document.as_ref()
+    }
+
+    /// 最後に記録したビューの状態
+    pub fn view(&self) -> Option<&ViewSnapshot> {
+        self.view.as_ref()
+    }
+
+    /// 現在の状態を記録し、前回からの変化を返す
+    ///
+    /// 初回とドキュメントが開かれた・閉じられた場合はすべての項目が変化したとみなす。
+    pub fn poll(&mut self) -> AIResult<DocumentChanges> {
+        let Some(document) = Document::current()? else {
+            let changes = match self.document.take() {
+                Some(_) => DocumentChanges::all(),
+                None => DocumentChanges::empty(),
+            };
+            self.view = None;
+            return Ok(changes);
+        };
+
+        let document = document.snapshot()?;
+        let view = DocumentView::current().snapshot()?;
+
+        let changes = match (&self.document, &self.view) {
+            (Some(old_document), Some(old_view)) => {
+                document.changes_since(old_document) | view.changes_since(old_view)
+            }
+            _ => DocumentChanges::all(),
+        };
+
+        self.document = Some(document);
+        self.view = Some(view);
+        Ok(changes)
+    }
+}
@@ -0,0 +1,200 @@
+use std::fmt;
+
+use crate::ai_sys::*;
+
+/// `AIError` のバリアントと SDK のエラー定数の対応を定義する
+macro_rules! ai_errors {
+    ($($(#[$meta:meta])* $variant:ident = $constant:ident,)*) => {
+        /// `ASErr` を表す Rust のエラー型
+        ///
+        /// SDK の `k*Err` 定数はそれぞれ一つのバリアントに対応し、
+        /// 未知のコードは `Other` にそのまま保持される。
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum AIError {
+            $($(#[$meta])* $variant,)*
+            /// ファイルの読み書きに失敗した（ホストへは `kRustIoErr` として返る）
+            Io(std::io::ErrorKind),
+            /// 定数として知られていない `ASErr`
+            Other(ASErr),
+        }
+
+        impl AIError {
+            /// ホストへ返す `ASErr`
+            pub fn code(self) -> ASErr {
+                match self {
+                    $(AIError::$variant => $constant as ASErr,)*
+                    AIError::Io(_) => kRustIoErr,
+                    AIError::Other(code) => code,
+                }
+            }
+
+            /// 対応する SDK の定数名
+            pub fn name(self) -> Option<&'static str> {
+                match self {
+                    $(AIError::$variant => Some(stringify!($constant)),)*
+                    AIError::Io(_) => Some("kRustIoErr"),
+                    AIError::Other(_) => None,
+                }
+            }
+        }
+
+        impl From<ASErr> for AIError {
+            fn from(code: ASErr) -> Self {
+                $(
+                    if code == $constant as ASErr {
+                        return AIError::$variant;
+                    }
+                )*
+                if code == kRustIoErr {
+                    return AIError::Io(std::io::ErrorKind::Other);
+                }
+                AIError::Other(code)
+            }
+        }
+    };
+}
+
+// バインディングの `k*Err`・`k*Error`・`AIErrorCode` から build.rs が生成した一覧
+include!(concat!(env!("OUT_DIR"), "/ai_errors.rs"));
+
+/// Rust 側のパニックを表すエラーコード（`'PNIC'`）
+#[allow(non_upper_case_globals)]
+pub const kRustPanicErr: ASErr = fourcc(b"PNIC");
+
+/// `std::io` のエラーを表すエラーコード（`'RSIO'`）
+#[allow(non_upper_case_globals)]
+pub const kRustIoErr: ASErr = fourcc(b"RSIO");
+
+pub type AIResult<T> = Result<T, AIError>;
+
+impl From<AIError> for ASErr {
+    fn from(error: AIError) -> Self {
+        error.code()
+    }
+}
+
+impl fmt::Display for AIError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = match four_char_code(self.code()) {
+            Some(chars) => format!("'{}'", String::from_utf8_lossy(&chars)),
+            None => self.code().to_string(),
+        };
+
+        match (self.name(), self) {
+            (Some(name), AIError::Io(kind)) => write!(f, "{} ({}: {})", code, name, kind),
+            (Some(name), _) => write!(f, "{} ({})", code, name),
+            (None, _) => f.write_str(&code),
+        }
+    }
+}
+
+impl std::error::Error for AIError {}
+
+impl From<std::io::Error> for AIError {
+    fn from(error: std::io::Error) -> Self {
+        AIError::Io(error.kind())
+    }
+}
+
+/// 4文字コードから `ASErr` を作る（`fourcc(b"PARM")`）
+pub const fn fourcc(chars: &[u8; 4]) -> ASErr {
+    i32::from_be_bytes(*chars)
+}
+
+/// `ASErr` が表示可能な4文字コードならその文字列を返す
+///
+/// 16384 以下の値は数値エラーとして扱う（`Plugin::DefaultError` と同じ判定）。
+pub fn four_char_code(code: ASErr) -> Option<[u8; 4]> {
+    let chars = code.to_be_bytes();
+
+    if code < 16385 || !chars.iter().all(|c| (0x20..0x7f).contains(c)) {
+        return None;
+    }
+    Some(chars)
+}
+
+/// `ASErr` を `Result` に変換する（`kNoErr` が `Ok`）
+pub fn check(code: ASErr) -> AIResult<()> {
+    if code == kNoErr {
+        Ok(())
+    } else {
+        Err(code.into())
+    }
+}
+
+/// `AIResult<()>` をホストへ返す `ASErr` に変換する
+pub fn to_as_err(result: AIResult<()>) -> ASErr {
+    match result {
+        Ok(()) => kNoErr,
+        Err(error) => error.into(),
+    }
+}
+
+/// `ASErr` を返す値に `?` を使うための拡張
+pub trait ToResult {
+    fn to_result(self) -> AIResult<()>;
+}
+
+impl ToResult for ASErr {
+    fn to_result(self) -> AIResult<()> {
+        check(self)
+    }
+}
+
+/// スイートの関数テーブルのエントリ（`Option<unsafe extern "C" fn(..) -> ASErr>`）
+///
+/// `suite.GetArtType.call((art, &mut art_type))?` のように呼び出せる。
+/// ホストが実装していない関数は `AIError::NotImplemented` になる。
+pub trait SuiteFn<Args> {
+    /// # Safety
+    /// 引数はスイート関数の契約を満たしていなければならない。
+    unsafe fn call(self, args: Args) -> AIResult<()>;
+}
+
+macro_rules! impl_suite_fn {
+    ($($arg:ident: $ty:ident),*) => {
+        impl<$($ty),*> SuiteFn<($($ty,)*)> for Option<unsafe extern "C" fn($($ty),*) -> ASErr> {
+            unsafe fn call(self, ($($arg,)*): ($($ty,)*)) -> AIResult<()> {
+                match self {
+                    Some(function) => check(function($($arg),*)),
+                    None => Err(AIError::NotImplemented),
+                }
+            }
+        }
+    };
+}
+
+impl_suite_fn!();
+impl_suite_fn!(a: A);
+impl_suite_fn!(a: A, b: B);
+impl_suite_fn!(a: A, b: B, c: C);
+impl_suite_fn!(a: A, b: B, c: C, d: D);
+impl_suite_fn!(a: A, b: B, c: C, d: D, e: E);
+impl_suite_fn!(a: A, b: B, c: C, d: D, e: E, f: F);
+impl_suite_fn!(a: A, b: B, c: C, d: D, e: E, f: F, g: G);
+impl_suite_fn!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H);
+impl_suite_fn!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I);
+impl_suite_fn!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn io_error_keeps_its_kind() {
+        let error = AIError::from(io::Error::from(io::ErrorKind::NotFound));
+        assert_eq!(error, AIError::Io(io::ErrorKind::NotFound));
+        assert_ne!(error, AIError::from(io::Error::from(io::ErrorKind::PermissionDenied)));
+        assert_eq!(error.code(), kRustIoErr);
+        assert_eq!(AIError::from(kRustIoErr), AIError::Io(io::ErrorKind::Other));
+    }
+
+    #[test]
+    fn codes_round_trip() {
+        for error in [AIError::Unhandled, AIError::BadParameter, AIError::SPSuiteNotFound, AIError::Panic] {
+            assert_eq!(AIError::from(error.code()), error);
+        }
+        assert_eq!(AIError::from(fourcc(b"ZZZZ")), AIError::Other(fourcc(b"ZZZZ")));
+    }
+}
@@ -1,38 +1,32 @@
 use std::ffi::{c_char, c_void, CStr};
 use std::ptr::null_mut;
+use std::sync::atomic::{AtomicPtr, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::ai_plugin::AIPlugin;
-use illustrator_sys::ai_sys::SPPluginRef;
+use crate::ai_suites::{sSPAccess, sSPBasic, sSPPlugins, AISuite, SuiteError, SuiteGuard, Suites};
+use crate::error::{to_as_err, AIError, SuiteFn, ToResult};
+use crate::messages::NotifierMessage;
+use crate::notifier::{NotifierType, Notifiers};
+use crate::router::{self, MessageRouter, Route};
+use crate::unicode::UnicodeString;
+use crate::ai_sys::*;
 
 
-/// Suites 構造体
-#[repr(C)]
-pub struct Suites {
-    // 実際のフィールドは省略
-}
-
-impl Suites {
-    pub fn new() -> Result<Box<Self>, ASErr> {
-        Ok(Box::new(Self {}))
-    }
-
-    pub fn Error(&self) -> ASErr {
-        kNoErr
-    }
+/// `SetGlobal` で記録した起動中のプラグイン
+static PLUGIN_REF: AtomicPtr<SPPlugin> = AtomicPtr::new(null_mut());
 
-    pub fn InitializeRefCount(&mut self) {
-    }
-
-    pub fn acquire_Optional_Suites(&mut self) {
-    }
+/// 起動中のプラグインの参照（メニューやツールの追加に使う。起動前は null）
+pub fn plugin_ref() -> SPPluginRef {
+    PLUGIN_REF.load(Ordering::Acquire)
 }
 
-
 #[repr(C)]
 pub struct Plugin<T: AIPlugin> {
     pub fPluginRef: SPPluginRef,
     pub fSuites: *mut Suites,
+    pub fRequiredSuites: &'static [AISuite],
+    pub fOptionalSuites: &'static [AISuite],
     pub fPluginName: [i8; kMaxStringLength],
     pub fLockCount: i32,
     pub fPluginAccess: *mut c_void,
@@ -40,18 +34,27 @@ pub struct Plugin<T: AIPlugin> {
     pub fSupressDuplicateErrors: bool,
     pub fErrorTimeout: i64,
     pub fLastErrorTime: i64,
-    pub fApplicationStartedNotifier: *mut c_void,
-    pub fApplicationShutdownNotifer: *mut c_void,
+    /// 起動時に取得できなかった必須スイート（`ReportError` で報告する）
+    pub fSuiteError: Option<SuiteError>,
+    pub fNotifiers: Notifiers<T>,
+    pub fRouter: MessageRouter<T>,
     pub handler: T,
 }
 
 /// プラグイン実装の関数群
-impl<T: AIPlugin + Default> Plugin<T> {
+impl<T: AIPlugin + Default + 'static> Plugin<T> {
     /// 新しいプラグインインスタンスを作成
-    pub fn new(plugin_ref: SPPluginRef, plugin_name: &str) -> Self {
+    pub fn new(
+        plugin_ref: SPPluginRef,
+        plugin_name: &str,
+        required_suites: &'static [AISuite],
+        optional_suites: &'static [AISuite],
+    ) -> Self {
         let mut plugin = Self {
             fPluginRef: plugin_ref,
             fSuites: null_mut(),
+            fRequiredSuites: required_suites,
+            fOptionalSuites: optional_suites,
             fPluginName: [0; kMaxStringLength],
             fLockCount: 0,
             fPluginAccess: null_mut(),
@@ -59,11 +62,15 @@ impl<T: AIPlugin + Default> Plugin<T> {
             fSupressDuplicateErrors: true,
             fErrorTimeout: 5, // seconds
             fLastErrorTime: 0,
-            fApplicationStartedNotifier: null_mut(),
-            fApplicationShutdownNotifer: null_mut(),
+            fSuiteError: None,
+            fNotifiers: Notifiers::new(),
+            fRouter: MessageRouter::new(),
             handler: T::default(),
         };
 
+        // ユーザー定義の caller/selector を登録
+        plugin.handler.RegisterMessageHandlers(&mut plugin.fRouter);
+
         // プラグイン名をコピー
         let name = format!("{}\0", plugin_name);
         for (i, b) in name.bytes().enumerate() {
@@ -88,62 +95,34 @@ impl<T: AIPlugin + Default> Plugin<T> {
             error = self.SetGlobal();
         }
 
-        // Suitesの作成
-        self.fSuites = match Suites::new() {
-            Ok(suites) => Box::into_raw(suites),
-            Err(err) => {
-                error = err;
-                null_mut()
-            }
-        };
+        if error == kNoErr {
+            error = self.AcquireSuites();
+        }
 
-        if !self.fSuites.is_null() && error == kNoErr {
+        if error == kNoErr {
             unsafe {
-                error = (*self.fSuites).Error();
-                (*self.fSuites).InitializeRefCount();
+                error = to_as_err((*sSPPlugins).SetPluginName.call(((*message).d.self_, self.fPluginName.as_ptr())));
             }
+        }
 
-            // エラーがあればSuitesを解放
-            if error != kNoErr {
-                unsafe {
-                    let _ = Box::from_raw(self.fSuites);
-                    self.fSuites = null_mut();
-                }
-            }
+        // アプリケーションの起動・終了を PostStartupPlugin / PreShutdownPlugin として受け取る
+        if error == kNoErr {
+            let started = self.fNotifiers.subscribe(NotifierType::ApplicationStarted, |handler: &mut T, _| {
+                handler.PostStartupPlugin().to_result()
+            });
+            error = to_as_err(started.map(|_| ()));
         }
 
         if error == kNoErr {
-            unsafe {
-                error = sSPPlugins.SetPluginName((*message).d.self_, self.fPluginName.as_ptr());
-
-                if error == kNoErr {
-                    let mut notifier_name = [0i8; kMaxStringLength];
-                    // sprintf相当の処理
-                    let name_str = format!("{} App Started Notifier\0", self.get_plugin_name_str());
-                    copy_cstr_to_buffer(&name_str, &mut notifier_name);
-
-                    error = sAINotifier.AddNotifier(
-                        (*message).d.self_,
-                        notifier_name.as_ptr(),
-                        kAIApplicationStartedNotifier,
-                        &mut self.fApplicationStartedNotifier as *mut _ as *mut c_void
-                    );
-                }
+            let shutdown = self.fNotifiers.subscribe(NotifierType::ApplicationShutdown, |handler: &mut T, _| {
+                handler.PreShutdownPlugin().to_result()
+            });
+            error = to_as_err(shutdown.map(|_| ()));
+        }
 
-                if error == kNoErr {
-                    let mut notifier_name = [0i8; kMaxStringLength];
-                    // sprintf相当の処理
-                    let name_str = format!("{} Application Shutdown Notifier\0", self.get_plugin_name_str());
-                    copy_cstr_to_buffer(&name_str, &mut notifier_name);
-
-                    error = sAINotifier.AddNotifier(
-                        (*message).d.self_,
-                        notifier_name.as_ptr(),
-                        kAIApplicationShutdownNotifier,
-                        &mut self.fApplicationShutdownNotifer as *mut _ as *mut c_void
-                    );
-                }
-            }
+        // メニューなど起動時に追加するもの
+        if error == kNoErr {
+            error = self.handler.StartupPlugin();
         }
 
         if error == kNoErr {
@@ -166,12 +145,7 @@ impl<T: AIPlugin + Default> Plugin<T> {
         let error = kNoErr;
 
         // Suitesの解放
-        if !self.fSuites.is_null() {
-            unsafe {
-                let _ = Box::from_raw(self.fSuites);
-                self.fSuites = null_mut();
-            }
-        }
+        self.ReleaseSuites();
 
         error
     }
@@ -181,13 +155,7 @@ impl<T: AIPlugin + Default> Plugin<T> {
         let error = kNoErr;
 
         self.EmptySuiteTables();
-
-        if !self.fSuites.is_null() {
-            unsafe {
-                let _ = Box::from_raw(self.fSuites);
-                self.fSuites = null_mut();
-            }
-        }
+        self.ReleaseSuites();
 
         error
     }
@@ -200,29 +168,9 @@ impl<T: AIPlugin + Default> Plugin<T> {
             error = self.SetGlobal();
         }
 
-        // suites が null なら新しく作成
-        if self.fSuites.is_null() {
-            self.fSuites = match Suites::new() {
-                Ok(suites) => Box::into_raw(suites),
-                Err(err) => {
-                    error = err;
-                    null_mut()
-                }
-            };
-
-            if !self.fSuites.is_null() && error == kNoErr {
-                unsafe {
-                    error = (*self.fSuites).Error();
-                    (*self.fSuites).InitializeRefCount();
-                }
-
-                if error != kNoErr {
-                    unsafe {
-                        let _ = Box::from_raw(self.fSuites);
-                        self.fSuites = null_mut();
-                    }
-                }
-            }
+        // アンロード時に解放したスイートを取得し直す
+        if error == kNoErr && self.fSuites.is_null() {
+            error = self.AcquireSuites();
         }
 
         if error == kNoErr {
@@ -232,13 +180,34 @@ impl<T: AIPlugin + Default> Plugin<T> {
         error
     }
 
+    /// 取得したスイートをホストへ返す（取得していなければ何もしない）
+    pub fn ReleaseSuites(&mut self) {
+        if !self.fSuites.is_null() {
+            unsafe { drop(Box::from_raw(self.fSuites)) };
+            self.fSuites = null_mut();
+        }
+    }
+
+    /// 宣言された必須・任意スイートを取得する
+    ///
+    /// 必須スイートが一つでも欠けていれば起動を中止する。欠けたスイートは `ReportError` が報告する。
+    pub fn AcquireSuites(&mut self) -> ASErr {
+        match Suites::new(self.fRequiredSuites, self.fOptionalSuites) {
+            Ok(suites) => {
+                self.fSuites = Box::into_raw(suites);
+                self.fSuiteError = None;
+                kNoErr
+            }
+            Err(err) => {
+                self.fSuiteError = Some(err);
+                err.into()
+            }
+        }
+    }
+
     /// メッセージがリロードメッセージかチェック
     pub fn IsReloadMsg(caller: *const c_char, selector: *const c_char) -> bool {
-        unsafe {
-            // C文字列を比較
-            strcmp(caller, kSPAccessCaller.as_ptr()) == 0 &&
-            strcmp(selector, kSPAccessReloadSelector.as_ptr()) == 0
-        }
+        unsafe { router::resolve(caller, selector) == Some(Route::AccessReload) }
     }
 
     /// メッセージ処理 - プラグインのコア機能
@@ -246,68 +215,51 @@ impl<T: AIPlugin + Default> Plugin<T> {
         // オプショナルスイートの取得
         self.AcquireOptionalSuites();
 
-        let mut error = kUnhandledMsgErr;
+        let Some(route) = (unsafe { self.fRouter.resolve(caller, selector) }) else {
+            unsafe { self.fRouter.report_unhandled(caller, selector); }
+            return kUnhandledMsgErr;
+        };
 
-        unsafe {
+        match route {
             // Sweet Pea メッセージ
-            if strcmp(caller, kSPAccessCaller.as_ptr()) == 0 {
-                if strcmp(selector, kSPAccessUnloadSelector.as_ptr()) == 0 {
-                    error = self.UnloadPlugin(message as *mut SPInterfaceMessage);
-                } else if strcmp(selector, kSPAccessReloadSelector.as_ptr()) == 0 {
-                    error = self.ReloadPlugin(message as *mut SPInterfaceMessage);
-                }
-            } else if strcmp(caller, kSPInterfaceCaller.as_ptr()) == 0 {
-                if strcmp(selector, kSPInterfaceAboutSelector.as_ptr()) == 0 {
-                    error = kNoErr;
-                } else if strcmp(selector, kSPInterfaceStartupSelector.as_ptr()) == 0 {
-                    error = kNoErr;
-                }
-            } else if strcmp(caller, kSPCacheCaller.as_ptr()) == 0 {
-                if strcmp(selector, kSPPluginPurgeCachesSelector.as_ptr()) == 0 {
-                    if self.Purge() {
-                        error = kSPPluginCachesFlushResponse;
-                    } else {
-                        error = kSPPluginCouldntFlushResponse;
-                    }
-                }
-            } else if strcmp(caller, kSPPropertiesCaller.as_ptr()) == 0 {
-                if strcmp(selector, kSPPropertiesAcquireSelector.as_ptr()) == 0 {
-                    error = self.handler.AcquireProperty(message as *mut SPPropertiesMessage);
-                } else if strcmp(selector, kSPPropertiesReleaseSelector.as_ptr()) == 0 {
-                    error = self.handler.ReleaseProperty(message as *mut SPPropertiesMessage);
+            Route::AccessUnload => self.UnloadPlugin(message as *mut SPInterfaceMessage),
+            Route::AccessReload => self.ReloadPlugin(message as *mut SPInterfaceMessage),
+            Route::InterfaceAbout | Route::InterfaceStartup => kNoErr,
+            Route::PurgeCaches => {
+                if self.Purge() {
+                    kSPPluginCachesFlushResponse
+                } else {
+                    kSPPluginCouldntFlushResponse
                 }
             }
 
             // アプリケーション通知
-            else if strcmp(caller, kCallerAINotify.as_ptr()) == 0 {
+            Route::Notify => {
                 let msg = message as *mut AINotifierMessage;
+                let handler = unsafe { msg.as_ref() }.and_then(|msg| self.fNotifiers.handler(msg.notifier));
 
-                if (*msg).notifier == self.fApplicationStartedNotifier {
-                    error = self.handler.PostStartupPlugin();
-                } else if (*msg).notifier == self.fApplicationShutdownNotifer {
-                    error = self.handler.PreShutdownPlugin();
-                }
+                let mut error = match handler.zip(unsafe { NotifierMessage::from_raw(msg) }) {
+                    Some((handler, view)) => to_as_err((&mut *handler.borrow_mut())(&mut self.handler, view)),
+                    None => kUnhandledMsgErr,
+                };
 
                 if error == kNoErr || error == kUnhandledMsgErr {
-                    if strcmp(selector, kSelectorAINotify.as_ptr()) == 0 {
-                        error = self.handler.Notify(msg);
-                    }
+                    error = self.handler.Notify(msg);
                 }
+                error
             }
 
+            // ユーザー登録のハンドラ
+            Route::Custom(index) => self.fRouter.call(index, &mut self.handler, message),
+
             // AIPluginトレイトの他のイベントハンドラを呼び出し
-            else {
-                // AIPluginトレイトのdispatch_messageを呼び出す
-                error = AIPlugin::dispatch_message(&mut self.handler, caller, selector, message);
-            }
+            route => self.handler.dispatch_route(route, message),
         }
-
-        error
     }
 
     /// グローバルプラグイン参照を設定
     pub fn SetGlobal(&self) -> ASErr {
-        // グローバルインスタンスが設定されていることを確認
+        PLUGIN_REF.store(self.fPluginRef, Ordering::Release);
         kNoErr
     }
 
@@ -323,6 +275,12 @@ impl<T: AIPlugin + Default> Plugin<T> {
 
     /// エラー報告
     pub fn ReportError(&mut self, error: ASErr, _caller: *const c_char, _selector: *const c_char, _message: *mut c_void) {
+        // 必須スイートの不足はエラーコードではなくスイート名で報告する
+        if let Some(suite_error) = self.fSuiteError.take() {
+            Self::ErrorAlert(&format!("{}: {}", self.get_plugin_name_str(), suite_error));
+            return;
+        }
+
         if Self::FilterError(error) {
             return;
         }
@@ -348,66 +306,31 @@ impl<T: AIPlugin + Default> Plugin<T> {
             return;
         }
 
-        unsafe {
-            let mut got_basic = false;
-            if sAIUser.is_null() {
-                if sSPBasic.is_null() {
-                    return;
-                }
-
-                let err = (*sSPBasic).AcquireSuite(
-                    kAIUserSuite.as_ptr(),
-                    kAIUserSuiteVersion,
-                    &mut sAIUser as *mut _ as *mut *const c_void
-                );
-
-                if err != 0 || sAIUser.is_null() {
-                    return;
-                }
-
-                got_basic = true;
-            }
-
-            let mut msg = [0i8; 128];
-            let m = Self::FindMsg(ref_, error, &mut msg);
+        let mut msg = [0i8; 128];
+        let m = Self::FindMsg(ref_, error, &mut msg);
 
-            if m.is_null() {
-                if got_basic {
-                    (*sSPBasic).ReleaseSuite(kAIUserSuite.as_ptr(), kAIUserSuiteVersion);
-                    sAIUser = null_mut();
-                }
-                return;
-            }
-
-            let mut mbuf = [0i8; 128];
-
-            if CStr::from_ptr(m).to_bytes().len() < 120 {
-                let mut err_string = [0i8; 10];
-
-                if error < 16385 {  // 単純な数値エラーの場合
-                    let err_str = format!("{}\0", error);
-                    copy_cstr_to_buffer(&err_str, &mut err_string);
-                } else {  // 4バイト文字列エラー
-                    for i in (0..=3).rev() {
-                        err_string[i] = (error & 0xff) as i8;
-                        error = error >> 8;
-                    }
-                    err_string[4] = 0;
-                }
+        if m.is_null() {
+            return;
+        }
 
-                copy_cstr_to_buffer(CStr::from_ptr(m).to_str().unwrap_or("Error"), &mut mbuf);
+        // メッセージ中の %s をエラーコード（4文字コードなら 'PARM' の形式）で置き換える
+        let text = unsafe { CStr::from_ptr(m) }
+            .to_str()
+            .unwrap_or("Error")
+            .replace("%s", &AIError::from(error).to_string());
 
-                m = mbuf.as_ptr();
-            }
+        Self::ErrorAlert(&text);
+    }
 
-            // Unicode文字列に変換してエラー表示
-            let unicode_str = ai::UnicodeString::new(CStr::from_ptr(m).to_str().unwrap_or("Error"));
-            (*sAIUser).ErrorAlert(unicode_str);
+    /// `AIUserSuite::ErrorAlert` でエラーを表示する
+    pub fn ErrorAlert(text: &str) {
+        let Ok(user) = SuiteGuard::<AIUserSuite>::acquire() else {
+            return;
+        };
 
-            if got_basic {
-                (*sSPBasic).ReleaseSuite(kAIUserSuite.as_ptr(), kAIUserSuiteVersion);
-                sAIUser = null_mut();
-            }
+        // Unicode文字列に変換してエラー表示
+        if let (Ok(unicode_str), Some(error_alert)) = (UnicodeString::new(text), user.ErrorAlert) {
+            unsafe { error_alert(unicode_str.as_ptr()) };
         }
     }
 
@@ -454,14 +377,14 @@ impl<T: AIPlugin + Default> Plugin<T> {
             self.fLockCount += 1;
             if self.fLockCount == 1 {
                 unsafe {
-                    (*sSPAccess).AcquirePlugin(self.fPluginRef, &mut self.fPluginAccess);
+                    let _ = (*sSPAccess).AcquirePlugin.call((self.fPluginRef, &mut self.fPluginAccess as *mut _ as *mut SPAccessRef));
                 }
             }
         } else {
             self.fLockCount -= 1;
             if self.fLockCount == 0 {
                 unsafe {
-                    (*sSPAccess).ReleasePlugin(self.fPluginAccess);
+                    let _ = (*sSPAccess).ReleasePlugin.call((self.fPluginAccess as SPAccessRef,));
                     self.fPluginAccess = null_mut();
                 }
             } else if self.fLockCount < 0 {
@@ -478,6 +401,79 @@ impl<T: AIPlugin + Default> Plugin<T> {
     pub fn Purge(&self) -> bool { false }
 }
 
+/// `define_plugin!` が生成する `PluginMain` の本体
+///
+/// 起動メッセージで `Plugin<T>` を確保して `SPMessageData::globals` に保存し、
+/// 終了メッセージで解放する。それ以外のメッセージは `globals` のプラグインへ渡す。
+///
+/// # Safety
+/// `caller`・`selector` は NUL 終端された文字列、`message` は null か `SPMessageData` で始まるメッセージで、
+/// いずれも呼び出しの間有効でなければならない。`globals` はこの関数が保存した値のまま渡されなければならない。
+pub unsafe fn plugin_main<T: AIPlugin + Default + 'static>(
+    caller: *const c_char,
+    selector: *const c_char,
+    message: *mut c_void,
+    plugin_name: &str,
+    required_suites: &'static [AISuite],
+    optional_suites: &'static [AISuite],
+) -> ASErr {
+    let Some(msg_data) = (message as *mut SPMessageData).as_mut() else {
+        return AIError::BadParameter.code();
+    };
+
+    sSPBasic = msg_data.basic;
+    // ホストはメッセージごとに新しい取り消し単位を始める
+    crate::undo::begin_message();
+
+    let plugin = msg_data.globals as *mut Plugin<T>;
+    let mut error = match router::resolve(caller, selector) {
+        Some(Route::InterfaceStartup) => {
+            let plugin = Box::into_raw(Box::new(Plugin::<T>::new(
+                msg_data.self_,
+                plugin_name,
+                required_suites,
+                optional_suites,
+            )));
+            msg_data.globals = plugin as *mut c_void;
+
+            let error = (*plugin).StartupPlugin(message as *mut SPInterfaceMessage);
+            if error != kNoErr {
+                // スタートアップに失敗した場合は報告してから、取得済みのスイートとプラグインを解放
+                (*plugin).ReportError(error, caller, selector, message);
+                (*plugin).EmptySuiteTables();
+                (*plugin).ReleaseSuites();
+                drop(Box::from_raw(plugin));
+                msg_data.globals = null_mut();
+            }
+            return error;
+        }
+        Some(Route::InterfaceShutdown) if !plugin.is_null() => {
+            let error = (*plugin).ShutdownPlugin(message as *mut SPInterfaceMessage);
+            if error != kNoErr {
+                (*plugin).ReportError(error, caller, selector, message);
+            }
+            drop(Box::from_raw(plugin));
+            msg_data.globals = null_mut();
+            return error;
+        }
+        _ if plugin.is_null() => kNoErr,
+        Some(Route::AccessReload) => (*plugin).ReloadPlugin(message as *mut SPInterfaceMessage),
+        // ロードやリロードがスイート取得に失敗していた場合、保護する
+        _ if !(*plugin).SuitesAcquired() => kNoErr,
+        _ => (*plugin).Message(caller, selector, message),
+    };
+
+    if error == kUnhandledMsgErr {
+        error = kNoErr;
+    }
+
+    if error != kNoErr {
+        (*plugin).ReportError(error, caller, selector, message);
+    }
+
+    error
+}
+
 /// C文字列をi8バッファにコピーするヘルパー関数
 pub fn copy_cstr_to_buffer(src: &str, dst: &mut [i8]) {
     let bytes = src.as_bytes();
@@ -487,20 +483,3 @@ pub fn copy_cstr_to_buffer(src: &str, dst: &mut [i8]) {
         }
     }
 }
-
-
-/// 型変換用のモジュール
-pub mod ai {
-    use std::ffi::CStr;
-
-    #[repr(C)]
-    pub struct UnicodeString {
-        // 必要なフィールド
-    }
-
-    impl UnicodeString {
-        pub fn new(_str: &str) -> Self {
-            Self { /* 初期化 */ }
-        }
-    }
-}
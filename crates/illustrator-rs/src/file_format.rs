@@ -0,0 +1,504 @@
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::ptr::{null, null_mut};
+use std::rc::Rc;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::bytes::{from_bytes, to_bytes};
+use crate::document::Document;
+use crate::error::{AIError, AIResult, SuiteFn};
+use crate::externs::plugin_ref;
+use crate::file_path::borrowed_path;
+use crate::unicode::UnicodeString;
+use crate::util::{self, flags, read_c_string, Registry};
+
+flags! {
+    /// フォーマットが対応する操作（`AIFileFormatOptions` の読み書きのビット）
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct FormatCapabilities(u32) {
+        /// 開く（新しいドキュメントとして読み込む）
+        const READ = AIFileFormatOptions_kFileFormatRead as u32;
+        /// 現在のドキュメントへ読み込む
+        const IMPORT_ART = AIFileFormatOptions_kFileFormatImportArt as u32;
+        /// 配置する
+        const PLACE_ART = AIFileFormatOptions_kFileFormatPlaceArt as u32;
+        /// リンクとして配置する
+        const LINK_ART = AIFileFormatOptions_kFileFormatLinkArt as u32;
+        /// 保存する
+        const WRITE = AIFileFormatOptions_kFileFormatWrite as u32;
+        /// 書き出す
+        const EXPORT = AIFileFormatOptions_kFileFormatExport as u32;
+    }
+}
+
+/// ホストが要求した操作（`AIFileFormatMessage::option`）
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FileOperation {
+    Open,
+    Import,
+    Place,
+    Link,
+    Save,
+    Export,
+}
+
+impl FileOperation {
+    fn from_option(option: ai_int32) -> Option<Self> {
+        let option = option as u32;
+        [
+            (FormatCapabilities::PLACE_ART, FileOperation::Place),
+            (FormatCapabilities::LINK_ART, FileOperation::Link),
+            (FormatCapabilities::IMPORT_ART, FileOperation::Import),
+            (FormatCapabilities::READ, FileOperation::Open),
+            (FormatCapabilities::EXPORT, FileOperation::Export),
+            (FormatCapabilities::WRITE, FileOperation::Save),
+        ]
+        .into_iter()
+        .find(|(capability, _)| option & capability.bits() != 0)
+        .map(|(_, operation)| operation)
+    }
+
+    /// ファイルから読み込む操作か
+    pub fn is_read(self) -> bool {
+        !matches!(self, FileOperation::Save | FileOperation::Export)
+    }
+}
+
+/// 登録されたファイルフォーマット
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Format(AIFileFormatHandle);
+
+impl Format {
+    /// # Safety
+    /// `handle` は `AIFileFormatSuite` が返した有効なフォーマットでなければならない。
+    pub unsafe fn from_raw(handle: AIFileFormatHandle) -> Option<Self> {
+        (!handle.is_null()).then_some(Self(handle))
+    }
+
+    pub fn as_raw(self) -> AIFileFormatHandle {
+        self.0
+    }
+
+    /// `AddFileFormat` に渡した一意な名前
+    pub fn name(self) -> AIResult<String> {
+        let suite = SuiteGuard::<AIFileFormatSuite>::acquire()?;
+        let mut name: *const c_char = null();
+
+        unsafe {
+            suite.GetFileFormatName.call((self.0, &mut name as *mut _))?;
+            read_c_string(name)
+        }
+    }
+
+    /// ダイアログのフォーマット一覧に表示される名前
+    pub fn title(self) -> AIResult<String> {
+        let suite = SuiteGuard::<AIFileFormatSuite>::acquire()?;
+        let mut title = UnicodeString::empty()?;
+
+        unsafe { suite.GetFileFormatTitle.call((self.0, title.as_mut_ptr()))? };
+        Ok(title.to_string_lossy())
+    }
+
+    /// 拡張子（複数ある場合はカンマ区切り）
+    pub fn extension(self) -> AIResult<String> {
+        let suite = SuiteGuard::<AIFileFormatSuite>::acquire()?;
+        let mut extension = UnicodeString::empty()?;
+
+        unsafe { suite.GetFileFormatExtension.call((self.0, extension.as_mut_ptr()))? };
+        Ok(extension.to_string_lossy())
+    }
+
+    pub fn capabilities(self) -> AIResult<FormatCapabilities> {
+        let suite = SuiteGuard::<AIFileFormatSuite>::acquire()?;
+        let mut options: ai_int32 = 0;
+
+        unsafe { suite.GetFileFormatOptions.call((self.0, &mut options as *mut _))? };
+        Ok(FormatCapabilities(options as u32))
+    }
+}
+
+/// 判定のために読むファイル先頭の長さ
+const PEEK_LEN: u64 = 4096;
+
+/// `FileFormat::check` に渡す、ファイル先頭の一部
+///
+/// 最大 4 KiB を読み込んであり、`Read` で先頭から読み直せる。
+pub struct Peek {
+    path: PathBuf,
+    head: Vec<u8>,
+    position: usize,
+}
+
+impl Peek {
+    fn open(path: PathBuf) -> io::Result<Self> {
+        let mut head = Vec::new();
+        File::open(&path)?.take(PEEK_LEN).read_to_end(&mut head)?;
+        Ok(Self { path, head, position: 0 })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 小文字にした拡張子
+    pub fn extension(&self) -> Option<String> {
+        self.path.extension().map(|extension| extension.to_string_lossy().to_lowercase())
+    }
+
+    /// 読み込んだ先頭部分（ファイルが短ければその全体）
+    pub fn head(&self) -> &[u8] {
+        &self.head
+    }
+
+    pub fn starts_with(&self, magic: &[u8]) -> bool {
+        self.head.starts_with(magic)
+    }
+}
+
+impl Read for Peek {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = (&self.head[self.position..]).read(buf)?;
+        self.position += read;
+        Ok(read)
+    }
+}
+
+/// 読み書きの対象（`AIFileFormatMessage` の内容）
+pub struct FileContext {
+    format: Format,
+    operation: FileOperation,
+    path: PathBuf,
+    operation_options: ai_int32,
+}
+
+impl FileContext {
+    fn new(message: &AIFileFormatMessage) -> AIResult<Self> {
+        Ok(Self {
+            format: unsafe { Format::from_raw(message.fileFormat) }.ok_or(AIError::BadParameter)?,
+            operation: FileOperation::from_option(message.option).ok_or(AIError::BadParameter)?,
+            path: borrowed_path(&message.filePath)?,
+            operation_options: message.operationOptions,
+        })
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn operation(&self) -> FileOperation {
+        self.operation
+    }
+
+    /// 読み書きするファイルのフルパス
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 対象のドキュメント（開く場合はホストが作った新しいドキュメント）
+    pub fn document(&self) -> AIResult<Document> {
+        Document::current()?.ok_or(AIError::NoDocument)
+    }
+
+    /// 選択中のアートだけを書き出す
+    pub fn selection_only(&self) -> bool {
+        self.operation_options & AIFileFormatOptions_kFileFormatExportSelection != 0
+    }
+
+    /// ダイアログを出さずに処理する（スクリプトやバッチからの実行）
+    pub fn suppress_ui(&self) -> bool {
+        self.operation_options & AIFileFormatOptions_kFileFormatSuppressUI != 0
+    }
+}
+
+/// ファイルフォーマットの振る舞い
+///
+/// `Options` は読み書きの設定で、アクションのパラメータ（`actionParm`）に保存されるため、
+/// アクションの記録・再生やスクリプトからの実行でも同じ設定が使われる。
+/// 登録時の `FormatCapabilities` に含まれる操作のメソッドだけを実装すればよい。
+#[allow(unused_variables)]
+pub trait FileFormat<P> {
+    type Options: Serialize + DeserializeOwned + Default;
+
+    /// このフォーマットとして読めるファイルか（既定は拡張子の一致だけで判断する）
+    fn check(&mut self, plugin: &mut P, file: &mut Peek) -> AIResult<bool> {
+        Ok(true)
+    }
+
+    /// 読み書きの前に設定を編集する（ダイアログを閉じたら `Err(AIError::Canceled)` を返す）
+    fn edit_options(&mut self, plugin: &mut P, context: &FileContext, options: &mut Self::Options) -> AIResult<()> {
+        Ok(())
+    }
+
+    /// ファイルを読み込んでアートを作る
+    fn read(
+        &mut self,
+        plugin: &mut P,
+        context: &FileContext,
+        reader: &mut dyn Read,
+        options: &Self::Options,
+    ) -> AIResult<()> {
+        Err(AIError::NotImplemented)
+    }
+
+    /// ドキュメントをファイルへ書き出す
+    fn write(
+        &mut self,
+        plugin: &mut P,
+        context: &FileContext,
+        writer: &mut dyn Write,
+        options: &Self::Options,
+    ) -> AIResult<()> {
+        Err(AIError::NotImplemented)
+    }
+}
+
+/// アクションのパラメータに設定を格納するキー（`'RsOp'`）
+const OPTIONS_KEY: ActionParamKeyID = u32::from_be_bytes(*b"RsOp");
+
+/// アクションのパラメータから設定を読む（パラメータがない・設定が含まれない・読めないなら `None`）
+///
+/// 古い・別の `Options` で記録されたアクションやスクリプトの設定は読めないので、直前の設定を使い続ける。
+fn load_options<T: DeserializeOwned>(param: AIActionParamValueRef) -> AIResult<Option<T>> {
+    if param.is_null() {
+        return Ok(None);
+    }
+    let suite = SuiteGuard::<AIActionManagerSuite>::acquire()?;
+    let mut has_key: AIBoolean = 0;
+    let mut size: ai_uint32 = 0;
+
+    unsafe {
+        suite.AIActionHasValueKey.call((param, OPTIONS_KEY, &mut has_key as *mut _))?;
+        if has_key == 0 {
+            return Ok(None);
+        }
+        suite.AIActionGetRawDataSize.call((param, OPTIONS_KEY, &mut size as *mut _))?;
+    }
+
+    let mut bytes = vec![0u8; size as usize];
+    unsafe { suite.AIActionGetRawData.call((param, OPTIONS_KEY, bytes.as_mut_ptr() as *mut c_char))? };
+    Ok(from_bytes(&bytes).ok())
+}
+
+fn store_options<T: Serialize>(param: AIActionParamValueRef, options: &T) -> AIResult<()> {
+    if param.is_null() {
+        return Ok(());
+    }
+    let suite = SuiteGuard::<AIActionManagerSuite>::acquire()?;
+    let bytes = to_bytes(options)?;
+    let size = ai_uint32::try_from(bytes.len()).map_err(|_| AIError::BadParameter)?;
+
+    unsafe {
+        suite
+            .AIActionSetRawDataBytes
+            .call((param, OPTIONS_KEY, size, bytes.as_ptr() as *const c_char))
+    }
+}
+
+/// `FileFormat` と現在の設定
+struct Registered<F, O> {
+    format: F,
+    options: O,
+}
+
+/// `Options` の型を隠してメッセージを `FileFormat` へ渡す
+pub(crate) trait Handler<P> {
+    fn parameters(&mut self, plugin: &mut P, message: &mut AIFileFormatMessage) -> AIResult<()>;
+    fn go(&mut self, plugin: &mut P, message: &mut AIFileFormatMessage) -> AIResult<()>;
+    fn check(&mut self, plugin: &mut P, message: &mut AIFileFormatMessage) -> AIResult<()>;
+    fn set_parameters(&mut self, plugin: &mut P, message: &mut DoActionMessage) -> AIResult<()>;
+}
+
+impl<P, F: FileFormat<P>> Handler<P> for Registered<F, F::Options> {
+    fn parameters(&mut self, plugin: &mut P, message: &mut AIFileFormatMessage) -> AIResult<()> {
+        let context = FileContext::new(message)?;
+        let param = message.actionParm as AIActionParamValueRef;
+        if let Some(options) = load_options(param)? {
+            self.options = options;
+        }
+
+        self.format.edit_options(plugin, &context, &mut self.options)?;
+        store_options(param, &self.options)
+    }
+
+    fn go(&mut self, plugin: &mut P, message: &mut AIFileFormatMessage) -> AIResult<()> {
+        let context = FileContext::new(message)?;
+        if let Some(options) = load_options(message.actionParm as AIActionParamValueRef)? {
+            self.options = options;
+        }
+
+        if context.operation().is_read() {
+            let mut reader = BufReader::new(File::open(context.path())?);
+            self.format.read(plugin, &context, &mut reader, &self.options)
+        } else {
+            let mut writer = BufWriter::new(File::create(context.path())?);
+            self.format.write(plugin, &context, &mut writer, &self.options)?;
+            Ok(writer.flush()?)
+        }
+    }
+
+    fn check(&mut self, plugin: &mut P, message: &mut AIFileFormatMessage) -> AIResult<()> {
+        let mut file = Peek::open(borrowed_path(&message.filePath)?)?;
+        if self.format.check(plugin, &mut file)? {
+            Ok(())
+        } else {
+            Err(AIError::UnknownFormat)
+        }
+    }
+
+    fn set_parameters(&mut self, _plugin: &mut P, message: &mut DoActionMessage) -> AIResult<()> {
+        if let Some(options) = load_options(message.param)? {
+            self.options = options;
+        }
+        Ok(())
+    }
+}
+
+/// 宛先のフォーマットを持つメッセージ
+pub(crate) trait FormatMessage {
+    fn format(&self) -> AIFileFormatHandle;
+}
+
+impl FormatMessage for AIFileFormatMessage {
+    fn format(&self) -> AIFileFormatHandle { self.fileFormat }
+}
+
+/// ファイルフォーマット caller の `kDoActionSelector` では `userData` がフォーマットのハンドル
+impl FormatMessage for DoActionMessage {
+    fn format(&self) -> AIFileFormatHandle { self.userData as AIFileFormatHandle }
+}
+
+/// プラグインが登録したファイルフォーマットと、その振る舞い
+///
+/// 起動時（`SafePlugin::startup`）にフォーマットを登録し、`SafePlugin::file_formats` から返すと、
+/// `kCallerAIFileFormat` のメッセージが `FileFormat` へ送られる。
+/// リンクの更新（`kSelectorAIUpdateFileFormat`）は常に `SafePlugin::file_format_update` へ送られる。
+pub struct FileFormats<P> {
+    entries: Registry<Format, dyn Handler<P>>,
+}
+
+impl<P> Default for FileFormats<P> {
+    fn default() -> Self {
+        Self { entries: Registry::default() }
+    }
+}
+
+impl<P> FileFormats<P> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// フォーマットの設定を始める
+    ///
+    /// `name` はフォーマットを識別する一意な名前、`title` はダイアログのフォーマット一覧に表示される名前。
+    pub fn format<'a>(&'a mut self, name: &'a CStr, title: &'a str) -> FileFormatBuilder<'a, P> {
+        FileFormatBuilder {
+            formats: self,
+            name,
+            title,
+            extensions: Vec::new(),
+            capabilities: FormatCapabilities::empty(),
+            priority: None,
+        }
+    }
+
+    /// 登録したフォーマット（登録順）
+    pub fn formats(&self) -> impl Iterator<Item = Format> + '_ {
+        self.entries.keys()
+    }
+
+    pub fn contains(&self, format: AIFileFormatHandle) -> bool {
+        self.entries.contains(Format(format))
+    }
+}
+
+/// `kCallerAIFileFormat` のメッセージを `call` で `FileFormat` へ送る
+pub(crate) fn dispatch<P, M: FormatMessage>(
+    plugin: &mut P,
+    formats: fn(&mut P) -> Option<&mut FileFormats<P>>,
+    message: *mut M,
+    call: fn(&mut dyn Handler<P>, &mut P, &mut M) -> AIResult<()>,
+) -> Option<AIResult<()>> {
+    let message = unsafe { message.as_mut() }?;
+    let format = Format(message.format());
+    util::dispatch(plugin, |plugin| Some(&formats(plugin)?.entries), format, |behavior, plugin, ()| {
+        call(behavior, plugin, message)
+    })
+}
+
+/// `FileFormats::format` が返す、フォーマットの設定
+pub struct FileFormatBuilder<'a, P> {
+    formats: &'a mut FileFormats<P>,
+    name: &'a CStr,
+    title: &'a str,
+    extensions: Vec<&'a str>,
+    capabilities: FormatCapabilities,
+    priority: Option<i32>,
+}
+
+impl<'a, P> FileFormatBuilder<'a, P> {
+    /// 拡張子を追加する（`"svg"` のように先頭の `.` は付けない）
+    pub fn extension(mut self, extension: &'a str) -> Self {
+        self.extensions.push(extension);
+        self
+    }
+
+    pub fn capabilities(mut self, capabilities: FormatCapabilities) -> Self {
+        self.capabilities |= capabilities;
+        self
+    }
+
+    /// 同じファイルを読める他のフォーマットとの優先度（大きいほど優先される）
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// フォーマットを登録し、`format` と結び付ける
+    pub fn add<F>(self, format: F) -> AIResult<Format>
+    where
+        F: FileFormat<P> + 'static,
+        F::Options: 'static,
+    {
+        let suite = SuiteGuard::<AIFileFormatSuite>::acquire()?;
+        let title = UnicodeString::new(self.title)?;
+        let extension = UnicodeString::new(&self.extensions.join(","))?;
+        let mut handle: AIFileFormatHandle = null_mut();
+
+        let mut data = PlatformAddFileFormatData {
+            type_: 0,
+            title: title.borrow_raw(),
+            titleOrder: 0,
+            extension: extension.borrow_raw(),
+        };
+
+        let registered = unsafe {
+            suite.AddFileFormat.call((
+                plugin_ref(),
+                self.name.as_ptr(),
+                &mut data as *mut _,
+                self.capabilities.bits() as ai_int32,
+                &mut handle as *mut _,
+                0,
+            ))?;
+            Format::from_raw(handle).ok_or(AIError::CantHappen)?
+        };
+
+        if let Some(priority) = self.priority {
+            unsafe { suite.SetFileFormatPriority.call((handle, priority))? };
+        }
+
+        let behavior = Registered {
+            format,
+            options: F::Options::default(),
+        };
+        self.formats.entries.push(registered, Rc::new(RefCell::new(behavior)), ());
+        Ok(registered)
+    }
+}
@@ -0,0 +1,86 @@
+use std::path::{Path, PathBuf};
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::error::{AIResult, SuiteFn};
+use crate::unicode::UnicodeString;
+
+/// `AIFilePathSuite` で管理される `ai::FilePath`
+///
+/// 破棄時に `DeleteFilePath` で中身を解放する。
+pub struct FilePath {
+    raw: ai_FilePath,
+    suite: SuiteGuard<AIFilePathSuite>,
+}
+
+impl FilePath {
+    /// 空のパスを作る（SDK 関数の出力引数として使う）
+    pub fn empty() -> AIResult<Self> {
+        let mut path = Self {
+            raw: unsafe { std::mem::zeroed() },
+            suite: SuiteGuard::acquire()?,
+        };
+
+        unsafe { path.suite.NewFilePath.call((&mut path.raw as *mut _,))? };
+        Ok(path)
+    }
+
+    /// Rust のパスから作る
+    pub fn new(path: &Path) -> AIResult<Self> {
+        let mut file_path = Self::empty()?;
+        let text = UnicodeString::new(&path.to_string_lossy())?;
+
+        unsafe {
+            file_path
+                .suite
+                .Set
+                .call((text.as_ptr(), false as AIBool8, true as AIBool8, &mut file_path.raw as *mut _))?;
+        }
+        Ok(file_path)
+    }
+
+    pub fn as_ptr(&self) -> *const ai_FilePath {
+        &self.raw
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut ai_FilePath {
+        &mut self.raw
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self.suite.IsEmpty {
+            Some(is_empty) => unsafe { is_empty(&self.raw) != 0 },
+            None => true,
+        }
+    }
+
+    /// プラットフォームの形式のフルパス
+    pub fn to_path_buf(&self) -> AIResult<PathBuf> {
+        full_path(&self.suite, &self.raw)
+    }
+}
+
+/// メッセージのメンバーなど、借用した `ai::FilePath` のフルパス
+pub(crate) fn borrowed_path(raw: &ai_FilePath) -> AIResult<PathBuf> {
+    let suite = SuiteGuard::<AIFilePathSuite>::acquire()?;
+    full_path(&suite, raw)
+}
+
+fn full_path(suite: &AIFilePathSuite, raw: &ai_FilePath) -> AIResult<PathBuf> {
+    let mut full_path = UnicodeString::empty()?;
+
+    unsafe {
+        suite
+            .GetFullPath
+            .call((raw as *const _, false as AIBool8, full_path.as_mut_ptr()))?;
+    }
+    Ok(PathBuf::from(full_path.to_string_lossy()))
+}
+
+impl Drop for FilePath {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = self.suite.DeleteFilePath.call((&mut self.raw as *mut _,));
+        }
+    }
+}
@@ -0,0 +1,352 @@
+use std::cell::RefCell;
+use std::ffi::{c_char, c_void, CStr};
+use std::ptr::null_mut;
+use std::rc::Rc;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::art::{Art, ArtAttributes, ArtType};
+use crate::bytes::{from_bytes, to_bytes};
+use crate::error::{AIError, AIResult, SuiteFn};
+use crate::externs::plugin_ref;
+use crate::layer::Layer;
+use crate::menu::MenuItem;
+use crate::path::Path;
+use crate::util::{self, read_c_string, Registry};
+
+/// フィルターメニューに登録されたフィルター
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FilterItem(AIFilterHandle);
+
+impl FilterItem {
+    /// # Safety
+    /// `handle` は `AIFilterSuite` が返した有効なフィルターでなければならない。
+    pub unsafe fn from_raw(handle: AIFilterHandle) -> Option<Self> {
+        (!handle.is_null()).then_some(Self(handle))
+    }
+
+    pub fn as_raw(self) -> AIFilterHandle {
+        self.0
+    }
+
+    /// `AddFilter` に渡した一意な名前
+    pub fn name(self) -> AIResult<String> {
+        let suite = SuiteGuard::<AIFilterSuite>::acquire()?;
+        let mut name: *mut c_char = null_mut();
+
+        unsafe {
+            suite.GetFilterName.call((self.0, &mut name as *mut _))?;
+            read_c_string(name)
+        }
+    }
+
+    /// フィルターメニューの項目
+    pub fn menu_item(self) -> AIResult<MenuItem> {
+        let suite = SuiteGuard::<AIFilterSuite>::acquire()?;
+        let mut item: AIMenuItemHandle = null_mut();
+
+        unsafe {
+            suite.GetFilterMenuItem.call((self.0, &mut item as *mut _))?;
+            MenuItem::from_raw(item).ok_or(AIError::CantHappen)
+        }
+    }
+}
+
+/// フィルターの振る舞い
+///
+/// `Parameters` は実行ごとのパラメータで、ホストが最後の実行時の値を保持する。
+/// 「フィルターを再実行」では `edit_parameters` を呼ばずに、前回の値で `go` が呼ばれる。
+#[allow(unused_variables)]
+pub trait Filter<P> {
+    type Parameters: Serialize + DeserializeOwned + Default;
+
+    /// 実行前にパラメータを編集する（ダイアログを閉じたら `Err(AIError::Canceled)` を返す）
+    fn edit_parameters(
+        &mut self,
+        plugin: &mut P,
+        filter: FilterItem,
+        parameters: &mut Self::Parameters,
+    ) -> AIResult<()> {
+        Ok(())
+    }
+
+    /// 現在の選択（`Selection::current`）に対してフィルターを実行する
+    fn go(&mut self, plugin: &mut P, filter: FilterItem, parameters: &Self::Parameters) -> AIResult<()>;
+}
+
+/// パラメータのブロックの先頭にあるデータ長
+const LEN_HEADER: usize = std::mem::size_of::<u32>();
+
+/// `AIFilterMessage::parameters` のブロックから値を読む（ブロックがなければ `None`）
+///
+/// ブロックは `store_parameters` が確保した、データ長（u32 LE）に続く `to_bytes` の出力。
+/// SPBlocks はブロックの大きさを返さないので、確保したときの大きさ `stored` と照らし合わせる。
+/// 自分で確保したブロックでない、データ長が大きさを超える、読み込みに失敗するときは `T::default()` にする。
+unsafe fn load_parameters<T: DeserializeOwned + Default>(
+    block: PlatformFilterParameters,
+    stored: StoredBlock,
+) -> Option<T> {
+    if block.is_null() {
+        return None;
+    }
+    if block != stored.block || stored.size < LEN_HEADER {
+        return Some(T::default());
+    }
+
+    let mut header = [0u8; LEN_HEADER];
+    std::ptr::copy_nonoverlapping(block as *const u8, header.as_mut_ptr(), LEN_HEADER);
+    let len = u32::from_le_bytes(header) as usize;
+    if len > stored.size - LEN_HEADER {
+        return Some(T::default());
+    }
+
+    let bytes = std::slice::from_raw_parts((block as *const u8).add(LEN_HEADER), len);
+    Some(from_bytes(bytes).unwrap_or_default())
+}
+
+/// 値をブロックに書き、そのブロックを返す（`block` があれば再確保して使い回す）
+fn store_parameters<T: Serialize>(block: PlatformFilterParameters, value: &T) -> AIResult<StoredBlock> {
+    let suite = SuiteGuard::<SPBlocksSuite>::acquire()?;
+    let bytes = to_bytes(value)?;
+    let len = u32::try_from(bytes.len()).map_err(|_| AIError::BadParameter)?;
+    let debug = b"illustrator-rs filter parameters\0".as_ptr() as *const c_char;
+    let mut stored: *mut c_void = null_mut();
+
+    unsafe {
+        if block.is_null() {
+            suite.AllocateBlock.call((LEN_HEADER + bytes.len(), debug, &mut stored as *mut _))?;
+        } else {
+            suite.ReallocateBlock.call((block, LEN_HEADER + bytes.len(), debug, &mut stored as *mut _))?;
+        }
+        if stored.is_null() {
+            return Err(AIError::CantHappen);
+        }
+
+        let data = stored as *mut u8;
+        std::ptr::copy_nonoverlapping(len.to_le_bytes().as_ptr(), data, LEN_HEADER);
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), data.add(LEN_HEADER), bytes.len());
+    }
+    Ok(StoredBlock {
+        block: stored,
+        size: LEN_HEADER + bytes.len(),
+    })
+}
+
+/// `store_parameters` が確保したブロックとその大きさ
+#[derive(Clone, Copy)]
+struct StoredBlock {
+    block: PlatformFilterParameters,
+    size: usize,
+}
+
+impl StoredBlock {
+    const NONE: Self = Self {
+        block: null_mut(),
+        size: 0,
+    };
+}
+
+/// `Filter` と最後に使ったパラメータ
+struct Registered<F, T> {
+    filter: F,
+    parameters: T,
+    stored: StoredBlock,
+}
+
+/// `Parameters` の型を隠してメッセージを `Filter` へ渡す
+pub(crate) trait Handler<P> {
+    fn parameters(&mut self, plugin: &mut P, message: &mut AIFilterMessage) -> AIResult<()>;
+    fn go(&mut self, plugin: &mut P, message: &mut AIFilterMessage) -> AIResult<()>;
+}
+
+impl<P, F: Filter<P>> Handler<P> for Registered<F, F::Parameters> {
+    fn parameters(&mut self, plugin: &mut P, message: &mut AIFilterMessage) -> AIResult<()> {
+        let filter = unsafe { FilterItem::from_raw(message.filter) }.ok_or(AIError::BadParameter)?;
+        if let Some(parameters) = unsafe { load_parameters(message.parameters, self.stored) } {
+            self.parameters = parameters;
+        }
+
+        self.filter.edit_parameters(plugin, filter, &mut self.parameters)?;
+        // 確保していないブロックは再確保せず、新しいブロックに書く
+        let block = if message.parameters == self.stored.block { message.parameters } else { null_mut() };
+        self.stored = store_parameters(block, &self.parameters)?;
+        message.parameters = self.stored.block;
+        Ok(())
+    }
+
+    fn go(&mut self, plugin: &mut P, message: &mut AIFilterMessage) -> AIResult<()> {
+        let filter = unsafe { FilterItem::from_raw(message.filter) }.ok_or(AIError::BadParameter)?;
+        if let Some(parameters) = unsafe { load_parameters(message.parameters, self.stored) } {
+            self.parameters = parameters;
+        }
+
+        self.filter.go(plugin, filter, &self.parameters)
+    }
+}
+
+/// プラグインが登録したフィルターと、その振る舞い
+///
+/// 起動時（`SafePlugin::startup`）にフィルターを登録し、`SafePlugin::filters` から返すと、
+/// `kCallerAIFilter` のメッセージが `Filter` へ送られる。
+pub struct Filters<P> {
+    entries: Registry<FilterItem, dyn Handler<P>>,
+}
+
+impl<P> Default for Filters<P> {
+    fn default() -> Self {
+        Self { entries: Registry::default() }
+    }
+}
+
+impl<P> Filters<P> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// フィルターを登録し、`filter` と結び付ける
+    ///
+    /// `name` はフィルターを識別する一意な名前、`category` はフィルターメニューのサブメニュー、
+    /// `title` はメニュー項目の表示名（ダイアログを開くなら末尾に `...` を付ける）。
+    /// `category` と `title` は Pascal 文字列として渡すため、255 バイトを超える部分は切り捨てられる。
+    pub fn add<F>(&mut self, name: &CStr, category: &str, title: &str, filter: F) -> AIResult<FilterItem>
+    where
+        F: Filter<P> + 'static,
+        F::Parameters: 'static,
+    {
+        let suite = SuiteGuard::<AIFilterSuite>::acquire()?;
+        let mut category = pascal(category);
+        let mut title = pascal(title);
+        let mut handle: AIFilterHandle = null_mut();
+
+        let mut data = PlatformAddFilterData {
+            category: category.as_mut_ptr(),
+            title: title.as_mut_ptr(),
+        };
+
+        let registered = unsafe {
+            suite.AddFilter.call((
+                plugin_ref(),
+                name.as_ptr() as *mut c_char,
+                &mut data as *mut _,
+                0,
+                &mut handle as *mut _,
+            ))?;
+            FilterItem::from_raw(handle).ok_or(AIError::CantHappen)?
+        };
+
+        let behavior = Registered {
+            filter,
+            parameters: F::Parameters::default(),
+            stored: StoredBlock::NONE,
+        };
+        self.entries.push(registered, Rc::new(RefCell::new(behavior)), ());
+        Ok(registered)
+    }
+
+    /// 登録したフィルター（登録順）
+    pub fn filters(&self) -> impl Iterator<Item = FilterItem> + '_ {
+        self.entries.keys()
+    }
+
+    pub fn contains(&self, filter: AIFilterHandle) -> bool {
+        self.entries.contains(FilterItem(filter))
+    }
+}
+
+/// 先頭 1 バイトが長さの Pascal 文字列（UTF-8 の文字の途中では切らない）
+fn pascal(text: &str) -> [u8; 256] {
+    let mut end = text.len().min(255);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    let mut buffer = [0u8; 256];
+    buffer[0] = end as u8;
+    buffer[1..=end].copy_from_slice(&text.as_bytes()[..end]);
+    buffer
+}
+
+/// `kCallerAIFilter` のメッセージを `call` で `Filter` へ送る
+pub(crate) fn dispatch<P>(
+    plugin: &mut P,
+    filters: fn(&mut P) -> Option<&mut Filters<P>>,
+    message: *mut AIFilterMessage,
+    call: fn(&mut dyn Handler<P>, &mut P, &mut AIFilterMessage) -> AIResult<()>,
+) -> Option<AIResult<()>> {
+    let message = unsafe { message.as_mut() }?;
+    let filter = FilterItem(message.filter);
+    util::dispatch(plugin, |plugin| Some(&filters(plugin)?.entries), filter, |behavior, plugin, ()| {
+        call(behavior, plugin, message)
+    })
+}
+
+/// 選択中のアート
+///
+/// 最上位のレイヤーから、親が先・重ね順の上からの順でアートツリーをたどって集める。
+/// レイヤーのグループそのものは含まない。
+#[derive(Clone, Debug, Default)]
+pub struct Selection {
+    /// アートと、完全に選択された親を持たないか
+    entries: Vec<(Art, bool)>,
+}
+
+impl Selection {
+    /// 現在のドキュメントの選択
+    pub fn current() -> AIResult<Self> {
+        let mut selection = Self::default();
+        for layer in Layer::all() {
+            selection.collect(layer?.group()?, false)?;
+        }
+        Ok(selection)
+    }
+
+    fn collect(&mut self, parent: Art, parent_selected: bool) -> AIResult<()> {
+        for child in parent.children() {
+            let child = child?;
+            if child.is_layer_group()? {
+                self.collect(child, false)?;
+                continue;
+            }
+
+            if child.is_selected()? {
+                self.entries.push((child, !parent_selected));
+            }
+            // グループの一部だけが選択されているときも親は選択状態になるので、完全な選択かで区別する
+            let fully_selected = child.has_attribute(ArtAttributes::FULLY_SELECTED)?;
+            self.collect(child, parent_selected || fully_selected)?;
+        }
+        Ok(())
+    }
+
+    /// 選択中のすべてのアート（選択されたグループの中身も含む）
+    pub fn art(&self) -> impl Iterator<Item = Art> + '_ {
+        self.entries.iter().map(|&(art, _)| art)
+    }
+
+    /// 完全に選択されたグループの中身を除いた、最上位の選択中のアート
+    pub fn top_level(&self) -> impl Iterator<Item = Art> + '_ {
+        self.entries.iter().filter(|(_, top_level)| *top_level).map(|&(art, _)| art)
+    }
+
+    /// 選択中のパス（グループや複合パスの中のパスを含む）
+    pub fn paths(&self) -> AIResult<Vec<Path>> {
+        let mut paths = Vec::new();
+        for art in self.art() {
+            if art.art_type()? == ArtType::Path {
+                paths.push(art.as_path()?);
+            }
+        }
+        Ok(paths)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,86 @@
+use crate::ai_sys::*;
+
+/// ドキュメント座標の点（`AIRealPoint`）
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub const fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<AIRealPoint> for Point {
+    fn from(point: AIRealPoint) -> Self {
+        Self::new(point.h, point.v)
+    }
+}
+
+impl From<Point> for AIRealPoint {
+    fn from(point: Point) -> Self {
+        AIRealPoint { h: point.x, v: point.y }
+    }
+}
+
+/// ドキュメント座標の矩形（`AIRealRect`）
+///
+/// Illustrator の座標系は y 軸が上向きなので、`top >= bottom` となる。
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub left: f64,
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+}
+
+impl Rect {
+    pub const fn new(left: f64, top: f64, right: f64, bottom: f64) -> Self {
+        Self { left, top, right, bottom }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> f64 {
+        self.top - self.bottom
+    }
+
+    pub fn center(&self) -> Point {
+        Point::new((self.left + self.right) / 2.0, (self.top + self.bottom) / 2.0)
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        (self.left..=self.right).contains(&point.x) && (self.bottom..=self.top).contains(&point.y)
+    }
+
+    /// 両方を含む最小の矩形
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect::new(
+            self.left.min(other.left),
+            self.top.max(other.top),
+            self.right.max(other.right),
+            self.bottom.min(other.bottom),
+        )
+    }
+}
+
+impl From<AIRealRect> for Rect {
+    fn from(rect: AIRealRect) -> Self {
+        Self::new(rect.left, rect.top, rect.right, rect.bottom)
+    }
+}
+
+impl From<Rect> for AIRealRect {
+    fn from(rect: Rect) -> Self {
+        AIRealRect {
+            left: rect.left,
+            top: rect.top,
+            right: rect.right,
+            bottom: rect.bottom,
+        }
+    }
+}
@@ -0,0 +1,555 @@
+use std::ffi::CStr;
+use std::ptr::null_mut;
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::art::{Art, PaintOrder};
+use crate::error::{AIError, AIResult, SuiteFn};
+use crate::unicode::UnicodeString;
+use crate::util::flags;
+
+/// レイヤーパネルでの表示色（`AIRGBColor`、各成分 0〜65535）
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LayerColor {
+    pub red: u16,
+    pub green: u16,
+    pub blue: u16,
+}
+
+impl LayerColor {
+    pub const fn new(red: u16, green: u16, blue: u16) -> Self {
+        Self { red, green, blue }
+    }
+
+    /// 各成分 0〜255 の色から作る
+    pub const fn from_rgb8(red: u8, green: u8, blue: u8) -> Self {
+        // 255 * 257 = 65535
+        Self::new(red as u16 * 257, green as u16 * 257, blue as u16 * 257)
+    }
+
+    /// 各成分 0〜255 に丸める
+    pub const fn to_rgb8(self) -> (u8, u8, u8) {
+        ((self.red / 257) as u8, (self.green / 257) as u8, (self.blue / 257) as u8)
+    }
+}
+
+impl From<AIRGBColor> for LayerColor {
+    fn from(color: AIRGBColor) -> Self {
+        Self::new(color.red, color.green, color.blue)
+    }
+}
+
+impl From<LayerColor> for AIRGBColor {
+    fn from(color: LayerColor) -> Self {
+        AIRGBColor {
+            red: color.red,
+            green: color.green,
+            blue: color.blue,
+        }
+    }
+}
+
+flags! {
+    /// レイヤーの表示・編集・印刷に関する設定
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct LayerFlags(u32) {
+        const VISIBLE = 1 << 0;
+        /// ロックされている（`GetLayerEditable` の逆）
+        const LOCKED = 1 << 1;
+        const PRINTED = 1 << 2;
+        /// プレビュー表示（含まなければアウトライン表示）
+        const PREVIEW = 1 << 3;
+        const TEMPLATE = 1 << 4;
+        const DIM_PLACED_IMAGES = 1 << 5;
+    }
+}
+
+impl Default for LayerFlags {
+    /// 新規レイヤーと同じ設定
+    fn default() -> Self {
+        Self::VISIBLE | Self::PRINTED | Self::PREVIEW
+    }
+}
+
+type GetFlag = Option<unsafe extern "C" fn(AILayerHandle, *mut AIBoolean) -> AIErr>;
+type SetFlag = Option<unsafe extern "C" fn(AILayerHandle, AIBoolean) -> AIErr>;
+
+/// ドキュメントのレイヤー（`AILayerHandle`）
+///
+/// 関数は現在のレイヤーリストのレイヤーに作用する。サブレイヤーは親レイヤーのグループアートの中にある。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Layer(AILayerHandle);
+
+impl Layer {
+    /// # Safety
+    /// `handle` は現在のドキュメントの有効なレイヤーでなければならない。
+    pub unsafe fn from_raw(handle: AILayerHandle) -> Option<Self> {
+        (!handle.is_null()).then_some(Self(handle))
+    }
+
+    pub fn as_raw(self) -> AILayerHandle {
+        self.0
+    }
+
+    /// 最上位のレイヤーの数（サブレイヤーは含まない）
+    pub fn count() -> AIResult<usize> {
+        let suite = SuiteGuard::<AILayerSuite>::acquire()?;
+        let mut count: ai_int32 = 0;
+
+        unsafe { suite.CountLayers.call((&mut count as *mut _,))? };
+        Ok(count.max(0) as usize)
+    }
+
+    /// 上から `index` 番目の最上位のレイヤー
+    pub fn nth(index: usize) -> AIResult<Layer> {
+        let suite = SuiteGuard::<AILayerSuite>::acquire()?;
+        let index = ai_int32::try_from(index).map_err(|_| AIError::BadParameter)?;
+        let mut layer: AILayerHandle = null_mut();
+
+        unsafe {
+            suite.GetNthLayer.call((index, &mut layer as *mut _))?;
+            Layer::from_raw(layer).ok_or(AIError::BadParameter)
+        }
+    }
+
+    /// 新しいアートが作られるレイヤー
+    pub fn current() -> AIResult<Layer> {
+        let suite = SuiteGuard::<AILayerSuite>::acquire()?;
+        let mut layer: AILayerHandle = null_mut();
+
+        unsafe {
+            suite.GetCurrentLayer.call((&mut layer as *mut _,))?;
+            Layer::from_raw(layer).ok_or(AIError::NoDocument)
+        }
+    }
+
+    pub fn make_current(self) -> AIResult<()> {
+        let suite = SuiteGuard::<AILayerSuite>::acquire()?;
+        unsafe { suite.SetCurrentLayer.call((self.0,)) }
+    }
+
+    /// 最前面の最上位のレイヤー
+    pub fn first() -> AIResult<Option<Layer>> {
+        let suite = SuiteGuard::<AILayerSuite>::acquire()?;
+        let mut layer: AILayerHandle = null_mut();
+
+        unsafe {
+            suite.GetFirstLayer.call((&mut layer as *mut _,))?;
+            Ok(Layer::from_raw(layer))
+        }
+    }
+
+    /// 最上位のレイヤーを上から列挙する
+    pub fn all() -> Layers {
+        Layers {
+            next: Layer::first().transpose(),
+            step: Layer::next,
+        }
+    }
+
+    /// サブレイヤーも含めたすべてのレイヤーをレイヤーパネルの表示順（親が先）で列挙する
+    pub fn preorder() -> Layers {
+        Layers {
+            next: Layer::first().transpose(),
+            step: Layer::next_preorder,
+        }
+    }
+
+    /// タイトルが一致する最初のレイヤー
+    pub fn by_title(title: &str) -> AIResult<Option<Layer>> {
+        let suite = SuiteGuard::<AILayerSuite>::acquire()?;
+        let title = UnicodeString::new(title)?;
+        let mut layer: AILayerHandle = null_mut();
+
+        unsafe {
+            suite.GetLayerByTitle.call((&mut layer as *mut _, title.as_ptr()))?;
+            Ok(Layer::from_raw(layer))
+        }
+    }
+
+    /// 新しいレイヤーを作る
+    ///
+    /// `Above`/`Below` は `relative` と同じ階層に、`InsideOnTop`/`InsideOnBottom` は
+    /// `relative` のサブレイヤーとして作る。`relative` が `None` なら最上位の最前面または最背面。
+    pub fn insert(paint_order: PaintOrder, relative: Option<Layer>) -> AIResult<Layer> {
+        let suite = SuiteGuard::<AILayerSuite>::acquire()?;
+        let mut layer: AILayerHandle = null_mut();
+
+        unsafe {
+            suite
+                .InsertLayer
+                .call((relative.map_or(null_mut(), Layer::as_raw), paint_order.to_raw(), &mut layer as *mut _))?;
+            Layer::from_raw(layer).ok_or(AIError::CantHappen)
+        }
+    }
+
+    /// レイヤーとその中のアートを削除する
+    pub fn delete(self) -> AIResult<()> {
+        let suite = SuiteGuard::<AILayerSuite>::acquire()?;
+        unsafe { suite.DeleteLayer.call((self.0,)) }
+    }
+
+    /// `target` を基準に移動する（`paint_order` の意味は `insert` と同じ）
+    ///
+    /// レイヤーのグループアートを `AIArtSuite::ReorderArt` で移動する。
+    pub fn move_to(self, paint_order: PaintOrder, target: Layer) -> AIResult<()> {
+        self.group()?.reorder(paint_order, Some(target.group()?))
+    }
+
+    /// 同じ階層の一つ下のレイヤー
+    pub fn next(self) -> AIResult<Option<Layer>> {
+        self.relative(|suite| suite.GetNextLayer)
+    }
+
+    /// 同じ階層の一つ上のレイヤー
+    pub fn prev(self) -> AIResult<Option<Layer>> {
+        self.relative(|suite| suite.GetPrevLayer)
+    }
+
+    /// レイヤーパネルの表示順で次のレイヤー（サブレイヤーを含む）
+    pub fn next_preorder(self) -> AIResult<Option<Layer>> {
+        self.relative(|suite| suite.GetNextPreorderLayer)
+    }
+
+    /// 親レイヤー（最上位のレイヤーなら `None`）
+    pub fn parent(self) -> AIResult<Option<Layer>> {
+        self.relative(|suite| suite.GetLayerParent)
+    }
+
+    /// 最前面のサブレイヤー
+    pub fn first_child(self) -> AIResult<Option<Layer>> {
+        self.relative(|suite| suite.GetLayerFirstChild)
+    }
+
+    /// サブレイヤーを上から列挙する
+    pub fn sublayers(self) -> Layers {
+        Layers {
+            next: self.first_child().transpose(),
+            step: Layer::next,
+        }
+    }
+
+    fn relative(
+        self,
+        function: impl FnOnce(&AILayerSuite) -> Option<unsafe extern "C" fn(AILayerHandle, *mut AILayerHandle) -> AIErr>,
+    ) -> AIResult<Option<Layer>> {
+        let suite = SuiteGuard::<AILayerSuite>::acquire()?;
+        let mut layer: AILayerHandle = null_mut();
+
+        unsafe {
+            function(&suite).call((self.0, &mut layer as *mut _))?;
+            Ok(Layer::from_raw(layer))
+        }
+    }
+
+    /// レイヤーの内容を保持するグループアート（サブレイヤーのグループもこの中にある）
+    pub fn group(self) -> AIResult<Art> {
+        unsafe { Art::first_of_layer(self.0) }
+    }
+
+    pub fn title(self) -> AIResult<String> {
+        let suite = SuiteGuard::<AILayerSuite>::acquire()?;
+        let mut title = UnicodeString::empty()?;
+
+        unsafe { suite.GetLayerTitle.call((self.0, title.as_mut_ptr()))? };
+        Ok(title.to_string_lossy())
+    }
+
+    pub fn set_title(self, title: &str) -> AIResult<()> {
+        let suite = SuiteGuard::<AILayerSuite>::acquire()?;
+        let title = UnicodeString::new(title)?;
+        unsafe { suite.SetLayerTitle.call((self.0, title.as_ptr())) }
+    }
+
+    pub fn color(self) -> AIResult<LayerColor> {
+        let suite = SuiteGuard::<AILayerSuite>::acquire()?;
+        let mut color: AIRGBColor = LayerColor::default().into();
+
+        unsafe { suite.GetLayerColor.call((self.0, &mut color as *mut _))? };
+        Ok(color.into())
+    }
+
+    pub fn set_color(self, color: LayerColor) -> AIResult<()> {
+        let suite = SuiteGuard::<AILayerSuite>::acquire()?;
+        unsafe { suite.SetLayerColor.call((self.0, AIRGBColor::from(color))) }
+    }
+
+    fn flag(self, get: impl FnOnce(&AILayerSuite) -> GetFlag) -> AIResult<bool> {
+        let suite = SuiteGuard::<AILayerSuite>::acquire()?;
+        let mut value: AIBoolean = 0;
+
+        unsafe { get(&suite).call((self.0, &mut value as *mut _))? };
+        Ok(value != 0)
+    }
+
+    fn set_flag(self, set: impl FnOnce(&AILayerSuite) -> SetFlag, value: bool) -> AIResult<()> {
+        let suite = SuiteGuard::<AILayerSuite>::acquire()?;
+        unsafe { set(&suite).call((self.0, value as AIBoolean)) }
+    }
+
+    pub fn is_visible(self) -> AIResult<bool> {
+        self.flag(|suite| suite.GetLayerVisible)
+    }
+
+    pub fn set_visible(self, visible: bool) -> AIResult<()> {
+        self.set_flag(|suite| suite.SetLayerVisible, visible)
+    }
+
+    pub fn is_locked(self) -> AIResult<bool> {
+        Ok(!self.flag(|suite| suite.GetLayerEditable)?)
+    }
+
+    pub fn set_locked(self, locked: bool) -> AIResult<()> {
+        self.set_flag(|suite| suite.SetLayerEditable, !locked)
+    }
+
+    pub fn is_printed(self) -> AIResult<bool> {
+        self.flag(|suite| suite.GetLayerPrinted)
+    }
+
+    pub fn set_printed(self, printed: bool) -> AIResult<()> {
+        self.set_flag(|suite| suite.SetLayerPrinted, printed)
+    }
+
+    pub fn is_preview(self) -> AIResult<bool> {
+        self.flag(|suite| suite.GetLayerPreview)
+    }
+
+    pub fn set_preview(self, preview: bool) -> AIResult<()> {
+        self.set_flag(|suite| suite.SetLayerPreview, preview)
+    }
+
+    pub fn is_template(self) -> AIResult<bool> {
+        self.flag(|suite| suite.GetLayerIsTemplate)
+    }
+
+    pub fn set_template(self, template: bool) -> AIResult<()> {
+        self.set_flag(|suite| suite.SetLayerIsTemplate, template)
+    }
+
+    pub fn dims_placed_images(self) -> AIResult<bool> {
+        self.flag(|suite| suite.GetLayerDimPlacedImages)
+    }
+
+    pub fn set_dim_placed_images(self, dimmed: bool) -> AIResult<()> {
+        self.set_flag(|suite| suite.SetLayerDimPlacedImages, dimmed)
+    }
+
+    /// 表示・ロック・印刷などの設定をまとめて取得する
+    pub fn flags(self) -> AIResult<LayerFlags> {
+        let mut flags = LayerFlags::empty();
+        for (flag, set) in [
+            (LayerFlags::VISIBLE, self.is_visible()?),
+            (LayerFlags::LOCKED, self.is_locked()?),
+            (LayerFlags::PRINTED, self.is_printed()?),
+            (LayerFlags::PREVIEW, self.is_preview()?),
+            (LayerFlags::TEMPLATE, self.is_template()?),
+            (LayerFlags::DIM_PLACED_IMAGES, self.dims_placed_images()?),
+        ] {
+            if set {
+                flags |= flag;
+            }
+        }
+        Ok(flags)
+    }
+
+    /// 設定をまとめて変更する
+    ///
+    /// テンプレートにすると Illustrator がロックと画像の淡色表示を変えるため、テンプレートを先に設定する。
+    pub fn set_flags(self, flags: LayerFlags) -> AIResult<()> {
+        self.set_template(flags.contains(LayerFlags::TEMPLATE))?;
+        self.set_visible(flags.contains(LayerFlags::VISIBLE))?;
+        self.set_locked(flags.contains(LayerFlags::LOCKED))?;
+        self.set_printed(flags.contains(LayerFlags::PRINTED))?;
+        self.set_preview(flags.contains(LayerFlags::PREVIEW))?;
+        self.set_dim_placed_images(flags.contains(LayerFlags::DIM_PLACED_IMAGES))
+    }
+
+    /// レイヤーパネルで選択されているか
+    pub fn is_selected(self) -> AIResult<bool> {
+        self.flag(|suite| suite.GetLayerSelected)
+    }
+
+    pub fn set_selected(self, selected: bool) -> AIResult<()> {
+        self.set_flag(|suite| suite.SetLayerSelected, selected)
+    }
+
+    pub fn has_art(self) -> AIResult<bool> {
+        self.flag(|suite| suite.LayerHasArt)
+    }
+
+    pub fn has_selected_art(self) -> AIResult<bool> {
+        self.flag(|suite| suite.LayerHasSelectedArt)
+    }
+
+    /// レイヤー上のアートをすべて選択する
+    pub fn select_art(self) -> AIResult<()> {
+        let suite = SuiteGuard::<AILayerSuite>::acquire()?;
+        unsafe { suite.SelectArtOnLayer.call((self.0,)) }
+    }
+
+    /// レイヤー上のアートの選択をすべて解除する
+    pub fn deselect_art(self) -> AIResult<()> {
+        let suite = SuiteGuard::<AILayerSuite>::acquire()?;
+        unsafe { suite.DeselectArtOnLayer.call((self.0,)) }
+    }
+}
+
+/// レイヤーを順にたどるイテレータ
+///
+/// エラーが起きた場合はそれを一度返して終了する。
+pub struct Layers {
+    next: Option<AIResult<Layer>>,
+    step: fn(Layer) -> AIResult<Option<Layer>>,
+}
+
+impl Iterator for Layers {
+    type Item = AIResult<Layer>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+
+        if let Ok(layer) = current {
+            self.next = (self.step)(layer).transpose();
+        }
+        Some(current)
+    }
+}
+
+/// レイヤーリスト（`AILayerList`）
+///
+/// レイヤーリストはスタックになっており、最下段がドキュメントのレイヤーリスト、
+/// 最上段がユーザーが編集中の現在のレイヤーリスト（透明マスクの編集中など）。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LayerList(AILayerList);
+
+impl LayerList {
+    /// # Safety
+    /// `list` は現在のドキュメントの有効なレイヤーリストでなければならない。
+    pub unsafe fn from_raw(list: AILayerList) -> Option<Self> {
+        (!list.is_null()).then_some(Self(list))
+    }
+
+    pub fn as_raw(self) -> AILayerList {
+        self.0
+    }
+
+    /// スタックにあるレイヤーリストの数
+    pub fn count() -> AIResult<usize> {
+        let suite = SuiteGuard::<AILayerListSuite>::acquire()?;
+        let mut count: ai_int32 = 0;
+
+        unsafe { suite.Count.call((&mut count as *mut _,))? };
+        Ok(count.max(0) as usize)
+    }
+
+    /// 現在のレイヤーリスト（スタックの最上段）
+    pub fn current() -> AIResult<LayerList> {
+        Self::end(|suite| suite.GetFirst)
+    }
+
+    /// ドキュメントのレイヤーリスト（スタックの最下段）
+    pub fn document() -> AIResult<LayerList> {
+        Self::end(|suite| suite.GetLast)
+    }
+
+    fn end(
+        function: impl FnOnce(&AILayerListSuite) -> Option<unsafe extern "C" fn(*mut AILayerList) -> AIErr>,
+    ) -> AIResult<LayerList> {
+        let suite = SuiteGuard::<AILayerListSuite>::acquire()?;
+        let mut list: AILayerList = null_mut();
+
+        unsafe {
+            function(&suite).call((&mut list as *mut _,))?;
+            LayerList::from_raw(list).ok_or(AIError::NoDocument)
+        }
+    }
+
+    /// スタックの上から列挙する
+    pub fn all() -> LayerLists {
+        LayerLists { next: Some(LayerList::current()) }
+    }
+
+    /// 一つ下のレイヤーリスト
+    pub fn next(self) -> AIResult<Option<LayerList>> {
+        let suite = SuiteGuard::<AILayerListSuite>::acquire()?;
+        let mut list: AILayerList = null_mut();
+
+        unsafe {
+            suite.GetNext.call((self.0, &mut list as *mut _))?;
+            Ok(LayerList::from_raw(list))
+        }
+    }
+
+    /// アートを含むレイヤーリストとレイヤー
+    pub fn of_art(art: Art) -> AIResult<(LayerList, Layer)> {
+        let suite = SuiteGuard::<AILayerListSuite>::acquire()?;
+        let mut list: AILayerList = null_mut();
+        let mut layer: AILayerHandle = null_mut();
+
+        unsafe {
+            suite
+                .GetLayerOfArt
+                .call((art.as_raw(), &mut list as *mut _, &mut layer as *mut _))?;
+            match (LayerList::from_raw(list), Layer::from_raw(layer)) {
+                (Some(list), Some(layer)) => Ok((list, layer)),
+                _ => Err(AIError::BadParameter),
+            }
+        }
+    }
+
+    /// リストを作ったときのタグ（`kAIDocumentLayerList` など）
+    pub fn tag(self) -> AIResult<String> {
+        let suite = SuiteGuard::<AILayerListSuite>::acquire()?;
+        let get_tag = suite.GetTag.ok_or(AIError::NotImplemented)?;
+
+        let tag = unsafe { get_tag(self.0) };
+        if tag.is_null() {
+            return Ok(String::new());
+        }
+        Ok(unsafe { CStr::from_ptr(tag) }.to_string_lossy().into_owned())
+    }
+
+    /// 最上位のレイヤーの数
+    pub fn layer_count(self) -> AIResult<usize> {
+        let suite = SuiteGuard::<AILayerListSuite>::acquire()?;
+        let mut count: ai_int32 = 0;
+
+        unsafe { suite.CountLayers.call((self.0, &mut count as *mut _))? };
+        Ok(count.max(0) as usize)
+    }
+
+    /// 最上位のレイヤーを上から列挙する
+    pub fn layers(self) -> AIResult<Vec<Layer>> {
+        let suite = SuiteGuard::<AILayerListSuite>::acquire()?;
+        let mut layers = Vec::new();
+        let mut layer: AILayerHandle = null_mut();
+
+        unsafe {
+            suite.GetFirstLayer.call((self.0, &mut layer as *mut _))?;
+            while let Some(current) = Layer::from_raw(layer) {
+                layers.push(current);
+                suite.GetNextLayer.call((self.0, layer, &mut layer as *mut _))?;
+            }
+        }
+        Ok(layers)
+    }
+}
+
+/// レイヤーリストのスタックを上からたどるイテレータ
+pub struct LayerLists {
+    next: Option<AIResult<LayerList>>,
+}
+
+impl Iterator for LayerLists {
+    type Item = AIResult<LayerList>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+
+        if let Ok(list) = current {
+            self.next = list.next().transpose();
+        }
+        Some(current)
+    }
+}
@@ -2,11 +2,78 @@
 
 
 mod plugin_base;
-mod externs;
+#[doc(hidden)]
+pub mod externs;
 mod ai_plugin;
+mod router;
+mod safe_plugin;
+mod util;
+
+pub mod action;
+pub mod ai_suites;
+pub mod art;
+pub mod artboard;
+pub mod bezier;
+pub mod bytes;
+pub mod dictionary;
+pub mod document;
+pub mod error;
+pub mod file_format;
+pub mod file_path;
+pub mod filter;
+pub mod geometry;
+pub mod layer;
+pub mod live_effect;
+pub mod menu;
+pub mod messages;
+pub mod notifier;
+pub mod path;
+pub mod plugin_group;
+pub mod preferences;
+pub mod panic_guard;
+#[cfg(feature = "svg")]
+pub mod svg;
+pub mod timer;
+pub mod tool;
+pub mod undo;
+pub mod unicode;
 
 
 pub use illustrator_sys as ai_sys;
-pub use plugin_base::define_plugin;
+pub use externs::{plugin_ref, Plugin};
+pub use action::{Action, ActionEvent, ActionParameters, ActionValues, Actions};
 pub use ai_plugin::AIPlugin;
-pub use plugin_base;
+pub use ai_suites::{AISuite, Suite, SuiteError, SuiteGuard, Suites};
+pub use art::{Art, ArtAttributes, ArtType, PaintOrder};
+pub use artboard::{ArtboardProperties, ArtboardRange, Artboards};
+pub use bezier::{BezierPath, CubicBezier, PathSegment};
+pub use dictionary::{Array, Dictionary, Entry, EntryType};
+pub use document::{
+    ColorModel, Document, DocumentChanges, DocumentSetup, DocumentSnapshot, DocumentView, DocumentWatcher,
+    RulerCoordinates, RulerUnits, ViewSnapshot,
+};
+pub use file_format::{
+    FileContext, FileFormat, FileFormatBuilder, FileFormats, FileOperation, Format, FormatCapabilities, Peek,
+};
+pub use file_path::FilePath;
+pub use filter::{Filter, FilterItem, Filters, Selection};
+pub use geometry::{Point, Rect};
+pub use layer::{Layer, LayerColor, LayerFlags, LayerList};
+pub use live_effect::{Effect, EffectFlags, EffectKind, InputArt, InputType, LiveEffect, LiveEffectBuilder, LiveEffects};
+pub use menu::{MenuCommands, MenuGroup, MenuGroupOptions, MenuItem, MenuUpdate, Menus};
+pub use notifier::{NotifierType, Notifiers, Subscription};
+pub use path::Path;
+pub use plugin_group::{
+    AfterEdit, Edit, EditPolicy, EditResponse, Operation, PluginArt, PluginGroup, PluginGroupBuilder, PluginGroupClass,
+    PluginGroupOptions, PluginGroups,
+};
+pub use preferences::{HostPreferences, MemoryPreferences, PreferenceStore, Preferences};
+pub use router::{MessageRouter, Route, RouteTable};
+pub use error::{kRustIoErr, kRustPanicErr, AIError, AIResult, SuiteFn, ToResult};
+pub use safe_plugin::SafePlugin;
+#[cfg(feature = "svg")]
+pub use svg::{ExportArea, SvgFormat, SvgOptions};
+pub use timer::{Debounce, Timer, Timers};
+pub use tool::{DragTracker, Modifiers, Tool, ToolBehavior, ToolEvent, ToolOptions, Tools};
+pub use undo::{UndoKind, UndoTransaction};
+pub use unicode::UnicodeString;
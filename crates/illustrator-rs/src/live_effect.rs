@@ -0,0 +1,475 @@
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr::{null, null_mut};
+use std::rc::Rc;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::art::Art;
+use crate::dictionary::Dictionary;
+use crate::error::{AIError, AIResult, SuiteFn};
+use crate::externs::plugin_ref;
+use crate::util::{self, flags, read_c_string, Registry};
+
+/// アピアランスパネル上のエフェクトの位置（`AIStyleFilterFlags` の種類）
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EffectKind {
+    /// 塗りの前に適用する
+    PreEffect,
+    /// 塗り・線の後に適用する
+    #[default]
+    PostEffect,
+    /// 線に適用する
+    Stroke,
+    /// 塗りに適用する
+    Fill,
+}
+
+impl EffectKind {
+    fn to_raw(self) -> AIStyleFilterFlags {
+        match self {
+            EffectKind::PreEffect => AIStyleFilterFlags_kPreEffectFilter,
+            EffectKind::PostEffect => AIStyleFilterFlags_kPostEffectFilter,
+            EffectKind::Stroke => AIStyleFilterFlags_kStrokeFilter,
+            EffectKind::Fill => AIStyleFilterFlags_kFillFilter,
+        }
+    }
+}
+
+flags! {
+    /// エフェクトが対応する機能（`AIStyleFilterFlags` の種類以外のビット）
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct EffectFlags(u32) {
+        const SPECIAL_GROUP_PRE_FILTER = AIStyleFilterFlags_kSpecialGroupPreFilter;
+        /// 拡大・縮小で `LiveEffect::scale_parameters` を受け取る
+        const SCALABLE_PARAMS = AIStyleFilterFlags_kHasScalableParams;
+        const AUTO_RASTERIZE = AIStyleFilterFlags_kUsesAutoRasterize;
+        const SVG_FILTER = AIStyleFilterFlags_kCanGenerateSVGFilter;
+        const ADJUST_COLORS = AIStyleFilterFlags_kHandlesAdjustColorsMsg;
+        const IS_COMPATIBLE = AIStyleFilterFlags_kHandlesIsCompatibleMsg;
+        const DOC_SCALE_CONVERTIBLE = AIStyleFilterFlags_kHasDocScaleConvertibleParams;
+        const PARALLEL_EXECUTION = AIStyleFilterFlags_kParallelExecutionFilter;
+    }
+}
+
+flags! {
+    /// エフェクトが受け取れる入力アート（`AIStyleFilterPreferredInputArtType`）
+    ///
+    /// 含まれない種類のアートは、ホストが受け取れる種類に変換してから渡す。
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct InputArt(u32) {
+        const GROUP = AIStyleFilterPreferredInputArtType_kGroupInputArt;
+        const PATH = AIStyleFilterPreferredInputArtType_kPathInputArt;
+        const COMPOUND_PATH = AIStyleFilterPreferredInputArtType_kCompoundPathInputArt;
+        const PLACED = AIStyleFilterPreferredInputArtType_kPlacedInputArt;
+        const MYSTERY_PATH = AIStyleFilterPreferredInputArtType_kMysteryPathInputArt;
+        const RASTER = AIStyleFilterPreferredInputArtType_kRasterInputArt;
+        const PLUGIN = AIStyleFilterPreferredInputArtType_kPluginInputArt;
+        const MESH = AIStyleFilterPreferredInputArtType_kMeshInputArt;
+        const TEXT_FRAME = AIStyleFilterPreferredInputArtType_kTextFrameInputArt;
+        const SYMBOL = AIStyleFilterPreferredInputArtType_kSymbolInputArt;
+        const FOREIGN = AIStyleFilterPreferredInputArtType_kForeignInputArt;
+        const CHART = AIStyleFilterPreferredInputArtType_kChartInputArt;
+        const ANY = AIStyleFilterPreferredInputArtType_kAnyInputArt;
+        const ANY_BUT_PLUGIN = AIStyleFilterPreferredInputArtType_kAnyInputArtButPluginArt;
+        /// 線をアウトライン化してから渡す
+        const OUTLINED_STROKE = AIStyleFilterPreferredInputArtType_kOutlinedStrokeInputArt;
+        /// クリッピングマスクを取り除いてから渡す
+        const NO_CLIP_MASKS = AIStyleFilterPreferredInputArtType_kNoClipMasksInputArt;
+    }
+}
+
+/// 入力アートの決め方
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InputType {
+    /// 常に同じ種類を受け取る
+    Fixed(InputArt),
+    /// パラメータと入力アートを見て `LiveEffect::input_type` で決める（`kInputArtDynamic`）
+    Dynamic,
+}
+
+impl Default for InputType {
+    fn default() -> Self {
+        InputType::Fixed(InputArt::ANY)
+    }
+}
+
+impl InputType {
+    fn to_raw(self) -> ai_int32 {
+        match self {
+            InputType::Fixed(art) => art.bits() as ai_int32,
+            InputType::Dynamic => AIStyleFilterPreferredInputArtType_kInputArtDynamic as ai_int32,
+        }
+    }
+}
+
+/// 登録されたライブエフェクト
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Effect(AILiveEffectHandle);
+
+impl Effect {
+    /// # Safety
+    /// `handle` は `AILiveEffectSuite` が返した有効なエフェクトでなければならない。
+    pub unsafe fn from_raw(handle: AILiveEffectHandle) -> Option<Self> {
+        (!handle.is_null()).then_some(Self(handle))
+    }
+
+    pub fn as_raw(self) -> AILiveEffectHandle {
+        self.0
+    }
+
+    /// 名前で探す（他のプラグインのエフェクトも含む）
+    pub fn find(name: &CStr) -> AIResult<Option<Effect>> {
+        let suite = SuiteGuard::<AILiveEffectSuite>::acquire()?;
+        let mut effect: AILiveEffectHandle = null_mut();
+
+        match unsafe { suite.GetLiveEffectHandleByName.call((name.as_ptr(), &mut effect as *mut _)) } {
+            Ok(()) => Ok(unsafe { Effect::from_raw(effect) }),
+            Err(AIError::BadParameter) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// `AddLiveEffect` に渡した一意な名前
+    pub fn name(self) -> AIResult<String> {
+        let suite = SuiteGuard::<AILiveEffectSuite>::acquire()?;
+        let mut name: *const c_char = null();
+
+        unsafe {
+            suite.GetLiveEffectName.call((self.0, &mut name as *mut _))?;
+            read_c_string(name)
+        }
+    }
+
+    /// アピアランスパネルに表示される名前
+    pub fn title(self) -> AIResult<String> {
+        let suite = SuiteGuard::<AILiveEffectSuite>::acquire()?;
+        let mut title: *const c_char = null();
+
+        unsafe {
+            suite.GetLiveEffectTitle.call((self.0, &mut title as *mut _))?;
+            read_c_string(title)
+        }
+    }
+
+    /// （メジャー, マイナー）バージョン
+    pub fn version(self) -> AIResult<(i32, i32)> {
+        let suite = SuiteGuard::<AILiveEffectSuite>::acquire()?;
+        let (mut major, mut minor): (ai_int32, ai_int32) = (0, 0);
+
+        unsafe { suite.GetLiveEffectVersion.call((self.0, &mut major as *mut _, &mut minor as *mut _))? };
+        Ok((major, minor))
+    }
+}
+
+/// ライブエフェクトの振る舞い
+///
+/// パラメータは `Params` としてエフェクトのパラメータ辞書に直接格納される
+/// （`Dictionary::store_fields`）。辞書が空のとき（新しいインスタンス）は `Params::default()` になる。
+/// `go` 以外は既定の実装がある。
+#[allow(unused_variables)]
+pub trait LiveEffect<P> {
+    type Params: Serialize + DeserializeOwned + Default + Clone;
+
+    /// エフェクトを適用し、結果のアートを返す（`art` をそのまま変更して返してもよい）
+    fn go(&mut self, plugin: &mut P, params: &Self::Params, art: Art) -> AIResult<Art>;
+
+    /// パラメータを編集する（ダイアログを閉じたら `Err(AIError::Canceled)` を返す）
+    fn edit_parameters(&mut self, plugin: &mut P, params: &mut Self::Params, is_new_instance: bool) -> AIResult<()> {
+        Ok(())
+    }
+
+    /// ブレンドなどで `start` から `end` へ `percent`（0.0〜1.0）だけ補間する
+    ///
+    /// 既定は 0.5 未満なら `start`、それ以外は `end`。
+    fn interpolate(
+        &mut self,
+        plugin: &mut P,
+        start: &Self::Params,
+        end: &Self::Params,
+        percent: f64,
+    ) -> AIResult<Self::Params> {
+        Ok(if percent < 0.5 { start.clone() } else { end.clone() })
+    }
+
+    /// オブジェクトの拡大・縮小に合わせてパラメータを変える（変えたら `true`）
+    ///
+    /// `EffectFlags::SCALABLE_PARAMS` を指定した場合のみ呼ばれる。
+    fn scale_parameters(&mut self, plugin: &mut P, params: &mut Self::Params, scale: f64) -> AIResult<bool> {
+        Ok(false)
+    }
+
+    /// ドキュメントのカラーモードの変更に合わせてパラメータ中の色を変換する
+    fn convert_color_space(&mut self, plugin: &mut P, params: &mut Self::Params, color_space: AIColorTag) -> AIResult<()> {
+        Ok(())
+    }
+
+    /// 入力アートの種類（`InputType::Dynamic` の場合のみ呼ばれる）
+    fn input_type(&mut self, plugin: &mut P, params: &Self::Params, art: Option<Art>) -> AIResult<InputArt> {
+        Ok(InputArt::ANY)
+    }
+}
+
+/// パラメータ辞書を `T` として読む（空なら既定値）
+fn load_params<T: DeserializeOwned + Default>(parameters: AILiveEffectParameters) -> AIResult<T> {
+    let dictionary = unsafe { Dictionary::from_borrowed(parameters)? }.ok_or(AIError::BadParameter)?;
+    if dictionary.is_empty()? {
+        return Ok(T::default());
+    }
+    Ok(dictionary.load_fields()?)
+}
+
+fn store_params<T: Serialize>(parameters: AILiveEffectParameters, params: &T) -> AIResult<()> {
+    let mut dictionary = unsafe { Dictionary::from_borrowed(parameters)? }.ok_or(AIError::BadParameter)?;
+    Ok(dictionary.store_fields(params)?)
+}
+
+/// `Params` の型を隠してメッセージを `LiveEffect` へ渡す
+pub(crate) trait Handler<P> {
+    fn edit(&mut self, plugin: &mut P, message: &mut AILiveEffectEditParamMessage) -> AIResult<()>;
+    fn go(&mut self, plugin: &mut P, message: &mut AILiveEffectGoMessage) -> AIResult<()>;
+    fn interpolate(&mut self, plugin: &mut P, message: &mut AILiveEffectInterpParamMessage) -> AIResult<()>;
+    fn input_type(&mut self, plugin: &mut P, message: &mut AILiveEffectInputTypeMessage) -> AIResult<()>;
+    fn scale(&mut self, plugin: &mut P, message: &mut AILiveEffectScaleParamMessage) -> AIResult<()>;
+    fn convert_color(&mut self, plugin: &mut P, message: &mut AILiveEffectConvertColorMessage) -> AIResult<()>;
+}
+
+impl<P, E: LiveEffect<P>> Handler<P> for E {
+    fn edit(&mut self, plugin: &mut P, message: &mut AILiveEffectEditParamMessage) -> AIResult<()> {
+        let is_new_instance = message.isNewInstance != 0;
+        let mut params = if is_new_instance {
+            E::Params::default()
+        } else {
+            load_params(message.parameters)?
+        };
+
+        self.edit_parameters(plugin, &mut params, is_new_instance)?;
+        store_params(message.parameters, &params)?;
+
+        let suite = SuiteGuard::<AILiveEffectSuite>::acquire()?;
+        unsafe { suite.UpdateParameters.call((message.context,)) }
+    }
+
+    fn go(&mut self, plugin: &mut P, message: &mut AILiveEffectGoMessage) -> AIResult<()> {
+        let params = load_params(message.parameters)?;
+        let art = unsafe { Art::from_raw(message.art) }.ok_or(AIError::BadParameter)?;
+
+        message.art = LiveEffect::go(self, plugin, &params, art)?.as_raw();
+        Ok(())
+    }
+
+    fn interpolate(&mut self, plugin: &mut P, message: &mut AILiveEffectInterpParamMessage) -> AIResult<()> {
+        let start = load_params(message.startParams)?;
+        let end = load_params(message.endParams)?;
+        let params = LiveEffect::interpolate(self, plugin, &start, &end, message.percent)?;
+        store_params(message.outParams, &params)
+    }
+
+    fn input_type(&mut self, plugin: &mut P, message: &mut AILiveEffectInputTypeMessage) -> AIResult<()> {
+        let params = load_params(message.parameters)?;
+        let art = unsafe { Art::from_raw(message.inputArt) };
+
+        message.typeMask = LiveEffect::input_type(self, plugin, &params, art)?.bits() as ai_int32;
+        Ok(())
+    }
+
+    fn scale(&mut self, plugin: &mut P, message: &mut AILiveEffectScaleParamMessage) -> AIResult<()> {
+        let mut params = load_params(message.parameters)?;
+        let scaled = self.scale_parameters(plugin, &mut params, message.scaleFactor)?;
+        if scaled {
+            store_params(message.parameters, &params)?;
+        }
+        message.scaledParams = scaled as AIBoolean;
+        Ok(())
+    }
+
+    fn convert_color(&mut self, plugin: &mut P, message: &mut AILiveEffectConvertColorMessage) -> AIResult<()> {
+        let mut params = load_params(message.parameters)?;
+        self.convert_color_space(plugin, &mut params, message.newColorSpace)?;
+        store_params(message.parameters, &params)
+    }
+}
+
+/// `effect` を持つライブエフェクトのメッセージ
+pub(crate) trait EffectMessage {
+    fn effect(&self) -> AILiveEffectHandle;
+}
+
+macro_rules! impl_effect_message {
+    ($($message:ty),* $(,)?) => {
+        $(
+            impl EffectMessage for $message {
+                fn effect(&self) -> AILiveEffectHandle { self.effect }
+            }
+        )*
+    };
+}
+
+impl_effect_message!(
+    AILiveEffectEditParamMessage,
+    AILiveEffectGoMessage,
+    AILiveEffectInterpParamMessage,
+    AILiveEffectInputTypeMessage,
+    AILiveEffectScaleParamMessage,
+    AILiveEffectConvertColorMessage,
+);
+
+/// プラグインが登録したライブエフェクトと、その振る舞い
+///
+/// 起動時（`SafePlugin::startup`）にエフェクトを登録し、`SafePlugin::live_effects` から返すと、
+/// `kCallerAILiveEffect` のメッセージが `LiveEffect` へ送られる。
+/// 色の調整（`kSelectorAILiveEffectAdjustColors`）と置き換え（`kSelectorAILiveEffectHandleMerge`）は
+/// 常に `SafePlugin` のメソッドへ送られる。
+pub struct LiveEffects<P> {
+    entries: Registry<Effect, dyn Handler<P>>,
+}
+
+impl<P> Default for LiveEffects<P> {
+    fn default() -> Self {
+        Self { entries: Registry::default() }
+    }
+}
+
+impl<P> LiveEffects<P> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// エフェクトの設定を始める
+    ///
+    /// `name` はエフェクトを識別する一意な名前（ドキュメントに保存される）。
+    pub fn effect<'a>(&'a mut self, name: &'a CStr, title: &'a str) -> LiveEffectBuilder<'a, P> {
+        LiveEffectBuilder {
+            effects: self,
+            name,
+            title,
+            version: (1, 0),
+            kind: EffectKind::default(),
+            flags: EffectFlags::empty(),
+            input: InputType::default(),
+            menu: None,
+        }
+    }
+
+    /// 登録したエフェクト（登録順）
+    pub fn effects(&self) -> impl Iterator<Item = Effect> + '_ {
+        self.entries.keys()
+    }
+
+    pub fn contains(&self, effect: AILiveEffectHandle) -> bool {
+        self.entries.contains(Effect(effect))
+    }
+}
+
+/// `kCallerAILiveEffect` のメッセージを `call` で `LiveEffect` へ送る
+pub(crate) fn dispatch<P, M: EffectMessage>(
+    plugin: &mut P,
+    effects: fn(&mut P) -> Option<&mut LiveEffects<P>>,
+    message: *mut M,
+    call: fn(&mut dyn Handler<P>, &mut P, &mut M) -> AIResult<()>,
+) -> Option<AIResult<()>> {
+    let message = unsafe { message.as_mut() }?;
+    let effect = Effect(message.effect());
+    util::dispatch(plugin, |plugin| Some(&effects(plugin)?.entries), effect, |behavior, plugin, ()| {
+        call(behavior, plugin, message)
+    })
+}
+
+/// `LiveEffects::effect` が返す、エフェクトの設定
+pub struct LiveEffectBuilder<'a, P> {
+    effects: &'a mut LiveEffects<P>,
+    name: &'a CStr,
+    title: &'a str,
+    version: (i32, i32),
+    kind: EffectKind,
+    flags: EffectFlags,
+    input: InputType,
+    menu: Option<(&'a str, &'a str)>,
+}
+
+impl<'a, P> LiveEffectBuilder<'a, P> {
+    /// パラメータの形式のバージョン（既定は 1.0）
+    pub fn version(mut self, major: i32, minor: i32) -> Self {
+        self.version = (major, minor);
+        self
+    }
+
+    pub fn kind(mut self, kind: EffectKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn flags(mut self, flags: EffectFlags) -> Self {
+        self.flags |= flags;
+        self
+    }
+
+    /// 受け取る入力アート（既定は `InputArt::ANY`）
+    pub fn input(mut self, input: InputType) -> Self {
+        self.input = input;
+        self
+    }
+
+    /// 効果メニューの `category` サブメニューに `title` の項目を追加する
+    pub fn menu(mut self, category: &'a str, title: &'a str) -> Self {
+        self.menu = Some((category, title));
+        self
+    }
+
+    /// エフェクトを登録し、`effect` と結び付ける
+    pub fn add<E>(self, effect: E) -> AIResult<Effect>
+    where
+        E: LiveEffect<P> + 'static,
+    {
+        let suite = SuiteGuard::<AILiveEffectSuite>::acquire()?;
+        let mut title = c_string(self.title)?;
+        let mut handle: AILiveEffectHandle = null_mut();
+
+        let mut data = AILiveEffectData {
+            self_: plugin_ref(),
+            name: self.name.as_ptr(),
+            title: title.as_mut_ptr(),
+            majorVersion: self.version.0,
+            minorVersion: self.version.1,
+            prefersAsInput: self.input.to_raw(),
+            styleFilterFlags: (self.kind.to_raw() | self.flags.bits()) as ai_int32,
+        };
+
+        let registered = unsafe {
+            suite.AddLiveEffect.call((&mut data as *mut _, &mut handle as *mut _))?;
+            Effect::from_raw(handle).ok_or(AIError::CantHappen)?
+        };
+
+        if let Some((category, title)) = self.menu {
+            let mut category = c_string(category)?;
+            let mut title = c_string(title)?;
+            let mut menu = AddLiveEffectMenuData {
+                category: category.as_mut_ptr(),
+                title: title.as_mut_ptr(),
+                options: 0,
+            };
+
+            unsafe {
+                suite.AddLiveEffectMenuItem.call((
+                    handle,
+                    self.name.as_ptr(),
+                    &mut menu as *mut _,
+                    null_mut(),
+                    null_mut(),
+                ))?;
+            }
+        }
+
+        self.effects.entries.push(registered, Rc::new(RefCell::new(effect)), ());
+        Ok(registered)
+    }
+}
+
+/// `AILiveEffectData` などの書き込み可能な C 文字列
+fn c_string(text: &str) -> AIResult<Vec<c_char>> {
+    let text = CString::new(text).map_err(|_| AIError::BadParameter)?;
+    Ok(text.as_bytes_with_nul().iter().map(|&b| b as c_char).collect())
+}
@@ -0,0 +1,405 @@
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr};
+use std::ptr::{null, null_mut};
+use std::rc::Rc;
+
+use crate::action::{ActionEvent, ActionParameters};
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::error::{AIError, AIResult, SuiteFn};
+use crate::externs::plugin_ref;
+use crate::unicode::UnicodeString;
+
+// AIMenu.h の AIMenuItemOption / AIMenuGroupOption（無名 enum のためバインディングに含まれない）
+#[allow(non_upper_case_globals)]
+const kMenuItemWantsUpdateOption: AIMenuItemOption = 1 << 0;
+#[allow(non_upper_case_globals)]
+const kMenuItemAlwaysEnabled: AIMenuItemOption = 1 << 1;
+#[allow(non_upper_case_globals)]
+const kMenuItemIgnoreSort: AIMenuItemOption = 1 << 2;
+#[allow(non_upper_case_globals)]
+const kMenuGroupSortedAlphabeticallyOption: AIMenuGroupOption = 1 << 0;
+#[allow(non_upper_case_globals)]
+const kMenuGroupAddAboveNearGroupOption: AIMenuGroupOption = 1 << 1;
+#[allow(non_upper_case_globals)]
+const kMenuGroupSeparatorOption: AIMenuGroupOption = 1 << 2;
+
+/// プラグインが追加したメニュー項目
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MenuItem(AIMenuItemHandle);
+
+impl MenuItem {
+    /// # Safety
+    /// `handle` は `AIMenuSuite` が返した有効なメニュー項目でなければならない。
+    pub unsafe fn from_raw(handle: AIMenuItemHandle) -> Option<Self> {
+        (!handle.is_null()).then_some(Self(handle))
+    }
+
+    pub fn as_raw(self) -> AIMenuItemHandle {
+        self.0
+    }
+
+    pub fn is_enabled(self) -> AIResult<bool> {
+        let suite = SuiteGuard::<AIMenuSuite>::acquire()?;
+        let mut enabled: ASBoolean = 0;
+
+        unsafe { suite.IsItemEnabled.call((self.0, &mut enabled as *mut _))? };
+        Ok(enabled != 0)
+    }
+
+    pub fn set_enabled(self, enabled: bool) -> AIResult<()> {
+        let suite = SuiteGuard::<AIMenuSuite>::acquire()?;
+        unsafe {
+            if enabled {
+                suite.EnableItem.call((self.0,))
+            } else {
+                suite.DisableItem.call((self.0,))
+            }
+        }
+    }
+
+    pub fn is_checked(self) -> AIResult<bool> {
+        let suite = SuiteGuard::<AIMenuSuite>::acquire()?;
+        let mut checked: AIBoolean = 0;
+
+        unsafe { suite.IsItemChecked.call((self.0, &mut checked as *mut _))? };
+        Ok(checked != 0)
+    }
+
+    pub fn set_checked(self, checked: bool) -> AIResult<()> {
+        let suite = SuiteGuard::<AIMenuSuite>::acquire()?;
+        unsafe { suite.CheckItem.call((self.0, checked as AIBoolean)) }
+    }
+
+    /// メニューに表示される文字列
+    pub fn text(self) -> AIResult<String> {
+        let suite = SuiteGuard::<AIMenuSuite>::acquire()?;
+        let mut text = UnicodeString::empty()?;
+
+        unsafe { suite.GetItemText.call((self.0, text.as_mut_ptr()))? };
+        Ok(text.to_string_lossy())
+    }
+
+    pub fn set_text(self, text: &str) -> AIResult<()> {
+        let suite = SuiteGuard::<AIMenuSuite>::acquire()?;
+        let text = UnicodeString::new(text)?;
+        unsafe { suite.SetItemText.call((self.0, text.as_ptr())) }
+    }
+
+    /// メニューから取り除く（以降ハンドルは無効）
+    pub fn remove(self) -> AIResult<()> {
+        let suite = SuiteGuard::<AIMenuSuite>::acquire()?;
+        unsafe { suite.RemoveMenuItem.call((self.0,)) }
+    }
+}
+
+/// メニューグループ
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MenuGroup(AIMenuGroup);
+
+impl MenuGroup {
+    /// # Safety
+    /// `handle` は `AIMenuSuite` が返した有効なメニューグループでなければならない。
+    pub unsafe fn from_raw(handle: AIMenuGroup) -> Option<Self> {
+        (!handle.is_null()).then_some(Self(handle))
+    }
+
+    pub fn as_raw(self) -> AIMenuGroup {
+        self.0
+    }
+
+    /// グループ名（`add_item` に渡す名前）
+    pub fn name(self) -> AIResult<&'static CStr> {
+        let suite = SuiteGuard::<AIMenuSuite>::acquire()?;
+        let mut name: *const c_char = null();
+
+        unsafe {
+            suite.GetMenuGroupName.call((self.0, &mut name as *mut _))?;
+            if name.is_null() {
+                return Err(AIError::CantHappen);
+            }
+            Ok(CStr::from_ptr(name))
+        }
+    }
+}
+
+/// メニューグループの配置
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MenuGroupOptions {
+    /// 項目を名前順に並べる
+    pub sorted: bool,
+    /// 基準のグループの上に置く（既定は下）
+    pub above: bool,
+    /// 区切り線を入れる
+    pub separator: bool,
+}
+
+impl MenuGroupOptions {
+    fn to_raw(self) -> AIMenuGroupOption {
+        let mut options = 0;
+        if self.sorted {
+            options |= kMenuGroupSortedAlphabeticallyOption;
+        }
+        if self.above {
+            options |= kMenuGroupAddAboveNearGroupOption;
+        }
+        if self.separator {
+            options |= kMenuGroupSeparatorOption;
+        }
+        options
+    }
+}
+
+/// `kSelectorAIUpdateMenuItem` への応答
+///
+/// `None` の項目は変更しない。
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MenuUpdate {
+    pub enabled: Option<bool>,
+    pub checked: Option<bool>,
+    pub text: Option<String>,
+}
+
+impl MenuUpdate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = Some(enabled);
+        self
+    }
+
+    pub fn checked(mut self, checked: bool) -> Self {
+        self.checked = Some(checked);
+        self
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// メニュー項目に反映する
+    pub fn apply(self, item: MenuItem) -> AIResult<()> {
+        if let Some(enabled) = self.enabled {
+            item.set_enabled(enabled)?;
+        }
+        if let Some(checked) = self.checked {
+            item.set_checked(checked)?;
+        }
+        if let Some(text) = self.text {
+            item.set_text(&text)?;
+        }
+        Ok(())
+    }
+}
+
+/// メニュー項目を enum の値で表すプラグイン
+///
+/// `MenuItemBuilder::command` で登録した項目は、選択・更新時にこのトレイトのメソッドへ送られる。
+pub trait MenuCommands {
+    type Command: Copy + 'static;
+
+    fn menu_command(&mut self, command: Self::Command) -> AIResult<()>;
+
+    fn update_menu_command(&mut self, _command: Self::Command) -> AIResult<MenuUpdate> {
+        Ok(MenuUpdate::default())
+    }
+}
+
+type GoHandler<T> = Rc<RefCell<dyn FnMut(&mut T) -> AIResult<()>>>;
+type UpdateHandler<T> = Rc<RefCell<dyn FnMut(&mut T) -> AIResult<MenuUpdate>>>;
+
+struct MenuEntry<T> {
+    item: MenuItem,
+    go: Option<GoHandler<T>>,
+    update: Option<UpdateHandler<T>>,
+}
+
+/// プラグインが追加したメニュー項目と、そのハンドラ
+///
+/// 起動時（`SafePlugin::startup`）に項目を追加し、`SafePlugin::menus` から返すと、
+/// `kSelectorAIGoMenuItem`・`kSelectorAIUpdateMenuItem` が項目ごとのハンドラへ送られる。
+/// ハンドラが登録されていない項目は `go_menu_item`・`update_menu_item` へ送られる。
+pub struct Menus<T> {
+    entries: Vec<MenuEntry<T>>,
+}
+
+impl<T> Default for Menus<T> {
+    fn default() -> Self {
+        Self { entries: Vec::new() }
+    }
+}
+
+impl<T> Menus<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// メニューグループを作る（既にあれば既存のグループを返す）
+    ///
+    /// `near` は配置の基準になる既存のグループ（`kOpenMenuGroup` など）。
+    pub fn add_group(&mut self, name: &CStr, near: &CStr, options: MenuGroupOptions) -> AIResult<MenuGroup> {
+        let suite = SuiteGuard::<AIMenuSuite>::acquire()?;
+        let mut group: AIMenuGroup = null_mut();
+
+        unsafe {
+            suite
+                .AddMenuGroup
+                .call((name.as_ptr(), options.to_raw(), near.as_ptr(), &mut group as *mut _))?;
+            MenuGroup::from_raw(group).ok_or(AIError::CantHappen)
+        }
+    }
+
+    /// `group` にサブメニューを持つ項目を追加し、サブメニューを `name` のグループとして返す
+    pub fn add_submenu(&mut self, key: &CStr, group: &CStr, text: &str, name: &CStr) -> AIResult<MenuGroup>
+    where
+        T: 'static,
+    {
+        let item = self.item(key, group, text).add()?;
+        let suite = SuiteGuard::<AIMenuSuite>::acquire()?;
+        let mut submenu: AIMenuGroup = null_mut();
+
+        unsafe {
+            suite
+                .AddMenuGroupAsSubMenu
+                .call((name.as_ptr(), 0, item.as_raw(), &mut submenu as *mut _))?;
+            MenuGroup::from_raw(submenu).ok_or(AIError::CantHappen)
+        }
+    }
+
+    /// `group` に追加する項目の設定を始める
+    ///
+    /// `key` は項目を識別する一意な名前（キーボードショートカットの保存に使われる）。
+    pub fn item<'a>(&'a mut self, key: &'a CStr, group: &'a CStr, text: &'a str) -> MenuItemBuilder<'a, T> {
+        MenuItemBuilder {
+            menus: self,
+            key,
+            group,
+            text,
+            options: 0,
+            go: None,
+            update: None,
+        }
+    }
+
+    /// 追加した項目（追加順）
+    pub fn items(&self) -> impl Iterator<Item = MenuItem> + '_ {
+        self.entries.iter().map(|entry| entry.item)
+    }
+
+    pub fn contains(&self, item: AIMenuItemHandle) -> bool {
+        self.entry(item).is_some()
+    }
+
+    /// 追加した項目をすべてメニューから取り除く
+    pub fn remove_all(&mut self) -> AIResult<()> {
+        for entry in self.entries.drain(..) {
+            entry.item.remove()?;
+        }
+        Ok(())
+    }
+
+    fn entry(&self, item: AIMenuItemHandle) -> Option<&MenuEntry<T>> {
+        self.entries.iter().find(|entry| entry.item.as_raw() == item)
+    }
+
+    /// `kSelectorAIGoMenuItem` を受け取ったときのハンドラ
+    pub(crate) fn go_handler(&self, item: AIMenuItemHandle) -> Option<GoHandler<T>> {
+        self.entry(item)?.go.clone()
+    }
+
+    /// `kSelectorAIUpdateMenuItem` を受け取ったときのハンドラ
+    pub(crate) fn update_handler(&self, item: AIMenuItemHandle) -> Option<UpdateHandler<T>> {
+        self.entry(item)?.update.clone()
+    }
+}
+
+/// `Menus::item` が返す、メニュー項目の設定
+pub struct MenuItemBuilder<'a, T> {
+    menus: &'a mut Menus<T>,
+    key: &'a CStr,
+    group: &'a CStr,
+    text: &'a str,
+    options: AIMenuItemOption,
+    go: Option<GoHandler<T>>,
+    update: Option<UpdateHandler<T>>,
+}
+
+impl<T: 'static> MenuItemBuilder<'_, T> {
+    /// ドキュメントが開いていなくても選択できる
+    pub fn always_enabled(mut self) -> Self {
+        self.options |= kMenuItemAlwaysEnabled;
+        self
+    }
+
+    /// グループが名前順でも追加した位置に置く
+    pub fn ignore_sort(mut self) -> Self {
+        self.options |= kMenuItemIgnoreSort;
+        self
+    }
+
+    /// 選択されたときのハンドラ
+    pub fn on_select<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&mut T) -> AIResult<()> + 'static,
+    {
+        self.go = Some(Rc::new(RefCell::new(handler)));
+        self
+    }
+
+    /// メニューが開かれる前に項目の状態を決めるハンドラ
+    pub fn on_update<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&mut T) -> AIResult<MenuUpdate> + 'static,
+    {
+        self.options |= kMenuItemWantsUpdateOption;
+        self.update = Some(Rc::new(RefCell::new(handler)));
+        self
+    }
+
+    /// 選択・更新を `MenuCommands` の `command` として扱う
+    pub fn command(self, command: T::Command) -> Self
+    where
+        T: MenuCommands,
+    {
+        self.on_select(move |plugin: &mut T| plugin.menu_command(command))
+            .on_update(move |plugin: &mut T| plugin.update_menu_command(command))
+    }
+
+    /// 選択されたら `event` を実行し、記録モードならアクションパネルに記録する
+    pub fn action<A: ActionParameters + 'static>(self, event: ActionEvent<T, A>) -> Self {
+        self.on_select(move |plugin: &mut T| event.run(plugin))
+    }
+
+    /// メニューに追加する
+    pub fn add(self) -> AIResult<MenuItem> {
+        let suite = SuiteGuard::<AIMenuSuite>::acquire()?;
+        let text = UnicodeString::new(self.text)?;
+        let mut item: AIMenuItemHandle = null_mut();
+
+        let mut data = AIPlatformAddMenuItemDataUS {
+            groupName: self.group.as_ptr(),
+            itemText: text.borrow_raw(),
+        };
+
+        let item = unsafe {
+            suite.AddMenuItem.call((
+                plugin_ref(),
+                self.key.as_ptr(),
+                &mut data as *mut _,
+                self.options,
+                &mut item as *mut _,
+            ))?;
+            MenuItem::from_raw(item).ok_or(AIError::CantHappen)?
+        };
+
+        self.menus.entries.push(MenuEntry {
+            item,
+            go: self.go,
+            update: self.update,
+        });
+        Ok(item)
+    }
+}
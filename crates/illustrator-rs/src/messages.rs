@@ -0,0 +1,328 @@
+use std::ffi::{c_void, CStr};
+
+use crate::ai_sys::*;
+use crate::notifier::NotifierType;
+
+/// ホストから渡されたメッセージ構造体を借用するビュー型を定義する
+macro_rules! message_view {
+    ($(#[$meta:meta])* $name:ident => $raw:ty, $($data:ident).+) => {
+        $(#[$meta])*
+        pub struct $name<'a> {
+            raw: &'a mut $raw,
+        }
+
+        impl<'a> $name<'a> {
+            /// ホストから受け取ったポインタを借用する（null なら `None`）
+            ///
+            /// # Safety
+            /// `ptr` は呼び出し中有効な `$raw` を指していなければならない。
+            pub unsafe fn from_raw(ptr: *mut $raw) -> Option<Self> {
+                ptr.as_mut().map(|raw| Self { raw })
+            }
+
+            /// 元の構造体への低レベルアクセス
+            pub fn raw(&mut self) -> &mut $raw {
+                self.raw
+            }
+
+            /// メッセージを受け取ったプラグイン
+            pub fn plugin_ref(&self) -> SPPluginRef {
+                self.raw.$($data).+.self_
+            }
+        }
+    };
+}
+
+message_view!(
+    /// `SPPropertiesMessage` のビュー
+    PropertiesMessage => SPPropertiesMessage, d
+);
+
+impl PropertiesMessage<'_> {
+    pub fn vendor_id(&self) -> PIType { self.raw.vendorID }
+    pub fn property_key(&self) -> PIType { self.raw.propertyKey }
+    pub fn property_id(&self) -> i32 { self.raw.propertyID }
+    pub fn property(&self) -> *mut c_void { self.raw.property }
+    pub fn set_property(&mut self, property: *mut c_void, cacheable: bool) {
+        self.raw.property = property;
+        self.raw.cacheable = cacheable as i32;
+    }
+}
+
+message_view!(
+    /// `AINotifierMessage` のビュー
+    NotifierMessage => AINotifierMessage, d
+);
+
+impl NotifierMessage<'_> {
+    pub fn notifier(&self) -> AINotifierHandle { self.raw.notifier }
+
+    /// 通知の種類（`kAI*Notifier`、ホストが渡さなければ `None`）
+    pub fn notifier_type(&self) -> Option<&CStr> {
+        (!self.raw.type_.is_null()).then(|| unsafe { CStr::from_ptr(self.raw.type_) })
+    }
+
+    /// 通知の種類（`NotifierType` にない通知は `None`）
+    pub fn kind(&self) -> Option<NotifierType> {
+        NotifierType::from_name(self.notifier_type()?)
+    }
+
+    /// 通知の種類が `notifier_type` と一致するか
+    pub fn is(&self, notifier_type: &[u8]) -> bool {
+        self.notifier_type().is_some_and(|name| name.to_bytes_with_nul() == notifier_type)
+    }
+
+    pub fn notify_data(&self) -> *mut c_void { self.raw.notifyData }
+}
+
+message_view!(
+    /// `DoActionMessage` のビュー
+    ActionMessage => DoActionMessage, d
+);
+
+impl ActionMessage<'_> {
+    pub fn user_data(&self) -> AIActionUserData { self.raw.userData }
+    pub fn show_dialog(&self) -> bool { self.raw.showDialog != 0 }
+    pub fn param(&self) -> AIActionParamValueRef { self.raw.param }
+}
+
+message_view!(
+    /// `AIMenuMessage` のビュー
+    MenuMessage => AIMenuMessage, d
+);
+
+impl MenuMessage<'_> {
+    pub fn menu_item(&self) -> AIMenuItemHandle { self.raw.menuItem }
+}
+
+message_view!(
+    /// `AIFilterMessage` のビュー
+    FilterMessage => AIFilterMessage, d
+);
+
+impl FilterMessage<'_> {
+    pub fn filter(&self) -> AIFilterHandle { self.raw.filter }
+    pub fn parameters(&self) -> PlatformFilterParameters { self.raw.parameters }
+    pub fn set_parameters(&mut self, parameters: PlatformFilterParameters) {
+        self.raw.parameters = parameters;
+    }
+}
+
+message_view!(
+    /// `AIPluginGroupMessage` のビュー
+    PluginGroupMessage => AIPluginGroupMessage, d
+);
+
+impl PluginGroupMessage<'_> {
+    pub fn entry(&self) -> AIPluginGroupHandle { self.raw.entry }
+    pub fn art(&self) -> AIArtHandle { self.raw.art }
+
+    /// 操作のタイミング（`kPreEditNotifyTime` など）
+    pub fn time(&self) -> &CStr {
+        unsafe { CStr::from_ptr(self.raw.time) }
+    }
+
+    /// 操作の種類（`kTransformOperationCode` など）
+    pub fn code(&self) -> &CStr {
+        unsafe { CStr::from_ptr(self.raw.code) }
+    }
+
+    pub fn pre_edit_art(&self) -> AIArtHandle { self.raw.preEditArt }
+    pub fn post_edit_art(&self) -> AIArtHandle { self.raw.postEditArt }
+    pub fn matrix(&self) -> &AIRealMatrix { &self.raw.matrix }
+}
+
+message_view!(
+    /// `AIFileFormatMessage` のビュー
+    FileFormatMessage => AIFileFormatMessage, d
+);
+
+impl FileFormatMessage<'_> {
+    pub fn file_format(&self) -> AIFileFormatHandle { self.raw.fileFormat }
+
+    /// 要求された操作（`kFileFormatRead` など）
+    pub fn option(&self) -> i32 { self.raw.option }
+
+    pub fn file_path(&self) -> &ai_FilePath { &self.raw.filePath }
+    pub fn action_param(&self) -> *mut c_void { self.raw.actionParm }
+    pub fn operation_options(&self) -> i32 { self.raw.operationOptions }
+    pub fn artboard_range(&self) -> AIArtboardRangeHandle { self.raw.rangeHandle }
+}
+
+message_view!(
+    /// `AIUpdateFileFormatMessage` のビュー
+    UpdateFileFormatMessage => AIUpdateFileFormatMessage, ffm.d
+);
+
+impl UpdateFileFormatMessage<'_> {
+    pub fn file_format(&self) -> AIFileFormatHandle { self.raw.ffm.fileFormat }
+    pub fn art(&self) -> AIArtHandle { self.raw.art }
+    pub fn data(&self) -> *mut c_void { self.raw.data }
+}
+
+message_view!(
+    /// `AIToolMessage` のビュー
+    ToolMessage => AIToolMessage, d
+);
+
+impl ToolMessage<'_> {
+    pub fn tool(&self) -> AIToolHandle { self.raw.tool }
+
+    /// ドキュメント座標系のカーソル位置
+    pub fn cursor(&self) -> AIRealPoint { self.raw.cursor }
+
+    pub fn pressure(&self) -> AIToolPressure { self.raw.pressure }
+    pub fn stylus_wheel(&self) -> AIToolPressure { self.raw.stylusWheel }
+    pub fn tilt(&self) -> AIToolAngle { self.raw.tilt }
+    pub fn bearing(&self) -> AIToolAngle { self.raw.bearing }
+    pub fn rotation(&self) -> AIToolAngle { self.raw.rotation }
+    pub fn flags(&self) -> i32 { self.raw.flags }
+
+    /// マウスイベント（修飾キーなど）
+    pub fn event(&self) -> Option<&AIEvent> {
+        unsafe { self.raw.event.as_ref() }
+    }
+}
+
+message_view!(
+    /// `AILiveEffectEditParamMessage` のビュー
+    LiveEffectEditParamMessage => AILiveEffectEditParamMessage, d
+);
+
+impl LiveEffectEditParamMessage<'_> {
+    pub fn effect(&self) -> AILiveEffectHandle { self.raw.effect }
+    pub fn parameters(&self) -> AILiveEffectParameters { self.raw.parameters }
+    pub fn context(&self) -> AILiveEffectParamContext { self.raw.context }
+    pub fn allow_preview(&self) -> bool { self.raw.allowPreview != 0 }
+    pub fn is_new_instance(&self) -> bool { self.raw.isNewInstance != 0 }
+}
+
+message_view!(
+    /// `AILiveEffectGoMessage` のビュー
+    LiveEffectGoMessage => AILiveEffectGoMessage, d
+);
+
+impl LiveEffectGoMessage<'_> {
+    pub fn effect(&self) -> AILiveEffectHandle { self.raw.effect }
+    pub fn parameters(&self) -> AILiveEffectParameters { self.raw.parameters }
+    pub fn instance_info(&self) -> AILiveEffectParameters { self.raw.instanceInfo }
+
+    /// 効果を適用する入力アート（結果をここに書き戻す）
+    pub fn art(&self) -> AIArtHandle { self.raw.art }
+    pub fn set_art(&mut self, art: AIArtHandle) { self.raw.art = art; }
+    pub fn src_art(&self) -> AIArtHandle { self.raw.srcArt }
+}
+
+message_view!(
+    /// `AILiveEffectInterpParamMessage` のビュー
+    LiveEffectInterpParamMessage => AILiveEffectInterpParamMessage, d
+);
+
+impl LiveEffectInterpParamMessage<'_> {
+    pub fn effect(&self) -> AILiveEffectHandle { self.raw.effect }
+    pub fn start_params(&self) -> AILiveEffectParameters { self.raw.startParams }
+    pub fn end_params(&self) -> AILiveEffectParameters { self.raw.endParams }
+    pub fn out_params(&self) -> AILiveEffectParameters { self.raw.outParams }
+    pub fn percent(&self) -> AIReal { self.raw.percent }
+}
+
+message_view!(
+    /// `AILiveEffectInputTypeMessage` のビュー
+    LiveEffectInputTypeMessage => AILiveEffectInputTypeMessage, d
+);
+
+impl LiveEffectInputTypeMessage<'_> {
+    pub fn effect(&self) -> AILiveEffectHandle { self.raw.effect }
+    pub fn parameters(&self) -> AILiveEffectParameters { self.raw.parameters }
+    pub fn input_art(&self) -> AIArtHandle { self.raw.inputArt }
+    pub fn type_mask(&self) -> i32 { self.raw.typeMask }
+    pub fn set_type_mask(&mut self, mask: i32) { self.raw.typeMask = mask; }
+}
+
+message_view!(
+    /// `AILiveEffectScaleParamMessage` のビュー
+    LiveEffectScaleParamMessage => AILiveEffectScaleParamMessage, d
+);
+
+impl LiveEffectScaleParamMessage<'_> {
+    pub fn effect(&self) -> AILiveEffectHandle { self.raw.effect }
+    pub fn parameters(&self) -> AILiveEffectParameters { self.raw.parameters }
+    pub fn scale_factor(&self) -> AIReal { self.raw.scaleFactor }
+    pub fn set_scaled_params(&mut self, scaled: bool) { self.raw.scaledParams = scaled as AIBoolean; }
+}
+
+message_view!(
+    /// `AILiveEffectConvertColorMessage` のビュー
+    LiveEffectConvertColorMessage => AILiveEffectConvertColorMessage, d
+);
+
+impl LiveEffectConvertColorMessage<'_> {
+    pub fn effect(&self) -> AILiveEffectHandle { self.raw.effect }
+    pub fn parameters(&self) -> AILiveEffectParameters { self.raw.parameters }
+    pub fn new_color_space(&self) -> AIColorTag { self.raw.newColorSpace }
+}
+
+message_view!(
+    /// `AILiveEffectAdjustColorsMessage` のビュー
+    LiveEffectAdjustColorsMessage => AILiveEffectAdjustColorsMessage, d
+);
+
+impl LiveEffectAdjustColorsMessage<'_> {
+    pub fn effect(&self) -> AILiveEffectHandle { self.raw.effect }
+    pub fn parameters(&self) -> AILiveEffectParameters { self.raw.parameters }
+    pub fn set_modified_something(&mut self, modified: bool) {
+        self.raw.modifiedSomething = modified as AIBoolean;
+    }
+}
+
+message_view!(
+    /// `AILiveEffectHandleMergeMessage` のビュー
+    LiveEffectHandleMergeMessage => AILiveEffectHandleMergeMessage, d
+);
+
+impl LiveEffectHandleMergeMessage<'_> {
+    pub fn effect(&self) -> AILiveEffectHandle { self.raw.effect }
+    pub fn old_effect(&self) -> AILiveEffectHandle { self.raw.oldEffect }
+    pub fn parameters(&self) -> AILiveEffectParameters { self.raw.parameters }
+    pub fn set_dont_merge(&mut self, dont_merge: bool) { self.raw.dontMerge = dont_merge as AIBoolean; }
+    pub fn set_keep_instance_info(&mut self, keep: bool) { self.raw.keepInstanceInfo = keep as AIBoolean; }
+}
+
+message_view!(
+    /// `AITimerMessage` のビュー
+    TimerMessage => AITimerMessage, d
+);
+
+impl TimerMessage<'_> {
+    pub fn timer(&self) -> AITimerHandle { self.raw.timer }
+}
+
+message_view!(
+    /// `AIClipboardMessage` のビュー
+    ClipboardMessage => AIClipboardMessage, d
+);
+
+impl ClipboardMessage<'_> {
+    pub fn clipboard(&self) -> AIClipboardHandle { self.raw.Clipboard }
+    pub fn clipboard_data(&self) -> AIStream { self.raw.ClipboardData }
+    pub fn option(&self) -> i32 { self.raw.option }
+}
+
+message_view!(
+    /// `AIWorkspaceMessage` のビュー
+    WorkspaceMessage => AIWorkspaceMessage, d
+);
+
+impl WorkspaceMessage<'_> {
+    pub fn workspace(&self) -> AIWorkspaceHandle { self.raw.workspace }
+
+    pub fn dialog_name(&self) -> Option<&CStr> {
+        if self.raw.dialogName.is_null() {
+            None
+        } else {
+            Some(unsafe { CStr::from_ptr(self.raw.dialogName) })
+        }
+    }
+
+    pub fn restore(&self) -> bool { self.raw.restore != 0 }
+}
@@ -0,0 +1,188 @@
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr::{null, null_mut};
+use std::rc::Rc;
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::error::{AIError, AIResult, SuiteFn};
+use crate::externs::plugin_ref;
+use crate::messages::NotifierMessage;
+
+/// `kAI*Notifier` 定数から `NotifierType` を定義する
+macro_rules! notifier_types {
+    ($($variant:ident => $name:ident),* $(,)?) => {
+        /// ホストが送る通知の種類（バインディングの `kAI*Notifier`）
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum NotifierType {
+            $($variant,)*
+        }
+
+        impl NotifierType {
+            /// すべての通知の種類
+            pub const ALL: &'static [NotifierType] = &[$(NotifierType::$variant,)*];
+
+            /// `AddNotifier` に渡す通知の名前
+            pub fn name(self) -> &'static CStr {
+                let name: &'static [u8] = match self {
+                    $(NotifierType::$variant => $name,)*
+                };
+                CStr::from_bytes_with_nul(name).expect("notifier name must be NUL terminated")
+            }
+        }
+    };
+}
+
+// バインディングの `kAI*Notifier` から build.rs が生成した一覧
+include!(concat!(env!("OUT_DIR"), "/notifier_types.rs"));
+
+impl NotifierType {
+    /// 通知の名前から引く（一覧にない通知は `None`）
+    pub fn from_name(name: &CStr) -> Option<Self> {
+        Self::ALL.iter().copied().find(|notifier| notifier.name() == name)
+    }
+}
+
+/// `AddNotifier` で登録した通知の購読
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Subscription(AINotifierHandle);
+
+impl Subscription {
+    /// # Safety
+    /// `handle` は `AINotifierSuite` が返した有効な通知でなければならない。
+    pub unsafe fn from_raw(handle: AINotifierHandle) -> Option<Self> {
+        (!handle.is_null()).then_some(Self(handle))
+    }
+
+    pub fn as_raw(self) -> AINotifierHandle {
+        self.0
+    }
+
+    pub fn is_active(self) -> AIResult<bool> {
+        let suite = SuiteGuard::<AINotifierSuite>::acquire()?;
+        let mut active: AIBoolean = 0;
+
+        unsafe { suite.GetNotifierActive.call((self.0, &mut active as *mut _))? };
+        Ok(active != 0)
+    }
+
+    /// 無効にした購読には通知が送られない（通知は取り除けないので、不要になったら無効にする）
+    pub fn set_active(self, active: bool) -> AIResult<()> {
+        let suite = SuiteGuard::<AINotifierSuite>::acquire()?;
+        unsafe { suite.SetNotifierActive.call((self.0, active as AIBoolean)) }
+    }
+
+    /// 購読している通知の名前
+    pub fn notifier_type(self) -> AIResult<CString> {
+        let suite = SuiteGuard::<AINotifierSuite>::acquire()?;
+        let mut name: *const c_char = null();
+
+        unsafe {
+            suite.GetNotifierType.call((self.0, &mut name as *mut _))?;
+            if name.is_null() {
+                return Err(AIError::CantHappen);
+            }
+            Ok(CStr::from_ptr(name).to_owned())
+        }
+    }
+}
+
+type Handler<T> = Rc<RefCell<dyn FnMut(&mut T, NotifierMessage) -> AIResult<()>>>;
+
+struct NotifierEntry<T> {
+    subscription: Subscription,
+    handler: Handler<T>,
+}
+
+/// プラグインが購読した通知と、そのハンドラ
+///
+/// 起動時（`SafePlugin::startup`）に購読し、`SafePlugin::notifiers` から返すと、
+/// `kSelectorAINotify` が購読ごとのハンドラへ送られる。
+/// どの購読にも当てはまらない通知は `notify` へ送られる。
+pub struct Notifiers<T> {
+    entries: Vec<NotifierEntry<T>>,
+}
+
+impl<T> Default for Notifiers<T> {
+    fn default() -> Self {
+        Self { entries: Vec::new() }
+    }
+}
+
+impl<T> Notifiers<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `notifier` を購読し、受け取ったときに `handler` を呼ぶ
+    pub fn subscribe<F>(&mut self, notifier: NotifierType, handler: F) -> AIResult<Subscription>
+    where
+        F: FnMut(&mut T, NotifierMessage) -> AIResult<()> + 'static,
+    {
+        self.subscribe_name(notifier.name(), handler)
+    }
+
+    /// `NotifierType` にない通知（他のプラグインが定義したものなど）を名前で購読する
+    pub fn subscribe_name<F>(&mut self, notifier_type: &CStr, handler: F) -> AIResult<Subscription>
+    where
+        F: FnMut(&mut T, NotifierMessage) -> AIResult<()> + 'static,
+    {
+        let suite = SuiteGuard::<AINotifierSuite>::acquire()?;
+        let name = notifier_name(notifier_type)?;
+        let mut notifier: AINotifierHandle = null_mut();
+
+        let subscription = unsafe {
+            suite.AddNotifier.call((
+                plugin_ref(),
+                name.as_ptr(),
+                notifier_type.as_ptr(),
+                &mut notifier as *mut _,
+            ))?;
+            Subscription::from_raw(notifier).ok_or(AIError::CantHappen)?
+        };
+
+        self.entries.push(NotifierEntry {
+            subscription,
+            handler: Rc::new(RefCell::new(handler)),
+        });
+        Ok(subscription)
+    }
+
+    /// 購読した通知（購読順）
+    pub fn subscriptions(&self) -> impl Iterator<Item = Subscription> + '_ {
+        self.entries.iter().map(|entry| entry.subscription)
+    }
+
+    pub fn contains(&self, notifier: AINotifierHandle) -> bool {
+        self.handler(notifier).is_some()
+    }
+
+    /// すべての購読を有効・無効にする
+    pub fn set_active(&self, active: bool) -> AIResult<()> {
+        self.entries.iter().try_for_each(|entry| entry.subscription.set_active(active))
+    }
+
+    /// `kSelectorAINotify` を受け取ったときのハンドラ
+    pub(crate) fn handler(&self, notifier: AINotifierHandle) -> Option<Handler<T>> {
+        self.entries
+            .iter()
+            .find(|entry| entry.subscription.as_raw() == notifier)
+            .map(|entry| entry.handler.clone())
+    }
+}
+
+/// `AddNotifier` に渡す一意な名前（`<プラグイン名> <通知の名前>`）
+fn notifier_name(notifier_type: &CStr) -> AIResult<CString> {
+    let suite = SuiteGuard::<SPPluginsSuite>::acquire()?;
+    let mut plugin_name: *const c_char = null();
+
+    let plugin_name = unsafe {
+        suite.GetPluginName.call((plugin_ref(), &mut plugin_name as *mut _))?;
+        if plugin_name.is_null() {
+            return Ok(notifier_type.to_owned());
+        }
+        CStr::from_ptr(plugin_name).to_string_lossy()
+    };
+
+    CString::new(format!("{} {}", plugin_name, notifier_type.to_string_lossy())).map_err(|_| AIError::BadParameter)
+}
@@ -0,0 +1,133 @@
+use std::any::Any;
+use std::ffi::{c_char, c_void, CStr};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::error::kRustPanicErr;
+use crate::router::{self, Route};
+use crate::unicode::UnicodeString;
+
+/// ハンドラ内で発生したパニックの内容
+#[derive(Debug, Clone)]
+pub struct PanicReport {
+    pub caller: String,
+    pub selector: String,
+    pub payload: String,
+}
+
+/// パニック発生後、リロードされるまでメッセージを拒否する
+static POISONED: AtomicBool = AtomicBool::new(false);
+
+/// パニックを `AIUserSuite::ErrorAlert` で表示するか
+static ALERT_ON_PANIC: AtomicBool = AtomicBool::new(true);
+
+static LOGGER: Mutex<Option<fn(&PanicReport)>> = Mutex::new(None);
+
+/// プラグインがパニックにより停止しているか
+pub fn is_poisoned() -> bool {
+    POISONED.load(Ordering::SeqCst)
+}
+
+/// 停止状態を解除する（通常はリロード時に自動で行われる）
+pub fn clear_poison() {
+    POISONED.store(false, Ordering::SeqCst);
+}
+
+/// パニック時にエラーアラートを表示するかを設定する
+pub fn set_alert_on_panic(alert: bool) {
+    ALERT_ON_PANIC.store(alert, Ordering::SeqCst);
+}
+
+/// パニックの記録先を設定する（未設定なら標準エラー出力）
+pub fn set_panic_logger(logger: fn(&PanicReport)) {
+    *LOGGER.lock().unwrap_or_else(|e| e.into_inner()) = Some(logger);
+}
+
+/// `PluginMain` の本体をパニックから保護する
+///
+/// パニックは C ABI を越えて巻き戻らないよう捕捉され、`kRustPanicErr` としてホストへ返される。
+/// 以降はリロードメッセージを受け取るまで、すべてのメッセージに `kRustPanicErr` を返す。
+pub fn guard<F>(caller: *const c_char, selector: *const c_char, message: *mut c_void, body: F) -> ASErr
+where
+    F: FnOnce() -> ASErr,
+{
+    if is_poisoned() {
+        if unsafe { router::resolve(caller, selector) } != Some(Route::AccessReload) {
+            return kRustPanicErr;
+        }
+        clear_poison();
+    }
+
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(error) => error,
+        Err(payload) => {
+            POISONED.store(true, Ordering::SeqCst);
+
+            let report = unsafe {
+                PanicReport {
+                    caller: CStr::from_ptr(caller).to_string_lossy().into_owned(),
+                    selector: CStr::from_ptr(selector).to_string_lossy().into_owned(),
+                    payload: payload_to_string(payload.as_ref()),
+                }
+            };
+
+            // ログやアラート自体のパニックはここで握りつぶす
+            let _ = catch_unwind(|| {
+                log(&report);
+
+                if ALERT_ON_PANIC.load(Ordering::SeqCst) {
+                    unsafe { alert(message, &report) };
+                }
+            });
+
+            kRustPanicErr
+        }
+    }
+}
+
+fn payload_to_string(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+fn log(report: &PanicReport) {
+    let logger = *LOGGER.lock().unwrap_or_else(|e| e.into_inner());
+
+    match logger {
+        Some(logger) => logger(report),
+        None => eprintln!(
+            "[illustrator-rs] panic in {} / {}: {}",
+            report.caller, report.selector, report.payload
+        ),
+    }
+}
+
+/// メッセージの `SPBasicSuite` から `AIUserSuite` を取得してエラーアラートを表示する
+unsafe fn alert(message: *mut c_void, report: &PanicReport) {
+    let Some(data) = (message as *mut SPMessageData).as_ref() else { return };
+    let Some(basic) = data.basic.as_ref() else { return };
+
+    let (Ok(user), Ok(unicode)) = (
+        SuiteGuard::<AIUserSuite>::acquire_from(basic),
+        SuiteGuard::<AIUnicodeStringSuite>::acquire_from(basic),
+    ) else {
+        return;
+    };
+
+    let text = format!(
+        "Plug-in error: {} ({} / {})",
+        report.payload, report.caller, report.selector
+    );
+
+    if let (Ok(string), Some(error_alert)) = (UnicodeString::with_suite(unicode, &text), user.ErrorAlert) {
+        error_alert(string.as_ptr());
+    }
+}
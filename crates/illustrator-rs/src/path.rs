@@ -0,0 +1,185 @@
+use std::ops::Range;
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::art::{Art, ArtType, PaintOrder};
+use crate::bezier::{BezierPath, PathSegment};
+use crate::error::{AIError, AIResult, SuiteFn};
+
+impl From<AIPathSegment> for PathSegment {
+    fn from(segment: AIPathSegment) -> Self {
+        Self {
+            anchor: segment.p.into(),
+            in_point: segment.in_.into(),
+            out_point: segment.out.into(),
+            corner: segment.corner != 0,
+        }
+    }
+}
+
+impl From<PathSegment> for AIPathSegment {
+    fn from(segment: PathSegment) -> Self {
+        AIPathSegment {
+            p: segment.anchor.into(),
+            in_: segment.in_point.into(),
+            out: segment.out_point.into(),
+            corner: segment.corner as AIBoolean,
+        }
+    }
+}
+
+/// パスアートの形状を扱うビュー（`AIPathSuite`）
+///
+/// セグメント番号は 0 始まり。SDK の制約でセグメント数は `i16` の範囲に収まる必要がある。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Path {
+    art: Art,
+}
+
+impl Path {
+    /// パスアートでなければ `AIError::BadParameter`
+    pub fn new(art: Art) -> AIResult<Path> {
+        match art.art_type()? {
+            ArtType::Path => Ok(Path { art }),
+            _ => Err(AIError::BadParameter),
+        }
+    }
+
+    /// 形状を指定して新しいパスアートを作る
+    pub fn create(paint_order: PaintOrder, prep: Option<Art>, bezier: &BezierPath) -> AIResult<Path> {
+        let art = Art::new(ArtType::Path, paint_order, prep)?;
+        let path = Path { art };
+
+        if let Err(error) = path.set_bezier(bezier) {
+            let _ = art.dispose();
+            return Err(error);
+        }
+        Ok(path)
+    }
+
+    pub fn art(self) -> Art {
+        self.art
+    }
+
+    pub fn segment_count(self) -> AIResult<usize> {
+        let suite = SuiteGuard::<AIPathSuite>::acquire()?;
+        let mut count: ai_int16 = 0;
+
+        unsafe { suite.GetPathSegmentCount.call((self.art.as_raw(), &mut count as *mut _))? };
+        Ok(count.max(0) as usize)
+    }
+
+    /// すべてのセグメント
+    pub fn segments(self) -> AIResult<Vec<PathSegment>> {
+        let count = self.segment_count()?;
+        self.segments_in(0..count)
+    }
+
+    /// `range` のセグメント
+    pub fn segments_in(self, range: Range<usize>) -> AIResult<Vec<PathSegment>> {
+        let suite = SuiteGuard::<AIPathSuite>::acquire()?;
+        let count = range.end.saturating_sub(range.start);
+        let mut segments: Vec<AIPathSegment> = vec![PathSegment::default().into(); count];
+
+        if count > 0 {
+            unsafe {
+                suite.GetPathSegments.call((
+                    self.art.as_raw(),
+                    to_i16(range.start)?,
+                    to_i16(count)?,
+                    segments.as_mut_ptr(),
+                ))?;
+            }
+        }
+        Ok(segments.into_iter().map(PathSegment::from).collect())
+    }
+
+    /// セグメントをすべて置き換える
+    pub fn set_segments(self, segments: &[PathSegment]) -> AIResult<()> {
+        let suite = SuiteGuard::<AIPathSuite>::acquire()?;
+        let raw = to_raw(segments);
+
+        unsafe {
+            suite.SetPathSegmentCount.call((self.art.as_raw(), to_i16(raw.len())?))?;
+            if !raw.is_empty() {
+                suite.SetPathSegments.call((self.art.as_raw(), 0, to_i16(raw.len())?, raw.as_ptr()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// `index` から始まるセグメントを上書きする（数は変わらない）
+    pub fn replace_segments(self, index: usize, segments: &[PathSegment]) -> AIResult<()> {
+        let suite = SuiteGuard::<AIPathSuite>::acquire()?;
+        let raw = to_raw(segments);
+
+        unsafe {
+            suite
+                .SetPathSegments
+                .call((self.art.as_raw(), to_i16(index)?, to_i16(raw.len())?, raw.as_ptr()))
+        }
+    }
+
+    /// `after` 番目のセグメントの後ろにセグメントを挿入する
+    pub fn insert_segments(self, after: usize, segments: &[PathSegment]) -> AIResult<()> {
+        let suite = SuiteGuard::<AIPathSuite>::acquire()?;
+        let raw = to_raw(segments);
+
+        unsafe {
+            suite
+                .InsertPathSegments
+                .call((self.art.as_raw(), to_i16(after)?, to_i16(raw.len())?, raw.as_ptr()))
+        }
+    }
+
+    /// `range` のセグメントを削除する
+    pub fn delete_segments(self, range: Range<usize>) -> AIResult<()> {
+        let suite = SuiteGuard::<AIPathSuite>::acquire()?;
+        let count = range.end.saturating_sub(range.start);
+
+        unsafe {
+            suite
+                .DeletePathSegments
+                .call((self.art.as_raw(), to_i16(range.start)?, to_i16(count)?))
+        }
+    }
+
+    pub fn is_closed(self) -> AIResult<bool> {
+        let suite = SuiteGuard::<AIPathSuite>::acquire()?;
+        let mut closed: AIBoolean = 0;
+
+        unsafe { suite.GetPathClosed.call((self.art.as_raw(), &mut closed as *mut _))? };
+        Ok(closed != 0)
+    }
+
+    pub fn set_closed(self, closed: bool) -> AIResult<()> {
+        let suite = SuiteGuard::<AIPathSuite>::acquire()?;
+        unsafe { suite.SetPathClosed.call((self.art.as_raw(), closed as AIBoolean)) }
+    }
+
+    /// ホストを介さずに扱える形状へ変換する
+    pub fn to_bezier(self) -> AIResult<BezierPath> {
+        Ok(BezierPath::new(self.segments()?, self.is_closed()?))
+    }
+
+    /// 形状をまとめて置き換える
+    pub fn set_bezier(self, bezier: &BezierPath) -> AIResult<()> {
+        self.set_segments(&bezier.segments)?;
+        self.set_closed(bezier.closed)
+    }
+}
+
+impl Art {
+    /// パスアートならパスとして扱う
+    pub fn as_path(self) -> AIResult<Path> {
+        Path::new(self)
+    }
+}
+
+fn to_i16(value: usize) -> AIResult<ai_int16> {
+    ai_int16::try_from(value).map_err(|_| AIError::BadParameter)
+}
+
+fn to_raw(segments: &[PathSegment]) -> Vec<AIPathSegment> {
+    segments.iter().copied().map(AIPathSegment::from).collect()
+}
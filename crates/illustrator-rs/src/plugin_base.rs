@@ -1,21 +1,23 @@
-use crate::AIPlugin;
-use crate::ai_suites::AISuite;
-
 /// マクロ `define_plugin!` は Adobe Illustrator プラグインのエントリーポイントを定義します。
 ///
 /// このマクロは、`AIPlugin` トレイトを実装した構造体を受け取り、
 /// C API との互換性を持つプラグインを生成します。
 ///
 /// # 引数
-/// * `$plugin_type` - `AIPlugin` トレイトを実装した構造体の型
+/// * `$plugin_type` - `AIPlugin` トレイトと `Default` を実装した構造体の型
 /// * `$plugin_name` - プラグインの名前（文字列リテラル）
 /// * `$suites` - オプション: 必要なスイートの配列 `suites = [AISuite::DocumentSuite, AISuite::RasterSuite]`
+/// * `$optional_suites` - オプション: 無くても起動できるスイートの配列 `optional_suites = [AISuite::LiveEffectSuite]`
+///
+/// 必須スイートが一つでも取得できなければ起動は失敗し、取得できなかったスイート名がエラーアラートで報告される。
+/// 任意スイートは取得できるまでメッセージごとに再取得される。
 ///
 /// # 例
 ///
-/// ```
-/// use illustrator::AIPlugin;
-/// use illustrator::ai_suites::AISuite;
+/// ```ignore
+/// use illustrator_rs::ai_suites::AISuite;
+/// use illustrator_rs::ai_sys::*;
+/// use illustrator_rs::AIPlugin;
 ///
 /// #[derive(Default)]
 /// struct MyPlugin {
@@ -31,126 +33,107 @@ use crate::ai_suites::AISuite;
 /// }
 ///
 /// // 基本形式
-/// illustrator::define_plugin!(MyPlugin, "My Illustrator Plugin");
+/// illustrator_rs::define_plugin!(MyPlugin, "My Illustrator Plugin");
 ///
 /// // スイート指定形式
-/// illustrator::define_plugin!(
+/// illustrator_rs::define_plugin!(
 ///     MyPlugin,
 ///     name = "My Illustrator Plugin",
 ///     suites = [AISuite::DocumentSuite, AISuite::RasterSuite]
 /// );
+///
+/// // 任意スイート指定形式
+/// illustrator_rs::define_plugin!(
+///     MyPlugin,
+///     name = "My Illustrator Plugin",
+///     suites = [AISuite::DocumentSuite],
+///     optional_suites = [AISuite::LiveEffectSuite]
+/// );
 /// ```
 #[macro_export]
 macro_rules! define_plugin {
     // 基本形式: プラグイン型と名前のみ
     ($plugin_type:ty, $plugin_name:expr) => {
-        define_plugin!($plugin_type, name = $plugin_name, suites = []);
+        $crate::define_plugin!($plugin_type, name = $plugin_name, suites = []);
     };
 
     // 拡張形式: プラグイン型、名前、スイート配列
-    ($plugin_type:ty, name = $plugin_name:expr, suites = [$($suite:expr),*]) => {
-        /// C言語関数のbindgen実装
-        extern "C" {
-          pub static mut sSPBasic: *mut SPBasicSuite;
-          pub static mut sAIUser: *mut AIUserSuite;
-          pub static mut sSPPlugins: *mut SPPluginsSuite;
-          pub static mut sAINotifier: *mut AINotifierSuite;
-          pub static mut sSPAccess: *mut  SPAccessSuite;
-        }
-
-        // グローバルプラグインインスタンス
-        static mut PLUGIN_INSTANCE: Option<Box<$crate::plugin_impl::Plugin<$plugin_type>>> = None;
-
-        // 必要なスイートを定義
-        static REQUIRED_SUITES: &'static [$crate::ai_suites::AISuite] = &[$($suite),*];
+    ($plugin_type:ty, name = $plugin_name:expr, suites = [$($suite:expr),* $(,)?]) => {
+        $crate::define_plugin!($plugin_type, name = $plugin_name, suites = [$($suite),*], optional_suites = []);
+    };
 
+    // 完全形式: プラグイン型、名前、必須スイート配列、任意スイート配列
+    (
+        $plugin_type:ty,
+        name = $plugin_name:expr,
+        suites = [$($suite:expr),* $(,)?],
+        optional_suites = [$($optional:expr),* $(,)?] $(,)?
+    ) => {
         /// プラグインのエントリーポイント
+        ///
+        /// パニックは `panic_guard` で捕捉され、C ABI を越えて巻き戻らない。
+        ///
+        /// # Safety
+        /// ホストから呼ばれることを前提とする。`caller`・`selector` は NUL 終端された文字列、
+        /// `message` は `SPMessageData` で始まるメッセージでなければならない。
         #[no_mangle]
-        pub extern "C" fn AllocatePlugin(plugin_ref: $crate::adobe_bindings::SPPluginRef) -> *mut $crate::plugin_impl::Plugin<$plugin_type> {
-            let plugin = Box::new($crate::plugin_impl::Plugin::<$plugin_type>::new(plugin_ref, $plugin_name));
-            unsafe {
-                PLUGIN_INSTANCE = Some(plugin);
-                PLUGIN_INSTANCE.as_mut().unwrap() as *mut _
-            }
-        }
-
-        #[no_mangle]
-        pub extern "C" fn FixupReload(_plugin: *mut $crate::plugin_impl::Plugin<$plugin_type>) {
-            // リロード処理後の修正処理
+        pub unsafe extern "C" fn PluginMain(
+            caller: *mut ::std::ffi::c_char,
+            selector: *mut ::std::ffi::c_char,
+            message: *mut ::std::ffi::c_void,
+        ) -> $crate::ai_sys::ASErr {
+            // 必須・任意スイートの宣言
+            static REQUIRED_SUITES: &[$crate::ai_suites::AISuite] = &[$($suite),*];
+            static OPTIONAL_SUITES: &[$crate::ai_suites::AISuite] = &[$($optional),*];
+
+            $crate::panic_guard::guard(caller, selector, message, || unsafe {
+                $crate::externs::plugin_main::<$plugin_type>(
+                    caller,
+                    selector,
+                    message,
+                    $plugin_name,
+                    REQUIRED_SUITES,
+                    OPTIONAL_SUITES,
+                )
+            })
         }
+    };
+}
 
-        #[no_mangle]
-        pub extern "C" fn PluginMain(
-            caller: *mut std::ffi::c_char,
-            selector: *mut std::ffi::c_char,
-            message: *mut std::ffi::c_void
-        ) -> $crate::adobe_bindings::ASErr {
-            use $crate::plugin_impl::*;
-            use $crate::adobe_bindings::*;
-            use std::ptr::null_mut;
-
-            let mut error = kNoErr;
-            let msg_data = unsafe { &mut *(message as *mut SPMessageData) };
-
-            let plugin = msg_data.globals as *mut Plugin<$plugin_type>;
-
-            unsafe { sSPBasic = msg_data.basic; }
-
-            unsafe {
-                // C文字列を比較
-                if strcmp(caller, kSPInterfaceCaller.as_ptr()) == 0 {
-                    if strcmp(selector, kSPInterfaceStartupSelector.as_ptr()) == 0 {
-                        let new_plugin = AllocatePlugin(msg_data.self_);
-                        if !new_plugin.is_null() {
-                            msg_data.globals = new_plugin as *mut std::ffi::c_void;
-                            error = (*new_plugin).StartupPlugin(message as *mut SPInterfaceMessage);
-
-                            if error != kNoErr {
-                                // スタートアップに失敗した場合は解放
-                                PLUGIN_INSTANCE = None;
-                                msg_data.globals = null_mut();
-                            }
-                        } else {
-                            error = kOutOfMemoryErr;
-                        }
-                    } else if strcmp(selector, kSPInterfaceShutdownSelector.as_ptr()) == 0 {
-                        if !plugin.is_null() {
-                            error = (*plugin).ShutdownPlugin(message as *mut SPInterfaceMessage);
-                            PLUGIN_INSTANCE = None;
-                            msg_data.globals = null_mut();
-                        }
-                    }
-                }
-
-                if !plugin.is_null() {
-                    if Plugin::<$plugin_type>::IsReloadMsg(caller, selector) {
-                        // virtual関数を呼び出す前にこれを呼ぶ（Message関数など）
-                        FixupReload(plugin);
-                        error = (*plugin).ReloadPlugin(message as *mut SPInterfaceMessage);
-                    } else {
-                        // ロードやリロードがスイート取得に失敗していた場合、保護する
-                        if (*plugin).SuitesAcquired() {
-                            error = (*plugin).Message(caller, selector, message);
-                        } else {
-                            error = kNoErr;
-                        }
-                    }
-
-                    if error == kUnhandledMsgErr {
-                        error = kNoErr;
-                    }
-                }
-
-                if error != 0 {
-                    if !plugin.is_null() {
-                        (*plugin).ReportError(error, caller, selector, message);
-                    } else {
-                        Plugin::<$plugin_type>::DefaultError(msg_data.self_, error);
-                    }
-                }
-            }
-
-            error
-        }
+#[cfg(test)]
+mod tests {
+    use std::ptr::null_mut;
+
+    use crate::ai_suites::AISuite;
+    use crate::ai_sys::*;
+    use crate::error::AIError;
+    use crate::AIPlugin;
+
+    #[derive(Default)]
+    struct DummyPlugin;
+
+    impl AIPlugin for DummyPlugin {}
+
+    crate::define_plugin!(
+        DummyPlugin,
+        name = "Dummy Plugin",
+        suites = [AISuite::ArtSuite, AISuite::PathSuite],
+        optional_suites = [AISuite::LiveEffectSuite],
+    );
+
+    #[test]
+    fn expands_to_plugin_main() {
+        let entry: unsafe extern "C" fn(*mut std::ffi::c_char, *mut std::ffi::c_char, *mut std::ffi::c_void) -> ASErr =
+            PluginMain;
+
+        // メッセージがなければ何もせずに拒否する
+        let error = unsafe {
+            entry(
+                kSPInterfaceCaller.as_ptr() as *mut _,
+                kSPInterfaceStartupSelector.as_ptr() as *mut _,
+                null_mut(),
+            )
+        };
+        assert_eq!(error, AIError::BadParameter.code());
     }
 }
@@ -0,0 +1,603 @@
+use std::cell::RefCell;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::ptr::null_mut;
+use std::rc::Rc;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::art::{Art, ArtType, PaintOrder};
+use crate::bytes::{from_bytes, to_bytes};
+use crate::dictionary;
+use crate::error::{AIError, AIResult, SuiteFn};
+use crate::externs::plugin_ref;
+use crate::util::{self, flags, read_c_string, Registry};
+
+// AIPluginGroup.h の `AIOperationTime`・`AIOperationCode` は文字列の #define でバインディングに含まれない
+#[allow(non_upper_case_globals)]
+const kCheckOperationTime: &[u8] = b"check";
+#[allow(non_upper_case_globals)]
+const kBeforeOperationTime: &[u8] = b"before";
+#[allow(non_upper_case_globals)]
+const kAfterOperationTime: &[u8] = b"after";
+
+/// `kSelectorAINotifyEdits` で通知される操作の種類（`AIOperationCode`）
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// ファイルから読み込まれた
+    Read,
+    /// ファイルへ書き出される
+    Write,
+    /// アートが `UseAIPluginGroup` でグループに結び付けられた
+    Attach,
+    /// 分割・拡張される
+    Expand,
+    /// 編集グループ内の重ね順の変更
+    Ordering,
+    /// 移動・拡大縮小などの変形
+    Transform,
+    /// パスの形の変更
+    Geometry,
+    /// パスの分割・連結などの構造の変更
+    Topology,
+    Delete,
+    /// 表示・ロックなどの属性の変更
+    Attribute,
+    /// 塗りや線などのスタイルの変更
+    Style,
+    /// プラグインの呼び出しによる変更
+    Change,
+    /// 上記以外
+    Other,
+}
+
+impl Operation {
+    const CODES: [(&'static [u8], Operation); 12] = [
+        (b"read", Operation::Read),
+        (b"write", Operation::Write),
+        (b"attach", Operation::Attach),
+        (b"expand", Operation::Expand),
+        (b"order", Operation::Ordering),
+        (b"xform", Operation::Transform),
+        (b"geometry", Operation::Geometry),
+        (b"topology", Operation::Topology),
+        (b"delete", Operation::Delete),
+        (b"attribute", Operation::Attribute),
+        (b"style", Operation::Style),
+        (b"change", Operation::Change),
+    ];
+
+    pub fn from_code(code: &CStr) -> Self {
+        Self::CODES
+            .iter()
+            .find(|(name, _)| *name == code.to_bytes())
+            .map_or(Operation::Other, |&(_, operation)| operation)
+    }
+}
+
+/// 編集の通知への応答
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EditResponse {
+    /// 編集を許し、結果グループを作り直す（`kNoErr`。後で `PluginGroupClass::update` が呼ばれる）
+    Update,
+    /// 編集を許すが、結果には影響しない（`kDontCarePluginGroupReply`）
+    Ignore,
+    /// 編集を拒む（`kRefusePluginGroupReply`）
+    Refuse,
+    /// 編集の後に `PluginGroupClass::after_edit` を呼ぶ（`kWantsAfterMsgPluginGroupReply`）
+    After,
+}
+
+/// 操作の種類ごとの、編集の通知への応答
+///
+/// ```ignore
+/// let policy = EditPolicy::new(EditResponse::Update)
+///     .on(Operation::Ordering, EditResponse::Refuse)
+///     .on(Operation::Attribute, EditResponse::Ignore);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EditPolicy {
+    default: EditResponse,
+    rules: Vec<(Operation, EditResponse)>,
+}
+
+impl Default for EditPolicy {
+    fn default() -> Self {
+        Self::new(EditResponse::Update)
+    }
+}
+
+impl EditPolicy {
+    /// `on` で指定しなかった操作には `default` で応える
+    pub fn new(default: EditResponse) -> Self {
+        Self { default, rules: Vec::new() }
+    }
+
+    pub fn on(mut self, operation: Operation, response: EditResponse) -> Self {
+        self.rules.retain(|&(existing, _)| existing != operation);
+        self.rules.push((operation, response));
+        self
+    }
+
+    pub fn response(&self, operation: Operation) -> EditResponse {
+        self.rules
+            .iter()
+            .find(|&&(existing, _)| existing == operation)
+            .map_or(self.default, |&(_, response)| response)
+    }
+
+    /// 操作の前（`kCheckOperationTime`・`kBeforeOperationTime`）に返す値
+    fn reply(&self, time: &[u8], operation: Operation) -> AIResult<()> {
+        let response = self.response(operation);
+        let reply = match response {
+            EditResponse::Refuse => kRefusePluginGroupReply,
+            // 確認の時点では拒むかどうかだけを返す
+            _ if time == kCheckOperationTime => return Ok(()),
+            EditResponse::Update => return Ok(()),
+            EditResponse::Ignore => kDontCarePluginGroupReply,
+            EditResponse::After => kWantsAfterMsgPluginGroupReply,
+        };
+        Err(AIError::Other(reply as ASErr))
+    }
+}
+
+/// `PluginGroupClass::after_edit` の結果
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AfterEdit {
+    /// 結果グループを作り直す（`kNoErr`）
+    Update,
+    /// 結果グループは更新済み（`kMarkValidPluginGroupReply`）
+    Valid,
+    /// プラグインアートを削除する（`kDestroyPluginGroupReply`）
+    Destroy,
+}
+
+/// `kAfterOperationTime` で通知された編集
+#[derive(Clone, Copy, Debug)]
+pub struct Edit {
+    pub operation: Operation,
+    /// 編集された編集グループ内のアート（操作によっては `None`）
+    pub pre_edit_art: Option<Art>,
+    pub post_edit_art: Option<Art>,
+    /// `Operation::Transform` の変形
+    pub matrix: AIRealMatrix,
+}
+
+flags! {
+    /// `AddAIPluginGroup` に渡すオプション（`AIPluginGroupOptions`）
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct PluginGroupOptions(u32) {
+        /// 重なったほかのアートの編集でも更新する
+        const OVERLAP_UPDATE = AIPluginGroupOptions_kPluginGroupWantsOverlapUpdateOption;
+        /// 移動を結果グループにもそのまま適用する（通知を送らない）
+        const AUTO_TRANSLATE = AIPluginGroupOptions_kPluginGroupWantsAutoTranslateOption;
+        const AUTO_UNIFORM_SCALE = AIPluginGroupOptions_kPluginGroupWantsAutoUScaleOption;
+        const AUTO_TRANSFORM = AIPluginGroupOptions_kPluginGroupWantsAutoTransformOption;
+        const EDIT_FILL_HIT = AIPluginGroupOptions_kPluginGroupDoEditFillHitOption;
+        /// 編集グループが空になっても削除しない
+        const KEEP_WHEN_EMPTY = AIPluginGroupOptions_kPluginGroupKeepWhenEmptyOption;
+        const FILL_LIKE = AIPluginGroupOptions_kPluginGroupIsFillLike;
+        const STROKE_LIKE = AIPluginGroupOptions_kPluginGroupIsStrokeLike;
+        const DO_NOT_TARGET = AIPluginGroupOptions_kPluginGroupDoNotTarget;
+        const ALWAYS_SMART_TARGET = AIPluginGroupOptions_kPluginGroupAlwaysSmartTarget;
+        const DO_NOT_SMART_TARGET = AIPluginGroupOptions_kPluginGroupDoNotSmartTarget;
+        const CAN_BE_CLIPPING = AIPluginGroupOptions_kPluginGroupCanBeClipping;
+        const ALWAYS_SHOW_CONTENTS = AIPluginGroupOptions_kPluginGroupAlwaysShowContents;
+        const ASK_TO_SHOW_CONTENTS = AIPluginGroupOptions_kPluginGroupAskToShowContents;
+        const DISABLE_BLENDS = AIPluginGroupOptions_kPluginGroupDisableBlends;
+        const BLEND_AS_PLUGIN_GROUP = AIPluginGroupOptions_kPluginGroupBlendAsPluginGroup;
+        const RESTRICTS_ART_TYPES = AIPluginGroupOptions_kPluginGroupRestrictsArtTypes;
+        const FORCE_FILL_HIT_ON_CLOSED_PATHS = AIPluginGroupOptions_kPluginGroupForceFillHitOnClosedPaths;
+        const FORCE_FILL_HIT_ON_ALL_PATHS = AIPluginGroupOptions_kPluginGroupForceFillHitOnAllPaths;
+        const MANAGES_PAINT_STYLES = AIPluginGroupOptions_kPluginGroupManagesPaintStyles;
+        /// 編集グループ内でのグループ化などを許す
+        const PERMITS_GROUP_COMMANDS = AIPluginGroupOptions_kPluginGroupPermitsGroupCmds;
+    }
+}
+
+/// 登録されたプラグイングループ
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PluginGroup(AIPluginGroupHandle);
+
+impl PluginGroup {
+    /// # Safety
+    /// `handle` は `AIPluginGroupSuite` が返した有効なプラグイングループでなければならない。
+    pub unsafe fn from_raw(handle: AIPluginGroupHandle) -> Option<Self> {
+        (!handle.is_null()).then_some(Self(handle))
+    }
+
+    pub fn as_raw(self) -> AIPluginGroupHandle {
+        self.0
+    }
+
+    /// `AddAIPluginGroup` に渡した名前
+    pub fn name(self) -> AIResult<String> {
+        let suite = SuiteGuard::<AIPluginGroupSuite>::acquire()?;
+        let mut name: *mut c_char = null_mut();
+
+        unsafe {
+            suite.GetAIPluginGroupName.call((self.0, &mut name as *mut _))?;
+            read_c_string(name)
+        }
+    }
+
+    pub fn description(self) -> AIResult<String> {
+        let suite = SuiteGuard::<AIPluginGroupSuite>::acquire()?;
+        let mut description: *mut c_char = null_mut();
+
+        unsafe {
+            suite.GetAIPluginGroupDescription.call((self.0, &mut description as *mut _))?;
+            read_c_string(description)
+        }
+    }
+
+    /// （メジャー, マイナー）バージョン
+    pub fn version(self) -> AIResult<(i32, i32)> {
+        let suite = SuiteGuard::<AIPluginGroupSuite>::acquire()?;
+        let (mut major, mut minor): (ai_int32, ai_int32) = (0, 0);
+
+        unsafe { suite.GetAIPluginGroupVersion.call((self.0, &mut major as *mut _, &mut minor as *mut _))? };
+        Ok((major, minor))
+    }
+
+    pub fn options(self) -> AIResult<PluginGroupOptions> {
+        let suite = SuiteGuard::<AIPluginGroupSuite>::acquire()?;
+        let mut options: ai_int32 = 0;
+
+        unsafe { suite.GetAIPluginGroupOptions.call((self.0, &mut options as *mut _))? };
+        Ok(PluginGroupOptions(options as u32))
+    }
+}
+
+/// プラグイングループに結び付いたアート（`kPluginArt`）
+///
+/// ユーザーが編集する編集グループと、プラグインが作る結果グループを持つ。
+/// どちらのグループもこのアートの子ではない。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PluginArt(Art);
+
+impl PluginArt {
+    /// プラグインアートを作り、`group` に結び付ける
+    pub fn new(group: PluginGroup, paint_order: PaintOrder, prep: Option<Art>) -> AIResult<PluginArt> {
+        let art = Art::new(ArtType::Plugin, paint_order, prep)?;
+        let suite = SuiteGuard::<AIPluginGroupSuite>::acquire()?;
+
+        unsafe { suite.UseAIPluginGroup.call((art.as_raw(), group.as_raw()))? };
+        Ok(PluginArt(art))
+    }
+
+    /// `art` がプラグインアートなら `Some`
+    pub fn from_art(art: Art) -> AIResult<Option<PluginArt>> {
+        Ok((art.art_type()? == ArtType::Plugin).then_some(PluginArt(art)))
+    }
+
+    pub fn art(self) -> Art {
+        self.0
+    }
+
+    pub fn plugin_group(self) -> AIResult<Option<PluginGroup>> {
+        let suite = SuiteGuard::<AIPluginGroupSuite>::acquire()?;
+        let mut group: AIPluginGroupHandle = null_mut();
+
+        unsafe {
+            suite.GetPluginArtPluginGroup.call((self.0.as_raw(), &mut group as *mut _))?;
+            Ok(PluginGroup::from_raw(group))
+        }
+    }
+
+    /// ユーザーが編集するアートを入れるグループ（非表示）
+    pub fn edit_group(self) -> AIResult<Art> {
+        let suite = SuiteGuard::<AIPluginGroupSuite>::acquire()?;
+        let mut art: AIArtHandle = null_mut();
+
+        unsafe {
+            suite.GetPluginArtEditArt.call((self.0.as_raw(), &mut art as *mut _))?;
+            Art::from_raw(art).ok_or(AIError::CantHappen)
+        }
+    }
+
+    /// プラグインが作ったアートを入れるグループ（表示されるが選択できない）
+    pub fn result_group(self) -> AIResult<Art> {
+        let suite = SuiteGuard::<AIPluginGroupSuite>::acquire()?;
+        let mut art: AIArtHandle = null_mut();
+
+        unsafe {
+            suite.GetPluginArtResultArt.call((self.0.as_raw(), &mut art as *mut _))?;
+            Art::from_raw(art).ok_or(AIError::CantHappen)
+        }
+    }
+
+    /// 結果グループの子をすべて削除する
+    pub fn clear_result(self) -> AIResult<()> {
+        let children = self.result_group()?.children().collect::<AIResult<Vec<_>>>()?;
+        children.into_iter().try_for_each(Art::dispose)
+    }
+
+    /// 結果グループを空にし、編集グループの子を重ね順どおりに複製して入れる（複製を上から返す）
+    pub fn copy_edit_to_result(self) -> AIResult<Vec<Art>> {
+        self.clear_result()?;
+        let result = self.result_group()?;
+
+        self.edit_group()?
+            .children()
+            .collect::<AIResult<Vec<_>>>()?
+            .into_iter()
+            .map(|child| child.duplicate(PaintOrder::InsideOnBottom, Some(result)))
+            .collect()
+    }
+
+    /// 編集グループの子を結果グループに複製し、複製ごとに `f` を呼ぶ
+    pub fn rebuild_result(self, f: impl FnMut(Art) -> AIResult<()>) -> AIResult<()> {
+        self.copy_edit_to_result()?.into_iter().try_for_each(f)
+    }
+
+    /// データ領域のバイト列
+    pub fn data(self) -> AIResult<Vec<u8>> {
+        let suite = SuiteGuard::<AIPluginGroupSuite>::acquire()?;
+        let mut count: usize = 0;
+
+        unsafe { suite.GetPluginArtDataCount.call((self.0.as_raw(), &mut count as *mut _))? };
+        let mut data = vec![0u8; count];
+        if count > 0 {
+            unsafe {
+                suite
+                    .GetPluginArtDataRange
+                    .call((self.0.as_raw(), data.as_mut_ptr() as *mut c_void, 0, count))?;
+            }
+        }
+        Ok(data)
+    }
+
+    /// データ領域を `data` で置き換える
+    pub fn set_data(self, data: &[u8]) -> AIResult<()> {
+        let suite = SuiteGuard::<AIPluginGroupSuite>::acquire()?;
+
+        unsafe {
+            suite.SetPluginArtDataCount.call((self.0.as_raw(), data.len()))?;
+            if !data.is_empty() {
+                // SDK は値を複製するだけなので `*mut` でも書き換えられない
+                suite
+                    .SetPluginArtDataRange
+                    .call((self.0.as_raw(), data.as_ptr() as *mut c_void, 0, data.len()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// データ領域を `T` として読む（空なら既定値）
+    pub fn load<T: DeserializeOwned + Default>(self) -> Result<T, dictionary::Error> {
+        let data = self.data()?;
+        if data.is_empty() {
+            return Ok(T::default());
+        }
+        from_bytes(&data)
+    }
+
+    /// `value` をシリアライズしてデータ領域に格納する
+    pub fn store<T: Serialize + ?Sized>(self, value: &T) -> Result<(), dictionary::Error> {
+        Ok(self.set_data(&to_bytes(value)?)?)
+    }
+
+    /// 結果グループを作り直す必要があることをホストに知らせる
+    pub fn mark_dirty(self) -> AIResult<()> {
+        let suite = SuiteGuard::<AIPluginGroupSuite>::acquire()?;
+        unsafe { suite.MarkPluginArtDirty.call((self.0.as_raw(),)) }
+    }
+
+    pub fn mark_clean(self) -> AIResult<()> {
+        let suite = SuiteGuard::<AIPluginGroupSuite>::acquire()?;
+        unsafe { suite.MarkPluginArtClean.call((self.0.as_raw(),)) }
+    }
+}
+
+/// プラグイングループの振る舞い
+///
+/// `Data` はプラグインアートのデータ領域に `to_bytes` で格納される（空なら `Data::default()`）。
+/// ハンドラが `Data` を変更した場合だけ書き戻す。
+#[allow(unused_variables)]
+pub trait PluginGroupClass<P> {
+    type Data: Serialize + DeserializeOwned + Default;
+
+    /// 編集グループから結果グループを作り直す（`kSelectorAIUpdateArt`）
+    ///
+    /// 多くの場合は `PluginArt::rebuild_result` で編集グループの複製を加工すればよい。
+    fn update(&mut self, plugin: &mut P, art: PluginArt, data: &mut Self::Data) -> AIResult<()>;
+
+    /// `EditResponse::After` と応えた操作の後に呼ばれる（`kAfterOperationTime`）
+    fn after_edit(&mut self, plugin: &mut P, art: PluginArt, edit: &Edit, data: &mut Self::Data) -> AIResult<AfterEdit> {
+        Ok(AfterEdit::Update)
+    }
+}
+
+/// プラグイングループへのメッセージの種類
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum PluginGroupSelector {
+    NotifyEdits,
+    Update,
+}
+
+/// `Data` の型を隠してメッセージを `PluginGroupClass` へ渡す
+trait Handler<P> {
+    fn after_edit(&mut self, plugin: &mut P, art: PluginArt, edit: &Edit) -> AIResult<AfterEdit>;
+    fn update(&mut self, plugin: &mut P, art: PluginArt) -> AIResult<()>;
+}
+
+impl<P, C: PluginGroupClass<P>> Handler<P> for C {
+    fn after_edit(&mut self, plugin: &mut P, art: PluginArt, edit: &Edit) -> AIResult<AfterEdit> {
+        with_data(art, |data: &mut C::Data| PluginGroupClass::after_edit(self, plugin, art, edit, data))
+    }
+
+    fn update(&mut self, plugin: &mut P, art: PluginArt) -> AIResult<()> {
+        with_data(art, |data: &mut C::Data| PluginGroupClass::update(self, plugin, art, data))
+    }
+}
+
+/// データ領域を読んで `f` に渡し、変わっていれば書き戻す
+fn with_data<T, R>(art: PluginArt, f: impl FnOnce(&mut T) -> AIResult<R>) -> AIResult<R>
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    let stored = art.data()?;
+    let mut data = if stored.is_empty() { T::default() } else { from_bytes(&stored)? };
+
+    let result = f(&mut data)?;
+    let updated = to_bytes(&data)?;
+    if updated != stored {
+        art.set_data(&updated)?;
+    }
+    Ok(result)
+}
+
+/// プラグインが登録したプラグイングループと、その振る舞い
+///
+/// 起動時（`SafePlugin::startup`）にグループを登録し、`SafePlugin::plugin_groups` から返すと、
+/// `kSelectorAINotifyEdits` には `EditPolicy` に従って応え、`kSelectorAIUpdateArt` は
+/// `PluginGroupClass::update` へ送られる。
+pub struct PluginGroups<P> {
+    entries: Registry<PluginGroup, dyn Handler<P>, EditPolicy>,
+}
+
+impl<P> Default for PluginGroups<P> {
+    fn default() -> Self {
+        Self { entries: Registry::default() }
+    }
+}
+
+impl<P> PluginGroups<P> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// グループの設定を始める
+    ///
+    /// `name` はグループを識別する一意な名前（ドキュメントに保存される）。
+    pub fn group<'a>(&'a mut self, name: &'a CStr, description: &'a str) -> PluginGroupBuilder<'a, P> {
+        PluginGroupBuilder {
+            groups: self,
+            name,
+            description,
+            version: (1, 0),
+            options: PluginGroupOptions::empty(),
+            policy: EditPolicy::default(),
+        }
+    }
+
+    /// 登録したグループ（登録順）
+    pub fn groups(&self) -> impl Iterator<Item = PluginGroup> + '_ {
+        self.entries.keys()
+    }
+
+    pub fn contains(&self, group: AIPluginGroupHandle) -> bool {
+        self.entries.contains(PluginGroup(group))
+    }
+
+    /// グループの編集への応答
+    pub fn policy(&self, group: AIPluginGroupHandle) -> Option<&EditPolicy> {
+        self.entries.get(PluginGroup(group))
+    }
+}
+
+/// 編集の前は `EditPolicy` で応え、編集の後と `kSelectorAIUpdateArt` は `PluginGroupClass` へ送る
+pub(crate) fn dispatch<P>(
+    plugin: &mut P,
+    groups: fn(&mut P) -> Option<&mut PluginGroups<P>>,
+    message: *mut AIPluginGroupMessage,
+    selector: PluginGroupSelector,
+) -> Option<AIResult<()>> {
+    let message = unsafe { message.as_ref() }?;
+    let group = PluginGroup(message.entry);
+    let art = unsafe { Art::from_raw(message.art) }.map(PluginArt);
+
+    util::dispatch(plugin, |plugin| Some(&groups(plugin)?.entries), group, |behavior, plugin, policy| {
+        let Some(art) = art else {
+            return Err(AIError::BadParameter);
+        };
+        match selector {
+            PluginGroupSelector::Update => behavior.update(plugin, art),
+            PluginGroupSelector::NotifyEdits => {
+                let time = unsafe { CStr::from_ptr(message.time) }.to_bytes();
+                let operation = Operation::from_code(unsafe { CStr::from_ptr(message.code) });
+
+                if time == kCheckOperationTime || time == kBeforeOperationTime {
+                    policy.reply(time, operation)
+                } else if time == kAfterOperationTime {
+                    let edit = Edit {
+                        operation,
+                        pre_edit_art: unsafe { Art::from_raw(message.preEditArt) },
+                        post_edit_art: unsafe { Art::from_raw(message.postEditArt) },
+                        matrix: message.matrix,
+                    };
+                    behavior.after_edit(plugin, art, &edit).and_then(|after| match after {
+                        AfterEdit::Update => Ok(()),
+                        AfterEdit::Valid => Err(AIError::Other(kMarkValidPluginGroupReply as ASErr)),
+                        AfterEdit::Destroy => Err(AIError::Other(kDestroyPluginGroupReply as ASErr)),
+                    })
+                } else {
+                    // kDuringOperationTime など
+                    Ok(())
+                }
+            }
+        }
+    })
+}
+
+/// `PluginGroups::group` が返す、グループの設定
+pub struct PluginGroupBuilder<'a, P> {
+    groups: &'a mut PluginGroups<P>,
+    name: &'a CStr,
+    description: &'a str,
+    version: (i32, i32),
+    options: PluginGroupOptions,
+    policy: EditPolicy,
+}
+
+impl<'a, P> PluginGroupBuilder<'a, P> {
+    /// データの形式のバージョン（既定は 1.0）
+    pub fn version(mut self, major: i32, minor: i32) -> Self {
+        self.version = (major, minor);
+        self
+    }
+
+    pub fn options(mut self, options: PluginGroupOptions) -> Self {
+        self.options |= options;
+        self
+    }
+
+    /// 編集の通知への応答（既定はすべて `EditResponse::Update`）
+    pub fn policy(mut self, policy: EditPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// グループを登録し、`class` と結び付ける
+    pub fn add<C>(self, class: C) -> AIResult<PluginGroup>
+    where
+        C: PluginGroupClass<P> + 'static,
+    {
+        let suite = SuiteGuard::<AIPluginGroupSuite>::acquire()?;
+        let description = CString::new(self.description).map_err(|_| AIError::BadParameter)?;
+        let mut handle: AIPluginGroupHandle = null_mut();
+
+        let mut data = AIAddPluginGroupData {
+            major: self.version.0,
+            minor: self.version.1,
+            desc: description.as_ptr(),
+        };
+
+        let group = unsafe {
+            suite.AddAIPluginGroup.call((
+                plugin_ref(),
+                self.name.as_ptr(),
+                &mut data as *mut _,
+                self.options.bits() as ai_int32,
+                &mut handle as *mut _,
+            ))?;
+            PluginGroup::from_raw(handle).ok_or(AIError::CantHappen)?
+        };
+
+        self.groups.entries.push(group, Rc::new(RefCell::new(class)), self.policy);
+        Ok(group)
+    }
+}
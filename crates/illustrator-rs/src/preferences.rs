@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+use std::ffi::CString;
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::error::{AIError, AIResult, SuiteFn};
+use crate::unicode::UnicodeString;
+
+pub use illustrator_derive::Preferences;
+
+/// 保存形式の版を記録するキー（`#[derive(Preferences)]` のフィールドには使えない）
+pub const VERSION_KEY: &str = "_version";
+
+/// 設定値の種類（`AIPreferenceSuite` の `Get*Preference` に対応）
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PreferenceKind {
+    Boolean,
+    Integer,
+    Real,
+    /// Unicode 文字列
+    String,
+}
+
+/// 設定値
+#[derive(Clone, Debug, PartialEq)]
+pub enum PreferenceValue {
+    Boolean(bool),
+    Integer(i32),
+    Real(f64),
+    String(String),
+}
+
+impl PreferenceValue {
+    pub fn kind(&self) -> PreferenceKind {
+        match self {
+            PreferenceValue::Boolean(_) => PreferenceKind::Boolean,
+            PreferenceValue::Integer(_) => PreferenceKind::Integer,
+            PreferenceValue::Real(_) => PreferenceKind::Real,
+            PreferenceValue::String(_) => PreferenceKind::String,
+        }
+    }
+}
+
+/// 設定値として保存できる型
+pub trait PreferenceField: Sized {
+    const KIND: PreferenceKind;
+
+    fn to_preference(&self) -> PreferenceValue;
+
+    /// 種類が違う、または範囲に収まらなければ `None`
+    fn from_preference(value: PreferenceValue) -> Option<Self>;
+}
+
+impl PreferenceField for bool {
+    const KIND: PreferenceKind = PreferenceKind::Boolean;
+
+    fn to_preference(&self) -> PreferenceValue {
+        PreferenceValue::Boolean(*self)
+    }
+
+    fn from_preference(value: PreferenceValue) -> Option<Self> {
+        match value {
+            PreferenceValue::Boolean(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// `i32` に収まる整数型
+macro_rules! integer_fields {
+    ($($ty:ty),*) => {
+        $(
+            impl PreferenceField for $ty {
+                const KIND: PreferenceKind = PreferenceKind::Integer;
+
+                fn to_preference(&self) -> PreferenceValue {
+                    PreferenceValue::Integer(i32::from(*self))
+                }
+
+                fn from_preference(value: PreferenceValue) -> Option<Self> {
+                    match value {
+                        PreferenceValue::Integer(value) => <$ty>::try_from(value).ok(),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+integer_fields!(i8, i16, i32, u8, u16);
+
+impl PreferenceField for f64 {
+    const KIND: PreferenceKind = PreferenceKind::Real;
+
+    fn to_preference(&self) -> PreferenceValue {
+        PreferenceValue::Real(*self)
+    }
+
+    fn from_preference(value: PreferenceValue) -> Option<Self> {
+        match value {
+            PreferenceValue::Real(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl PreferenceField for f32 {
+    const KIND: PreferenceKind = PreferenceKind::Real;
+
+    fn to_preference(&self) -> PreferenceValue {
+        PreferenceValue::Real(f64::from(*self))
+    }
+
+    fn from_preference(value: PreferenceValue) -> Option<Self> {
+        f64::from_preference(value).map(|value| value as f32)
+    }
+}
+
+impl PreferenceField for String {
+    const KIND: PreferenceKind = PreferenceKind::String;
+
+    fn to_preference(&self) -> PreferenceValue {
+        PreferenceValue::String(self.clone())
+    }
+
+    fn from_preference(value: PreferenceValue) -> Option<Self> {
+        match value {
+            PreferenceValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// 設定の保存先
+///
+/// 設定はプレフィックス（通常はプラグイン名）とサフィックス（キー）の組で識別される。
+pub trait PreferenceBackend {
+    /// 保存された値（なければ `None`）
+    fn get(&self, prefix: &str, suffix: &str, kind: PreferenceKind) -> AIResult<Option<PreferenceValue>>;
+
+    fn put(&mut self, prefix: &str, suffix: &str, value: &PreferenceValue) -> AIResult<()>;
+
+    /// 値を削除する（なければ何もしない）
+    fn remove(&mut self, prefix: &str, suffix: &str) -> AIResult<()>;
+}
+
+/// `AIPreferenceSuite` を使う保存先（Illustrator の環境設定ファイル）
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HostPreferences;
+
+fn c_string(value: &str) -> AIResult<CString> {
+    CString::new(value).map_err(|_| AIError::BadParameter)
+}
+
+impl PreferenceBackend for HostPreferences {
+    fn get(&self, prefix: &str, suffix: &str, kind: PreferenceKind) -> AIResult<Option<PreferenceValue>> {
+        let suite = SuiteGuard::<AIPreferenceSuite>::acquire()?;
+        let (prefix, suffix) = (c_string(prefix)?, c_string(suffix)?);
+        let (prefix, suffix) = (prefix.as_ptr(), suffix.as_ptr());
+        let mut exists: AIBoolean = 0;
+
+        unsafe { suite.PreferenceExists.call((prefix, suffix, &mut exists as *mut _))? };
+        if exists == 0 {
+            return Ok(None);
+        }
+
+        let value = unsafe {
+            match kind {
+                PreferenceKind::Boolean => {
+                    let mut value: AIBoolean = 0;
+                    suite.GetBooleanPreference.call((prefix, suffix, &mut value as *mut _))?;
+                    PreferenceValue::Boolean(value != 0)
+                }
+                PreferenceKind::Integer => {
+                    let mut value: ai_int32 = 0;
+                    suite.GetIntegerPreference.call((prefix, suffix, &mut value as *mut _))?;
+                    PreferenceValue::Integer(value)
+                }
+                PreferenceKind::Real => {
+                    let mut value: f64 = 0.0;
+                    suite.GetRealPreference.call((prefix, suffix, &mut value as *mut _))?;
+                    PreferenceValue::Real(value)
+                }
+                PreferenceKind::String => {
+                    let mut value = UnicodeString::empty()?;
+                    suite.GetUnicodeStringPreference.call((prefix, suffix, value.as_mut_ptr()))?;
+                    PreferenceValue::String(value.to_string_lossy())
+                }
+            }
+        };
+        Ok(Some(value))
+    }
+
+    fn put(&mut self, prefix: &str, suffix: &str, value: &PreferenceValue) -> AIResult<()> {
+        let suite = SuiteGuard::<AIPreferenceSuite>::acquire()?;
+        let (prefix, suffix) = (c_string(prefix)?, c_string(suffix)?);
+        let (prefix, suffix) = (prefix.as_ptr(), suffix.as_ptr());
+
+        unsafe {
+            match value {
+                PreferenceValue::Boolean(value) => {
+                    suite.PutBooleanPreference.call((prefix, suffix, *value as AIBoolean))
+                }
+                PreferenceValue::Integer(value) => suite.PutIntegerPreference.call((prefix, suffix, *value)),
+                PreferenceValue::Real(value) => suite.PutRealPreference.call((prefix, suffix, *value)),
+                PreferenceValue::String(value) => {
+                    let value = UnicodeString::new(value)?;
+                    suite.PutUnicodeStringPreference.call((prefix, suffix, value.as_ptr()))
+                }
+            }
+        }
+    }
+
+    fn remove(&mut self, prefix: &str, suffix: &str) -> AIResult<()> {
+        let suite = SuiteGuard::<AIPreferenceSuite>::acquire()?;
+        let (prefix, suffix) = (c_string(prefix)?, c_string(suffix)?);
+        unsafe { suite.RemovePreference.call((prefix.as_ptr(), suffix.as_ptr())) }
+    }
+}
+
+/// メモリ上の保存先（ホストなしで設定の読み書きを確かめる）
+///
+/// 種類の違う値は保存されていないものとして扱う。
+#[derive(Clone, Debug, Default)]
+pub struct MemoryPreferences {
+    values: HashMap<(String, String), PreferenceValue>,
+}
+
+impl MemoryPreferences {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 種類を問わず保存された値
+    pub fn value(&self, prefix: &str, suffix: &str) -> Option<&PreferenceValue> {
+        self.values.get(&(prefix.to_owned(), suffix.to_owned()))
+    }
+
+    pub fn insert(&mut self, prefix: &str, suffix: &str, value: PreferenceValue) {
+        self.values.insert((prefix.to_owned(), suffix.to_owned()), value);
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl PreferenceBackend for MemoryPreferences {
+    fn get(&self, prefix: &str, suffix: &str, kind: PreferenceKind) -> AIResult<Option<PreferenceValue>> {
+        Ok(self
+            .value(prefix, suffix)
+            .filter(|value| value.kind() == kind)
+            .cloned())
+    }
+
+    fn put(&mut self, prefix: &str, suffix: &str, value: &PreferenceValue) -> AIResult<()> {
+        self.insert(prefix, suffix, value.clone());
+        Ok(())
+    }
+
+    fn remove(&mut self, prefix: &str, suffix: &str) -> AIResult<()> {
+        self.values.remove(&(prefix.to_owned(), suffix.to_owned()));
+        Ok(())
+    }
+}
+
+/// プレフィックスを固定した型付きの保存先
+pub struct PreferenceStore<'a> {
+    backend: &'a mut dyn PreferenceBackend,
+    prefix: &'a str,
+}
+
+impl<'a> PreferenceStore<'a> {
+    pub fn new(backend: &'a mut dyn PreferenceBackend, prefix: &'a str) -> Self {
+        Self { backend, prefix }
+    }
+
+    pub fn prefix(&self) -> &str {
+        self.prefix
+    }
+
+    /// 保存された値（なければ、または型に合わなければ `None`）
+    pub fn get<T: PreferenceField>(&self, key: &str) -> AIResult<Option<T>> {
+        let value = self.backend.get(self.prefix, key, T::KIND)?;
+        Ok(value.and_then(T::from_preference))
+    }
+
+    pub fn set<T: PreferenceField>(&mut self, key: &str, value: &T) -> AIResult<()> {
+        self.backend.put(self.prefix, key, &value.to_preference())
+    }
+
+    pub fn remove(&mut self, key: &str) -> AIResult<()> {
+        self.backend.remove(self.prefix, key)
+    }
+
+    /// `from` の値を `to` へ移す（`to` に値があれば `from` を消すだけ）
+    ///
+    /// 値を移したかどうかを返す。
+    pub fn rename<T: PreferenceField>(&mut self, from: &str, to: &str) -> AIResult<bool> {
+        let Some(value) = self.get::<T>(from)? else {
+            return Ok(false);
+        };
+
+        let moved = self.get::<T>(to)?.is_none();
+        if moved {
+            self.set(to, &value)?;
+        }
+        self.remove(from)?;
+        Ok(moved)
+    }
+}
+
+/// プラグインの設定をまとめた構造体（`#[derive(Preferences)]` で実装する）
+///
+/// 各フィールドは `PREFIX` の下のキーに保存され、`VERSION_KEY` に保存形式の版が記録される。
+/// 記録された版が `VERSION` より古ければ、読み込む前に `migrate` が呼ばれる。
+pub trait Preferences: Sized {
+    const PREFIX: &'static str;
+    const VERSION: i32;
+
+    /// 各フィールドを読む（保存されていなければ既定値）
+    fn read(store: &mut PreferenceStore<'_>) -> AIResult<Self>;
+
+    fn write(&self, store: &mut PreferenceStore<'_>) -> AIResult<()>;
+
+    /// `from` 版で保存された値を現在の版に合わせる（版が記録されていなければ `from` は 0）
+    fn migrate(_store: &mut PreferenceStore<'_>, _from: i32) -> AIResult<()> {
+        Ok(())
+    }
+
+    /// Illustrator の環境設定から読み込む
+    fn load() -> AIResult<Self> {
+        Self::load_from(&mut HostPreferences)
+    }
+
+    /// Illustrator の環境設定へ保存する
+    fn save(&self) -> AIResult<()> {
+        self.save_to(&mut HostPreferences)
+    }
+
+    fn load_from(backend: &mut dyn PreferenceBackend) -> AIResult<Self> {
+        let mut store = PreferenceStore::new(backend, Self::PREFIX);
+        let version = store.get::<i32>(VERSION_KEY)?.unwrap_or(0);
+
+        if version < Self::VERSION {
+            Self::migrate(&mut store, version)?;
+            store.set(VERSION_KEY, &Self::VERSION)?;
+        }
+        Self::read(&mut store)
+    }
+
+    fn save_to(&self, backend: &mut dyn PreferenceBackend) -> AIResult<()> {
+        let mut store = PreferenceStore::new(backend, Self::PREFIX);
+        self.write(&mut store)?;
+        store.set(VERSION_KEY, &Self::VERSION)
+    }
+}
+
+/// `SafePlugin::preferences` が返す、型を消した設定
+///
+/// `Preferences` を実装した型はすべてこれを実装する。
+pub trait PluginPreferences {
+    /// 環境設定から読み込んで置き換える
+    fn load_preferences(&mut self) -> AIResult<()>;
+
+    fn save_preferences(&self) -> AIResult<()>;
+}
+
+impl<T: Preferences> PluginPreferences for T {
+    fn load_preferences(&mut self) -> AIResult<()> {
+        *self = T::load()?;
+        Ok(())
+    }
+
+    fn save_preferences(&self) -> AIResult<()> {
+        self.save()
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::ffi::{c_char, c_void, CStr};
+use std::sync::OnceLock;
+
+use crate::ai_sys::*;
+
+/// caller/selector の組から解決されるハンドラ
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Route {
+    // Sweet Pea
+    AccessUnload,
+    AccessReload,
+    InterfaceStartup,
+    InterfaceShutdown,
+    InterfaceAbout,
+    PurgeCaches,
+    AcquireProperty,
+    ReleaseProperty,
+
+    // 通知
+    Notify,
+
+    // アクション
+    GoAction,
+
+    // メニュー
+    GoMenuItem,
+    UpdateMenuItem,
+
+    // フィルター
+    GetFilterParameters,
+    GoFilter,
+
+    // プラグイングループ
+    PluginGroupNotify,
+    PluginGroupUpdate,
+
+    // ファイルフォーマット
+    GetFileFormatParameters,
+    GoFileFormat,
+    CheckFileFormat,
+    FileFormatUpdate,
+    SetFileFormatParameters,
+
+    // ツール
+    EditTool,
+    TrackToolCursor,
+    ToolMouseDown,
+    ToolMouseDrag,
+    ToolMouseUp,
+    SelectTool,
+    DeselectTool,
+    ReselectTool,
+    DecreaseDiameter,
+    IncreaseDiameter,
+
+    // ライブエフェクト
+    EditLiveEffectParameters,
+    GoLiveEffect,
+    LiveEffectInterpolate,
+    LiveEffectGetInputType,
+    LiveEffectScaleParameters,
+    LiveEffectConvertColorSpace,
+    LiveEffectAdjustColors,
+    LiveEffectHandleMerge,
+
+    // タイマー
+    GoTimer,
+
+    // クリップボード
+    GoClipboard,
+    CanCopyClipboard,
+    CloneClipboard,
+    DisposeClipboard,
+
+    // ワークスペース
+    WorkspaceWrite,
+    WorkspaceRestore,
+    WorkspaceDefault,
+
+    /// `MessageRouter::register` で追加されたハンドラ
+    Custom(usize),
+}
+
+/// 組み込みの caller/selector 対応表
+///
+/// SetFileFormatParameters はファイルフォーマット caller の下で `kDoActionSelector` を再利用するため、
+/// 必ず caller と selector の組で引く。
+static BUILTIN_ROUTES: &[(&[u8], &[u8], Route)] = &[
+    (kSPAccessCaller, kSPAccessUnloadSelector, Route::AccessUnload),
+    (kSPAccessCaller, kSPAccessReloadSelector, Route::AccessReload),
+    (kSPInterfaceCaller, kSPInterfaceStartupSelector, Route::InterfaceStartup),
+    (kSPInterfaceCaller, kSPInterfaceShutdownSelector, Route::InterfaceShutdown),
+    (kSPInterfaceCaller, kSPInterfaceAboutSelector, Route::InterfaceAbout),
+    (kSPCacheCaller, kSPPluginPurgeCachesSelector, Route::PurgeCaches),
+    (kSPPropertiesCaller, kSPPropertiesAcquireSelector, Route::AcquireProperty),
+    (kSPPropertiesCaller, kSPPropertiesReleaseSelector, Route::ReleaseProperty),
+
+    (kCallerAINotify, kSelectorAINotify, Route::Notify),
+
+    (kActionCaller, kDoActionSelector, Route::GoAction),
+
+    (kCallerAIMenu, kSelectorAIGoMenuItem, Route::GoMenuItem),
+    (kCallerAIMenu, kSelectorAIUpdateMenuItem, Route::UpdateMenuItem),
+
+    (kCallerAIFilter, kSelectorAIGetFilterParameters, Route::GetFilterParameters),
+    (kCallerAIFilter, kSelectorAIGoFilter, Route::GoFilter),
+
+    (kCallerAIPluginGroup, kSelectorAINotifyEdits, Route::PluginGroupNotify),
+    (kCallerAIPluginGroup, kSelectorAIUpdateArt, Route::PluginGroupUpdate),
+
+    (kCallerAIFileFormat, kSelectorAIGetFileFormatParameters, Route::GetFileFormatParameters),
+    (kCallerAIFileFormat, kSelectorAIGoFileFormat, Route::GoFileFormat),
+    (kCallerAIFileFormat, kSelectorAICheckFileFormat, Route::CheckFileFormat),
+    (kCallerAIFileFormat, kSelectorAIUpdateFileFormat, Route::FileFormatUpdate),
+    (kCallerAIFileFormat, kDoActionSelector, Route::SetFileFormatParameters),
+
+    (kCallerAITool, kSelectorAIEditToolOptions, Route::EditTool),
+    (kCallerAITool, kSelectorAITrackToolCursor, Route::TrackToolCursor),
+    (kCallerAITool, kSelectorAIToolMouseDown, Route::ToolMouseDown),
+    (kCallerAITool, kSelectorAIToolMouseDrag, Route::ToolMouseDrag),
+    (kCallerAITool, kSelectorAIToolMouseUp, Route::ToolMouseUp),
+    (kCallerAITool, kSelectorAISelectTool, Route::SelectTool),
+    (kCallerAITool, kSelectorAIDeselectTool, Route::DeselectTool),
+    (kCallerAITool, kSelectorAIReselectTool, Route::ReselectTool),
+    (kCallerAITool, kSelectorAIToolDecreaseDiameter, Route::DecreaseDiameter),
+    (kCallerAITool, kSelectorAIToolIncreaseDiameter, Route::IncreaseDiameter),
+
+    (kCallerAILiveEffect, kSelectorAIEditLiveEffectParameters, Route::EditLiveEffectParameters),
+    (kCallerAILiveEffect, kSelectorAIGoLiveEffect, Route::GoLiveEffect),
+    (kCallerAILiveEffect, kSelectorAILiveEffectInterpolate, Route::LiveEffectInterpolate),
+    (kCallerAILiveEffect, kSelectorAILiveEffectInputType, Route::LiveEffectGetInputType),
+    (kCallerAILiveEffect, kSelectorAILiveEffectScaleParameters, Route::LiveEffectScaleParameters),
+    (kCallerAILiveEffect, kSelectorAILiveEffectConverColorSpace, Route::LiveEffectConvertColorSpace),
+    (kCallerAILiveEffect, kSelectorAILiveEffectAdjustColors, Route::LiveEffectAdjustColors),
+    (kCallerAILiveEffect, kSelectorAILiveEffectHandleMerge, Route::LiveEffectHandleMerge),
+
+    (kCallerAITimer, kSelectorAIGoTimer, Route::GoTimer),
+
+    (kCallerAIClipboard, kSelectorAIGoClipboard, Route::GoClipboard),
+    (kCallerAIClipboard, kSelectorAICanCopyClipboard, Route::CanCopyClipboard),
+    (kCallerAIClipboard, kSelectorAICloneClipboard, Route::CloneClipboard),
+    (kCallerAIClipboard, kSelectorAIDisposeClipboard, Route::DisposeClipboard),
+
+    (kAIWorkspaceCaller, kAIWSWriteSelector, Route::WorkspaceWrite),
+    (kAIWorkspaceCaller, kAIWSRestoreSelector, Route::WorkspaceRestore),
+    (kAIWorkspaceCaller, kAIWSDefaultSelector, Route::WorkspaceDefault),
+];
+
+/// caller → selector → Route の二段の表
+#[derive(Default)]
+pub struct RouteTable {
+    map: HashMap<Box<[u8]>, HashMap<Box<[u8]>, Route>>,
+}
+
+impl RouteTable {
+    /// 組み込みの対応表（初回参照時に一度だけ構築）
+    pub fn builtin() -> &'static RouteTable {
+        static TABLE: OnceLock<RouteTable> = OnceLock::new();
+
+        TABLE.get_or_init(|| {
+            let mut table = RouteTable::default();
+            for &(caller, selector, route) in BUILTIN_ROUTES {
+                table.insert(strip_nul(caller), strip_nul(selector), route);
+            }
+            table
+        })
+    }
+
+    pub fn insert(&mut self, caller: &[u8], selector: &[u8], route: Route) {
+        self.map
+            .entry(caller.into())
+            .or_default()
+            .insert(selector.into(), route);
+    }
+
+    pub fn get(&self, caller: &[u8], selector: &[u8]) -> Option<Route> {
+        self.map.get(caller)?.get(selector).copied()
+    }
+}
+
+fn strip_nul(bytes: &[u8]) -> &[u8] {
+    bytes.strip_suffix(&[0]).unwrap_or(bytes)
+}
+
+/// 組み込みの対応表から caller/selector を解決する
+///
+/// # Safety
+/// `caller`・`selector` は null でない NUL 終端された文字列で、呼び出しの間有効でなければならない。
+pub unsafe fn resolve(caller: *const c_char, selector: *const c_char) -> Option<Route> {
+    RouteTable::builtin().get(CStr::from_ptr(caller).to_bytes(), CStr::from_ptr(selector).to_bytes())
+}
+
+type Handler<T> = Box<dyn FnMut(&mut T, *mut c_void) -> ASErr>;
+type DiagnosticHook = Box<dyn FnMut(&CStr, &CStr)>;
+
+/// プラグインごとのルーター
+///
+/// 組み込みの対応表に加えて、ユーザーが登録した caller/selector を扱う。
+/// 登録された組は組み込みより優先される。
+pub struct MessageRouter<T> {
+    custom: RouteTable,
+    handlers: Vec<Handler<T>>,
+    diagnostic_hook: Option<DiagnosticHook>,
+}
+
+impl<T> Default for MessageRouter<T> {
+    fn default() -> Self {
+        Self {
+            custom: RouteTable::default(),
+            handlers: Vec::new(),
+            diagnostic_hook: None,
+        }
+    }
+}
+
+impl<T> MessageRouter<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// caller/selector の組にハンドラを登録する
+    pub fn register<F>(&mut self, caller: &CStr, selector: &CStr, handler: F)
+    where
+        F: FnMut(&mut T, *mut c_void) -> ASErr + 'static,
+    {
+        let index = self.handlers.len();
+        self.handlers.push(Box::new(handler));
+        self.custom.insert(caller.to_bytes(), selector.to_bytes(), Route::Custom(index));
+    }
+
+    /// 未知の caller/selector を受け取ったときに呼ばれるフックを設定する
+    pub fn set_diagnostic_hook<F>(&mut self, hook: F)
+    where
+        F: FnMut(&CStr, &CStr) + 'static,
+    {
+        self.diagnostic_hook = Some(Box::new(hook));
+    }
+
+    /// 登録済みの組、組み込みの組の順に解決する
+    ///
+    /// # Safety
+    /// `caller`・`selector` は null でない NUL 終端された文字列で、呼び出しの間有効でなければならない。
+    pub unsafe fn resolve(&self, caller: *const c_char, selector: *const c_char) -> Option<Route> {
+        let caller = CStr::from_ptr(caller).to_bytes();
+        let selector = CStr::from_ptr(selector).to_bytes();
+
+        self.custom
+            .get(caller, selector)
+            .or_else(|| RouteTable::builtin().get(caller, selector))
+    }
+
+    /// `Route::Custom` のハンドラを呼び出す
+    pub fn call(&mut self, index: usize, target: &mut T, message: *mut c_void) -> ASErr {
+        match self.handlers.get_mut(index) {
+            Some(handler) => handler(target, message),
+            None => kUnhandledMsgErr,
+        }
+    }
+
+    /// 未知の caller/selector を診断フックへ通知する
+    ///
+    /// # Safety
+    /// `caller`・`selector` は null でない NUL 終端された文字列で、呼び出しの間有効でなければならない。
+    pub unsafe fn report_unhandled(&mut self, caller: *const c_char, selector: *const c_char) {
+        if let Some(hook) = self.diagnostic_hook.as_mut() {
+            hook(CStr::from_ptr(caller), CStr::from_ptr(selector));
+        }
+    }
+}
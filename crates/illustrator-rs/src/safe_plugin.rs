@@ -0,0 +1,265 @@
+use crate::ai_plugin::AIPlugin;
+use crate::action::{self, Actions};
+use crate::ai_sys::*;
+use crate::error::{to_as_err, AIError, AIResult};
+use crate::file_format::{self, FileFormats};
+use crate::filter::{self, Filters};
+use crate::live_effect::{self, LiveEffects};
+use crate::menu::{MenuItem, Menus};
+use crate::messages::*;
+use crate::notifier::Notifiers;
+use crate::plugin_group::{self, PluginGroupSelector, PluginGroups};
+use crate::preferences::PluginPreferences;
+use crate::router::MessageRouter;
+use crate::timer::Timers;
+use crate::tool::{self, ToolSelector, Tools};
+
+/// 生ポインタを扱わずに書ける `AIPlugin`
+///
+/// 各ハンドラはメッセージのビュー型を借用で受け取り、`AIResult<()>` を返す。
+/// `Err(AIError::Unhandled)` は `kUnhandledMsgErr` としてホストへ返される。
+/// このトレイトを実装した型は自動的に `AIPlugin` を実装する。
+pub trait SafePlugin {
+    // プラグイン基本イベント
+    fn startup(&mut self) -> AIResult<()> { Ok(()) }
+    fn post_startup(&mut self) -> AIResult<()> { Ok(()) }
+    fn pre_shutdown(&mut self) -> AIResult<()> { Ok(()) }
+
+    // 設定（post_startup の前に読み込み、pre_shutdown の後に保存される）
+    fn preferences(&mut self) -> Option<&mut dyn PluginPreferences> { None }
+
+    // ルーター拡張
+    fn register_message_handlers(&mut self, _router: &mut MessageRouter<Self>) where Self: Sized {}
+
+    // プロパティ関連
+    fn acquire_property(&mut self, _message: PropertiesMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn release_property(&mut self, _message: PropertiesMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+
+    // 通知処理（`notifiers` で購読した通知はそのハンドラへ、それ以外は notify へ送られる）
+    fn notifiers(&mut self) -> Option<&mut Notifiers<Self>> where Self: Sized { None }
+    fn notify(&mut self, _message: NotifierMessage) -> AIResult<()> { Ok(()) }
+
+    // アクション（`actions` が返したイベントの再生は `Action` へ、それ以外は go_action へ送られる）
+    fn actions(&mut self) -> Option<&mut Actions<Self>> where Self: Sized { None }
+    fn go_action(&mut self, _message: ActionMessage) -> AIResult<()> { Ok(()) }
+
+    // メニュー（`menus` が返した項目はそのハンドラへ、それ以外は go_menu_item/update_menu_item へ送られる）
+    fn menus(&mut self) -> Option<&mut Menus<Self>> where Self: Sized { None }
+    fn go_menu_item(&mut self, _message: MenuMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn update_menu_item(&mut self, _message: MenuMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+
+    // フィルター（`filters` が返したフィルターは `Filter` へ、それ以外は以下のメソッドへ送られる）
+    fn filters(&mut self) -> Option<&mut Filters<Self>> where Self: Sized { None }
+    fn get_filter_parameters(&mut self, _message: FilterMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn go_filter(&mut self, _message: FilterMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+
+    // プラグイングループ（`plugin_groups` が返したグループは `PluginGroupClass` へ、それ以外は以下のメソッドへ送られる）
+    fn plugin_groups(&mut self) -> Option<&mut PluginGroups<Self>> where Self: Sized { None }
+    fn plugin_group_notify(&mut self, _message: PluginGroupMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn plugin_group_update(&mut self, _message: PluginGroupMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+
+    // ファイルフォーマット（`file_formats` が返したフォーマットは `FileFormat` へ、それ以外は以下のメソッドへ送られる）
+    fn file_formats(&mut self) -> Option<&mut FileFormats<Self>> where Self: Sized { None }
+    fn get_file_format_parameters(&mut self, _message: FileFormatMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn go_file_format(&mut self, _message: FileFormatMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn check_file_format(&mut self, _message: FileFormatMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn file_format_update(&mut self, _message: UpdateFileFormatMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn set_file_format_parameters(&mut self, _message: ActionMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+
+    // ツール関連（`tools` が返したツールは `ToolBehavior` へ、それ以外は以下のメソッドへ送られる）
+    fn tools(&mut self) -> Option<&mut Tools<Self>> where Self: Sized { None }
+    fn edit_tool(&mut self, _message: ToolMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn track_tool_cursor(&mut self, _message: ToolMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn tool_mouse_down(&mut self, _message: ToolMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn tool_mouse_drag(&mut self, _message: ToolMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn tool_mouse_up(&mut self, _message: ToolMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn select_tool(&mut self, _message: ToolMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn deselect_tool(&mut self, _message: ToolMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn reselect_tool(&mut self, _message: ToolMessage) -> AIResult<()> { Ok(()) }
+    fn decrease_diameter(&mut self, _message: ToolMessage) -> AIResult<()> { Ok(()) }
+    fn increase_diameter(&mut self, _message: ToolMessage) -> AIResult<()> { Ok(()) }
+
+    // ライブエフェクト（`live_effects` が返したエフェクトは `LiveEffect` へ、それ以外は以下のメソッドへ送られる）
+    fn live_effects(&mut self) -> Option<&mut LiveEffects<Self>> where Self: Sized { None }
+    fn edit_live_effect_parameters(&mut self, _message: LiveEffectEditParamMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn go_live_effect(&mut self, _message: LiveEffectGoMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn live_effect_interpolate(&mut self, _message: LiveEffectInterpParamMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn live_effect_get_input_type(&mut self, _message: LiveEffectInputTypeMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn live_effect_scale_parameters(&mut self, _message: LiveEffectScaleParamMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn live_effect_convert_color_space(&mut self, _message: LiveEffectConvertColorMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn live_effect_adjust_colors(&mut self, _message: LiveEffectAdjustColorsMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn live_effect_handle_merge(&mut self, _message: LiveEffectHandleMergeMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+
+    // タイマー（`timers` が返したタイマーはそのハンドラへ、それ以外は go_timer へ送られる）
+    fn timers(&mut self) -> Option<&mut Timers<Self>> where Self: Sized { None }
+    fn go_timer(&mut self, _message: TimerMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+
+    // クリップボード
+    fn go_clipboard(&mut self, _message: ClipboardMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn can_copy_clipboard(&mut self, _message: ClipboardMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn clone_clipboard(&mut self, _message: ClipboardMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn dispose_clipboard(&mut self, _message: ClipboardMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+
+    // ワークスペース
+    fn workspace_write(&mut self, _message: WorkspaceMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn workspace_restore(&mut self, _message: WorkspaceMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+    fn workspace_default(&mut self, _message: WorkspaceMessage) -> AIResult<()> { Err(AIError::Unhandled) }
+}
+
+/// `tools` が返したツールへのメッセージは `ToolBehavior` へ、それ以外は `forward!` と同じ
+macro_rules! forward_tool {
+    ($self:ident . $method:ident ( $message:ident ), $selector:ident) => {
+        match tool::dispatch($self, Self::tools, $message, ToolSelector::$selector) {
+            Some(result) => to_as_err(result),
+            None => forward!($self.$method(ToolMessage, $message)),
+        }
+    };
+}
+
+/// `plugin_groups` が返したグループへのメッセージは `PluginGroupClass` へ、それ以外は `forward!` と同じ
+macro_rules! forward_plugin_group {
+    ($self:ident . $method:ident ( $message:ident ), $selector:ident) => {
+        match plugin_group::dispatch($self, Self::plugin_groups, $message, PluginGroupSelector::$selector) {
+            Some(result) => to_as_err(result),
+            None => forward!($self.$method(PluginGroupMessage, $message)),
+        }
+    };
+}
+
+/// `live_effects` が返したエフェクトへのメッセージは `LiveEffect` へ、それ以外は `forward!` と同じ
+macro_rules! forward_live_effect {
+    ($self:ident . $method:ident ( $view:ident, $message:ident ), $call:ident) => {
+        match live_effect::dispatch($self, Self::live_effects, $message, |behavior, plugin, message| behavior.$call(plugin, message)) {
+            Some(result) => to_as_err(result),
+            None => forward!($self.$method($view, $message)),
+        }
+    };
+}
+
+/// `filters` が返したフィルターへのメッセージは `Filter` へ、それ以外は `forward!` と同じ
+macro_rules! forward_filter {
+    ($self:ident . $method:ident ( $message:ident ), $call:ident) => {
+        match filter::dispatch($self, Self::filters, $message, |behavior, plugin, message| behavior.$call(plugin, message)) {
+            Some(result) => to_as_err(result),
+            None => forward!($self.$method(FilterMessage, $message)),
+        }
+    };
+}
+
+/// `file_formats` が返したフォーマットへのメッセージは `FileFormat` へ、それ以外は `forward!` と同じ
+macro_rules! forward_file_format {
+    ($self:ident . $method:ident ( $view:ident, $message:ident ), $call:ident) => {
+        match file_format::dispatch($self, Self::file_formats, $message, |behavior, plugin, message| behavior.$call(plugin, message)) {
+            Some(result) => to_as_err(result),
+            None => forward!($self.$method($view, $message)),
+        }
+    };
+}
+
+/// 生ポインタをビュー型に変換してハンドラを呼ぶ（null は `kBadParameterErr`）
+macro_rules! forward {
+    ($self:ident . $method:ident ( $view:ident, $message:ident )) => {
+        match unsafe { $view::from_raw($message) } {
+            Some(view) => to_as_err($self.$method(view)),
+            None => AIError::BadParameter.into(),
+        }
+    };
+}
+
+impl<T: SafePlugin> AIPlugin for T {
+    fn StartupPlugin(&mut self) -> ASErr { to_as_err(self.startup()) }
+    fn PostStartupPlugin(&mut self) -> ASErr {
+        let loaded = self.preferences().map_or(Ok(()), |preferences| preferences.load_preferences());
+        to_as_err(loaded.and_then(|()| self.post_startup()))
+    }
+    fn PreShutdownPlugin(&mut self) -> ASErr {
+        // 終了処理が失敗しても設定は保存する
+        let result = self.pre_shutdown();
+        let saved = self.preferences().map_or(Ok(()), |preferences| preferences.save_preferences());
+        to_as_err(result.and(saved))
+    }
+
+    fn RegisterMessageHandlers(&mut self, router: &mut MessageRouter<Self>) { self.register_message_handlers(router) }
+
+    fn AcquireProperty(&mut self, message: *mut SPPropertiesMessage) -> ASErr { forward!(self.acquire_property(PropertiesMessage, message)) }
+    fn ReleaseProperty(&mut self, message: *mut SPPropertiesMessage) -> ASErr { forward!(self.release_property(PropertiesMessage, message)) }
+
+    fn Notify(&mut self, message: *mut AINotifierMessage) -> ASErr {
+        let handler = unsafe { message.as_ref() }.and_then(|message| self.notifiers()?.handler(message.notifier));
+        match handler.zip(unsafe { NotifierMessage::from_raw(message) }) {
+            Some((handler, view)) => to_as_err((&mut *handler.borrow_mut())(self, view)),
+            None => forward!(self.notify(NotifierMessage, message)),
+        }
+    }
+
+    fn GoAction(&mut self, message: *mut DoActionMessage) -> ASErr {
+        match action::dispatch(self, Self::actions, message) {
+            Some(result) => to_as_err(result),
+            None => forward!(self.go_action(ActionMessage, message)),
+        }
+    }
+
+    fn GoMenuItem(&mut self, message: *mut AIMenuMessage) -> ASErr {
+        let item = unsafe { message.as_ref() }.map(|message| message.menuItem);
+        match item.and_then(|item| self.menus()?.go_handler(item)) {
+            Some(handler) => to_as_err((&mut *handler.borrow_mut())(self)),
+            None => forward!(self.go_menu_item(MenuMessage, message)),
+        }
+    }
+    fn UpdateMenuItem(&mut self, message: *mut AIMenuMessage) -> ASErr {
+        let item = unsafe { message.as_ref() }.and_then(|message| unsafe { MenuItem::from_raw(message.menuItem) });
+        match item.and_then(|item| Some((item, self.menus()?.update_handler(item.as_raw())?))) {
+            Some((item, handler)) => to_as_err((&mut *handler.borrow_mut())(self).and_then(|update| update.apply(item))),
+            None => forward!(self.update_menu_item(MenuMessage, message)),
+        }
+    }
+
+    fn GetFilterParameters(&mut self, message: *mut AIFilterMessage) -> ASErr { forward_filter!(self.get_filter_parameters(message), parameters) }
+    fn GoFilter(&mut self, message: *mut AIFilterMessage) -> ASErr { forward_filter!(self.go_filter(message), go) }
+
+    fn PluginGroupNotify(&mut self, message: *mut AIPluginGroupMessage) -> ASErr { forward_plugin_group!(self.plugin_group_notify(message), NotifyEdits) }
+    fn PluginGroupUpdate(&mut self, message: *mut AIPluginGroupMessage) -> ASErr { forward_plugin_group!(self.plugin_group_update(message), Update) }
+
+    fn GetFileFormatParameters(&mut self, message: *mut AIFileFormatMessage) -> ASErr { forward_file_format!(self.get_file_format_parameters(FileFormatMessage, message), parameters) }
+    fn GoFileFormat(&mut self, message: *mut AIFileFormatMessage) -> ASErr { forward_file_format!(self.go_file_format(FileFormatMessage, message), go) }
+    fn CheckFileFormat(&mut self, message: *mut AIFileFormatMessage) -> ASErr { forward_file_format!(self.check_file_format(FileFormatMessage, message), check) }
+    fn FileFormatUpdate(&mut self, message: *mut AIUpdateFileFormatMessage) -> ASErr { forward!(self.file_format_update(UpdateFileFormatMessage, message)) }
+    fn SetFileFormatParameters(&mut self, message: *mut DoActionMessage) -> ASErr { forward_file_format!(self.set_file_format_parameters(ActionMessage, message), set_parameters) }
+
+    fn EditTool(&mut self, message: *mut AIToolMessage) -> ASErr { forward_tool!(self.edit_tool(message), EditOptions) }
+    fn TrackToolCursor(&mut self, message: *mut AIToolMessage) -> ASErr { forward_tool!(self.track_tool_cursor(message), TrackCursor) }
+    fn ToolMouseDown(&mut self, message: *mut AIToolMessage) -> ASErr { forward_tool!(self.tool_mouse_down(message), MouseDown) }
+    fn ToolMouseDrag(&mut self, message: *mut AIToolMessage) -> ASErr { forward_tool!(self.tool_mouse_drag(message), MouseDrag) }
+    fn ToolMouseUp(&mut self, message: *mut AIToolMessage) -> ASErr { forward_tool!(self.tool_mouse_up(message), MouseUp) }
+    fn SelectTool(&mut self, message: *mut AIToolMessage) -> ASErr { forward_tool!(self.select_tool(message), Select) }
+    fn DeselectTool(&mut self, message: *mut AIToolMessage) -> ASErr { forward_tool!(self.deselect_tool(message), Deselect) }
+    fn ReselectTool(&mut self, message: *mut AIToolMessage) -> ASErr { forward_tool!(self.reselect_tool(message), Reselect) }
+    fn DecreaseDiameter(&mut self, message: *mut AIToolMessage) -> ASErr { forward_tool!(self.decrease_diameter(message), DecreaseDiameter) }
+    fn IncreaseDiameter(&mut self, message: *mut AIToolMessage) -> ASErr { forward_tool!(self.increase_diameter(message), IncreaseDiameter) }
+
+    fn EditLiveEffectParameters(&mut self, message: *mut AILiveEffectEditParamMessage) -> ASErr { forward_live_effect!(self.edit_live_effect_parameters(LiveEffectEditParamMessage, message), edit) }
+    fn GoLiveEffect(&mut self, message: *mut AILiveEffectGoMessage) -> ASErr { forward_live_effect!(self.go_live_effect(LiveEffectGoMessage, message), go) }
+    fn LiveEffectInterpolate(&mut self, message: *mut AILiveEffectInterpParamMessage) -> ASErr { forward_live_effect!(self.live_effect_interpolate(LiveEffectInterpParamMessage, message), interpolate) }
+    fn LiveEffectGetInputType(&mut self, message: *mut AILiveEffectInputTypeMessage) -> ASErr { forward_live_effect!(self.live_effect_get_input_type(LiveEffectInputTypeMessage, message), input_type) }
+    fn LiveEffectScaleParameters(&mut self, message: *mut AILiveEffectScaleParamMessage) -> ASErr { forward_live_effect!(self.live_effect_scale_parameters(LiveEffectScaleParamMessage, message), scale) }
+    fn LiveEffectConvertColorSpace(&mut self, message: *mut AILiveEffectConvertColorMessage) -> ASErr { forward_live_effect!(self.live_effect_convert_color_space(LiveEffectConvertColorMessage, message), convert_color) }
+    fn LiveEffectAdjustColors(&mut self, message: *mut AILiveEffectAdjustColorsMessage) -> ASErr { forward!(self.live_effect_adjust_colors(LiveEffectAdjustColorsMessage, message)) }
+    fn LiveEffectHandleMerge(&mut self, message: *mut AILiveEffectHandleMergeMessage) -> ASErr { forward!(self.live_effect_handle_merge(LiveEffectHandleMergeMessage, message)) }
+
+    fn GoTimer(&mut self, message: *mut AITimerMessage) -> ASErr {
+        let timer = unsafe { message.as_ref() }.map(|message| message.timer);
+        match timer.and_then(|timer| self.timers()?.handler(timer)) {
+            Some(handler) => to_as_err((&mut *handler.borrow_mut())(self)),
+            None => forward!(self.go_timer(TimerMessage, message)),
+        }
+    }
+
+    fn GoClipboard(&mut self, message: *mut AIClipboardMessage) -> ASErr { forward!(self.go_clipboard(ClipboardMessage, message)) }
+    fn CanCopyClipboard(&mut self, message: *mut AIClipboardMessage) -> ASErr { forward!(self.can_copy_clipboard(ClipboardMessage, message)) }
+    fn CloneClipboard(&mut self, message: *mut AIClipboardMessage) -> ASErr { forward!(self.clone_clipboard(ClipboardMessage, message)) }
+    fn DisposeClipboard(&mut self, message: *mut AIClipboardMessage) -> ASErr { forward!(self.dispose_clipboard(ClipboardMessage, message)) }
+
+    fn WorkspaceWrite(&mut self, message: *mut AIWorkspaceMessage) -> ASErr { forward!(self.workspace_write(WorkspaceMessage, message)) }
+    fn WorkspaceRestore(&mut self, message: *mut AIWorkspaceMessage) -> ASErr { forward!(self.workspace_restore(WorkspaceMessage, message)) }
+    fn WorkspaceDefault(&mut self, message: *mut AIWorkspaceMessage) -> ASErr { forward!(self.workspace_default(WorkspaceMessage, message)) }
+}
@@ -0,0 +1,276 @@
+mod writer;
+
+use std::io::Write;
+
+use serde::de::Deserializer;
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+pub use writer::{Group, LineCap, LineJoin, Node, Rgb, Scene, Shape, Stroke, Style};
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::art::{Art, ArtAttributes, ArtType};
+use crate::artboard::Artboards;
+use crate::bezier::BezierPath;
+use crate::error::{AIResult, SuiteFn};
+use crate::file_format::{FileContext, FileFormat};
+use crate::geometry::Rect;
+use crate::layer::Layer;
+
+/// 書き出す範囲（SVG の `viewBox`）
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ExportArea {
+    /// アクティブなアートボード
+    #[default]
+    ActiveArtboard,
+    /// 番号で指定したアートボード
+    Artboard(usize),
+    /// 書き出すすべての図形の外接矩形
+    ArtBounds,
+}
+
+/// SVG の書き出し設定
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SvgOptions {
+    pub area: ExportArea,
+    /// 座標の小数点以下の桁数
+    pub precision: u8,
+    /// 非表示のレイヤーとアートも書き出すか
+    pub include_hidden: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            area: ExportArea::ActiveArtboard,
+            precision: 3,
+            include_hidden: false,
+        }
+    }
+}
+
+// 範囲は整数 1 つで表す（-1: アクティブなアートボード、-2: 図形の外接矩形、0 以上: アートボードの番号）
+impl Serialize for SvgOptions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let area = match self.area {
+            ExportArea::ActiveArtboard => -1,
+            ExportArea::ArtBounds => -2,
+            ExportArea::Artboard(index) => index as i32,
+        };
+        (area, self.precision, self.include_hidden).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SvgOptions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (area, precision, include_hidden) = <(i32, u8, bool)>::deserialize(deserializer)?;
+        let area = match area {
+            -2 => ExportArea::ArtBounds,
+            area => usize::try_from(area).map_or(ExportArea::ActiveArtboard, ExportArea::Artboard),
+        };
+        Ok(Self { area, precision, include_hidden })
+    }
+}
+
+/// 現在のドキュメントのアートを集める
+///
+/// 最上位のレイヤーごとに、レイヤー名を持つグループを1つ作る。
+/// 集めるのはグループ・パス・複合パス・クリップグループと単色の塗りと線のみで、
+/// テキストや配置画像などは含まれない。
+pub fn document_scene(options: &SvgOptions) -> AIResult<Scene> {
+    let mut children = Vec::new();
+
+    for layer in Layer::all() {
+        let layer = layer?;
+        if !options.include_hidden && !layer.is_visible()? {
+            continue;
+        }
+        if let Some(Node::Group(mut group)) = art_node(layer.group()?, options)? {
+            group.name = layer.title()?;
+            children.push(Node::Group(group));
+        }
+    }
+    // レイヤーは前面から列挙される
+    children.reverse();
+
+    let mut scene = Scene::new(Rect::default());
+    scene.children = children;
+    scene.bounds = match options.area {
+        ExportArea::ActiveArtboard => {
+            let mut artboards = Artboards::current()?;
+            let index = artboards.active()?;
+            artboards.bounds(index)?
+        }
+        ExportArea::Artboard(index) => Artboards::current()?.bounds(index)?,
+        ExportArea::ArtBounds => scene.content_bounds().unwrap_or_default(),
+    };
+    Ok(scene)
+}
+
+/// アートを `Node` にする（書き出さないアートは `None`）
+pub fn art_node(art: Art, options: &SvgOptions) -> AIResult<Option<Node>> {
+    if !options.include_hidden && art.is_hidden()? {
+        return Ok(None);
+    }
+
+    let art_type = art.art_type()?;
+    let node = match art_type {
+        ArtType::Group => {
+            let mut group = Group {
+                name: art.name()?,
+                ..Group::default()
+            };
+            for child in art.children() {
+                let child = child?;
+                if child.has_attribute(ArtAttributes::IS_CLIP_MASK)? {
+                    // クリップパスが複数あるときはすべての形状でクリップする
+                    group.clip.get_or_insert_with(Vec::new).extend(outline(child)?);
+                } else if let Some(node) = art_node(child, options)? {
+                    group.children.push(node);
+                }
+            }
+            // 子は前面から列挙される
+            group.children.reverse();
+            Node::Group(group)
+        }
+        ArtType::Path | ArtType::CompoundPath => {
+            // 複合パスは最初の子のスタイルで塗られる
+            let styled = match art_type {
+                ArtType::Path => Some(art),
+                _ => art.first_child()?,
+            };
+            let style = match styled {
+                Some(art) => path_style(art)?,
+                None => Style::default(),
+            };
+            Node::Shape(Shape {
+                name: art.name()?,
+                paths: outline(art)?,
+                style,
+            })
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(node))
+}
+
+/// パスまたは複合パスの形状
+fn outline(art: Art) -> AIResult<Vec<BezierPath>> {
+    match art.art_type()? {
+        ArtType::Path => Ok(vec![art.as_path()?.to_bezier()?]),
+        ArtType::CompoundPath | ArtType::Group => {
+            let mut paths = Vec::new();
+            for child in art.children() {
+                paths.extend(outline(child?)?);
+            }
+            Ok(paths)
+        }
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// パスの塗りと線
+///
+/// 単色以外（パターン・グラデーションなど）は塗らないものとして扱う。
+pub fn path_style(art: Art) -> AIResult<Style> {
+    let suite = SuiteGuard::<AIPathStyleSuite>::acquire()?;
+    let mut style: AIPathStyle = unsafe { std::mem::zeroed() };
+    let mut has_advanced_fill: AIBoolean = 0;
+
+    unsafe { suite.GetPathStyle.call((art.as_raw(), &mut style as *mut _, &mut has_advanced_fill as *mut _))? };
+    Ok(to_style(&style))
+}
+
+fn to_style(style: &AIPathStyle) -> Style {
+    let fill = (style.fillPaint != 0).then(|| to_rgb(&style.fill.color)).flatten();
+    let stroke = (style.strokePaint != 0)
+        .then(|| to_rgb(&style.stroke.color))
+        .flatten()
+        .map(|color| {
+            let dash = &style.stroke.dash;
+            let count = (dash.length.max(0) as usize).min(dash.array.len());
+
+            Stroke {
+                color,
+                width: style.stroke.width,
+                cap: match style.stroke.cap {
+                    AILineCap_kAIRoundCap => LineCap::Round,
+                    AILineCap_kAIProjectingCap => LineCap::Square,
+                    _ => LineCap::Butt,
+                },
+                join: match style.stroke.join {
+                    AILineJoin_kAIRoundJoin => LineJoin::Round,
+                    AILineJoin_kAIBevelJoin => LineJoin::Bevel,
+                    _ => LineJoin::Miter,
+                },
+                miter_limit: style.stroke.miterLimit,
+                dash: dash.array[..count].iter().map(|&length| length as f64).collect(),
+                dash_offset: dash.offset as f64,
+            }
+        });
+
+    Style {
+        fill,
+        even_odd: style.evenodd != 0,
+        stroke,
+    }
+}
+
+/// 単色を sRGB にする（CMYK は単純な式で変換する）
+fn to_rgb(color: &AIColor) -> Option<Rgb> {
+    unsafe {
+        match color.kind {
+            AIColorTag_kGrayColor => {
+                // グレーはインキの量なので 1 が黒
+                let value = 1.0 - color.c.g.gray;
+                Some(Rgb::new(value, value, value))
+            }
+            AIColorTag_kFourColor => {
+                let cmyk = color.c.f;
+                let white = 1.0 - cmyk.black;
+                Some(Rgb::new(
+                    (1.0 - cmyk.cyan) * white,
+                    (1.0 - cmyk.magenta) * white,
+                    (1.0 - cmyk.yellow) * white,
+                ))
+            }
+            AIColorTag_kThreeColor => {
+                let rgb = color.c.rgb;
+                Some(Rgb::new(rgb.red, rgb.green, rgb.blue))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// ドキュメントを SVG として書き出すファイルフォーマット
+///
+/// `document_scene` で集めた `Scene` を書き出す。`Scene` 以下はホストに依存しないので、
+/// 手で組み立てた木を `Scene::to_svg` で文字列にすることもできる。
+///
+/// ```ignore
+/// formats
+///     .format(c"illustrator-rs SVG", "SVG (illustrator-rs)")
+///     .extension("svg")
+///     .capabilities(FormatCapabilities::EXPORT)
+///     .add(SvgFormat)?;
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SvgFormat;
+
+impl<P> FileFormat<P> for SvgFormat {
+    type Options = SvgOptions;
+
+    fn write(
+        &mut self,
+        _plugin: &mut P,
+        _context: &FileContext,
+        writer: &mut dyn Write,
+        options: &SvgOptions,
+    ) -> AIResult<()> {
+        let scene = document_scene(options)?;
+        scene.write_svg(writer, options.precision)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,513 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use crate::bezier::{BezierPath, CubicBezier};
+use crate::geometry::{Point, Rect};
+
+/// sRGB の色（各成分 0.0〜1.0）
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rgb {
+    pub red: f64,
+    pub green: f64,
+    pub blue: f64,
+}
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb::new(0.0, 0.0, 0.0);
+    pub const WHITE: Rgb = Rgb::new(1.0, 1.0, 1.0);
+
+    pub const fn new(red: f64, green: f64, blue: f64) -> Self {
+        Self { red, green, blue }
+    }
+
+    /// `#rrggbb` 形式
+    pub fn to_hex(self) -> String {
+        let channel = |value: f64| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        format!("#{:02x}{:02x}{:02x}", channel(self.red), channel(self.green), channel(self.blue))
+    }
+}
+
+/// 線端の形状
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Round,
+    Square,
+}
+
+/// 角の形状
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LineJoin {
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+/// 線の塗り
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stroke {
+    pub color: Rgb,
+    pub width: f64,
+    pub cap: LineCap,
+    pub join: LineJoin,
+    pub miter_limit: f64,
+    /// 破線の長さの並び（空なら実線）
+    pub dash: Vec<f64>,
+    pub dash_offset: f64,
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        Self {
+            color: Rgb::BLACK,
+            width: 1.0,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: 10.0,
+            dash: Vec::new(),
+            dash_offset: 0.0,
+        }
+    }
+}
+
+/// パスの塗りと線（`None` は塗らない）
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Style {
+    pub fill: Option<Rgb>,
+    /// 塗りを奇偶規則で行うか
+    pub even_odd: bool,
+    pub stroke: Option<Stroke>,
+}
+
+/// 出力する図形
+///
+/// 子は背面から前面の順（SVG の描画順）に並べる。
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    Group(Group),
+    Shape(Shape),
+}
+
+impl Node {
+    /// パスの外接矩形（線幅は含まない。クリップされたグループはクリップの外接矩形）
+    pub fn bounds(&self) -> Option<Rect> {
+        match self {
+            Node::Group(group) => match &group.clip {
+                Some(clip) => union(clip.iter().filter_map(BezierPath::bounds)),
+                None => union(group.children.iter().filter_map(Node::bounds)),
+            },
+            Node::Shape(shape) => union(shape.paths.iter().filter_map(BezierPath::bounds)),
+        }
+    }
+}
+
+fn union(mut rects: impl Iterator<Item = Rect>) -> Option<Rect> {
+    let first = rects.next()?;
+    Some(rects.fold(first, |bounds, rect| bounds.union(&rect)))
+}
+
+/// グループ（`clip` があればその形状でクリップする）
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Group {
+    /// 名前（空なら出力しない）
+    pub name: String,
+    pub clip: Option<Vec<BezierPath>>,
+    pub children: Vec<Node>,
+}
+
+/// ひとつのスタイルで描く1つ以上のパス（複合パスは複数になる）
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Shape {
+    /// 名前（空なら出力しない）
+    pub name: String,
+    pub paths: Vec<BezierPath>,
+    pub style: Style,
+}
+
+/// SVG ドキュメント全体
+///
+/// 座標はドキュメント座標（y 軸が上向き）のまま持ち、書き出すときに `bounds` の左上を原点とする
+/// y 軸下向きの座標へ変換する。`bounds` の外は表示されない。
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Scene {
+    pub bounds: Rect,
+    pub children: Vec<Node>,
+}
+
+impl Scene {
+    pub fn new(bounds: Rect) -> Self {
+        Self { bounds, children: Vec::new() }
+    }
+
+    /// すべての図形の外接矩形
+    pub fn content_bounds(&self) -> Option<Rect> {
+        union(self.children.iter().filter_map(Node::bounds))
+    }
+
+    /// SVG を書き出す（数値は小数点以下 `precision` 桁に丸める）
+    pub fn write_svg(&self, out: &mut dyn Write, precision: u8) -> io::Result<()> {
+        out.write_all(self.to_svg(precision).as_bytes())
+    }
+
+    /// SVG の文字列にする
+    pub fn to_svg(&self, precision: u8) -> String {
+        let mut writer = SvgWriter {
+            out: String::new(),
+            bounds: self.bounds,
+            precision: precision as usize,
+            clip_ids: 0,
+        };
+        writer.scene(self);
+        writer.out
+    }
+}
+
+struct SvgWriter {
+    out: String,
+    bounds: Rect,
+    precision: usize,
+    clip_ids: usize,
+}
+
+// `String` への書き込みは失敗しないので `write!` の結果は捨てる
+impl SvgWriter {
+    fn scene(&mut self, scene: &Scene) {
+        let width = self.number(scene.bounds.width());
+        let height = self.number(scene.bounds.height());
+
+        self.out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            self.out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" width=\"{width}\" height=\"{height}\" \
+             viewBox=\"0 0 {width} {height}\">"
+        );
+        for node in &scene.children {
+            self.node(node, 1);
+        }
+        self.out.push_str("</svg>\n");
+    }
+
+    fn node(&mut self, node: &Node, depth: usize) {
+        match node {
+            Node::Group(group) => self.group(group, depth),
+            Node::Shape(shape) => self.shape(shape, depth),
+        }
+    }
+
+    fn group(&mut self, group: &Group, depth: usize) {
+        let clip = group.clip.as_ref().map(|paths| {
+            self.clip_ids += 1;
+            let id = format!("clip{}", self.clip_ids);
+
+            self.indent(depth);
+            let _ = writeln!(self.out, "<clipPath id=\"{id}\">");
+            self.indent(depth + 1);
+            let _ = writeln!(self.out, "<path d=\"{}\"/>", self.path_data(paths));
+            self.indent(depth);
+            self.out.push_str("</clipPath>\n");
+            id
+        });
+
+        self.indent(depth);
+        self.out.push_str("<g");
+        self.name(&group.name);
+        if let Some(id) = clip {
+            let _ = write!(self.out, " clip-path=\"url(#{id})\"");
+        }
+
+        if group.children.is_empty() {
+            self.out.push_str("/>\n");
+            return;
+        }
+        self.out.push_str(">\n");
+        for child in &group.children {
+            self.node(child, depth + 1);
+        }
+        self.indent(depth);
+        self.out.push_str("</g>\n");
+    }
+
+    fn shape(&mut self, shape: &Shape, depth: usize) {
+        let data = self.path_data(&shape.paths);
+        if data.is_empty() {
+            return;
+        }
+
+        self.indent(depth);
+        self.out.push_str("<path");
+        self.name(&shape.name);
+        let _ = write!(self.out, " d=\"{data}\"");
+        self.style(&shape.style);
+        self.out.push_str("/>\n");
+    }
+
+    fn style(&mut self, style: &Style) {
+        match style.fill {
+            Some(color) => {
+                let _ = write!(self.out, " fill=\"{}\"", color.to_hex());
+                if style.even_odd {
+                    self.out.push_str(" fill-rule=\"evenodd\"");
+                }
+            }
+            None => self.out.push_str(" fill=\"none\""),
+        }
+
+        let Some(stroke) = &style.stroke else {
+            return;
+        };
+        let _ = write!(self.out, " stroke=\"{}\"", stroke.color.to_hex());
+        if stroke.width != 1.0 {
+            let _ = write!(self.out, " stroke-width=\"{}\"", self.number(stroke.width));
+        }
+        match stroke.cap {
+            LineCap::Butt => {}
+            LineCap::Round => self.out.push_str(" stroke-linecap=\"round\""),
+            LineCap::Square => self.out.push_str(" stroke-linecap=\"square\""),
+        }
+        match stroke.join {
+            // SVG の既定のマイター比は 4、Illustrator は 10 なので常に書く
+            LineJoin::Miter => {
+                let _ = write!(self.out, " stroke-miterlimit=\"{}\"", self.number(stroke.miter_limit));
+            }
+            LineJoin::Round => self.out.push_str(" stroke-linejoin=\"round\""),
+            LineJoin::Bevel => self.out.push_str(" stroke-linejoin=\"bevel\""),
+        }
+        if !stroke.dash.is_empty() {
+            let dash: Vec<String> = stroke.dash.iter().map(|&length| self.number(length)).collect();
+            let _ = write!(self.out, " stroke-dasharray=\"{}\"", dash.join(" "));
+            if stroke.dash_offset != 0.0 {
+                let _ = write!(self.out, " stroke-dashoffset=\"{}\"", self.number(stroke.dash_offset));
+            }
+        }
+    }
+
+    fn name(&mut self, name: &str) {
+        if !name.is_empty() {
+            let _ = write!(self.out, " data-name=\"{}\"", escape(name));
+        }
+    }
+
+    /// パスの `d` 属性（セグメントのないパスは飛ばす）
+    fn path_data(&self, paths: &[BezierPath]) -> String {
+        let mut data = Vec::new();
+
+        for path in paths {
+            let Some(first) = path.segments.first() else {
+                continue;
+            };
+            data.push(format!("M{}", self.point(first.anchor)));
+
+            let curves = path.curves();
+            let count = curves.len();
+            for (index, curve) in curves.iter().enumerate() {
+                // 閉じたパスの最後の直線は `Z` が引く
+                let closing = path.closed && index + 1 == count && path.segments.len() > 1;
+                if closing && curve.is_line() {
+                    break;
+                }
+                data.push(self.curve(curve));
+            }
+            if path.closed {
+                data.push("Z".into());
+            }
+        }
+        data.join(" ")
+    }
+
+    fn curve(&self, curve: &CubicBezier) -> String {
+        if curve.is_line() {
+            return format!("L{}", self.point(curve.p3));
+        }
+        format!("C{} {} {}", self.point(curve.p1), self.point(curve.p2), self.point(curve.p3))
+    }
+
+    fn point(&self, point: Point) -> String {
+        let x = self.number(point.x - self.bounds.left);
+        let y = self.number(self.bounds.top - point.y);
+        format!("{x},{y}")
+    }
+
+    fn number(&self, value: f64) -> String {
+        format_number(value, self.precision)
+    }
+
+    fn indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.out.push_str("  ");
+        }
+    }
+}
+
+/// 小数点以下 `precision` 桁に丸め、末尾の 0 を除いた数値
+fn format_number(value: f64, precision: usize) -> String {
+    let text = format!("{:.*}", precision, value);
+    let text = if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &text
+    };
+
+    match text {
+        "-0" => "0".into(),
+        text => text.into(),
+    }
+}
+
+/// 属性値として書けるように XML の特殊文字を置き換える
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+
+    fn square(left: f64, top: f64, size: f64) -> BezierPath {
+        BezierPath::rectangle(Rect::new(left, top, left + size, top - size))
+    }
+
+    fn filled(paths: Vec<BezierPath>) -> Node {
+        Node::Shape(Shape {
+            name: String::new(),
+            paths,
+            style: Style { fill: Some(Rgb::BLACK), ..Style::default() },
+        })
+    }
+
+    #[test]
+    fn nested_group() {
+        let mut scene = Scene::new(Rect::new(0.0, 100.0, 100.0, 0.0));
+        scene.children.push(Node::Group(Group {
+            name: "outer".into(),
+            clip: None,
+            children: vec![
+                Node::Group(Group {
+                    name: "inner".into(),
+                    clip: None,
+                    children: vec![filled(vec![square(10.0, 90.0, 20.0)])],
+                }),
+                Node::Group(Group::default()),
+            ],
+        }));
+
+        let expected = [
+            HEADER,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" width=\"100\" height=\"100\" viewBox=\"0 0 100 100\">\n",
+            "  <g data-name=\"outer\">\n",
+            "    <g data-name=\"inner\">\n",
+            "      <path d=\"M10,10 L30,10 L30,30 L10,30 Z\" fill=\"#000000\"/>\n",
+            "    </g>\n",
+            "    <g/>\n",
+            "  </g>\n",
+            "</svg>\n",
+        ]
+        .concat();
+        assert_eq!(scene.to_svg(2), expected);
+    }
+
+    #[test]
+    fn compound_path_with_even_odd() {
+        let mut scene = Scene::new(Rect::new(0.0, 100.0, 100.0, 0.0));
+        scene.children.push(Node::Shape(Shape {
+            name: "ring & hole".into(),
+            paths: vec![square(0.0, 100.0, 100.0), square(25.0, 75.0, 50.0)],
+            style: Style { fill: Some(Rgb::new(1.0, 0.5, 0.0)), even_odd: true, stroke: None },
+        }));
+
+        let expected = [
+            HEADER,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" width=\"100\" height=\"100\" viewBox=\"0 0 100 100\">\n",
+            "  <path data-name=\"ring &amp; hole\" d=\"M0,0 L100,0 L100,100 L0,100 Z M25,25 L75,25 L75,75 L25,75 Z\" \
+             fill=\"#ff8000\" fill-rule=\"evenodd\"/>\n",
+            "</svg>\n",
+        ]
+        .concat();
+        assert_eq!(scene.to_svg(2), expected);
+    }
+
+    #[test]
+    fn clip_group() {
+        let mut scene = Scene::new(Rect::new(0.0, 100.0, 100.0, 0.0));
+        scene.children.push(Node::Group(Group {
+            name: String::new(),
+            clip: Some(vec![square(0.0, 100.0, 50.0), square(50.0, 50.0, 50.0)]),
+            children: vec![filled(vec![square(0.0, 100.0, 100.0)])],
+        }));
+
+        let expected = [
+            HEADER,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" width=\"100\" height=\"100\" viewBox=\"0 0 100 100\">\n",
+            "  <clipPath id=\"clip1\">\n",
+            "    <path d=\"M0,0 L50,0 L50,50 L0,50 Z M50,50 L100,50 L100,100 L50,100 Z\"/>\n",
+            "  </clipPath>\n",
+            "  <g clip-path=\"url(#clip1)\">\n",
+            "    <path d=\"M0,0 L100,0 L100,100 L0,100 Z\" fill=\"#000000\"/>\n",
+            "  </g>\n",
+            "</svg>\n",
+        ]
+        .concat();
+        assert_eq!(scene.to_svg(2), expected);
+        assert_eq!(scene.content_bounds(), Some(Rect::new(0.0, 100.0, 100.0, 0.0)));
+    }
+
+    #[test]
+    fn dashed_stroke() {
+        let mut scene = Scene::new(Rect::new(0.0, 10.0, 100.0, 0.0));
+        scene.children.push(Node::Shape(Shape {
+            name: String::new(),
+            paths: vec![BezierPath::from_points(&[Point::new(0.0, 5.0), Point::new(100.0, 5.0)], false)],
+            style: Style {
+                fill: None,
+                even_odd: false,
+                stroke: Some(Stroke {
+                    color: Rgb::new(0.0, 0.0, 1.0),
+                    width: 2.5,
+                    cap: LineCap::Round,
+                    dash: vec![4.0, 2.0],
+                    dash_offset: 1.0,
+                    ..Stroke::default()
+                }),
+            },
+        }));
+
+        let expected = [
+            HEADER,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" width=\"100\" height=\"10\" viewBox=\"0 0 100 10\">\n",
+            "  <path d=\"M0,5 L100,5\" fill=\"none\" stroke=\"#0000ff\" stroke-width=\"2.5\" stroke-linecap=\"round\" \
+             stroke-miterlimit=\"10\" stroke-dasharray=\"4 2\" stroke-dashoffset=\"1\"/>\n",
+            "</svg>\n",
+        ]
+        .concat();
+        assert_eq!(scene.to_svg(2), expected);
+    }
+
+    #[test]
+    fn artboard_view_box() {
+        // アートボードの左上が原点になり、y 軸が反転する
+        let mut scene = Scene::new(Rect::new(100.0, 500.0, 300.0, 400.0));
+        scene.children.push(filled(vec![square(110.0, 490.0, 10.333)]));
+
+        let expected = [
+            HEADER,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" width=\"200\" height=\"100\" viewBox=\"0 0 200 100\">\n",
+            "  <path d=\"M10,10 L20.33,10 L20.33,20.33 L10,20.33 Z\" fill=\"#000000\"/>\n",
+            "</svg>\n",
+        ]
+        .concat();
+        assert_eq!(scene.to_svg(2), expected);
+    }
+}
@@ -0,0 +1,210 @@
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr};
+use std::ptr::null_mut;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::error::{AIError, AIResult, SuiteFn};
+use crate::externs::plugin_ref;
+use crate::util::read_c_string;
+
+/// タイマーの周期の単位（1 秒あたりのティック数）
+pub const TICKS_PER_SECOND: i32 = 60;
+
+/// 時間をティック数に変換する（最低 1 ティック）
+pub fn ticks(duration: Duration) -> i32 {
+    let ticks = duration.as_secs_f64() * f64::from(TICKS_PER_SECOND);
+    ticks.round().clamp(1.0, f64::from(i32::MAX)) as i32
+}
+
+/// `AddTimer` で追加したタイマー
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Timer(AITimerHandle);
+
+impl Timer {
+    /// # Safety
+    /// `handle` は `AITimerSuite` が返した有効なタイマーでなければならない。
+    pub unsafe fn from_raw(handle: AITimerHandle) -> Option<Self> {
+        (!handle.is_null()).then_some(Self(handle))
+    }
+
+    pub fn as_raw(self) -> AITimerHandle {
+        self.0
+    }
+
+    /// `AddTimer` に渡した名前
+    pub fn name(self) -> AIResult<String> {
+        let suite = SuiteGuard::<AITimerSuite>::acquire()?;
+        let mut name: *mut c_char = null_mut();
+
+        unsafe {
+            suite.GetTimerName.call((self.0, &mut name as *mut _))?;
+            read_c_string(name)
+        }
+    }
+
+    pub fn is_active(self) -> AIResult<bool> {
+        let suite = SuiteGuard::<AITimerSuite>::acquire()?;
+        let mut active: AIBoolean = 0;
+
+        unsafe { suite.GetTimerActive.call((self.0, &mut active as *mut _))? };
+        Ok(active != 0)
+    }
+
+    pub fn set_active(self, active: bool) -> AIResult<()> {
+        let suite = SuiteGuard::<AITimerSuite>::acquire()?;
+        unsafe { suite.SetTimerActive.call((self.0, active as AIBoolean)) }
+    }
+
+    pub fn activate(self) -> AIResult<()> {
+        self.set_active(true)
+    }
+
+    /// 無効にしたタイマーには `kSelectorAIGoTimer` が送られない（タイマーは取り除けない）
+    pub fn deactivate(self) -> AIResult<()> {
+        self.set_active(false)
+    }
+
+    /// 周期（ティック数）
+    pub fn period(self) -> AIResult<i32> {
+        let suite = SuiteGuard::<AITimerSuite>::acquire()?;
+        let mut period: ai_int32 = 0;
+
+        unsafe { suite.GetTimerPeriod.call((self.0, &mut period as *mut _))? };
+        Ok(period)
+    }
+
+    /// 周期を変える
+    pub fn reschedule(self, period: i32) -> AIResult<()> {
+        let suite = SuiteGuard::<AITimerSuite>::acquire()?;
+        unsafe { suite.SetTimerPeriod.call((self.0, period)) }
+    }
+}
+
+/// 短い間に何度も起きる出来事をまとめて、最後の `trigger` から一定時間後に一度だけ処理する
+///
+/// `Timers::debounce` で作る。通知のハンドラから `trigger` を呼ぶと、
+/// 呼ぶたびに待ち時間がやり直され、最後の呼び出しから `delay` ティック後にハンドラが一度呼ばれる。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Debounce {
+    timer: Timer,
+    delay: i32,
+}
+
+impl Debounce {
+    pub fn timer(&self) -> Timer {
+        self.timer
+    }
+
+    /// 待ち時間（ティック数）
+    pub fn delay(&self) -> i32 {
+        self.delay
+    }
+
+    /// ハンドラの呼び出しを予約し直す
+    pub fn trigger(&self) -> AIResult<()> {
+        self.timer.deactivate()?;
+        self.timer.reschedule(self.delay)?;
+        self.timer.activate()
+    }
+
+    /// 予約を取り消す
+    pub fn cancel(&self) -> AIResult<()> {
+        self.timer.deactivate()
+    }
+
+    /// ハンドラの呼び出しを待っているか
+    pub fn is_pending(&self) -> AIResult<bool> {
+        self.timer.is_active()
+    }
+}
+
+type Handler<T> = Rc<RefCell<dyn FnMut(&mut T) -> AIResult<()>>>;
+
+struct TimerEntry<T> {
+    timer: Timer,
+    handler: Handler<T>,
+}
+
+/// プラグインが追加したタイマーと、そのハンドラ
+///
+/// 起動時（`SafePlugin::startup`）にタイマーを追加し、`SafePlugin::timers` から返すと、
+/// `kSelectorAIGoTimer` がタイマーごとのハンドラへ送られる。
+/// ハンドラが登録されていないタイマーは `go_timer` へ送られる。
+pub struct Timers<T> {
+    entries: Vec<TimerEntry<T>>,
+}
+
+impl<T> Default for Timers<T> {
+    fn default() -> Self {
+        Self { entries: Vec::new() }
+    }
+}
+
+impl<T> Timers<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 追加したタイマー（追加順）
+    pub fn timers(&self) -> impl Iterator<Item = Timer> + '_ {
+        self.entries.iter().map(|entry| entry.timer)
+    }
+
+    pub fn contains(&self, timer: AITimerHandle) -> bool {
+        self.handler(timer).is_some()
+    }
+
+    /// `kSelectorAIGoTimer` を受け取ったときのハンドラ
+    pub(crate) fn handler(&self, timer: AITimerHandle) -> Option<Handler<T>> {
+        self.entries
+            .iter()
+            .find(|entry| entry.timer.as_raw() == timer)
+            .map(|entry| entry.handler.clone())
+    }
+}
+
+impl<T: 'static> Timers<T> {
+    /// `period` ティックごとに `handler` を呼ぶタイマーを追加する（追加した時点で有効）
+    pub fn schedule<F>(&mut self, name: &CStr, period: i32, handler: F) -> AIResult<Timer>
+    where
+        F: FnMut(&mut T) -> AIResult<()> + 'static,
+    {
+        let timer = add_timer(name, period)?;
+        self.entries.push(TimerEntry {
+            timer,
+            handler: Rc::new(RefCell::new(handler)),
+        });
+        Ok(timer)
+    }
+
+    /// `Debounce::trigger` の最後の呼び出しから `delay` ティック後に `handler` を一度呼ぶ
+    pub fn debounce<F>(&mut self, name: &CStr, delay: i32, mut handler: F) -> AIResult<Debounce>
+    where
+        F: FnMut(&mut T) -> AIResult<()> + 'static,
+    {
+        let timer = add_timer(name, delay)?;
+        timer.deactivate()?;
+
+        self.entries.push(TimerEntry {
+            timer,
+            handler: Rc::new(RefCell::new(move |plugin: &mut T| {
+                timer.deactivate()?;
+                handler(plugin)
+            })),
+        });
+        Ok(Debounce { timer, delay })
+    }
+}
+
+fn add_timer(name: &CStr, period: i32) -> AIResult<Timer> {
+    let suite = SuiteGuard::<AITimerSuite>::acquire()?;
+    let mut timer: AITimerHandle = null_mut();
+
+    unsafe {
+        suite.AddTimer.call((plugin_ref(), name.as_ptr(), period, &mut timer as *mut _))?;
+        Timer::from_raw(timer).ok_or(AIError::CantHappen)
+    }
+}
@@ -0,0 +1,498 @@
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr};
+use std::ptr::null_mut;
+use std::rc::Rc;
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::error::{AIError, AIResult, SuiteFn};
+use crate::externs::plugin_ref;
+use crate::geometry::Point;
+use crate::unicode::UnicodeString;
+use crate::util::{self, flags, read_c_string, Registry};
+
+// AITypes.h / AIToolMessage の定数（`#define` のためバインディングに含まれない）
+#[allow(non_upper_case_globals)]
+const kNoTool: AIToolType = -2;
+#[allow(non_upper_case_globals)]
+const kMaxToolPressure: f64 = 255.0;
+
+flags! {
+    /// `AddTool` に渡すツールのオプション（`AIToolOptions`）
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct ToolOptions(u32) {
+        /// マウスボタンを押していないときも `track_cursor` を受け取る
+        const TRACK_CURSOR = AIToolOptions_kToolWantsToTrackCursorOption;
+        const NO_AUTO_SCROLL = AIToolOptions_kToolDoesntWantAutoScrollOption;
+        const BUFFERED_DRAGGING = AIToolOptions_kToolWantsBufferedDraggingOption;
+        const MAINTAIN_EDIT_CONTEXT = AIToolOptions_kToolMaintainEditContextOption;
+        const TEXT_TOOL = AIToolOptions_kToolIsTextToolOption;
+        /// `[`・`]` キーで `change_diameter` を受け取る
+        const CHANGE_DIAMETER = AIToolOptions_kToolWantsToChangeDiameterOption;
+        const HIDDEN = AIToolOptions_kToolWantsHiddenToolOption;
+        const NO_SOFT_SELECTION = AIToolOptions_kToolDoesntWantSoftSelectionOption;
+    }
+}
+
+/// ツールアイコンの形式（`ai::IconType`）
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum IconType {
+    #[default]
+    Svg,
+    Png,
+}
+
+impl IconType {
+    fn to_raw(self) -> ai_IconType {
+        match self {
+            IconType::Svg => ai_IconType_kSVG,
+            IconType::Png => ai_IconType_kPNG,
+        }
+    }
+}
+
+/// ツールパネルのツール
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Tool(AIToolHandle);
+
+impl Tool {
+    /// # Safety
+    /// `handle` は `AIToolSuite` が返した有効なツールでなければならない。
+    pub unsafe fn from_raw(handle: AIToolHandle) -> Option<Self> {
+        (!handle.is_null()).then_some(Self(handle))
+    }
+
+    pub fn as_raw(self) -> AIToolHandle {
+        self.0
+    }
+
+    /// 選択中のツール
+    pub fn selected() -> AIResult<Option<Tool>> {
+        let suite = SuiteGuard::<AIToolSuite>::acquire()?;
+        let mut tool: AIToolHandle = null_mut();
+
+        unsafe {
+            suite.GetSelectedTool.call((&mut tool as *mut _,))?;
+            Ok(Tool::from_raw(tool))
+        }
+    }
+
+    /// ツールを選択する
+    pub fn select(self) -> AIResult<()> {
+        let suite = SuiteGuard::<AIToolSuite>::acquire()?;
+        unsafe { suite.SetSelectedTool.call((self.0,)) }
+    }
+
+    /// `AddTool` に渡した一意な名前
+    pub fn name(self) -> AIResult<String> {
+        let suite = SuiteGuard::<AIToolSuite>::acquire()?;
+        let mut name: *mut c_char = null_mut();
+
+        unsafe {
+            suite.GetToolName.call((self.0, &mut name as *mut _))?;
+            read_c_string(name)
+        }
+    }
+
+    /// ツール番号（ツールグループの指定に使う）
+    pub fn number(self) -> AIResult<AIToolType> {
+        let suite = SuiteGuard::<AIToolSuite>::acquire()?;
+        let mut number: AIToolType = 0;
+
+        unsafe { suite.GetToolNumberFromHandle.call((self.0, &mut number as *mut _))? };
+        Ok(number)
+    }
+
+    pub fn options(self) -> AIResult<ToolOptions> {
+        let suite = SuiteGuard::<AIToolSuite>::acquire()?;
+        let mut options: ai_int32 = 0;
+
+        unsafe { suite.GetToolOptions.call((self.0, &mut options as *mut _))? };
+        Ok(ToolOptions(options as u32))
+    }
+
+    pub fn set_options(self, options: ToolOptions) -> AIResult<()> {
+        let suite = SuiteGuard::<AIToolSuite>::acquire()?;
+        unsafe { suite.SetToolOptions.call((self.0, options.bits() as ai_int32)) }
+    }
+
+    /// ツールパネルに表示される名前
+    pub fn title(self) -> AIResult<String> {
+        let suite = SuiteGuard::<AIToolSuite>::acquire()?;
+        let mut title = UnicodeString::empty()?;
+
+        unsafe { suite.GetToolTitle.call((self.0, title.as_mut_ptr()))? };
+        Ok(title.to_string_lossy())
+    }
+
+    pub fn set_title(self, title: &str) -> AIResult<()> {
+        let suite = SuiteGuard::<AIToolSuite>::acquire()?;
+        let title = UnicodeString::new(title)?;
+        unsafe { suite.SetToolTitle.call((self.0, title.borrow_raw())) }
+    }
+
+    pub fn tooltip(self) -> AIResult<String> {
+        let suite = SuiteGuard::<AIToolSuite>::acquire()?;
+        let mut tooltip = UnicodeString::empty()?;
+
+        unsafe { suite.GetTooltip.call((self.0, tooltip.as_mut_ptr()))? };
+        Ok(tooltip.to_string_lossy())
+    }
+
+    pub fn set_tooltip(self, tooltip: &str) -> AIResult<()> {
+        let suite = SuiteGuard::<AIToolSuite>::acquire()?;
+        let tooltip = UnicodeString::new(tooltip)?;
+        unsafe { suite.SetTooltip.call((self.0, tooltip.borrow_raw())) }
+    }
+
+    /// アイコンをリソース名で設定する（通常・ダークテーマ用）
+    pub fn set_icons(self, normal: &CStr, dark: &CStr) -> AIResult<()> {
+        let suite = SuiteGuard::<AIToolSuite>::acquire()?;
+        unsafe { suite.SetToolIcons.call((self.0, normal.as_ptr(), dark.as_ptr())) }
+    }
+}
+
+fn tool_number(name: &CStr) -> AIResult<AIToolType> {
+    let suite = SuiteGuard::<AIToolSuite>::acquire()?;
+    let mut number: AIToolType = 0;
+
+    unsafe { suite.GetToolNumberFromName.call((name.as_ptr(), &mut number as *mut _))? };
+    Ok(number)
+}
+
+/// 押されている修飾キー
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub shift: bool,
+    /// Option（Windows では Alt）
+    pub option: bool,
+    /// Command（Windows では Ctrl）
+    pub command: bool,
+    /// Control（Windows では右クリック相当）
+    pub control: bool,
+    pub space: bool,
+    pub caps_lock: bool,
+    pub double_click: bool,
+}
+
+impl Modifiers {
+    pub fn from_raw(raw: u32) -> Self {
+        let has = |flag: AIEventModifersValue| raw & flag != 0;
+        Self {
+            shift: has(AIEventModifersValue_aiEventModifiers_shiftKey),
+            option: has(AIEventModifersValue_aiEventModifiers_optionKey),
+            command: has(AIEventModifersValue_aiEventModifiers_cmdKey),
+            control: has(AIEventModifersValue_aiEventModifiers_controlKey),
+            space: has(AIEventModifersValue_aiEventModifiers_spaceKey),
+            caps_lock: has(AIEventModifersValue_aiEventModifiers_capsLockOn),
+            double_click: has(AIEventModifersValue_aiEventModifiers_doubleClick),
+        }
+    }
+}
+
+/// ツールが受け取ったマウス・タブレットの状態
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ToolEvent {
+    pub tool: Tool,
+    /// ドキュメント座標のカーソル位置
+    pub cursor: Point,
+    pub modifiers: Modifiers,
+    /// 筆圧（0.0〜1.0、マウスでは 0.5 付近）
+    pub pressure: f64,
+    /// スタイラスのホイール（0.0〜1.0）
+    pub stylus_wheel: f64,
+    /// ペンの傾き（度）
+    pub tilt: f64,
+    /// ペンの方位（度）
+    pub bearing: f64,
+    /// ペンの回転（度）
+    pub rotation: f64,
+}
+
+impl ToolEvent {
+    /// `AIToolMessage` から作る（ツールが null なら `None`）
+    ///
+    /// # Safety
+    /// `message.event` は null か、メッセージの処理中有効な `EventRecord` を指していなければならない。
+    pub(crate) unsafe fn from_raw(message: &AIToolMessage) -> Option<Self> {
+        let modifiers = message.event.as_ref().map_or(0, |event| event.modifiers as u32);
+
+        Some(Self {
+            tool: Tool::from_raw(message.tool)?,
+            cursor: message.cursor.into(),
+            modifiers: Modifiers::from_raw(modifiers),
+            pressure: f64::from(message.pressure) / kMaxToolPressure,
+            stylus_wheel: f64::from(message.stylusWheel) / kMaxToolPressure,
+            tilt: f64::from(message.tilt),
+            bearing: f64::from(message.bearing),
+            rotation: f64::from(message.rotation),
+        })
+    }
+}
+
+/// ドラッグの始点と現在位置
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Drag {
+    pub start: Point,
+    pub current: Point,
+}
+
+impl Drag {
+    /// 始点からの移動量
+    pub fn delta(&self) -> Point {
+        Point::new(self.current.x - self.start.x, self.current.y - self.start.y)
+    }
+}
+
+/// mouse down → drag → up の状態を追う
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DragTracker {
+    drag: Option<Drag>,
+}
+
+impl DragTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn begin(&mut self, event: &ToolEvent) -> Drag {
+        let drag = Drag { start: event.cursor, current: event.cursor };
+        self.drag = Some(drag);
+        drag
+    }
+
+    /// ドラッグ中なら現在位置を更新する（`begin` 前なら `None`）
+    pub fn update(&mut self, event: &ToolEvent) -> Option<Drag> {
+        let drag = self.drag.as_mut()?;
+        drag.current = event.cursor;
+        Some(*drag)
+    }
+
+    /// ドラッグを終える（`begin` 前なら `None`）
+    pub fn end(&mut self, event: &ToolEvent) -> Option<Drag> {
+        let mut drag = self.drag.take()?;
+        drag.current = event.cursor;
+        Some(drag)
+    }
+
+    pub fn current(&self) -> Option<Drag> {
+        self.drag
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+}
+
+/// ツールの振る舞い
+///
+/// `ToolBuilder::add` でツールと結び付けると、そのツールの `kCallerAITool` のメッセージがここへ送られる。
+/// 既定の実装は何もしない。
+#[allow(unused_variables)]
+pub trait ToolBehavior<P> {
+    fn select(&mut self, plugin: &mut P, tool: Tool) -> AIResult<()> { Ok(()) }
+    fn deselect(&mut self, plugin: &mut P, tool: Tool) -> AIResult<()> { Ok(()) }
+    fn reselect(&mut self, plugin: &mut P, tool: Tool) -> AIResult<()> { Ok(()) }
+
+    /// ツールをダブルクリックしたとき（`ToolBuilder::options_dialog` を指定した場合のみ）
+    fn edit_options(&mut self, plugin: &mut P, tool: Tool) -> AIResult<()> { Ok(()) }
+
+    /// カーソルの移動（`ToolOptions::TRACK_CURSOR` を指定した場合のみ）
+    fn track_cursor(&mut self, plugin: &mut P, event: &ToolEvent) -> AIResult<()> { Ok(()) }
+
+    fn mouse_down(&mut self, plugin: &mut P, event: &ToolEvent) -> AIResult<()> { Ok(()) }
+    fn mouse_drag(&mut self, plugin: &mut P, event: &ToolEvent) -> AIResult<()> { Ok(()) }
+    fn mouse_up(&mut self, plugin: &mut P, event: &ToolEvent) -> AIResult<()> { Ok(()) }
+
+    /// `[`・`]` キー（`ToolOptions::CHANGE_DIAMETER` を指定した場合のみ、`increase` は `]`）
+    fn change_diameter(&mut self, plugin: &mut P, tool: Tool, increase: bool) -> AIResult<()> { Ok(()) }
+}
+
+/// `kCallerAITool` のセレクタ
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ToolSelector {
+    EditOptions,
+    TrackCursor,
+    MouseDown,
+    MouseDrag,
+    MouseUp,
+    Select,
+    Deselect,
+    Reselect,
+    DecreaseDiameter,
+    IncreaseDiameter,
+}
+
+/// プラグインが追加したツールと、その振る舞い
+///
+/// 起動時（`SafePlugin::startup`）にツールを追加し、`SafePlugin::tools` から返すと、
+/// ツールへのメッセージが `ToolBehavior` へ送られる。
+pub struct Tools<P> {
+    /// 項目ごとの設定は `ToolBuilder::options_dialog` の指定
+    entries: Registry<Tool, dyn ToolBehavior<P>, bool>,
+}
+
+impl<P> Default for Tools<P> {
+    fn default() -> Self {
+        Self { entries: Registry::default() }
+    }
+}
+
+impl<P> Tools<P> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ツールの設定を始める
+    ///
+    /// `name` はツールを識別する一意な名前（ツールグループの指定にも使う）。
+    pub fn tool<'a>(&'a mut self, name: &'a CStr, title: &'a str) -> ToolBuilder<'a, P> {
+        ToolBuilder {
+            tools: self,
+            name,
+            title,
+            tooltip: None,
+            icons: (0, 0, IconType::default()),
+            icon_names: None,
+            same_group_as: None,
+            same_toolset_as: None,
+            options: ToolOptions::empty(),
+            options_dialog: false,
+        }
+    }
+
+    /// 追加したツール（追加順）
+    pub fn iter(&self) -> impl Iterator<Item = Tool> + '_ {
+        self.entries.keys()
+    }
+
+    pub fn contains(&self, tool: AIToolHandle) -> bool {
+        self.entries.contains(Tool(tool))
+    }
+}
+
+/// `kCallerAITool` のメッセージを `ToolBehavior` へ送る（`EditOptions` は `options_dialog` を指定したツールだけ）
+pub(crate) fn dispatch<P>(
+    plugin: &mut P,
+    tools: fn(&mut P) -> Option<&mut Tools<P>>,
+    message: *mut AIToolMessage,
+    selector: ToolSelector,
+) -> Option<AIResult<()>> {
+    let message = unsafe { message.as_ref() }?;
+    let tool = Tool(message.tool);
+    let options_dialog = *tools(plugin)?.entries.get(tool)?;
+    if selector == ToolSelector::EditOptions && !options_dialog {
+        return None;
+    }
+
+    let event = unsafe { ToolEvent::from_raw(message)? };
+    util::dispatch(plugin, |plugin| Some(&tools(plugin)?.entries), tool, |behavior, plugin, _| match selector {
+        ToolSelector::EditOptions => behavior.edit_options(plugin, tool),
+        ToolSelector::TrackCursor => behavior.track_cursor(plugin, &event),
+        ToolSelector::MouseDown => behavior.mouse_down(plugin, &event),
+        ToolSelector::MouseDrag => behavior.mouse_drag(plugin, &event),
+        ToolSelector::MouseUp => behavior.mouse_up(plugin, &event),
+        ToolSelector::Select => behavior.select(plugin, tool),
+        ToolSelector::Deselect => behavior.deselect(plugin, tool),
+        ToolSelector::Reselect => behavior.reselect(plugin, tool),
+        ToolSelector::DecreaseDiameter => behavior.change_diameter(plugin, tool, false),
+        ToolSelector::IncreaseDiameter => behavior.change_diameter(plugin, tool, true),
+    })
+}
+
+/// `Tools::tool` が返す、ツールの設定
+pub struct ToolBuilder<'a, P> {
+    tools: &'a mut Tools<P>,
+    name: &'a CStr,
+    title: &'a str,
+    tooltip: Option<&'a str>,
+    icons: (u32, u32, IconType),
+    icon_names: Option<(&'a CStr, &'a CStr)>,
+    same_group_as: Option<&'a CStr>,
+    same_toolset_as: Option<&'a CStr>,
+    options: ToolOptions,
+    options_dialog: bool,
+}
+
+impl<'a, P> ToolBuilder<'a, P> {
+    pub fn tooltip(mut self, tooltip: &'a str) -> Self {
+        self.tooltip = Some(tooltip);
+        self
+    }
+
+    /// アイコンのリソース ID（通常・ダークテーマ用）
+    pub fn icons(mut self, normal: u32, dark: u32, icon_type: IconType) -> Self {
+        self.icons = (normal, dark, icon_type);
+        self
+    }
+
+    /// アイコンのリソース名（通常・ダークテーマ用、追加後に `SetToolIcons` で設定する）
+    pub fn icon_names(mut self, normal: &'a CStr, dark: &'a CStr) -> Self {
+        self.icon_names = Some((normal, dark));
+        self
+    }
+
+    /// `tool` と同じツールグループに置く（既定は新しいグループ）
+    pub fn same_group_as(mut self, tool: &'a CStr) -> Self {
+        self.same_group_as = Some(tool);
+        self
+    }
+
+    /// `tool` と同じツールセットに置く（既定は新しいツールセット）
+    pub fn same_toolset_as(mut self, tool: &'a CStr) -> Self {
+        self.same_toolset_as = Some(tool);
+        self
+    }
+
+    pub fn options(mut self, options: ToolOptions) -> Self {
+        self.options |= options;
+        self
+    }
+
+    /// ダブルクリックで `ToolBehavior::edit_options` を呼ぶ
+    pub fn options_dialog(mut self) -> Self {
+        self.options_dialog = true;
+        self
+    }
+
+    /// ツールパネルに追加し、`behavior` と結び付ける
+    pub fn add(self, behavior: impl ToolBehavior<P> + 'static) -> AIResult<Tool> {
+        let number = |name: Option<&CStr>| name.map_or(Ok(kNoTool), tool_number);
+        let same_group_as = number(self.same_group_as)?;
+        let same_toolset_as = number(self.same_toolset_as)?;
+
+        let suite = SuiteGuard::<AIToolSuite>::acquire()?;
+        let title = UnicodeString::new(self.title)?;
+        let tooltip = UnicodeString::new(self.tooltip.unwrap_or(self.title))?;
+        let (normal, dark, icon_type) = self.icons;
+        let mut tool: AIToolHandle = null_mut();
+
+        let data = AIAddToolData {
+            title: title.borrow_raw(),
+            tooltip: tooltip.borrow_raw(),
+            normalIconResID: normal,
+            darkIconResID: dark,
+            sameGroupAs: same_group_as,
+            sameToolsetAs: same_toolset_as,
+            iconType: icon_type.to_raw(),
+        };
+
+        let tool = unsafe {
+            suite.AddTool.call((
+                plugin_ref(),
+                self.name.as_ptr(),
+                &data as *const _,
+                self.options.bits() as ai_int32,
+                &mut tool as *mut _,
+            ))?;
+            Tool::from_raw(tool).ok_or(AIError::CantHappen)?
+        };
+
+        if let Some((normal, dark)) = self.icon_names {
+            tool.set_icons(normal, dark)?;
+        }
+
+        self.tools.entries.push(tool, Rc::new(RefCell::new(behavior)), self.options_dialog);
+        Ok(tool)
+    }
+}
@@ -0,0 +1,165 @@
+use std::cell::Cell;
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::error::{AIError, AIResult, SuiteFn};
+use crate::unicode::UnicodeString;
+
+thread_local! {
+    /// 処理中のメッセージで `UndoTransaction` を始めたか
+    static STARTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// メッセージの処理を始める（`PluginMain` から呼ばれる）
+pub(crate) fn begin_message() {
+    STARTED.with(|started| started.set(false));
+}
+
+/// 取り消し単位の種類（`AIUndoContextKind`）
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum UndoKind {
+    /// 通常の取り消し単位
+    #[default]
+    Standard,
+    /// 取り消しの履歴に残らない（直前の取り消し単位を取り消すと一緒に戻る）
+    Silent,
+    /// 直前の取り消し単位に加える
+    Append,
+}
+
+impl UndoKind {
+    fn to_raw(self) -> AIUndoContextKind {
+        match self {
+            UndoKind::Standard => AIUndoContextKind_kAIStandardUndoContext,
+            UndoKind::Silent => AIUndoContextKind_kAISilentUndoContext,
+            UndoKind::Append => AIUndoContextKind_kAIAppendUndoContext,
+        }
+    }
+}
+
+/// 取り消し・やり直しの単位
+///
+/// Illustrator はプラグインへのメッセージ 1 回分の変更を 1 つの取り消し単位にまとめる。
+/// このガードはその単位に表示名を付け、`commit` せずに破棄されたとき（`?` で抜けたときやパニック）は
+/// `UndoChanges` でメッセージの開始時点まで変更を戻す。
+///
+/// `UndoChanges` はメッセージ全体を戻すため、トランザクションはメッセージごとに一つしか始められない
+/// （二つ目を始めると `AIError::BadParameter`）。確定済みの変更を後のトランザクションが巻き込んで戻すことはない。
+///
+/// ```ignore
+/// let transaction = UndoTransaction::begin("Undo Round Corners", "Redo Round Corners")?;
+/// for path in Selection::current()?.paths()? {
+///     round_corners(path, radius)?;
+/// }
+/// transaction.commit();
+/// ```
+#[must_use = "dropping the transaction without commit reverts the changes"]
+pub struct UndoTransaction {
+    committed: bool,
+    /// `SetSilent` で履歴に残らない操作にしたか
+    silent: bool,
+    /// `SetKind` で種類を変えたか
+    kind_changed: bool,
+}
+
+impl UndoTransaction {
+    /// 「編集」メニューに表示する取り消し・やり直しの名前を設定して始める
+    ///
+    /// 同じメッセージで既にトランザクションを始めていれば `AIError::BadParameter`。
+    pub fn begin(undo_text: &str, redo_text: &str) -> AIResult<Self> {
+        let transaction = Self::start()?;
+        transaction.set_text(undo_text, redo_text)?;
+        Ok(transaction)
+    }
+
+    /// 取り消しの履歴に残らない操作として始める（`SetSilent`）
+    pub fn silent() -> AIResult<Self> {
+        let mut transaction = Self::start()?;
+        let suite = SuiteGuard::<AIUndoSuite>::acquire()?;
+        unsafe { suite.SetSilent.call((1,))? };
+        transaction.silent = true;
+        Ok(transaction)
+    }
+
+    /// 種類を指定して始める
+    pub fn with_kind(kind: UndoKind) -> AIResult<Self> {
+        let mut transaction = Self::start()?;
+        let suite = SuiteGuard::<AIUndoSuite>::acquire()?;
+        unsafe { suite.SetKind.call((kind.to_raw() as ai_int32,))? };
+        transaction.kind_changed = true;
+        Ok(transaction)
+    }
+
+    /// メッセージで最初のトランザクションなら始める
+    fn start() -> AIResult<Self> {
+        if STARTED.with(|started| started.replace(true)) {
+            return Err(AIError::BadParameter);
+        }
+        Ok(Self {
+            committed: false,
+            silent: false,
+            kind_changed: false,
+        })
+    }
+
+    /// `f` を実行し、成功したら確定する（失敗したら変更を戻してエラーを返す）
+    pub fn run<T>(undo_text: &str, redo_text: &str, f: impl FnOnce() -> AIResult<T>) -> AIResult<T> {
+        let transaction = Self::begin(undo_text, redo_text)?;
+        let value = f()?;
+        transaction.commit();
+        Ok(value)
+    }
+
+    /// 取り消し・やり直しの名前を変える
+    pub fn set_text(&self, undo_text: &str, redo_text: &str) -> AIResult<()> {
+        let suite = SuiteGuard::<AIUndoSuite>::acquire()?;
+        let undo_text = UnicodeString::new(undo_text)?;
+        let redo_text = UnicodeString::new(redo_text)?;
+
+        unsafe { suite.SetUndoTextUS.call((undo_text.as_ptr(), redo_text.as_ptr())) }
+    }
+
+    /// 変更を確定する
+    pub fn commit(mut self) {
+        self.committed = true;
+    }
+
+    /// 変更をすぐに戻す
+    pub fn revert(mut self) -> AIResult<()> {
+        self.committed = true;
+        self.undo()
+    }
+
+    /// 変更を戻し、`silent`・`with_kind` で変えた種類を通常に戻す
+    fn undo(&self) -> AIResult<()> {
+        let suite = SuiteGuard::<AIUndoSuite>::acquire()?;
+
+        unsafe {
+            suite.UndoChanges.call(())?;
+            if self.silent {
+                suite.SetSilent.call((0,))?;
+            }
+            if self.kind_changed {
+                suite.SetKind.call((UndoKind::Standard.to_raw() as ai_int32,))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 現在の取り消し単位が履歴に残らないか
+    pub fn is_silent() -> AIResult<bool> {
+        let suite = SuiteGuard::<AIUndoSuite>::acquire()?;
+        let mut silent: AIBoolean = 0;
+
+        unsafe { suite.IsSilent.call((&mut silent as *mut _,))? };
+        Ok(silent != 0)
+    }
+}
+
+impl Drop for UndoTransaction {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = self.undo();
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::fmt;
+use std::ptr::null;
+
+use crate::ai_suites::SuiteGuard;
+use crate::ai_sys::*;
+use crate::error::{AIResult, SuiteFn};
+
+/// `AIUnicodeStringSuite` で管理される `ai::UnicodeString`
+///
+/// 破棄時に `Destroy` で中身を解放する。
+pub struct UnicodeString {
+    raw: ai_UnicodeString,
+    suite: SuiteGuard<AIUnicodeStringSuite>,
+}
+
+impl UnicodeString {
+    /// Rust の文字列から作る
+    pub fn new(text: &str) -> AIResult<Self> {
+        Self::with_suite(SuiteGuard::acquire()?, text)
+    }
+
+    /// 取得済みの `AIUnicodeStringSuite` を使って作る
+    pub fn with_suite(suite: SuiteGuard<AIUnicodeStringSuite>, text: &str) -> AIResult<Self> {
+        let units: Vec<u16> = text.encode_utf16().collect();
+        let mut string = Self {
+            raw: unsafe { std::mem::zeroed() },
+            suite,
+        };
+
+        unsafe {
+            string
+                .suite
+                .InitializeUTF16
+                .call((&mut string.raw as *mut _, units.as_ptr(), units.len() as ai_UnicodeString_offset_type))?;
+        }
+        Ok(string)
+    }
+
+    /// 空文字列を作る（SDK 関数の出力引数として使う）
+    pub fn empty() -> AIResult<Self> {
+        Self::new("")
+    }
+
+    pub fn as_ptr(&self) -> *const ai_UnicodeString {
+        &self.raw
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut ai_UnicodeString {
+        &mut self.raw
+    }
+
+    /// 値渡しの引数や構造体のメンバー用に中身を借用する（ホストが複製するため所有権は移さない）
+    pub(crate) fn borrow_raw(&self) -> ai_UnicodeString {
+        ai_UnicodeString { fImpl: self.raw.fImpl }
+    }
+
+    /// UTF-16 から Rust の文字列へ変換する
+    pub fn to_string_lossy(&self) -> String {
+        let Some(utf_16) = self.suite.UTF_16 else {
+            return String::new();
+        };
+
+        let mut buffer: *const ai_UnicodeString_UTF16Char = null();
+        let length = unsafe { utf_16(&self.raw, &mut buffer) };
+
+        if buffer.is_null() || length == 0 {
+            return String::new();
+        }
+        let units = unsafe { std::slice::from_raw_parts(buffer as *const u16, length) };
+        String::from_utf16_lossy(units)
+    }
+}
+
+impl fmt::Display for UnicodeString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_string_lossy())
+    }
+}
+
+impl fmt::Debug for UnicodeString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_string_lossy(), f)
+    }
+}
+
+impl Drop for UnicodeString {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = self.suite.Destroy.call((&mut self.raw as *mut _,));
+        }
+    }
+}
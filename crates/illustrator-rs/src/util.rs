@@ -0,0 +1,173 @@
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr};
+use std::rc::Rc;
+
+use crate::error::{AIError, AIResult};
+
+/// SDK が返した C 文字列を `String` にする（`null` なら `CantHappen`）
+///
+/// # Safety
+/// `ptr` は `null` か、NUL で終わる有効な文字列を指していなければならない。
+pub(crate) unsafe fn read_c_string(ptr: *const c_char) -> AIResult<String> {
+    if ptr.is_null() {
+        return Err(AIError::CantHappen);
+    }
+    Ok(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+}
+
+struct RegistryEntry<K, B: ?Sized, X> {
+    key: K,
+    behavior: Rc<RefCell<B>>,
+    extra: X,
+}
+
+/// プラグインが登録した項目と、その振る舞い
+///
+/// `K` は項目のハンドル、`B` は振る舞いのトレイトオブジェクト、`X` は振る舞いと一緒に渡す項目ごとの設定。
+/// メッセージが届いたら `dispatch` でハンドルから振る舞いを探して呼ぶ。
+pub(crate) struct Registry<K, B: ?Sized, X = ()> {
+    entries: Vec<RegistryEntry<K, B, X>>,
+}
+
+impl<K, B: ?Sized, X> Default for Registry<K, B, X> {
+    fn default() -> Self {
+        Self { entries: Vec::new() }
+    }
+}
+
+impl<K: Copy + PartialEq, B: ?Sized, X> Registry<K, B, X> {
+    pub fn push(&mut self, key: K, behavior: Rc<RefCell<B>>, extra: X) {
+        self.entries.push(RegistryEntry { key, behavior, extra });
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 登録した項目（登録順）
+    pub fn keys(&self) -> impl Iterator<Item = K> + '_ {
+        self.entries.iter().map(|entry| entry.key)
+    }
+
+    /// 項目ごとの設定（登録順）
+    pub fn extras(&self) -> impl Iterator<Item = &X> + '_ {
+        self.entries.iter().map(|entry| &entry.extra)
+    }
+
+    pub fn contains(&self, key: K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// 項目ごとの設定
+    pub fn get(&self, key: K) -> Option<&X> {
+        self.entry(key).map(|entry| &entry.extra)
+    }
+
+    fn entry(&self, key: K) -> Option<&RegistryEntry<K, B, X>> {
+        self.entries.iter().find(|entry| entry.key == key)
+    }
+}
+
+/// `registry` に登録された項目なら、その振る舞いを借りて `call` を呼ぶ（登録されていなければ `None`）
+pub(crate) fn dispatch<P, K, B, X, R>(
+    plugin: &mut P,
+    registry: impl FnOnce(&mut P) -> Option<&Registry<K, B, X>>,
+    key: K,
+    call: impl FnOnce(&mut B, &mut P, X) -> R,
+) -> Option<R>
+where
+    K: Copy + PartialEq,
+    B: ?Sized,
+    X: Clone,
+{
+    let (behavior, extra) = registry(plugin)?
+        .entry(key)
+        .map(|entry| (entry.behavior.clone(), entry.extra.clone()))?;
+    let mut behavior = behavior.borrow_mut();
+
+    Some(call(&mut *behavior, plugin, extra))
+}
+
+/// `u32` のビットを組み合わせるフラグ型を定義する
+///
+/// 定数のほかに `empty`・`all`・`bits`・`contains`・`intersects`・`is_empty` と `|`・`&`・`!` を実装する。
+/// 定数の値は `u32` で与える。
+macro_rules! flags {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident($field_vis:vis u32) {
+            $(
+                $(#[$flag_meta:meta])*
+                const $flag:ident = $value:expr;
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name($field_vis u32);
+
+        impl $name {
+            $(
+                $(#[$flag_meta])*
+                pub const $flag: Self = Self($value);
+            )*
+
+            pub const fn empty() -> Self {
+                Self(0)
+            }
+
+            /// 定義されたすべてのフラグ
+            pub const fn all() -> Self {
+                Self(0 $(| $value)*)
+            }
+
+            pub const fn bits(self) -> u32 {
+                self.0
+            }
+
+            pub const fn contains(self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            /// いずれかが含まれるか
+            pub const fn intersects(self, other: Self) -> bool {
+                self.0 & other.0 != 0
+            }
+
+            pub const fn is_empty(self) -> bool {
+                self.0 == 0
+            }
+        }
+
+        impl ::std::ops::BitOr for $name {
+            type Output = Self;
+
+            fn bitor(self, rhs: Self) -> Self {
+                Self(self.0 | rhs.0)
+            }
+        }
+
+        impl ::std::ops::BitOrAssign for $name {
+            fn bitor_assign(&mut self, rhs: Self) {
+                self.0 |= rhs.0;
+            }
+        }
+
+        impl ::std::ops::BitAnd for $name {
+            type Output = Self;
+
+            fn bitand(self, rhs: Self) -> Self {
+                Self(self.0 & rhs.0)
+            }
+        }
+
+        impl ::std::ops::Not for $name {
+            type Output = Self;
+
+            fn not(self) -> Self {
+                Self(!self.0)
+            }
+        }
+    };
+}
+
+pub(crate) use flags;
@@ -19,6 +19,11 @@ fn main() {
     println!("{}", env::var("AISDK_ROOT").unwrap_or("".to_string()));
     if env::var("AISDK_ROOT").is_err() {
         println!("cargo:rustc-cfg=builtin_bindings");
+
+        // 同梱のバインディングを使う
+        let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+        println!("cargo:bindings={}", manifest_dir.join("bindings_macos.rs").display());
+        return;
     }
 
     let ai_sdk_path = &env::var("AISDK_ROOT").expect("AISDK_ROOT is not set");
@@ -84,6 +89,9 @@ fn main() {
         .allowlist_var("AIAPI_VERSION")
         .allowlist_var("kAIUserSuiteVersion")
 
+        // kAI*SuiteVersion は AIAPI_VERSION(n) 経由の関数マクロなので clang に評価させる
+        .clang_macro_fallback()
+
         .clang_arg("-std=c++14")
         .clang_args(&["-x", "c++"])
         .layout_tests(false);
@@ -122,6 +130,9 @@ fn main() {
 
     bindings.write_all(consts.as_bytes()).unwrap();
 
+    // illustrator-rs の build.rs が `DEP_ILLUSTRATOR_BINDINGS` として受け取る
+    println!("cargo:bindings={}", out_path.join("bindings.rs").display());
+
     // let profile = env::var("PROFILE").unwrap_or_else(|_| "debug".to_string());
     // let platform = env::var("PLATFORM").unwrap();
     //
@@ -154,6 +165,9 @@ fn extract_constants_from_clang(include_dirs: &Vec<String>) -> io::Result<String
 
   let filter_patterns = vec![
       Wildcard::new("kAI*".as_bytes()).unwrap(),
+      // 'PARM' のような4文字コードで定義されたエラー定数
+      Wildcard::new("k*Err".as_bytes()).unwrap(),
+      Wildcard::new("k*Error".as_bytes()).unwrap(),
   ];
 
   for line in stdout.lines() {
@@ -219,6 +233,13 @@ fn extract_constants_from_clang(include_dirs: &Vec<String>) -> io::Result<String
         );
     } else if value.starts_with("'") && value.ends_with("'") {
         let val = &value[1..value.len() - 1];
+        // C の複数文字リテラル 'PARM' は先頭の文字が最上位バイトになる
+        if val.len() == 4 {
+            return format!(
+                "pub const {}: u32 = u32::from_be_bytes(*b\"{}\");\n",
+                key, val
+            );
+        }
         return format!(
             "pub const {}: u32 = to_u32_char!('{}');\n",
             key, val